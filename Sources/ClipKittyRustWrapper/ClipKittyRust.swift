@@ -8,17 +8,32 @@ import UniformTypeIdentifiers
 // MARK: - ClipboardItem Extensions
 
 extension ClipboardItem {
-    @MainActor
-    private static let timeAgoFormatter: RelativeDateTimeFormatter = {
-        let formatter = RelativeDateTimeFormatter()
-        formatter.unitsStyle = .abbreviated
-        return formatter
-    }()
-
-    @MainActor
     public var timeAgo: String {
-        let timestamp = Date(timeIntervalSince1970: TimeInterval(itemMetadata.timestampUnix))
-        return Self.timeAgoFormatter.localizedString(for: timestamp, relativeTo: Date())
+        itemMetadata.timeAgo
+    }
+}
+
+// MARK: - ItemMetadata Extensions
+
+public extension ItemMetadata {
+    /// Relative time in the current locale and time zone, formatted by purr
+    /// so every surface reads the same.
+    var timeAgo: String {
+        formatRelativeTime(
+            timestampUnix: timestampUnix,
+            nowUnix: Int64(Date().timeIntervalSince1970),
+            utcOffsetSeconds: Int32(TimeZone.current.secondsFromGMT()),
+            locale: Locale.current.identifier
+        )
+    }
+
+    /// Date and time of day in the current locale and time zone.
+    var absoluteTime: String {
+        formatAbsoluteTime(
+            timestampUnix: timestampUnix,
+            utcOffsetSeconds: Int32(TimeZone.current.secondsFromGMT()),
+            locale: Locale.current.identifier
+        )
     }
 }
 
@@ -33,6 +48,11 @@ public extension IconType {
         case .image: return "photo"
         case .color: return "paintpalette"
         case .file: return "doc"
+        case .code: return "chevron.left.forwardslash.chevron.right"
+        case .document: return "doc.richtext"
+        case .phoneNumber: return "phone"
+        case .postalAddress: return "mappin.and.ellipse"
+        case .markdown: return "doc.plaintext"
         }
     }
 
@@ -44,20 +64,138 @@ public extension IconType {
         case .image: return .image
         case .color: return .text
         case .file: return .fileURL
+        case .code: return .sourceCode
+        case .document: return .content
+        case .phoneNumber: return .text
+        case .postalAddress: return .text
+        case .markdown: return UTType("net.daringfireball.markdown") ?? .plainText
+        }
+    }
+}
+
+// MARK: - CodeLanguage Extensions
+
+public extension CodeLanguage {
+    /// SF Symbol name for a snippet in this language
+    var sfSymbolName: String {
+        switch self {
+        case .swift: return "swift"
+        case .shell: return "terminal"
+        case .sql: return "cylinder.split.1x2"
+        case .json: return "curlybraces"
+        case .html: return "globe"
+        case .rust, .python, .javaScript, .cFamily: return "chevron.left.forwardslash.chevron.right"
         }
     }
 }
 
+// MARK: - DocumentKind Extensions
+
+public extension DocumentKind {
+    /// SF Symbol name for a file of this kind
+    var sfSymbolName: String {
+        switch self {
+        case .pdf: return "doc.richtext"
+        case .image: return "photo"
+        case .audio: return "waveform"
+        case .video: return "film"
+        case .archive: return "archivebox"
+        case .spreadsheet: return "tablecells"
+        case .presentation: return "rectangle.on.rectangle"
+        case .wordProcessing: return "doc.text"
+        case .plainText: return "doc.plaintext"
+        case .sourceCode: return "chevron.left.forwardslash.chevron.right"
+        case .folder: return "folder"
+        case .application: return "app"
+        case .generic: return "doc"
+        }
+    }
+
+    /// UTType for the kind (used for system document icons)
+    var utType: UTType {
+        switch self {
+        case .pdf: return .pdf
+        case .image: return .image
+        case .audio: return .audio
+        case .video: return .movie
+        case .archive: return .archive
+        case .spreadsheet: return .spreadsheet
+        case .presentation: return .presentation
+        case .wordProcessing: return UTType("org.openxmlformats.wordprocessingml.document") ?? .rtf
+        case .plainText: return .plainText
+        case .sourceCode: return .sourceCode
+        case .folder: return .folder
+        case .application: return .application
+        case .generic: return .data
+        }
+    }
+}
+
+// MARK: - ItemIcon Extensions
+
+public extension ItemIcon {
+    /// The symbol category this icon falls back to (mirrors Rust's `base_icon_type`)
+    var baseIconType: IconType {
+        switch self {
+        case let .symbol(iconType), let .appBadged(iconType, _):
+            return iconType
+        case .colorSwatch:
+            return .color
+        case .thumbnail, .sharedThumbnail:
+            return .image
+        case .code:
+            return .code
+        case .document:
+            return .document
+        }
+    }
+
+    /// SF Symbol name for the icon when it is drawn as a symbol
+    var sfSymbolName: String {
+        switch self {
+        case let .code(language):
+            return language.sfSymbolName
+        case let .document(kind):
+            return kind.sfSymbolName
+        default:
+            return baseIconType.sfSymbolName
+        }
+    }
+
+    /// Bundle ID of the app whose icon badges this one, if any
+    var badgeBundleId: String? {
+        if case let .appBadged(_, bundleId) = self {
+            return bundleId
+        }
+        return nil
+    }
+}
+
 // MARK: - ItemMatch Extensions
 
 // MARK: - ClipboardContent Extensions
 
 public extension ClipboardContent {
+    /// The text written to the pasteboard: `textContent`, except Markdown
+    /// pastes its source rather than the plain-text render.
+    var pasteText: String {
+        if case let .markdown(_, source) = self {
+            return source
+        }
+        return textContent
+    }
+
     /// The searchable/displayable text content
     var textContent: String {
         switch self {
         case let .text(value):
             return value
+        case let .richText(value, _, _):
+            return value
+        case let .phoneNumber(value), let .postalAddress(value):
+            return value
+        case let .markdown(value, _):
+            return value
         case let .color(value):
             return value
         case let .link(url, _):
@@ -0,0 +1,224 @@
+// Decoder for search matches packed by purr's `packed_results.rs`.
+// The layout and tag tables are documented there; keep both sides in step.
+
+import Foundation
+
+public enum PackedMatchDecodingError: Error, Equatable {
+    case badMagic
+    case unsupportedVersion(UInt8)
+    case truncated
+    case invalidUTF8
+    case unknownTag(field: String, value: UInt8)
+}
+
+public enum PackedMatchDecoder {
+    static let supportedVersion: UInt8 = 4
+
+    public static func decode(_ data: Data) throws -> [ItemMatch] {
+        var reader = PackedReader(bytes: [UInt8](data))
+        guard try reader.take(4) == Array("CKPM".utf8) else {
+            throw PackedMatchDecodingError.badMagic
+        }
+        let version = try reader.u8()
+        guard version == supportedVersion else {
+            throw PackedMatchDecodingError.unsupportedVersion(version)
+        }
+        let count = try reader.u32()
+        var matches: [ItemMatch] = []
+        matches.reserveCapacity(Int(count))
+        for _ in 0 ..< count {
+            let metadata = try reader.metadata()
+            matches.append(ItemMatch(itemMetadata: metadata, presentation: try reader.presentation()))
+        }
+        return matches
+    }
+}
+
+public extension PackedSearchResult {
+    /// Unpack into the record form the rest of the app consumes.
+    func unpacked() throws -> SearchResult {
+        SearchResult(
+            matches: try PackedMatchDecoder.decode(packedMatches),
+            totalCount: totalCount,
+            firstPreviewPayload: firstPreviewPayload
+        )
+    }
+}
+
+private struct PackedReader {
+    let bytes: [UInt8]
+    var offset = 0
+
+    mutating func take(_ count: Int) throws -> ArraySlice<UInt8> {
+        guard count <= bytes.count - offset else { throw PackedMatchDecodingError.truncated }
+        defer { offset += count }
+        return bytes[offset ..< offset + count]
+    }
+
+    mutating func u8() throws -> UInt8 {
+        try take(1).first!
+    }
+
+    mutating func u32() throws -> UInt32 {
+        try take(4).reversed().reduce(0) { $0 << 8 | UInt32($1) }
+    }
+
+    mutating func u64() throws -> UInt64 {
+        try take(8).reversed().reduce(0) { $0 << 8 | UInt64($1) }
+    }
+
+    mutating func i64() throws -> Int64 {
+        Int64(bitPattern: try u64())
+    }
+
+    mutating func bool() throws -> Bool {
+        try u8() != 0
+    }
+
+    mutating func data() throws -> Data {
+        Data(try take(Int(try u32())))
+    }
+
+    mutating func string() throws -> String {
+        guard let value = String(bytes: try take(Int(try u32())), encoding: .utf8) else {
+            throw PackedMatchDecodingError.invalidUTF8
+        }
+        return value
+    }
+
+    mutating func optionalString() throws -> String? {
+        try bool() ? try string() : nil
+    }
+
+    /// Read a tag and map it through `cases`, indexed by tag value.
+    mutating func tag<T>(_ field: String, _ cases: [T]) throws -> T {
+        let value = try u8()
+        guard Int(value) < cases.count else {
+            throw PackedMatchDecodingError.unknownTag(field: field, value: value)
+        }
+        return cases[Int(value)]
+    }
+
+    mutating func metadata() throws -> ItemMetadata {
+        let itemId = try string()
+        let icon = try self.icon()
+        let sourceApp = try optionalString()
+        let sourceAppBundleId = try optionalString()
+        let timestampUnix = try i64()
+        var tags: [ItemTag] = []
+        for _ in 0 ..< (try u32()) {
+            tags.append(try tag("item_tag", [ItemTag.bookmark]))
+        }
+        return ItemMetadata(
+            itemId: itemId,
+            icon: icon,
+            sourceApp: sourceApp,
+            sourceAppBundleId: sourceAppBundleId,
+            timestampUnix: timestampUnix,
+            tags: tags,
+            isPinned: try bool()
+        )
+    }
+
+    mutating func iconType() throws -> IconType {
+        try tag("icon_type", [
+            .text, .link, .image, .color, .file, .code, .document, .phoneNumber, .postalAddress,
+            .markdown,
+        ])
+    }
+
+    mutating func icon() throws -> ItemIcon {
+        switch try u8() {
+        case 0:
+            return .symbol(iconType: try iconType())
+        case 1:
+            let rgba = try u32()
+            return .colorSwatch(rgba: rgba, palette: try bool() ? try data() : nil)
+        case 2:
+            return .thumbnail(bytes: try data())
+        case 3:
+            return .code(language: try tag("code_language", [
+                .rust, .swift, .python, .javaScript, .shell, .sql, .json, .html, .cFamily,
+            ]))
+        case 4:
+            return .document(kind: try tag("document_kind", [
+                .pdf, .image, .audio, .video, .archive, .spreadsheet, .presentation,
+                .wordProcessing, .plainText, .sourceCode, .folder, .application, .generic,
+            ]))
+        case 5:
+            let iconType = try self.iconType()
+            return .appBadged(iconType: iconType, bundleId: try string())
+        case 6:
+            let offset = try u64()
+            return .sharedThumbnail(offset: offset, length: try u64())
+        case let value:
+            throw PackedMatchDecodingError.unknownTag(field: "icon", value: value)
+        }
+    }
+
+    mutating func presentation() throws -> RowPresentation {
+        switch try u8() {
+        case 0:
+            return .baseline(excerpt: try baseline())
+        case 1:
+            return .matched(excerpt: try matched())
+        case 2:
+            let request = try self.request()
+            return .deferred(request: request, placeholder: try placeholder())
+        case 3:
+            let fallback = try baseline()
+            return .unavailable(
+                fallback: fallback,
+                reason: try tag("unavailable_reason", [.itemMissing, .contentChanged, .emptyQuery])
+            )
+        case let value:
+            throw PackedMatchDecodingError.unknownTag(field: "presentation", value: value)
+        }
+    }
+
+    mutating func baseline() throws -> BaselineExcerpt {
+        BaselineExcerpt(text: try string())
+    }
+
+    mutating func matched() throws -> MatchedExcerpt {
+        let text = try string()
+        let count = try u32()
+        var highlights: [Utf16HighlightRange] = []
+        highlights.reserveCapacity(Int(count))
+        for _ in 0 ..< count {
+            let start = try u64()
+            let end = try u64()
+            let kind: HighlightKind = try tag("highlight_kind", [
+                .exact, .prefix, .prefixTail, .subwordPrefix, .substring, .fuzzy, .subsequence,
+            ])
+            highlights.append(Utf16HighlightRange(utf16Start: start, utf16End: end, kind: kind))
+        }
+        return MatchedExcerpt(text: text, highlights: highlights, lineNumber: try u64())
+    }
+
+    mutating func request() throws -> MatchedExcerptRequest {
+        let itemId = try string()
+        let query = try string()
+        let profile: ListPresentationProfile = try tag("profile", [.compactRow, .card])
+        return MatchedExcerptRequest(
+            itemId: itemId,
+            query: query,
+            presentationProfile: profile,
+            contentHash: try string()
+        )
+    }
+
+    mutating func placeholder() throws -> ExcerptPlaceholder {
+        switch try u8() {
+        case 0:
+            return .baseline(excerpt: try baseline())
+        case 1:
+            let sourceQuery = try string()
+            return .compatibleCached(sourceQuery: sourceQuery, excerpt: try matched())
+        case 2:
+            return .provisional(excerpt: try baseline())
+        case let value:
+            throw PackedMatchDecodingError.unknownTag(field: "placeholder", value: value)
+        }
+    }
+}
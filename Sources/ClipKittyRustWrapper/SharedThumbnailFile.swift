@@ -0,0 +1,46 @@
+// Read side of purr's shared thumbnail handoff (`thumbnail_cache.rs`).
+// Rust appends thumbnails to the cache file; rows carry ranges into it.
+
+import Foundation
+
+public final class SharedThumbnailFile: @unchecked Sendable {
+    public let url: URL
+    private let lock = NSLock()
+    private var mapped = Data()
+
+    public init(url: URL) {
+        self.url = url
+    }
+
+    /// Bytes at `offset..<offset + length`, remapping when the file has
+    /// grown past the current mapping. The slice shares the mapping rather
+    /// than copying it, so its indices start at `offset`.
+    public func bytes(offset: UInt64, length: UInt64) -> Data? {
+        let start = Int(offset)
+        let end = start + Int(length)
+        lock.lock()
+        defer { lock.unlock() }
+        if end > mapped.count {
+            guard let data = try? Data(contentsOf: url, options: .alwaysMapped) else {
+                return nil
+            }
+            mapped = data
+        }
+        guard end <= mapped.count else { return nil }
+        return mapped[start ..< end]
+    }
+}
+
+public extension ItemIcon {
+    /// Thumbnail bytes, read from `shared` for shared thumbnail ranges.
+    func thumbnailBytes(shared: SharedThumbnailFile?) -> Data? {
+        switch self {
+        case let .thumbnail(bytes):
+            return bytes
+        case let .sharedThumbnail(offset, length):
+            return shared?.bytes(offset: offset, length: length)
+        default:
+            return nil
+        }
+    }
+}
@@ -128,7 +128,7 @@ struct BrowserPreviewPane: View {
     private func previewContent(for content: SelectedItemState) -> some View {
         let item = content.item
         switch item.content {
-        case .text, .color:
+        case .text, .color, .richText, .phoneNumber, .postalAddress, .markdown:
             let previewText: String = {
                 switch viewModel.editSession {
                 case let .dirty(dirtyId, draft) where dirtyId == item.itemMetadata.itemId:
@@ -158,6 +158,14 @@ struct BrowserPreviewPane: View {
                     }
                 }(),
                 interaction: {
+                    // Only plain text items save edits; rich text would also
+                    // lose its formatting.
+                    switch item.content {
+                    case .richText, .phoneNumber, .postalAddress, .markdown:
+                        return .readOnly
+                    case .text, .color, .link, .image, .file:
+                        break
+                    }
                     switch viewModel.editSession {
                     case let .dirty(dirtyId, _) where dirtyId != item.itemMetadata.itemId,
                          let .suspendedDirty(dirtyId, _) where dirtyId != item.itemMetadata.itemId:
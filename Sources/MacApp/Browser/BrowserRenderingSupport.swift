@@ -1430,6 +1430,7 @@ private enum RowIconCache {
     }()
 
     private static var symbolIcons: [IconType: NSImage] = [:]
+    private static var documentIcons: [DocumentKind: NSImage] = [:]
     private static var sourceAppIcons: [String: NSImage] = [:]
     private static var missingSourceAppBundleIDs: Set<String> = []
 
@@ -1444,7 +1445,7 @@ private enum RowIconCache {
             image = browserIcon
         case .file:
             image = finderIcon
-        case .text, .image, .color:
+        case .text, .image, .color, .code, .document, .phoneNumber, .postalAddress, .markdown:
             image = workspace.icon(for: iconType.utType)
         }
 
@@ -1452,6 +1453,15 @@ private enum RowIconCache {
         return image
     }
 
+    static func documentImage(for kind: DocumentKind) -> NSImage {
+        if let cachedImage = documentIcons[kind] {
+            return cachedImage
+        }
+        let image = workspace.icon(for: kind.utType)
+        documentIcons[kind] = image
+        return image
+    }
+
     static func sourceAppImage(bundleID: String) -> NSImage? {
         if let cachedImage = sourceAppIcons[bundleID] {
             return cachedImage
@@ -1595,9 +1605,11 @@ struct ItemRow: View {
 
     private var showsSourceAppBadge: Bool {
         switch metadata.icon {
-        case let .symbol(iconType):
+        case let .symbol(iconType), let .appBadged(iconType, _):
             return iconType != .link && iconType != .file
-        case .thumbnail, .colorSwatch:
+        case .document:
+            return false
+        case .thumbnail, .sharedThumbnail, .colorSwatch, .code:
             return true
         }
     }
@@ -1629,7 +1641,9 @@ struct ItemRow: View {
                                 switch metadata.icon {
                                 case let .thumbnail(bytes):
                                     RowThumbnailView(itemId: metadata.itemId, data: bytes)
-                                case let .colorSwatch(rgba):
+                                case let .colorSwatch(_, palette?):
+                                    RowThumbnailView(itemId: metadata.itemId, data: palette)
+                                case let .colorSwatch(rgba, nil):
                                     RoundedRectangle(cornerRadius: 6)
                                         .fill(Color(nsColor: NSColor(
                                             red: CGFloat((rgba >> 24) & 0xFF) / 255.0,
@@ -1641,9 +1655,21 @@ struct ItemRow: View {
                                             RoundedRectangle(cornerRadius: 6)
                                                 .strokeBorder(Color.primary.opacity(0.15), lineWidth: 1)
                                         )
-                                case let .symbol(iconType):
+                                case let .symbol(iconType), let .appBadged(iconType, _):
                                     Image(nsImage: RowIconCache.symbolImage(for: iconType))
                                         .resizable()
+                                case let .code(language):
+                                    Image(systemName: language.sfSymbolName)
+                                        .resizable()
+                                        .scaledToFit()
+                                        .padding(6)
+                                        .foregroundColor(.secondary)
+                                case let .document(kind):
+                                    Image(nsImage: RowIconCache.documentImage(for: kind))
+                                        .resizable()
+                                case .sharedThumbnail:
+                                    Image(nsImage: RowIconCache.symbolImage(for: .image))
+                                        .resizable()
                                 }
                             }
                             .frame(width: settings.scaled(32), height: settings.scaled(32))
@@ -1657,7 +1683,7 @@ struct ItemRow: View {
                                     .shadow(color: .black.opacity(0.3), radius: 1, x: 0, y: 1)
                                     .offset(x: 4, y: 4)
                                     .transition(.scale.combined(with: .opacity))
-                            } else if let bundleID = metadata.sourceAppBundleId,
+                            } else if let bundleID = metadata.icon.badgeBundleId ?? metadata.sourceAppBundleId,
                                       let sourceAppImage = RowIconCache.sourceAppImage(bundleID: bundleID)
                             {
                                 if showsSourceAppBadge {
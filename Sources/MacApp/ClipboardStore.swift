@@ -50,6 +50,31 @@ struct StoreRuntime {
     let repository: ClipboardRepository
 }
 
+/// Forwards background index rebuild callbacks, which arrive on Rust's
+/// rebuilding thread.
+private final class IndexRebuildRelay: IndexRebuildObserver, @unchecked Sendable {
+    private let onProgress: @Sendable (UInt8) -> Void
+    private let onComplete: @Sendable (String?) -> Void
+
+    init(onProgress: @escaping @Sendable (UInt8) -> Void, onComplete: @escaping @Sendable (String?) -> Void) {
+        self.onProgress = onProgress
+        self.onComplete = onComplete
+    }
+
+    func onRebuildProgress(indexedItems _: UInt64, totalItems _: UInt64, percent: UInt8) {
+        onProgress(percent)
+    }
+
+    func onRebuildComplete(error: String?) {
+        onComplete(error)
+    }
+}
+
+private struct IndexRebuildFailure: LocalizedError {
+    let message: String
+    var errorDescription: String? { message }
+}
+
 /// Observable lifecycle state for the clipboard store.
 enum StoreLifecycle: Equatable {
     case initializing
@@ -65,6 +90,9 @@ final class ClipboardStore {
 
     private(set) var lifecycle: StoreLifecycle = .initializing
 
+    /// Progress of a background index rebuild, while one runs.
+    private(set) var indexRebuildPercent: UInt8?
+
     // MARK: - Private State
 
     /// Rust-backed repository facade (available after bootstrap completes)
@@ -198,6 +226,8 @@ final class ClipboardStore {
     private func openSynchronously(dbPath: String) {
         do {
             let rustStore = try ClipKittyRust.ClipboardStore(dbPath: dbPath)
+            // Items tagged for other languages (localized demo data) stay hidden.
+            try? rustStore.setPreferredLocale(locale: Locale.current.identifier)
             let repository = ClipboardRepository(store: rustStore)
             self.repository = repository
             previewLoader = PreviewLoader(repository: repository)
@@ -213,11 +243,12 @@ final class ClipboardStore {
         }
     }
 
-    /// Slow path: rebuild index on a background thread.
+    /// Slow path: open the store, then rebuild its index in the background.
+    /// Searches serve the existing index until the rebuilt one swaps in.
     private func openWithRebuild(dbPath: String) {
         bootstrapTask = Task.detached(priority: .userInitiated) {
             let rustStore = try ClipKittyRust.ClipboardStore(dbPath: dbPath)
-            try rustStore.rebuildIndex()
+            try? rustStore.setPreferredLocale(locale: Locale.current.identifier)
             let repository = ClipboardRepository(store: rustStore)
             return StoreRuntime(store: rustStore, repository: repository)
         }
@@ -227,11 +258,16 @@ final class ClipboardStore {
                 let runtime = try await bootstrapTask!.value
                 self.repository = runtime.repository
                 self.previewLoader = PreviewLoader(repository: runtime.repository)
-                self.lifecycle = .ready
-                self.pruneIfNeeded()
-                #if ENABLE_ICLOUD_SYNC
-                    self.initializeSyncRuntime(with: runtime.store)
-                #endif
+                self.indexRebuildPercent = 0
+                let relay = IndexRebuildRelay(
+                    onProgress: { [weak self] percent in
+                        Task { @MainActor in self?.indexRebuildPercent = percent }
+                    },
+                    onComplete: { [weak self] error in
+                        Task { @MainActor in self?.finishIndexRebuild(runtime: runtime, error: error) }
+                    }
+                )
+                runtime.store.rebuildIndexInBackground(observer: relay)
             } catch {
                 let dbError = ClipboardError.databaseInitFailed(underlying: error)
                 ErrorReporter.reportCritical(dbError)
@@ -240,6 +276,21 @@ final class ClipboardStore {
         }
     }
 
+    private func finishIndexRebuild(runtime: StoreRuntime, error: String?) {
+        indexRebuildPercent = nil
+        if let error {
+            let dbError = ClipboardError.databaseInitFailed(underlying: IndexRebuildFailure(message: error))
+            ErrorReporter.reportCritical(dbError)
+            lifecycle = .failed(dbError.localizedDescription)
+            return
+        }
+        lifecycle = .ready
+        pruneIfNeeded()
+        #if ENABLE_ICLOUD_SYNC
+            initializeSyncRuntime(with: runtime.store)
+        #endif
+    }
+
     private func resolveDatabasePath() -> String? {
         do {
             guard let appSupport = fileManager.urls(for: .applicationSupportDirectory, in: .userDomainMask).first else {
@@ -315,8 +366,10 @@ final class ClipboardStore {
     }
 
     func formatExcerpt(content: String, presentation: ListPresentationProfile) -> String {
-        guard let repository else { return String(content.prefix(200)) }
-        return repository.store.formatExcerpt(content: content, presentation: presentation)
+        guard let repository,
+              let excerpt = try? repository.store.formatExcerpt(content: content, presentation: presentation)
+        else { return String(content.prefix(200)) }
+        return excerpt
     }
 
     #if ENABLE_LINK_PREVIEWS
@@ -951,7 +1004,13 @@ final class ClipboardStore {
             }
         #endif
 
-        pasteboardMonitor.acknowledgeLocalWrite(changeCount: pasteService.writeText(content.textContent))
+        let changeCount: Int
+        if case let .richText(value, rtfData, html) = content {
+            changeCount = pasteService.writeRichText(value, rtf: rtfData, html: html)
+        } else {
+            changeCount = pasteService.writeText(content.pasteText)
+        }
+        pasteboardMonitor.acknowledgeLocalWrite(changeCount: changeCount)
 
         Task { [weak self] in
             await self?.updateItemTimestamp(id: itemId)
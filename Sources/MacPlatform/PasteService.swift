@@ -15,6 +15,20 @@ public final class PasteService {
         return pasteboard.changeCount
     }
 
+    /// Writes formatted text with its plain-text fallback, so apps that take
+    /// rich text keep the styling.
+    public func writeRichText(_ text: String, rtf: Data?, html: String?) -> Int {
+        pasteboard.clearContents()
+        if let rtf {
+            _ = pasteboard.setData(rtf, forType: .rtf)
+        }
+        if let html {
+            _ = pasteboard.setString(html, forType: .html)
+        }
+        _ = pasteboard.setString(text, forType: .string)
+        return pasteboard.changeCount
+    }
+
     #if ENABLE_FILE_CLIPBOARD_ITEMS
         public func writeFiles(_ urls: [URL]) -> Int {
             let filenameType = NSPasteboard.PasteboardType("NSFilenamesPboardType")
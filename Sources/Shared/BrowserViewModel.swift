@@ -719,7 +719,8 @@ public final class BrowserViewModel {
             sourceApp: currentItem.itemMetadata.sourceApp,
             sourceAppBundleId: currentItem.itemMetadata.sourceAppBundleId,
             timestampUnix: currentItem.itemMetadata.timestampUnix,
-            tags: currentItem.itemMetadata.tags
+            tags: currentItem.itemMetadata.tags,
+            isPinned: currentItem.itemMetadata.isPinned
         )
         let updatedItem = ClipboardItem(itemMetadata: updatedMetadata, content: updatedContent)
         let updatedPreviewState: SelectedPreviewState = .plain
@@ -1369,7 +1370,8 @@ public final class BrowserViewModel {
                         sourceApp: updatedItem.itemMetadata.sourceApp,
                         sourceAppBundleId: updatedItem.itemMetadata.sourceAppBundleId,
                         timestampUnix: updatedItem.itemMetadata.timestampUnix,
-                        tags: currentTags
+                        tags: currentTags,
+                        isPinned: updatedItem.itemMetadata.isPinned
                     )
                     let mergedPreviewItem = ClipboardItem(itemMetadata: mergedPreviewMetadata, content: updatedItem.content)
                     let updatedPreviewPayload = PreviewPayload(
@@ -1791,7 +1793,8 @@ public final class BrowserViewModel {
             sourceApp: metadata.sourceApp,
             sourceAppBundleId: metadata.sourceAppBundleId,
             timestampUnix: metadata.timestampUnix,
-            tags: updatedTags
+            tags: updatedTags,
+            isPinned: metadata.isPinned
         )
     }
 
@@ -1857,7 +1860,7 @@ public final class BrowserViewModel {
         }
 
         switch item.content {
-        case .text, .color:
+        case .text, .richText, .color, .phoneNumber, .postalAddress, .markdown:
             return true
         case .image, .link, .file:
             return false
@@ -25,10 +25,11 @@ private final class RustClipboardSearchOperation: ClipboardSearchOperation {
 
     func awaitOutcome() async -> RepositorySearchOutcome {
         do {
-            let outcome = try await operation.awaitResult()
+            // Thousands of matches cross the FFI far faster as one buffer.
+            let outcome = try await operation.awaitPackedResult()
             switch outcome {
             case let .success(result):
-                return .success(result)
+                return .success(try result.unpacked())
             case .cancelled:
                 return .cancelled
             }
@@ -38,6 +39,21 @@ private final class RustClipboardSearchOperation: ClipboardSearchOperation {
     }
 }
 
+/// A search the store refused to start.
+private final class FailedClipboardSearchOperation: ClipboardSearchOperation {
+    private let error: Error
+
+    init(error: Error) {
+        self.error = error
+    }
+
+    func cancel() {}
+
+    func awaitOutcome() async -> RepositorySearchOutcome {
+        .failure(.databaseOperationFailed(operation: "search", underlying: error))
+    }
+}
+
 public func runRepositoryOperation<T: Sendable>(
     _ operation: String,
     on store: ClipKittyRust.ClipboardStore,
@@ -65,14 +81,30 @@ public final class ClipboardRepository: @unchecked Sendable {
     }
 
     public func startSearch(query: String, filter: ItemQueryFilter, presentation: ListPresentationProfile) -> ClipboardSearchOperation {
-        let operation = store.startSearch(query: query, filter: filter, presentation: presentation)
-        return RustClipboardSearchOperation(operation: operation)
+        do {
+            let operation = try store.startSearch(query: query, filter: filter, presentation: presentation)
+            return RustClipboardSearchOperation(operation: operation)
+        } catch {
+            return FailedClipboardSearchOperation(error: error)
+        }
     }
 
     public func search(query: String, filter: ItemQueryFilter, presentation: ListPresentationProfile) async -> RepositorySearchOutcome {
         await startSearch(query: query, filter: filter, presentation: presentation).awaitOutcome()
     }
 
+    public func searchAllSources(
+        query: String,
+        limits: SearchSectionLimits,
+        presentation: ListPresentationProfile
+    ) async -> Result<SectionedSearchResult, ClipboardError> {
+        do {
+            return .success(try await store.searchAllSources(query: query, limits: limits, presentation: presentation))
+        } catch {
+            return .failure(.databaseOperationFailed(operation: "searchAllSources", underlying: error))
+        }
+    }
+
     public func fetchItem(id: String) async -> ClipboardItem? {
         let result = await runRepositoryOperation("fetchItem", on: store) { store in
             try store.fetchByIds(itemIds: [id])
@@ -83,6 +115,49 @@ public final class ClipboardRepository: @unchecked Sendable {
         return nil
     }
 
+    /// Rows for the menu bar list. Cheap while `changeSequence` is unchanged.
+    public func quickList(limit: UInt32) async -> Result<QuickList, ClipboardError> {
+        await runRepositoryOperation("quickList", on: store) { store in
+            try store.quickList(limit: limit)
+        }
+    }
+
+    public func accessibilityDescription(itemId: String) async -> Result<String, ClipboardError> {
+        await runRepositoryOperation("accessibilityDescription", on: store) { store in
+            try store.accessibilityDescription(itemId: itemId)
+        }
+    }
+
+    public func seedDemoData(locale: String) async -> Result<[String], ClipboardError> {
+        await runRepositoryOperation("seedDemoData", on: store) { store in
+            try store.seedDemoData(locale: locale)
+        }
+    }
+
+    public func createSnippet(title: String, body: String) async -> Result<Snippet, ClipboardError> {
+        await runRepositoryOperation("createSnippet", on: store) { store in
+            try store.createSnippet(title: title, body: body)
+        }
+    }
+
+    public func listSnippets() async -> Result<[Snippet], ClipboardError> {
+        await runRepositoryOperation("listSnippets", on: store) { store in
+            try store.listSnippets()
+        }
+    }
+
+    public func deleteSnippet(snippetId: String) async -> Result<Void, ClipboardError> {
+        await runRepositoryOperation("deleteSnippet", on: store) { store in
+            try store.deleteSnippet(snippetId: snippetId)
+        }
+    }
+
+    public func expandSnippet(snippetId: String, variables: [SnippetVariable]) async -> Result<String, ClipboardError> {
+        await runRepositoryOperation("expandSnippet", on: store) { store in
+            try store.expandSnippet(snippetId: snippetId, variables: variables)
+        }
+    }
+
     public func resolveMatchedExcerpts(requests: [MatchedExcerptRequest]) async -> [MatchedExcerptResolution] {
         let result = await runRepositoryOperation("resolveMatchedExcerpts", on: store) { store in
             try store.resolveMatchedExcerpts(requests: requests)
@@ -117,6 +192,12 @@ public final class ClipboardRepository: @unchecked Sendable {
         }
     }
 
+    public func saveTexts(_ inputs: [TextInput]) async -> Result<[String], ClipboardError> {
+        await runRepositoryOperation("saveTexts", on: store) { store in
+            try store.saveTextsBatch(inputs: inputs)
+        }
+    }
+
     public func saveImage(
         imageData: Data,
         thumbnail: Data?,
@@ -211,6 +292,19 @@ public final class ClipboardRepository: @unchecked Sendable {
         }
     }
 
+    /// Take an item back out of the trash. Returns its new item ID.
+    public func restore(itemId: String) async -> Result<String, ClipboardError> {
+        await runRepositoryOperation("restoreItem", on: store) { store in
+            try store.restoreItem(itemId: itemId)
+        }
+    }
+
+    public func emptyTrash(olderThanSeconds: UInt64) async -> Result<UInt64, ClipboardError> {
+        await runRepositoryOperation("emptyTrash", on: store) { store in
+            try store.emptyTrash(olderThanSeconds: olderThanSeconds)
+        }
+    }
+
     public func clear() async -> Result<Void, ClipboardError> {
         await runRepositoryOperation("clear", on: store) { store in
             try store.clear()
@@ -225,4 +319,12 @@ public final class ClipboardRepository: @unchecked Sendable {
             try store.pruneToSize(maxBytes: maxBytes, keepRatio: keepRatio)
         }
     }
+
+    /// Apply the retention policy, then compact the database and search
+    /// index. Slow; run it when the app is idle.
+    public func runMaintenance() async -> Result<MaintenanceReport, ClipboardError> {
+        await runRepositoryOperation("runMaintenance", on: store) { store in
+            try store.runMaintenance()
+        }
+    }
 }
@@ -138,8 +138,11 @@ private enum ShortcutTextExtraction {
 
     static func parse(_ content: ClipboardContent) -> Self {
         switch content {
-        case let .text(value):
+        case let .text(value), let .richText(value, _, _),
+             let .phoneNumber(value), let .postalAddress(value):
             return .value(value)
+        case let .markdown(_, source):
+            return .value(source)
         case .color, .link, .image, .file:
             return .unsupported
         }
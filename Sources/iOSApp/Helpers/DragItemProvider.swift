@@ -32,6 +32,9 @@ enum DragItemProvider {
         register(provider, type: .plainText, itemId: itemId, fetch: fetch) { item in
             switch item.content {
             case let .text(value): return value.data(using: .utf8)
+            case let .richText(value, _, _): return value.data(using: .utf8)
+            case let .phoneNumber(value), let .postalAddress(value): return value.data(using: .utf8)
+            case let .markdown(_, source): return source.data(using: .utf8)
             case let .color(value): return value.data(using: .utf8)
             case let .link(url, _): return url.data(using: .utf8)
             case let .image(_, description, _): return description.data(using: .utf8)
@@ -42,6 +45,9 @@ enum DragItemProvider {
         register(provider, type: .utf8PlainText, itemId: itemId, fetch: fetch) { item in
             switch item.content {
             case let .text(value): return value.data(using: .utf8)
+            case let .richText(value, _, _): return value.data(using: .utf8)
+            case let .phoneNumber(value), let .postalAddress(value): return value.data(using: .utf8)
+            case let .markdown(_, source): return source.data(using: .utf8)
             case let .color(value): return value.data(using: .utf8)
             case let .link(url, _): return url.data(using: .utf8)
             case let .image(_, description, _): return description.data(using: .utf8)
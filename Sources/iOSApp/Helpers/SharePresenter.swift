@@ -23,8 +23,11 @@ enum SharePresenter {
 
     private static func shareItems(for item: ClipboardItem) -> [Any] {
         switch item.content {
-        case let .text(value):
+        case let .text(value), let .richText(value, _, _),
+             let .phoneNumber(value), let .postalAddress(value):
             return [value]
+        case let .markdown(_, source):
+            return [source]
         case let .link(url, _):
             if let linkURL = URL(string: url) {
                 return [linkURL]
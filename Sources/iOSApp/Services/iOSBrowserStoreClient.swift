@@ -94,6 +94,7 @@ final class iOSBrowserStoreClient: BrowserStoreClient {
     }
 
     func formatExcerpt(content: String) -> String {
-        repository.store.formatExcerpt(content: content, presentation: listPresentationProfile)
+        (try? repository.store.formatExcerpt(content: content, presentation: listPresentationProfile))
+            ?? String(content.prefix(200))
     }
 }
@@ -28,8 +28,21 @@ final class iOSClipboardService {
     func copy(content: ClipboardContent) {
         let pasteboard = UIPasteboard.general
         switch content {
-        case let .text(value):
+        case let .text(value), let .phoneNumber(value), let .postalAddress(value):
             pasteboard.string = value
+        case let .markdown(_, source):
+            pasteboard.string = source
+        case let .richText(value, rtfData, html):
+            // Formatted representations first, so apps that read rich text
+            // keep the styling; the plain text is there for everyone else.
+            var representations: [String: Any] = [UTType.utf8PlainText.identifier: value]
+            if let rtfData {
+                representations[UTType.rtf.identifier] = rtfData
+            }
+            if let html {
+                representations[UTType.html.identifier] = html
+            }
+            pasteboard.items = [representations]
         case let .color(value):
             pasteboard.string = value
         case let .link(url, _):
@@ -71,20 +71,20 @@ struct CardRowChunk: Identifiable {
         switch row.metadata.icon {
         case .colorSwatch:
             return 240
-        case .thumbnail:
+        case .thumbnail, .sharedThumbnail:
             // The card image is height-capped (see CardView.thumbnailPreview),
             // so image cards no longer want the full-bleed widths they did
             // uncapped.
             return 360
-        case let .symbol(iconType):
-            switch iconType {
+        case .symbol, .appBadged, .code, .document:
+            switch row.metadata.icon.baseIconType {
             case .link:
                 return 340
-            case .image, .file:
+            case .image, .file, .document:
                 return 340
-            case .color:
+            case .color, .phoneNumber, .postalAddress:
                 return 240
-            case .text:
+            case .text, .code, .markdown:
                 // The longest rendered line drives the natural width: ~8pt
                 // per character at the 15pt preview size, plus card padding.
                 // Cards clamp text to 8 lines, so later lines can't widen it.
@@ -31,12 +31,6 @@ struct CardView: View {
         )
     }
 
-    private static let relativeDateFormatter: RelativeDateTimeFormatter = {
-        let formatter = RelativeDateTimeFormatter()
-        formatter.unitsStyle = .abbreviated
-        return formatter
-    }()
-
     private var metadata: ItemMetadata {
         row.metadata
     }
@@ -115,7 +109,7 @@ struct CardView: View {
     @ViewBuilder
     private var sourceAppBadge: some View {
         if showsSourceAppBadge,
-           let symbol = SourceAppIcon.symbolName(forBundleID: metadata.sourceAppBundleId)
+           let symbol = SourceAppIcon.symbolName(forBundleID: metadata.icon.badgeBundleId ?? metadata.sourceAppBundleId)
         {
             Image(systemName: symbol)
                 .font(.caption2)
@@ -129,9 +123,11 @@ struct CardView: View {
     /// filtered out of the iOS feed entirely.)
     private var showsSourceAppBadge: Bool {
         switch metadata.icon {
-        case let .symbol(iconType):
+        case let .symbol(iconType), let .appBadged(iconType, _):
             return iconType != .link && iconType != .file
-        case .thumbnail, .colorSwatch:
+        case .document:
+            return false
+        case .thumbnail, .sharedThumbnail, .colorSwatch, .code:
             return true
         }
     }
@@ -141,14 +137,17 @@ struct CardView: View {
     @ViewBuilder
     private var contentPreview: some View {
         switch metadata.icon {
-        case let .symbol(iconType):
+        case let .symbol(iconType), let .appBadged(iconType, _):
             symbolContentPreview(iconType: iconType)
 
-        case let .colorSwatch(rgba):
-            colorSwatchPreview(rgba: rgba)
+        case let .colorSwatch(rgba, palette):
+            colorSwatchPreview(rgba: rgba, palette: palette)
 
         case let .thumbnail(bytes):
             thumbnailPreview(bytes: bytes)
+
+        case .code, .document, .sharedThumbnail:
+            symbolContentPreview(iconType: metadata.icon.baseIconType)
         }
     }
 
@@ -168,7 +167,7 @@ struct CardView: View {
     @ViewBuilder
     private func symbolContentPreview(iconType: IconType) -> some View {
         switch iconType {
-        case .text:
+        case .text, .code, .markdown:
             highlightedText(displayExcerpt.text, highlights: displayExcerpt.highlights, font: monoFont(size: 15))
                 .lineLimit(8)
 
@@ -190,28 +189,43 @@ struct CardView: View {
                     .lineLimit(2)
             }
 
-        case .file:
+        case .file, .document:
             // File items are filtered out of the iOS feed
             EmptyView()
 
         case .color:
             // Fallback for symbol-based color (shouldn't normally hit this path)
             highlightedText(displayExcerpt.text, highlights: displayExcerpt.highlights, font: monoFont(size: 15))
+
+        case .phoneNumber, .postalAddress:
+            highlightedText(displayExcerpt.text, highlights: displayExcerpt.highlights, font: sansFont(size: 15))
+                .lineLimit(4)
         }
     }
 
-    private func colorSwatchPreview(rgba: UInt32) -> some View {
+    private func colorSwatchPreview(rgba: UInt32, palette: Data?) -> some View {
         HStack(spacing: 12) {
-            RoundedRectangle(cornerRadius: 8, style: .continuous)
-                .fill(colorFromRGBA(rgba))
-                .frame(width: 40, height: 40)
-                .overlay(
+            Group {
+                // Palettes and gradients show every color; the single
+                // swatch would only show the first.
+                if let palette, let image = UIImage(data: palette) {
+                    Image(uiImage: image)
+                        .resizable()
+                        .frame(width: 80, height: 40)
+                } else {
                     RoundedRectangle(cornerRadius: 8, style: .continuous)
-                        .strokeBorder(.primary.opacity(0.1), lineWidth: 1)
-                )
+                        .fill(colorFromRGBA(rgba))
+                        .frame(width: 40, height: 40)
+                }
+            }
+            .clipShape(RoundedRectangle(cornerRadius: 8, style: .continuous))
+            .overlay(
+                RoundedRectangle(cornerRadius: 8, style: .continuous)
+                    .strokeBorder(.primary.opacity(0.1), lineWidth: 1)
+            )
 
             highlightedText(
-                displayExcerpt.highlights.isEmpty ? hexStringFromRGBA(rgba) : displayExcerpt.text,
+                displayExcerpt.highlights.isEmpty && palette == nil ? hexStringFromRGBA(rgba) : displayExcerpt.text,
                 highlights: displayExcerpt.highlights,
                 font: monoFont(size: 15)
             )
@@ -321,36 +335,26 @@ struct CardView: View {
     }
 
     private var iconSymbolName: String {
-        switch metadata.icon {
-        case let .symbol(iconType):
-            return iconType.sfSymbolName
-        case .colorSwatch:
-            return "paintpalette"
-        case .thumbnail:
-            return "photo"
-        }
+        metadata.icon.sfSymbolName
     }
 
     private var typeLabel: String {
-        switch metadata.icon {
-        case let .symbol(iconType):
-            switch iconType {
-            case .text: return String(localized: "Text")
-            case .link: return String(localized: "Link")
-            case .image: return String(localized: "Image")
-            case .color: return String(localized: "Color")
-            case .file: return String(localized: "File")
-            }
-        case .colorSwatch:
-            return String(localized: "Color")
-        case .thumbnail:
-            return String(localized: "Image")
+        switch metadata.icon.baseIconType {
+        case .text: return String(localized: "Text")
+        case .link: return String(localized: "Link")
+        case .image: return String(localized: "Image")
+        case .color: return String(localized: "Color")
+        case .file: return String(localized: "File")
+        case .code: return String(localized: "Code")
+        case .document: return String(localized: "Document")
+        case .phoneNumber: return String(localized: "Phone Number")
+        case .postalAddress: return String(localized: "Address")
+        case .markdown: return String(localized: "Markdown")
         }
     }
 
     private var relativeTime: String {
-        let date = Date(timeIntervalSince1970: TimeInterval(metadata.timestampUnix))
-        return Self.relativeDateFormatter.localizedString(for: date, relativeTo: Date())
+        metadata.timeAgo
     }
 
     private func colorFromRGBA(_ rgba: UInt32) -> Color {
@@ -209,8 +209,8 @@ struct HomeFeedView: View {
     /// Filter out file items — iPhone app doesn't support file sharing.
     private var filteredRows: [DisplayRow] {
         viewModel.displayRows.filter { row in
-            if case .symbol(.file) = row.metadata.icon { return false }
-            return true
+            let iconType = row.metadata.icon.baseIconType
+            return iconType != .file && iconType != .document
         }
     }
 
@@ -24,13 +24,6 @@ struct PreviewScreen: View {
     #if ENABLE_TEST_FIXTURES
         private let isUITestPreviewDebugEnabled = CommandLine.arguments.contains("--use-simulated-db")
     #endif
-    private static let detailDateFormatter: DateFormatter = {
-        let formatter = DateFormatter()
-        formatter.dateStyle = .long
-        formatter.timeStyle = .medium
-        return formatter
-    }()
-
     var body: some View {
         Group {
             if let selectedItemState = viewModel.selectedItemState {
@@ -66,11 +59,14 @@ struct PreviewScreen: View {
     private var navigationTitle: String {
         guard let item = viewModel.selectedItemState?.item else { return String(localized: "Detail") }
         switch item.content {
-        case .text: return String(localized: "Text")
+        case .text, .richText: return String(localized: "Text")
         case .link: return String(localized: "Link")
         case .image: return String(localized: "Image")
         case .color: return String(localized: "Color")
         case .file: return String(localized: "File")
+        case .phoneNumber: return String(localized: "Phone Number")
+        case .postalAddress: return String(localized: "Address")
+        case .markdown: return String(localized: "Markdown")
         }
     }
 
@@ -185,6 +181,30 @@ struct PreviewScreen: View {
                     viewModel.onEditingStateChange(editing, for: item.itemMetadata.itemId)
                 }
             )
+        case let .richText(value, _, _):
+            // Read-only: edits save plain text, which would drop the formatting.
+            let decoration = previewDecoration(for: selectedItemState)
+            TextPreviewView(
+                itemId: item.itemMetadata.itemId,
+                text: value,
+                highlights: decoration?.highlights ?? [],
+                initialScrollHighlightIndex: decoration?.initialScrollHighlightIndex,
+                isEditable: false,
+                fontPreference: settings.fontPreference,
+                previewStyle: settings.previewFontPreference
+            )
+        case let .phoneNumber(value), let .postalAddress(value), let .markdown(value, _):
+            // Read-only: only plain text items save edits.
+            let decoration = previewDecoration(for: selectedItemState)
+            TextPreviewView(
+                itemId: item.itemMetadata.itemId,
+                text: value,
+                highlights: decoration?.highlights ?? [],
+                initialScrollHighlightIndex: decoration?.initialScrollHighlightIndex,
+                isEditable: false,
+                fontPreference: settings.fontPreference,
+                previewStyle: settings.previewFontPreference
+            )
         case let .color(value):
             let decoration = isDirty ? nil : previewDecoration(for: selectedItemState)
             VStack(spacing: 0) {
@@ -348,7 +368,7 @@ struct PreviewScreen: View {
             }
 
             LabeledContent(String(localized: "Time")) {
-                Text(formattedDate(from: item.itemMetadata.timestampUnix))
+                Text(item.itemMetadata.absoluteTime)
             }
 
             LabeledContent(String(localized: "Bookmarked")) {
@@ -358,11 +378,6 @@ struct PreviewScreen: View {
         .font(.subheadline)
     }
 
-    private func formattedDate(from unix: Int64) -> String {
-        let date = Date(timeIntervalSince1970: TimeInterval(unix))
-        return Self.detailDateFormatter.string(from: date)
-    }
-
     // MARK: - Action Bar
 
     @ViewBuilder
@@ -2288,7 +2288,8 @@ final class BrowserViewModelTests: XCTestCase {
                 sourceApp: nil,
                 sourceAppBundleId: nil,
                 timestampUnix: 0,
-                tags: tags
+                tags: tags,
+                isPinned: false
             ),
             presentation: .baseline(excerpt: BaselineExcerpt(text: excerpt))
         )
@@ -2302,7 +2303,8 @@ final class BrowserViewModelTests: XCTestCase {
                 sourceApp: nil,
                 sourceAppBundleId: nil,
                 timestampUnix: 0,
-                tags: tags
+                tags: tags,
+                isPinned: false
             ),
             presentation: .deferred(
                 request: MatchedExcerptRequest(
@@ -2324,7 +2326,8 @@ final class BrowserViewModelTests: XCTestCase {
                 sourceApp: nil,
                 sourceAppBundleId: nil,
                 timestampUnix: 0,
-                tags: tags
+                tags: tags,
+                isPinned: false
             ),
             content: .text(value: text)
         )
@@ -0,0 +1,44 @@
+import ClipKittyRust
+import XCTest
+
+/// The packed search transport must decode to exactly the records UniFFI returns.
+final class PackedMatchDecoderTests: XCTestCase {
+    private func makeStore() throws -> ClipKittyRust.ClipboardStore {
+        let tmp = FileManager.default.temporaryDirectory
+            .appendingPathComponent("clipkitty-test-\(UUID().uuidString)")
+        try FileManager.default.createDirectory(at: tmp, withIntermediateDirectories: true)
+        let dbPath = tmp.appendingPathComponent("test.sqlite").path
+        return try ClipKittyRust.ClipboardStore(dbPath: dbPath)
+    }
+
+    func testPackedResultDecodesToTheRecordResult() async throws {
+        let store = try makeStore()
+        _ = try store.saveText(text: "café invoice draft", sourceApp: "Mail", sourceAppBundleId: "com.apple.mail")
+        _ = try store.saveText(text: "https://example.com/invoice", sourceApp: nil, sourceAppBundleId: nil)
+        _ = try store.saveText(text: "#ff8800", sourceApp: nil, sourceAppBundleId: nil)
+        for i in 0 ..< 40 {
+            _ = try store.saveText(text: "invoice line \(i) 👋", sourceApp: nil, sourceAppBundleId: nil)
+        }
+
+        for query in ["invoice", "inv", "ff8800", "zzzz"] {
+            let recordOutcome = try await store.startSearch(query: query, filter: .all, presentation: .card).awaitResult()
+            let packedOutcome = try await store.startSearch(query: query, filter: .all, presentation: .card).awaitPackedResult()
+            guard case let .success(expected) = recordOutcome, case let .success(packed) = packedOutcome else {
+                return XCTFail("search for \(query) was cancelled")
+            }
+            XCTAssertEqual(try packed.unpacked(), expected, "query \(query)")
+        }
+    }
+
+    func testRejectsForeignAndTruncatedBuffers() {
+        XCTAssertThrowsError(try PackedMatchDecoder.decode(Data("NOPE".utf8))) { error in
+            XCTAssertEqual(error as? PackedMatchDecodingError, .badMagic)
+        }
+        XCTAssertThrowsError(try PackedMatchDecoder.decode(Data("CKPM".utf8) + Data([9]))) { error in
+            XCTAssertEqual(error as? PackedMatchDecodingError, .unsupportedVersion(9))
+        }
+        XCTAssertThrowsError(try PackedMatchDecoder.decode(Data("CKPM".utf8) + Data([1, 1, 0, 0, 0]))) { error in
+            XCTAssertEqual(error as? PackedMatchDecodingError, .truncated)
+        }
+    }
+}
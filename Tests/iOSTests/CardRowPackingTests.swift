@@ -14,7 +14,8 @@ final class CardRowPackingTests: XCTestCase {
                 sourceApp: nil,
                 sourceAppBundleId: nil,
                 timestampUnix: 0,
-                tags: []
+                tags: [],
+                isPinned: false
             ),
             presentation: .baseline(excerpt: BaselineExcerpt(text: text))
         )
@@ -28,7 +29,8 @@ final class CardRowPackingTests: XCTestCase {
                 sourceApp: nil,
                 sourceAppBundleId: nil,
                 timestampUnix: 0,
-                tags: []
+                tags: [],
+                isPinned: false
             ),
             presentation: .baseline(excerpt: BaselineExcerpt(text: ""))
         )
@@ -42,7 +44,8 @@ final class CardRowPackingTests: XCTestCase {
                 sourceApp: nil,
                 sourceAppBundleId: nil,
                 timestampUnix: 0,
-                tags: []
+                tags: [],
+                isPinned: false
             ),
             presentation: .baseline(excerpt: BaselineExcerpt(text: url))
         )
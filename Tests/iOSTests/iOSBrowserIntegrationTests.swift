@@ -199,10 +199,10 @@ final class iOSBrowserIntegrationTests: XCTestCase {
         // ViewModel sees all items
         XCTAssertEqual(viewModel.itemIds, ["1", "2", "3"])
 
-        // iOS HomeFeedView filteredRows logic: exclude .file icons
+        // iOS HomeFeedView filteredRows logic: exclude file and document icons
         let filteredRows = viewModel.displayRows.filter { row in
-            if case .symbol(.file) = row.metadata.icon { return false }
-            return true
+            let iconType = row.metadata.icon.baseIconType
+            return iconType != .file && iconType != .document
         }
 
         XCTAssertEqual(filteredRows.map(\.id), ["1", "3"])
@@ -311,7 +311,8 @@ final class iOSBrowserIntegrationTests: XCTestCase {
                 sourceApp: nil,
                 sourceAppBundleId: nil,
                 timestampUnix: 0,
-                tags: tags
+                tags: tags,
+                isPinned: false
             ),
             presentation: .baseline(excerpt: BaselineExcerpt(text: excerpt))
         )
@@ -325,7 +326,8 @@ final class iOSBrowserIntegrationTests: XCTestCase {
                 sourceApp: nil,
                 sourceAppBundleId: nil,
                 timestampUnix: 0,
-                tags: tags
+                tags: tags,
+                isPinned: false
             ),
             content: .text(value: text)
         )
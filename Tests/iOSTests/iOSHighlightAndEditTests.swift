@@ -346,7 +346,8 @@ final class iOSHighlightAndEditTests: XCTestCase {
             sourceApp: nil,
             sourceAppBundleId: nil,
             timestampUnix: 0,
-            tags: []
+            tags: [],
+            isPinned: false
         )
     }
 
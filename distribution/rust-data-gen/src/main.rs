@@ -173,49 +173,19 @@ fn image_exists(db_path: &str, description: &str, locale: &str) -> bool {
     };
 
     conn.query_row(
-        "SELECT 1 FROM image_items WHERE description = ?1 AND locale = ?2 LIMIT 1",
-        params![description, locale],
+        "SELECT 1 FROM items WHERE contentType = 'image' AND content = ?1 AND locale = ?2 LIMIT 1",
+        params![description, locale.to_ascii_lowercase()],
         |_| Ok(()),
     )
     .is_ok()
 }
 
-/// Ensure the locale column exists in the image_items table.
-/// This allows the schema to be migrated from single-locale to multi-locale.
-fn ensure_locale_column(db_path: &str) -> Result<()> {
-    let conn = rusqlite::Connection::open(db_path)?;
-
-    // Check if the locale column already exists
-    let column_exists: bool = conn.query_row(
-        "SELECT COUNT(*) FROM pragma_table_info('image_items') WHERE name = 'locale'",
-        [],
-        |row| {
-            let count: i64 = row.get(0)?;
-            Ok(count > 0)
-        },
-    )?;
-
-    if !column_exists {
-        // Add the locale column with a default value
-        conn.execute(
-            "ALTER TABLE image_items ADD COLUMN locale TEXT DEFAULT 'en'",
-            [],
-        )?;
-        // Create an index for efficient locale-based queries
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_image_locale ON image_items(locale)",
-            [],
-        )?;
-    }
-
-    Ok(())
-}
-
 /// Save an image item directly via SQL (for synthetic data generation).
 /// Inserts into both `items` and `image_items` (normalized schema).
 /// If `thumbnail` is None, one is generated from `image_data` (requires a format
 /// the `image` crate can decode — not HEIC).
-/// The `locale` parameter is stored in the image_items table for multi-locale support.
+/// The `locale` is stored on the item, lowercased as the store compares it, so
+/// the app only shows images for its own locale.
 fn save_image_direct(
     db_path: &str,
     image_data: Vec<u8>,
@@ -239,8 +209,8 @@ fn save_image_direct(
     let tx = conn.unchecked_transaction()?;
 
     tx.execute(
-        r#"INSERT INTO items (item_id, contentType, contentHash, content, timestamp, sourceApp, sourceAppBundleId, thumbnail)
-           VALUES (?1, 'image', ?2, ?3, ?4, ?5, ?6, ?7)"#,
+        r#"INSERT INTO items (item_id, contentType, contentHash, content, timestamp, sourceApp, sourceAppBundleId, thumbnail, locale)
+           VALUES (?1, 'image', ?2, ?3, ?4, ?5, ?6, ?7, ?8)"#,
         params![
             item_uuid,
             content_hash,
@@ -249,13 +219,14 @@ fn save_image_direct(
             source_app,
             source_app_bundle_id,
             thumbnail,
+            locale.to_ascii_lowercase(),
         ],
     )?;
     let item_id = tx.last_insert_rowid();
 
     tx.execute(
-        "INSERT INTO image_items (itemId, data, description, locale) VALUES (?1, ?2, ?3, ?4)",
-        params![item_id, image_data, description, locale],
+        "INSERT INTO image_items (itemId, data, description) VALUES (?1, ?2, ?3)",
+        params![item_id, image_data, description],
     )?;
 
     tx.commit()?;
@@ -405,10 +376,9 @@ fn generate_timestamp(item_index: usize, now: i64) -> i64 {
     now - age_seconds
 }
 
-use demo_data::localized::{get_localized_demo_items, get_localized_image_keywords};
+use demo_data::localized::get_localized_image_keywords;
 use demo_data::video::VIDEO_ITEMS;
 use demo_data::video_localized::get_localized_video_items;
-use demo_data::DEMO_ITEMS;
 
 /// Source images with keyword captions (mirrors Vision framework output)
 /// Format: (filename, keywords, source_app, bundle_id, time_offset_seconds)
@@ -496,94 +466,15 @@ fn find_id_by_hash(db_path: &str, content_hash: &str) -> Option<i64> {
     .ok()
 }
 
-/// Delete English demo items from the database (used before inserting localized versions)
-fn delete_english_demo_items(db_path: &str) -> Result<()> {
-    let conn = rusqlite::Connection::open(db_path)?;
-
-    // Delete items matching English demo content by exact hash
-    for item in DEMO_ITEMS {
-        let mut hasher = DefaultHasher::new();
-        item.content.hash(&mut hasher);
-        let content_hash = hasher.finish().to_string();
-
-        // Get the item ID first
-        let item_id: Option<i64> = conn
-            .query_row(
-                "SELECT id FROM items WHERE contentHash = ?1",
-                params![content_hash],
-                |row| row.get(0),
-            )
-            .ok();
-
-        if let Some(id) = item_id {
-            // Delete from text_items first (foreign key)
-            conn.execute("DELETE FROM text_items WHERE itemId = ?1", params![id])?;
-            // Then delete from items
-            conn.execute("DELETE FROM items WHERE id = ?1", params![id])?;
-        }
-    }
-
-    // Also delete by pattern for items that might have slightly different content
-    // (e.g., older versions of ClipKitty bullet points with different wording)
-    let patterns = [
-        "ClipKitty\n• Copy it once%",                  // ClipKitty bullet points
-        "Apartment walkthrough notes:%",               // Apartment notes
-        "# Deploy API server to production%",          // Deploy command
-        "The quick brown fox jumps over the lazy dog", // Greeting text
-        "#!/bin/bash\nset -euo pipefail%",             // Code comment/script
-        "https://developer.apple.com/documentation%",  // URL
-        "%riverside_park_picnic_directions.txt",       // File path
-        "%driver_config.yaml",                         // File path
-        "%river_animation_keyframes.css",              // File path
-        "%README.md",                                  // File path
-        "%catalog_api_response.json",                  // File path
-    ];
-
-    for pattern in patterns {
-        // Find all matching item IDs
-        let mut stmt = conn.prepare("SELECT itemId FROM text_items WHERE value LIKE ?1")?;
-        let item_ids: Vec<i64> = stmt
-            .query_map(params![pattern], |row| row.get(0))?
-            .filter_map(|r| r.ok())
-            .collect();
-
-        for id in item_ids {
-            conn.execute("DELETE FROM text_items WHERE itemId = ?1", params![id])?;
-            conn.execute("DELETE FROM items WHERE id = ?1", params![id])?;
-        }
-    }
-
-    // Images are now stored with locale column and don't need to be deleted
-    // The base generation inserts images for all locales upfront
-
-    Ok(())
-}
-
 fn insert_demo_items(store: &ClipboardStore, db_path: &str, locale: Option<&str>) -> Result<()> {
     let now = Utc::now().timestamp();
 
-    // Ensure the locale column exists in image_items table (migration from old schema)
-    ensure_locale_column(db_path)?;
-
     match locale {
         None => {
             // Base generation: Insert English text items and images for ALL locales
 
             // Insert English text demo items
-            for item in DEMO_ITEMS {
-                let _ = store.save_text(
-                    item.content.to_string(),
-                    Some(item.source_app.to_string()),
-                    Some(item.bundle_id.to_string()),
-                );
-                // Always set the correct timestamp (handles both new and duplicate items)
-                let mut hasher = DefaultHasher::new();
-                item.content.hash(&mut hasher);
-                let content_hash = hasher.finish().to_string();
-                if let Some(id) = find_id_by_hash(db_path, &content_hash) {
-                    let _ = set_timestamp_direct(db_path, id, now + item.offset);
-                }
-            }
+            store.seed_demo_data("en".to_string())?;
 
             // Insert images for ALL locales (including English)
             let base_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -666,30 +557,12 @@ fn insert_demo_items(store: &ClipboardStore, db_path: &str, locale: Option<&str>
             }
         }
         Some(loc) => {
-            // Localized generation: Replace text items only (images already exist from base)
-
-            // Delete English text demo items
-            delete_english_demo_items(db_path)?;
-
-            // Insert localized text demo items
-            let demo_items = get_localized_demo_items(loc).unwrap_or(DEMO_ITEMS);
-            for item in demo_items {
-                let _ = store.save_text(
-                    item.content.to_string(),
-                    Some(item.source_app.to_string()),
-                    Some(item.bundle_id.to_string()),
-                );
-                // Always set the correct timestamp (handles both new and duplicate items)
-                let mut hasher = DefaultHasher::new();
-                item.content.hash(&mut hasher);
-                let content_hash = hasher.finish().to_string();
-                if let Some(id) = find_id_by_hash(db_path, &content_hash) {
-                    let _ = set_timestamp_direct(db_path, id, now + item.offset);
-                }
-            }
+            // Localized generation: Replace text items only (images already exist from base).
+            // Seeding replaces the English items the base generation seeded.
+            store.seed_demo_data(loc.to_string())?;
 
-            // Images are already in the database from the base generation (with locale column)
-            // The UI will filter images by locale when displaying them
+            // Images are already in the database from the base generation, each tagged
+            // with its locale; the store shows those for the app's preferred locale.
         }
     }
 
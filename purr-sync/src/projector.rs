@@ -3,6 +3,7 @@
 //! Conflict rules:
 //! - bookmark vs content edit: both apply (independent domains)
 //! - bookmark vs debookmark: latest bookmark-domain version wins
+//! - reminder set vs cleared: latest reminder-domain version wins
 //! - stale bookmark/touch against tombstone: ignore
 //! - edit vs delete from stale base: fork if content was user-authored
 //! - edit vs edit from same base with different text: fork into new logical item
@@ -45,6 +46,13 @@ pub fn apply_event(aggregate: Option<&ItemAggregate>, payload: &ItemEventPayload
             description,
             base_content_version,
         } => apply_image_description_updated(aggregate, description, *base_content_version),
+        ItemEventPayload::ReminderSet {
+            fire_at_unix,
+            base_reminder_version,
+        } => apply_reminder_changed(aggregate, Some(*fire_at_unix), *base_reminder_version),
+        ItemEventPayload::ReminderCleared {
+            base_reminder_version,
+        } => apply_reminder_changed(aggregate, None, *base_reminder_version),
         ItemEventPayload::Unknown { raw_type, .. } => {
             ApplyResult::Ignored(IgnoreReason::UnknownPayload {
                 raw_type: raw_type.clone(),
@@ -72,6 +80,11 @@ fn apply_item_created(
         existence: 1,
         touch: 1,
         metadata: 1,
+        reminder: if snapshot.reminder_at_unix.is_some() {
+            1
+        } else {
+            0
+        },
     };
 
     let new_aggregate = ItemAggregate::Live(LiveItemState {
@@ -88,6 +101,9 @@ fn apply_item_created(
     if snapshot.is_bookmarked {
         bumped_domains.push(VersionDomain::Bookmark);
     }
+    if snapshot.reminder_at_unix.is_some() {
+        bumped_domains.push(VersionDomain::Reminder);
+    }
 
     ApplyResult::Applied(ProjectionDelta {
         new_aggregate,
@@ -242,6 +258,52 @@ fn apply_bookmark_cleared(
     }
 }
 
+fn apply_reminder_changed(
+    aggregate: Option<&ItemAggregate>,
+    fire_at_unix: Option<i64>,
+    base_reminder_version: u64,
+) -> ApplyResult {
+    let Some(agg) = aggregate else {
+        return ApplyResult::Deferred(DeferredReason::MissingItem);
+    };
+
+    match agg {
+        ItemAggregate::Tombstoned(_) => ApplyResult::Ignored(IgnoreReason::OperationOnTombstone),
+        ItemAggregate::Live(live) => {
+            let current = live.versions.reminder;
+            if base_reminder_version < current {
+                return ApplyResult::Ignored(IgnoreReason::StaleVersion {
+                    domain: VersionDomain::Reminder,
+                    event_base: base_reminder_version,
+                    current,
+                });
+            }
+            if base_reminder_version > current {
+                return ApplyResult::Deferred(DeferredReason::FutureVersion {
+                    domain: VersionDomain::Reminder,
+                    event_base: base_reminder_version,
+                    current,
+                });
+            }
+
+            let mut new_snapshot = live.snapshot.clone();
+            new_snapshot.reminder_at_unix = fire_at_unix;
+            let mut new_versions = live.versions;
+            new_versions.reminder = new_versions.reminder.saturating_add(1);
+
+            ApplyResult::Applied(ProjectionDelta {
+                new_aggregate: ItemAggregate::Live(LiveItemState {
+                    snapshot: new_snapshot,
+                    versions: new_versions,
+                }),
+                bumped_domains: vec![VersionDomain::Reminder],
+                read_model_dirty: true,
+                index_dirty: false,
+            })
+        }
+    }
+}
+
 fn apply_item_deleted(
     aggregate: Option<&ItemAggregate>,
     base_existence_version: u64,
@@ -448,6 +510,8 @@ fn fork_from_text_edit(aggregate: &ItemAggregate, new_text: &str) -> ApplyResult
     let forked_snapshot = match aggregate {
         ItemAggregate::Live(live) => {
             let mut snap = live.snapshot.clone();
+            // The fork is a new item and gets its own external ID.
+            snap.external_id = None;
             snap.content_text = new_text.to_string();
             snap.content_hash = crate::util::content_hash(new_text);
             if let TypeSpecificData::Text { ref mut value } = snap.type_specific {
@@ -457,6 +521,7 @@ fn fork_from_text_edit(aggregate: &ItemAggregate, new_text: &str) -> ApplyResult
             snap
         }
         ItemAggregate::Tombstoned(tomb) => ItemSnapshotData {
+            external_id: None,
             content_type: tomb.content_type.clone(),
             content_text: new_text.to_string(),
             content_hash: crate::util::content_hash(new_text),
@@ -466,6 +531,7 @@ fn fork_from_text_edit(aggregate: &ItemAggregate, new_text: &str) -> ApplyResult
             is_bookmarked: false,
             thumbnail_base64: None,
             color_rgba: None,
+            reminder_at_unix: None,
             type_specific: TypeSpecificData::Text {
                 value: new_text.to_string(),
             },
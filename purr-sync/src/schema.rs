@@ -103,7 +103,8 @@ fn migrate_sync_index_queue_if_needed(conn: &rusqlite::Connection) -> SyncResult
 }
 
 /// Create all sync tables on the given connection.
-/// Called by the host crate during database initialization.
+/// Called by the host crate during database initialization. The host also
+/// registers the `seal` and `unseal` functions the store's payload SQL uses.
 pub fn setup_sync_schema(conn: &rusqlite::Connection) -> SyncResult<()> {
     reset_pre_release_sync_schema_if_needed(conn)?;
     migrate_sync_index_queue_if_needed(conn)?;
@@ -150,6 +151,7 @@ pub fn setup_sync_schema(conn: &rusqlite::Connection) -> SyncResult<()> {
             existence_version INTEGER NOT NULL DEFAULT 0,
             touch_version INTEGER NOT NULL DEFAULT 0,
             metadata_version INTEGER NOT NULL DEFAULT 0,
+            reminder_version INTEGER NOT NULL DEFAULT 0,
             is_tombstoned INTEGER NOT NULL DEFAULT 0,
             is_materialized INTEGER NOT NULL DEFAULT 0
         );
@@ -202,6 +204,10 @@ pub fn setup_sync_schema(conn: &rusqlite::Connection) -> SyncResult<()> {
         "ALTER TABLE sync_snapshots ADD COLUMN uploaded_at INTEGER",
         [],
     );
+    let _ = conn.execute(
+        "ALTER TABLE sync_projection ADD COLUMN reminder_version INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
 
     Ok(())
 }
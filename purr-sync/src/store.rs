@@ -1,6 +1,11 @@
 //! Sync store — local persistence layer for sync state.
 //!
 //! CRUD operations on all sync tables through a shared connection pool.
+//!
+//! Event and snapshot payloads are written through the SQL function `seal`
+//! and read through `unseal`, which the host registers on every pooled
+//! connection. A host that encrypts at rest seals them there; one that does
+//! not registers both as pass-throughs.
 
 use crate::error::{SyncError, SyncResult};
 use crate::event::ItemEvent;
@@ -37,7 +42,7 @@ impl<'a> SyncStore<'a> {
             r#"INSERT INTO sync_events
                (event_id, item_id, origin_device_id, schema_version, recorded_at,
                 payload_type, payload_data, is_local, uploaded, compacted)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 1, 0, 0)"#,
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, seal(?7), 1, 0, 0)"#,
             params![
                 event.event_id,
                 event.item_id,
@@ -58,7 +63,7 @@ impl<'a> SyncStore<'a> {
             r#"INSERT OR IGNORE INTO sync_events
                (event_id, item_id, origin_device_id, schema_version, recorded_at,
                 payload_type, payload_data, is_local, uploaded, compacted)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 0, 1, 0)"#,
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, seal(?7), 0, 1, 0)"#,
             params![
                 event.event_id,
                 event.item_id,
@@ -78,7 +83,7 @@ impl<'a> SyncStore<'a> {
         let conn = self.get_conn()?;
         let mut stmt = conn.prepare(
             r#"SELECT event_id, item_id, origin_device_id, schema_version,
-                      recorded_at, payload_type, payload_data
+                      recorded_at, payload_type, unseal(payload_data)
                FROM sync_events
                WHERE item_id = ?1 AND compacted = 0
                ORDER BY recorded_at ASC, event_id ASC"#,
@@ -110,7 +115,7 @@ impl<'a> SyncStore<'a> {
         let conn = self.get_conn()?;
         let mut stmt = conn.prepare(
             r#"SELECT event_id, item_id, origin_device_id, schema_version,
-                      recorded_at, payload_type, payload_data
+                      recorded_at, payload_type, unseal(payload_data)
                FROM sync_events
                WHERE is_local = 1 AND uploaded = 0
                ORDER BY recorded_at ASC"#,
@@ -200,7 +205,7 @@ impl<'a> SyncStore<'a> {
     pub fn uncompacted_payload_size(&self, item_id: &str) -> SyncResult<usize> {
         let conn = self.get_conn()?;
         let size: i64 = conn.query_row(
-            "SELECT COALESCE(SUM(LENGTH(payload_data)), 0) FROM sync_events WHERE item_id = ?1 AND compacted = 0",
+            "SELECT COALESCE(SUM(LENGTH(unseal(payload_data))), 0) FROM sync_events WHERE item_id = ?1 AND compacted = 0",
             params![item_id],
             |row| row.get(0),
         )?;
@@ -238,7 +243,7 @@ impl<'a> SyncStore<'a> {
             r#"INSERT INTO sync_snapshots
                (item_id, snapshot_revision, schema_version,
                 covers_through_event, aggregate_state, uploaded, uploaded_at)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+               VALUES (?1, ?2, ?3, ?4, seal(?5), ?6, ?7)
                ON CONFLICT(item_id) DO UPDATE SET
                  snapshot_revision = excluded.snapshot_revision,
                  schema_version = excluded.schema_version,
@@ -264,7 +269,7 @@ impl<'a> SyncStore<'a> {
         let conn = self.get_conn()?;
         let result = conn.query_row(
             r#"SELECT item_id, snapshot_revision, schema_version,
-                      covers_through_event, aggregate_state, uploaded, uploaded_at
+                      covers_through_event, unseal(aggregate_state), uploaded, uploaded_at
                FROM sync_snapshots WHERE item_id = ?1"#,
             params![item_id],
             |row| {
@@ -302,7 +307,7 @@ impl<'a> SyncStore<'a> {
         let conn = self.get_conn()?;
         let mut stmt = conn.prepare(
             r#"SELECT item_id, snapshot_revision, schema_version,
-                      covers_through_event, aggregate_state, uploaded, uploaded_at
+                      covers_through_event, unseal(aggregate_state), uploaded, uploaded_at
                FROM sync_snapshots"#,
         )?;
         let rows = stmt
@@ -352,7 +357,7 @@ impl<'a> SyncStore<'a> {
         let conn = self.get_conn()?;
         let mut stmt = conn.prepare(
             r#"SELECT item_id, snapshot_revision, schema_version,
-                      covers_through_event, aggregate_state, uploaded, uploaded_at
+                      covers_through_event, unseal(aggregate_state), uploaded, uploaded_at
                FROM sync_snapshots WHERE uploaded = 0"#,
         )?;
         let rows = stmt
@@ -453,14 +458,15 @@ impl<'a> SyncStore<'a> {
             r#"INSERT INTO sync_projection
                (item_id, content_version, bookmark_version,
                 existence_version, touch_version, metadata_version,
-                is_tombstoned, is_materialized)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                reminder_version, is_tombstoned, is_materialized)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
                ON CONFLICT(item_id) DO UPDATE SET
                  content_version = excluded.content_version,
                  bookmark_version = excluded.bookmark_version,
                  existence_version = excluded.existence_version,
                  touch_version = excluded.touch_version,
                  metadata_version = excluded.metadata_version,
+                 reminder_version = excluded.reminder_version,
                  is_tombstoned = excluded.is_tombstoned,
                  is_materialized = excluded.is_materialized"#,
             params![
@@ -470,6 +476,7 @@ impl<'a> SyncStore<'a> {
                 versions.existence as i64,
                 versions.touch as i64,
                 versions.metadata as i64,
+                versions.reminder as i64,
                 is_tombstoned as i32,
                 is_materialized as i32,
             ],
@@ -483,7 +490,7 @@ impl<'a> SyncStore<'a> {
         let result = conn.query_row(
             r#"SELECT item_id, content_version, bookmark_version,
                       existence_version, touch_version, metadata_version,
-                      is_tombstoned, is_materialized
+                      reminder_version, is_tombstoned, is_materialized
                FROM sync_projection WHERE item_id = ?1"#,
             params![item_id],
             |row| {
@@ -493,9 +500,10 @@ impl<'a> SyncStore<'a> {
                     existence: row.get::<_, i64>(3)? as u64,
                     touch: row.get::<_, i64>(4)? as u64,
                     metadata: row.get::<_, i64>(5)? as u64,
+                    reminder: row.get::<_, i64>(6)? as u64,
                 };
-                let is_tombstoned = row.get::<_, i32>(6)? != 0;
-                let is_materialized = row.get::<_, i32>(7)? != 0;
+                let is_tombstoned = row.get::<_, i32>(7)? != 0;
+                let is_materialized = row.get::<_, i32>(8)? != 0;
                 let state = if is_tombstoned {
                     ProjectionState::Tombstoned { versions }
                 } else if is_materialized {
@@ -550,7 +558,7 @@ impl<'a> SyncStore<'a> {
             r#"INSERT OR REPLACE INTO sync_deferred_events
                (event_id, item_id, origin_device_id, schema_version, recorded_at,
                 payload_type, payload_data, deferred_reason, deferred_at)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"#,
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, seal(?7), ?8, ?9)"#,
             params![
                 event.event_id,
                 event.item_id,
@@ -571,7 +579,7 @@ impl<'a> SyncStore<'a> {
         let conn = self.get_conn()?;
         let mut stmt = conn.prepare(
             r#"SELECT event_id, item_id, origin_device_id, schema_version,
-                      recorded_at, payload_type, payload_data
+                      recorded_at, payload_type, unseal(payload_data)
                FROM sync_deferred_events
                WHERE item_id = ?1
                ORDER BY recorded_at ASC"#,
@@ -603,7 +611,7 @@ impl<'a> SyncStore<'a> {
         let conn = self.get_conn()?;
         let mut stmt = conn.prepare(
             r#"SELECT event_id, item_id, origin_device_id, schema_version,
-                      recorded_at, payload_type, payload_data
+                      recorded_at, payload_type, unseal(payload_data)
                FROM sync_deferred_events
                ORDER BY recorded_at ASC"#,
         )?;
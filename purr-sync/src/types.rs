@@ -27,6 +27,9 @@ pub struct VersionVector {
     pub existence: u64,
     pub touch: u64,
     pub metadata: u64,
+    /// Absent from snapshots written before reminders existed.
+    #[serde(default)]
+    pub reminder: u64,
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -52,7 +55,7 @@ pub struct LinkMetadataSnapshot {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ItemEventPayload {
     ItemCreated {
-        snapshot: ItemSnapshotData,
+        snapshot: Box<ItemSnapshotData>,
     },
     TextEdited {
         new_text: String,
@@ -79,6 +82,13 @@ pub enum ItemEventPayload {
         description: String,
         base_content_version: u64,
     },
+    ReminderSet {
+        fire_at_unix: i64,
+        base_reminder_version: u64,
+    },
+    ReminderCleared {
+        base_reminder_version: u64,
+    },
     /// Payload from a newer schema version that we don't understand.
     /// Preserved for round-tripping but ignored by the projector.
     Unknown {
@@ -99,6 +109,8 @@ impl ItemEventPayload {
             Self::ItemTouched { .. } => "item_touched".to_string(),
             Self::LinkMetadataUpdated { .. } => "link_metadata_updated".to_string(),
             Self::ImageDescriptionUpdated { .. } => "image_description_updated".to_string(),
+            Self::ReminderSet { .. } => "reminder_set".to_string(),
+            Self::ReminderCleared { .. } => "reminder_cleared".to_string(),
             Self::Unknown { raw_type, .. } => raw_type.clone(),
         }
     }
@@ -112,6 +124,10 @@ impl ItemEventPayload {
 /// and in compacted snapshots. Serializable for CloudKit transport.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ItemSnapshotData {
+    /// The item's deep-link ID, shared by every device. Absent from
+    /// snapshots written before external IDs existed.
+    #[serde(default)]
+    pub external_id: Option<String>,
     pub content_type: String,
     pub content_text: String,
     pub content_hash: String,
@@ -122,6 +138,9 @@ pub struct ItemSnapshotData {
     /// Base64-encoded thumbnail for transport.
     pub thumbnail_base64: Option<String>,
     pub color_rgba: Option<u32>,
+    /// When the item should resurface, if a reminder is set.
+    #[serde(default)]
+    pub reminder_at_unix: Option<i64>,
     /// Type-specific data (text value, image bytes, link url, file entries, etc.)
     pub type_specific: TypeSpecificData,
 }
@@ -149,6 +168,23 @@ pub enum TypeSpecificData {
         display_name: String,
         files: Vec<FileSnapshotEntry>,
     },
+    RichText {
+        value: String,
+        /// Base64-encoded RTF data for transport.
+        rtf_data_base64: Option<String>,
+        html: Option<String>,
+    },
+    PhoneNumber {
+        value: String,
+    },
+    PostalAddress {
+        value: String,
+    },
+    Markdown {
+        /// Plain-text render used for search and previews.
+        value: String,
+        source: String,
+    },
 }
 
 /// A single file entry in a snapshot.
@@ -228,6 +264,7 @@ pub enum VersionDomain {
     Existence,
     Touch,
     Metadata,
+    Reminder,
 }
 
 /// Why an event was ignored (no state change).
@@ -0,0 +1,245 @@
+//! Spoken descriptions of items for VoiceOver.
+//!
+//! A description names the kind of item, where and when it was copied, and
+//! the detail that tells it apart: the opening words of text, an image's
+//! keywords, or file names. Ages are spelled out ("two hours ago") so they
+//! read aloud well. Descriptions are English.
+
+use crate::interface::{ClipboardContent, UNTITLED_IMAGE_DESCRIPTION};
+use crate::models::StoredItem;
+
+/// Words of text read out before the rest is cut off.
+const EXCERPT_WORDS: usize = 12;
+/// Image keywords and file names read out.
+const MAX_LISTED: usize = 5;
+/// Vision labels are short; a longer phrase is recognized text instead.
+const MAX_KEYWORD_WORDS: usize = 3;
+
+pub(crate) fn describe(item: &StoredItem, now_unix: i64) -> String {
+    let (kind, detail) = kind_and_detail(&item.content);
+    let mut description = kind;
+    if let Some(app) = item.source_app.as_deref().filter(|app| !app.is_empty()) {
+        description.push_str(" copied from ");
+        description.push_str(app);
+    }
+    description.push(' ');
+    description.push_str(&spoken_age(now_unix - item.timestamp_unix));
+    if let Some(detail) = detail {
+        description.push_str(", ");
+        description.push_str(&detail);
+    }
+    description
+}
+
+fn kind_and_detail(content: &ClipboardContent) -> (String, Option<String>) {
+    match content {
+        ClipboardContent::Text { value } => ("Text".into(), excerpt(value)),
+        ClipboardContent::RichText { value, .. } => ("Formatted text".into(), excerpt(value)),
+        ClipboardContent::Color { value } => (format!("Color {value}"), None),
+        ClipboardContent::PhoneNumber { value } => (format!("Phone number {value}"), None),
+        ClipboardContent::PostalAddress { value } => ("Address".into(), excerpt(value)),
+        ClipboardContent::Markdown { value, .. } => ("Markdown".into(), excerpt(value)),
+        ClipboardContent::Link { url, .. } => {
+            let host = url::Url::parse(url)
+                .ok()
+                .and_then(|url| url.host_str().map(|host| host.to_string()));
+            match host {
+                Some(host) => (format!("Link to {}", host.trim_start_matches("www.")), None),
+                None => ("Link".into(), Some(url.clone())),
+            }
+        }
+        ClipboardContent::Image {
+            description,
+            is_animated,
+            ..
+        } => {
+            let kind = if *is_animated {
+                "Animated image"
+            } else {
+                "Image"
+            };
+            let keywords = image_keywords(description);
+            let detail = (!keywords.is_empty()).then(|| format!("keywords: {}", listed(&keywords)));
+            (kind.into(), detail)
+        }
+        ClipboardContent::File { files, .. } => {
+            let names: Vec<&str> = files.iter().map(|file| file.filename.as_str()).collect();
+            match names.as_slice() {
+                [] => ("File".into(), None),
+                [name] => (format!("File {name}"), None),
+                _ => (
+                    format!("{} files", spoken_number(names.len() as i64)),
+                    Some(listed(&names)),
+                ),
+            }
+        }
+    }
+}
+
+/// The text's opening words, on one line.
+fn excerpt(text: &str) -> Option<String> {
+    let mut words = text.split_whitespace();
+    let mut excerpt = words
+        .by_ref()
+        .take(EXCERPT_WORDS)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if excerpt.is_empty() {
+        return None;
+    }
+    if words.next().is_some() {
+        excerpt.push('…');
+    }
+    Some(excerpt)
+}
+
+/// Labels from an image description as the host writes it: "Image: " and
+/// a list of Vision labels, then any recognized text after ". ".
+fn image_keywords(description: &str) -> Vec<&str> {
+    let Some(labels) = description
+        .strip_prefix(UNTITLED_IMAGE_DESCRIPTION)
+        .and_then(|rest| rest.strip_prefix(':'))
+        .and_then(|rest| rest.split(". ").next())
+    else {
+        return Vec::new();
+    };
+    let keywords: Vec<&str> = labels
+        .split(", ")
+        .flat_map(|part| part.split(" and "))
+        .map(|part| part.trim().trim_start_matches("and "))
+        .filter(|part| !part.is_empty())
+        .collect();
+    if keywords
+        .iter()
+        .any(|keyword| keyword.split_whitespace().count() > MAX_KEYWORD_WORDS)
+    {
+        return Vec::new();
+    }
+    keywords
+}
+
+/// Up to `MAX_LISTED` of `names`, then how many more there are.
+fn listed(names: &[&str]) -> String {
+    let shown = names
+        .iter()
+        .take(MAX_LISTED)
+        .map(|name| name.replace('_', " "))
+        .collect::<Vec<_>>()
+        .join(", ");
+    match names.len().saturating_sub(MAX_LISTED) {
+        0 => shown,
+        more => format!("{shown} and {} more", spoken_number(more as i64)),
+    }
+}
+
+fn spoken_age(seconds: i64) -> String {
+    const UNITS: [(i64, &str); 6] = [
+        (365 * 24 * 3600, "year"),
+        (30 * 24 * 3600, "month"),
+        (7 * 24 * 3600, "week"),
+        (24 * 3600, "day"),
+        (3600, "hour"),
+        (60, "minute"),
+    ];
+    if (24 * 3600..2 * 24 * 3600).contains(&seconds) {
+        return "yesterday".into();
+    }
+    match UNITS.iter().find(|(unit, _)| seconds >= *unit) {
+        None => "just now".into(),
+        Some((unit, name)) => {
+            let count = seconds / unit;
+            let plural = if count == 1 { "" } else { "s" };
+            format!("{} {name}{plural} ago", spoken_number(count))
+        }
+    }
+}
+
+/// Small counts as words, which speech reads more naturally than digits.
+fn spoken_number(count: i64) -> String {
+    const WORDS: [&str; 11] = [
+        "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    ];
+    usize::try_from(count)
+        .ok()
+        .and_then(|index| WORDS.get(index))
+        .map_or_else(|| count.to_string(), |word| word.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(content: ClipboardContent, source_app: Option<&str>, age: i64) -> StoredItem {
+        let mut item = StoredItem::new_text("placeholder".into(), None, None);
+        item.content = content;
+        item.source_app = source_app.map(str::to_string);
+        item.timestamp_unix = 1_000_000 - age;
+        item
+    }
+
+    #[test]
+    fn descriptions_name_kind_source_age_and_detail() {
+        let image = item(
+            ClipboardContent::Image {
+                data: Vec::new(),
+                description: "Image: wave, ocean, and Hokusai. THE GREAT WAVE".into(),
+                is_animated: false,
+            },
+            Some("Safari"),
+            2 * 3600 + 5,
+        );
+        assert_eq!(
+            describe(&image, 1_000_000),
+            "Image copied from Safari two hours ago, keywords: wave, ocean, Hokusai"
+        );
+
+        let text = item(
+            ClipboardContent::Text {
+                value: "  Meeting moved\nto Thursday at noon, bring the quarterly numbers and the draft agenda".into(),
+            },
+            Some("Notes"),
+            30,
+        );
+        assert_eq!(
+            describe(&text, 1_000_000),
+            "Text copied from Notes just now, Meeting moved to Thursday at noon, bring the quarterly numbers and the…"
+        );
+
+        let link = item(
+            ClipboardContent::Link {
+                url: "https://www.example.com/a?b=c".into(),
+                metadata_state: crate::interface::LinkMetadataState::Pending,
+            },
+            None,
+            26 * 3600,
+        );
+        assert_eq!(describe(&link, 1_000_000), "Link to example.com yesterday");
+
+        let untitled = item(
+            ClipboardContent::Image {
+                data: Vec::new(),
+                description: UNTITLED_IMAGE_DESCRIPTION.into(),
+                is_animated: true,
+            },
+            None,
+            15 * 86_400,
+        );
+        assert_eq!(
+            describe(&untitled, 1_000_000),
+            "Animated image two weeks ago"
+        );
+    }
+
+    #[test]
+    fn ages_and_counts_read_as_words() {
+        assert_eq!(spoken_age(-10), "just now");
+        assert_eq!(spoken_age(60), "one minute ago");
+        assert_eq!(spoken_age(3 * 86_400), "three days ago");
+        assert_eq!(spoken_age(400 * 86_400), "one year ago");
+        assert_eq!(spoken_number(42), "42");
+        assert_eq!(
+            listed(&["a", "b", "c", "d", "e", "f", "g"]),
+            "a, b, c, d, e and two more"
+        );
+    }
+}
@@ -0,0 +1,381 @@
+//! Aggregate clipboard activity for insights and export.
+//!
+//! Only counts leave the store: days, apps, content types, and item IDs,
+//! never item content.
+
+use crate::database::{DailyActivityRow, Database};
+use crate::interface::{
+    ActivityHeatmap, ClipKittyError, ContentTypeUsage, DailyUsage, HeatmapDay, ItemPasteStats,
+    SourceAppUsage, TimeRange, UsageStats,
+};
+use chrono::{DateTime, Days, NaiveDate, TimeZone, Utc};
+use std::collections::HashMap;
+
+const ACTIVITY_CSV_HEADER: &str = "date,activity,app,content_type,count";
+/// A year of days, as in a contribution graph.
+const MAX_HEATMAP_WEEKS: u32 = 53;
+/// Local days covered by `UsageStats::by_day`, today included.
+const STATS_DAYS: u32 = 30;
+/// Items listed in `UsageStats::most_pasted`.
+const MOST_PASTED_LIMIT: usize = 20;
+
+/// CSV of per-day copy and paste counts by app and content type within
+/// `range`, with days in the device's local time zone.
+pub(crate) fn export_activity_csv(
+    db: &Database,
+    range: &TimeRange,
+    utc_offset_seconds: i32,
+) -> Result<String, ClipKittyError> {
+    if range.end_unix <= range.start_unix {
+        return Err(ClipKittyError::InvalidInput(
+            "activity range ends before it starts".into(),
+        ));
+    }
+    let bound = |unix: i64| {
+        Utc.timestamp_opt(unix, 0).single().ok_or_else(|| {
+            ClipKittyError::InvalidInput(format!("timestamp {unix} is out of range"))
+        })
+    };
+    let rows = db.fetch_daily_activity(
+        bound(range.start_unix)?,
+        bound(range.end_unix)?,
+        utc_offset_seconds,
+    )?;
+    Ok(activity_csv(&rows))
+}
+
+/// Day × hour copy counts for the last `weeks` weeks up to and including the
+/// day holding `now`, with days and hours in local time.
+pub(crate) fn activity_heatmap(
+    db: &Database,
+    weeks: u32,
+    now: DateTime<Utc>,
+    utc_offset_seconds: i32,
+) -> Result<ActivityHeatmap, ClipKittyError> {
+    if weeks == 0 {
+        return Err(ClipKittyError::InvalidInput(
+            "a heatmap needs at least one week".into(),
+        ));
+    }
+    let day_count = weeks.min(MAX_HEATMAP_WEEKS) * 7;
+    let offset = chrono::Duration::seconds(utc_offset_seconds.into());
+    let today = (now + offset).date_naive();
+    let first_day = today - Days::new(u64::from(day_count - 1));
+    let start = Utc.from_utc_datetime(&first_day.and_time(chrono::NaiveTime::MIN)) - offset;
+
+    let mut cells: HashMap<NaiveDate, Vec<u32>> = HashMap::new();
+    for (date, hour, count) in db.fetch_hourly_activity(start, utc_offset_seconds)? {
+        let (Ok(date), Some(hour)) = (date.parse::<NaiveDate>(), usize::try_from(hour).ok()) else {
+            continue;
+        };
+        if hour < 24 {
+            cells.entry(date).or_insert_with(|| vec![0; 24])[hour] = count;
+        }
+    }
+
+    let days: Vec<HeatmapDay> = first_day
+        .iter_days()
+        .take(day_count as usize)
+        .map(|date| HeatmapDay {
+            date: date.to_string(),
+            hour_counts: cells.remove(&date).unwrap_or_else(|| vec![0; 24]),
+        })
+        .collect();
+    let max_count = days
+        .iter()
+        .flat_map(|day| day.hour_counts.iter().copied())
+        .max()
+        .unwrap_or(0);
+    Ok(ActivityHeatmap { days, max_count })
+}
+
+/// Totals by content type and source app, daily copies and pastes for the
+/// last `STATS_DAYS` local days up to `now`, and the most pasted items.
+pub(crate) fn usage_stats(
+    db: &Database,
+    now: DateTime<Utc>,
+    utc_offset_seconds: i32,
+) -> Result<UsageStats, ClipKittyError> {
+    let (total_items, total_copies, total_pastes) = db.fetch_usage_totals()?;
+    let by_content_type = db
+        .fetch_content_type_usage()?
+        .into_iter()
+        .map(|row| ContentTypeUsage {
+            content_type: row.name.unwrap_or_default(),
+            item_count: row.item_count,
+            paste_count: row.paste_count,
+        })
+        .collect();
+    let by_source_app = db
+        .fetch_source_app_usage()?
+        .into_iter()
+        .map(|row| SourceAppUsage {
+            bundle_id: row.bundle_id,
+            name: row.name,
+            item_count: row.item_count,
+            paste_count: row.paste_count,
+        })
+        .collect();
+
+    let offset = chrono::Duration::seconds(utc_offset_seconds.into());
+    let today = (now + offset).date_naive();
+    let first_day = today - Days::new(u64::from(STATS_DAYS - 1));
+    let start = Utc.from_utc_datetime(&first_day.and_time(chrono::NaiveTime::MIN)) - offset;
+    let mut counts: HashMap<NaiveDate, (u64, u64)> = db
+        .fetch_daily_usage(start, utc_offset_seconds)?
+        .into_iter()
+        .filter_map(|(date, copies, pastes)| Some((date.parse().ok()?, (copies, pastes))))
+        .collect();
+    let by_day = first_day
+        .iter_days()
+        .take(STATS_DAYS as usize)
+        .map(|date| {
+            let (copy_count, paste_count) = counts.remove(&date).unwrap_or_default();
+            DailyUsage {
+                date: date.to_string(),
+                copy_count,
+                paste_count,
+            }
+        })
+        .collect();
+
+    let most_pasted = db
+        .fetch_most_pasted(MOST_PASTED_LIMIT)?
+        .into_iter()
+        .map(|row| ItemPasteStats {
+            item_id: row.item_id,
+            paste_count: row.paste_count,
+            last_pasted_at_unix: row.last_pasted_at_unix,
+        })
+        .collect();
+
+    Ok(UsageStats {
+        total_items,
+        total_copies,
+        total_pastes,
+        by_content_type,
+        by_source_app,
+        by_day,
+        most_pasted,
+    })
+}
+
+fn activity_csv(rows: &[DailyActivityRow]) -> String {
+    let mut csv = String::from(ACTIVITY_CSV_HEADER);
+    csv.push('\n');
+    for row in rows {
+        let fields = [
+            row.day.as_str(),
+            row.activity.as_str(),
+            row.app.as_deref().unwrap_or_default(),
+            row.content_type.as_str(),
+        ];
+        for field in fields {
+            csv.push_str(&csv_field(field));
+            csv.push(',');
+        }
+        csv.push_str(&row.count.to_string());
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quote a field when it holds a delimiter, quote, or line break (RFC 4180).
+pub(crate) fn csv_field(value: &str) -> std::borrow::Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\"")).into()
+    } else {
+        value.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StoredItem;
+
+    #[test]
+    fn counts_copies_and_pastes_per_local_day() {
+        let db = Database::open_in_memory().unwrap();
+        // 23:30 UTC on 2024-03-01 is already 2024-03-02 at UTC+1.
+        let late = Utc
+            .with_ymd_and_hms(2024, 3, 1, 23, 30, 0)
+            .unwrap()
+            .timestamp();
+        let insert = |text: &str, app: Option<&str>, timestamp_unix: i64| {
+            let mut item = StoredItem::new_text(text.into(), None, app.map(str::to_string));
+            item.timestamp_unix = timestamp_unix;
+            db.insert_item(&item).unwrap()
+        };
+        let note = insert("first", Some("com.apple.Notes"), late);
+        insert("second", Some("com.apple.Notes"), late + 60);
+        insert("third", None, late - 12 * 3600);
+        db.insert_paste_event(
+            note,
+            "com.apple.Safari",
+            None,
+            Utc.timestamp_opt(late + 120, 0).unwrap(),
+        )
+        .unwrap();
+
+        let range = TimeRange {
+            start_unix: late - 24 * 3600,
+            end_unix: late + 24 * 3600,
+        };
+        assert_eq!(
+            export_activity_csv(&db, &range, 3600).unwrap(),
+            "date,activity,app,content_type,count\n\
+             2024-03-01,copy,,text,1\n\
+             2024-03-02,copy,com.apple.Notes,text,2\n\
+             2024-03-02,paste,com.apple.Safari,text,1\n"
+        );
+        assert!(export_activity_csv(
+            &db,
+            &TimeRange {
+                start_unix: late,
+                end_unix: late
+            },
+            0
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn heatmap_covers_whole_weeks_in_local_time() {
+        let db = Database::open_in_memory().unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 3, 14, 12, 0, 0).unwrap();
+        for timestamp_unix in [
+            // 09:15 local at UTC-5, twice.
+            Utc.with_ymd_and_hms(2024, 3, 14, 14, 15, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 3, 14, 14, 45, 0).unwrap(),
+            // 23:00 on the 7th in local time, though the 8th in UTC.
+            Utc.with_ymd_and_hms(2024, 3, 8, 4, 0, 0).unwrap(),
+            // Before the two-week window.
+            Utc.with_ymd_and_hms(2024, 2, 20, 12, 0, 0).unwrap(),
+        ]
+        .map(|at| at.timestamp())
+        {
+            let mut item = StoredItem::new_text(format!("copied {timestamp_unix}"), None, None);
+            item.timestamp_unix = timestamp_unix;
+            db.insert_item(&item).unwrap();
+        }
+
+        let heatmap = activity_heatmap(&db, 2, now, -5 * 3600).unwrap();
+        assert_eq!(heatmap.days.len(), 14);
+        assert_eq!(heatmap.days[0].date, "2024-03-01");
+        let today = heatmap.days.last().unwrap();
+        assert_eq!(today.date, "2024-03-14");
+        assert_eq!(today.hour_counts[9], 2);
+        let seventh = heatmap.days.iter().find(|day| day.date == "2024-03-07");
+        assert_eq!(seventh.unwrap().hour_counts[23], 1);
+        assert_eq!(
+            heatmap
+                .days
+                .iter()
+                .flat_map(|day| &day.hour_counts)
+                .sum::<u32>(),
+            3
+        );
+        assert_eq!(heatmap.max_count, 2);
+        assert!(activity_heatmap(&db, 0, now, 0).is_err());
+    }
+
+    #[test]
+    fn usage_stats_count_items_copies_and_pastes() {
+        let db = Database::open_in_memory().unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 3, 14, 12, 0, 0).unwrap();
+        let insert = |content: StoredItem| {
+            let row_id = db.insert_item(&content).unwrap();
+            db.insert_copy_event(row_id, now, None, None).unwrap();
+            (row_id, content.item_id)
+        };
+        let (snippet, snippet_id) = insert(StoredItem::new_text(
+            "kubectl get pods".into(),
+            Some("Terminal".into()),
+            Some("com.apple.Terminal".into()),
+        ));
+        let (note, note_id) = insert(StoredItem::new_text(
+            "groceries".into(),
+            None,
+            Some("com.apple.Notes".into()),
+        ));
+        insert(StoredItem::new_text("unpasted".into(), None, None));
+        db.insert_copy_event(snippet, now - chrono::Duration::days(1), None, None)
+            .unwrap();
+        // Before the 30-day window, but still in the totals.
+        db.insert_copy_event(snippet, now - chrono::Duration::days(40), None, None)
+            .unwrap();
+        let paste = |row_id: i64, at: DateTime<Utc>| {
+            db.insert_paste_event(row_id, "com.apple.dt.Xcode", None, at)
+                .unwrap();
+        };
+        paste(snippet, now - chrono::Duration::hours(2));
+        paste(snippet, now - chrono::Duration::hours(1));
+        paste(note, now);
+        // Trashed items and their events are left out everywhere.
+        let (trashed, _) = insert(StoredItem::new_text(
+            "deleted draft".into(),
+            None,
+            Some("com.apple.Notes".into()),
+        ));
+        paste(trashed, now);
+        paste(trashed, now);
+        paste(trashed, now);
+        db.trash_item(trashed, now).unwrap();
+
+        let stats = usage_stats(&db, now, 0).unwrap();
+        assert_eq!(
+            (stats.total_items, stats.total_copies, stats.total_pastes),
+            (3, 5, 3)
+        );
+        assert_eq!(
+            stats.by_content_type,
+            [ContentTypeUsage {
+                content_type: "text".into(),
+                item_count: 3,
+                paste_count: 3,
+            }]
+        );
+        let apps: Vec<_> = stats
+            .by_source_app
+            .iter()
+            .map(|app| (app.bundle_id.as_deref(), app.item_count, app.paste_count))
+            .collect();
+        assert_eq!(
+            apps,
+            [
+                (Some("com.apple.Terminal"), 1, 2),
+                (Some("com.apple.Notes"), 1, 1)
+            ]
+        );
+        assert_eq!(stats.by_day.len(), 30);
+        let today = stats.by_day.last().unwrap();
+        assert_eq!(
+            (today.date.as_str(), today.copy_count, today.paste_count),
+            ("2024-03-14", 3, 3)
+        );
+        assert_eq!(stats.by_day[28].copy_count, 1);
+        assert_eq!(
+            stats.most_pasted,
+            [
+                ItemPasteStats {
+                    item_id: snippet_id,
+                    paste_count: 2,
+                    last_pasted_at_unix: (now - chrono::Duration::hours(1)).timestamp(),
+                },
+                ItemPasteStats {
+                    item_id: note_id,
+                    paste_count: 1,
+                    last_pasted_at_unix: now.timestamp(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn quotes_fields_with_delimiters() {
+        assert_eq!(csv_field("Notes, Pro"), "\"Notes, Pro\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("plain"), "plain");
+    }
+}
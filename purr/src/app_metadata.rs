@@ -0,0 +1,179 @@
+//! Display metadata for source apps, keyed by bundle ID.
+//!
+//! The host app registers each app it sees once. Hydration then prefers the
+//! registered name over the one captured with the item, so items captured
+//! without a name get one and renamed apps show their current name. Entries
+//! outlive uninstalls, so old items keep their last known name.
+
+use crate::database::Database;
+use crate::interface::{AppMetadata, ClipKittyError, ItemMetadata, SourceAppSummary};
+
+pub(crate) fn register(db: &Database, apps: &[AppMetadata]) -> Result<(), ClipKittyError> {
+    if let Some(app) = apps
+        .iter()
+        .find(|app| app.bundle_id.trim().is_empty() || app.display_name.trim().is_empty())
+    {
+        return Err(ClipKittyError::InvalidInput(format!(
+            "app metadata for `{}` needs a bundle ID and a display name",
+            app.bundle_id
+        )));
+    }
+    Ok(db.upsert_app_metadata(apps)?)
+}
+
+pub(crate) fn list_source_apps(db: &Database) -> Result<Vec<SourceAppSummary>, ClipKittyError> {
+    Ok(db
+        .fetch_source_app_counts()?
+        .into_iter()
+        .map(|row| SourceAppSummary {
+            bundle_id: row.bundle_id,
+            name: row.name,
+            item_count: row.item_count,
+        })
+        .collect())
+}
+
+/// Fill `source_app` from registered app metadata wherever the item's bundle
+/// ID is known.
+pub(crate) fn hydrate_source_apps<'a>(
+    db: &Database,
+    items: impl IntoIterator<Item = &'a mut ItemMetadata>,
+) -> Result<(), ClipKittyError> {
+    let items: Vec<&mut ItemMetadata> = items
+        .into_iter()
+        .filter(|item| item.source_app_bundle_id.is_some())
+        .collect();
+    let bundle_ids: Vec<&str> = items
+        .iter()
+        .filter_map(|item| item.source_app_bundle_id.as_deref())
+        .collect();
+    let names = db.fetch_app_display_names(&bundle_ids)?;
+    if names.is_empty() {
+        return Ok(());
+    }
+    for item in items {
+        let name = item
+            .source_app_bundle_id
+            .as_ref()
+            .and_then(|bundle_id| names.get(bundle_id));
+        if let Some(name) = name {
+            item.source_app = Some(name.clone());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StoredItem;
+
+    fn app(bundle_id: &str, display_name: &str) -> AppMetadata {
+        AppMetadata {
+            bundle_id: bundle_id.into(),
+            display_name: display_name.into(),
+            icon_hash: None,
+            category: None,
+            is_installed: true,
+        }
+    }
+
+    #[test]
+    fn registered_names_fill_and_replace_captured_names() {
+        let db = Database::open_in_memory().unwrap();
+        let unnamed = StoredItem::new_text("a".into(), None, Some("com.example.notes".into()));
+        let renamed = StoredItem::new_text(
+            "b".into(),
+            Some("Old Name".into()),
+            Some("com.example.editor".into()),
+        );
+        let unknown = StoredItem::new_text(
+            "c".into(),
+            Some("Elsewhere".into()),
+            Some("com.example.other".into()),
+        );
+        let mut metadata: Vec<ItemMetadata> = [unnamed, renamed, unknown]
+            .iter()
+            .map(|item| item.to_clipboard_item().item_metadata)
+            .collect();
+
+        register(
+            &db,
+            &[
+                app("com.example.notes", "Notes"),
+                app("com.example.editor", "New Name"),
+            ],
+        )
+        .unwrap();
+        hydrate_source_apps(&db, &mut metadata).unwrap();
+        let names: Vec<_> = metadata
+            .iter()
+            .map(|item| item.source_app.as_deref())
+            .collect();
+        assert_eq!(names, [Some("Notes"), Some("New Name"), Some("Elsewhere")]);
+
+        // Uninstalling keeps the entry, and the name, around.
+        let mut gone = app("com.example.notes", "Notes");
+        gone.is_installed = false;
+        register(&db, &[gone.clone()]).unwrap();
+        assert_eq!(
+            db.fetch_app_metadata("com.example.notes").unwrap(),
+            Some(gone)
+        );
+        assert!(register(&db, &[app("com.example.blank", " ")]).is_err());
+    }
+
+    #[test]
+    fn source_apps_are_normalized_and_counted() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("clipboard.db");
+        {
+            let db = Database::open(&path).unwrap();
+            let insert = |text: &str, name: Option<&str>, bundle_id: Option<&str>| {
+                db.insert_item(&StoredItem::new_text(
+                    text.into(),
+                    name.map(str::to_string),
+                    bundle_id.map(str::to_string),
+                ))
+                .unwrap();
+            };
+            insert("a", Some("Editor"), Some("com.example.editor"));
+            insert("b", Some("Editor 2"), Some("com.example.editor"));
+            insert("c", Some("Terminal"), None);
+            insert("d", None, None);
+            // Rows written without `appId`, as by an older build.
+            db.get_conn()
+                .unwrap()
+                .execute_batch(
+                    "INSERT INTO items (item_id, contentType, contentHash, content, timestamp, sourceApp, sourceAppBundleId)
+                     VALUES ('legacy-1', 'text', 'h1', 'e', '2024-01-01 00:00:00', 'Notes', 'com.example.notes'),
+                            ('legacy-2', 'text', 'h2', 'f', '2024-01-01 00:00:01', 'Terminal', '')",
+                )
+                .unwrap();
+        }
+
+        let db = Database::open(&path).unwrap();
+        register(&db, &[app("com.example.notes", "Notes.app")]).unwrap();
+        let apps: Vec<_> = list_source_apps(&db)
+            .unwrap()
+            .into_iter()
+            .map(|app| (app.bundle_id, app.name, app.item_count))
+            .collect();
+        assert_eq!(
+            apps,
+            [
+                (
+                    Some("com.example.editor".to_string()),
+                    Some("Editor 2".to_string()),
+                    2
+                ),
+                (None, Some("Terminal".to_string()), 2),
+                (
+                    Some("com.example.notes".to_string()),
+                    Some("Notes.app".to_string()),
+                    1
+                ),
+            ]
+        );
+    }
+}
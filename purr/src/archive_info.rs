@@ -0,0 +1,371 @@
+//! Entry names of copied zip and tar archives.
+//!
+//! Only the entry table is read: a zip's central directory, or a tar's
+//! headers with the data between them skipped (or, for gzip-compressed tars,
+//! decompressed and discarded). Listings are bounded, so a huge archive
+//! yields its first entries and a truncation flag.
+
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::interface::{ArchiveListing, FilePreviewSnapshot, FileTextPreviewSnapshot};
+
+/// Most entry names listed per archive.
+const MAX_LISTED_ENTRIES: usize = 500;
+/// Largest zip central directory read into memory.
+const MAX_CENTRAL_DIRECTORY_BYTES: u64 = 4 * 1024 * 1024;
+/// Most bytes decompressed from a gzip-compressed tar before giving up on
+/// finding more entries.
+const MAX_GZIP_BYTES: u64 = 256 * 1024 * 1024;
+
+const ZIP_END_SIGNATURE: u32 = 0x0605_4B50;
+const ZIP_ENTRY_SIGNATURE: u32 = 0x0201_4B50;
+const ZIP64_END_LOCATOR_SIGNATURE: u32 = 0x0706_4B50;
+const ZIP64_END_SIGNATURE: u32 = 0x0606_4B50;
+const TAR_BLOCK: usize = 512;
+
+/// The entries of the zip or tar archive at `path`.
+pub(crate) fn probe(path: &Path) -> Option<ArchiveListing> {
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic).ok()?;
+    reader.seek(SeekFrom::Start(0)).ok()?;
+    match magic {
+        [b'P', b'K', 3 | 5, 4 | 6] => list_zip(&mut reader),
+        [0x1F, 0x8B, ..] => {
+            list_tar(&mut flate2::read::GzDecoder::new(reader).take(MAX_GZIP_BYTES))
+        }
+        _ => list_tar(&mut reader),
+    }
+}
+
+/// The listing as a text file preview, one entry per line.
+pub(crate) fn preview(listing: &ArchiveListing) -> FilePreviewSnapshot {
+    let sample = listing.entries.join("\n");
+    FilePreviewSnapshot::Text {
+        text: if listing.truncated {
+            FileTextPreviewSnapshot::Truncated { sample }
+        } else {
+            FileTextPreviewSnapshot::Complete { sample }
+        },
+    }
+}
+
+/// An archive's entry names as search text, one per line.
+pub(crate) fn index_text(entries: &[String]) -> Option<String> {
+    (!entries.is_empty()).then(|| entries.join("\n"))
+}
+
+/// Keep `name` unless it names a directory, whose files are listed anyway.
+fn push_entry(listing: &mut ArchiveListing, name: &str) {
+    let name = name.trim_start_matches("./");
+    if name.is_empty() || name.ends_with('/') {
+        return;
+    }
+    if listing.entries.len() == MAX_LISTED_ENTRIES {
+        listing.truncated = true;
+    } else {
+        listing.entries.push(name.to_string());
+    }
+}
+
+fn found(listing: ArchiveListing) -> Option<ArchiveListing> {
+    (!listing.entries.is_empty()).then_some(listing)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// zip
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn list_zip<R: Read + Seek>(reader: &mut R) -> Option<ArchiveListing> {
+    let (offset, size) = central_directory(reader)?;
+    if size > MAX_CENTRAL_DIRECTORY_BYTES {
+        return None;
+    }
+    reader.seek(SeekFrom::Start(offset)).ok()?;
+    let mut directory = vec![0u8; size as usize];
+    reader.read_exact(&mut directory).ok()?;
+
+    let mut listing = ArchiveListing {
+        entries: Vec::new(),
+        truncated: false,
+    };
+    let mut at = 0;
+    while le_u32(&directory, at) == Some(ZIP_ENTRY_SIGNATURE) {
+        let flags = le_u16(&directory, at + 8)?;
+        let name_len = le_u16(&directory, at + 28)? as usize;
+        let extra_len = le_u16(&directory, at + 30)? as usize;
+        let comment_len = le_u16(&directory, at + 32)? as usize;
+        let name = directory.get(at + 46..at + 46 + name_len)?;
+        // Names are UTF-8 when flagged, and CP437 by the spec otherwise,
+        // though most tools write UTF-8 regardless.
+        let name = if flags & 0x0800 != 0 {
+            String::from_utf8_lossy(name).into_owned()
+        } else {
+            String::from_utf8(name.to_vec())
+                .unwrap_or_else(|_| name.iter().map(|&byte| byte as char).collect())
+        };
+        push_entry(&mut listing, &name);
+        if listing.truncated {
+            break;
+        }
+        at += 46 + name_len + extra_len + comment_len;
+    }
+    found(listing)
+}
+
+/// Offset and size of the central directory, from the end of central
+/// directory record near the end of the file.
+fn central_directory<R: Read + Seek>(reader: &mut R) -> Option<(u64, u64)> {
+    let len = reader.seek(SeekFrom::End(0)).ok()?;
+    // The record is 22 bytes plus a comment of up to 64 KiB.
+    let tail_len = len.min(22 + 0xFFFF);
+    reader.seek(SeekFrom::Start(len - tail_len)).ok()?;
+    let mut tail = vec![0u8; tail_len as usize];
+    reader.read_exact(&mut tail).ok()?;
+    let end = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|&at| le_u32(&tail, at) == Some(ZIP_END_SIGNATURE))?;
+    let size = u64::from(le_u32(&tail, end + 12)?);
+    let offset = u64::from(le_u32(&tail, end + 16)?);
+    if offset != 0xFFFF_FFFF && size != 0xFFFF_FFFF {
+        return Some((offset, size));
+    }
+    // Zip64: a locator just before the record points at the larger record.
+    let locator = end.checked_sub(20)?;
+    (le_u32(&tail, locator) == Some(ZIP64_END_LOCATOR_SIGNATURE)).then_some(())?;
+    let record_offset = le_u64(&tail, locator + 8)?;
+    reader.seek(SeekFrom::Start(record_offset)).ok()?;
+    let mut record = [0u8; 56];
+    reader.read_exact(&mut record).ok()?;
+    (le_u32(&record, 0) == Some(ZIP64_END_SIGNATURE)).then_some(())?;
+    Some((le_u64(&record, 48)?, le_u64(&record, 40)?))
+}
+
+fn le_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn le_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le_u64(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(at..at + 8)?.try_into().ok()?))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// tar
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn list_tar<R: Read>(reader: &mut R) -> Option<ArchiveListing> {
+    let mut listing = ArchiveListing {
+        entries: Vec::new(),
+        truncated: false,
+    };
+    let mut header = [0u8; TAR_BLOCK];
+    let mut long_name: Option<String> = None;
+    while reader.read_exact(&mut header).is_ok() {
+        if header.iter().all(|&byte| byte == 0) {
+            break;
+        }
+        if !has_valid_checksum(&header) {
+            // Not a tar, or damaged past here.
+            break;
+        }
+        let size = octal(&header[124..136])?;
+        let kind = header[156];
+        if kind == b'L' {
+            // GNU long name: the next entry's name is this entry's data.
+            let mut name = vec![0u8; size.min(64 * 1024) as usize];
+            reader.read_exact(&mut name).ok()?;
+            skip(reader, padded(size) - name.len() as u64)?;
+            long_name = Some(c_string(&name));
+            continue;
+        }
+        let name = long_name.take().unwrap_or_else(|| {
+            let name = c_string(&header[..100]);
+            let prefix = c_string(&header[345..500]);
+            if &header[257..262] == b"ustar" && !prefix.is_empty() {
+                format!("{prefix}/{name}")
+            } else {
+                name
+            }
+        });
+        // Regular files only; links, directories, and pax headers are not
+        // entries anyone looks for.
+        if matches!(kind, 0 | b'0' | b'7') {
+            push_entry(&mut listing, &name);
+            if listing.truncated {
+                break;
+            }
+        }
+        skip(reader, padded(size))?;
+    }
+    found(listing)
+}
+
+/// Whether the header's checksum, counting its own field as spaces, matches.
+fn has_valid_checksum(header: &[u8; TAR_BLOCK]) -> bool {
+    let Some(stored) = octal(&header[148..156]) else {
+        return false;
+    };
+    let sum: u64 = header
+        .iter()
+        .enumerate()
+        .map(|(index, &byte)| {
+            if (148..156).contains(&index) {
+                u64::from(b' ')
+            } else {
+                u64::from(byte)
+            }
+        })
+        .sum();
+    sum == stored
+}
+
+fn octal(field: &[u8]) -> Option<u64> {
+    let digits = String::from_utf8_lossy(field);
+    let digits = digits.trim_matches(|c: char| c == '\0' || c == ' ');
+    if digits.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(digits, 8).ok()
+}
+
+fn c_string(field: &[u8]) -> String {
+    let end = field
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// `size` rounded up to whole blocks.
+fn padded(size: u64) -> u64 {
+    size.div_ceil(TAR_BLOCK as u64) * TAR_BLOCK as u64
+}
+
+fn skip<R: Read>(reader: &mut R, bytes: u64) -> Option<()> {
+    let skipped = std::io::copy(&mut reader.take(bytes), &mut std::io::sink()).ok()?;
+    (skipped == bytes).then_some(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    /// A stored-only zip of empty files named `names`.
+    pub(crate) fn zip(names: &[&str]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut directory = Vec::new();
+        for name in names {
+            let offset = data.len() as u32;
+            data.extend_from_slice(&0x0403_4B50u32.to_le_bytes());
+            data.extend_from_slice(&[0; 22]);
+            data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            data.extend_from_slice(&[0; 2]);
+            data.extend_from_slice(name.as_bytes());
+
+            directory.extend_from_slice(&ZIP_ENTRY_SIGNATURE.to_le_bytes());
+            directory.extend_from_slice(&[0; 4]);
+            directory.extend_from_slice(&0x0800u16.to_le_bytes());
+            directory.extend_from_slice(&[0; 18]);
+            directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            directory.extend_from_slice(&[0; 12]);
+            directory.extend_from_slice(&offset.to_le_bytes());
+            directory.extend_from_slice(name.as_bytes());
+        }
+        let directory_offset = data.len() as u32;
+        data.extend_from_slice(&directory);
+        data.extend_from_slice(&ZIP_END_SIGNATURE.to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&(names.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(names.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        data.extend_from_slice(&directory_offset.to_le_bytes());
+        data.extend_from_slice(&[0; 2]);
+        data
+    }
+
+    fn tar(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for (name, contents) in files {
+            let mut header = [0u8; TAR_BLOCK];
+            header[..name.len()].copy_from_slice(name.as_bytes());
+            header[100..107].copy_from_slice(b"0000644");
+            header[124..135].copy_from_slice(format!("{:011o}", contents.len()).as_bytes());
+            header[156] = if name.ends_with('/') { b'5' } else { b'0' };
+            header[257..263].copy_from_slice(b"ustar\0");
+            header[148..156].copy_from_slice(b"        ");
+            let sum: u32 = header.iter().map(|&byte| u32::from(byte)).sum();
+            header[148..155].copy_from_slice(format!("{sum:06o}\0").as_bytes());
+            data.extend_from_slice(&header);
+            data.extend_from_slice(contents);
+            data.resize(padded(data.len() as u64) as usize, 0);
+        }
+        data.extend_from_slice(&[0; TAR_BLOCK * 2]);
+        data
+    }
+
+    #[test]
+    fn lists_zip_entries_from_the_central_directory() {
+        let data = zip(&["invoices/", "invoices/invoice-0042.pdf", "readme.txt"]);
+        assert_eq!(
+            list_zip(&mut Cursor::new(data)),
+            Some(ArchiveListing {
+                entries: vec!["invoices/invoice-0042.pdf".into(), "readme.txt".into()],
+                truncated: false,
+            })
+        );
+    }
+
+    #[test]
+    fn lists_tar_entries_plain_and_gzipped() {
+        let data = tar(&[
+            ("site/", b""),
+            ("site/index.html", b"<html></html>"),
+            ("site/logo.svg", &[b'x'; 700]),
+        ]);
+        let expected = Some(ArchiveListing {
+            entries: vec!["site/index.html".into(), "site/logo.svg".into()],
+            truncated: false,
+        });
+        assert_eq!(list_tar(&mut Cursor::new(&data)), expected);
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(&data).unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("site.tar.gz");
+        std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+        assert_eq!(probe(&path), expected);
+    }
+
+    #[test]
+    fn long_listings_are_cut_short_and_flagged() {
+        let names: Vec<String> = (0..MAX_LISTED_ENTRIES + 3)
+            .map(|index| format!("photos/IMG_{index:04}.jpg"))
+            .collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let listing = list_zip(&mut Cursor::new(zip(&names))).unwrap();
+        assert_eq!(listing.entries.len(), MAX_LISTED_ENTRIES);
+        assert!(listing.truncated);
+        assert!(matches!(
+            preview(&listing),
+            FilePreviewSnapshot::Text {
+                text: FileTextPreviewSnapshot::Truncated { .. }
+            }
+        ));
+    }
+
+    #[test]
+    fn files_that_are_not_archives_list_nothing() {
+        assert_eq!(list_tar(&mut Cursor::new(vec![b'a'; 2048])), None);
+        assert_eq!(
+            list_zip(&mut Cursor::new(b"PK\x03\x04 truncated".to_vec())),
+            None
+        );
+    }
+}
@@ -0,0 +1,347 @@
+//! Single-file backups of the whole store.
+//!
+//! A backup archive holds `MAGIC`, the manifest length (u32, little endian),
+//! the manifest's SHA-256, the JSON manifest, and then the SQLite database as
+//! copied by the online backup API. The manifest records the database's size
+//! and SHA-256 and describes the search index at backup time. The index
+//! itself is not archived: restoring rebuilds it from the database.
+
+use crate::database::{Database, DatabaseError};
+use crate::encryption;
+use crate::indexer::{Indexer, INDEX_VERSION};
+use crate::interface::{BackupInfo, ClipKittyError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 8] = b"CKBACKUP";
+const BACKUP_FORMAT: &str = "clipkitty-backup";
+const BACKUP_VERSION: u64 = 1;
+/// Manifests take a few hundred bytes; a larger length means a damaged header.
+const MAX_MANIFEST_BYTES: u32 = 64 * 1024;
+const COPY_BUFFER_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupManifest {
+    format: String,
+    version: u64,
+    created_at_unix: i64,
+    item_count: u64,
+    database: ArchivedDatabase,
+    index: IndexManifest,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchivedDatabase {
+    bytes: u64,
+    sha256: String,
+}
+
+/// The search index when the backup was taken.
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexManifest {
+    version: String,
+    doc_count: u64,
+}
+
+/// Backups written by `create_backup` in `dir`, newest first. Files that
+/// aren't backups are skipped. Database hashes are only checked on restore,
+/// so listing stays cheap.
+#[uniffi::export]
+pub fn list_backups(dir: String) -> Result<Vec<BackupInfo>, ClipKittyError> {
+    list(Path::new(&dir))
+}
+
+pub(crate) fn list(directory: &Path) -> Result<Vec<BackupInfo>, ClipKittyError> {
+    let mut backups: Vec<BackupInfo> = std::fs::read_dir(directory)
+        .map_err(DatabaseError::from)?
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .filter_map(|entry| {
+            let path = entry.path();
+            let (manifest, _) = open_archive(&path).ok()?;
+            Some(backup_info(&path, &manifest))
+        })
+        .collect();
+    backups.sort_by(|a, b| {
+        b.created_at_unix
+            .cmp(&a.created_at_unix)
+            .then_with(|| b.path.cmp(&a.path))
+    });
+    Ok(backups)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Create
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Write a backup of `db` to `destination`, which must not exist. The archive
+/// is staged beside it and renamed into place, so `destination` never holds
+/// a partial backup.
+pub(crate) fn create(
+    db: &Database,
+    indexer: &Indexer,
+    destination: &Path,
+) -> Result<BackupInfo, ClipKittyError> {
+    if destination.exists() {
+        return Err(ClipKittyError::InvalidInput(format!(
+            "{} already exists",
+            destination.display()
+        )));
+    }
+    let snapshot = with_suffix(destination, ".snapshot");
+    let staged = with_suffix(destination, ".partial");
+    let written = write_archive(db, indexer, &snapshot, &staged).and_then(|manifest| {
+        std::fs::rename(&staged, destination).map_err(DatabaseError::from)?;
+        Ok(manifest)
+    });
+    let _ = std::fs::remove_file(&snapshot);
+    let _ = std::fs::remove_file(&staged);
+    Ok(backup_info(destination, &written?))
+}
+
+fn write_archive(
+    db: &Database,
+    indexer: &Indexer,
+    snapshot: &Path,
+    staged: &Path,
+) -> Result<BackupManifest, ClipKittyError> {
+    let _ = std::fs::remove_file(snapshot);
+    db.backup_to(snapshot)?;
+    let item_count = Database::verify_snapshot(snapshot)?;
+    let (sha256, bytes) = hash_file(snapshot)?;
+    // Count what the snapshot holds, not the last deferred commit.
+    indexer.flush()?;
+    let manifest = BackupManifest {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        created_at_unix: chrono::Utc::now().timestamp(),
+        item_count,
+        database: ArchivedDatabase { bytes, sha256 },
+        index: IndexManifest {
+            version: INDEX_VERSION.to_string(),
+            doc_count: indexer.num_docs(),
+        },
+    };
+    let encoded = serde_json::to_vec(&manifest)
+        .map_err(|error| ClipKittyError::Internal(error.to_string()))?;
+
+    let mut archive = BufWriter::new(File::create(staged).map_err(DatabaseError::from)?);
+    let header: [&[u8]; 4] = [
+        MAGIC,
+        &(encoded.len() as u32).to_le_bytes(),
+        &Sha256::digest(&encoded),
+        &encoded,
+    ];
+    for part in header {
+        archive.write_all(part).map_err(DatabaseError::from)?;
+    }
+    let mut database = File::open(snapshot).map_err(DatabaseError::from)?;
+    std::io::copy(&mut database, &mut archive).map_err(DatabaseError::from)?;
+    let archive = archive
+        .into_inner()
+        .map_err(|error| DatabaseError::from(error.into_error()))?;
+    archive.sync_all().map_err(DatabaseError::from)?;
+    Ok(manifest)
+}
+
+fn hash_file(path: &Path) -> Result<(String, u64), ClipKittyError> {
+    let mut hasher = Sha256::new();
+    let mut file = File::open(path).map_err(DatabaseError::from)?;
+    let bytes = std::io::copy(&mut file, &mut hasher).map_err(DatabaseError::from)?;
+    Ok((format!("{:x}", hasher.finalize()), bytes))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Restore
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Replace the contents of `db` with the backup at `source`. The database is
+/// extracted to a temporary file and checked against the manifest's hash and
+/// SQLite's `quick_check` before anything in `db` changes. The caller
+/// rebuilds the index.
+pub(crate) fn restore(db: &Database, source: &Path) -> Result<BackupInfo, ClipKittyError> {
+    let (manifest, mut archive) = open_archive(source)?;
+    let staged =
+        std::env::temp_dir().join(format!("clipkitty-restore-{}.db", uuid::Uuid::new_v4()));
+    let restored = restore_staged(db, source, &manifest, &mut archive, &staged);
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(with_suffix(&staged, suffix));
+    }
+    restored?;
+    Ok(backup_info(source, &manifest))
+}
+
+fn restore_staged(
+    db: &Database,
+    source: &Path,
+    manifest: &BackupManifest,
+    archive: &mut impl Read,
+    staged: &Path,
+) -> Result<(), ClipKittyError> {
+    let mut hasher = Sha256::new();
+    let mut bytes = 0u64;
+    let mut extracted = BufWriter::new(File::create(staged).map_err(DatabaseError::from)?);
+    let mut buffer = vec![0u8; COPY_BUFFER_BYTES];
+    loop {
+        let read = archive.read(&mut buffer).map_err(DatabaseError::from)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        extracted
+            .write_all(&buffer[..read])
+            .map_err(DatabaseError::from)?;
+        bytes += read as u64;
+    }
+    extracted.flush().map_err(DatabaseError::from)?;
+    drop(extracted);
+    if bytes != manifest.database.bytes
+        || format!("{:x}", hasher.finalize()) != manifest.database.sha256
+    {
+        return Err(damaged(source, "the database does not match its checksum"));
+    }
+    Database::verify_snapshot(staged)?;
+
+    // Opening migrates a backup taken by an older version to the current schema.
+    let restored = Database::open(staged)?;
+    if !encryption::shares_key(db, &restored)? {
+        return Err(ClipKittyError::InvalidInput(
+            "the backup is not encrypted with this store's key".into(),
+        ));
+    }
+    db.restore_from(&restored)?;
+    Ok(())
+}
+
+/// Read an archive's header and manifest, leaving the reader at the database.
+fn open_archive(path: &Path) -> Result<(BackupManifest, BufReader<File>), ClipKittyError> {
+    let mut reader = BufReader::new(File::open(path).map_err(DatabaseError::from)?);
+    let mut magic = [0u8; MAGIC.len()];
+    if reader.read_exact(&mut magic).is_err() || &magic != MAGIC {
+        return Err(ClipKittyError::InvalidInput(format!(
+            "{} is not a ClipKitty backup",
+            path.display()
+        )));
+    }
+    let mut length = [0u8; 4];
+    let mut expected_sha256 = [0u8; 32];
+    reader
+        .read_exact(&mut length)
+        .and_then(|()| reader.read_exact(&mut expected_sha256))
+        .map_err(|_| damaged(path, "the header is truncated"))?;
+    let length = u32::from_le_bytes(length);
+    if length > MAX_MANIFEST_BYTES {
+        return Err(damaged(path, "the manifest length is out of range"));
+    }
+    let mut encoded = vec![0u8; length as usize];
+    reader
+        .read_exact(&mut encoded)
+        .map_err(|_| damaged(path, "the manifest is truncated"))?;
+    if Sha256::digest(&encoded).as_slice() != expected_sha256 {
+        return Err(damaged(path, "the manifest does not match its checksum"));
+    }
+    let manifest: BackupManifest =
+        serde_json::from_slice(&encoded).map_err(|error| damaged(path, &error.to_string()))?;
+    if manifest.format != BACKUP_FORMAT || manifest.version > BACKUP_VERSION {
+        return Err(ClipKittyError::InvalidInput(format!(
+            "{} uses unsupported backup format {} v{}",
+            path.display(),
+            manifest.format,
+            manifest.version
+        )));
+    }
+    Ok((manifest, reader))
+}
+
+fn backup_info(path: &Path, manifest: &BackupManifest) -> BackupInfo {
+    BackupInfo {
+        path: path.display().to_string(),
+        created_at_unix: manifest.created_at_unix,
+        item_count: manifest.item_count,
+        bytes: std::fs::metadata(path).map_or(0, |metadata| metadata.len()),
+    }
+}
+
+fn damaged(path: &Path, reason: &str) -> ClipKittyError {
+    ClipKittyError::InvalidInput(format!("{} is damaged: {reason}", path.display()))
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    PathBuf::from(format!("{}{suffix}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StoredItem;
+
+    fn database_with(texts: &[&str], path: &Path) -> Database {
+        let db = Database::open(path).unwrap();
+        for text in texts {
+            db.insert_item(&StoredItem::new_text(text.to_string(), None, None))
+                .unwrap();
+        }
+        db
+    }
+
+    #[test]
+    fn backups_restore_into_another_database() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = database_with(&["first", "second"], &temp.path().join("source.db"));
+        let indexer = Indexer::new_in_memory().unwrap();
+        let archive = temp.path().join("history.ckbackup");
+
+        let created = create(&source, &indexer, &archive).unwrap();
+        assert_eq!(created.item_count, 2);
+        assert!(created.bytes > 0);
+        assert!(matches!(
+            create(&source, &indexer, &archive),
+            Err(ClipKittyError::InvalidInput(_))
+        ));
+
+        let target = database_with(&["only here"], &temp.path().join("target.db"));
+        let restored = restore(&target, &archive).unwrap();
+        assert_eq!(restored.item_count, 2);
+        assert_eq!(target.count_items().unwrap(), 2);
+    }
+
+    #[test]
+    fn damaged_backups_are_refused_before_anything_changes() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = database_with(&["keep me"], &temp.path().join("source.db"));
+        let indexer = Indexer::new_in_memory().unwrap();
+        let archive = temp.path().join("history.ckbackup");
+        create(&source, &indexer, &archive).unwrap();
+
+        let mut bytes = std::fs::read(&archive).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        let damaged = temp.path().join("damaged.ckbackup");
+        std::fs::write(&damaged, &bytes).unwrap();
+        let not_a_backup = temp.path().join("notes.txt");
+        std::fs::write(&not_a_backup, "just some notes").unwrap();
+
+        let target = database_with(&["one", "two", "three"], &temp.path().join("target.db"));
+        for path in [&damaged, &not_a_backup] {
+            assert!(matches!(
+                restore(&target, path),
+                Err(ClipKittyError::InvalidInput(_))
+            ));
+        }
+        assert_eq!(target.count_items().unwrap(), 3);
+
+        // Listing skips the text file but can't tell the damaged database
+        // apart without hashing it.
+        let listed: Vec<String> = list(temp.path())
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.path)
+            .collect();
+        assert_eq!(listed.len(), 2);
+        assert!(listed.contains(&archive.display().to_string()));
+        assert!(!listed.contains(&not_a_backup.display().to_string()));
+    }
+}
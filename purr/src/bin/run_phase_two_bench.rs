@@ -0,0 +1,117 @@
+//! Time Phase 2 bucket scoring alone over the synthetic benchmark corpus.
+//!
+//! `run_search_bench` measures whole searches; this isolates the per-candidate
+//! tokenize, fold, and match work that Phase 2 repeats on every keystroke.
+//!
+//! Usage:
+//!     cargo run --release --bin run_phase_two_bench
+//!     cargo run --release --bin run_phase_two_bench -- --query "erro retur"
+
+use clap::Parser;
+use purr::benchmark_fixture::{
+    default_synthetic_bench_db_path, ensure_synthetic_benchmark_fixture,
+};
+use purr::ranking::{
+    compute_bucket_score, prepare_document_for_ranking, FrecencyBand, PersonalizationBand,
+    PreparedQuery, QualityTier, ScoringContext, LARGE_DOC_THRESHOLD_BYTES,
+};
+use std::path::PathBuf;
+use std::time::Instant;
+
+#[derive(Parser, Debug)]
+struct Args {
+    #[arg(long)]
+    db: Option<PathBuf>,
+    #[arg(long, default_value_t = 3)]
+    warmup: usize,
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u64).range(1..))]
+    iterations: u64,
+    #[arg(long = "query")]
+    queries: Vec<String>,
+    /// Skip documents larger than this; the default keeps the small-document
+    /// pipeline, where every query word is matched against every token.
+    #[arg(long, default_value_t = LARGE_DOC_THRESHOLD_BYTES)]
+    max_doc_bytes: usize,
+}
+
+const DEFAULT_QUERIES: [&str; 5] = [
+    "function",
+    "functoin",
+    "error return",
+    "erro retur",
+    "fun ret",
+];
+
+fn load_documents(db_path: &PathBuf, max_doc_bytes: usize) -> Vec<String> {
+    let conn = rusqlite::Connection::open(db_path).expect("failed to open benchmark database");
+    let mut stmt = conn
+        .prepare("SELECT content FROM items WHERE contentType = 'text' AND length(content) <= ?1")
+        .expect("failed to query benchmark documents");
+    stmt.query_map([max_doc_bytes as i64], |row| row.get(0))
+        .expect("failed to read benchmark documents")
+        .collect::<Result<_, _>>()
+        .expect("failed to read benchmark documents")
+}
+
+fn score_all(documents: &[String], query: &PreparedQuery, now: i64) -> usize {
+    documents
+        .iter()
+        .filter(|content| {
+            let document = prepare_document_for_ranking(content);
+            let score = compute_bucket_score(&ScoringContext {
+                document: &document,
+                query,
+                prefix_preference: None,
+                timestamp: now,
+                now,
+                personalization: PersonalizationBand::None,
+                frecency: FrecencyBand::None,
+            });
+            score.quality_tier != QualityTier::NoMatch
+        })
+        .count()
+}
+
+fn main() {
+    let args = Args::parse();
+    let db_path = args.db.unwrap_or_else(default_synthetic_bench_db_path);
+    ensure_synthetic_benchmark_fixture(&db_path, false)
+        .expect("failed to prepare synthetic benchmark fixture");
+    let documents = load_documents(&db_path, args.max_doc_bytes);
+    let queries: Vec<String> = if args.queries.is_empty() {
+        DEFAULT_QUERIES
+            .iter()
+            .map(|query| query.to_string())
+            .collect()
+    } else {
+        args.queries
+    };
+    let now = chrono::Utc::now().timestamp();
+
+    println!(
+        "Scoring {} documents of at most {} bytes",
+        documents.len(),
+        args.max_doc_bytes
+    );
+    for query in &queries {
+        let prepared = PreparedQuery::new(query);
+        for _ in 0..args.warmup {
+            score_all(&documents, &prepared, now);
+        }
+        let mut samples_us = Vec::with_capacity(args.iterations as usize);
+        let mut matched = 0;
+        for _ in 0..args.iterations {
+            let start = Instant::now();
+            matched = score_all(&documents, &prepared, now);
+            samples_us.push(start.elapsed().as_micros());
+        }
+        samples_us.sort_unstable();
+        let mean = samples_us.iter().sum::<u128>() as f64 / samples_us.len() as f64;
+
+        println!();
+        println!("query: {query}");
+        println!("matched: {matched}");
+        println!("p50_us: {}", samples_us[samples_us.len() / 2]);
+        println!("mean_us: {mean:.1}");
+    }
+}
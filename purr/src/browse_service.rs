@@ -0,0 +1,211 @@
+//! Browse Service
+//!
+//! Specialised browsing surfaces that page through stored items without
+//! going through search, such as the photo grid and rule-resolved lists.
+
+use crate::app_metadata;
+use crate::database::{Database, ImageGridRow};
+use crate::interface::{
+    ClipKittyError, ImageGridItem, ImageGridPage, ItemMatch, ListPresentationProfile,
+    RowPresentation,
+};
+use crate::thumbnail::render_grid_thumbnail;
+
+/// Largest page `browse_images` will return.
+const MAX_IMAGE_GRID_PAGE: u64 = 500;
+
+/// Keyset position after the last item of a page: `"{row_id}:{timestamp}"`.
+struct ImageGridCursor {
+    row_id: i64,
+    timestamp: String,
+}
+
+impl ImageGridCursor {
+    fn encode(row: &ImageGridRow) -> String {
+        format!("{}:{}", row.row_id, row.timestamp)
+    }
+
+    fn decode(cursor: &str) -> Result<Self, ClipKittyError> {
+        let invalid =
+            || ClipKittyError::InvalidInput(format!("invalid image grid cursor `{cursor}`"));
+        let (row_id, timestamp) = cursor.split_once(':').ok_or_else(invalid)?;
+        Ok(Self {
+            row_id: row_id.parse().map_err(|_| invalid())?,
+            timestamp: timestamp.to_string(),
+        })
+    }
+}
+
+/// Load baseline list rows, with tags, for already-resolved row IDs in order.
+pub(crate) fn baseline_matches(
+    db: &Database,
+    ids: &[i64],
+    presentation: ListPresentationProfile,
+) -> Result<Vec<ItemMatch>, ClipKittyError> {
+    let mut rows = db.fetch_row_metadata_by_ids(ids, presentation)?;
+    let item_ids: Vec<String> = rows
+        .iter()
+        .map(|row| row.item_metadata.item_id.clone())
+        .collect();
+    let mut tags_by_id = db.get_tags_for_item_ids(&item_ids)?;
+    let pinned = db.get_pinned_item_ids(&item_ids)?;
+    for row in &mut rows {
+        row.item_metadata.tags = tags_by_id
+            .remove(&row.item_metadata.item_id)
+            .unwrap_or_default();
+        row.item_metadata.is_pinned = pinned.contains(&row.item_metadata.item_id);
+    }
+    app_metadata::hydrate_source_apps(db, rows.iter_mut().map(|row| &mut row.item_metadata))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ItemMatch {
+            item_metadata: row.item_metadata,
+            presentation: RowPresentation::Baseline {
+                excerpt: row.baseline_excerpt,
+            },
+        })
+        .collect())
+}
+
+/// Page through image items newest first. Grid thumbnails and dimensions are
+/// computed on first sight and cached in `image_items`.
+pub(crate) fn browse_images(
+    db: &Database,
+    cursor: Option<String>,
+    limit: u64,
+) -> Result<ImageGridPage, ClipKittyError> {
+    let limit = limit.min(MAX_IMAGE_GRID_PAGE) as usize;
+    let cursor = cursor.as_deref().map(ImageGridCursor::decode).transpose()?;
+    let after = cursor
+        .as_ref()
+        .map(|cursor| (cursor.timestamp.as_str(), cursor.row_id));
+
+    // Fetch one extra row to learn whether another page exists.
+    let (mut rows, total_count) = db.fetch_image_grid_page(after, limit + 1)?;
+    let has_more = rows.len() > limit;
+    rows.truncate(limit);
+    let next_cursor = if has_more {
+        rows.last().map(ImageGridCursor::encode)
+    } else {
+        None
+    };
+
+    let items = rows
+        .into_iter()
+        .map(|row| fill_grid_metadata(db, row).map(grid_item))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ImageGridPage {
+        items,
+        total_count,
+        next_cursor,
+    })
+}
+
+fn fill_grid_metadata(
+    db: &Database,
+    mut row: ImageGridRow,
+) -> Result<ImageGridRow, ClipKittyError> {
+    if row.width.is_some() {
+        return Ok(row);
+    }
+    let Some(data) = db.fetch_image_data(row.row_id)? else {
+        return Ok(row);
+    };
+    let (grid_thumbnail, width, height) = match render_grid_thumbnail(&data) {
+        Some(grid) => (grid.thumbnail, grid.width, grid.height),
+        None => (None, 0, 0),
+    };
+    db.set_image_grid_metadata(row.row_id, grid_thumbnail.as_deref(), width, height)?;
+    row.grid_thumbnail = grid_thumbnail;
+    row.width = Some(width);
+    row.height = Some(height);
+    Ok(row)
+}
+
+fn grid_item(row: ImageGridRow) -> ImageGridItem {
+    // Zero marks an image Rust could not decode; report unknown dimensions.
+    let dimensions = match (row.width, row.height) {
+        (Some(width), Some(height)) if width > 0 && height > 0 => Some((width, height)),
+        _ => None,
+    };
+    ImageGridItem {
+        item_id: row.item_id,
+        timestamp_unix: row.timestamp_unix,
+        thumbnail: row.grid_thumbnail.or(row.thumbnail),
+        width: dimensions.map(|(width, _)| width),
+        height: dimensions.map(|(_, height)| height),
+        is_animated: row.is_animated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StoredItem;
+    use image::{Rgb, RgbImage};
+
+    fn png_bytes(width: u32, height: u32, shade: u8) -> Vec<u8> {
+        let image = RgbImage::from_pixel(width, height, Rgb([shade, shade, shade]));
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut bytes, image::ImageFormat::Png).unwrap();
+        bytes.into_inner()
+    }
+
+    fn insert_image(db: &Database, data: Vec<u8>, timestamp_unix: i64) -> i64 {
+        let mut item = StoredItem::new_image_with_thumbnail(data, Some(vec![9]), None, None, false);
+        item.timestamp_unix = timestamp_unix;
+        db.insert_item(&item).unwrap()
+    }
+
+    #[test]
+    fn pages_newest_first_with_dimensions() {
+        let db = Database::open_in_memory().unwrap();
+        for (offset, shade) in [10u8, 20, 30].iter().enumerate() {
+            insert_image(
+                &db,
+                png_bytes(40 + offset as u32, 20, *shade),
+                1_000 + offset as i64,
+            );
+        }
+        db.insert_item(&StoredItem::new_text("not an image".into(), None, None))
+            .unwrap();
+
+        let first = browse_images(&db, None, 2).unwrap();
+        assert_eq!(first.total_count, 3);
+        assert_eq!(first.items.len(), 2);
+        assert_eq!(first.items[0].width, Some(42));
+        assert_eq!(first.items[1].width, Some(41));
+        assert_eq!(first.items[0].height, Some(20));
+        assert!(first.items[0].thumbnail.as_ref().unwrap().len() > 1);
+
+        let second = browse_images(&db, first.next_cursor, 2).unwrap();
+        assert_eq!(second.items.len(), 1);
+        assert_eq!(second.items[0].width, Some(40));
+        assert_eq!(second.next_cursor, None);
+    }
+
+    #[test]
+    fn undecodable_images_fall_back_to_list_thumbnail() {
+        let db = Database::open_in_memory().unwrap();
+        insert_image(&db, b"heic-bytes".to_vec(), 1_000);
+
+        let page = browse_images(&db, None, 10).unwrap();
+        assert_eq!(page.items[0].width, None);
+        assert_eq!(page.items[0].thumbnail, Some(vec![9]));
+
+        // The failed decode is cached rather than retried.
+        let (rows, _) = db.fetch_image_grid_page(None, 10).unwrap();
+        assert_eq!(rows[0].width, Some(0));
+    }
+
+    #[test]
+    fn rejects_malformed_cursor() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(matches!(
+            browse_images(&db, Some("garbage".into()), 10),
+            Err(ClipKittyError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,601 @@
+//! Directory bundles for lossless export and import.
+//!
+//! A bundle is a directory holding `items.jsonl` (one item per line), an
+//! `attachments/` folder with every binary payload (image data, thumbnails,
+//! link preview images, file bookmarks and previews) stored once under its
+//! SHA-256, and `manifest.json` listing each file with its checksum. Items
+//! refer to attachments by relative path, so a bundle can be inspected by
+//! hand and an import restores binary content byte for byte.
+
+use crate::database::{Database, DatabaseError, ItemLinks};
+use crate::indexer::Indexer;
+use crate::interface::{
+    BundleExportSummary, ClipKittyError, ClipboardContent, FileEntry, FilePreviewSnapshot,
+    FileStatus, FileTextPreviewSnapshot, ItemTag, LinkMetadataState,
+};
+use crate::models::StoredItem;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+
+pub(crate) const BUNDLE_FORMAT: &str = "clipkitty-bundle";
+pub(crate) const BUNDLE_VERSION: u64 = 1;
+const ITEMS_FILE: &str = "items.jsonl";
+const MANIFEST_FILE: &str = "manifest.json";
+const ATTACHMENTS_DIR: &str = "attachments";
+/// Items loaded per database round trip; bounds memory for large histories.
+const BATCH_SIZE: usize = 200;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Export
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Write every item into a new bundle at `directory`, which must be missing
+/// or empty.
+pub(crate) fn export_bundle(
+    db: &Database,
+    directory: &Path,
+) -> Result<BundleExportSummary, ClipKittyError> {
+    let occupied = std::fs::read_dir(directory).is_ok_and(|mut entries| entries.next().is_some());
+    if occupied {
+        return Err(ClipKittyError::InvalidInput(format!(
+            "{} is not empty",
+            directory.display()
+        )));
+    }
+    std::fs::create_dir_all(directory.join(ATTACHMENTS_DIR)).map_err(DatabaseError::from)?;
+
+    let mut attachments = AttachmentWriter {
+        root: directory.to_path_buf(),
+        written: BTreeMap::new(),
+    };
+    let items_file = File::create(directory.join(ITEMS_FILE)).map_err(DatabaseError::from)?;
+    let mut items = HashingWriter::new(BufWriter::new(items_file));
+    let mut item_count = 0u64;
+
+    for ids in db.fetch_all_item_ids()?.chunks(BATCH_SIZE) {
+        let batch = db.fetch_items_by_ids(ids)?;
+        let item_ids: Vec<String> = batch.iter().map(|item| item.item_id.clone()).collect();
+        let tags = db.get_tags_for_item_ids(&item_ids)?;
+        let reminders = db.fetch_reminders_for_item_ids(&item_ids)?;
+        for item in &batch {
+            let item_tags = tags.get(&item.item_id).map(Vec::as_slice).unwrap_or(&[]);
+            let reminder_at_unix = reminders.get(&item.item_id).copied();
+            let line = encode_item(item, item_tags, reminder_at_unix, &mut attachments)?;
+            writeln!(items, "{line}").map_err(DatabaseError::from)?;
+            item_count += 1;
+        }
+    }
+    let (items_sha256, items_bytes) = items.finish()?;
+
+    let mut files = vec![json!({
+        "path": ITEMS_FILE,
+        "sha256": items_sha256,
+        "bytes": items_bytes,
+    })];
+    let mut total_bytes = items_bytes;
+    for (path, (sha256, bytes)) in &attachments.written {
+        files.push(json!({ "path": path, "sha256": sha256, "bytes": bytes }));
+        total_bytes += bytes;
+    }
+    let manifest = json!({
+        "format": BUNDLE_FORMAT,
+        "version": BUNDLE_VERSION,
+        "created_at_unix": chrono::Utc::now().timestamp(),
+        "item_count": item_count,
+        "files": files,
+    });
+    let manifest = serde_json::to_vec_pretty(&manifest)
+        .map_err(|error| ClipKittyError::Internal(error.to_string()))?;
+    std::fs::write(directory.join(MANIFEST_FILE), manifest).map_err(DatabaseError::from)?;
+
+    Ok(BundleExportSummary {
+        item_count,
+        attachment_count: attachments.written.len() as u64,
+        total_bytes,
+    })
+}
+
+/// Content-addressed attachment files, each written once.
+struct AttachmentWriter {
+    root: PathBuf,
+    /// Relative path -> (sha256, size).
+    written: BTreeMap<String, (String, u64)>,
+}
+
+impl AttachmentWriter {
+    fn attach(&mut self, bytes: &[u8], extension: &str) -> Result<String, ClipKittyError> {
+        let sha256 = StoredItem::hash_bytes(bytes);
+        let path = format!("{ATTACHMENTS_DIR}/{sha256}.{extension}");
+        if !self.written.contains_key(&path) {
+            std::fs::write(self.root.join(&path), bytes).map_err(DatabaseError::from)?;
+            self.written
+                .insert(path.clone(), (sha256, bytes.len() as u64));
+        }
+        Ok(path)
+    }
+
+    /// Images keep a real extension so the bundle opens in other tools.
+    fn attach_image(&mut self, bytes: &[u8]) -> Result<String, ClipKittyError> {
+        let extension = image::guess_format(bytes)
+            .ok()
+            .and_then(|format| format.extensions_str().first().copied())
+            .unwrap_or("bin");
+        self.attach(bytes, extension)
+    }
+}
+
+fn encode_item(
+    item: &StoredItem,
+    tags: &[ItemTag],
+    reminder_at_unix: Option<i64>,
+    attachments: &mut AttachmentWriter,
+) -> Result<Value, ClipKittyError> {
+    let content = match &item.content {
+        ClipboardContent::Text { value } => json!({ "type": "text", "value": value }),
+        ClipboardContent::Color { value } => json!({ "type": "color", "value": value }),
+        ClipboardContent::PhoneNumber { value } => json!({ "type": "phone", "value": value }),
+        ClipboardContent::PostalAddress { value } => {
+            json!({ "type": "address", "value": value })
+        }
+        ClipboardContent::Link {
+            url,
+            metadata_state,
+        } => {
+            let (title, description, image) = metadata_state.to_database_fields();
+            let image = image
+                .map(|image| attachments.attach_image(&image))
+                .transpose()?;
+            json!({
+                "type": "link",
+                "url": url,
+                "title": title,
+                "description": description,
+                "image": image,
+            })
+        }
+        ClipboardContent::Image {
+            data,
+            description,
+            is_animated,
+        } => json!({
+            "type": "image",
+            "description": description,
+            "is_animated": is_animated,
+            "data": attachments.attach_image(data)?,
+        }),
+        ClipboardContent::File {
+            display_name,
+            files,
+        } => {
+            let files = files
+                .iter()
+                .map(|file| encode_file_entry(file, attachments))
+                .collect::<Result<Vec<_>, _>>()?;
+            json!({ "type": "file", "display_name": display_name, "files": files })
+        }
+        ClipboardContent::RichText {
+            value,
+            rtf_data,
+            html,
+        } => {
+            let rtf = rtf_data
+                .as_ref()
+                .map(|rtf| attachments.attach(rtf, "rtf"))
+                .transpose()?;
+            json!({ "type": "rich_text", "value": value, "rtf": rtf, "html": html })
+        }
+        ClipboardContent::Markdown { value, source } => {
+            json!({ "type": "markdown", "value": value, "source": source })
+        }
+    };
+    let thumbnail = item
+        .thumbnail
+        .as_deref()
+        .map(|thumbnail| attachments.attach_image(thumbnail))
+        .transpose()?;
+    Ok(json!({
+        "item_id": item.item_id,
+        "external_id": item.external_id,
+        "content_hash": item.content_hash,
+        "timestamp_unix": item.timestamp_unix,
+        "source_app": item.source_app,
+        "source_app_bundle_id": item.source_app_bundle_id,
+        "color_rgba": item.color_rgba,
+        "thumbnail": thumbnail,
+        "tags": tags.iter().map(ItemTag::database_str).collect::<Vec<_>>(),
+        "reminder_at_unix": reminder_at_unix,
+        "content": content,
+    }))
+}
+
+fn encode_file_entry(
+    file: &FileEntry,
+    attachments: &mut AttachmentWriter,
+) -> Result<Value, ClipKittyError> {
+    let preview = match &file.preview {
+        FilePreviewSnapshot::Unavailable { reason } => {
+            json!({ "kind": "unavailable", "reason": reason.to_database_str() })
+        }
+        FilePreviewSnapshot::Text { text } => {
+            let (sample, truncated) = match text {
+                FileTextPreviewSnapshot::Complete { sample } => (sample, false),
+                FileTextPreviewSnapshot::Truncated { sample } => (sample, true),
+            };
+            json!({ "kind": "text", "sample": sample, "truncated": truncated })
+        }
+        FilePreviewSnapshot::Image { preview_data } => {
+            json!({ "kind": "image", "data": attachments.attach_image(preview_data)? })
+        }
+    };
+    let bookmark = if file.bookmark_data.is_empty() {
+        None
+    } else {
+        Some(attachments.attach(&file.bookmark_data, "bookmark")?)
+    };
+    Ok(json!({
+        "path": file.path,
+        "filename": file.filename,
+        "file_size": file.file_size,
+        "uti": file.uti,
+        "bookmark": bookmark,
+        "status": file.file_status.to_database_str(),
+        "preview": preview,
+    }))
+}
+
+/// Streams bytes through while computing their SHA-256.
+struct HashingWriter<W: Write> {
+    inner: W,
+    hasher: sha2::Sha256,
+    bytes: u64,
+}
+
+impl<W: Write> HashingWriter<W> {
+    fn new(inner: W) -> Self {
+        use sha2::Digest;
+        Self {
+            inner,
+            hasher: sha2::Sha256::new(),
+            bytes: 0,
+        }
+    }
+
+    fn finish(mut self) -> Result<(String, u64), ClipKittyError> {
+        use sha2::Digest;
+        self.inner.flush().map_err(DatabaseError::from)?;
+        Ok((format!("{:x}", self.hasher.finalize()), self.bytes))
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        use sha2::Digest;
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Import
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// A bundle whose manifest checksums have been verified.
+pub(crate) struct VerifiedBundle {
+    root: PathBuf,
+    /// Attachment paths listed in the manifest and matching their checksum.
+    attachments: HashSet<String>,
+}
+
+impl VerifiedBundle {
+    /// Read the manifest and check every listed file against its checksum.
+    pub(crate) fn open(directory: &Path) -> Result<Self, ClipKittyError> {
+        let manifest = std::fs::read(directory.join(MANIFEST_FILE)).map_err(|error| {
+            ClipKittyError::InvalidInput(format!(
+                "{} is not a bundle: {error}",
+                directory.display()
+            ))
+        })?;
+        let manifest: Value = serde_json::from_slice(&manifest).map_err(|error| {
+            ClipKittyError::DataInconsistency(format!("{MANIFEST_FILE}: {error}"))
+        })?;
+        if manifest["format"] != BUNDLE_FORMAT {
+            return Err(ClipKittyError::InvalidInput(format!(
+                "{} is not a {BUNDLE_FORMAT}",
+                directory.display()
+            )));
+        }
+        let version = manifest["version"].as_u64().unwrap_or(0);
+        if version == 0 || version > BUNDLE_VERSION {
+            return Err(ClipKittyError::InvalidInput(format!(
+                "unsupported bundle version {version}"
+            )));
+        }
+
+        let mut attachments = HashSet::new();
+        let mut saw_items = false;
+        for file in manifest["files"].as_array().into_iter().flatten() {
+            let (Some(path), Some(sha256)) = (file["path"].as_str(), file["sha256"].as_str())
+            else {
+                return Err(ClipKittyError::DataInconsistency(format!(
+                    "{MANIFEST_FILE}: malformed file entry"
+                )));
+            };
+            let bytes = std::fs::read(resolve(directory, path)?)
+                .map_err(|error| ClipKittyError::DataInconsistency(format!("{path}: {error}")))?;
+            if StoredItem::hash_bytes(&bytes) != sha256 {
+                return Err(ClipKittyError::DataInconsistency(format!(
+                    "{path}: checksum mismatch"
+                )));
+            }
+            if path == ITEMS_FILE {
+                saw_items = true;
+            } else {
+                attachments.insert(path.to_string());
+            }
+        }
+        if !saw_items {
+            return Err(ClipKittyError::DataInconsistency(format!(
+                "{MANIFEST_FILE} does not list {ITEMS_FILE}"
+            )));
+        }
+        Ok(Self {
+            root: directory.to_path_buf(),
+            attachments,
+        })
+    }
+
+    /// Decode each item in order, passing it to `visit` with its tags and
+    /// reminder time as links.
+    pub(crate) fn for_each_item(
+        &self,
+        mut visit: impl FnMut(StoredItem, ItemLinks) -> Result<(), ClipKittyError>,
+    ) -> Result<(), ClipKittyError> {
+        let items = File::open(self.root.join(ITEMS_FILE)).map_err(DatabaseError::from)?;
+        for (index, line) in BufReader::new(items).lines().enumerate() {
+            let line = line.map_err(DatabaseError::from)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let inconsistent = |message: String| {
+                ClipKittyError::DataInconsistency(format!(
+                    "{ITEMS_FILE} line {}: {message}",
+                    index + 1
+                ))
+            };
+            let value: Value =
+                serde_json::from_str(&line).map_err(|error| inconsistent(error.to_string()))?;
+            let (item, tags) = self.decode_item(&value).map_err(inconsistent)?;
+            let links = ItemLinks {
+                tags,
+                reminder_at_unix: value["reminder_at_unix"].as_i64(),
+                ..ItemLinks::default()
+            };
+            visit(item, links)?;
+        }
+        Ok(())
+    }
+
+    fn attachment(&self, value: &Value) -> Result<Option<Vec<u8>>, String> {
+        let Some(path) = value.as_str() else {
+            return Ok(None);
+        };
+        if !self.attachments.contains(path) {
+            return Err(format!("attachment `{path}` is not in the manifest"));
+        }
+        std::fs::read(self.root.join(path))
+            .map(Some)
+            .map_err(|error| format!("{path}: {error}"))
+    }
+
+    fn required_attachment(&self, value: &Value, key: &str) -> Result<Vec<u8>, String> {
+        self.attachment(&value[key])?
+            .ok_or_else(|| format!("missing `{key}` attachment"))
+    }
+
+    fn decode_item(&self, value: &Value) -> Result<(StoredItem, Vec<ItemTag>), String> {
+        let content = &value["content"];
+        let content = match string(content, "type")?.as_str() {
+            "text" => ClipboardContent::Text {
+                value: string(content, "value")?,
+            },
+            "color" => ClipboardContent::Color {
+                value: string(content, "value")?,
+            },
+            "phone" => ClipboardContent::PhoneNumber {
+                value: string(content, "value")?,
+            },
+            "address" => ClipboardContent::PostalAddress {
+                value: string(content, "value")?,
+            },
+            "link" => ClipboardContent::Link {
+                url: string(content, "url")?,
+                metadata_state: LinkMetadataState::from_database(
+                    content["title"].as_str(),
+                    content["description"].as_str(),
+                    self.attachment(&content["image"])?,
+                )?,
+            },
+            "image" => ClipboardContent::Image {
+                data: self.required_attachment(content, "data")?,
+                description: string(content, "description")?,
+                is_animated: content["is_animated"].as_bool().unwrap_or(false),
+            },
+            "file" => ClipboardContent::File {
+                display_name: string(content, "display_name")?,
+                files: content["files"]
+                    .as_array()
+                    .ok_or("missing `files`")?
+                    .iter()
+                    .map(|file| self.decode_file_entry(file))
+                    .collect::<Result<_, _>>()?,
+            },
+            "rich_text" => ClipboardContent::RichText {
+                value: string(content, "value")?,
+                rtf_data: self.attachment(&content["rtf"])?,
+                html: content["html"].as_str().map(String::from),
+            },
+            "markdown" => ClipboardContent::Markdown {
+                value: string(content, "value")?,
+                source: string(content, "source")?,
+            },
+            other => return Err(format!("unknown content type `{other}`")),
+        };
+        let tags = value["tags"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|tag| ItemTag::from_database_str(tag.as_str().unwrap_or_default()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let timestamp_unix = value["timestamp_unix"]
+            .as_i64()
+            .ok_or("missing `timestamp_unix`")?;
+        let item = StoredItem {
+            id: None,
+            item_id: string(value, "item_id")?,
+            external_id: value["external_id"]
+                .as_str()
+                .filter(|id| !id.is_empty())
+                .map_or_else(|| StoredItem::external_id_at(timestamp_unix), String::from),
+            content,
+            content_hash: string(value, "content_hash")?,
+            timestamp_unix,
+            source_app: value["source_app"].as_str().map(String::from),
+            source_app_bundle_id: value["source_app_bundle_id"].as_str().map(String::from),
+            thumbnail: self.attachment(&value["thumbnail"])?,
+            color_rgba: value["color_rgba"].as_u64().map(|rgba| rgba as u32),
+            ocr_text: None,
+            file_contents_text: None,
+        };
+        Ok((item, tags))
+    }
+
+    fn decode_file_entry(&self, value: &Value) -> Result<FileEntry, String> {
+        let preview = &value["preview"];
+        let preview = FilePreviewSnapshot::from_database(
+            preview["kind"].as_str().unwrap_or("unavailable"),
+            preview["reason"].as_str(),
+            preview["sample"].as_str().map(String::from),
+            self.attachment(&preview["data"])?,
+            preview["truncated"].as_bool().unwrap_or(false),
+        )?;
+        Ok(FileEntry {
+            path: string(value, "path")?,
+            filename: string(value, "filename")?,
+            file_size: value["file_size"].as_u64().unwrap_or(0),
+            uti: string(value, "uti")?,
+            bookmark_data: self.attachment(&value["bookmark"])?.unwrap_or_default(),
+            file_status: FileStatus::from_database_str(
+                value["status"].as_str().unwrap_or("available"),
+            ),
+            preview,
+        })
+    }
+}
+
+/// Insert a decoded item with its links and queue it for indexing. The item
+/// and its links are written together or not at all. The caller commits the
+/// index.
+pub(crate) fn insert_imported(
+    db: &Database,
+    indexer: &Indexer,
+    item: &mut StoredItem,
+    links: &ItemLinks,
+) -> Result<(), ClipKittyError> {
+    let row_id = db.insert_item_with_links(item, links)?;
+    item.id = Some(row_id);
+    let index_text = item
+        .file_index_text()
+        .unwrap_or_else(|| item.text_content().to_string());
+    indexer.add_document_with_row(
+        &item.item_id,
+        &index_text,
+        item.timestamp_unix,
+        &links.user_tags,
+        &Database::indexed_row(item),
+    )?;
+    Ok(())
+}
+
+/// Resolve a manifest path inside the bundle, refusing anything that could
+/// point outside it.
+fn resolve(root: &Path, relative: &str) -> Result<PathBuf, ClipKittyError> {
+    let path = Path::new(relative);
+    if !path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(ClipKittyError::DataInconsistency(format!(
+            "{MANIFEST_FILE}: `{relative}` leaves the bundle"
+        )));
+    }
+    Ok(root.join(path))
+}
+
+fn string(value: &Value, key: &str) -> Result<String, String> {
+    value[key]
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| format!("missing `{key}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exported_bundle() -> tempfile::TempDir {
+        let db = Database::open_in_memory().unwrap();
+        db.insert_item(&StoredItem::new_image_with_thumbnail(
+            vec![5; 64],
+            None,
+            None,
+            None,
+            false,
+        ))
+        .unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        export_bundle(&db, temp.path()).unwrap();
+        temp
+    }
+
+    #[test]
+    fn tampered_attachment_fails_verification() {
+        let temp = exported_bundle();
+        let attachment = std::fs::read_dir(temp.path().join(ATTACHMENTS_DIR))
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        std::fs::write(&attachment, [0u8; 64]).unwrap();
+
+        let Err(ClipKittyError::DataInconsistency(message)) = VerifiedBundle::open(temp.path())
+        else {
+            panic!("expected a checksum failure");
+        };
+        assert!(message.ends_with("checksum mismatch"));
+    }
+
+    #[test]
+    fn manifest_paths_cannot_leave_the_bundle() {
+        let temp = exported_bundle();
+        let manifest_path = temp.path().join(MANIFEST_FILE);
+        let mut manifest: Value =
+            serde_json::from_slice(&std::fs::read(&manifest_path).unwrap()).unwrap();
+        manifest["files"][1]["path"] = json!("../outside.bin");
+        std::fs::write(&manifest_path, manifest.to_string()).unwrap();
+
+        assert!(matches!(
+            VerifiedBundle::open(temp.path()),
+            Err(ClipKittyError::DataInconsistency(_))
+        ));
+    }
+}
@@ -1,5 +1,6 @@
 //! Search candidates and match contexts returned from Tantivy.
 
+use crate::ranking::TokenWordMatch;
 use std::sync::Arc;
 
 #[derive(Debug, Clone)]
@@ -76,38 +77,61 @@ impl ChunkMatchContext {
 pub enum SearchMatchContext {
     WholeItem(WholeItemMatchContext),
     Chunk(ChunkMatchContext),
+    /// Matched the text recognized in an image rather than its description;
+    /// the content is the recognized text.
+    RecognizedText(WholeItemMatchContext),
 }
 
 impl SearchMatchContext {
     pub fn content(&self) -> &str {
         match self {
-            Self::WholeItem(ctx) => ctx.content(),
+            Self::WholeItem(ctx) | Self::RecognizedText(ctx) => ctx.content(),
             Self::Chunk(ctx) => ctx.content(),
         }
     }
 
     pub fn parent_len(&self) -> usize {
         match self {
-            Self::WholeItem(ctx) => ctx.parent_len(),
+            Self::WholeItem(ctx) | Self::RecognizedText(ctx) => ctx.parent_len(),
             Self::Chunk(ctx) => ctx.parent_len(),
         }
     }
 
     pub fn chunk_range(&self) -> Option<(usize, usize)> {
         match self {
-            Self::WholeItem(_) => None,
+            Self::WholeItem(_) | Self::RecognizedText(_) => None,
             Self::Chunk(ctx) => Some((ctx.chunk_start(), ctx.chunk_end())),
         }
     }
 
     pub fn chunk_index(&self) -> Option<u32> {
         match self {
-            Self::WholeItem(_) => None,
+            Self::WholeItem(_) | Self::RecognizedText(_) => None,
             Self::Chunk(ctx) => Some(ctx.chunk_index()),
         }
     }
 }
 
+/// Token matches Phase 2 recorded while scoring a candidate's match content,
+/// for the query text it scored. Highlighting that content for the same
+/// query reuses them instead of tokenizing and matching it again.
+#[derive(Debug)]
+pub(crate) struct ScoredWordMatches {
+    query: Arc<str>,
+    tokens: Vec<TokenWordMatch>,
+}
+
+impl ScoredWordMatches {
+    pub(crate) fn new(query: Arc<str>, tokens: Vec<TokenWordMatch>) -> Self {
+        Self { query, tokens }
+    }
+
+    /// The recorded matches, if they were recorded for `query`.
+    pub(crate) fn for_query(&self, query: &str) -> Option<&[TokenWordMatch]> {
+        (*self.query == *query).then_some(self.tokens.as_slice())
+    }
+}
+
 /// Whether a candidate was fully scored in Phase 2 or only had Phase 1 recall.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScoringPhase {
@@ -126,6 +150,7 @@ pub struct SearchCandidate {
     pub(crate) phase_one_score: crate::search_admission::PhaseOneBlendedScore,
     match_context: SearchMatchContext,
     scoring_phase: ScoringPhase,
+    scored_matches: Option<Arc<ScoredWordMatches>>,
 }
 
 impl SearchCandidate {
@@ -141,6 +166,7 @@ impl SearchCandidate {
             phase_one_score,
             match_context,
             scoring_phase: ScoringPhase::PhaseOneOnly,
+            scored_matches: None,
         }
     }
 
@@ -152,6 +178,14 @@ impl SearchCandidate {
         self.scoring_phase = phase;
     }
 
+    pub(crate) fn scored_matches(&self) -> Option<&Arc<ScoredWordMatches>> {
+        self.scored_matches.as_ref()
+    }
+
+    pub(crate) fn set_scored_matches(&mut self, matches: ScoredWordMatches) {
+        self.scored_matches = Some(Arc::new(matches));
+    }
+
     pub fn word_match_count(&self) -> u32 {
         self.phase_one_score.word_match_count
     }
@@ -172,4 +206,8 @@ impl SearchCandidate {
     pub fn match_context(&self) -> &SearchMatchContext {
         &self.match_context
     }
+
+    pub fn matched_recognized_text(&self) -> bool {
+        matches!(self.match_context, SearchMatchContext::RecognizedText(_))
+    }
 }
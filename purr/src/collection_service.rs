@@ -0,0 +1,365 @@
+//! Collection Service
+//!
+//! Smart collections are persisted rule sets. Their membership is never stored:
+//! each browse or count re-resolves the rules against current history, so new
+//! items appear and deleted ones disappear without bookkeeping.
+
+use crate::browse_service::baseline_matches;
+use crate::database::Database;
+use crate::icon_service::detect_code_language;
+use crate::interface::{
+    ClipKittyError, ListPresentationProfile, SmartCollection, SmartCollectionPage,
+    SmartCollectionRule, SmartCollectionSummary,
+};
+
+pub(crate) fn create_collection(
+    db: &Database,
+    name: String,
+    rules: Vec<SmartCollectionRule>,
+) -> Result<SmartCollection, ClipKittyError> {
+    let collection = validated_collection(uuid::Uuid::new_v4().to_string(), name, rules)?;
+    db.insert_smart_collection(&collection)?;
+    Ok(collection)
+}
+
+pub(crate) fn update_collection(
+    db: &Database,
+    collection_id: String,
+    name: String,
+    rules: Vec<SmartCollectionRule>,
+) -> Result<SmartCollection, ClipKittyError> {
+    let collection = validated_collection(collection_id, name, rules)?;
+    if !db.update_smart_collection(&collection)? {
+        return Err(unknown_collection(&collection.collection_id));
+    }
+    Ok(collection)
+}
+
+pub(crate) fn delete_collection(db: &Database, collection_id: &str) -> Result<(), ClipKittyError> {
+    if !db.delete_smart_collection(collection_id)? {
+        return Err(unknown_collection(collection_id));
+    }
+    Ok(())
+}
+
+/// All collections in creation order, each with its current item count.
+/// Counts come from SQL without loading members, except for `ContainsCode`,
+/// which only Rust can evaluate.
+pub(crate) fn list_collections(
+    db: &Database,
+) -> Result<Vec<SmartCollectionSummary>, ClipKittyError> {
+    db.fetch_smart_collections()?
+        .into_iter()
+        .map(|collection| {
+            let item_count = if collection
+                .rules
+                .contains(&SmartCollectionRule::ContainsCode)
+            {
+                resolve_members(db, &collection.rules)?.len() as u64
+            } else {
+                db.count_smart_collection_candidates(&collection.rules)?
+            };
+            Ok(SmartCollectionSummary {
+                collection,
+                item_count,
+            })
+        })
+        .collect()
+}
+
+/// Resolve a collection and return one page of its members, newest first.
+pub(crate) fn browse_collection(
+    db: &Database,
+    collection_id: &str,
+    offset: u64,
+    limit: u64,
+    presentation: ListPresentationProfile,
+) -> Result<SmartCollectionPage, ClipKittyError> {
+    let collection = db
+        .fetch_smart_collections()?
+        .into_iter()
+        .find(|collection| collection.collection_id == collection_id)
+        .ok_or_else(|| unknown_collection(collection_id))?;
+
+    let members = resolve_members(db, &collection.rules)?;
+    let total_count = members.len() as u64;
+    let page: Vec<i64> = members
+        .into_iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+        .collect();
+
+    let matches = baseline_matches(db, &page, presentation)?;
+
+    Ok(SmartCollectionPage {
+        matches,
+        total_count,
+    })
+}
+
+/// Row IDs matching every rule, newest first. SQL narrows the candidates;
+/// rules SQLite cannot express are applied to the content prefix here.
+fn resolve_members(
+    db: &Database,
+    rules: &[SmartCollectionRule],
+) -> Result<Vec<i64>, ClipKittyError> {
+    let needs_code = rules.contains(&SmartCollectionRule::ContainsCode);
+    Ok(db
+        .fetch_smart_collection_candidates(rules)?
+        .into_iter()
+        .filter(|(_, prefix)| !needs_code || detect_code_language(prefix).is_some())
+        .map(|(id, _)| id)
+        .collect())
+}
+
+fn validated_collection(
+    collection_id: String,
+    name: String,
+    rules: Vec<SmartCollectionRule>,
+) -> Result<SmartCollection, ClipKittyError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(ClipKittyError::InvalidInput(
+            "smart collection name must not be empty".into(),
+        ));
+    }
+    if rules.is_empty() {
+        return Err(ClipKittyError::InvalidInput(
+            "smart collection needs at least one rule".into(),
+        ));
+    }
+    for rule in &rules {
+        let invalid = match rule {
+            SmartCollectionRule::SourceApps { bundle_ids } => {
+                bundle_ids.is_empty()
+                    || bundle_ids
+                        .iter()
+                        .any(|id| id.trim().is_empty() || id.contains('\n'))
+            }
+            SmartCollectionRule::ContainsText { text } => text.is_empty(),
+            _ => false,
+        };
+        if invalid {
+            return Err(ClipKittyError::InvalidInput(format!(
+                "invalid smart collection rule {rule:?}"
+            )));
+        }
+    }
+    Ok(SmartCollection {
+        collection_id,
+        name,
+        rules,
+    })
+}
+
+fn unknown_collection(collection_id: &str) -> ClipKittyError {
+    ClipKittyError::InvalidInput(format!("unknown smart collection `{collection_id}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::{ContentTypeFilter, ItemTag};
+    use crate::models::StoredItem;
+
+    fn insert_text(db: &Database, text: &str, bundle_id: Option<&str>, timestamp_unix: i64) -> i64 {
+        let mut item = StoredItem::new_text(
+            text.to_string(),
+            bundle_id.map(|_| "App".to_string()),
+            bundle_id.map(str::to_string),
+        );
+        item.timestamp_unix = timestamp_unix;
+        db.insert_item(&item).unwrap()
+    }
+
+    #[test]
+    fn membership_follows_rules_at_browse_time() {
+        let db = Database::open_in_memory().unwrap();
+        insert_text(
+            &db,
+            "https://example.com/work",
+            Some("com.tinyspeck.slackmacgap"),
+            1_000,
+        );
+        insert_text(
+            &db,
+            "https://example.com/home",
+            Some("com.apple.Safari"),
+            1_001,
+        );
+        insert_text(&db, "#ff0000", None, 1_002);
+
+        let links = create_collection(
+            &db,
+            "Links from work apps".into(),
+            vec![
+                SmartCollectionRule::ContentType {
+                    content_type: ContentTypeFilter::Links,
+                },
+                SmartCollectionRule::SourceApps {
+                    bundle_ids: vec!["com.tinyspeck.slackmacgap".into()],
+                },
+            ],
+        )
+        .unwrap();
+        let colors = create_collection(
+            &db,
+            "All colors".into(),
+            vec![SmartCollectionRule::ContentType {
+                content_type: ContentTypeFilter::Colors,
+            }],
+        )
+        .unwrap();
+
+        let summaries = list_collections(&db).unwrap();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].collection, links);
+        assert_eq!(summaries[0].item_count, 1);
+        assert_eq!(summaries[1].item_count, 1);
+
+        // A new item joins without any membership bookkeeping.
+        insert_text(&db, "#00ff00", None, 1_003);
+        let page = browse_collection(
+            &db,
+            &colors.collection_id,
+            0,
+            10,
+            ListPresentationProfile::CompactRow,
+        )
+        .unwrap();
+        assert_eq!(page.total_count, 2);
+        assert_eq!(page.matches.len(), 2);
+    }
+
+    #[test]
+    fn code_rule_filters_in_rust() {
+        let db = Database::open_in_memory().unwrap();
+        insert_text(
+            &db,
+            "pub fn main() {\n    // TODO: wire up\n    let mut x = 1;\n}",
+            None,
+            1_000,
+        );
+        insert_text(&db, "TODO: buy milk", None, 1_001);
+        let older = insert_text(
+            &db,
+            "def run(self):\n    # TODO\n    print(self.value)",
+            None,
+            999,
+        );
+        db.add_tag(older, ItemTag::Bookmark).unwrap();
+
+        let collection = create_collection(
+            &db,
+            "Code containing TODO".into(),
+            vec![
+                SmartCollectionRule::ContainsCode,
+                SmartCollectionRule::ContainsText {
+                    text: "todo".into(),
+                },
+            ],
+        )
+        .unwrap();
+
+        let page = browse_collection(
+            &db,
+            &collection.collection_id,
+            1,
+            10,
+            ListPresentationProfile::CompactRow,
+        )
+        .unwrap();
+        assert_eq!(page.total_count, 2);
+        assert_eq!(page.matches.len(), 1);
+        assert_eq!(page.matches[0].item_metadata.tags, vec![ItemTag::Bookmark]);
+    }
+
+    #[test]
+    fn text_rule_folds_case_and_accents_beyond_ascii() {
+        let db = Database::open_in_memory().unwrap();
+        insert_text(&db, "Grüße aus MÜNCHEN", None, 1_000);
+        insert_text(&db, "Ελληνικά ΚΕΊΜΕΝΟ", None, 1_001);
+        insert_text(&db, "Munchkin", None, 1_002);
+
+        let munich = create_collection(
+            &db,
+            "Munich".into(),
+            vec![SmartCollectionRule::ContainsText {
+                text: "münchen".into(),
+            }],
+        )
+        .unwrap();
+        let greek = create_collection(
+            &db,
+            "Greek".into(),
+            vec![SmartCollectionRule::ContainsText {
+                text: "κειμενο".into(),
+            }],
+        )
+        .unwrap();
+
+        let summaries = list_collections(&db).unwrap();
+        assert_eq!(summaries[0].item_count, 1);
+        assert_eq!(summaries[1].item_count, 1);
+        for collection in [munich, greek] {
+            let page = browse_collection(
+                &db,
+                &collection.collection_id,
+                0,
+                10,
+                ListPresentationProfile::CompactRow,
+            )
+            .unwrap();
+            assert_eq!(page.total_count, 1);
+        }
+    }
+
+    #[test]
+    fn update_and_delete_round_trip() {
+        let db = Database::open_in_memory().unwrap();
+        let collection = create_collection(
+            &db,
+            "Bookmarks".into(),
+            vec![SmartCollectionRule::Tagged {
+                tag: ItemTag::Bookmark,
+            }],
+        )
+        .unwrap();
+
+        let updated = update_collection(
+            &db,
+            collection.collection_id.clone(),
+            " Slack ".into(),
+            vec![SmartCollectionRule::SourceApps {
+                bundle_ids: vec!["com.a".into(), "com.b".into()],
+            }],
+        )
+        .unwrap();
+        assert_eq!(updated.name, "Slack");
+        assert_eq!(list_collections(&db).unwrap()[0].collection, updated);
+
+        delete_collection(&db, &collection.collection_id).unwrap();
+        assert!(list_collections(&db).unwrap().is_empty());
+        assert!(matches!(
+            delete_collection(&db, &collection.collection_id),
+            Err(ClipKittyError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_empty_rules_and_names() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(create_collection(&db, "Empty".into(), Vec::new()).is_err());
+        assert!(
+            create_collection(&db, "  ".into(), vec![SmartCollectionRule::ContainsCode]).is_err()
+        );
+        assert!(create_collection(
+            &db,
+            "Apps".into(),
+            vec![SmartCollectionRule::SourceApps {
+                bundle_ids: Vec::new()
+            }]
+        )
+        .is_err());
+    }
+}
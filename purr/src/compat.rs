@@ -0,0 +1,231 @@
+//! Interface versioning for hosts built against an older core.
+//!
+//! UniFFI writes records field by field in declaration order, so a Swift
+//! client generated from an older build reads a record correctly only while
+//! its fields keep their order and types. The rules that keep old clients
+//! working:
+//!
+//! - New data goes in new records or new calls; fields of an exported record
+//!   are never reordered, retyped, or removed.
+//! - A new variant of an enum the core hands to clients (`ClipboardContent`,
+//!   `IconType`, `ItemIcon`, ...) breaks every older client: its generated
+//!   reader rejects the unknown variant and its Swift switches are
+//!   exhaustive. The same goes for a new method on a callback interface the
+//!   client implements. Variants of enums clients only send are safe.
+//! - A call that changes shape keeps its old name here, forwarding to the
+//!   new one and marked `#[deprecated]`, until no supported client uses it.
+//! - Any change to the exported surface bumps `INTERFACE_VERSION`. A
+//!   breaking change, including a new returned enum variant, also raises
+//!   `MIN_SUPPORTED_INTERFACE_VERSION` to the new version.
+//!
+//! The tests below pin the wire layout of the records every client reads.
+
+use crate::interface::Capabilities;
+
+/// Version of the exported interface.
+/// History: 1 = `interface_version`, 2 = `capabilities`, 3 = `search_structured`,
+/// 4 = `item_entities`, 5 = `parse_query`, 6 = `collect_matches`,
+/// 7 = phone number and postal address content, 8 = `list_topics`,
+/// 9 = `items_containing_file`, 10 = `format_structured`, 11 = Markdown content,
+/// 12 = `matched_files`, 13 = `transform`, 14 = `file_kind`, 15 = media info,
+/// 16 = `merge_items`, 17 = `run_integrity_check` and `compact`, 18 = document info,
+/// 19 = email info, 20 = `verify_index`, 21 = archive listings, 22 = item events
+/// on `StoreObserver`, 23 = `set_repeat_copy_window`, 24 = `open_read_only`,
+/// 25 = `last_transforms`, 26 = `create_backup`, `restore_backup`, and
+/// `list_backups`, 27 = `diff_results`, 28 = palettes on `ItemIcon::ColorSwatch`, 29 = tag and
+/// kind filters in `ParsedQuery`, 30 = errors from `start_search`,
+/// `format_excerpt`, and the store setters
+pub const INTERFACE_VERSION: u32 = 30;
+/// Oldest client interface version this core still serves correctly.
+/// Breaking versions: 7 and 11 added `ClipboardContent` and `IconType`
+/// variants, 22 added methods to `StoreObserver`, 28 changed the fields of
+/// `ItemIcon::ColorSwatch`, 29 added fields to `ParsedQuery`, and 30 made
+/// `start_search`, `format_excerpt`, and the store setters throw.
+pub const MIN_SUPPORTED_INTERFACE_VERSION: u32 = 30;
+
+/// Version of the interface this core exports. A host compares it with the
+/// version its bindings were generated for before calling anything newer.
+#[uniffi::export]
+pub fn interface_version() -> u32 {
+    INTERFACE_VERSION
+}
+
+/// Whether a client generated for `client_version` can use this core: it is
+/// not older than the oldest supported version, nor newer than this core.
+#[uniffi::export]
+pub fn is_interface_version_supported(client_version: u32) -> bool {
+    (MIN_SUPPORTED_INTERFACE_VERSION..=INTERFACE_VERSION).contains(&client_version)
+}
+
+/// Optional subsystems in this build, so a host hides what is missing
+/// instead of guessing from versions.
+#[uniffi::export]
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        encryption: true,
+        embeddings: false,
+        ocr_indexing: true,
+        sync: cfg!(feature = "sync"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::{
+        HighlightKind, IconType, ItemIcon, ItemMetadata, ItemTag, MatchedExcerpt, Snippet,
+        Utf16HighlightRange,
+    };
+    use crate::UniFfiTag;
+
+    fn wire<T: uniffi::Lower<UniFfiTag>>(value: T) -> Vec<u8> {
+        let mut buf = Vec::new();
+        T::write(value, &mut buf);
+        buf
+    }
+
+    /// Expected bytes, written the way UniFFI serializes each field type.
+    #[derive(Default)]
+    struct Layout(Vec<u8>);
+
+    impl Layout {
+        fn string(mut self, value: &str) -> Self {
+            self.0.extend((value.len() as i32).to_be_bytes());
+            self.0.extend(value.as_bytes());
+            self
+        }
+
+        fn none(mut self) -> Self {
+            self.0.push(0);
+            self
+        }
+
+        fn some_string(mut self, value: &str) -> Self {
+            self.0.push(1);
+            self.string(value)
+        }
+
+        fn bool(mut self, value: bool) -> Self {
+            self.0.push(value as u8);
+            self
+        }
+
+        fn i64(mut self, value: i64) -> Self {
+            self.0.extend(value.to_be_bytes());
+            self
+        }
+
+        fn u64(mut self, value: u64) -> Self {
+            self.0.extend(value.to_be_bytes());
+            self
+        }
+
+        /// A sequence's length, or an enum's 1-based variant index.
+        fn i32(mut self, value: i32) -> Self {
+            self.0.extend(value.to_be_bytes());
+            self
+        }
+    }
+
+    #[test]
+    fn interface_version_bounds_supported_clients() {
+        assert_eq!(interface_version(), INTERFACE_VERSION);
+        assert!(is_interface_version_supported(INTERFACE_VERSION));
+        assert!(is_interface_version_supported(
+            MIN_SUPPORTED_INTERFACE_VERSION
+        ));
+        assert!(!is_interface_version_supported(INTERFACE_VERSION + 1));
+        assert!(!is_interface_version_supported(
+            MIN_SUPPORTED_INTERFACE_VERSION - 1
+        ));
+    }
+
+    /// Versions that old clients cannot read; see the rules above.
+    const BREAKING_INTERFACE_VERSIONS: &[u32] = &[7, 11, 22, 28, 29, 30];
+
+    #[test]
+    fn breaking_versions_raise_the_minimum() {
+        for &version in BREAKING_INTERFACE_VERSIONS {
+            assert!(version <= INTERFACE_VERSION);
+            assert!(
+                MIN_SUPPORTED_INTERFACE_VERSION >= version,
+                "version {version} breaks older clients but they are still supported"
+            );
+            assert!(!is_interface_version_supported(version - 1));
+        }
+    }
+
+    #[test]
+    fn capabilities_follow_the_build() {
+        let capabilities = capabilities();
+        assert_eq!(capabilities.sync, cfg!(feature = "sync"));
+        assert!(capabilities.encryption && capabilities.ocr_indexing);
+        assert!(!capabilities.embeddings);
+    }
+
+    #[test]
+    fn item_metadata_wire_shape_is_frozen() {
+        let metadata = ItemMetadata {
+            item_id: "id".into(),
+            icon: ItemIcon::Symbol {
+                icon_type: IconType::Link,
+            },
+            source_app: Some("Safari".into()),
+            source_app_bundle_id: None,
+            timestamp_unix: 1_700_000_000,
+            tags: vec![ItemTag::Bookmark],
+            is_pinned: true,
+        };
+        let expected = Layout::default()
+            .string("id")
+            .i32(1) // ItemIcon::Symbol
+            .i32(2) // IconType::Link
+            .some_string("Safari")
+            .none()
+            .i64(1_700_000_000)
+            .i32(1)
+            .i32(1) // ItemTag::Bookmark
+            .bool(true);
+        assert_eq!(wire(metadata), expected.0);
+    }
+
+    #[test]
+    fn matched_excerpt_wire_shape_is_frozen() {
+        let excerpt = MatchedExcerpt {
+            text: "hi".into(),
+            highlights: vec![Utf16HighlightRange {
+                utf16_start: 0,
+                utf16_end: 2,
+                kind: HighlightKind::Prefix,
+            }],
+            line_number: 3,
+        };
+        let expected = Layout::default()
+            .string("hi")
+            .i32(1)
+            .u64(0)
+            .u64(2)
+            .i32(2) // HighlightKind::Prefix
+            .u64(3);
+        assert_eq!(wire(excerpt), expected.0);
+    }
+
+    #[test]
+    fn snippet_wire_shape_is_frozen() {
+        let snippet = Snippet {
+            snippet_id: "s".into(),
+            title: "t".into(),
+            body: "b {x}".into(),
+            placeholders: vec!["x".into()],
+            updated_at_unix: 7,
+        };
+        let expected = Layout::default()
+            .string("s")
+            .string("t")
+            .string("b {x}")
+            .i32(1)
+            .string("x")
+            .i64(7);
+        assert_eq!(wire(snippet), expected.0);
+    }
+}
@@ -0,0 +1,602 @@
+//! Context Service
+//!
+//! Suggestions for the app (and document) ClipKitty was invoked from, driven by
+//! the local paste-event log, the per-app priors that personalize search
+//! ranking, and the frequently used items drawn from copy and paste events.
+//! None of it syncs; it only describes how this Mac's apps are used.
+
+use crate::browse_service::baseline_matches;
+use crate::database::{ContextCandidateRow, Database, PasteEventRow, UsageEventRow};
+use crate::icon_service::detect_code_language;
+use crate::indexer::ItemPersonalization;
+use crate::interface::{
+    AppPastePriors, ClipKittyError, ItemMatch, ListPresentationProfile, PasteTally, RecentPaste,
+};
+use crate::ranking::{FrecencyBand, PersonalizationBand};
+use chrono::{Duration, Utc};
+use std::collections::HashMap;
+
+/// Most recent items always considered, in addition to anything pasted into the app.
+const RECENT_CANDIDATES: usize = 500;
+/// Paste events per app consulted when scoring.
+const PASTE_HISTORY_LIMIT: usize = 2_000;
+/// Half-life of a paste's influence, in days.
+const PASTE_HALF_LIFE_DAYS: f64 = 14.0;
+
+const APP_PASTE_WEIGHT: f64 = 2.0;
+const DOCUMENT_PASTE_WEIGHT: f64 = 3.0;
+const SAME_SOURCE_APP_WEIGHT: f64 = 1.0;
+const KIND_AFFINITY_WEIGHT: f64 = 2.0;
+
+/// A paste counts for this many copies in `frequently_used`.
+const FREQUENT_PASTE_WEIGHT: f64 = 2.0;
+/// Copies and pastes an item needs within the window to count as frequent.
+const MIN_FREQUENT_USES: u32 = 2;
+const MAX_FREQUENT_WINDOW_DAYS: u32 = 3_650;
+
+/// Decayed paste count for `FrecencyBand::Reused`: two pastes within about
+/// six days.
+const REUSED_FRECENCY: f64 = 1.5;
+/// Decayed paste count for `FrecencyBand::Frequent`.
+const FREQUENT_FRECENCY: f64 = 5.0;
+
+/// Pastes into an app before its priors influence search ranking.
+const MIN_PRIOR_PASTES: u64 = 5;
+/// Combined content-type and source-app share for `PersonalizationBand::Preferred`.
+const PREFERRED_AFFINITY: f64 = 1.0;
+/// Combined share for `PersonalizationBand::Familiar`.
+const FAMILIAR_AFFINITY: f64 = 0.4;
+
+const CONTENT_TYPE_DIMENSION: &str = "content_type";
+const SOURCE_APP_DIMENSION: &str = "source_app";
+
+/// Log that an item was pasted into `target_bundle_id`.
+pub(crate) fn record_paste(
+    db: &Database,
+    row_id: i64,
+    target_bundle_id: &str,
+    document_hint: Option<&str>,
+) -> Result<(), ClipKittyError> {
+    let target_bundle_id = target_bundle_id.trim();
+    if target_bundle_id.is_empty() {
+        return Err(ClipKittyError::InvalidInput(
+            "paste target bundle id must not be empty".into(),
+        ));
+    }
+    let document_hint = document_hint.map(str::trim).filter(|hint| !hint.is_empty());
+    db.insert_paste_event(row_id, target_bundle_id, document_hint, Utc::now())?;
+    Ok(())
+}
+
+/// Items by when they were last pasted, which copy timestamps cannot tell.
+pub(crate) fn recent_pastes(
+    db: &Database,
+    limit: usize,
+) -> Result<Vec<RecentPaste>, ClipKittyError> {
+    let pastes = db.fetch_recent_pastes(limit)?;
+    let ids: Vec<i64> = pastes.iter().map(|paste| paste.row_id).collect();
+    let mut matches: HashMap<String, ItemMatch> =
+        baseline_matches(db, &ids, ListPresentationProfile::CompactRow)?
+            .into_iter()
+            .map(|item_match| (item_match.item_metadata.item_id.clone(), item_match))
+            .collect();
+    // An item deleted between the two queries simply drops out.
+    Ok(pastes
+        .into_iter()
+        .filter_map(|paste| {
+            Some(RecentPaste {
+                item_match: matches.remove(&paste.item_id)?,
+                pasted_at_unix: paste.pasted_at_unix,
+                target_bundle_id: paste.target_bundle_id,
+                paste_count: paste.paste_count,
+            })
+        })
+        .collect())
+}
+
+/// Rank history for the frontmost app. Items pasted into the same app or
+/// document rise, as do items copied from it and items of the content kind
+/// (code, prose, links, ...) the app usually receives.
+pub(crate) fn suggest_for_context(
+    db: &Database,
+    bundle_id: &str,
+    document_hint: Option<&str>,
+    limit: usize,
+) -> Result<Vec<ItemMatch>, ClipKittyError> {
+    let candidates = db.fetch_context_candidates(bundle_id, RECENT_CANDIDATES)?;
+    let pastes = db.fetch_paste_events_for_app(bundle_id, PASTE_HISTORY_LIMIT)?;
+    let now = Utc::now().timestamp();
+
+    let kinds: HashMap<i64, ContentKind> = candidates
+        .iter()
+        .map(|candidate| (candidate.row_id, ContentKind::of(candidate)))
+        .collect();
+    let profile = AppProfile::from_pastes(&pastes, &kinds, document_hint, now);
+
+    let mut scored: Vec<(f64, i64, i64)> = candidates
+        .iter()
+        .map(|candidate| {
+            let score = profile.score(candidate, kinds[&candidate.row_id], bundle_id, now);
+            (score, candidate.timestamp_unix, candidate.row_id)
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then(b.1.cmp(&a.1)).then(b.2.cmp(&a.2)));
+
+    let ids: Vec<i64> = scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, _, row_id)| row_id)
+        .collect();
+    baseline_matches(db, &ids, ListPresentationProfile::CompactRow)
+}
+
+/// Items copied or pasted at least twice in the last `window_days`, ranked
+/// by decayed use: each copy counts once and each paste twice, with older
+/// uses fading.
+pub(crate) fn frequently_used(
+    db: &Database,
+    limit: usize,
+    window_days: u32,
+) -> Result<Vec<ItemMatch>, ClipKittyError> {
+    if window_days == 0 {
+        return Err(ClipKittyError::InvalidInput(
+            "frequently used window must be at least one day".into(),
+        ));
+    }
+    let now = Utc::now();
+    let since = now - Duration::days(window_days.min(MAX_FREQUENT_WINDOW_DAYS).into());
+    let events = db.fetch_usage_events_since(since)?;
+    let ids: Vec<i64> = frequency_ranking(&events, now.timestamp())
+        .into_iter()
+        .take(limit)
+        .collect();
+    baseline_matches(db, &ids, ListPresentationProfile::CompactRow)
+}
+
+/// Row IDs with enough uses, highest decayed score first, then most
+/// recently used.
+fn frequency_ranking(events: &[UsageEventRow], now: i64) -> Vec<i64> {
+    // Per item: decayed score, use count, last use.
+    let mut usage: HashMap<i64, (f64, u32, i64)> = HashMap::new();
+    for event in events {
+        let weight = if event.is_paste {
+            FREQUENT_PASTE_WEIGHT
+        } else {
+            1.0
+        };
+        let entry = usage.entry(event.row_id).or_insert((0.0, 0, i64::MIN));
+        entry.0 += weight * decay(now - event.at_unix);
+        entry.1 += 1;
+        entry.2 = entry.2.max(event.at_unix);
+    }
+    let mut ranked: Vec<(i64, (f64, u32, i64))> = usage
+        .into_iter()
+        .filter(|(_, (_, uses, _))| *uses >= MIN_FREQUENT_USES)
+        .collect();
+    ranked.sort_by(|(a_id, a), (b_id, b)| {
+        b.0.total_cmp(&a.0).then(b.2.cmp(&a.2)).then(b_id.cmp(a_id))
+    });
+    ranked.into_iter().map(|(row_id, _)| row_id).collect()
+}
+
+/// Coarse content kind; text splits into code and prose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ContentKind {
+    Code,
+    Prose,
+    Other(&'static str),
+}
+
+impl ContentKind {
+    fn of(candidate: &ContextCandidateRow) -> Self {
+        match candidate.db_type.as_str() {
+            "text" if detect_code_language(&candidate.content_prefix).is_some() => Self::Code,
+            "text" | "rich_text" | "markdown" => Self::Prose,
+            "link" => Self::Other("link"),
+            "image" => Self::Other("image"),
+            "color" => Self::Other("color"),
+            "phone" => Self::Other("phone"),
+            "address" => Self::Other("address"),
+            "file" => Self::Other("file"),
+            _ => Self::Other("other"),
+        }
+    }
+}
+
+/// What the log says about one target app.
+struct AppProfile {
+    /// Decayed paste weight per item.
+    item_weight: HashMap<i64, f64>,
+    /// Items pasted into the current document.
+    document_items: HashMap<i64, f64>,
+    /// Share of pastes per content kind, summing to 1.
+    kind_share: HashMap<ContentKind, f64>,
+}
+
+impl AppProfile {
+    fn from_pastes(
+        pastes: &[PasteEventRow],
+        kinds: &HashMap<i64, ContentKind>,
+        document_hint: Option<&str>,
+        now: i64,
+    ) -> Self {
+        let mut item_weight: HashMap<i64, f64> = HashMap::new();
+        let mut document_items: HashMap<i64, f64> = HashMap::new();
+        let mut kind_weight: HashMap<ContentKind, f64> = HashMap::new();
+        for paste in pastes {
+            let weight = decay(now - paste.pasted_at_unix);
+            *item_weight.entry(paste.row_id).or_default() += weight;
+            if document_hint.is_some() && paste.document_hint.as_deref() == document_hint {
+                *document_items.entry(paste.row_id).or_default() += weight;
+            }
+            if let Some(kind) = kinds.get(&paste.row_id) {
+                *kind_weight.entry(*kind).or_default() += weight;
+            }
+        }
+
+        let total: f64 = kind_weight.values().sum();
+        let kind_share = if total > 0.0 {
+            kind_weight
+                .into_iter()
+                .map(|(kind, weight)| (kind, weight / total))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        Self {
+            item_weight,
+            document_items,
+            kind_share,
+        }
+    }
+
+    fn score(
+        &self,
+        candidate: &ContextCandidateRow,
+        kind: ContentKind,
+        bundle_id: &str,
+        now: i64,
+    ) -> f64 {
+        let age_hours = (now - candidate.timestamp_unix).max(0) as f64 / 3600.0;
+        let recency = 1.0 / (1.0 + age_hours / 24.0);
+        let app_pastes = self
+            .item_weight
+            .get(&candidate.row_id)
+            .map_or(0.0, |weight| weight.ln_1p());
+        let document_pastes = self
+            .document_items
+            .get(&candidate.row_id)
+            .map_or(0.0, |weight| weight.ln_1p());
+        let same_source = if candidate.source_app_bundle_id.as_deref() == Some(bundle_id) {
+            SAME_SOURCE_APP_WEIGHT
+        } else {
+            0.0
+        };
+        let kind_affinity = self.kind_share.get(&kind).copied().unwrap_or(0.0);
+
+        recency
+            + APP_PASTE_WEIGHT * app_pastes
+            + DOCUMENT_PASTE_WEIGHT * document_pastes
+            + same_source
+            + KIND_AFFINITY_WEIGHT * kind_affinity
+    }
+}
+
+/// Learned content-type and source-app counts for the current paste target.
+pub(crate) struct PersonalizationPriors {
+    total: u64,
+    content_types: HashMap<String, u64>,
+    source_apps: HashMap<String, u64>,
+}
+
+impl PersonalizationPriors {
+    /// Priors for `target_bundle_id`, or None until enough pastes were seen.
+    pub(crate) fn load(
+        db: &Database,
+        target_bundle_id: &str,
+    ) -> Result<Option<Self>, ClipKittyError> {
+        let priors = list_priors(db, Some(target_bundle_id))?;
+        let Some(priors) = priors.into_iter().next() else {
+            return Ok(None);
+        };
+        if priors.total_pastes < MIN_PRIOR_PASTES {
+            return Ok(None);
+        }
+        let counts = |tallies: Vec<PasteTally>| {
+            tallies
+                .into_iter()
+                .map(|tally| (tally.value, tally.paste_count))
+                .collect()
+        };
+        Ok(Some(Self {
+            total: priors.total_pastes,
+            content_types: counts(priors.content_types),
+            source_apps: counts(priors.source_apps),
+        }))
+    }
+
+    pub(crate) fn band(
+        &self,
+        db_type: &str,
+        source_app_bundle_id: Option<&str>,
+    ) -> PersonalizationBand {
+        let share = |count: Option<&u64>| count.copied().unwrap_or(0) as f64 / self.total as f64;
+        let affinity = share(self.content_types.get(db_type))
+            + share(source_app_bundle_id.and_then(|id| self.source_apps.get(id)));
+        if affinity >= PREFERRED_AFFINITY {
+            PersonalizationBand::Preferred
+        } else if affinity >= FAMILIAR_AFFINITY {
+            PersonalizationBand::Familiar
+        } else {
+            PersonalizationBand::None
+        }
+    }
+
+    /// Bands for recalled item IDs. Personalization is best-effort: a failed
+    /// lookup ranks every candidate without it.
+    pub(crate) fn resolve(
+        &self,
+        db: &Database,
+        item_ids: &[&str],
+    ) -> HashMap<String, PersonalizationBand> {
+        db.fetch_type_and_source_by_string_ids(item_ids)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(item_id, db_type, source)| {
+                let band = self.band(&db_type, source.as_deref());
+                (item_id, band)
+            })
+            .collect()
+    }
+}
+
+/// Personalization for recalled item IDs: paste-target bands when `priors`
+/// are loaded, and paste frecency, where each paste counts for less as it
+/// ages (`PASTE_HALF_LIFE_DAYS`). Best-effort like `PersonalizationPriors::resolve`.
+pub(crate) fn resolve_personalization(
+    db: &Database,
+    priors: Option<&PersonalizationPriors>,
+    item_ids: &[&str],
+    now_unix: i64,
+) -> HashMap<String, ItemPersonalization> {
+    let mut resolved: HashMap<String, ItemPersonalization> = HashMap::new();
+    if let Some(priors) = priors {
+        for (item_id, band) in priors.resolve(db, item_ids) {
+            resolved.entry(item_id).or_default().target = band;
+        }
+    }
+    let mut frecency: HashMap<String, f64> = HashMap::new();
+    for (item_id, pasted_at_unix) in db
+        .fetch_paste_times_by_string_ids(item_ids)
+        .unwrap_or_default()
+    {
+        *frecency.entry(item_id).or_default() += decay(now_unix - pasted_at_unix);
+    }
+    for (item_id, score) in frecency {
+        let band = if score >= FREQUENT_FRECENCY {
+            FrecencyBand::Frequent
+        } else if score >= REUSED_FRECENCY {
+            FrecencyBand::Reused
+        } else {
+            continue;
+        };
+        resolved.entry(item_id).or_default().frecency = band;
+    }
+    resolved
+}
+
+/// Learned priors per target app, optionally for a single app.
+pub(crate) fn list_priors(
+    db: &Database,
+    target_bundle_id: Option<&str>,
+) -> Result<Vec<AppPastePriors>, ClipKittyError> {
+    let mut priors: Vec<AppPastePriors> = Vec::new();
+    for (target, dimension, value, paste_count) in db.fetch_paste_priors(target_bundle_id)? {
+        if priors.last().map(|p| &p.target_bundle_id) != Some(&target) {
+            priors.push(AppPastePriors {
+                target_bundle_id: target,
+                total_pastes: 0,
+                content_types: Vec::new(),
+                source_apps: Vec::new(),
+            });
+        }
+        let Some(entry) = priors.last_mut() else {
+            continue;
+        };
+        let tally = PasteTally { value, paste_count };
+        match dimension.as_str() {
+            CONTENT_TYPE_DIMENSION => {
+                entry.total_pastes += paste_count;
+                entry.content_types.push(tally);
+            }
+            SOURCE_APP_DIMENSION => entry.source_apps.push(tally),
+            _ => {}
+        }
+    }
+    Ok(priors)
+}
+
+/// Forget learned priors for one app, or every app when `target_bundle_id` is None.
+pub(crate) fn reset_priors(
+    db: &Database,
+    target_bundle_id: Option<&str>,
+) -> Result<(), ClipKittyError> {
+    db.clear_paste_priors(target_bundle_id)?;
+    Ok(())
+}
+
+fn decay(age_seconds: i64) -> f64 {
+    let age_days = age_seconds.max(0) as f64 / 86_400.0;
+    0.5_f64.powf(age_days / PASTE_HALF_LIFE_DAYS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StoredItem;
+
+    const XCODE: &str = "com.apple.dt.Xcode";
+    const MAIL: &str = "com.apple.mail";
+
+    fn insert_text(db: &Database, text: &str, age_seconds: i64) -> (i64, String) {
+        let mut item = StoredItem::new_text(text.to_string(), None, None);
+        item.timestamp_unix = Utc::now().timestamp() - age_seconds;
+        let row_id = db.insert_item(&item).unwrap();
+        (row_id, item.item_id)
+    }
+
+    fn suggested_ids(db: &Database, bundle_id: &str, hint: Option<&str>) -> Vec<String> {
+        suggest_for_context(db, bundle_id, hint, 10)
+            .unwrap()
+            .into_iter()
+            .map(|item| item.item_metadata.item_id)
+            .collect()
+    }
+
+    #[test]
+    fn frequent_items_weigh_pastes_above_copies_and_fade() {
+        let db = Database::open_in_memory().unwrap();
+        let (copied, copied_id) = insert_text(&db, "git status", 60);
+        let (pasted, pasted_id) = insert_text(&db, "cargo test", 60);
+        let (stale, _) = insert_text(&db, "old command", 60);
+        let (once, _) = insert_text(&db, "one-off", 60);
+        let now = Utc::now();
+        for _ in 0..3 {
+            db.insert_copy_event(copied, now, None, None).unwrap();
+        }
+        db.insert_copy_event(pasted, now, None, None).unwrap();
+        record_paste(&db, pasted, XCODE, None).unwrap();
+        record_paste(&db, pasted, XCODE, None).unwrap();
+        for _ in 0..5 {
+            db.insert_copy_event(stale, now - Duration::days(60), None, None)
+                .unwrap();
+        }
+        db.insert_copy_event(once, now, None, None).unwrap();
+
+        let ids: Vec<String> = frequently_used(&db, 10, 30)
+            .unwrap()
+            .into_iter()
+            .map(|item| item.item_metadata.item_id)
+            .collect();
+        assert_eq!(ids, [pasted_id, copied_id]);
+        assert_eq!(frequently_used(&db, 10, 90).unwrap().len(), 3);
+        assert!(frequently_used(&db, 10, 0).is_err());
+    }
+
+    #[test]
+    fn app_kind_preference_reorders_history() {
+        let db = Database::open_in_memory().unwrap();
+        let (old_code, old_code_id) =
+            insert_text(&db, "pub fn old() -> u32 {\n    let mut x = 1;\n}", 600);
+        let (old_prose, old_prose_id) =
+            insert_text(&db, "Thanks for the update, see you tomorrow.", 500);
+        let (_, new_code_id) =
+            insert_text(&db, "impl Foo {\n    pub fn new() -> Self { Self }\n}", 20);
+        let (_, new_prose_id) = insert_text(&db, "Could you send the slides before lunch?", 10);
+
+        record_paste(&db, old_code, XCODE, None).unwrap();
+        record_paste(&db, old_prose, MAIL, None).unwrap();
+
+        let xcode = suggested_ids(&db, XCODE, None);
+        assert_eq!(xcode[0], old_code_id);
+        assert_eq!(xcode[1], new_code_id);
+
+        let mail = suggested_ids(&db, MAIL, None);
+        assert_eq!(mail[0], old_prose_id);
+        assert_eq!(mail[1], new_prose_id);
+    }
+
+    #[test]
+    fn document_hint_outranks_other_pastes_into_the_app() {
+        let db = Database::open_in_memory().unwrap();
+        let (first, first_id) = insert_text(&db, "Quarterly numbers look good.", 300);
+        let (second, second_id) = insert_text(&db, "Let's sync on Friday.", 200);
+        record_paste(&db, first, MAIL, Some("Re: Q3 report")).unwrap();
+        record_paste(&db, second, MAIL, Some("Team offsite")).unwrap();
+
+        let ids = suggested_ids(&db, MAIL, Some("Re: Q3 report"));
+        assert_eq!(ids[0], first_id);
+        let ids = suggested_ids(&db, MAIL, Some("Team offsite"));
+        assert_eq!(ids[0], second_id);
+    }
+
+    #[test]
+    fn priors_learn_types_and_sources_per_app() {
+        let db = Database::open_in_memory().unwrap();
+        let mut from_terminal = StoredItem::new_text(
+            "cargo test --workspace".into(),
+            Some("Terminal".into()),
+            Some("com.apple.Terminal".into()),
+        );
+        from_terminal.timestamp_unix = Utc::now().timestamp();
+        let terminal_row = db.insert_item(&from_terminal).unwrap();
+        let (link_row, _) = insert_text(&db, "https://example.com", 10);
+
+        for _ in 0..4 {
+            record_paste(&db, terminal_row, XCODE, None).unwrap();
+        }
+        assert!(PersonalizationPriors::load(&db, XCODE).unwrap().is_none());
+        record_paste(&db, link_row, XCODE, None).unwrap();
+        record_paste(&db, link_row, MAIL, None).unwrap();
+
+        let xcode = &list_priors(&db, Some(XCODE)).unwrap()[0];
+        assert_eq!(xcode.total_pastes, 5);
+        assert_eq!(
+            xcode.content_types[0],
+            PasteTally {
+                value: "text".into(),
+                paste_count: 4
+            }
+        );
+        assert_eq!(xcode.source_apps[0].value, "com.apple.Terminal");
+
+        let priors = PersonalizationPriors::load(&db, XCODE).unwrap().unwrap();
+        assert_eq!(
+            priors.band("text", Some("com.apple.Terminal")),
+            PersonalizationBand::Preferred
+        );
+        assert_eq!(priors.band("text", None), PersonalizationBand::Familiar);
+        assert_eq!(priors.band("image", None), PersonalizationBand::None);
+
+        reset_priors(&db, Some(XCODE)).unwrap();
+        let remaining = list_priors(&db, None).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].target_bundle_id, MAIL);
+    }
+
+    #[test]
+    fn recent_pastes_order_by_last_paste_not_copy_time() {
+        let db = Database::open_in_memory().unwrap();
+        let (old_copy, old_copy_id) = insert_text(&db, "copied long ago", 86_400);
+        let (new_copy, new_copy_id) = insert_text(&db, "copied just now", 1);
+        insert_text(&db, "never pasted", 0);
+
+        let earlier = Utc::now() - chrono::Duration::minutes(5);
+        db.insert_paste_event(new_copy, MAIL, None, earlier)
+            .unwrap();
+        db.insert_paste_event(old_copy, MAIL, None, earlier)
+            .unwrap();
+        db.insert_paste_event(old_copy, XCODE, None, Utc::now())
+            .unwrap();
+
+        let pastes = recent_pastes(&db, 10).unwrap();
+        assert_eq!(pastes.len(), 2);
+        assert_eq!(pastes[0].item_match.item_metadata.item_id, old_copy_id);
+        assert_eq!(pastes[0].target_bundle_id, XCODE);
+        assert_eq!(pastes[0].paste_count, 2);
+        assert_eq!(pastes[1].item_match.item_metadata.item_id, new_copy_id);
+        assert_eq!(pastes[1].paste_count, 1);
+
+        assert_eq!(recent_pastes(&db, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn unknown_app_falls_back_to_recency() {
+        let db = Database::open_in_memory().unwrap();
+        insert_text(&db, "older", 100);
+        let (_, newest_id) = insert_text(&db, "newest", 1);
+        assert_eq!(suggested_ids(&db, "com.example.new", None)[0], newest_id);
+        assert!(record_paste(&db, 1, "  ", None).is_err());
+    }
+}
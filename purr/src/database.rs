@@ -3,18 +3,31 @@
 //! Normalized schema: base `items` table + type-specific child tables.
 //! Uses r2d2 connection pooling to allow concurrent reads without mutex blocking.
 
+use crate::content_detection::detect_entities;
+use crate::encryption::{self, ContentCipher};
+use crate::file_kind;
+use crate::icon_service::{select_icon, IconSource};
+use crate::indexer::IndexedRow;
 use crate::interface::{
-    BaselineExcerpt, ClipboardContent, ContentTypeFilter, FileEntry, FilePreviewSnapshot,
-    FileStatus, FileTextPreviewSnapshot, ItemIcon, ItemMetadata, ItemTag, LinkMetadataState,
-    ListPresentationProfile,
+    AppMetadata, ArchiveListing, BaselineExcerpt, ClipboardContent, ContentTypeFilter,
+    DetectedEntity, DocumentInfo, EmailInfo, EntityKind, FileEntry, FileKind, FilePreviewSnapshot,
+    FileStatus, FileTextPreviewSnapshot, IconType, ItemMetadata, ItemTag, LinkMetadataState,
+    ListPresentationProfile, MediaInfo, SmartCollection, SmartCollectionRule, TagFilter,
 };
 use crate::models::StoredItem;
+use crate::ranking::fold_str;
 use crate::search::{generate_preview_for_profile, SNIPPET_CONTEXT_CHARS};
 use chrono::{DateTime, TimeZone, Utc};
 use r2d2::{Pool, PooledConnection};
 use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::params;
+use rusqlite::backup::{Backup, StepResult};
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::ValueRef;
+use rusqlite::{params, Connection, OpenFlags};
+use std::borrow::Cow;
 use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -35,6 +48,17 @@ pub enum DatabaseError {
 
 pub type DatabaseResult<T> = Result<T, DatabaseError>;
 
+/// A stored snippet: `(snippet_id, title, body, updated_at)`.
+pub(crate) type SnippetRow = (String, String, String, DateTime<Utc>);
+
+/// A menu bar quick list row: `(item_id, preview, icon_type,
+/// timestamp_unix, pinned)`.
+pub(crate) type QuickListRow = (String, String, IconType, i64, bool);
+
+/// An item a retention policy may delete: `(row_id, item_id, contentType,
+/// timestamp)`.
+pub(crate) type RetentionCandidate = (i64, String, String, DateTime<Utc>);
+
 #[cfg(feature = "sync")]
 impl From<purr_sync::SyncError> for DatabaseError {
     fn from(e: purr_sync::SyncError) -> Self {
@@ -48,6 +72,15 @@ impl From<purr_sync::SyncError> for DatabaseError {
 
 const SEARCH_METADATA_PREFIX_CHARS: usize = SNIPPET_CONTEXT_CHARS * 4;
 const BROWSE_METADATA_PREFIX_CHARS: usize = SNIPPET_CONTEXT_CHARS * 8;
+/// Enough leading content for `detect_code_language`.
+const CODE_DETECTION_PREFIX_CHARS: usize = 512;
+/// Crash breadcrumbs kept; older ones are dropped on insert.
+const MAX_CRASH_BREADCRUMBS: usize = 50;
+
+/// How long a backup step waits before retrying when the database is busy.
+const BACKUP_BUSY_RETRY: Duration = Duration::from_millis(50);
+/// UTI and filename of a file item's first entry, for document-kind icons.
+const FIRST_FILE_ICON_COLUMNS: &str = "(SELECT e.uti FROM file_items f JOIN file_entries e ON e.id = f.entryId WHERE f.itemId = items.id ORDER BY f.ordinal LIMIT 1), (SELECT e.filename FROM file_items f JOIN file_entries e ON e.id = f.entryId WHERE f.itemId = items.id ORDER BY f.ordinal LIMIT 1)";
 const GENERATED_ITEM_ID_SQL: &str = r#"lower(
     hex(randomblob(4)) || '-' ||
     hex(randomblob(2)) || '-4' ||
@@ -57,6 +90,223 @@ const GENERATED_ITEM_ID_SQL: &str = r#"lower(
     hex(randomblob(6))
 )"#;
 
+/// Image item row for grid browsing.
+pub(crate) struct ImageGridRow {
+    pub(crate) row_id: i64,
+    pub(crate) item_id: String,
+    /// Raw database timestamp, kept verbatim for keyset paging.
+    pub(crate) timestamp: String,
+    pub(crate) timestamp_unix: i64,
+    pub(crate) thumbnail: Option<Vec<u8>>,
+    pub(crate) grid_thumbnail: Option<Vec<u8>>,
+    pub(crate) width: Option<u32>,
+    pub(crate) height: Option<u32>,
+    pub(crate) is_animated: bool,
+}
+
+/// A logged paste of an item into a target app.
+pub(crate) struct PasteEventRow {
+    pub(crate) row_id: i64,
+    pub(crate) document_hint: Option<String>,
+    pub(crate) pasted_at_unix: i64,
+}
+
+/// One logged copy of an item, with the registered app name preferred
+/// over the captured one.
+pub(crate) struct CopyEventRow {
+    pub(crate) copied_at_unix: i64,
+    pub(crate) source_app: Option<String>,
+    pub(crate) source_app_bundle_id: Option<String>,
+}
+
+/// An item last copied within a digest day.
+pub(crate) struct DigestItemRow {
+    pub(crate) item_id: String,
+    pub(crate) content_type: String,
+    pub(crate) content_prefix: String,
+    pub(crate) source_app: Option<String>,
+    pub(crate) source_app_bundle_id: Option<String>,
+    pub(crate) link_url: Option<String>,
+    pub(crate) link_title: Option<String>,
+    /// Copy events within the day.
+    pub(crate) copies: u64,
+    /// Whether the item was also copied before the day began.
+    pub(crate) copied_before: bool,
+}
+
+/// A logged copy or paste of an item.
+pub(crate) struct UsageEventRow {
+    pub(crate) row_id: i64,
+    pub(crate) is_paste: bool,
+    pub(crate) at_unix: i64,
+}
+
+/// Latest paste of an item, with its paste count across all apps.
+pub(crate) struct RecentPasteRow {
+    pub(crate) row_id: i64,
+    pub(crate) item_id: String,
+    pub(crate) target_bundle_id: String,
+    pub(crate) pasted_at_unix: i64,
+    pub(crate) paste_count: u64,
+}
+
+/// Item fields written by `export_history`, with the registered app name
+/// preferred over the captured one.
+pub(crate) struct HistoryExportRow {
+    pub(crate) row_id: i64,
+    pub(crate) item_id: String,
+    /// Raw `timestamp` column, the keyset cursor for the next page.
+    pub(crate) stored_timestamp: String,
+    pub(crate) timestamp: DateTime<Utc>,
+    pub(crate) content_type: String,
+    pub(crate) content: String,
+    pub(crate) source_app: Option<String>,
+    pub(crate) source_app_bundle_id: Option<String>,
+}
+
+/// A source app and how many items it contributed.
+pub(crate) struct SourceAppRow {
+    pub(crate) bundle_id: Option<String>,
+    pub(crate) name: Option<String>,
+    pub(crate) item_count: u64,
+}
+
+/// Panic recorded by an FFI entry point.
+pub(crate) struct CrashBreadcrumbRow {
+    pub(crate) occurred_at_unix: i64,
+    pub(crate) operation: String,
+    pub(crate) message: String,
+    pub(crate) backtrace: String,
+}
+
+/// Activity count for one local day, kind (`copy` or `paste`), app, and
+/// content type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DailyActivityRow {
+    pub(crate) day: String,
+    pub(crate) activity: String,
+    pub(crate) app: Option<String>,
+    pub(crate) content_type: String,
+    pub(crate) count: u64,
+}
+
+/// Items and pastes for one content type or source app.
+pub(crate) struct UsageTallyRow {
+    pub(crate) bundle_id: Option<String>,
+    pub(crate) name: Option<String>,
+    pub(crate) item_count: u64,
+    pub(crate) paste_count: u64,
+}
+
+/// Paste count and latest paste of one item.
+pub(crate) struct ItemPasteRow {
+    pub(crate) item_id: String,
+    pub(crate) paste_count: u64,
+    pub(crate) last_pasted_at_unix: i64,
+}
+
+/// Item fields needed to score context suggestions.
+pub(crate) struct ContextCandidateRow {
+    pub(crate) row_id: i64,
+    pub(crate) db_type: String,
+    pub(crate) content_prefix: String,
+    pub(crate) source_app_bundle_id: Option<String>,
+    pub(crate) timestamp_unix: i64,
+}
+
+/// Whether an item's `locale` suits a reader's normalized locale, bound
+/// twice: untagged items always do, and a tag matches the locale itself or
+/// any more specific one ("zh-hans" suits "zh-hans-cn").
+const LOCALE_MATCH_CONDITION: &str = "(locale IS NULL OR locale = ? OR ? LIKE locale || '-%')";
+
+/// Whether an item holds a file of the bound kind.
+const FILE_KIND_CONDITION: &str = "id IN (SELECT f.itemId FROM file_items f JOIN file_entries e ON e.id = f.entryId WHERE e.fileKind = ?)";
+
+/// Tag and source-app constraints for list and short-query SQL. Every
+/// constraint must hold.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct TagRestriction {
+    pub(crate) system: Option<ItemTag>,
+    /// User-tag filters, with canonical tag names.
+    pub(crate) user: Vec<TagFilter>,
+    /// Bundle ID of the app the item was copied from.
+    pub(crate) source_app_bundle_id: Option<String>,
+    /// Normalized locale the viewer reads. Items tagged with another locale
+    /// are left out; untagged items always match.
+    pub(crate) locale: Option<String>,
+    /// Kind of file, from a `kind:` query word, the item must hold.
+    pub(crate) file_kind: Option<FileKind>,
+}
+
+impl TagRestriction {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.system.is_none()
+            && self.user.is_empty()
+            && self.source_app_bundle_id.is_none()
+            && self.locale.is_none()
+            && self.file_kind.is_none()
+    }
+
+    /// SQL conditions on `items` columns, and their parameters in order.
+    fn conditions(&self) -> (Vec<String>, Vec<rusqlite::types::Value>) {
+        let mut conditions = Vec::new();
+        let mut params: Vec<rusqlite::types::Value> = Vec::new();
+        if let Some(bundle_id) = &self.source_app_bundle_id {
+            conditions.push("sourceAppBundleId = ?".to_string());
+            params.push(bundle_id.clone().into());
+        }
+        if let Some(locale) = &self.locale {
+            conditions.push(LOCALE_MATCH_CONDITION.to_string());
+            params.push(locale.clone().into());
+            params.push(locale.clone().into());
+        }
+        if let Some(kind) = self.file_kind {
+            conditions.push(FILE_KIND_CONDITION.to_string());
+            params.push(kind.database_str().to_string().into());
+        }
+        if let Some(tag) = &self.system {
+            conditions.push("id IN (SELECT itemId FROM item_tags WHERE tag = ?)".to_string());
+            params.push(tag.database_str().to_string().into());
+        }
+        for filter in &self.user {
+            let (names, require_all) = match filter {
+                TagFilter::All { tags } => (tags, true),
+                TagFilter::Any { tags } => (tags, false),
+            };
+            if names.is_empty() {
+                // Every tag of none holds vacuously; any tag of none never does.
+                if !require_all {
+                    conditions.push("0".to_string());
+                }
+                continue;
+            }
+            let placeholders = names.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+            conditions.push(if require_all {
+                format!(
+                    "id IN (SELECT itemId FROM tags WHERE name IN ({placeholders}) GROUP BY itemId HAVING COUNT(*) = {})",
+                    names.len()
+                )
+            } else {
+                format!("id IN (SELECT itemId FROM tags WHERE name IN ({placeholders}))")
+            });
+            params.extend(names.iter().map(|name| name.clone().into()));
+        }
+        (conditions, params)
+    }
+}
+
+/// An item's rows in the side tables, keyed by the item's external ID
+/// rather than its row ID so they carry over between databases whose row
+/// IDs collide.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ItemLinks {
+    pub(crate) tags: Vec<ItemTag>,
+    /// User tags, with canonical names.
+    pub(crate) user_tags: Vec<String>,
+    pub(crate) reminder_at_unix: Option<i64>,
+    pub(crate) immutable: bool,
+}
+
 /// Intermediate row with raw content prefix; excerpt formatting is deferred to caller.
 struct RawRowMetadata {
     item_metadata: ItemMetadata,
@@ -88,6 +338,7 @@ struct FileEntryRow {
     preview_text: Option<String>,
     preview_data: Option<Vec<u8>>,
     preview_truncated: bool,
+    contents_text: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -105,6 +356,48 @@ fn parse_db_timestamp(timestamp_str: &str) -> DateTime<Utc> {
         .unwrap_or_else(|_| Utc::now())
 }
 
+/// Pair the first-file icon columns; a file item always has both or neither.
+fn first_file_pair<'a>(
+    uti: &'a Option<String>,
+    filename: &'a Option<String>,
+) -> Option<(&'a str, &'a str)> {
+    Some((uti.as_deref()?, filename.as_deref()?))
+}
+
+/// Encode a smart collection rule as a `(kind, value)` row.
+/// Bundle IDs never contain newlines, so a source-app list is newline-joined.
+fn encode_collection_rule(rule: &SmartCollectionRule) -> (&'static str, String) {
+    match rule {
+        SmartCollectionRule::ContentType { content_type } => {
+            ("content_type", content_type.database_str().to_string())
+        }
+        SmartCollectionRule::SourceApps { bundle_ids } => ("source_apps", bundle_ids.join("\n")),
+        SmartCollectionRule::ContainsText { text } => ("contains_text", text.clone()),
+        SmartCollectionRule::ContainsCode => ("contains_code", String::new()),
+        SmartCollectionRule::Tagged { tag } => ("tagged", tag.database_str().to_string()),
+    }
+}
+
+fn decode_collection_rule(kind: &str, value: &str) -> Result<SmartCollectionRule, String> {
+    match kind {
+        "content_type" => {
+            let content_type = ContentTypeFilter::from_database_str(value)?;
+            Ok(SmartCollectionRule::ContentType { content_type })
+        }
+        "source_apps" => Ok(SmartCollectionRule::SourceApps {
+            bundle_ids: value.split('\n').map(str::to_string).collect(),
+        }),
+        "contains_text" => Ok(SmartCollectionRule::ContainsText {
+            text: value.to_string(),
+        }),
+        "contains_code" => Ok(SmartCollectionRule::ContainsCode),
+        "tagged" => Ok(SmartCollectionRule::Tagged {
+            tag: ItemTag::from_database_str(value)?,
+        }),
+        other => Err(format!("unknown smart collection rule `{other}`")),
+    }
+}
+
 fn table_column_not_null(
     conn: &rusqlite::Connection,
     table: &str,
@@ -149,6 +442,203 @@ fn repair_item_ids(conn: &rusqlite::Connection) -> DatabaseResult<()> {
     Ok(())
 }
 
+fn backfill_external_ids(conn: &rusqlite::Connection) -> DatabaseResult<()> {
+    let missing = conn
+        .prepare("SELECT id, timestamp FROM items WHERE externalId IS NULL")?
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    if missing.is_empty() {
+        return Ok(());
+    }
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut update = tx.prepare("UPDATE items SET externalId = ?1 WHERE id = ?2")?;
+        for (id, timestamp) in missing {
+            let timestamp_unix = parse_db_timestamp(&timestamp).timestamp();
+            update.execute(params![StoredItem::external_id_at(timestamp_unix), id])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Key of a shared file entry: the file is the same when its path and
+/// bookmark are.
+fn file_entry_hash(path: &str, bookmark_data: &[u8]) -> String {
+    let mut key = Vec::with_capacity(path.len() + 1 + bookmark_data.len());
+    key.extend_from_slice(path.as_bytes());
+    key.push(0);
+    key.extend_from_slice(bookmark_data);
+    StoredItem::hash_bytes(&key)
+}
+
+/// The file entry at `file_index` of a file item, None when it has no such
+/// file.
+fn file_entry_id(
+    conn: &rusqlite::Connection,
+    item_id: i64,
+    file_index: u64,
+) -> DatabaseResult<Option<i64>> {
+    match conn.query_row(
+        "SELECT entryId FROM file_items WHERE itemId = ?1 AND ordinal = ?2",
+        params![item_id, file_index as i64],
+        |row| row.get(0),
+    ) {
+        Ok(entry_id) => Ok(Some(entry_id)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(other) => Err(other.into()),
+    }
+}
+
+/// Make `image` the file entry's preview and, for an item's first file, the
+/// item's list thumbnail, sealed with `cipher` in an encrypted store.
+fn set_file_image_preview(
+    conn: &rusqlite::Connection,
+    cipher: Option<&ContentCipher>,
+    item_id: i64,
+    file_index: u64,
+    entry_id: i64,
+    image: &[u8],
+) -> DatabaseResult<()> {
+    conn.execute(
+        "UPDATE file_entries SET previewKind = 'image', previewReason = NULL, previewText = NULL,
+             previewData = ?1, previewTruncated = 0
+         WHERE id = ?2",
+        params![image, entry_id],
+    )?;
+    if file_index == 0 {
+        let thumbnail = crate::thumbnail::render_grid_thumbnail(image)
+            .and_then(|image| image.thumbnail)
+            .unwrap_or_else(|| image.to_vec());
+        let thumbnail = match cipher {
+            Some(cipher) => cipher.seal(&thumbnail),
+            None => thumbnail,
+        };
+        conn.execute(
+            "UPDATE items SET thumbnail = ?1 WHERE id = ?2",
+            params![thumbnail, item_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Rebuild a `file_items` table that holds its entries into links to
+/// `file_entries`, one entry per distinct file. Where items disagree about a
+/// file, the newest row's status and preview are kept.
+fn share_file_entries(conn: &rusqlite::Connection) -> DatabaseResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(
+        r#"
+        DROP INDEX IF EXISTS idx_file_items_item;
+        ALTER TABLE file_items RENAME TO legacy_file_items;
+        CREATE TABLE file_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            itemId INTEGER NOT NULL REFERENCES items(id) ON DELETE CASCADE,
+            ordinal INTEGER NOT NULL DEFAULT 0,
+            entryId INTEGER NOT NULL REFERENCES file_entries(id)
+        );
+        CREATE INDEX idx_file_items_item ON file_items(itemId);
+        "#,
+    )?;
+    {
+        let mut rows =
+            tx.prepare("SELECT id, path, bookmarkData FROM legacy_file_items ORDER BY id")?;
+        let mut upsert = tx.prepare(
+            r#"INSERT INTO file_entries
+               (entryHash, path, filename, fileSize, uti, bookmarkData, fileStatus,
+                previewKind, previewReason, previewText, previewData, previewTruncated)
+               SELECT ?1, path, filename, fileSize, uti, bookmarkData, fileStatus,
+                      previewKind, previewReason, previewText, previewData, previewTruncated
+               FROM legacy_file_items WHERE id = ?2
+               ON CONFLICT(entryHash) DO UPDATE SET
+                   filename = excluded.filename,
+                   fileSize = excluded.fileSize,
+                   uti = excluded.uti,
+                   fileStatus = excluded.fileStatus,
+                   previewKind = excluded.previewKind,
+                   previewReason = excluded.previewReason,
+                   previewText = excluded.previewText,
+                   previewData = excluded.previewData,
+                   previewTruncated = excluded.previewTruncated"#,
+        )?;
+        let mut link = tx.prepare(
+            "INSERT INTO file_items (itemId, ordinal, entryId)
+             SELECT l.itemId, l.ordinal, e.id FROM legacy_file_items l, file_entries e
+             WHERE l.id = ?1 AND e.entryHash = ?2",
+        )?;
+        let mut query = rows.query([])?;
+        while let Some(row) = query.next()? {
+            let id: i64 = row.get(0)?;
+            let entry_hash = file_entry_hash(&row.get::<_, String>(1)?, &row.get::<_, Vec<u8>>(2)?);
+            upsert.execute(params![entry_hash, id])?;
+            link.execute(params![id, entry_hash])?;
+        }
+    }
+    tx.execute_batch(
+        r#"
+        DROP TABLE legacy_file_items;
+        UPDATE file_entries SET refCount = (
+            SELECT COUNT(*) FROM file_items WHERE entryId = file_entries.id
+        );
+        "#,
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Fill in the kind of entries stored before kinds were.
+fn backfill_file_kinds(conn: &rusqlite::Connection) -> DatabaseResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut rows =
+            tx.prepare("SELECT id, uti, filename FROM file_entries WHERE fileKind IS NULL")?;
+        let mut update = tx.prepare("UPDATE file_entries SET fileKind = ?1 WHERE id = ?2")?;
+        let mut query = rows.query([])?;
+        while let Some(row) = query.next()? {
+            let kind = file_kind::kind_of(&row.get::<_, String>(1)?, &row.get::<_, String>(2)?);
+            update.execute(params![kind.database_str(), row.get::<_, i64>(0)?])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+fn backfill_entities(conn: &rusqlite::Connection) -> DatabaseResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut stmt =
+            tx.prepare("SELECT id, content FROM items WHERE contentType IN ('text', 'rich_text')")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            write_entities(&tx, row.get(0)?, &row.get::<_, String>(1)?)?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Store the entities found in `text` for the item, replacing any it had.
+fn write_entities(conn: &rusqlite::Connection, item_id: i64, text: &str) -> DatabaseResult<()> {
+    conn.execute("DELETE FROM entities WHERE itemId = ?1", [item_id])?;
+    let mut insert = conn.prepare_cached(
+        "INSERT INTO entities (itemId, ordinal, kind, value, utf16Start, utf16End)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    for (ordinal, entity) in detect_entities(text).into_iter().enumerate() {
+        insert.execute(params![
+            item_id,
+            ordinal as i64,
+            entity.kind.database_str(),
+            entity.value,
+            entity.utf16_start as i64,
+            entity.utf16_end as i64,
+        ])?;
+    }
+    Ok(())
+}
+
 fn enforce_non_null_item_ids(conn: &rusqlite::Connection) -> DatabaseResult<()> {
     if table_column_not_null(conn, "items", "item_id")? {
         return Ok(());
@@ -227,18 +717,65 @@ fn enforce_non_null_item_ids(conn: &rusqlite::Connection) -> DatabaseResult<()>
     Ok(())
 }
 
+/// Run `backup` in a single step, retrying while the source or destination
+/// is busy. One step copies every page inside one read transaction.
+fn copy_all_pages(backup: &Backup) -> DatabaseResult<()> {
+    loop {
+        match backup.step(-1)? {
+            StepResult::Done => return Ok(()),
+            _ => std::thread::sleep(BACKUP_BUSY_RETRY),
+        }
+    }
+}
+
+/// The store's cipher, set once it is unlocked. Shared with every pooled
+/// connection so `seal` and `unseal` can seal and open columns in SQL.
+type CipherSlot = Arc<OnceLock<ContentCipher>>;
+
+/// SQL functions every connection gets: `fold(text)` is the search folding
+/// of `text` (`ranking::fold_str`), for case- and accent-insensitive
+/// matching in SQL. `seal(text)` seals text once the store is unlocked and
+/// passes it through until then; `unseal(value)` opens sealed text and
+/// blobs. The sync tables write and read their payloads through these.
+fn register_functions(conn: &Connection, cipher: &CipherSlot) -> rusqlite::Result<()> {
+    conn.create_scalar_function(
+        "fold",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            Ok(match ctx.get_raw(0) {
+                ValueRef::Text(text) => Some(fold_str(&String::from_utf8_lossy(text))),
+                _ => None,
+            })
+        },
+    )?;
+    let sealing = Arc::clone(cipher);
+    conn.create_scalar_function("seal", 1, FunctionFlags::SQLITE_UTF8, move |ctx| {
+        Ok(encryption::seal_value(sealing.get(), ctx.get_raw(0)))
+    })?;
+    let cipher = Arc::clone(cipher);
+    conn.create_scalar_function("unseal", 1, FunctionFlags::SQLITE_UTF8, move |ctx| {
+        encryption::unseal_value(cipher.get(), ctx.get_raw(0))
+            .map_err(|error| rusqlite::Error::UserFunctionError(Box::new(error)))
+    })
+}
+
 /// Thread-safe database wrapper using connection pooling
 ///
 /// Uses r2d2 connection pool for concurrent read access.
 /// WAL mode enables readers to proceed without blocking each other.
 pub struct Database {
     pool: Pool<SqliteConnectionManager>,
+    /// Seals payload columns once the store is unlocked with a key.
+    cipher: CipherSlot,
 }
 
 impl Database {
     /// Open or create a database at the given path with connection pooling
     pub fn open<P: AsRef<Path>>(path: P) -> DatabaseResult<Self> {
-        let manager = SqliteConnectionManager::file(path).with_init(|conn| {
+        let cipher = CipherSlot::default();
+        let slot = Arc::clone(&cipher);
+        let manager = SqliteConnectionManager::file(path).with_init(move |conn| {
             conn.execute_batch(
                 "
                     PRAGMA journal_mode=WAL;
@@ -249,19 +786,45 @@ impl Database {
                     PRAGMA secure_delete=ON;
                 ",
             )?;
-            Ok(())
+            register_functions(conn, &slot)
         });
 
         let pool = Pool::builder().max_size(8).build(manager)?;
 
-        let db = Self { pool };
+        let db = Self { pool, cipher };
         db.setup_schema()?;
         Ok(db)
     }
 
+    /// Open an existing database for reading alongside the process that
+    /// writes it. WAL lets these reads run while that process writes; the
+    /// schema is left as found, and writes fail with SQLite's read-only error.
+    pub fn open_read_only<P: AsRef<Path>>(path: P) -> DatabaseResult<Self> {
+        let cipher = CipherSlot::default();
+        let slot = Arc::clone(&cipher);
+        let manager = SqliteConnectionManager::file(path)
+            .with_flags(OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+            .with_init(move |conn| {
+                conn.execute_batch(
+                    "
+                        PRAGMA query_only=ON;
+                        PRAGMA foreign_keys=ON;
+                        PRAGMA mmap_size=67108864;
+                        PRAGMA cache_size=-32000;
+                    ",
+                )?;
+                register_functions(conn, &slot)
+            });
+
+        let pool = Pool::builder().max_size(8).build(manager)?;
+        Ok(Self { pool, cipher })
+    }
+
     /// Open an in-memory database (for testing)
     pub fn open_in_memory() -> DatabaseResult<Self> {
-        let manager = SqliteConnectionManager::memory().with_init(|conn| {
+        let cipher = CipherSlot::default();
+        let slot = Arc::clone(&cipher);
+        let manager = SqliteConnectionManager::memory().with_init(move |conn| {
             conn.execute_batch(
                 "
                     PRAGMA journal_mode=WAL;
@@ -269,17 +832,167 @@ impl Database {
                     PRAGMA foreign_keys=ON;
                 ",
             )?;
-            Ok(())
+            register_functions(conn, &slot)
         });
 
         // In-memory needs single connection to maintain state
         let pool = Pool::builder().max_size(1).build(manager)?;
 
-        let db = Self { pool };
+        let db = Self { pool, cipher };
         db.setup_schema()?;
         Ok(db)
     }
 
+    pub(crate) fn set_cipher(&mut self, cipher: ContentCipher) {
+        // A store is unlocked once, right after it opens.
+        let _ = self.cipher.set(cipher);
+    }
+
+    pub(crate) fn cipher(&self) -> Option<&ContentCipher> {
+        self.cipher.get()
+    }
+
+    fn seal_payload(&self, plaintext: &[u8]) -> Vec<u8> {
+        match self.cipher() {
+            Some(cipher) => cipher.seal(plaintext),
+            None => plaintext.to_vec(),
+        }
+    }
+
+    fn seal_text<'a>(&self, plaintext: &'a str) -> Cow<'a, str> {
+        match self.cipher() {
+            Some(cipher) => Cow::Owned(cipher.seal_text(plaintext)),
+            None => Cow::Borrowed(plaintext),
+        }
+    }
+
+    /// SQL for the plaintext of a sealed column. Unencrypted stores read the
+    /// column directly, so its indexes stay usable.
+    fn unsealed(&self, column: &str) -> String {
+        match self.cipher() {
+            Some(_) => format!("unseal({column})"),
+            None => column.to_string(),
+        }
+    }
+
+    /// The `items` columns `row_to_base_item` reads.
+    fn base_item_columns(&self) -> String {
+        format!(
+            "id, contentType, contentHash, {}, timestamp, sourceApp, sourceAppBundleId, {}, colorRgba, item_id, externalId",
+            self.unsealed("content"),
+            self.unsealed("thumbnail")
+        )
+    }
+
+    /// Decrypt a payload column. Plain payloads pass through, so rows written
+    /// before encryption was enabled stay readable.
+    fn open_payload(&self, stored: Vec<u8>) -> DatabaseResult<Vec<u8>> {
+        if !encryption::is_sealed(&stored) {
+            return Ok(stored);
+        }
+        match self.cipher() {
+            Some(cipher) => cipher.open(&stored),
+            None => Err(DatabaseError::InconsistentData(
+                "payload is encrypted but the store was opened without a key".into(),
+            )),
+        }
+    }
+
+    /// Seal every plain payload in place and store `verifier`, in one
+    /// transaction, turning an unencrypted store into an encrypted one.
+    /// The file is then vacuumed and its WAL truncated, so the plaintext
+    /// pages the sealed values replaced do not linger on disk.
+    pub(crate) fn seal_payloads(
+        &self,
+        cipher: &ContentCipher,
+        verifier: &[u8],
+    ) -> DatabaseResult<()> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+        for (table, key, column) in [
+            ("text_items", "itemId", "value"),
+            ("image_items", "itemId", "data"),
+            ("image_items", "itemId", "gridThumbnail"),
+            ("rich_text_items", "itemId", "rtfData"),
+            ("rich_text_items", "itemId", "htmlData"),
+            ("items", "id", "thumbnail"),
+        ] {
+            let rows: Vec<(i64, Vec<u8>)> = {
+                let mut stmt = tx.prepare(&format!(
+                    "SELECT {key}, CAST({column} AS BLOB) FROM {table} WHERE {column} IS NOT NULL"
+                ))?;
+                let rows = stmt
+                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                rows
+            };
+            let mut update = tx.prepare(&format!(
+                "UPDATE {table} SET {column} = ?1 WHERE {key} = ?2"
+            ))?;
+            for (row_id, stored) in rows {
+                if !encryption::is_sealed(&stored) {
+                    update.execute(params![cipher.seal(&stored), row_id])?;
+                }
+            }
+        }
+        for (table, column) in [
+            ("items", "content"),
+            ("image_items", "description"),
+            ("image_items", "ocrText"),
+            ("link_items", "url"),
+            ("link_items", "title"),
+            ("link_items", "description"),
+            ("snippets", "body"),
+            ("sync_events", "payload_data"),
+            ("sync_deferred_events", "payload_data"),
+            ("sync_snapshots", "aggregate_state"),
+        ] {
+            // The sync tables exist only in builds with sync.
+            let exists: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+                [table],
+                |row| row.get(0),
+            )?;
+            if !exists {
+                continue;
+            }
+            let rows: Vec<(i64, String)> = {
+                let mut stmt = tx.prepare(&format!(
+                    "SELECT rowid, {column} FROM {table} WHERE {column} IS NOT NULL"
+                ))?;
+                let rows = stmt
+                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                rows
+            };
+            let mut update = tx.prepare(&format!(
+                "UPDATE {table} SET {column} = ?1 WHERE rowid = ?2"
+            ))?;
+            for (row_id, stored) in rows {
+                if !encryption::is_sealed_text(&stored) {
+                    update.execute(params![cipher.seal_text(&stored), row_id])?;
+                }
+            }
+        }
+        tx.execute(
+            "INSERT INTO key_values (namespace, key, value, updatedAt) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(namespace, key) DO UPDATE SET value = excluded.value, updatedAt = excluded.updatedAt",
+            params![
+                encryption::ENCRYPTION_NAMESPACE,
+                encryption::VERIFIER_KEY,
+                verifier,
+                Utc::now().format("%Y-%m-%d %H:%M:%S%.f").to_string()
+            ],
+        )?;
+        tx.commit()?;
+        conn.execute_batch(
+            "PRAGMA secure_delete=ON;
+             VACUUM;
+             PRAGMA wal_checkpoint(TRUNCATE);",
+        )?;
+        Ok(())
+    }
+
     /// Get a connection from the pool
     pub(crate) fn get_conn(&self) -> DatabaseResult<PooledConnection<SqliteConnectionManager>> {
         Ok(self.pool.get()?)
@@ -316,7 +1029,8 @@ impl Database {
                 sourceApp TEXT,
                 sourceAppBundleId TEXT,
                 thumbnail BLOB,
-                colorRgba INTEGER
+                colorRgba INTEGER,
+                pinned INTEGER NOT NULL DEFAULT 0
             );
 
             CREATE TABLE IF NOT EXISTS text_items (
@@ -324,6 +1038,12 @@ impl Database {
                 value TEXT NOT NULL
             );
 
+            CREATE TABLE IF NOT EXISTS rich_text_items (
+                itemId INTEGER PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
+                rtfData BLOB,
+                htmlData BLOB
+            );
+
             CREATE TABLE IF NOT EXISTS image_items (
                 itemId INTEGER PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
                 data BLOB NOT NULL,
@@ -338,10 +1058,9 @@ impl Database {
                 description TEXT
             );
 
-            CREATE TABLE IF NOT EXISTS file_items (
+            CREATE TABLE IF NOT EXISTS file_entries (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
-                itemId INTEGER NOT NULL REFERENCES items(id) ON DELETE CASCADE,
-                ordinal INTEGER NOT NULL DEFAULT 0,
+                entryHash TEXT NOT NULL UNIQUE,
                 path TEXT NOT NULL,
                 filename TEXT NOT NULL,
                 fileSize INTEGER NOT NULL DEFAULT 0,
@@ -352,7 +1071,29 @@ impl Database {
                 previewReason TEXT DEFAULT 'migrated_without_preview',
                 previewText TEXT,
                 previewData BLOB,
-                previewTruncated INTEGER NOT NULL DEFAULT 0
+                previewTruncated INTEGER NOT NULL DEFAULT 0,
+                refCount INTEGER NOT NULL DEFAULT 0,
+                fileKind TEXT,
+                mediaDurationMs INTEGER,
+                mediaCodec TEXT,
+                mediaWidth INTEGER,
+                mediaHeight INTEGER,
+                pdfPageCount INTEGER,
+                pdfTitle TEXT,
+                pdfAuthor TEXT,
+                emailSubject TEXT,
+                emailSender TEXT,
+                emailSentAt INTEGER,
+                archiveEntries TEXT,
+                archiveTruncated INTEGER
+            );
+            CREATE INDEX IF NOT EXISTS idx_file_entries_path ON file_entries(path);
+
+            CREATE TABLE IF NOT EXISTS file_items (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                itemId INTEGER NOT NULL REFERENCES items(id) ON DELETE CASCADE,
+                ordinal INTEGER NOT NULL DEFAULT 0,
+                entryId INTEGER NOT NULL REFERENCES file_entries(id)
             );
 
             CREATE INDEX IF NOT EXISTS idx_items_hash ON items(contentHash);
@@ -366,6 +1107,134 @@ impl Database {
                 PRIMARY KEY (itemId, tag)
             );
             CREATE INDEX IF NOT EXISTS idx_item_tags_tag ON item_tags(tag);
+
+            CREATE TABLE IF NOT EXISTS tags (
+                itemId INTEGER NOT NULL REFERENCES items(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                createdAt TEXT NOT NULL,
+                PRIMARY KEY (itemId, name)
+            );
+            CREATE INDEX IF NOT EXISTS idx_tags_name ON tags(name);
+
+            CREATE TABLE IF NOT EXISTS smart_collections (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                createdAt TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS smart_collection_rules (
+                collectionId TEXT NOT NULL REFERENCES smart_collections(id) ON DELETE CASCADE,
+                ordinal INTEGER NOT NULL,
+                kind TEXT NOT NULL,
+                value TEXT NOT NULL DEFAULT '',
+                PRIMARY KEY (collectionId, ordinal)
+            );
+
+            CREATE TABLE IF NOT EXISTS topics (
+                id TEXT PRIMARY KEY,
+                label TEXT NOT NULL,
+                centroid TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS topic_items (
+                itemId INTEGER PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
+                topicId TEXT NOT NULL REFERENCES topics(id) ON DELETE CASCADE
+            );
+            CREATE INDEX IF NOT EXISTS idx_topic_items_topic ON topic_items(topicId);
+
+            CREATE TABLE IF NOT EXISTS paste_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                itemId INTEGER NOT NULL REFERENCES items(id) ON DELETE CASCADE,
+                targetBundleId TEXT NOT NULL,
+                documentHint TEXT,
+                pastedAt TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_paste_events_target ON paste_events(targetBundleId, pastedAt);
+            CREATE INDEX IF NOT EXISTS idx_paste_events_item ON paste_events(itemId);
+
+            CREATE TABLE IF NOT EXISTS transform_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                itemId INTEGER NOT NULL REFERENCES items(id) ON DELETE CASCADE,
+                transformation TEXT NOT NULL,
+                targetBundleId TEXT,
+                appliedAt TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_transform_events_item ON transform_events(itemId, appliedAt);
+
+            CREATE TABLE IF NOT EXISTS copy_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                itemId INTEGER NOT NULL REFERENCES items(id) ON DELETE CASCADE,
+                copiedAt TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_copy_events_copied ON copy_events(copiedAt);
+            CREATE INDEX IF NOT EXISTS idx_copy_events_item ON copy_events(itemId);
+
+            CREATE TABLE IF NOT EXISTS app_paste_priors (
+                targetBundleId TEXT NOT NULL,
+                dimension TEXT NOT NULL,
+                value TEXT NOT NULL,
+                pasteCount INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (targetBundleId, dimension, value)
+            );
+
+            CREATE TABLE IF NOT EXISTS immutable_items (
+                itemId INTEGER PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
+                markedAt TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS item_expiry (
+                itemId INTEGER PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
+                expiresAt TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_item_expiry_expires ON item_expiry(expiresAt);
+
+            -- Captures made during a temporary session. previousTimestamp
+            -- is the pre-session timestamp of an item the session touched,
+            -- NULL for one it inserted.
+            CREATE TABLE IF NOT EXISTS session_items (
+                itemId INTEGER PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
+                previousTimestamp TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS reminders (
+                itemId INTEGER PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
+                fireAt TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_reminders_fire ON reminders(fireAt);
+
+            CREATE TABLE IF NOT EXISTS app_metadata (
+                bundleId TEXT PRIMARY KEY,
+                displayName TEXT NOT NULL,
+                iconHash TEXT,
+                category TEXT,
+                installed INTEGER NOT NULL DEFAULT 1,
+                updatedAt TEXT NOT NULL
+            );
+
+            -- One row per source app: keyed by bundle ID, or by name for
+            -- apps captured without one. `name` is the latest captured name.
+            CREATE TABLE IF NOT EXISTS apps (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                bundleId TEXT UNIQUE,
+                name TEXT
+            );
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_apps_unbundled_name ON apps(name) WHERE bundleId IS NULL;
+
+            CREATE TABLE IF NOT EXISTS key_values (
+                namespace TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value BLOB NOT NULL,
+                updatedAt TEXT NOT NULL,
+                PRIMARY KEY (namespace, key)
+            );
+
+            CREATE TABLE IF NOT EXISTS crash_breadcrumbs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                occurredAt TEXT NOT NULL,
+                operation TEXT NOT NULL,
+                message TEXT NOT NULL,
+                backtrace TEXT NOT NULL
+            );
         "#,
         )?;
 
@@ -383,39 +1252,139 @@ impl Database {
             [],
         );
 
-        // Migration: Add file preview snapshot columns to existing file_items tables.
-        let _ = conn.execute(
-            "ALTER TABLE file_items ADD COLUMN previewKind TEXT NOT NULL DEFAULT 'unavailable'",
+        // Migration: shared file entries. `file_items` used to hold each
+        // entry itself, so a file grouped into several items was stored once
+        // per item; it now links items to rows in `file_entries`.
+        let legacy_file_items: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('file_items') WHERE name = 'path'",
             [],
-        );
-        let _ = conn.execute(
-            "ALTER TABLE file_items ADD COLUMN previewReason TEXT DEFAULT 'migrated_without_preview'",
+            |row| row.get(0),
+        )?;
+        if legacy_file_items {
+            // Add file preview snapshot columns to existing file_items tables.
+            let _ = conn.execute(
+                "ALTER TABLE file_items ADD COLUMN previewKind TEXT NOT NULL DEFAULT 'unavailable'",
+                [],
+            );
+            let _ = conn.execute(
+                "ALTER TABLE file_items ADD COLUMN previewReason TEXT DEFAULT 'migrated_without_preview'",
+                [],
+            );
+            let _ = conn.execute("ALTER TABLE file_items ADD COLUMN previewText TEXT", []);
+            let _ = conn.execute("ALTER TABLE file_items ADD COLUMN previewData BLOB", []);
+            let _ = conn.execute(
+                "ALTER TABLE file_items ADD COLUMN previewTruncated INTEGER NOT NULL DEFAULT 0",
+                [],
+            );
+            share_file_entries(&conn)?;
+        }
+        // Entries count the items holding them and go with the last one.
+        conn.execute_batch(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS file_entries_ref AFTER INSERT ON file_items
+            BEGIN UPDATE file_entries SET refCount = refCount + 1 WHERE id = NEW.entryId; END;
+            CREATE TRIGGER IF NOT EXISTS file_entries_unref AFTER DELETE ON file_items
+            BEGIN
+                UPDATE file_entries SET refCount = refCount - 1 WHERE id = OLD.entryId;
+                DELETE FROM file_entries WHERE id = OLD.entryId AND refCount <= 0;
+            END;
+            "#,
+        )?;
+
+        // Migration: file kinds, derived from each entry's UTI and filename.
+        let _ = conn.execute("ALTER TABLE file_entries ADD COLUMN fileKind TEXT", []);
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_file_entries_kind ON file_entries(fileKind)",
             [],
-        );
-        let _ = conn.execute("ALTER TABLE file_items ADD COLUMN previewText TEXT", []);
-        let _ = conn.execute("ALTER TABLE file_items ADD COLUMN previewData BLOB", []);
+        )?;
+        backfill_file_kinds(&conn)?;
+
+        // Migration: audio and video metadata, from the app or read at capture.
+        for column in [
+            "mediaDurationMs INTEGER",
+            "mediaCodec TEXT",
+            "mediaWidth INTEGER",
+            "mediaHeight INTEGER",
+        ] {
+            let _ = conn.execute(&format!("ALTER TABLE file_entries ADD COLUMN {column}"), []);
+        }
+
+        // Migration: PDF document info, read at capture or from the app.
+        for column in ["pdfPageCount INTEGER", "pdfTitle TEXT", "pdfAuthor TEXT"] {
+            let _ = conn.execute(&format!("ALTER TABLE file_entries ADD COLUMN {column}"), []);
+        }
+
+        // Migration: headers of exported emails, read at capture.
+        for column in [
+            "emailSubject TEXT",
+            "emailSender TEXT",
+            "emailSentAt INTEGER",
+        ] {
+            let _ = conn.execute(&format!("ALTER TABLE file_entries ADD COLUMN {column}"), []);
+        }
+
+        // Migration: entry names of zip and tar archives, read at capture.
+        for column in ["archiveEntries TEXT", "archiveTruncated INTEGER"] {
+            let _ = conn.execute(&format!("ALTER TABLE file_entries ADD COLUMN {column}"), []);
+        }
+
+        // Migration: grid browsing metadata, filled lazily by `browse_images`.
+        // NULL width means not yet computed; 0 means undecodable in Rust.
+        let _ = conn.execute("ALTER TABLE image_items ADD COLUMN width INTEGER", []);
+        let _ = conn.execute("ALTER TABLE image_items ADD COLUMN height INTEGER", []);
+        let _ = conn.execute("ALTER TABLE image_items ADD COLUMN gridThumbnail BLOB", []);
+
+        // Migration: text recognized in an image, set by `update_image_ocr_text`.
+        let _ = conn.execute("ALTER TABLE image_items ADD COLUMN ocrText TEXT", []);
+
+        // Migration: the app each copy came from, for `get_copy_history`.
+        // Events logged before this stay NULL.
+        let _ = conn.execute("ALTER TABLE copy_events ADD COLUMN sourceApp TEXT", []);
         let _ = conn.execute(
-            "ALTER TABLE file_items ADD COLUMN previewTruncated INTEGER NOT NULL DEFAULT 0",
+            "ALTER TABLE copy_events ADD COLUMN sourceAppBundleId TEXT",
             [],
         );
 
+        // Migration: the locale an item was written for, set by
+        // `set_item_locale`. Demo databases from the data-gen tool kept it on
+        // `image_items`; carry it over where that column exists.
+        let _ = conn.execute("ALTER TABLE items ADD COLUMN locale TEXT", []);
+        let has_image_locale: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('image_items') WHERE name = 'locale'",
+            [],
+            |row| row.get(0),
+        )?;
+        if has_image_locale {
+            conn.execute(
+                "UPDATE items SET locale = (
+                     SELECT lower(replace(img.locale, '_', '-')) FROM image_items img
+                     WHERE img.itemId = items.id
+                 )
+                 WHERE locale IS NULL
+                   AND id IN (SELECT itemId FROM image_items WHERE locale IS NOT NULL)",
+                [],
+            )?;
+        }
+
         // Migration: bake the "Image: " label into existing image descriptions
         // so older rows match the form new images are stored in (see
         // `format_image_description`). Skips the bare "Image" placeholder and any
-        // row already prefixed, so it is safe to re-run. The description is
-        // denormalized into both `items.content` and `image_items.description`,
-        // so both are updated.
+        // row already prefixed, so it is safe to re-run, and sealed rows, which
+        // are already past it. The description is denormalized into both
+        // `items.content` and `image_items.description`, so both are updated.
         conn.execute(
             "UPDATE items SET content = 'Image: ' || content
              WHERE contentType = 'image'
                AND content <> 'Image'
-               AND content NOT LIKE 'Image: %'",
+               AND content NOT LIKE 'Image: %'
+               AND content NOT LIKE 'ckenc1:%'",
             [],
         )?;
         conn.execute(
             "UPDATE image_items SET description = 'Image: ' || description
              WHERE description <> 'Image'
-               AND description NOT LIKE 'Image: %'",
+               AND description NOT LIKE 'Image: %'
+               AND description NOT LIKE 'ckenc1:%'",
             [],
         )?;
 
@@ -432,8 +1401,112 @@ impl Database {
             [],
         )?;
 
-        // ── Sync tables (delegated to purr-sync) ──────────────────────
-        #[cfg(feature = "sync")]
+        // Migration: pinned flag. Added after the item_id rebuild above, whose
+        // replacement table predates it.
+        let _ = conn.execute(
+            "ALTER TABLE items ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0",
+            [],
+        );
+
+        // Migration: normalized source apps. `sourceApp` and
+        // `sourceAppBundleId` stay on each row as captured; `appId` links the
+        // row to its entry in `apps`. Rows written without it, by older
+        // builds or external tools, are linked here.
+        let _ = conn.execute(
+            "ALTER TABLE items ADD COLUMN appId INTEGER REFERENCES apps(id)",
+            [],
+        );
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_items_app ON items(appId, timestamp)",
+            [],
+        )?;
+        conn.execute_batch(
+            r#"
+            INSERT OR IGNORE INTO apps (bundleId, name)
+                SELECT sourceAppBundleId, NULLIF(sourceApp, '') FROM items
+                WHERE appId IS NULL AND sourceAppBundleId <> ''
+                ORDER BY timestamp DESC;
+            INSERT OR IGNORE INTO apps (bundleId, name)
+                SELECT DISTINCT NULL, sourceApp FROM items
+                WHERE appId IS NULL AND COALESCE(sourceAppBundleId, '') = '' AND sourceApp <> '';
+            UPDATE items SET appId = (
+                SELECT a.id FROM apps a
+                WHERE CASE WHEN COALESCE(items.sourceAppBundleId, '') <> ''
+                           THEN a.bundleId = items.sourceAppBundleId
+                           ELSE a.bundleId IS NULL AND a.name = items.sourceApp END
+            )
+            WHERE appId IS NULL AND (sourceAppBundleId <> '' OR sourceApp <> '');
+            "#,
+        )?;
+
+        // Migration: external IDs for deep links. Rows written without one
+        // get a ULID carrying their own timestamp.
+        let _ = conn.execute("ALTER TABLE items ADD COLUMN externalId TEXT", []);
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_items_external_id ON items(externalId)",
+            [],
+        )?;
+        backfill_external_ids(&conn)?;
+
+        // Migration: trash. Deleted items keep their rows with `deletedAt`
+        // set until `empty_trash` purges them.
+        let _ = conn.execute("ALTER TABLE items ADD COLUMN deletedAt TEXT", []);
+
+        // Change sequence: bumped by every write to `items`, so cached
+        // lists can tell they are stale without re-reading rows.
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS change_sequence (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                value INTEGER NOT NULL
+            );
+            INSERT OR IGNORE INTO change_sequence (id, value) VALUES (1, 0);
+            CREATE TRIGGER IF NOT EXISTS items_changed_insert AFTER INSERT ON items
+            BEGIN UPDATE change_sequence SET value = value + 1; END;
+            CREATE TRIGGER IF NOT EXISTS items_changed_update AFTER UPDATE ON items
+            BEGIN UPDATE change_sequence SET value = value + 1; END;
+            CREATE TRIGGER IF NOT EXISTS items_changed_delete AFTER DELETE ON items
+            BEGIN UPDATE change_sequence SET value = value + 1; END;
+            "#,
+        )?;
+
+        // Saved snippets, kept apart from history.
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS snippets (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                body TEXT NOT NULL,
+                createdAt TEXT NOT NULL,
+                updatedAt TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Entities found in text items, for "copy just the URL" actions.
+        // Rows written before the table existed are scanned once here.
+        let entities_exist: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'entities')",
+            [],
+            |row| row.get(0),
+        )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS entities (
+                itemId INTEGER NOT NULL REFERENCES items(id) ON DELETE CASCADE,
+                ordinal INTEGER NOT NULL,
+                kind TEXT NOT NULL,
+                value TEXT NOT NULL,
+                utf16Start INTEGER NOT NULL,
+                utf16End INTEGER NOT NULL,
+                PRIMARY KEY (itemId, ordinal)
+            )",
+            [],
+        )?;
+        if !entities_exist {
+            backfill_entities(&conn)?;
+        }
+
+        // ── Sync tables (delegated to purr-sync) ──────────────────────
+        #[cfg(feature = "sync")]
         purr_sync::schema::setup_sync_schema(&conn)?;
 
         Ok(())
@@ -447,13 +1520,74 @@ impl Database {
         Ok(page_count * page_size)
     }
 
-    /// Get total number of items in the database
-    pub fn count_items(&self) -> DatabaseResult<u64> {
+    /// Counter bumped by every write to `items`.
+    pub(crate) fn change_sequence(&self) -> DatabaseResult<u64> {
+        let conn = self.get_conn()?;
+        let value: i64 = conn.query_row(
+            "SELECT value FROM change_sequence WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(value as u64)
+    }
+
+    /// Write a consistent copy of the database to `path`, which must not exist.
+    pub fn snapshot_to(&self, path: &Path) -> DatabaseResult<()> {
         let conn = self.get_conn()?;
+        conn.execute("VACUUM INTO ?1", [path.to_string_lossy()])?;
+        Ok(())
+    }
+
+    /// Copy the database to a new file at `path` with SQLite's online backup
+    /// API. Every page goes over in one step, inside a single read
+    /// transaction, so the copy is consistent while other connections write.
+    pub fn backup_to(&self, path: &Path) -> DatabaseResult<()> {
+        let source = self.get_conn()?;
+        let mut destination = rusqlite::Connection::open(path)?;
+        copy_all_pages(&Backup::new(&source, &mut destination)?)?;
+        // The copy takes the source's WAL mode; a rollback journal keeps it
+        // one self-contained file.
+        destination.pragma_update_and_check(None, "journal_mode", "DELETE", |_| Ok(()))?;
+        Ok(())
+    }
+
+    /// Replace this database's contents with those of `source` through the
+    /// backup API, so every pooled connection sees the new contents.
+    pub fn restore_from(&self, source: &Database) -> DatabaseResult<()> {
+        let source = source.get_conn()?;
+        let mut destination = self.get_conn()?;
+        copy_all_pages(&Backup::new(&source, &mut destination)?)?;
+        Ok(())
+    }
+
+    /// Check a snapshot written by `snapshot_to` without modifying it.
+    /// Returns its item count.
+    pub fn verify_snapshot(path: &Path) -> DatabaseResult<u64> {
+        let conn = rusqlite::Connection::open_with_flags(
+            path,
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+        )?;
+        let check: String = conn.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+        if check != "ok" {
+            return Err(DatabaseError::InconsistentData(format!(
+                "snapshot failed quick_check: {check}"
+            )));
+        }
         let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0))?;
         Ok(count as u64)
     }
 
+    /// Get total number of items in the database, not counting the trash
+    pub fn count_items(&self) -> DatabaseResult<u64> {
+        let conn = self.get_conn()?;
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM items WHERE deletedAt IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count as u64)
+    }
+
     /// Look up the stable string item_id for a given numeric row ID.
     pub fn fetch_item_id_by_row_id(&self, row_id: i64) -> DatabaseResult<Option<String>> {
         let conn = self.get_conn()?;
@@ -470,32 +1604,242 @@ impl Database {
     /// Insert a new clipboard item using a transaction.
     /// Inserts into `items` + the appropriate child table(s).
     /// Returns the item ID.
+    #[tracing::instrument(level = "debug", name = "db.insert_item", skip_all)]
     pub fn insert_item(&self, item: &StoredItem) -> DatabaseResult<i64> {
         let conn = self.get_conn()?;
         let tx = conn.unchecked_transaction()?;
+        let (item_id, _) = self.insert_item_in(&tx, item)?;
+        tx.commit()?;
+        Ok(item_id)
+    }
+
+    /// Insert `items` in one transaction, logging a copy of each. An item
+    /// whose content is already stored, before or earlier in the batch, is
+    /// touched at `now` instead. Returns each item's row ID and, for a
+    /// touched item, its timestamp before the touch, in order.
+    pub(crate) fn insert_items_deduplicated(
+        &self,
+        items: &[StoredItem],
+        now: DateTime<Utc>,
+    ) -> DatabaseResult<Vec<(i64, Option<DateTime<Utc>>)>> {
+        let conn = self.get_conn()?;
+        let tx = conn.unchecked_transaction()?;
+        let mut outcomes = Vec::with_capacity(items.len());
+        for item in items {
+            let existing: Option<(i64, String)> = match tx.query_row(
+                "SELECT id, timestamp FROM items WHERE contentHash = ?1 AND deletedAt IS NULL LIMIT 1",
+                [&item.content_hash],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            ) {
+                Ok(row) => Some(row),
+                Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                Err(e) => return Err(e.into()),
+            };
+            let (row_id, copied_at, previous) = match existing {
+                Some((id, previous)) => {
+                    tx.execute(
+                        "UPDATE items SET timestamp = ?1 WHERE id = ?2",
+                        params![now.format("%Y-%m-%d %H:%M:%S%.f").to_string(), id],
+                    )?;
+                    (id, now, Some(parse_db_timestamp(&previous)))
+                }
+                None => {
+                    let (id, _) = self.insert_item_in(&tx, item)?;
+                    let copied_at = Utc
+                        .timestamp_opt(item.timestamp_unix, 0)
+                        .single()
+                        .unwrap_or(now);
+                    (id, copied_at, None)
+                }
+            };
+            Self::insert_copy_event_in(
+                &tx,
+                row_id,
+                copied_at,
+                item.source_app.as_deref(),
+                item.source_app_bundle_id.as_deref(),
+            )?;
+            outcomes.push((row_id, previous));
+        }
+        tx.commit()?;
+        Ok(outcomes)
+    }
+
+    /// Insert an item and its side-table rows in one transaction, so an
+    /// import never leaves an item without its tags or reminder. The rows are
+    /// attached through the external ID the item was stored under, never a
+    /// row ID from another database. Returns the item's row ID.
+    pub(crate) fn insert_item_with_links(
+        &self,
+        item: &StoredItem,
+        links: &ItemLinks,
+    ) -> DatabaseResult<i64> {
+        let conn = self.get_conn()?;
+        let tx = conn.unchecked_transaction()?;
+        let (_, external_id) = self.insert_item_in(&tx, item)?;
+        let item_id = Self::attach_item_links(&tx, &external_id, links)?;
+        tx.commit()?;
+        Ok(item_id)
+    }
+
+    /// Write `links` for the item with `external_id`. Fails when no item has
+    /// that external ID, so the caller's transaction rolls back rather than
+    /// attaching rows to nothing.
+    fn attach_item_links(
+        tx: &rusqlite::Transaction<'_>,
+        external_id: &str,
+        links: &ItemLinks,
+    ) -> DatabaseResult<i64> {
+        let item_id: i64 = match tx.query_row(
+            "SELECT id FROM items WHERE externalId = ?1",
+            [external_id],
+            |row| row.get(0),
+        ) {
+            Ok(id) => id,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                return Err(DatabaseError::InconsistentData(format!(
+                    "no item has external ID {external_id}"
+                )))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        for tag in &links.tags {
+            tx.execute(
+                "INSERT OR IGNORE INTO item_tags (itemId, tag) VALUES (?1, ?2)",
+                params![item_id, tag.database_str()],
+            )?;
+        }
+        let now = Utc::now().format("%Y-%m-%d %H:%M:%S%.f").to_string();
+        for name in &links.user_tags {
+            tx.execute(
+                "INSERT OR IGNORE INTO tags (itemId, name, createdAt) VALUES (?1, ?2, ?3)",
+                params![item_id, name, now],
+            )?;
+        }
+        if let Some(fire_at_unix) = links.reminder_at_unix {
+            let fire_at = DateTime::from_timestamp(fire_at_unix, 0).ok_or_else(|| {
+                DatabaseError::InconsistentData(format!("{fire_at_unix} is not a valid time"))
+            })?;
+            tx.execute(
+                "INSERT INTO reminders (itemId, fireAt) VALUES (?1, ?2)
+                 ON CONFLICT(itemId) DO UPDATE SET fireAt = excluded.fireAt",
+                params![item_id, fire_at.format("%Y-%m-%d %H:%M:%S%.f").to_string()],
+            )?;
+        }
+        if links.immutable {
+            tx.execute(
+                "INSERT OR IGNORE INTO immutable_items (itemId, markedAt) VALUES (?1, ?2)",
+                params![item_id, Utc::now().to_rfc3339()],
+            )?;
+        }
+        Ok(item_id)
+    }
+
+    /// Side-table rows of every item that has any, keyed by external ID.
+    pub(crate) fn fetch_item_links(
+        &self,
+    ) -> DatabaseResult<std::collections::HashMap<String, ItemLinks>> {
+        let conn = self.get_conn()?;
+        let mut links: std::collections::HashMap<String, ItemLinks> =
+            std::collections::HashMap::new();
+        // Every row must belong to an item with an external ID; `open`
+        // backfills them, so a gap here means the database is damaged.
+        let external_id = |id: Option<String>| {
+            id.ok_or_else(|| {
+                DatabaseError::InconsistentData("item is missing its external ID".into())
+            })
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT i.externalId, t.tag FROM item_tags t JOIN items i ON i.id = t.itemId ORDER BY t.tag",
+        )?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for (id, tag) in rows {
+            let tag = ItemTag::from_database_str(&tag).map_err(DatabaseError::InconsistentData)?;
+            links.entry(external_id(id)?).or_default().tags.push(tag);
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT i.externalId, t.name FROM tags t JOIN items i ON i.id = t.itemId ORDER BY t.name",
+        )?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for (id, name) in rows {
+            links
+                .entry(external_id(id)?)
+                .or_default()
+                .user_tags
+                .push(name);
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT i.externalId, r.fireAt FROM reminders r JOIN items i ON i.id = r.itemId",
+        )?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for (id, fire_at) in rows {
+            links.entry(external_id(id)?).or_default().reminder_at_unix =
+                Some(parse_db_timestamp(&fire_at).timestamp());
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT i.externalId FROM immutable_items m JOIN items i ON i.id = m.itemId",
+        )?;
+        let rows = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for id in rows {
+            links.entry(external_id(id)?).or_default().immutable = true;
+        }
+        Ok(links)
+    }
 
+    /// Insert `item` within `tx`. Returns its row ID and the external ID it
+    /// was stored under.
+    fn insert_item_in(
+        &self,
+        tx: &rusqlite::Transaction<'_>,
+        item: &StoredItem,
+    ) -> DatabaseResult<(i64, String)> {
         let (timestamp_str, content_type, content_text) = Self::base_item_fields(item);
+        let app_id = Self::resolve_app_id(tx, item)?;
+        // An external ID already in use, say from a copy of this item imported
+        // under another item ID, must not take over the existing link.
+        let external_id_taken: bool = tx.query_row(
+            "SELECT EXISTS(SELECT 1 FROM items WHERE externalId = ?1)",
+            [&item.external_id],
+            |row| row.get(0),
+        )?;
+        let external_id = if external_id_taken || item.external_id.is_empty() {
+            StoredItem::external_id_at(item.timestamp_unix)
+        } else {
+            item.external_id.clone()
+        };
 
         tx.execute(
-            r#"INSERT INTO items (item_id, contentType, contentHash, content, timestamp, sourceApp, sourceAppBundleId, thumbnail, colorRgba)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"#,
+            r#"INSERT INTO items (item_id, contentType, contentHash, content, timestamp, sourceApp, sourceAppBundleId, thumbnail, colorRgba, appId, externalId)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"#,
             params![
                 item.item_id,
                 content_type,
                 item.content_hash,
-                content_text,
+                self.seal_text(&content_text),
                 timestamp_str,
                 item.source_app,
                 item.source_app_bundle_id,
-                item.thumbnail,
+                item.thumbnail.as_deref().map(|thumbnail| self.seal_payload(thumbnail)),
                 item.color_rgba,
+                app_id,
+                external_id,
             ],
         )?;
         let item_id = tx.last_insert_rowid();
-        Self::write_child_rows(&tx, item_id, item)?;
-
-        tx.commit()?;
-        Ok(item_id)
+        self.write_child_rows(tx, item_id, item)?;
+        Ok((item_id, external_id))
     }
 
     /// Replace an existing clipboard item while preserving its local row ID.
@@ -507,6 +1851,7 @@ impl Database {
         let conn = self.get_conn()?;
         let tx = conn.unchecked_transaction()?;
         let (timestamp_str, content_type, content_text) = Self::base_item_fields(item);
+        let app_id = Self::resolve_app_id(&tx, item)?;
 
         tx.execute(
             r#"UPDATE items
@@ -517,34 +1862,77 @@ impl Database {
                    sourceApp = ?5,
                    sourceAppBundleId = ?6,
                    thumbnail = ?7,
-                   colorRgba = ?8
-               WHERE id = ?9"#,
+                   colorRgba = ?8,
+                   appId = ?9
+               WHERE id = ?10"#,
             params![
                 content_type,
                 item.content_hash,
-                content_text,
+                self.seal_text(&content_text),
                 timestamp_str,
                 item.source_app,
                 item.source_app_bundle_id,
-                item.thumbnail,
+                item.thumbnail
+                    .as_deref()
+                    .map(|thumbnail| self.seal_payload(thumbnail)),
                 item.color_rgba,
+                app_id,
                 item_id,
             ],
         )?;
 
         tx.execute("DELETE FROM text_items WHERE itemId = ?1", params![item_id])?;
+        tx.execute(
+            "DELETE FROM rich_text_items WHERE itemId = ?1",
+            params![item_id],
+        )?;
         tx.execute(
             "DELETE FROM image_items WHERE itemId = ?1",
             params![item_id],
         )?;
         tx.execute("DELETE FROM link_items WHERE itemId = ?1", params![item_id])?;
         tx.execute("DELETE FROM file_items WHERE itemId = ?1", params![item_id])?;
-        Self::write_child_rows(&tx, item_id, item)?;
+        tx.execute("DELETE FROM entities WHERE itemId = ?1", params![item_id])?;
+        self.write_child_rows(&tx, item_id, item)?;
 
         tx.commit()?;
         Ok(())
     }
 
+    /// The `apps` row for the item's source app, created on first sight.
+    /// Items without a bundle ID or name have none.
+    fn resolve_app_id(
+        conn: &rusqlite::Connection,
+        item: &StoredItem,
+    ) -> DatabaseResult<Option<i64>> {
+        let non_empty = |value: &Option<String>| value.clone().filter(|value| !value.is_empty());
+        let name = non_empty(&item.source_app);
+        let app_id = match non_empty(&item.source_app_bundle_id) {
+            Some(bundle_id) => conn.query_row(
+                "INSERT INTO apps (bundleId, name) VALUES (?1, ?2)
+                 ON CONFLICT(bundleId) DO UPDATE SET name = COALESCE(excluded.name, apps.name)
+                 RETURNING id",
+                params![bundle_id, name],
+                |row| row.get(0),
+            )?,
+            None => match name {
+                Some(name) => {
+                    conn.execute(
+                        "INSERT OR IGNORE INTO apps (bundleId, name) VALUES (NULL, ?1)",
+                        [&name],
+                    )?;
+                    conn.query_row(
+                        "SELECT id FROM apps WHERE bundleId IS NULL AND name = ?1",
+                        [&name],
+                        |row| row.get(0),
+                    )?
+                }
+                None => return Ok(None),
+            },
+        };
+        Ok(Some(app_id))
+    }
+
     fn base_item_fields(item: &StoredItem) -> (String, String, String) {
         let timestamp = Utc
             .timestamp_opt(item.timestamp_unix, 0)
@@ -556,17 +1944,119 @@ impl Database {
         (timestamp_str, content_type, content_text)
     }
 
+    /// The row index-only search renders for `item`, matching what
+    /// `fetch_search_row_metadata_by_string_ids` reads from SQL.
+    pub(crate) fn indexed_row(item: &StoredItem) -> IndexedRow {
+        let (_, db_type, content_text) = Self::base_item_fields(item);
+        let first_file = match &item.content {
+            ClipboardContent::File { files, .. } => files.first(),
+            _ => None,
+        };
+        IndexedRow {
+            content_hash: item.content_hash.clone(),
+            db_type,
+            content_prefix: content_text
+                .chars()
+                .take(SEARCH_METADATA_PREFIX_CHARS)
+                .collect(),
+            source_app: item.source_app.clone(),
+            source_app_bundle_id: item.source_app_bundle_id.clone(),
+            color_rgba: item.color_rgba,
+            first_file_uti: first_file.map(|file| file.uti.clone()),
+            first_file_name: first_file.map(|file| file.filename.clone()),
+            thumbnail: item.thumbnail.clone(),
+            ocr_text: item.ocr_text.clone(),
+        }
+    }
+
+    /// Search metadata built from a stored index row instead of SQL.
+    pub(crate) fn search_row_metadata_from_index(
+        item_id: &str,
+        timestamp_unix: i64,
+        row: IndexedRow,
+        presentation: ListPresentationProfile,
+    ) -> SearchRowMetadata {
+        let icon = select_icon(IconSource {
+            db_type: &row.db_type,
+            text: &row.content_prefix,
+            color_rgba: row.color_rgba,
+            thumbnail: row.thumbnail,
+            source_app_bundle_id: row.source_app_bundle_id.as_deref(),
+            first_file: first_file_pair(&row.first_file_uti, &row.first_file_name),
+        });
+        SearchRowMetadata {
+            content_hash: row.content_hash,
+            db_type: row.db_type,
+            row_metadata: RowMetadata {
+                item_metadata: ItemMetadata {
+                    item_id: item_id.to_string(),
+                    icon,
+                    source_app: row.source_app,
+                    source_app_bundle_id: row.source_app_bundle_id,
+                    timestamp_unix,
+                    tags: Vec::new(),
+                    is_pinned: false,
+                },
+                baseline_excerpt: BaselineExcerpt {
+                    text: generate_preview_for_profile(&row.content_prefix, presentation),
+                },
+            },
+        }
+    }
+
     fn write_child_rows(
+        &self,
         tx: &rusqlite::Transaction<'_>,
         item_id: i64,
         item: &StoredItem,
     ) -> DatabaseResult<()> {
         match &item.content {
-            ClipboardContent::Text { value } | ClipboardContent::Color { value } => {
-                tx.execute(
-                    "INSERT INTO text_items (itemId, value) VALUES (?1, ?2)",
-                    params![item_id, value],
-                )?;
+            ClipboardContent::Text { value }
+            | ClipboardContent::Color { value }
+            | ClipboardContent::RichText { value, .. }
+            | ClipboardContent::PhoneNumber { value }
+            | ClipboardContent::PostalAddress { value } => {
+                match self.cipher() {
+                    Some(cipher) => tx.execute(
+                        "INSERT INTO text_items (itemId, value) VALUES (?1, ?2)",
+                        params![item_id, cipher.seal(value.as_bytes())],
+                    )?,
+                    None => tx.execute(
+                        "INSERT INTO text_items (itemId, value) VALUES (?1, ?2)",
+                        params![item_id, value],
+                    )?,
+                };
+                if matches!(
+                    item.content,
+                    ClipboardContent::Text { .. } | ClipboardContent::RichText { .. }
+                ) {
+                    write_entities(tx, item_id, value)?;
+                }
+                if let ClipboardContent::RichText { rtf_data, html, .. } = &item.content {
+                    tx.execute(
+                        "INSERT INTO rich_text_items (itemId, rtfData, htmlData) VALUES (?1, ?2, ?3)",
+                        params![
+                            item_id,
+                            rtf_data.as_deref().map(|rtf| self.seal_payload(rtf)),
+                            html.as_deref().map(|html| self.seal_payload(html.as_bytes())),
+                        ],
+                    )?;
+                }
+            }
+            // The render lives in `items.content`; the Markdown source is
+            // the item's value.
+            ClipboardContent::Markdown { value, source } => {
+                match self.cipher() {
+                    Some(cipher) => tx.execute(
+                        "INSERT INTO text_items (itemId, value) VALUES (?1, ?2)",
+                        params![item_id, cipher.seal(source.as_bytes())],
+                    )?,
+                    None => tx.execute(
+                        "INSERT INTO text_items (itemId, value) VALUES (?1, ?2)",
+                        params![item_id, source],
+                    )?,
+                };
+                write_entities(tx, item_id, value)?;
             }
             ClipboardContent::Image {
                 data,
@@ -574,8 +2064,14 @@ impl Database {
                 is_animated,
             } => {
                 tx.execute(
-                    "INSERT INTO image_items (itemId, data, description, is_animated) VALUES (?1, ?2, ?3, ?4)",
-                    params![item_id, data, description, *is_animated as i32],
+                    "INSERT INTO image_items (itemId, data, description, is_animated, ocrText) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        item_id,
+                        self.seal_payload(data),
+                        self.seal_text(description),
+                        *is_animated as i32,
+                        item.ocr_text.as_deref().map(|text| self.seal_text(text))
+                    ],
                 )?;
             }
             ClipboardContent::Link {
@@ -585,7 +2081,14 @@ impl Database {
                 let (title, description, _) = metadata_state.to_database_fields();
                 tx.execute(
                     "INSERT INTO link_items (itemId, url, title, description) VALUES (?1, ?2, ?3, ?4)",
-                    params![item_id, url, title, description],
+                    params![
+                        item_id,
+                        self.seal_text(url),
+                        title.as_deref().map(|title| self.seal_text(title)),
+                        description
+                            .as_deref()
+                            .map(|description| self.seal_text(description))
+                    ],
                 )?;
             }
             ClipboardContent::File { files, .. } => {
@@ -597,14 +2100,27 @@ impl Database {
                         preview_data,
                         preview_truncated,
                     ) = Self::file_preview_database_fields(&file.preview);
+                    // The same file captured again describes it as it is now.
+                    let entry_hash = file_entry_hash(&file.path, &file.bookmark_data);
                     tx.execute(
-                        r#"INSERT INTO file_items
-                           (itemId, ordinal, path, filename, fileSize, uti, bookmarkData, fileStatus,
-                            previewKind, previewReason, previewText, previewData, previewTruncated)
-                           VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)"#,
+                        r#"INSERT INTO file_entries
+                           (entryHash, path, filename, fileSize, uti, bookmarkData, fileStatus,
+                            previewKind, previewReason, previewText, previewData, previewTruncated,
+                            fileKind)
+                           VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+                           ON CONFLICT(entryHash) DO UPDATE SET
+                               filename = excluded.filename,
+                               fileSize = excluded.fileSize,
+                               uti = excluded.uti,
+                               fileStatus = excluded.fileStatus,
+                               previewKind = excluded.previewKind,
+                               previewReason = excluded.previewReason,
+                               previewText = excluded.previewText,
+                               previewData = excluded.previewData,
+                               previewTruncated = excluded.previewTruncated,
+                               fileKind = excluded.fileKind"#,
                         params![
-                            item_id,
-                            ordinal as i64,
+                            entry_hash,
                             file.path,
                             file.filename,
                             file.file_size as i64,
@@ -616,8 +2132,14 @@ impl Database {
                             preview_text,
                             preview_data,
                             preview_truncated,
+                            file_kind::kind_of(&file.uti, &file.filename).database_str(),
                         ],
                     )?;
+                    tx.execute(
+                        "INSERT INTO file_items (itemId, ordinal, entryId)
+                         SELECT ?1, ?2, id FROM file_entries WHERE entryHash = ?3",
+                        params![item_id, ordinal as i64, entry_hash],
+                    )?;
                 }
             }
         }
@@ -656,11 +2178,16 @@ impl Database {
         }
     }
 
-    /// Find an existing item by content hash
+    /// Find an existing item by content hash. Trashed items don't count, so
+    /// copying one again saves a new item.
+    #[tracing::instrument(level = "debug", name = "db.find_by_hash", skip_all)]
     pub fn find_by_hash(&self, hash: &str) -> DatabaseResult<Option<StoredItem>> {
         let conn = self.get_conn()?;
         let result = conn.query_row(
-            "SELECT id, contentType, contentHash, content, timestamp, sourceApp, sourceAppBundleId, thumbnail, colorRgba, item_id FROM items WHERE contentHash = ?1 LIMIT 1",
+            &format!(
+                "SELECT {} FROM items WHERE contentHash = ?1 AND deletedAt IS NULL LIMIT 1",
+                self.base_item_columns()
+            ),
             [hash],
             Self::row_to_base_item,
         );
@@ -668,7 +2195,7 @@ impl Database {
         match result {
             Ok(mut item) => {
                 if let Some(id) = item.id {
-                    Self::populate_child_content(&conn, &mut item, id)?;
+                    self.populate_child_content(&conn, &mut item, id)?;
                 }
                 Ok(Some(item))
             }
@@ -700,109 +2227,465 @@ impl Database {
         let conn = self.get_conn()?;
         conn.execute(
             "UPDATE link_items SET title = ?1, description = ?2 WHERE itemId = ?3",
-            params![title.unwrap_or(""), description, id],
+            params![
+                self.seal_text(title.unwrap_or("")),
+                description.map(|description| self.seal_text(description)),
+                id
+            ],
         )?;
         // Store link preview image as items.thumbnail
         conn.execute(
             "UPDATE items SET thumbnail = ?1 WHERE id = ?2",
-            params![image_data, id],
+            params![image_data.map(|image| self.seal_payload(image)), id],
         )?;
         Ok(())
     }
 
-    /// Update image description
-    pub fn update_image_description(&self, id: i64, description: &str) -> DatabaseResult<()> {
+    /// Store media info for the file at `file_index` of a file item. A frame
+    /// grab becomes the file's image preview and, for the first file, the
+    /// item's list thumbnail. Returns false when the item has no such file.
+    pub fn set_file_media_info(
+        &self,
+        item_id: i64,
+        file_index: u64,
+        info: &MediaInfo,
+        frame_grab: Option<&[u8]>,
+    ) -> DatabaseResult<bool> {
         let conn = self.get_conn()?;
-        // Update both the denormalized content in items and the child table
-        conn.execute(
-            "UPDATE items SET content = ?1 WHERE id = ?2 AND contentType = 'image'",
-            params![description, id],
-        )?;
-        conn.execute(
-            "UPDATE image_items SET description = ?1 WHERE itemId = ?2",
-            params![description, id],
+        let tx = conn.unchecked_transaction()?;
+        let Some(entry_id) = file_entry_id(&tx, item_id, file_index)? else {
+            return Ok(false);
+        };
+        tx.execute(
+            "UPDATE file_entries SET mediaDurationMs = ?1, mediaCodec = ?2, mediaWidth = ?3, mediaHeight = ?4
+             WHERE id = ?5",
+            params![
+                info.duration_ms.map(|ms| ms as i64),
+                info.codec,
+                info.width,
+                info.height,
+                entry_id
+            ],
         )?;
-        Ok(())
+        if let Some(frame) = frame_grab {
+            set_file_image_preview(&tx, self.cipher(), item_id, file_index, entry_id, frame)?;
+        }
+        tx.commit()?;
+        Ok(true)
     }
 
-    /// Update text item content in-place
-    pub fn update_text_item(&self, id: i64, text: &str, content_hash: &str) -> DatabaseResult<()> {
+    /// Media info of each file of a file item that has any, by file index.
+    pub fn fetch_file_media_info(&self, item_id: i64) -> DatabaseResult<Vec<(u64, MediaInfo)>> {
         let conn = self.get_conn()?;
-        // Update the denormalized content in items table and the hash
-        conn.execute(
-            "UPDATE items SET content = ?1, contentHash = ?2 WHERE id = ?3 AND contentType = 'text'",
-            params![text, content_hash, id],
-        )?;
-        // Update the child table
-        conn.execute(
-            "UPDATE text_items SET value = ?1 WHERE itemId = ?2",
-            params![text, id],
+        let mut stmt = conn.prepare(
+            "SELECT f.ordinal, e.mediaDurationMs, e.mediaCodec, e.mediaWidth, e.mediaHeight
+             FROM file_items f JOIN file_entries e ON e.id = f.entryId
+             WHERE f.itemId = ?1
+               AND COALESCE(e.mediaDurationMs, e.mediaCodec, e.mediaWidth, e.mediaHeight) IS NOT NULL
+             ORDER BY f.ordinal",
         )?;
-        Ok(())
+        let rows = stmt.query_map([item_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)? as u64,
+                MediaInfo {
+                    duration_ms: row.get::<_, Option<i64>>(1)?.map(|ms| ms as u64),
+                    codec: row.get(2)?,
+                    width: row.get(3)?,
+                    height: row.get(4)?,
+                },
+            ))
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
     }
 
-    /// Delete an item by ID (CASCADE handles child tables)
-    pub fn delete_item(&self, id: i64) -> DatabaseResult<()> {
+    /// Store document info for the PDF at `file_index` of a file item. A
+    /// render of page one becomes the file's image preview and, for the first
+    /// file, the item's list thumbnail. Returns false when the item has no
+    /// such file.
+    pub fn set_file_document_info(
+        &self,
+        item_id: i64,
+        file_index: u64,
+        info: &DocumentInfo,
+        first_page: Option<&[u8]>,
+    ) -> DatabaseResult<bool> {
         let conn = self.get_conn()?;
-        conn.execute("DELETE FROM items WHERE id = ?1", [id])?;
-        Ok(())
+        let tx = conn.unchecked_transaction()?;
+        let Some(entry_id) = file_entry_id(&tx, item_id, file_index)? else {
+            return Ok(false);
+        };
+        tx.execute(
+            "UPDATE file_entries SET pdfPageCount = ?1, pdfTitle = ?2, pdfAuthor = ?3 WHERE id = ?4",
+            params![info.page_count, info.title, info.author, entry_id],
+        )?;
+        if let Some(page) = first_page {
+            set_file_image_preview(&tx, self.cipher(), item_id, file_index, entry_id, page)?;
+        }
+        tx.commit()?;
+        Ok(true)
     }
 
-    /// Delete all items (CASCADE handles children).
-    ///
-    /// Clearing history must leave no recoverable plaintext residue. With
-    /// `secure_delete=ON` the DELETE zeroes freed pages; VACUUM then rewrites the
-    /// database file to reclaim and overwrite slack space, and a TRUNCATE
-    /// checkpoint shrinks the WAL so no stale content survives there either.
-    pub fn clear_all(&self) -> DatabaseResult<()> {
+    /// Document info of each file of a file item that has any, by file index.
+    pub fn fetch_file_document_info(
+        &self,
+        item_id: i64,
+    ) -> DatabaseResult<Vec<(u64, DocumentInfo)>> {
         let conn = self.get_conn()?;
-        conn.execute("DELETE FROM items", [])?;
-        // VACUUM cannot run inside a transaction; execute it standalone.
-        conn.execute_batch("VACUUM;")?;
-        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
-        Ok(())
+        let mut stmt = conn.prepare(
+            "SELECT f.ordinal, e.pdfPageCount, e.pdfTitle, e.pdfAuthor
+             FROM file_items f JOIN file_entries e ON e.id = f.entryId
+             WHERE f.itemId = ?1
+               AND COALESCE(e.pdfPageCount, e.pdfTitle, e.pdfAuthor) IS NOT NULL
+             ORDER BY f.ordinal",
+        )?;
+        let rows = stmt.query_map([item_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)? as u64,
+                DocumentInfo {
+                    page_count: row.get(1)?,
+                    title: row.get(2)?,
+                    author: row.get(3)?,
+                },
+            ))
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
     }
 
-    /// Fetch lightweight item metadata for list display.
-    /// No JOINs needed — `thumbnail` covers link images too.
-    pub(crate) fn fetch_browse_row_metadata(
+    /// Store email info for the file at `file_index` of a file item. Returns
+    /// false when the item has no such file.
+    pub fn set_file_email_info(
         &self,
-        before_timestamp: Option<DateTime<Utc>>,
-        limit: usize,
+        item_id: i64,
+        file_index: u64,
+        info: &EmailInfo,
+    ) -> DatabaseResult<bool> {
+        let conn = self.get_conn()?;
+        let Some(entry_id) = file_entry_id(&conn, item_id, file_index)? else {
+            return Ok(false);
+        };
+        conn.execute(
+            "UPDATE file_entries SET emailSubject = ?1, emailSender = ?2, emailSentAt = ?3
+             WHERE id = ?4",
+            params![info.subject, info.sender, info.sent_at_unix, entry_id],
+        )?;
+        Ok(true)
+    }
+
+    /// Email info of each file of a file item that has any, by file index.
+    pub fn fetch_file_email_info(&self, item_id: i64) -> DatabaseResult<Vec<(u64, EmailInfo)>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT f.ordinal, e.emailSubject, e.emailSender, e.emailSentAt
+             FROM file_items f JOIN file_entries e ON e.id = f.entryId
+             WHERE f.itemId = ?1
+               AND COALESCE(e.emailSubject, e.emailSender, e.emailSentAt) IS NOT NULL
+             ORDER BY f.ordinal",
+        )?;
+        let rows = stmt.query_map([item_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)? as u64,
+                EmailInfo {
+                    subject: row.get(1)?,
+                    sender: row.get(2)?,
+                    sent_at_unix: row.get(3)?,
+                },
+            ))
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Store the archive listing for the file at `file_index` of a file item.
+    /// Returns false when the item has no such file.
+    pub fn set_file_archive_listing(
+        &self,
+        item_id: i64,
+        file_index: u64,
+        listing: &ArchiveListing,
+    ) -> DatabaseResult<bool> {
+        let conn = self.get_conn()?;
+        let Some(entry_id) = file_entry_id(&conn, item_id, file_index)? else {
+            return Ok(false);
+        };
+        conn.execute(
+            "UPDATE file_entries SET archiveEntries = ?1, archiveTruncated = ?2 WHERE id = ?3",
+            params![listing.entries.join("\n"), listing.truncated, entry_id],
+        )?;
+        Ok(true)
+    }
+
+    /// Archive listing of each file of a file item that has one, by file index.
+    pub fn fetch_file_archive_listings(
+        &self,
+        item_id: i64,
+    ) -> DatabaseResult<Vec<(u64, ArchiveListing)>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT f.ordinal, e.archiveEntries, e.archiveTruncated
+             FROM file_items f JOIN file_entries e ON e.id = f.entryId
+             WHERE f.itemId = ?1 AND e.archiveEntries IS NOT NULL
+             ORDER BY f.ordinal",
+        )?;
+        let rows = stmt.query_map([item_id], |row| {
+            let entries: String = row.get(1)?;
+            Ok((
+                row.get::<_, i64>(0)? as u64,
+                ArchiveListing {
+                    entries: entries.lines().map(str::to_string).collect(),
+                    truncated: row.get::<_, Option<bool>>(2)?.unwrap_or(false),
+                },
+            ))
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Update image description
+    pub fn update_image_description(&self, id: i64, description: &str) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        // Update both the denormalized content in items and the child table
+        conn.execute(
+            "UPDATE items SET content = ?1 WHERE id = ?2 AND contentType = 'image'",
+            params![self.seal_text(description), id],
+        )?;
+        conn.execute(
+            "UPDATE image_items SET description = ?1 WHERE itemId = ?2",
+            params![self.seal_text(description), id],
+        )?;
+        Ok(())
+    }
+
+    /// Replace an image's recognized text; `None` clears it. Returns false
+    /// when the item is not an image.
+    pub fn update_image_ocr_text(&self, id: i64, text: Option<&str>) -> DatabaseResult<bool> {
+        let conn = self.get_conn()?;
+        let updated = conn.execute(
+            "UPDATE image_items SET ocrText = ?1 WHERE itemId = ?2",
+            params![text.map(|text| self.seal_text(text)), id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Update text item content in-place
+    pub fn update_text_item(&self, id: i64, text: &str, content_hash: &str) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        let tx = conn.unchecked_transaction()?;
+        // Update the denormalized content in items table and the hash
+        let updated = tx.execute(
+            "UPDATE items SET content = ?1, contentHash = ?2 WHERE id = ?3 AND contentType = 'text'",
+            params![self.seal_text(text), content_hash, id],
+        )?;
+        // Update the child table
+        match self.cipher() {
+            Some(cipher) => tx.execute(
+                "UPDATE text_items SET value = ?1 WHERE itemId = ?2",
+                params![cipher.seal(text.as_bytes()), id],
+            )?,
+            None => tx.execute(
+                "UPDATE text_items SET value = ?1 WHERE itemId = ?2",
+                params![text, id],
+            )?,
+        };
+        if updated > 0 {
+            write_entities(&tx, id, text)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Entities found in an item's text, in source order.
+    pub fn fetch_entities(&self, id: i64) -> DatabaseResult<Vec<DetectedEntity>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT kind, value, utf16Start, utf16End FROM entities
+             WHERE itemId = ?1 ORDER BY ordinal",
+        )?;
+        let rows = stmt
+            .query_map([id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut entities = Vec::with_capacity(rows.len());
+        for (kind, value, start, end) in rows {
+            entities.push(DetectedEntity {
+                kind: EntityKind::from_database_str(&kind)
+                    .map_err(DatabaseError::InconsistentData)?,
+                value,
+                utf16_start: start as u64,
+                utf16_end: end as u64,
+            });
+        }
+        Ok(entities)
+    }
+
+    /// Row IDs of file items outside the trash holding the file at `path`,
+    /// newest first.
+    pub(crate) fn fetch_item_ids_with_file(&self, path: &str) -> DatabaseResult<Vec<i64>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id FROM items
+             WHERE deletedAt IS NULL AND id IN (
+                 SELECT f.itemId FROM file_entries e JOIN file_items f ON f.entryId = e.id
+                 WHERE e.path = ?1
+             )
+             ORDER BY timestamp DESC, id DESC",
+        )?;
+        let ids = stmt
+            .query_map([path], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ids)
+    }
+
+    /// Delete an item by ID (CASCADE handles child tables)
+    pub fn delete_item(&self, id: i64) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute("DELETE FROM items WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    /// Move an item to the trash. Returns false if it was already there.
+    pub(crate) fn trash_item(&self, id: i64, now: DateTime<Utc>) -> DatabaseResult<bool> {
+        let conn = self.get_conn()?;
+        let updated = conn.execute(
+            "UPDATE items SET deletedAt = ?1 WHERE id = ?2 AND deletedAt IS NULL",
+            params![now.format("%Y-%m-%d %H:%M:%S%.f").to_string(), id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Take an item back out of the trash under `item_id`. Returns false if
+    /// it wasn't there.
+    pub(crate) fn restore_item(&self, id: i64, item_id: &str) -> DatabaseResult<bool> {
+        let conn = self.get_conn()?;
+        let updated = conn.execute(
+            "UPDATE items SET deletedAt = NULL, item_id = ?2 WHERE id = ?1 AND deletedAt IS NOT NULL",
+            params![id, item_id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    pub(crate) fn is_trashed(&self, id: i64) -> DatabaseResult<bool> {
+        let conn = self.get_conn()?;
+        let trashed = conn.query_row(
+            "SELECT deletedAt IS NOT NULL FROM items WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        );
+        match trashed {
+            Ok(trashed) => Ok(trashed),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Trashed items as `(row_id, deleted_at_unix)`, most recently trashed
+    /// first.
+    pub(crate) fn fetch_trashed_items(&self) -> DatabaseResult<Vec<(i64, i64)>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, deletedAt FROM items WHERE deletedAt IS NOT NULL
+             ORDER BY deletedAt DESC, id DESC",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                let deleted_at: String = row.get(1)?;
+                Ok((row.get(0)?, parse_db_timestamp(&deleted_at).timestamp()))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Permanently delete items trashed at or before `cutoff`. Returns
+    /// their item IDs.
+    pub(crate) fn purge_trash(&self, cutoff: DateTime<Utc>) -> DatabaseResult<Vec<String>> {
+        let conn = self.get_conn()?;
+        let tx = conn.unchecked_transaction()?;
+        let cutoff = cutoff.format("%Y-%m-%d %H:%M:%S%.f").to_string();
+        let purged = tx
+            .prepare("SELECT item_id FROM items WHERE deletedAt IS NOT NULL AND deletedAt <= ?1")?
+            .query_map([&cutoff], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        tx.execute(
+            "DELETE FROM items WHERE deletedAt IS NOT NULL AND deletedAt <= ?1",
+            [&cutoff],
+        )?;
+        tx.commit()?;
+        Ok(purged)
+    }
+
+    /// Delete all items except immutable ones (CASCADE handles children).
+    ///
+    /// Clearing history must leave no recoverable plaintext residue. With
+    /// `secure_delete=ON` the DELETE zeroes freed pages; VACUUM then rewrites the
+    /// database file to reclaim and overwrite slack space, and a TRUNCATE
+    /// checkpoint shrinks the WAL so no stale content survives there either.
+    pub fn clear_all(&self) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "DELETE FROM items WHERE id NOT IN (SELECT itemId FROM immutable_items)",
+            [],
+        )?;
+        // VACUUM cannot run inside a transaction; execute it standalone.
+        conn.execute_batch("VACUUM;")?;
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        Ok(())
+    }
+
+    /// Fetch lightweight item metadata for list display.
+    /// No JOINs needed — `thumbnail` covers link images too.
+    #[tracing::instrument(level = "debug", name = "db.fetch_browse_row_metadata", skip_all)]
+    pub(crate) fn fetch_browse_row_metadata(
+        &self,
+        before_timestamp: Option<DateTime<Utc>>,
+        offset: usize,
+        limit: usize,
         filter: Option<&ContentTypeFilter>,
-        tag: Option<&ItemTag>,
+        tags: &TagRestriction,
         presentation: ListPresentationProfile,
     ) -> DatabaseResult<(Vec<RowMetadata>, u64)> {
         let conn = self.get_conn()?;
 
         let type_filter_clause = Self::content_type_where_clause(filter, "");
         let type_filter_clause_and = Self::content_type_where_clause(filter, "AND");
-        let tag_clause_where =
-            Self::tag_where_clause(tag, type_filter_clause.is_empty(), "WHERE", "AND");
-        let tag_clause_and = Self::tag_where_clause(tag, false, "WHERE", "AND");
+        let (tag_clause_where, tag_params) =
+            Self::tag_where_clause(tags, type_filter_clause.is_empty(), "WHERE", "AND");
+        let (tag_clause_and, _) = Self::tag_where_clause(tags, false, "WHERE", "AND");
 
         let count_sql = format!(
             "SELECT COUNT(*) FROM items {} {}",
             type_filter_clause, tag_clause_where
         );
-        let total_count: i64 = if let Some(tag) = tag {
-            conn.query_row(&count_sql, params![tag.database_str()], |row| row.get(0))?
-        } else {
-            conn.query_row(&count_sql, [], |row| row.get(0))?
-        };
+        let total_count: i64 = conn.query_row(
+            &count_sql,
+            rusqlite::params_from_iter(tag_params.iter()),
+            |row| row.get(0),
+        )?;
         let total_count = total_count as u64;
 
         let sql = if before_timestamp.is_some() {
             format!(
-                r#"SELECT id, substr(ltrim(content, char(9) || char(10) || char(13) || ' '), 1, {}), contentType, timestamp, sourceApp, sourceAppBundleId, thumbnail, colorRgba, item_id
-                   FROM items WHERE timestamp < ? {} {} ORDER BY timestamp DESC LIMIT ?"#,
-                BROWSE_METADATA_PREFIX_CHARS, type_filter_clause_and, tag_clause_and
+                r#"SELECT id, substr(ltrim({}, char(9) || char(10) || char(13) || ' '), 1, {}), contentType, timestamp, sourceApp, sourceAppBundleId, {}, colorRgba, item_id, {}
+                   FROM items WHERE timestamp < ? {} {} ORDER BY pinned DESC, timestamp DESC, id DESC LIMIT ? OFFSET ?"#,
+                self.unsealed("content"),
+                BROWSE_METADATA_PREFIX_CHARS,
+                self.unsealed("thumbnail"),
+                FIRST_FILE_ICON_COLUMNS,
+                type_filter_clause_and,
+                tag_clause_and
             )
         } else {
             format!(
-                r#"SELECT id, substr(ltrim(content, char(9) || char(10) || char(13) || ' '), 1, {}), contentType, timestamp, sourceApp, sourceAppBundleId, thumbnail, colorRgba, item_id
-                   FROM items {} {} ORDER BY timestamp DESC LIMIT ?"#,
-                BROWSE_METADATA_PREFIX_CHARS, type_filter_clause, tag_clause_where
+                r#"SELECT id, substr(ltrim({}, char(9) || char(10) || char(13) || ' '), 1, {}), contentType, timestamp, sourceApp, sourceAppBundleId, {}, colorRgba, item_id, {}
+                   FROM items {} {} ORDER BY pinned DESC, timestamp DESC, id DESC LIMIT ? OFFSET ?"#,
+                self.unsealed("content"),
+                BROWSE_METADATA_PREFIX_CHARS,
+                self.unsealed("thumbnail"),
+                FIRST_FILE_ICON_COLUMNS,
+                type_filter_clause,
+                tag_clause_where
             )
         };
 
@@ -810,21 +2693,18 @@ impl Database {
         let raw_items = if let Some(ts) = before_timestamp {
             let ts_str = ts.format("%Y-%m-%d %H:%M:%S%.f").to_string();
             let mut param_values: Vec<rusqlite::types::Value> = vec![ts_str.into()];
-            if let Some(tag) = tag {
-                param_values.push(tag.database_str().to_string().into());
-            }
+            param_values.extend(tag_params);
             param_values.push((limit as i64).into());
+            param_values.push((offset as i64).into());
             stmt.query_map(
                 rusqlite::params_from_iter(param_values),
                 Self::row_to_raw_row_metadata,
             )?
             .collect::<Result<Vec<_>, _>>()?
         } else {
-            let mut param_values: Vec<rusqlite::types::Value> = Vec::new();
-            if let Some(tag) = tag {
-                param_values.push(tag.database_str().to_string().into());
-            }
+            let mut param_values = tag_params;
             param_values.push((limit as i64).into());
+            param_values.push((offset as i64).into());
             stmt.query_map(
                 rusqlite::params_from_iter(param_values),
                 Self::row_to_raw_row_metadata,
@@ -845,7 +2725,178 @@ impl Database {
         Ok((items, total_count))
     }
 
+    /// Rows for the menu bar quick list, pinned first and then newest, with
+    /// one-line previews. Thumbnails are not loaded.
+    pub(crate) fn fetch_quick_list_rows(&self, limit: usize) -> DatabaseResult<Vec<QuickListRow>> {
+        let conn = self.get_conn()?;
+        let sql = format!(
+            r#"SELECT item_id, substr(ltrim({}, char(9) || char(10) || char(13) || ' '), 1, {}), contentType, timestamp, pinned, {}
+               FROM items WHERE deletedAt IS NULL
+               ORDER BY pinned DESC, timestamp DESC, id DESC LIMIT ?1"#,
+            self.unsealed("content"),
+            BROWSE_METADATA_PREFIX_CHARS,
+            FIRST_FILE_ICON_COLUMNS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map([limit as i64], |row| {
+                let content: String = row.get(1)?;
+                let content_type: Option<String> = row.get(2)?;
+                let timestamp: String = row.get(3)?;
+                let first_file_uti: Option<String> = row.get(5)?;
+                let first_file_name: Option<String> = row.get(6)?;
+                let icon = select_icon(IconSource {
+                    db_type: content_type.as_deref().unwrap_or("text"),
+                    text: &content,
+                    color_rgba: None,
+                    thumbnail: None,
+                    source_app_bundle_id: None,
+                    first_file: first_file_pair(&first_file_uti, &first_file_name),
+                });
+                Ok((
+                    row.get(0)?,
+                    generate_preview_for_profile(&content, ListPresentationProfile::CompactRow),
+                    icon.base_icon_type(),
+                    parse_db_timestamp(&timestamp).timestamp(),
+                    row.get(4)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Fetch one page of image items for grid browsing, newest first, plus the
+    /// total image count. Full image blobs are not loaded.
+    pub(crate) fn fetch_image_grid_page(
+        &self,
+        after: Option<(&str, i64)>,
+        limit: usize,
+    ) -> DatabaseResult<(Vec<ImageGridRow>, u64)> {
+        let conn = self.get_conn()?;
+        let total_count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM items WHERE contentType = 'image' AND deletedAt IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
+
+        let keyset_clause = if after.is_some() {
+            "AND (i.timestamp < ? OR (i.timestamp = ? AND i.id < ?))"
+        } else {
+            ""
+        };
+        let sql = format!(
+            r#"SELECT i.id, i.item_id, i.timestamp, {}, {}, img.width, img.height, img.is_animated
+               FROM items i JOIN image_items img ON img.itemId = i.id
+               WHERE i.contentType = 'image' AND i.deletedAt IS NULL {}
+               ORDER BY i.timestamp DESC, i.id DESC LIMIT ?"#,
+            self.unsealed("i.thumbnail"),
+            self.unsealed("img.gridThumbnail"),
+            keyset_clause
+        );
+        let mut param_values: Vec<rusqlite::types::Value> = Vec::new();
+        if let Some((timestamp, row_id)) = after {
+            param_values.push(timestamp.to_string().into());
+            param_values.push(timestamp.to_string().into());
+            param_values.push(row_id.into());
+        }
+        param_values.push((limit as i64).into());
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(param_values), |row| {
+                let timestamp: String = row.get(2)?;
+                Ok(ImageGridRow {
+                    row_id: row.get(0)?,
+                    item_id: row.get(1)?,
+                    timestamp_unix: parse_db_timestamp(&timestamp).timestamp(),
+                    timestamp,
+                    thumbnail: row.get(3)?,
+                    grid_thumbnail: row.get(4)?,
+                    width: row.get(5)?,
+                    height: row.get(6)?,
+                    is_animated: row.get::<_, i32>(7)? != 0,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((rows, total_count as u64))
+    }
+
+    /// Load the full image blob for a single image item.
+    pub(crate) fn fetch_image_data(&self, row_id: i64) -> DatabaseResult<Option<Vec<u8>>> {
+        let conn = self.get_conn()?;
+        let result = conn.query_row(
+            "SELECT data FROM image_items WHERE itemId = ?1",
+            [row_id],
+            |row| row.get(0),
+        );
+        match result {
+            Ok(data) => Ok(Some(self.open_payload(data)?)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Persist grid browsing metadata computed for an image item.
+    pub(crate) fn set_image_grid_metadata(
+        &self,
+        row_id: i64,
+        grid_thumbnail: Option<&[u8]>,
+        width: u32,
+        height: u32,
+    ) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE image_items SET gridThumbnail = ?1, width = ?2, height = ?3 WHERE itemId = ?4",
+            params![
+                grid_thumbnail.map(|thumbnail| self.seal_payload(thumbnail)),
+                width,
+                height,
+                row_id
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Fetch list metadata for the given row IDs, preserving input order.
+    pub(crate) fn fetch_row_metadata_by_ids(
+        &self,
+        ids: &[i64],
+        presentation: ListPresentationProfile,
+    ) -> DatabaseResult<Vec<RowMetadata>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.get_conn()?;
+        let placeholders = ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let sql = format!(
+            r#"SELECT id, substr(ltrim(content, char(9) || char(10) || char(13) || ' '), 1, {}), contentType, timestamp, sourceApp, sourceAppBundleId, thumbnail, colorRgba, item_id, {}
+               FROM items WHERE id IN ({})"#,
+            BROWSE_METADATA_PREFIX_CHARS, FIRST_FILE_ICON_COLUMNS, placeholders
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let params: Vec<rusqlite::types::Value> = ids.iter().map(|&id| id.into()).collect();
+        let mut by_id: std::collections::HashMap<i64, RawRowMetadata> = stmt
+            .query_map(rusqlite::params_from_iter(params), |row| {
+                Ok((row.get::<_, i64>(0)?, Self::row_to_raw_row_metadata(row)?))
+            })?
+            .collect::<Result<_, _>>()?;
+
+        Ok(ids
+            .iter()
+            .filter_map(|id| by_id.remove(id))
+            .map(|raw| RowMetadata {
+                item_metadata: raw.item_metadata,
+                baseline_excerpt: BaselineExcerpt {
+                    text: generate_preview_for_profile(&raw.content_prefix, presentation),
+                },
+            })
+            .collect())
+    }
+
     /// Fetch items by IDs, preserving the order of the input IDs
+    #[tracing::instrument(level = "debug", name = "db.fetch_items_by_ids", skip_all, fields(count = ids.len()))]
     pub fn fetch_items_by_ids(&self, ids: &[i64]) -> DatabaseResult<Vec<StoredItem>> {
         if ids.is_empty() {
             return Ok(Vec::new());
@@ -854,7 +2905,8 @@ impl Database {
         let conn = self.get_conn()?;
         let placeholders = ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
         let sql = format!(
-            "SELECT id, contentType, contentHash, content, timestamp, sourceApp, sourceAppBundleId, thumbnail, colorRgba, item_id FROM items WHERE id IN ({})",
+            "SELECT {} FROM items WHERE id IN ({})",
+            self.base_item_columns(),
             placeholders
         );
 
@@ -867,7 +2919,7 @@ impl Database {
         // Populate child content for each item
         for item in &mut items {
             if let Some(id) = item.id {
-                Self::populate_child_content(&conn, item, id)?;
+                self.populate_child_content(&conn, item, id)?;
             }
         }
 
@@ -884,6 +2936,7 @@ impl Database {
     }
 
     /// Fetch lightweight search result metadata by string item_ids, preserving order.
+    #[tracing::instrument(level = "debug", name = "db.fetch_search_row_metadata", skip_all, fields(count = item_ids.len()))]
     pub(crate) fn fetch_search_row_metadata_by_string_ids(
         &self,
         item_ids: &[&str],
@@ -896,8 +2949,11 @@ impl Database {
         let conn = self.get_conn()?;
         let placeholders = item_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
         let sql = format!(
-            "SELECT contentHash, substr(content, 1, {}), contentType, timestamp, sourceApp, sourceAppBundleId, thumbnail, colorRgba, item_id FROM items WHERE item_id IN ({})",
+            "SELECT contentHash, substr({}, 1, {}), contentType, timestamp, sourceApp, sourceAppBundleId, {}, colorRgba, item_id, {} FROM items WHERE item_id IN ({})",
+            self.unsealed("content"),
             SEARCH_METADATA_PREFIX_CHARS,
+            self.unsealed("thumbnail"),
+            FIRST_FILE_ICON_COLUMNS,
             placeholders
         );
         let mut stmt = conn.prepare(&sql)?;
@@ -969,40 +3025,109 @@ impl Database {
         Ok(result)
     }
 
-    /// Fetch items by IDs with SQLite C-level interrupt support.
-    pub fn fetch_items_by_ids_interruptible(
+    /// The IDs among `item_ids` whose locale suits `locale` (normalized).
+    pub(crate) fn filter_string_ids_by_locale(
         &self,
-        ids: &[i64],
-        token: &tokio_util::sync::CancellationToken,
-        runtime: &tokio::runtime::Handle,
-    ) -> DatabaseResult<Vec<StoredItem>> {
-        use tokio_util::task::AbortOnDropHandle;
-
-        if ids.is_empty() {
+        item_ids: &[&str],
+        locale: &str,
+    ) -> DatabaseResult<Vec<String>> {
+        if item_ids.is_empty() {
             return Ok(Vec::new());
         }
 
         let conn = self.get_conn()?;
-        let interrupt_handle = conn.get_interrupt_handle();
-
-        let token_clone = token.clone();
-        let watcher = runtime.spawn(async move {
-            token_clone.cancelled().await;
-            interrupt_handle.interrupt();
-        });
-        let _abort_guard = AbortOnDropHandle::new(watcher);
-
-        let placeholders = ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let placeholders = item_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
         let sql = format!(
-            "SELECT id, contentType, contentHash, content, timestamp, sourceApp, sourceAppBundleId, thumbnail, colorRgba, item_id FROM items WHERE id IN ({})",
-            placeholders
+            "SELECT item_id FROM items WHERE {LOCALE_MATCH_CONDITION} AND item_id IN ({placeholders})"
+        );
+        let mut params: Vec<rusqlite::types::Value> =
+            vec![locale.to_string().into(), locale.to_string().into()];
+        params.extend(
+            item_ids
+                .iter()
+                .map(|&id| rusqlite::types::Value::from(id.to_string())),
         );
-
         let mut stmt = conn.prepare(&sql)?;
-        let params: Vec<rusqlite::types::Value> = ids.iter().map(|&id| id.into()).collect();
-
-        let mut items: Vec<StoredItem> =
-            match stmt.query_map(rusqlite::params_from_iter(params), Self::row_to_base_item) {
+        let result: Vec<String> = stmt
+            .query_map(rusqlite::params_from_iter(params), |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(result)
+    }
+
+    /// The items among `item_ids` holding a file of `kind`.
+    pub(crate) fn filter_string_ids_by_file_kind(
+        &self,
+        item_ids: &[&str],
+        kind: FileKind,
+    ) -> DatabaseResult<Vec<String>> {
+        if item_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.get_conn()?;
+        let placeholders = item_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let sql = format!(
+            "SELECT item_id FROM items WHERE {FILE_KIND_CONDITION} AND item_id IN ({placeholders})"
+        );
+        let mut params: Vec<rusqlite::types::Value> = vec![kind.database_str().to_string().into()];
+        params.extend(
+            item_ids
+                .iter()
+                .map(|&id| rusqlite::types::Value::from(id.to_string())),
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let result: Vec<String> = stmt
+            .query_map(rusqlite::params_from_iter(params), |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(result)
+    }
+
+    /// Set or clear the locale an item was written for. Returns false if
+    /// there is no such item.
+    pub(crate) fn set_item_locale(&self, id: i64, locale: Option<&str>) -> DatabaseResult<bool> {
+        let conn = self.get_conn()?;
+        let changed = conn.execute(
+            "UPDATE items SET locale = ?1 WHERE id = ?2",
+            params![locale, id],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Fetch items by IDs with SQLite C-level interrupt support.
+    pub fn fetch_items_by_ids_interruptible(
+        &self,
+        ids: &[i64],
+        token: &tokio_util::sync::CancellationToken,
+        runtime: &tokio::runtime::Handle,
+    ) -> DatabaseResult<Vec<StoredItem>> {
+        use tokio_util::task::AbortOnDropHandle;
+
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.get_conn()?;
+        let interrupt_handle = conn.get_interrupt_handle();
+
+        let token_clone = token.clone();
+        let watcher = runtime.spawn(async move {
+            token_clone.cancelled().await;
+            interrupt_handle.interrupt();
+        });
+        let _abort_guard = AbortOnDropHandle::new(watcher);
+
+        let placeholders = ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let sql = format!(
+            "SELECT {} FROM items WHERE id IN ({})",
+            self.base_item_columns(),
+            placeholders
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let params: Vec<rusqlite::types::Value> = ids.iter().map(|&id| id.into()).collect();
+
+        let mut items: Vec<StoredItem> =
+            match stmt.query_map(rusqlite::params_from_iter(params), Self::row_to_base_item) {
                 Ok(rows) => rows.collect::<Result<Vec<_>, _>>()?,
                 Err(rusqlite::Error::SqliteFailure(err, _))
                     if err.code == rusqlite::ffi::ErrorCode::OperationInterrupted =>
@@ -1015,7 +3140,7 @@ impl Database {
         // Populate child content
         for item in &mut items {
             if let Some(id) = item.id {
-                Self::populate_child_content(&conn, item, id)?;
+                self.populate_child_content(&conn, item, id)?;
             }
         }
 
@@ -1035,12 +3160,13 @@ impl Database {
             .collect())
     }
 
-    /// Fetch all items (for index rebuilding)
+    /// Fetch all items outside the trash (for index rebuilding)
     pub fn fetch_all_items(&self) -> DatabaseResult<Vec<StoredItem>> {
         let conn = self.get_conn()?;
-        let mut stmt = conn.prepare(
-            "SELECT id, contentType, contentHash, content, timestamp, sourceApp, sourceAppBundleId, thumbnail, colorRgba, item_id FROM items ORDER BY timestamp DESC"
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM items WHERE deletedAt IS NULL ORDER BY timestamp DESC, id DESC",
+            self.base_item_columns()
+        ))?;
         let mut items = stmt
             .query_map([], Self::row_to_base_item)?
             .collect::<Result<Vec<_>, _>>()?;
@@ -1048,23 +3174,36 @@ impl Database {
         // Populate child content
         for item in &mut items {
             if let Some(id) = item.id {
-                Self::populate_child_content(&conn, item, id)?;
+                self.populate_child_content(&conn, item, id)?;
             }
         }
 
         Ok(items)
     }
 
-    /// Fetch all item IDs, ordered by recency.
+    /// Fetch the IDs of all items outside the trash, ordered by recency.
     pub fn fetch_all_item_ids(&self) -> DatabaseResult<Vec<i64>> {
         let conn = self.get_conn()?;
-        let mut stmt = conn.prepare("SELECT id FROM items ORDER BY timestamp DESC")?;
+        let mut stmt = conn.prepare(
+            "SELECT id FROM items WHERE deletedAt IS NULL ORDER BY timestamp DESC, id DESC",
+        )?;
         let ids = stmt
             .query_map([], |row| row.get(0))?
             .collect::<Result<Vec<i64>, _>>()?;
         Ok(ids)
     }
 
+    /// Item IDs of every item outside the trash: those the search index
+    /// should hold.
+    pub fn fetch_live_item_ids(&self) -> DatabaseResult<Vec<String>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare("SELECT item_id FROM items WHERE deletedAt IS NULL")?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(ids)
+    }
+
     /// Get IDs that would be pruned (for index deletion before database prune).
     /// Returns (row_id, item_id) pairs so callers can delete from both DB and search index.
     pub fn get_prunable_ids(
@@ -1092,8 +3231,11 @@ impl Database {
         let items_to_delete =
             std::cmp::max(100, ((current_size - target_size) / avg_item_size) as usize);
 
-        let mut stmt =
-            conn.prepare("SELECT id, item_id FROM items ORDER BY timestamp ASC LIMIT ?1")?;
+        let mut stmt = conn.prepare(
+            "SELECT id, item_id FROM items
+             WHERE pinned = 0 AND id NOT IN (SELECT itemId FROM immutable_items)
+             ORDER BY timestamp ASC LIMIT ?1",
+        )?;
         let ids: Vec<(i64, String)> = stmt
             .query_map([items_to_delete as i64], |row| {
                 Ok((row.get(0)?, row.get(1)?))
@@ -1107,33 +3249,34 @@ impl Database {
     /// Prefix-only search for very short queries (< 3 chars).
     /// Uses LIKE prefix matching which can leverage the index.
     /// Returns (id, content, timestamp) sorted by recency.
-    pub fn search_prefix_query(
+    #[tracing::instrument(level = "debug", name = "db.search_prefix_query", skip_all)]
+    pub(crate) fn search_prefix_query(
         &self,
         query: &str,
         limit: usize,
         filter: Option<&ContentTypeFilter>,
-        tag: Option<&ItemTag>,
+        tags: &TagRestriction,
     ) -> DatabaseResult<Vec<(i64, String, i64)>> {
         let conn = self.get_conn()?;
         let query_lower = query.to_lowercase();
         let escaped = query_lower.replace('%', "\\%").replace('_', "\\_");
         let type_filter_and = Self::content_type_where_clause(filter, "AND");
-        let tag_filter_and = Self::tag_where_clause(tag, false, "WHERE", "AND");
+        let (tag_filter_and, tag_params) = Self::tag_where_clause(tags, false, "WHERE", "AND");
 
         let prefix_pattern = format!("{}%", escaped);
         let sql = format!(
-            r#"SELECT id, content, CAST(strftime('%s', timestamp) AS INTEGER)
+            r#"SELECT id, {content}, CAST(strftime('%s', timestamp) AS INTEGER)
                FROM items
-               WHERE content LIKE ? ESCAPE '\' COLLATE NOCASE {} {}
-               ORDER BY timestamp DESC
+               WHERE {content} LIKE ? ESCAPE '\' COLLATE NOCASE {} {}
+               ORDER BY timestamp DESC, id DESC
                LIMIT ?"#,
-            type_filter_and, tag_filter_and
+            type_filter_and,
+            tag_filter_and,
+            content = self.unsealed("content")
         );
         let mut stmt = conn.prepare(&sql)?;
         let mut param_values: Vec<rusqlite::types::Value> = vec![prefix_pattern.into()];
-        if let Some(tag) = tag {
-            param_values.push(tag.database_str().to_string().into());
-        }
+        param_values.extend(tag_params);
         param_values.push((limit as i64).into());
         let results: Vec<(i64, String, i64)> = stmt
             .query_map(rusqlite::params_from_iter(param_values), |row| {
@@ -1150,28 +3293,33 @@ impl Database {
 
     /// Fetch recent items for short-query fallback scanning.
     /// Returns (id, content, timestamp) sorted by recency.
-    pub fn fetch_recent_items_for_short_query(
+    #[tracing::instrument(
+        level = "debug",
+        name = "db.fetch_recent_items_for_short_query",
+        skip_all
+    )]
+    pub(crate) fn fetch_recent_items_for_short_query(
         &self,
         limit: usize,
         filter: Option<&ContentTypeFilter>,
-        tag: Option<&ItemTag>,
+        tags: &TagRestriction,
     ) -> DatabaseResult<Vec<(i64, String, i64)>> {
         let conn = self.get_conn()?;
         let type_filter_where = Self::content_type_where_clause(filter, "WHERE");
-        let tag_filter_where = Self::tag_where_clause(tag, false, "WHERE", "AND");
+        let (tag_filter_where, tag_params) =
+            Self::tag_where_clause(tags, type_filter_where.is_empty(), "WHERE", "AND");
         let sql = format!(
-            r#"SELECT id, content, CAST(strftime('%s', timestamp) AS INTEGER)
+            r#"SELECT id, {}, CAST(strftime('%s', timestamp) AS INTEGER)
                FROM items
                {} {}
-               ORDER BY timestamp DESC
+               ORDER BY timestamp DESC, id DESC
                LIMIT ?"#,
-            type_filter_where, tag_filter_where
+            self.unsealed("content"),
+            type_filter_where,
+            tag_filter_where
         );
         let mut stmt = conn.prepare(&sql)?;
-        let mut param_values: Vec<rusqlite::types::Value> = Vec::new();
-        if let Some(tag) = tag {
-            param_values.push(tag.database_str().to_string().into());
-        }
+        let mut param_values = tag_params;
         param_values.push((limit as i64).into());
         let results: Vec<(i64, String, i64)> = stmt
             .query_map(rusqlite::params_from_iter(param_values), |row| {
@@ -1186,7 +3334,68 @@ impl Database {
         Ok(results)
     }
 
-    /// Prune old items to stay under max size (CASCADE handles children)
+    /// One page of items for export, newest first, strictly after the
+    /// `(timestamp, id)` cursor of the previous page.
+    pub(crate) fn fetch_history_export_page(
+        &self,
+        filter: Option<&ContentTypeFilter>,
+        tags: &TagRestriction,
+        after: Option<(&str, i64)>,
+        limit: usize,
+    ) -> DatabaseResult<Vec<HistoryExportRow>> {
+        let conn = self.get_conn()?;
+        let type_filter_where = Self::content_type_where_clause(filter, "WHERE");
+        let (tag_filter_where, mut param_values) =
+            Self::tag_where_clause(tags, type_filter_where.is_empty(), "WHERE", "AND");
+        let cursor_where = match after {
+            Some((timestamp, id)) => {
+                param_values.push(timestamp.to_string().into());
+                param_values.push(timestamp.to_string().into());
+                param_values.push(id.into());
+                let keyword = if type_filter_where.is_empty() && tag_filter_where.is_empty() {
+                    "WHERE"
+                } else {
+                    "AND"
+                };
+                format!("{keyword} (i.timestamp < ? OR (i.timestamp = ? AND i.id < ?))")
+            }
+            None => String::new(),
+        };
+        let sql = format!(
+            r#"SELECT i.id, i.item_id, i.timestamp, i.contentType, {},
+                      COALESCE(a.displayName, i.sourceApp), i.sourceAppBundleId
+               FROM items i
+               LEFT JOIN app_metadata a ON a.bundleId = i.sourceAppBundleId
+               {} {} {}
+               ORDER BY i.timestamp DESC, i.id DESC
+               LIMIT ?"#,
+            self.unsealed("i.content"),
+            type_filter_where,
+            tag_filter_where,
+            cursor_where
+        );
+        param_values.push((limit as i64).into());
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(param_values), |row| {
+                let stored_timestamp: String = row.get(2)?;
+                Ok(HistoryExportRow {
+                    row_id: row.get(0)?,
+                    item_id: row.get(1)?,
+                    timestamp: parse_db_timestamp(&stored_timestamp),
+                    stored_timestamp,
+                    content_type: row.get(3)?,
+                    content: row.get(4)?,
+                    source_app: row.get(5)?,
+                    source_app_bundle_id: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Prune old items to stay under max size (CASCADE handles children).
+    /// Pinned and immutable items are never pruned.
     pub fn prune_to_size(&self, max_bytes: i64, keep_ratio: f64) -> DatabaseResult<usize> {
         let current_size = self.database_size()?;
         if current_size <= max_bytes {
@@ -1210,7 +3419,9 @@ impl Database {
 
         conn.execute(
             r#"DELETE FROM items WHERE id IN (
-                SELECT id FROM items ORDER BY timestamp ASC LIMIT ?1
+                SELECT id FROM items
+                WHERE pinned = 0 AND id NOT IN (SELECT itemId FROM immutable_items)
+                ORDER BY timestamp ASC LIMIT ?1
             )"#,
             [items_to_delete as i64],
         )?;
@@ -1219,85 +3430,1689 @@ impl Database {
     }
 
     /// Build a SQL clause for filtering by content type.
+    /// SQL clause keeping out trashed items and, with a filter, items of
+    /// other content types. Never empty.
     fn content_type_where_clause(filter: Option<&ContentTypeFilter>, prefix: &str) -> String {
         let types = match filter {
             Some(f) => f.database_types(),
             None => None,
         };
+        let keyword = if prefix.is_empty() { "WHERE" } else { prefix };
         match types {
-            None => String::new(),
+            None => format!("{keyword} deletedAt IS NULL"),
             Some(types) => {
                 let quoted: Vec<String> = types.iter().map(|t| format!("'{}'", t)).collect();
-                let keyword = if prefix.is_empty() { "WHERE" } else { prefix };
-                format!("{} contentType IN ({})", keyword, quoted.join(","))
+                format!(
+                    "{} deletedAt IS NULL AND contentType IN ({})",
+                    keyword,
+                    quoted.join(",")
+                )
             }
         }
     }
 
+    /// Build a SQL clause for `tags`, with the parameters it binds.
     fn tag_where_clause(
-        tag: Option<&ItemTag>,
+        tags: &TagRestriction,
         no_prior_clause: bool,
         where_prefix: &str,
         and_prefix: &str,
-    ) -> String {
-        let Some(_) = tag else {
-            return String::new();
-        };
+    ) -> (String, Vec<rusqlite::types::Value>) {
+        let (conditions, params) = tags.conditions();
+        if conditions.is_empty() {
+            return (String::new(), params);
+        }
         let prefix = if no_prior_clause {
             where_prefix
         } else {
             and_prefix
         };
-        format!("{prefix} id IN (SELECT itemId FROM item_tags WHERE tag = ?)")
+        (format!("{prefix} {}", conditions.join(" AND ")), params)
     }
 
-    pub fn add_tag(&self, item_id: i64, tag: ItemTag) -> DatabaseResult<()> {
+    /// Schedule an item for deletion at `expires_at`, replacing any earlier
+    /// schedule.
+    pub(crate) fn set_item_expiry(
+        &self,
+        item_id: i64,
+        expires_at: DateTime<Utc>,
+    ) -> DatabaseResult<()> {
         let conn = self.get_conn()?;
         conn.execute(
-            "INSERT OR IGNORE INTO item_tags (itemId, tag) VALUES (?1, ?2)",
-            params![item_id, tag.database_str()],
+            "INSERT INTO item_expiry (itemId, expiresAt) VALUES (?1, ?2)
+             ON CONFLICT(itemId) DO UPDATE SET expiresAt = excluded.expiresAt",
+            params![
+                item_id,
+                expires_at.format("%Y-%m-%d %H:%M:%S%.f").to_string()
+            ],
         )?;
         Ok(())
     }
 
-    pub fn remove_tag(&self, item_id: i64, tag: ItemTag) -> DatabaseResult<()> {
+    /// Items whose expiry has passed at `now`. Pinned and immutable items
+    /// are left out; they stay until unpinned or unmarked.
+    pub(crate) fn fetch_expired_items(
+        &self,
+        now: DateTime<Utc>,
+    ) -> DatabaseResult<Vec<(i64, String)>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"SELECT i.id, i.item_id FROM item_expiry e
+               JOIN items i ON i.id = e.itemId
+               WHERE e.expiresAt <= ?1
+                 AND i.pinned = 0
+                 AND i.id NOT IN (SELECT itemId FROM immutable_items)"#,
+        )?;
+        let rows = stmt
+            .query_map([now.format("%Y-%m-%d %H:%M:%S%.f").to_string()], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Items a retention policy may delete, newest first. Pinned, immutable
+    /// and trashed items are left out.
+    pub(crate) fn fetch_retention_candidates(&self) -> DatabaseResult<Vec<RetentionCandidate>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"SELECT id, item_id, contentType, timestamp FROM items
+               WHERE pinned = 0 AND deletedAt IS NULL
+                 AND id NOT IN (SELECT itemId FROM immutable_items)
+               ORDER BY timestamp DESC, id DESC"#,
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                let timestamp: String = row.get(3)?;
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    parse_db_timestamp(&timestamp),
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Rewrite the database file without its free pages and truncate the
+    /// WAL.
+    pub(crate) fn compact(&self) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        // VACUUM cannot run inside a transaction; execute it standalone.
+        conn.execute_batch("VACUUM;")?;
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        Ok(())
+    }
+
+    /// Problems SQLite's integrity check finds in the database file, one per
+    /// line of its report; empty when it reports "ok".
+    pub(crate) fn integrity_problems(&self) -> DatabaseResult<Vec<String>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+        let lines = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(lines.into_iter().filter(|line| line != "ok").collect())
+    }
+
+    /// Items whose content lives in a child row that is missing, so they
+    /// cannot be loaded.
+    pub(crate) fn fetch_item_ids_missing_content(&self) -> DatabaseResult<Vec<String>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT item_id FROM items
+             WHERE (contentType = 'link' AND id NOT IN (SELECT itemId FROM link_items))
+                OR (contentType = 'image' AND id NOT IN (SELECT itemId FROM image_items))
+                OR (contentType = 'file' AND id NOT IN (SELECT itemId FROM file_items))
+                OR (contentType = 'markdown' AND id NOT IN (SELECT itemId FROM text_items))
+             ORDER BY id",
+        )?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ids)
+    }
+
+    /// Link and image rows whose item no longer exists, counted.
+    pub(crate) fn count_orphaned_content_rows(&self) -> DatabaseResult<(u64, u64)> {
+        let conn = self.get_conn()?;
+        let count = |table: &str| -> DatabaseResult<u64> {
+            let sql =
+                format!("SELECT COUNT(*) FROM {table} WHERE itemId NOT IN (SELECT id FROM items)");
+            Ok(conn.query_row(&sql, [], |row| row.get::<_, i64>(0))? as u64)
+        };
+        Ok((count("link_items")?, count("image_items")?))
+    }
+
+    /// Delete link and image rows whose item no longer exists. Returns how
+    /// many were deleted.
+    pub(crate) fn delete_orphaned_content_rows(&self) -> DatabaseResult<u64> {
+        let conn = self.get_conn()?;
+        let mut deleted = 0;
+        for table in ["link_items", "image_items"] {
+            deleted += conn.execute(
+                &format!("DELETE FROM {table} WHERE itemId NOT IN (SELECT id FROM items)"),
+                [],
+            )? as u64;
+        }
+        Ok(deleted)
+    }
+
+    /// Record a capture made during a temporary session. `previous` is the
+    /// item's timestamp before the session touched it, None if the session
+    /// inserted it. The first record for an item wins.
+    pub(crate) fn record_session_item(
+        &self,
+        item_id: i64,
+        previous: Option<DateTime<Utc>>,
+    ) -> DatabaseResult<()> {
         let conn = self.get_conn()?;
         conn.execute(
-            "DELETE FROM item_tags WHERE itemId = ?1 AND tag = ?2",
-            params![item_id, tag.database_str()],
+            "INSERT OR IGNORE INTO session_items (itemId, previousTimestamp) VALUES (?1, ?2)",
+            params![
+                item_id,
+                previous.map(|at| at.format("%Y-%m-%d %H:%M:%S%.f").to_string())
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Captures of the temporary session as `(row_id, previous timestamp)`.
+    pub(crate) fn fetch_session_items(&self) -> DatabaseResult<Vec<(i64, Option<DateTime<Utc>>)>> {
+        let conn = self.get_conn()?;
+        let mut stmt =
+            conn.prepare("SELECT itemId, previousTimestamp FROM session_items ORDER BY itemId")?;
+        let rows = stmt
+            .query_map([], |row| {
+                let previous: Option<String> = row.get(1)?;
+                Ok((row.get(0)?, previous.as_deref().map(parse_db_timestamp)))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    pub(crate) fn clear_session_items(&self) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute("DELETE FROM session_items", [])?;
+        Ok(())
+    }
+
+    /// Forget copies of an item logged at or after `since`.
+    pub(crate) fn delete_copy_events_since(
+        &self,
+        item_id: i64,
+        since: DateTime<Utc>,
+    ) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "DELETE FROM copy_events WHERE itemId = ?1 AND copiedAt >= ?2",
+            params![item_id, since.format("%Y-%m-%d %H:%M:%S%.f").to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Set when an item resurfaces, replacing any earlier reminder.
+    pub(crate) fn set_reminder(&self, item_id: i64, fire_at: DateTime<Utc>) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT INTO reminders (itemId, fireAt) VALUES (?1, ?2)
+             ON CONFLICT(itemId) DO UPDATE SET fireAt = excluded.fireAt",
+            params![item_id, fire_at.format("%Y-%m-%d %H:%M:%S%.f").to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Remove an item's reminder. Returns whether it had one.
+    pub(crate) fn clear_reminder(&self, item_id: i64) -> DatabaseResult<bool> {
+        let conn = self.get_conn()?;
+        let removed = conn.execute("DELETE FROM reminders WHERE itemId = ?1", [item_id])?;
+        Ok(removed > 0)
+    }
+
+    /// Reminders due at `now` as `(item_id, fire_at_unix)`, earliest first.
+    pub(crate) fn fetch_due_reminders(
+        &self,
+        now: DateTime<Utc>,
+    ) -> DatabaseResult<Vec<(String, i64)>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"SELECT i.item_id, r.fireAt FROM reminders r
+               JOIN items i ON i.id = r.itemId
+               WHERE r.fireAt <= ?1 AND i.deletedAt IS NULL
+               ORDER BY r.fireAt, i.id"#,
+        )?;
+        let rows = stmt
+            .query_map([now.format("%Y-%m-%d %H:%M:%S%.f").to_string()], |row| {
+                let fire_at: String = row.get(1)?;
+                Ok((row.get(0)?, parse_db_timestamp(&fire_at).timestamp()))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Reminder times keyed by item ID, for the items that have one.
+    pub(crate) fn fetch_reminders_for_item_ids(
+        &self,
+        item_ids: &[String],
+    ) -> DatabaseResult<std::collections::HashMap<String, i64>> {
+        if item_ids.is_empty() {
+            return Ok(std::collections::HashMap::new());
+        }
+        let conn = self.get_conn()?;
+        let placeholders = item_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let sql = format!(
+            "SELECT i.item_id, r.fireAt FROM reminders r JOIN items i ON i.id = r.itemId WHERE i.item_id IN ({placeholders})"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(item_ids), |row| {
+                let fire_at: String = row.get(1)?;
+                Ok((row.get(0)?, parse_db_timestamp(&fire_at).timestamp()))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(rows)
+    }
+
+    /// Mark or unmark an item as immutable. Immutable items survive deletion,
+    /// edits, clearing, and pruning until the flag is cleared.
+    pub fn set_immutable(&self, item_id: i64, immutable: bool) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        if immutable {
+            conn.execute(
+                "INSERT OR IGNORE INTO immutable_items (itemId, markedAt) VALUES (?1, ?2)",
+                params![item_id, Utc::now().to_rfc3339()],
+            )?;
+        } else {
+            conn.execute("DELETE FROM immutable_items WHERE itemId = ?1", [item_id])?;
+        }
+        Ok(())
+    }
+
+    pub fn is_immutable(&self, item_id: i64) -> DatabaseResult<bool> {
+        let conn = self.get_conn()?;
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM immutable_items WHERE itemId = ?1",
+            [item_id],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// (row_id, item_id) pairs of every immutable item, newest first.
+    pub fn fetch_immutable_ids(&self) -> DatabaseResult<Vec<(i64, String)>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT items.id, items.item_id FROM immutable_items
+             JOIN items ON items.id = immutable_items.itemId
+             ORDER BY items.timestamp DESC, items.id DESC",
+        )?;
+        let ids = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ids)
+    }
+
+    /// Attach a user tag; `name` must already be canonical.
+    pub(crate) fn add_user_tag(&self, item_id: i64, name: &str) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT OR IGNORE INTO tags (itemId, name, createdAt) VALUES (?1, ?2, ?3)",
+            params![
+                item_id,
+                name,
+                Utc::now().format("%Y-%m-%d %H:%M:%S%.f").to_string()
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn remove_user_tag(&self, item_id: i64, name: &str) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "DELETE FROM tags WHERE itemId = ?1 AND name = ?2",
+            params![item_id, name],
+        )?;
+        Ok(())
+    }
+
+    /// User tags on one item, sorted by name.
+    pub(crate) fn fetch_user_tags(&self, item_id: i64) -> DatabaseResult<Vec<String>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare("SELECT name FROM tags WHERE itemId = ?1 ORDER BY name")?;
+        let names = stmt
+            .query_map([item_id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(names)
+    }
+
+    /// User tags of every tagged item, keyed by string item_id.
+    pub(crate) fn fetch_all_user_tags(
+        &self,
+    ) -> DatabaseResult<std::collections::HashMap<String, Vec<String>>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT i.item_id, t.name FROM tags t JOIN items i ON i.id = t.itemId ORDER BY t.name",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut map: std::collections::HashMap<String, Vec<String>> =
+            std::collections::HashMap::new();
+        for row in rows {
+            let (item_id, name) = row?;
+            map.entry(item_id).or_default().push(name);
+        }
+        Ok(map)
+    }
+
+    /// Every user tag in use with its item count, sorted by name.
+    pub(crate) fn fetch_user_tag_counts(&self) -> DatabaseResult<Vec<(String, u64)>> {
+        let conn = self.get_conn()?;
+        let mut stmt =
+            conn.prepare("SELECT name, COUNT(*) FROM tags GROUP BY name ORDER BY name")?;
+        let counts = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(counts)
+    }
+
+    /// The subset of `names` that tag at least one item.
+    pub(crate) fn filter_existing_user_tags(
+        &self,
+        names: &[String],
+    ) -> DatabaseResult<std::collections::HashSet<String>> {
+        if names.is_empty() {
+            return Ok(std::collections::HashSet::new());
+        }
+        let conn = self.get_conn()?;
+        let placeholders = names.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let sql = format!("SELECT DISTINCT name FROM tags WHERE name IN ({placeholders})");
+        let mut stmt = conn.prepare(&sql)?;
+        let existing = stmt
+            .query_map(rusqlite::params_from_iter(names), |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(existing)
+    }
+
+    pub fn add_tag(&self, item_id: i64, tag: ItemTag) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT OR IGNORE INTO item_tags (itemId, tag) VALUES (?1, ?2)",
+            params![item_id, tag.database_str()],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_tag(&self, item_id: i64, tag: ItemTag) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "DELETE FROM item_tags WHERE itemId = ?1 AND tag = ?2",
+            params![item_id, tag.database_str()],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_tags_for_ids(
+        &self,
+        ids: &[i64],
+    ) -> DatabaseResult<std::collections::HashMap<i64, Vec<ItemTag>>> {
+        if ids.is_empty() {
+            return Ok(std::collections::HashMap::new());
+        }
+
+        let conn = self.get_conn()?;
+        let placeholders = ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let sql = format!(
+            "SELECT itemId, tag FROM item_tags WHERE itemId IN ({}) ORDER BY tag",
+            placeholders
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let params: Vec<rusqlite::types::Value> = ids.iter().map(|&id| id.into()).collect();
+        let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut map: std::collections::HashMap<i64, Vec<ItemTag>> =
+            std::collections::HashMap::new();
+        for row in rows {
+            let (id, tag) = row?;
+            let tag = ItemTag::from_database_str(&tag).map_err(DatabaseError::InconsistentData)?;
+            map.entry(id).or_default().push(tag);
+        }
+
+        Ok(map)
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Smart collections
+    // ─────────────────────────────────────────────────────────────────────────
+
+    pub(crate) fn insert_smart_collection(
+        &self,
+        collection: &SmartCollection,
+    ) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        let tx = conn.unchecked_transaction()?;
+        let now = Utc::now().format("%Y-%m-%d %H:%M:%S%.f").to_string();
+        tx.execute(
+            "INSERT INTO smart_collections (id, name, createdAt) VALUES (?1, ?2, ?3)",
+            params![collection.collection_id, collection.name, now],
+        )?;
+        Self::insert_smart_collection_rules(&tx, collection)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Replace a collection's name and rules. Returns false if it does not exist.
+    pub(crate) fn update_smart_collection(
+        &self,
+        collection: &SmartCollection,
+    ) -> DatabaseResult<bool> {
+        let conn = self.get_conn()?;
+        let tx = conn.unchecked_transaction()?;
+        let updated = tx.execute(
+            "UPDATE smart_collections SET name = ?1 WHERE id = ?2",
+            params![collection.name, collection.collection_id],
+        )?;
+        if updated == 0 {
+            return Ok(false);
+        }
+        tx.execute(
+            "DELETE FROM smart_collection_rules WHERE collectionId = ?1",
+            [&collection.collection_id],
+        )?;
+        Self::insert_smart_collection_rules(&tx, collection)?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Delete a collection and its rules. Returns false if it does not exist.
+    pub(crate) fn delete_smart_collection(&self, collection_id: &str) -> DatabaseResult<bool> {
+        let conn = self.get_conn()?;
+        let deleted = conn.execute(
+            "DELETE FROM smart_collections WHERE id = ?1",
+            [collection_id],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// Fetch all smart collections in creation order.
+    pub(crate) fn fetch_smart_collections(&self) -> DatabaseResult<Vec<SmartCollection>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"SELECT c.id, c.name, r.kind, r.value
+               FROM smart_collections c
+               LEFT JOIN smart_collection_rules r ON r.collectionId = c.id
+               ORDER BY c.createdAt, c.rowid, r.ordinal"#,
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?;
+
+        let mut collections: Vec<SmartCollection> = Vec::new();
+        for row in rows {
+            let (collection_id, name, kind, value) = row?;
+            if collections.last().map(|c| &c.collection_id) != Some(&collection_id) {
+                collections.push(SmartCollection {
+                    collection_id,
+                    name,
+                    rules: Vec::new(),
+                });
+            }
+            if let (Some(kind), Some(value)) = (kind, value) {
+                let rule = decode_collection_rule(&kind, &value)
+                    .map_err(DatabaseError::InconsistentData)?;
+                if let Some(collection) = collections.last_mut() {
+                    collection.rules.push(rule);
+                }
+            }
+        }
+        Ok(collections)
+    }
+
+    /// Resolve smart collection rules to matching row IDs, newest first, each with
+    /// a content prefix for rules that are evaluated in Rust.
+    pub(crate) fn fetch_smart_collection_candidates(
+        &self,
+        rules: &[SmartCollectionRule],
+    ) -> DatabaseResult<Vec<(i64, String)>> {
+        let (condition, param_values) = self.smart_collection_condition(rules);
+        let sql = format!(
+            "SELECT id, substr(ltrim({}, char(9) || char(10) || char(13) || ' '), 1, {}) FROM items WHERE {} ORDER BY timestamp DESC, id DESC",
+            self.unsealed("content"),
+            CODE_DETECTION_PREFIX_CHARS,
+            condition
+        );
+
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(param_values), |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Count the rows matching the rules SQLite can express, without loading
+    /// them. Exact for rule sets without `ContainsCode`.
+    pub(crate) fn count_smart_collection_candidates(
+        &self,
+        rules: &[SmartCollectionRule],
+    ) -> DatabaseResult<u64> {
+        let (condition, param_values) = self.smart_collection_condition(rules);
+        let conn = self.get_conn()?;
+        let count: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM items WHERE {condition}"),
+            rusqlite::params_from_iter(param_values),
+            |row| row.get(0),
+        )?;
+        Ok(count as u64)
+    }
+
+    fn smart_collection_condition(
+        &self,
+        rules: &[SmartCollectionRule],
+    ) -> (String, Vec<rusqlite::types::Value>) {
+        let mut clauses: Vec<String> = vec!["deletedAt IS NULL".to_string()];
+        let mut param_values: Vec<rusqlite::types::Value> = Vec::new();
+        for rule in rules {
+            match rule {
+                SmartCollectionRule::ContentType { content_type } => {
+                    let clause = Self::content_type_where_clause(Some(content_type), "");
+                    if let Some(condition) = clause.strip_prefix("WHERE ") {
+                        clauses.push(condition.to_string());
+                    }
+                }
+                SmartCollectionRule::SourceApps { bundle_ids } => {
+                    let placeholders = bundle_ids.iter().map(|_| "?").collect::<Vec<_>>();
+                    clauses.push(format!("sourceAppBundleId IN ({})", placeholders.join(",")));
+                    param_values.extend(bundle_ids.iter().map(|id| id.clone().into()));
+                }
+                SmartCollectionRule::ContainsText { text } => {
+                    // `fold` applies the search folding, so case and accents
+                    // match beyond ASCII.
+                    clauses.push(format!("instr(fold({}), ?) > 0", self.unsealed("content")));
+                    param_values.push(fold_str(text).into());
+                }
+                SmartCollectionRule::ContainsCode => {
+                    clauses.push("contentType = 'text'".to_string());
+                }
+                SmartCollectionRule::Tagged { tag } => {
+                    clauses.push("id IN (SELECT itemId FROM item_tags WHERE tag = ?)".to_string());
+                    param_values.push(tag.database_str().to_string().into());
+                }
+            }
+        }
+        (clauses.join(" AND "), param_values)
+    }
+
+    fn insert_smart_collection_rules(
+        tx: &rusqlite::Transaction,
+        collection: &SmartCollection,
+    ) -> DatabaseResult<()> {
+        for (ordinal, rule) in collection.rules.iter().enumerate() {
+            let (kind, value) = encode_collection_rule(rule);
+            tx.execute(
+                "INSERT INTO smart_collection_rules (collectionId, ordinal, kind, value) VALUES (?1, ?2, ?3, ?4)",
+                params![collection.collection_id, ordinal as i64, kind, value],
+            )?;
+        }
+        Ok(())
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Snippets
+    // ─────────────────────────────────────────────────────────────────────────
+
+    pub(crate) fn insert_snippet(
+        &self,
+        snippet_id: &str,
+        title: &str,
+        body: &str,
+        created_at: DateTime<Utc>,
+    ) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        let created_at = created_at.format("%Y-%m-%d %H:%M:%S%.f").to_string();
+        conn.execute(
+            "INSERT INTO snippets (id, title, body, createdAt, updatedAt) VALUES (?1, ?2, ?3, ?4, ?4)",
+            params![snippet_id, title, self.seal_text(body), created_at],
+        )?;
+        Ok(())
+    }
+
+    /// Delete a snippet. Returns false if it does not exist.
+    pub(crate) fn delete_snippet(&self, snippet_id: &str) -> DatabaseResult<bool> {
+        let conn = self.get_conn()?;
+        let deleted = conn.execute("DELETE FROM snippets WHERE id = ?1", [snippet_id])?;
+        Ok(deleted > 0)
+    }
+
+    /// Fetch every snippet, ordered by title.
+    pub(crate) fn fetch_snippets(&self) -> DatabaseResult<Vec<SnippetRow>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, title, {}, updatedAt FROM snippets ORDER BY title COLLATE NOCASE, id",
+            self.unsealed("body")
+        ))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    parse_db_timestamp(&row.get::<_, String>(3)?),
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Text of the newest item in history that has any, for `{clipboard}`.
+    pub(crate) fn fetch_latest_text(&self) -> DatabaseResult<Option<String>> {
+        let conn = self.get_conn()?;
+        let result = conn.query_row(
+            &format!(
+                "SELECT item_id, contentType, {} FROM items
+                 WHERE deletedAt IS NULL AND contentType IN ('text', 'rich_text', 'link', 'color', 'phone', 'address', 'markdown')
+                 ORDER BY timestamp DESC, id DESC LIMIT 1",
+                self.unsealed("content")
+            ),
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            },
+        );
+        match result {
+            // Markdown pastes as written, not as its plain-text render.
+            Ok((item_id, content_type, _)) if content_type == "markdown" => {
+                drop(conn);
+                Ok(self
+                    .fetch_items_by_item_ids(&[item_id])?
+                    .into_iter()
+                    .find_map(|item| match item.content {
+                        ClipboardContent::Markdown { source, .. } => Some(source),
+                        _ => None,
+                    }))
+            }
+            Ok((_, _, content)) => Ok(Some(content)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Topics
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Row IDs and leading text of live text items newer than `after_id`,
+    /// newest first, for topic clustering.
+    pub(crate) fn fetch_topic_documents(
+        &self,
+        after_id: i64,
+        limit: usize,
+        prefix_chars: usize,
+    ) -> DatabaseResult<Vec<(i64, String)>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, substr({}, 1, ?1) FROM items
+             WHERE deletedAt IS NULL AND contentType IN ('text', 'rich_text') AND id > ?2
+             ORDER BY id DESC LIMIT ?3",
+            self.unsealed("content")
+        ))?;
+        let rows = stmt
+            .query_map(
+                params![prefix_chars as i64, after_id, limit as i64],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Replace every topic. `topics` holds (id, label, centroid) and
+    /// `members` holds (row ID, topic ID).
+    pub(crate) fn replace_topics(
+        &self,
+        topics: &[(String, String, String)],
+        members: &[(i64, String)],
+    ) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        let tx = conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM topics", [])?;
+        for (id, label, centroid) in topics {
+            tx.execute(
+                "INSERT INTO topics (id, label, centroid) VALUES (?1, ?2, ?3)",
+                params![id, label, centroid],
+            )?;
+        }
+        Self::insert_topic_members(&tx, members)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Add items to existing topics.
+    pub(crate) fn add_topic_members(&self, members: &[(i64, String)]) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        let tx = conn.unchecked_transaction()?;
+        Self::insert_topic_members(&tx, members)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn insert_topic_members(
+        tx: &rusqlite::Transaction,
+        members: &[(i64, String)],
+    ) -> DatabaseResult<()> {
+        // An item deleted since it was read has nothing left to join.
+        let mut stmt = tx.prepare(
+            "INSERT OR REPLACE INTO topic_items (itemId, topicId)
+             SELECT id, ?2 FROM items WHERE id = ?1",
+        )?;
+        for (item_id, topic_id) in members {
+            stmt.execute(params![item_id, topic_id])?;
+        }
+        Ok(())
+    }
+
+    /// Every topic's (id, centroid).
+    pub(crate) fn fetch_topic_centroids(&self) -> DatabaseResult<Vec<(String, String)>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare("SELECT id, centroid FROM topics ORDER BY id")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Delete topics left with fewer than `min_items` items outside the trash.
+    pub(crate) fn delete_small_topics(&self, min_items: u64) -> DatabaseResult<u64> {
+        let conn = self.get_conn()?;
+        let deleted = conn.execute(
+            "DELETE FROM topics WHERE (
+                SELECT COUNT(*) FROM topic_items t JOIN items i ON i.id = t.itemId
+                WHERE t.topicId = topics.id AND i.deletedAt IS NULL
+             ) < ?1",
+            [min_items as i64],
+        )?;
+        Ok(deleted as u64)
+    }
+
+    /// Every topic's (id, label, item count), items in the trash not
+    /// counted, largest first.
+    pub(crate) fn fetch_topic_summaries(&self) -> DatabaseResult<Vec<(String, String, u64)>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT topics.id, topics.label, COUNT(i.id) AS itemCount
+             FROM topics
+             JOIN topic_items t ON t.topicId = topics.id
+             JOIN items i ON i.id = t.itemId AND i.deletedAt IS NULL
+             GROUP BY topics.id
+             ORDER BY itemCount DESC, topics.label, topics.id",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get::<_, i64>(2)? as u64))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Row IDs of a topic's items outside the trash, newest first. None if
+    /// there is no such topic.
+    pub(crate) fn fetch_topic_member_ids(
+        &self,
+        topic_id: &str,
+    ) -> DatabaseResult<Option<Vec<i64>>> {
+        let conn = self.get_conn()?;
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM topics WHERE id = ?1)",
+            [topic_id],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Ok(None);
+        }
+        let mut stmt = conn.prepare(
+            "SELECT i.id FROM topic_items t JOIN items i ON i.id = t.itemId
+             WHERE t.topicId = ?1 AND i.deletedAt IS NULL
+             ORDER BY i.timestamp DESC, i.id DESC",
+        )?;
+        let ids = stmt
+            .query_map([topic_id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Some(ids))
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Paste events
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Log that an item was copied, as a new item or again as a duplicate,
+    /// and which app it was copied from.
+    pub(crate) fn insert_copy_event(
+        &self,
+        row_id: i64,
+        copied_at: DateTime<Utc>,
+        source_app: Option<&str>,
+        source_app_bundle_id: Option<&str>,
+    ) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        Self::insert_copy_event_in(&conn, row_id, copied_at, source_app, source_app_bundle_id)
+    }
+
+    fn insert_copy_event_in(
+        conn: &rusqlite::Connection,
+        row_id: i64,
+        copied_at: DateTime<Utc>,
+        source_app: Option<&str>,
+        source_app_bundle_id: Option<&str>,
+    ) -> DatabaseResult<()> {
+        conn.execute(
+            "INSERT INTO copy_events (itemId, copiedAt, sourceApp, sourceAppBundleId)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                row_id,
+                copied_at.format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+                source_app,
+                source_app_bundle_id,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Every logged copy of an item, oldest first. An item saved before
+    /// copies were logged reports its own timestamp and source app instead.
+    pub(crate) fn fetch_copy_history(&self, row_id: i64) -> DatabaseResult<Vec<CopyEventRow>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"SELECT CAST(strftime('%s', c.copiedAt) AS INTEGER) AS at,
+                      COALESCE(m.displayName, c.sourceApp), c.sourceAppBundleId, c.id
+               FROM copy_events c
+               LEFT JOIN app_metadata m ON m.bundleId = c.sourceAppBundleId
+               WHERE c.itemId = ?1
+               UNION ALL
+               SELECT CAST(strftime('%s', i.timestamp) AS INTEGER),
+                      COALESCE(m.displayName, i.sourceApp), i.sourceAppBundleId, 0
+               FROM items i
+               LEFT JOIN app_metadata m ON m.bundleId = i.sourceAppBundleId
+               WHERE i.id = ?1
+                 AND NOT EXISTS (SELECT 1 FROM copy_events WHERE itemId = ?1)
+               ORDER BY at, 4"#,
+        )?;
+        let rows = stmt
+            .query_map([row_id], |row| {
+                Ok(CopyEventRow {
+                    copied_at_unix: row.get(0)?,
+                    source_app: row.get(1)?,
+                    source_app_bundle_id: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Items whose timestamp falls in `[start, end)`, newest first, with
+    /// their copy counts in that range.
+    pub(crate) fn fetch_digest_items(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> DatabaseResult<Vec<DigestItemRow>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(&format!(
+            r#"SELECT i.item_id, i.contentType, substr({}, 1, 256),
+                      COALESCE(m.displayName, i.sourceApp), i.sourceAppBundleId,
+                      {}, {},
+                      (SELECT COUNT(*) FROM copy_events c
+                       WHERE c.itemId = i.id AND c.copiedAt >= ?1 AND c.copiedAt < ?2),
+                      EXISTS (SELECT 1 FROM copy_events c
+                              WHERE c.itemId = i.id AND c.copiedAt < ?1)
+               FROM items i
+               LEFT JOIN link_items l ON l.itemId = i.id
+               LEFT JOIN app_metadata m ON m.bundleId = i.sourceAppBundleId
+               WHERE i.timestamp >= ?1 AND i.timestamp < ?2 AND i.deletedAt IS NULL
+               ORDER BY i.timestamp DESC, i.id DESC"#,
+            self.unsealed("i.content"),
+            self.unsealed("l.url"),
+            self.unsealed("l.title")
+        ))?;
+        let rows = stmt
+            .query_map(
+                params![
+                    start.format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+                    end.format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+                ],
+                |row| {
+                    Ok(DigestItemRow {
+                        item_id: row.get(0)?,
+                        content_type: row.get(1)?,
+                        content_prefix: row.get(2)?,
+                        source_app: row.get(3)?,
+                        source_app_bundle_id: row.get(4)?,
+                        link_url: row.get(5)?,
+                        link_title: row.get(6)?,
+                        copies: row.get::<_, i64>(7)? as u64,
+                        copied_before: row.get(8)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Copy and paste events at or after `since`, oldest first.
+    pub(crate) fn fetch_usage_events_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> DatabaseResult<Vec<UsageEventRow>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"SELECT itemId, 0, CAST(strftime('%s', copiedAt) AS INTEGER) AS at
+               FROM copy_events WHERE copiedAt >= ?1
+               UNION ALL
+               SELECT itemId, 1, CAST(strftime('%s', pastedAt) AS INTEGER)
+               FROM paste_events WHERE pastedAt >= ?1
+               ORDER BY at"#,
+        )?;
+        let rows = stmt
+            .query_map([since.format("%Y-%m-%d %H:%M:%S%.f").to_string()], |row| {
+                Ok(UsageEventRow {
+                    row_id: row.get(0)?,
+                    is_paste: row.get(1)?,
+                    at_unix: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Log a paste and count it towards the target app's content-type and
+    /// source-app priors.
+    pub(crate) fn insert_paste_event(
+        &self,
+        row_id: i64,
+        target_bundle_id: &str,
+        document_hint: Option<&str>,
+        pasted_at: DateTime<Utc>,
+    ) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO paste_events (itemId, targetBundleId, documentHint, pastedAt) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                row_id,
+                target_bundle_id,
+                document_hint,
+                pasted_at.format("%Y-%m-%d %H:%M:%S%.f").to_string()
+            ],
+        )?;
+        tx.execute(
+            r#"INSERT INTO app_paste_priors (targetBundleId, dimension, value, pasteCount)
+               SELECT ?1, 'content_type', contentType, 1 FROM items WHERE id = ?2
+               UNION ALL
+               SELECT ?1, 'source_app', sourceAppBundleId, 1 FROM items
+               WHERE id = ?2 AND sourceAppBundleId IS NOT NULL AND sourceAppBundleId != ''
+               ON CONFLICT (targetBundleId, dimension, value)
+               DO UPDATE SET pasteCount = pasteCount + 1"#,
+            params![target_bundle_id, row_id],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Record that `transformation` was applied to an item, with the app it
+    /// was headed for.
+    pub(crate) fn insert_transform_event(
+        &self,
+        row_id: i64,
+        transformation: &str,
+        target_bundle_id: Option<&str>,
+        applied_at: DateTime<Utc>,
+    ) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT INTO transform_events (itemId, transformation, targetBundleId, appliedAt) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                row_id,
+                transformation,
+                target_bundle_id,
+                applied_at.format("%Y-%m-%d %H:%M:%S%.f").to_string()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// An item's latest transform events as `(transformation, target,
+    /// applied_at_unix)`, newest first.
+    pub(crate) fn fetch_transform_events(
+        &self,
+        row_id: i64,
+        limit: usize,
+    ) -> DatabaseResult<Vec<(String, Option<String>, i64)>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT transformation, targetBundleId, appliedAt FROM transform_events
+             WHERE itemId = ?1 ORDER BY appliedAt DESC, id DESC LIMIT ?2",
+        )?;
+        let rows = stmt
+            .query_map(params![row_id, limit as i64], |row| {
+                let applied_at: String = row.get(2)?;
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    parse_db_timestamp(&applied_at).timestamp(),
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Learned paste counts as `(target, dimension, value, count)`, optionally
+    /// for a single target app.
+    pub(crate) fn fetch_paste_priors(
+        &self,
+        target_bundle_id: Option<&str>,
+    ) -> DatabaseResult<Vec<(String, String, String, u64)>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"SELECT targetBundleId, dimension, value, pasteCount FROM app_paste_priors
+               WHERE ?1 IS NULL OR targetBundleId = ?1
+               ORDER BY targetBundleId, dimension, pasteCount DESC, value"#,
+        )?;
+        let rows = stmt
+            .query_map([target_bundle_id], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get::<_, i64>(3)? as u64,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Forget learned priors for one target app, or for all apps.
+    pub(crate) fn clear_paste_priors(&self, target_bundle_id: Option<&str>) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "DELETE FROM app_paste_priors WHERE ?1 IS NULL OR targetBundleId = ?1",
+            [target_bundle_id],
+        )?;
+        Ok(())
+    }
+
+    /// Content type and source app bundle ID for each string item ID.
+    pub(crate) fn fetch_type_and_source_by_string_ids(
+        &self,
+        item_ids: &[&str],
+    ) -> DatabaseResult<Vec<(String, String, Option<String>)>> {
+        if item_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let conn = self.get_conn()?;
+        let placeholders = item_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let sql = format!(
+            "SELECT item_id, contentType, sourceAppBundleId FROM items WHERE item_id IN ({})",
+            placeholders
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(item_ids), |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Every logged paste of the given items as `(item_id, pasted_at_unix)`.
+    pub(crate) fn fetch_paste_times_by_string_ids(
+        &self,
+        item_ids: &[&str],
+    ) -> DatabaseResult<Vec<(String, i64)>> {
+        if item_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let conn = self.get_conn()?;
+        let placeholders = item_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let sql = format!(
+            "SELECT i.item_id, CAST(strftime('%s', p.pastedAt) AS INTEGER)
+             FROM paste_events p
+             JOIN items i ON i.id = p.itemId
+             WHERE i.item_id IN ({})",
+            placeholders
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(item_ids), |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Most recent pastes into an app, newest first.
+    pub(crate) fn fetch_paste_events_for_app(
+        &self,
+        target_bundle_id: &str,
+        limit: usize,
+    ) -> DatabaseResult<Vec<PasteEventRow>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT itemId, documentHint, pastedAt FROM paste_events
+             WHERE targetBundleId = ?1 ORDER BY pastedAt DESC LIMIT ?2",
+        )?;
+        let rows = stmt
+            .query_map(params![target_bundle_id, limit as i64], |row| {
+                let pasted_at: String = row.get(2)?;
+                Ok(PasteEventRow {
+                    row_id: row.get(0)?,
+                    document_hint: row.get(1)?,
+                    pasted_at_unix: parse_db_timestamp(&pasted_at).timestamp(),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Record a contained panic, keeping only the newest `MAX_CRASH_BREADCRUMBS`.
+    pub(crate) fn insert_crash_breadcrumb(
+        &self,
+        operation: &str,
+        message: &str,
+        backtrace: &str,
+    ) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO crash_breadcrumbs (occurredAt, operation, message, backtrace) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                Utc::now().format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+                operation,
+                message,
+                backtrace
+            ],
+        )?;
+        tx.execute(
+            "DELETE FROM crash_breadcrumbs WHERE id NOT IN (
+                SELECT id FROM crash_breadcrumbs ORDER BY id DESC LIMIT ?1
+             )",
+            [MAX_CRASH_BREADCRUMBS as i64],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Insert or replace app metadata by bundle ID.
+    pub(crate) fn upsert_app_metadata(&self, apps: &[AppMetadata]) -> DatabaseResult<()> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+        let now = Utc::now().format("%Y-%m-%d %H:%M:%S%.f").to_string();
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO app_metadata (bundleId, displayName, iconHash, category, installed, updatedAt)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT(bundleId) DO UPDATE SET
+                     displayName = excluded.displayName,
+                     iconHash = excluded.iconHash,
+                     category = excluded.category,
+                     installed = excluded.installed,
+                     updatedAt = excluded.updatedAt",
+            )?;
+            for app in apps {
+                stmt.execute(params![
+                    app.bundle_id,
+                    app.display_name,
+                    app.icon_hash,
+                    app.category,
+                    app.is_installed,
+                    now
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub(crate) fn fetch_app_metadata(
+        &self,
+        bundle_id: &str,
+    ) -> DatabaseResult<Option<AppMetadata>> {
+        let conn = self.get_conn()?;
+        let result = conn.query_row(
+            "SELECT bundleId, displayName, iconHash, category, installed FROM app_metadata WHERE bundleId = ?1",
+            [bundle_id],
+            |row| {
+                Ok(AppMetadata {
+                    bundle_id: row.get(0)?,
+                    display_name: row.get(1)?,
+                    icon_hash: row.get(2)?,
+                    category: row.get(3)?,
+                    is_installed: row.get(4)?,
+                })
+            },
+        );
+        match result {
+            Ok(app) => Ok(Some(app)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Registered display names for `bundle_ids`, keyed by bundle ID.
+    pub(crate) fn fetch_app_display_names(
+        &self,
+        bundle_ids: &[&str],
+    ) -> DatabaseResult<std::collections::HashMap<String, String>> {
+        if bundle_ids.is_empty() {
+            return Ok(std::collections::HashMap::new());
+        }
+        let conn = self.get_conn()?;
+        let placeholders = bundle_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let sql = format!(
+            "SELECT bundleId, displayName FROM app_metadata WHERE bundleId IN ({placeholders})"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let names = stmt
+            .query_map(rusqlite::params_from_iter(bundle_ids), |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(names)
+    }
+
+    /// Source apps with at least one item, most items first, named by
+    /// registered metadata when there is any.
+    pub(crate) fn fetch_source_app_counts(&self) -> DatabaseResult<Vec<SourceAppRow>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"SELECT a.bundleId, COALESCE(m.displayName, a.name), COUNT(*) AS itemCount
+               FROM items i
+               JOIN apps a ON a.id = i.appId
+               LEFT JOIN app_metadata m ON m.bundleId = a.bundleId
+               WHERE i.deletedAt IS NULL
+               GROUP BY a.id
+               ORDER BY itemCount DESC, a.id"#,
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(SourceAppRow {
+                    bundle_id: row.get(0)?,
+                    name: row.get(1)?,
+                    item_count: row.get::<_, i64>(2)? as u64,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    pub(crate) fn put_key_value(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: &[u8],
+    ) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT INTO key_values (namespace, key, value, updatedAt) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(namespace, key) DO UPDATE SET value = excluded.value, updatedAt = excluded.updatedAt",
+            params![
+                namespace,
+                key,
+                value,
+                Utc::now().format("%Y-%m-%d %H:%M:%S%.f").to_string()
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn get_key_value(
+        &self,
+        namespace: &str,
+        key: &str,
+    ) -> DatabaseResult<Option<Vec<u8>>> {
+        let conn = self.get_conn()?;
+        let result = conn.query_row(
+            "SELECT value FROM key_values WHERE namespace = ?1 AND key = ?2",
+            params![namespace, key],
+            |row| row.get(0),
+        );
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub(crate) fn delete_key_value(&self, namespace: &str, key: &str) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "DELETE FROM key_values WHERE namespace = ?1 AND key = ?2",
+            params![namespace, key],
+        )?;
+        Ok(())
+    }
+
+    /// All `(key, value)` pairs in `namespace`, sorted by key.
+    pub(crate) fn fetch_key_values(
+        &self,
+        namespace: &str,
+    ) -> DatabaseResult<Vec<(String, Vec<u8>)>> {
+        let conn = self.get_conn()?;
+        let mut stmt =
+            conn.prepare("SELECT key, value FROM key_values WHERE namespace = ?1 ORDER BY key")?;
+        let rows = stmt
+            .query_map([namespace], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Recorded panics, newest first.
+    pub(crate) fn fetch_crash_breadcrumbs(
+        &self,
+        limit: usize,
+    ) -> DatabaseResult<Vec<CrashBreadcrumbRow>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT occurredAt, operation, message, backtrace FROM crash_breadcrumbs
+             ORDER BY id DESC LIMIT ?1",
+        )?;
+        let rows = stmt
+            .query_map([limit as i64], |row| {
+                let occurred_at: String = row.get(0)?;
+                Ok(CrashBreadcrumbRow {
+                    occurred_at_unix: parse_db_timestamp(&occurred_at).timestamp(),
+                    operation: row.get(1)?,
+                    message: row.get(2)?,
+                    backtrace: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Per-day copy and paste counts between `start` and `end`. Days are
+    /// bucketed after shifting UTC timestamps by `utc_offset_seconds`. Copies
+    /// are attributed to the source app (bundle ID, else name) and pastes to
+    /// the target app.
+    pub(crate) fn fetch_daily_activity(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        utc_offset_seconds: i32,
+    ) -> DatabaseResult<Vec<DailyActivityRow>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"SELECT day, activity, app, contentType, COUNT(*) FROM (
+                   SELECT date(timestamp, ?3) AS day, 'copy' AS activity,
+                          COALESCE(sourceAppBundleId, sourceApp) AS app, contentType
+                   FROM items
+                   WHERE timestamp >= ?1 AND timestamp < ?2 AND deletedAt IS NULL
+                   UNION ALL
+                   SELECT date(p.pastedAt, ?3), 'paste', p.targetBundleId, i.contentType
+                   FROM paste_events p
+                   JOIN items i ON i.id = p.itemId
+                   WHERE p.pastedAt >= ?1 AND p.pastedAt < ?2 AND i.deletedAt IS NULL
+               )
+               GROUP BY day, activity, app, contentType
+               ORDER BY day, activity, COUNT(*) DESC, app, contentType"#,
+        )?;
+        let rows = stmt
+            .query_map(
+                params![
+                    start.format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+                    end.format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+                    format!("{utc_offset_seconds:+} seconds"),
+                ],
+                |row| {
+                    Ok(DailyActivityRow {
+                        day: row.get(0)?,
+                        activity: row.get(1)?,
+                        app: row.get(2)?,
+                        content_type: row.get(3)?,
+                        count: row.get::<_, i64>(4)? as u64,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Item, copy event, and paste event counts, leaving out the trash.
+    pub(crate) fn fetch_usage_totals(&self) -> DatabaseResult<(u64, u64, u64)> {
+        let conn = self.get_conn()?;
+        let totals = conn.query_row(
+            r#"SELECT (SELECT COUNT(*) FROM items WHERE deletedAt IS NULL),
+                      (SELECT COUNT(*) FROM copy_events c
+                       JOIN items i ON i.id = c.itemId WHERE i.deletedAt IS NULL),
+                      (SELECT COUNT(*) FROM paste_events p
+                       JOIN items i ON i.id = p.itemId WHERE i.deletedAt IS NULL)"#,
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)? as u64,
+                    row.get::<_, i64>(1)? as u64,
+                    row.get::<_, i64>(2)? as u64,
+                ))
+            },
+        )?;
+        Ok(totals)
+    }
+
+    /// Items and their pastes per content type, most items first. The
+    /// content type is returned as `name`.
+    pub(crate) fn fetch_content_type_usage(&self) -> DatabaseResult<Vec<UsageTallyRow>> {
+        self.fetch_usage_tallies(
+            r#"SELECT NULL, i.contentType, COUNT(*) AS itemCount, COALESCE(SUM(p.pastes), 0)
+               FROM items i
+               LEFT JOIN (SELECT itemId, COUNT(*) AS pastes FROM paste_events GROUP BY itemId) p
+                 ON p.itemId = i.id
+               WHERE i.deletedAt IS NULL
+               GROUP BY i.contentType
+               ORDER BY itemCount DESC, i.contentType"#,
+        )
+    }
+
+    /// Items and their pastes per source app, most items first, named by
+    /// registered metadata when there is any.
+    pub(crate) fn fetch_source_app_usage(&self) -> DatabaseResult<Vec<UsageTallyRow>> {
+        self.fetch_usage_tallies(
+            r#"SELECT a.bundleId, COALESCE(m.displayName, a.name), COUNT(*) AS itemCount,
+                      COALESCE(SUM(p.pastes), 0)
+               FROM items i
+               JOIN apps a ON a.id = i.appId
+               LEFT JOIN app_metadata m ON m.bundleId = a.bundleId
+               LEFT JOIN (SELECT itemId, COUNT(*) AS pastes FROM paste_events GROUP BY itemId) p
+                 ON p.itemId = i.id
+               WHERE i.deletedAt IS NULL
+               GROUP BY a.id
+               ORDER BY itemCount DESC, a.id"#,
+        )
+    }
+
+    fn fetch_usage_tallies(&self, sql: &str) -> DatabaseResult<Vec<UsageTallyRow>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(UsageTallyRow {
+                    bundle_id: row.get(0)?,
+                    name: row.get(1)?,
+                    item_count: row.get::<_, i64>(2)? as u64,
+                    paste_count: row.get::<_, i64>(3)? as u64,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Copy and paste events since `start` per local day, after shifting
+    /// UTC timestamps by `utc_offset_seconds`. Returns
+    /// `(YYYY-MM-DD, copies, pastes)` for non-empty days only.
+    pub(crate) fn fetch_daily_usage(
+        &self,
+        start: DateTime<Utc>,
+        utc_offset_seconds: i32,
+    ) -> DatabaseResult<Vec<(String, u64, u64)>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"SELECT day, SUM(copied), SUM(pasted) FROM (
+                   SELECT date(c.copiedAt, ?2) AS day, 1 AS copied, 0 AS pasted
+                   FROM copy_events c JOIN items i ON i.id = c.itemId
+                   WHERE c.copiedAt >= ?1 AND i.deletedAt IS NULL
+                   UNION ALL
+                   SELECT date(p.pastedAt, ?2), 0, 1
+                   FROM paste_events p JOIN items i ON i.id = p.itemId
+                   WHERE p.pastedAt >= ?1 AND i.deletedAt IS NULL
+               )
+               GROUP BY day"#,
+        )?;
+        let rows = stmt
+            .query_map(
+                params![
+                    start.format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+                    format!("{utc_offset_seconds:+} seconds"),
+                ],
+                |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get::<_, i64>(1)? as u64,
+                        row.get::<_, i64>(2)? as u64,
+                    ))
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Pasted items, most pasted first, then most recently pasted.
+    pub(crate) fn fetch_most_pasted(&self, limit: usize) -> DatabaseResult<Vec<ItemPasteRow>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"SELECT i.item_id, COUNT(*) AS pasteCount, MAX(p.pastedAt) AS lastPastedAt
+               FROM paste_events p
+               JOIN items i ON i.id = p.itemId
+               WHERE i.deletedAt IS NULL
+               GROUP BY p.itemId
+               ORDER BY pasteCount DESC, lastPastedAt DESC, p.itemId DESC
+               LIMIT ?1"#,
+        )?;
+        let rows = stmt
+            .query_map([limit as i64], |row| {
+                let last_pasted_at: String = row.get(2)?;
+                Ok(ItemPasteRow {
+                    item_id: row.get(0)?,
+                    paste_count: row.get::<_, i64>(1)? as u64,
+                    last_pasted_at_unix: parse_db_timestamp(&last_pasted_at).timestamp(),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Items copied since `start`, counted per local day and hour after
+    /// shifting UTC timestamps by `utc_offset_seconds`. Returns
+    /// `(YYYY-MM-DD, hour, count)` for non-empty cells only.
+    pub(crate) fn fetch_hourly_activity(
+        &self,
+        start: DateTime<Utc>,
+        utc_offset_seconds: i32,
+    ) -> DatabaseResult<Vec<(String, u32, u32)>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"SELECT date(timestamp, ?2) AS day,
+                      CAST(strftime('%H', timestamp, ?2) AS INTEGER) AS hour,
+                      COUNT(*)
+               FROM items
+               WHERE timestamp >= ?1 AND deletedAt IS NULL
+               GROUP BY day, hour"#,
+        )?;
+        let rows = stmt
+            .query_map(
+                params![
+                    start.format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+                    format!("{utc_offset_seconds:+} seconds"),
+                ],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Distinct pasted items ordered by their latest paste, newest first.
+    pub(crate) fn fetch_recent_pastes(&self, limit: usize) -> DatabaseResult<Vec<RecentPasteRow>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"SELECT p.itemId, i.item_id, p.targetBundleId, p.pastedAt, p.pasteCount FROM (
+                   SELECT itemId, targetBundleId, pastedAt,
+                          COUNT(*) OVER (PARTITION BY itemId) AS pasteCount,
+                          ROW_NUMBER() OVER (PARTITION BY itemId ORDER BY pastedAt DESC, id DESC) AS latest
+                   FROM paste_events
+               ) p
+               JOIN items i ON i.id = p.itemId
+               WHERE p.latest = 1
+               ORDER BY p.pastedAt DESC
+               LIMIT ?1"#,
         )?;
-        Ok(())
+        let rows = stmt
+            .query_map([limit as i64], |row| {
+                let pasted_at: String = row.get(3)?;
+                Ok(RecentPasteRow {
+                    row_id: row.get(0)?,
+                    item_id: row.get(1)?,
+                    target_bundle_id: row.get(2)?,
+                    pasted_at_unix: parse_db_timestamp(&pasted_at).timestamp(),
+                    paste_count: row.get::<_, i64>(4)? as u64,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
     }
 
-    pub fn get_tags_for_ids(
+    /// The most recent items plus every item ever pasted into `target_bundle_id`.
+    pub(crate) fn fetch_context_candidates(
         &self,
-        ids: &[i64],
-    ) -> DatabaseResult<std::collections::HashMap<i64, Vec<ItemTag>>> {
-        if ids.is_empty() {
-            return Ok(std::collections::HashMap::new());
-        }
-
+        target_bundle_id: &str,
+        recent_limit: usize,
+    ) -> DatabaseResult<Vec<ContextCandidateRow>> {
         let conn = self.get_conn()?;
-        let placeholders = ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
         let sql = format!(
-            "SELECT itemId, tag FROM item_tags WHERE itemId IN ({}) ORDER BY tag",
-            placeholders
+            r#"SELECT id, contentType, substr(ltrim({}, char(9) || char(10) || char(13) || ' '), 1, {}), sourceAppBundleId, timestamp
+               FROM items
+               WHERE deletedAt IS NULL
+                 AND (id IN (SELECT id FROM items WHERE deletedAt IS NULL ORDER BY timestamp DESC, id DESC LIMIT ?1)
+                      OR id IN (SELECT itemId FROM paste_events WHERE targetBundleId = ?2))"#,
+            self.unsealed("content"),
+            CODE_DETECTION_PREFIX_CHARS
         );
         let mut stmt = conn.prepare(&sql)?;
-        let params: Vec<rusqlite::types::Value> = ids.iter().map(|&id| id.into()).collect();
-        let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
-            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
-        })?;
-
-        let mut map: std::collections::HashMap<i64, Vec<ItemTag>> =
-            std::collections::HashMap::new();
-        for row in rows {
-            let (id, tag) = row?;
-            let tag = ItemTag::from_database_str(&tag).map_err(DatabaseError::InconsistentData)?;
-            map.entry(id).or_default().push(tag);
-        }
-
-        Ok(map)
+        let rows = stmt
+            .query_map(params![recent_limit as i64, target_bundle_id], |row| {
+                let timestamp: String = row.get(4)?;
+                Ok(ContextCandidateRow {
+                    row_id: row.get(0)?,
+                    db_type: row
+                        .get::<_, Option<String>>(1)?
+                        .unwrap_or_else(|| "text".to_string()),
+                    content_prefix: row.get(2)?,
+                    source_app_bundle_id: row.get(3)?,
+                    timestamp_unix: parse_db_timestamp(&timestamp).timestamp(),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
     }
 
     /// Resolve a string item_id to its numeric row ID.
@@ -1315,7 +5130,46 @@ impl Database {
         }
     }
 
+    /// The item ID behind an external ID, if the item still exists.
+    pub(crate) fn fetch_item_id_by_external_id(
+        &self,
+        external_id: &str,
+    ) -> DatabaseResult<Option<String>> {
+        let conn = self.get_conn()?;
+        let result = conn.query_row(
+            "SELECT item_id FROM items WHERE externalId = ?1",
+            [external_id],
+            |row| row.get(0),
+        );
+        match result {
+            Ok(item_id) => Ok(Some(item_id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The item's external ID, assigning one if the row has none yet.
+    pub(crate) fn fetch_external_id(&self, row_id: i64) -> DatabaseResult<String> {
+        let conn = self.get_conn()?;
+        let (external_id, timestamp): (Option<String>, String) = conn.query_row(
+            "SELECT externalId, timestamp FROM items WHERE id = ?1",
+            [row_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        if let Some(external_id) = external_id {
+            return Ok(external_id);
+        }
+        let external_id = StoredItem::external_id_at(parse_db_timestamp(&timestamp).timestamp());
+        conn.execute(
+            "UPDATE items SET externalId = ?1 WHERE id = ?2",
+            params![external_id, row_id],
+        )?;
+        Ok(external_id)
+    }
+
     /// Fetch full items by string item_ids, preserving the order of the input IDs.
+    /// Items by stable ID, in the order given. Trashed items are left out.
+    #[tracing::instrument(level = "debug", name = "db.fetch_items_by_item_ids", skip_all, fields(count = item_ids.len()))]
     pub fn fetch_items_by_item_ids(&self, item_ids: &[String]) -> DatabaseResult<Vec<StoredItem>> {
         if item_ids.is_empty() {
             return Ok(Vec::new());
@@ -1324,7 +5178,8 @@ impl Database {
         let conn = self.get_conn()?;
         let placeholders = item_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
         let sql = format!(
-            "SELECT id, contentType, contentHash, content, timestamp, sourceApp, sourceAppBundleId, thumbnail, colorRgba, item_id FROM items WHERE item_id IN ({})",
+            "SELECT {} FROM items WHERE item_id IN ({}) AND deletedAt IS NULL",
+            self.base_item_columns(),
             placeholders
         );
 
@@ -1337,7 +5192,7 @@ impl Database {
 
         for item in &mut items {
             if let Some(id) = item.id {
-                Self::populate_child_content(&conn, item, id)?;
+                self.populate_child_content(&conn, item, id)?;
             }
         }
 
@@ -1385,6 +5240,39 @@ impl Database {
         Ok(map)
     }
 
+    pub fn set_pinned(&self, id: i64, pinned: bool) -> DatabaseResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE items SET pinned = ?1 WHERE id = ?2",
+            params![pinned, id],
+        )?;
+        Ok(())
+    }
+
+    /// The subset of `item_ids` that are pinned.
+    pub fn get_pinned_item_ids(
+        &self,
+        item_ids: &[String],
+    ) -> DatabaseResult<std::collections::HashSet<String>> {
+        if item_ids.is_empty() {
+            return Ok(std::collections::HashSet::new());
+        }
+
+        let conn = self.get_conn()?;
+        let placeholders = item_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let sql = format!(
+            "SELECT item_id FROM items WHERE pinned = 1 AND item_id IN ({})",
+            placeholders
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let params: Vec<rusqlite::types::Value> =
+            item_ids.iter().map(|id| id.clone().into()).collect();
+        let pinned = stmt
+            .query_map(rusqlite::params_from_iter(params), |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(pinned)
+    }
+
     pub fn filter_ids_by_tag(&self, ids: &[i64], tag: ItemTag) -> DatabaseResult<Vec<i64>> {
         if ids.is_empty() {
             return Ok(Vec::new());
@@ -1418,6 +5306,8 @@ impl Database {
         let thumbnail: Option<Vec<u8>> = row.get(7)?;
         let color_rgba: Option<u32> = row.get(8)?;
         let item_id: String = row.get(9)?;
+        // Rows inserted behind the store's back have none until the next open.
+        let external_id: String = row.get::<_, Option<String>>(10)?.unwrap_or_default();
 
         let timestamp = parse_db_timestamp(&timestamp_str);
 
@@ -1426,6 +5316,12 @@ impl Database {
             "color" => ClipboardContent::Color {
                 value: content_text,
             },
+            "phone" => ClipboardContent::PhoneNumber {
+                value: content_text,
+            },
+            "address" => ClipboardContent::PostalAddress {
+                value: content_text,
+            },
             "image" => ClipboardContent::Image {
                 data: Vec::new(),
                 description: content_text,
@@ -1439,6 +5335,15 @@ impl Database {
                 display_name: content_text,
                 files: Vec::new(),
             },
+            "rich_text" => ClipboardContent::RichText {
+                value: content_text,
+                rtf_data: None,
+                html: None,
+            },
+            "markdown" => ClipboardContent::Markdown {
+                value: content_text,
+                source: String::new(),
+            },
             _ => ClipboardContent::Text {
                 value: content_text,
             },
@@ -1447,6 +5352,7 @@ impl Database {
         Ok(StoredItem {
             id: Some(id),
             item_id,
+            external_id,
             content,
             content_hash,
             timestamp_unix: timestamp.timestamp(),
@@ -1454,12 +5360,15 @@ impl Database {
             source_app_bundle_id,
             thumbnail,
             color_rgba,
+            ocr_text: None,
+            file_contents_text: None,
         })
     }
 
     /// Populate the child table content for a StoredItem.
     /// Must be called after `row_to_base_item` to fill in type-specific data.
     fn populate_child_content(
+        &self,
         conn: &rusqlite::Connection,
         item: &mut StoredItem,
         item_id: i64,
@@ -1467,14 +5376,17 @@ impl Database {
         match &item.content {
             ClipboardContent::Image { description, .. } => {
                 let description = description.clone();
-                let (data, is_animated): (Vec<u8>, bool) = conn
+                let (data, is_animated, ocr_text): (Vec<u8>, bool, Option<String>) = conn
                     .query_row(
-                        "SELECT data, is_animated FROM image_items WHERE itemId = ?1",
+                        &format!(
+                            "SELECT data, is_animated, {} FROM image_items WHERE itemId = ?1",
+                            self.unsealed("ocrText")
+                        ),
                         [item_id],
                         |row| {
                             let data: Vec<u8> = row.get(0)?;
                             let is_animated: i32 = row.get(1)?;
-                            Ok((data, is_animated != 0))
+                            Ok((data, is_animated != 0, row.get(2)?))
                         },
                     )
                     .map_err(|error| match error {
@@ -1484,15 +5396,20 @@ impl Database {
                         other => DatabaseError::Sqlite(other),
                     })?;
                 item.content = ClipboardContent::Image {
-                    data,
+                    data: self.open_payload(data)?,
                     description,
                     is_animated,
                 };
+                item.ocr_text = ocr_text;
             }
             ClipboardContent::Link { url, .. } => {
                 let url = url.clone();
                 let result = conn.query_row(
-                    "SELECT title, description FROM link_items WHERE itemId = ?1",
+                    &format!(
+                        "SELECT {}, {} FROM link_items WHERE itemId = ?1",
+                        self.unsealed("title"),
+                        self.unsealed("description")
+                    ),
                     [item_id],
                     |row| {
                         let title: Option<String> = row.get(0)?;
@@ -1526,7 +5443,10 @@ impl Database {
             ClipboardContent::File { display_name, .. } => {
                 let display_name = display_name.clone();
                 let mut stmt = conn.prepare(
-                    "SELECT path, filename, fileSize, uti, bookmarkData, fileStatus, previewKind, previewReason, previewText, previewData, previewTruncated FROM file_items WHERE itemId = ?1 ORDER BY ordinal"
+                    "SELECT e.path, e.filename, e.fileSize, e.uti, e.bookmarkData, e.fileStatus, e.previewKind, e.previewReason, e.previewText, e.previewData, e.previewTruncated,
+                            e.pdfTitle, e.pdfAuthor, e.emailSubject, e.emailSender, e.archiveEntries
+                     FROM file_items f JOIN file_entries e ON e.id = f.entryId
+                     WHERE f.itemId = ?1 ORDER BY f.ordinal"
                 )?;
                 let rows = stmt.query_map([item_id], |row| {
                     let path: String = row.get(0)?;
@@ -1548,11 +5468,28 @@ impl Database {
                         preview_text: row.get(8)?,
                         preview_data: row.get(9)?,
                         preview_truncated: preview_truncated != 0,
+                        contents_text: [
+                            crate::pdf_info::index_text(
+                                row.get::<_, Option<String>>(11)?.as_deref(),
+                                row.get::<_, Option<String>>(12)?.as_deref(),
+                            ),
+                            crate::email_info::index_text(
+                                row.get::<_, Option<String>>(13)?.as_deref(),
+                                row.get::<_, Option<String>>(14)?.as_deref(),
+                            ),
+                            // Stored one entry per line, as indexed.
+                            row.get::<_, Option<String>>(15)?,
+                        ]
+                        .into_iter()
+                        .flatten()
+                        .reduce(|text, more| text + "\n" + &more),
                     })
                 })?;
                 let mut files = Vec::new();
+                let mut contents = Vec::new();
                 for row in rows {
                     let row = row?;
+                    contents.extend(row.contents_text);
                     let preview = FilePreviewSnapshot::from_database(
                         &row.preview_kind,
                         row.preview_reason.as_deref(),
@@ -1579,6 +5516,69 @@ impl Database {
                     display_name,
                     files,
                 };
+                item.file_contents_text = (!contents.is_empty()).then(|| contents.join("\n"));
+            }
+            ClipboardContent::RichText { value, .. } => {
+                let value = value.clone();
+                let (rtf_data, html_data): (Option<Vec<u8>>, Option<Vec<u8>>) = conn
+                    .query_row(
+                        "SELECT rtfData, htmlData FROM rich_text_items WHERE itemId = ?1",
+                        [item_id],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .map_err(|error| match error {
+                        rusqlite::Error::QueryReturnedNoRows => {
+                            DatabaseError::InconsistentData(format!(
+                                "rich text item {item_id} is missing its rich_text_items child row"
+                            ))
+                        }
+                        other => DatabaseError::Sqlite(other),
+                    })?;
+                let html = html_data
+                    .map(|html| {
+                        String::from_utf8(self.open_payload(html)?).map_err(|_| {
+                            DatabaseError::InconsistentData(format!(
+                                "rich text item {item_id} has HTML that is not UTF-8"
+                            ))
+                        })
+                    })
+                    .transpose()?;
+                item.content = ClipboardContent::RichText {
+                    value,
+                    rtf_data: rtf_data.map(|rtf| self.open_payload(rtf)).transpose()?,
+                    html,
+                };
+            }
+            ClipboardContent::Markdown { value, .. } => {
+                let value = value.clone();
+                let stored: rusqlite::types::Value = conn
+                    .query_row(
+                        "SELECT value FROM text_items WHERE itemId = ?1",
+                        [item_id],
+                        |row| row.get(0),
+                    )
+                    .map_err(|error| match error {
+                        rusqlite::Error::QueryReturnedNoRows => DatabaseError::InconsistentData(
+                            format!("markdown item {item_id} is missing its text_items child row"),
+                        ),
+                        other => DatabaseError::Sqlite(other),
+                    })?;
+                let source = match stored {
+                    rusqlite::types::Value::Text(source) => source,
+                    rusqlite::types::Value::Blob(sealed) => {
+                        String::from_utf8(self.open_payload(sealed)?).map_err(|_| {
+                            DatabaseError::InconsistentData(format!(
+                                "markdown item {item_id} has a source that is not UTF-8"
+                            ))
+                        })?
+                    }
+                    _ => {
+                        return Err(DatabaseError::InconsistentData(format!(
+                            "markdown item {item_id} has no source"
+                        )))
+                    }
+                };
+                item.content = ClipboardContent::Markdown { value, source };
             }
             // Text, Color, Email, Phone — content_text from items is sufficient
             _ => {}
@@ -1597,11 +5597,20 @@ impl Database {
         let thumbnail: Option<Vec<u8>> = row.get(6)?;
         let color_rgba: Option<u32> = row.get(7)?;
         let item_id: String = row.get(8)?;
+        let first_file_uti: Option<String> = row.get(9)?;
+        let first_file_name: Option<String> = row.get(10)?;
 
         let timestamp = parse_db_timestamp(&timestamp_str);
         let db_type = content_type.as_deref().unwrap_or("text");
 
-        let icon = ItemIcon::from_database(db_type, color_rgba, thumbnail);
+        let icon = select_icon(IconSource {
+            db_type,
+            text: &content,
+            color_rgba,
+            thumbnail,
+            source_app_bundle_id: source_app_bundle_id.as_deref(),
+            first_file: first_file_pair(&first_file_uti, &first_file_name),
+        });
 
         Ok(RawRowMetadata {
             content_prefix: content,
@@ -1612,6 +5621,7 @@ impl Database {
                 source_app_bundle_id,
                 timestamp_unix: timestamp.timestamp(),
                 tags: Vec::new(),
+                is_pinned: false,
             },
         })
     }
@@ -1630,9 +5640,18 @@ impl Database {
         let thumbnail: Option<Vec<u8>> = row.get(6)?;
         let color_rgba: Option<u32> = row.get(7)?;
         let item_id: String = row.get(8)?;
+        let first_file_uti: Option<String> = row.get(9)?;
+        let first_file_name: Option<String> = row.get(10)?;
 
         let timestamp = parse_db_timestamp(&timestamp_str);
-        let icon = ItemIcon::from_database(&db_type, color_rgba, thumbnail);
+        let icon = select_icon(IconSource {
+            db_type: &db_type,
+            text: &content_prefix,
+            color_rgba,
+            thumbnail,
+            source_app_bundle_id: source_app_bundle_id.as_deref(),
+            first_file: first_file_pair(&first_file_uti, &first_file_name),
+        });
 
         Ok(RawSearchRowMetadata {
             content_hash,
@@ -1646,6 +5665,7 @@ impl Database {
                     source_app_bundle_id,
                     timestamp_unix: timestamp.timestamp(),
                     tags: Vec::new(),
+                    is_pinned: false,
                 },
             },
         })
@@ -1731,7 +5751,14 @@ mod tests {
         seed_base_item(&db, "text", &content, None);
 
         let (items, total_count) = db
-            .fetch_browse_row_metadata(None, 1, None, None, ListPresentationProfile::CompactRow)
+            .fetch_browse_row_metadata(
+                None,
+                0,
+                1,
+                None,
+                &TagRestriction::default(),
+                ListPresentationProfile::CompactRow,
+            )
             .unwrap();
 
         assert_eq!(total_count, 1);
@@ -1742,6 +5769,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_equal_timestamps_order_by_id_in_short_queries_and_browse_pages() {
+        let db = Database::open_in_memory().unwrap();
+        // One timestamp for all, as with a burst of copies in one second.
+        let mut expected: Vec<i64> = (0..10)
+            .map(|n| seed_base_item(&db, "text", &format!("a note {n}"), None))
+            .collect();
+        expected.reverse();
+        let tags = TagRestriction::default();
+        let ids = |rows: Vec<(i64, String, i64)>| -> Vec<i64> {
+            rows.into_iter().map(|(id, _, _)| id).collect()
+        };
+
+        for _ in 0..5 {
+            assert_eq!(
+                ids(db.search_prefix_query("a", 100, None, &tags).unwrap()),
+                expected
+            );
+            assert_eq!(
+                ids(db
+                    .fetch_recent_items_for_short_query(100, None, &tags)
+                    .unwrap()),
+                expected
+            );
+        }
+
+        let mut paged = Vec::new();
+        for offset in (0..10).step_by(3) {
+            let (rows, total_count) = db
+                .fetch_browse_row_metadata(
+                    None,
+                    offset,
+                    3,
+                    None,
+                    &tags,
+                    ListPresentationProfile::CompactRow,
+                )
+                .unwrap();
+            assert_eq!(total_count, 10);
+            paged.extend(rows.into_iter().map(|row| row.item_metadata.item_id));
+        }
+        let (all, _) = db
+            .fetch_browse_row_metadata(
+                None,
+                0,
+                10,
+                None,
+                &tags,
+                ListPresentationProfile::CompactRow,
+            )
+            .unwrap();
+        let all: Vec<String> = all
+            .into_iter()
+            .map(|row| row.item_metadata.item_id)
+            .collect();
+        assert_eq!(paged, all);
+    }
+
     #[test]
     fn test_new_schema_requires_non_null_item_id() {
         let db = Database::open_in_memory().unwrap();
@@ -1802,6 +5887,68 @@ mod tests {
         assert_eq!(items[0].content.text_content(), "legacy text");
     }
 
+    #[test]
+    fn test_entities_follow_text_and_are_backfilled() {
+        let temp = NamedTempFile::new().unwrap();
+        {
+            let conn = rusqlite::Connection::open(temp.path()).unwrap();
+            conn.execute_batch(
+                r#"
+                CREATE TABLE items (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    contentType TEXT NOT NULL,
+                    contentHash TEXT NOT NULL,
+                    content TEXT NOT NULL,
+                    timestamp TEXT NOT NULL,
+                    sourceApp TEXT,
+                    sourceAppBundleId TEXT,
+                    thumbnail BLOB,
+                    colorRgba INTEGER
+                );
+                CREATE TABLE text_items (
+                    itemId INTEGER PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
+                    value TEXT NOT NULL
+                );
+                INSERT INTO items (id, contentType, contentHash, content, timestamp)
+                VALUES (1, 'text', 'hash-1', 'mail me@example.com', '2026-01-01 00:00:00');
+                INSERT INTO text_items (itemId, value) VALUES (1, 'mail me@example.com');
+                "#,
+            )
+            .unwrap();
+        }
+
+        let db = Database::open(temp.path()).unwrap();
+        let kinds = |id| -> Vec<(EntityKind, String)> {
+            db.fetch_entities(id)
+                .unwrap()
+                .into_iter()
+                .map(|entity| (entity.kind, entity.value))
+                .collect()
+        };
+        assert_eq!(kinds(1), vec![(EntityKind::Email, "me@example.com".into())]);
+
+        db.update_text_item(1, "see https://example.com", "hash-2")
+            .unwrap();
+        assert_eq!(
+            kinds(1),
+            vec![(EntityKind::Url, "https://example.com".into())]
+        );
+
+        let id = db
+            .insert_item(&StoredItem::new_text(
+                "call +1 555-123-4567".into(),
+                None,
+                None,
+            ))
+            .unwrap();
+        assert_eq!(
+            kinds(id),
+            vec![(EntityKind::PhoneNumber, "+1 555-123-4567".into())]
+        );
+        db.delete_item(id).unwrap();
+        assert_eq!(kinds(id), vec![]);
+    }
+
     #[test]
     fn test_legacy_image_descriptions_are_prefixed() {
         let temp = NamedTempFile::new().unwrap();
@@ -1867,6 +6014,186 @@ mod tests {
         assert_eq!(description(3), "Image: a cat");
     }
 
+    fn file_entry_counts(db: &Database) -> Vec<(String, i64)> {
+        let conn = db.get_conn().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT path, refCount FROM file_entries ORDER BY path")
+            .unwrap();
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_file_entries_are_shared_between_items() {
+        let db = Database::open_in_memory().unwrap();
+        let files = |paths: &[&str]| {
+            StoredItem::new_files(
+                paths.iter().map(|p| p.to_string()).collect(),
+                paths
+                    .iter()
+                    .map(|p| p.rsplit('/').next().unwrap().into())
+                    .collect(),
+                vec![10; paths.len()],
+                vec!["public.plain-text".into(); paths.len()],
+                paths.iter().map(|p| p.as_bytes().to_vec()).collect(),
+                vec![
+                    FilePreviewSnapshot::Unavailable {
+                        reason: crate::interface::FilePreviewUnavailableReason::NotCaptured,
+                    };
+                    paths.len()
+                ],
+                None,
+                None,
+            )
+        };
+        let first = files(&["/tmp/a.txt", "/tmp/b.txt"]);
+        let second = files(&["/tmp/b.txt", "/tmp/c.txt"]);
+        let first_id = db.insert_item(&first).unwrap();
+        let second_id = db.insert_item(&second).unwrap();
+        assert_eq!(
+            file_entry_counts(&db),
+            vec![
+                ("/tmp/a.txt".into(), 1),
+                ("/tmp/b.txt".into(), 2),
+                ("/tmp/c.txt".into(), 1)
+            ]
+        );
+        assert_eq!(
+            db.fetch_item_ids_with_file("/tmp/b.txt").unwrap(),
+            vec![second_id, first_id]
+        );
+        let fetched = db
+            .fetch_items_by_item_ids(std::slice::from_ref(&second.item_id))
+            .unwrap();
+        assert_eq!(fetched[0].content, second.content);
+
+        db.delete_item(first_id).unwrap();
+        assert_eq!(
+            file_entry_counts(&db),
+            vec![("/tmp/b.txt".into(), 1), ("/tmp/c.txt".into(), 1)]
+        );
+        db.delete_item(second_id).unwrap();
+        assert_eq!(file_entry_counts(&db), vec![]);
+    }
+
+    #[test]
+    fn test_legacy_file_items_move_to_shared_entries() {
+        let temp = NamedTempFile::new().unwrap();
+        {
+            let conn = rusqlite::Connection::open(temp.path()).unwrap();
+            conn.execute_batch(
+                r#"
+                PRAGMA foreign_keys=ON;
+                CREATE TABLE items (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    contentType TEXT NOT NULL,
+                    contentHash TEXT NOT NULL,
+                    content TEXT NOT NULL,
+                    timestamp TEXT NOT NULL,
+                    sourceApp TEXT,
+                    sourceAppBundleId TEXT,
+                    thumbnail BLOB,
+                    colorRgba INTEGER
+                );
+                CREATE TABLE file_items (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    itemId INTEGER NOT NULL REFERENCES items(id) ON DELETE CASCADE,
+                    ordinal INTEGER NOT NULL DEFAULT 0,
+                    path TEXT NOT NULL,
+                    filename TEXT NOT NULL,
+                    fileSize INTEGER NOT NULL DEFAULT 0,
+                    uti TEXT NOT NULL DEFAULT 'public.item',
+                    bookmarkData BLOB NOT NULL,
+                    fileStatus TEXT NOT NULL DEFAULT 'available'
+                );
+                CREATE INDEX idx_file_items_item ON file_items(itemId);
+                INSERT INTO items (id, contentType, contentHash, content, timestamp) VALUES
+                    (1, 'file', 'hash-1', 'a.txt', '2026-01-01 00:00:00'),
+                    (2, 'file', 'hash-2', '2 Files: a.txt, b.txt', '2026-01-01 00:00:01');
+                INSERT INTO file_items (itemId, ordinal, path, filename, fileSize, bookmarkData, fileStatus) VALUES
+                    (1, 0, '/tmp/a.txt', 'a.txt', 10, X'01', 'available'),
+                    (2, 0, '/tmp/a.txt', 'a.txt', 12, X'01', 'moved:/tmp/old/a.txt'),
+                    (2, 1, '/tmp/b.txt', 'b.txt', 20, X'02', 'available');
+                "#,
+            )
+            .unwrap();
+        }
+
+        let db = Database::open(temp.path()).unwrap();
+        assert_eq!(
+            file_entry_counts(&db),
+            vec![("/tmp/a.txt".into(), 2), ("/tmp/b.txt".into(), 1)]
+        );
+        assert_eq!(
+            db.fetch_item_ids_with_file("/tmp/a.txt").unwrap(),
+            vec![2, 1]
+        );
+        let item_id: String = db
+            .get_conn()
+            .unwrap()
+            .query_row("SELECT item_id FROM items WHERE id = 2", [], |r| r.get(0))
+            .unwrap();
+        let fetched = db.fetch_items_by_item_ids(&[item_id]).unwrap();
+        let ClipboardContent::File { files, .. } = &fetched[0].content else {
+            panic!("expected a file item");
+        };
+        let paths: Vec<_> = files
+            .iter()
+            .map(|f| (f.path.as_str(), f.file_size))
+            .collect();
+        assert_eq!(paths, vec![("/tmp/a.txt", 12), ("/tmp/b.txt", 20)]);
+        assert_eq!(
+            files[0].file_status,
+            FileStatus::Moved {
+                new_path: "/tmp/old/a.txt".into()
+            }
+        );
+
+        db.delete_item(2).unwrap();
+        assert_eq!(file_entry_counts(&db), vec![("/tmp/a.txt".into(), 1)]);
+    }
+
+    #[test]
+    fn test_external_ids_are_backfilled_and_kept_unique() {
+        let temp = NamedTempFile::new().unwrap();
+        let copy = StoredItem::new_text("deep link me".into(), None, None);
+        {
+            let db = Database::open(temp.path()).unwrap();
+            let row_id = db.insert_item(&copy).unwrap();
+            assert_eq!(db.fetch_external_id(row_id).unwrap(), copy.external_id);
+            // Same external ID under another item ID, as from an import.
+            let mut twin = StoredItem::new_text("twin".into(), None, None);
+            twin.external_id = copy.external_id.clone();
+            let twin_row = db.insert_item(&twin).unwrap();
+            assert_ne!(db.fetch_external_id(twin_row).unwrap(), copy.external_id);
+            db.get_conn()
+                .unwrap()
+                .execute(
+                    "INSERT INTO items (item_id, contentType, contentHash, content, timestamp)
+                     VALUES ('legacy', 'text', 'h', 'old', '2024-05-01 12:00:00')",
+                    [],
+                )
+                .unwrap();
+        }
+
+        let db = Database::open(temp.path()).unwrap();
+        let legacy_row = db.fetch_row_id_by_item_id("legacy").unwrap().unwrap();
+        let legacy = db.fetch_external_id(legacy_row).unwrap();
+        let ulid: ulid::Ulid = legacy.parse().unwrap();
+        assert_eq!(ulid.timestamp_ms(), 1_714_564_800_000);
+        assert_eq!(
+            db.fetch_item_id_by_external_id(&legacy).unwrap().as_deref(),
+            Some("legacy")
+        );
+        assert_eq!(
+            db.fetch_item_id_by_external_id(&copy.external_id).unwrap(),
+            Some(copy.item_id)
+        );
+        assert_eq!(db.fetch_item_id_by_external_id("missing").unwrap(), None);
+    }
+
     #[test]
     fn test_pinned_item_tags_are_migrated_to_bookmarks() {
         let temp = NamedTempFile::new().unwrap();
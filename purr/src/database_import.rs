@@ -0,0 +1,191 @@
+//! Merging another ClipKitty database into the store.
+//!
+//! The source file is never written: it is copied with `VACUUM INTO`, which
+//! also folds in any pending WAL, and the copy is opened and migrated like a
+//! store, so databases from older versions import too. Incoming items are
+//! classified by `ImportClassifier`, exactly as in bundle imports, and keep
+//! their original timestamps. Tags, reminders, and immutability travel with
+//! each item by external ID, since the two databases' row IDs overlap.
+
+use crate::bundle;
+use crate::database::{Database, DatabaseError, ItemLinks};
+use crate::import_preview::{ImportClassifier, ImportDisposition};
+use crate::indexer::Indexer;
+use crate::interface::{ClipKittyError, DatabaseImportSummary, ImportStrategy};
+use crate::models::StoredItem;
+use chrono::{TimeZone, Utc};
+use std::path::{Path, PathBuf};
+
+const BATCH_SIZE: usize = 500;
+
+/// A migrated private copy of the database being imported, removed on drop.
+pub(crate) struct SourceDatabase {
+    db: Option<Database>,
+    directory: PathBuf,
+}
+
+impl SourceDatabase {
+    pub(crate) fn open(path: &Path) -> Result<Self, ClipKittyError> {
+        if !path.is_file() {
+            return Err(ClipKittyError::InvalidInput(format!(
+                "{} is not a database file",
+                path.display()
+            )));
+        }
+        let directory = std::env::temp_dir().join(format!(
+            "clipkitty-import-{}-{}",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::create_dir_all(&directory).map_err(DatabaseError::from)?;
+        let mut source = Self {
+            db: None,
+            directory,
+        };
+        let copy = source.directory.join("source.sqlite");
+        let conn =
+            rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .map_err(DatabaseError::from)?;
+        conn.execute("VACUUM INTO ?1", [copy.to_string_lossy()])
+            .map_err(DatabaseError::from)?;
+        drop(conn);
+        source.db = Some(Database::open(&copy)?);
+        Ok(source)
+    }
+
+    /// Call `f` with every item, newest first, along with its links. Links
+    /// are matched to items by external ID, which `Database::open` backfilled
+    /// for databases from older versions.
+    pub(crate) fn for_each_item(
+        &self,
+        mut f: impl FnMut(StoredItem, ItemLinks) -> Result<(), ClipKittyError>,
+    ) -> Result<(), ClipKittyError> {
+        let db = self
+            .db
+            .as_ref()
+            .expect("source database is open until drop");
+        let mut links = db.fetch_item_links()?;
+        for ids in db.fetch_all_item_ids()?.chunks(BATCH_SIZE) {
+            for item in db.fetch_items_by_ids(ids)? {
+                let item_links = links.remove(&item.external_id).unwrap_or_default();
+                f(item, item_links)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SourceDatabase {
+    fn drop(&mut self) {
+        // Close the pool before deleting the files under it.
+        self.db = None;
+        let _ = std::fs::remove_dir_all(&self.directory);
+    }
+}
+
+/// A change `import_database` made, for the caller to pass on (as sync
+/// events, in the store).
+pub(crate) enum ImportEvent<'a> {
+    Inserted(&'a StoredItem, &'a ItemLinks),
+    Touched {
+        item_id: &'a str,
+        timestamp_unix: i64,
+    },
+}
+
+/// Merge the database at `source` into `db`, classifying each item as new, a
+/// duplicate, or a conflict and applying `strategy`. With `since_unix`, only
+/// items copied at or after that time are considered. The caller commits the
+/// index, even on failure, since inserted items stay.
+pub(crate) fn import_database(
+    db: &Database,
+    indexer: &Indexer,
+    source: &Path,
+    strategy: ImportStrategy,
+    since_unix: Option<i64>,
+    mut on_event: impl FnMut(ImportEvent<'_>) -> Result<(), ClipKittyError>,
+) -> Result<DatabaseImportSummary, ClipKittyError> {
+    let source = SourceDatabase::open(source)?;
+    let mut classifier = ImportClassifier::new(db);
+    let mut summary = DatabaseImportSummary {
+        imported: 0,
+        duplicates: 0,
+        conflicts: 0,
+    };
+    source.for_each_item(|mut item, links| {
+        if since_unix.is_some_and(|since| item.timestamp_unix < since) {
+            return Ok(());
+        }
+        match (classifier.classify(&item)?, strategy) {
+            (ImportDisposition::New, _) => {}
+            (ImportDisposition::Duplicate, ImportStrategy::KeepLocal) => {
+                summary.duplicates += 1;
+                return Ok(());
+            }
+            (ImportDisposition::Duplicate, ImportStrategy::Merge) => {
+                summary.duplicates += 1;
+                if let Some((item_id, timestamp_unix)) = keep_newer_timestamp(db, indexer, &item)? {
+                    on_event(ImportEvent::Touched {
+                        item_id: &item_id,
+                        timestamp_unix,
+                    })?;
+                }
+                return Ok(());
+            }
+            (ImportDisposition::Conflict, ImportStrategy::KeepLocal) => {
+                summary.conflicts += 1;
+                return Ok(());
+            }
+            (ImportDisposition::Conflict, ImportStrategy::Merge) => {
+                summary.conflicts += 1;
+                item.item_id = uuid::Uuid::new_v4().to_string();
+                item.external_id = StoredItem::external_id_at(item.timestamp_unix);
+            }
+        }
+        bundle::insert_imported(db, indexer, &mut item, &links)?;
+        on_event(ImportEvent::Inserted(&item, &links))?;
+        summary.imported += 1;
+        Ok(())
+    })?;
+    Ok(summary)
+}
+
+/// Move the local copy of a duplicate to the incoming timestamp when that is
+/// newer. Returns the local item ID and new timestamp if it moved; the caller
+/// commits the index.
+pub(crate) fn keep_newer_timestamp(
+    db: &Database,
+    indexer: &Indexer,
+    incoming: &StoredItem,
+) -> Result<Option<(String, i64)>, ClipKittyError> {
+    let local = match db.fetch_row_id_by_item_id(&incoming.item_id)? {
+        Some(row_id) => db.fetch_items_by_ids(&[row_id])?.into_iter().next(),
+        None => db.find_by_hash(&incoming.content_hash)?,
+    };
+    let Some(local) = local else {
+        return Ok(None);
+    };
+    let (Some(row_id), Some(timestamp)) = (
+        local.id,
+        Utc.timestamp_opt(incoming.timestamp_unix, 0).single(),
+    ) else {
+        return Ok(None);
+    };
+    if incoming.timestamp_unix <= local.timestamp_unix {
+        return Ok(None);
+    }
+    db.update_timestamp(row_id, timestamp)?;
+    indexer.add_document_with_row(
+        &local.item_id,
+        &index_text(&local),
+        incoming.timestamp_unix,
+        &db.fetch_user_tags(row_id)?,
+        &Database::indexed_row(&local),
+    )?;
+    Ok(Some((local.item_id, incoming.timestamp_unix)))
+}
+
+fn index_text(item: &StoredItem) -> String {
+    item.file_index_text()
+        .unwrap_or_else(|| item.text_content().to_string())
+}
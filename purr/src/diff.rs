@@ -0,0 +1,329 @@
+//! Line and word diffs between two text items.
+//!
+//! Lines are aligned with Myers' O((N+M)D) algorithm. Runs of deleted lines
+//! directly followed by inserted lines are treated as edits: each deleted line
+//! is paired with the inserted line at the same offset and the pair is diffed
+//! again word by word, so the preview pane can highlight the changed words.
+
+use crate::interface::{DiffLine, DiffOp, DiffSpan, ItemDiff};
+
+/// Lines beyond which a side is compared as a single replaced block, keeping
+/// Myers' trace memory bounded for pathological inputs.
+const MAX_DIFF_LINES: usize = 20_000;
+
+pub(crate) fn diff_texts(old: &str, new: &str) -> ItemDiff {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = if old_lines.len() > MAX_DIFF_LINES || new_lines.len() > MAX_DIFF_LINES {
+        let mut ops = vec![DiffOp::Delete; old_lines.len()];
+        ops.extend(vec![DiffOp::Insert; new_lines.len()]);
+        ops
+    } else {
+        myers(&old_lines, &new_lines)
+    };
+
+    let mut lines = Vec::with_capacity(ops.len());
+    let (mut old_index, mut new_index) = (0usize, 0usize);
+    let mut op_index = 0;
+    while op_index < ops.len() {
+        if ops[op_index] == DiffOp::Equal {
+            lines.push(DiffLine {
+                op: DiffOp::Equal,
+                old_line_number: Some(old_index as u32 + 1),
+                new_line_number: Some(new_index as u32 + 1),
+                spans: vec![span(DiffOp::Equal, old_lines[old_index])],
+            });
+            old_index += 1;
+            new_index += 1;
+            op_index += 1;
+            continue;
+        }
+
+        // A change block: every deletion and insertion up to the next equal
+        // line. Listing the block's deletions before its insertions is valid
+        // whatever order Myers interleaved them in.
+        let block = count_changes(&ops[op_index..]);
+        let deleted = ops[op_index..op_index + block]
+            .iter()
+            .filter(|op| **op == DiffOp::Delete)
+            .count();
+        let inserted = block - deleted;
+        let paired = deleted.min(inserted);
+        let mut deleted_lines = Vec::with_capacity(deleted);
+        let mut inserted_lines = Vec::with_capacity(inserted);
+        for offset in 0..deleted {
+            let old_line = old_lines[old_index + offset];
+            let spans = if offset < paired {
+                let (old_spans, _) = word_spans(old_line, new_lines[new_index + offset]);
+                old_spans
+            } else {
+                vec![span(DiffOp::Delete, old_line)]
+            };
+            deleted_lines.push(DiffLine {
+                op: DiffOp::Delete,
+                old_line_number: Some((old_index + offset) as u32 + 1),
+                new_line_number: None,
+                spans,
+            });
+        }
+        for offset in 0..inserted {
+            let new_line = new_lines[new_index + offset];
+            let spans = if offset < paired {
+                let (_, new_spans) = word_spans(old_lines[old_index + offset], new_line);
+                new_spans
+            } else {
+                vec![span(DiffOp::Insert, new_line)]
+            };
+            inserted_lines.push(DiffLine {
+                op: DiffOp::Insert,
+                old_line_number: None,
+                new_line_number: Some((new_index + offset) as u32 + 1),
+                spans,
+            });
+        }
+        lines.extend(deleted_lines);
+        lines.extend(inserted_lines);
+        old_index += deleted;
+        new_index += inserted;
+        op_index += deleted + inserted;
+    }
+
+    let deletions = lines.iter().filter(|l| l.op == DiffOp::Delete).count() as u32;
+    let insertions = lines.iter().filter(|l| l.op == DiffOp::Insert).count() as u32;
+    ItemDiff {
+        identical: old == new,
+        lines,
+        insertions,
+        deletions,
+    }
+}
+
+fn count_changes(ops: &[DiffOp]) -> usize {
+    ops.iter().take_while(|op| **op != DiffOp::Equal).count()
+}
+
+fn span(op: DiffOp, text: &str) -> DiffSpan {
+    DiffSpan {
+        op,
+        text: text.to_string(),
+    }
+}
+
+/// Word-level spans for an edited line pair: the old line's spans hold Equal
+/// and Delete runs, the new line's spans hold Equal and Insert runs.
+fn word_spans(old: &str, new: &str) -> (Vec<DiffSpan>, Vec<DiffSpan>) {
+    let old_words = tokenize_words(old);
+    let new_words = tokenize_words(new);
+    let ops = myers(&old_words, &new_words);
+
+    let mut old_spans: Vec<DiffSpan> = Vec::new();
+    let mut new_spans: Vec<DiffSpan> = Vec::new();
+    let (mut old_index, mut new_index) = (0usize, 0usize);
+    for op in ops {
+        match op {
+            DiffOp::Equal => {
+                push_span(&mut old_spans, DiffOp::Equal, old_words[old_index]);
+                push_span(&mut new_spans, DiffOp::Equal, new_words[new_index]);
+                old_index += 1;
+                new_index += 1;
+            }
+            DiffOp::Delete => {
+                push_span(&mut old_spans, DiffOp::Delete, old_words[old_index]);
+                old_index += 1;
+            }
+            DiffOp::Insert => {
+                push_span(&mut new_spans, DiffOp::Insert, new_words[new_index]);
+                new_index += 1;
+            }
+        }
+    }
+    (old_spans, new_spans)
+}
+
+/// Append text, merging with the previous span when the op matches.
+fn push_span(spans: &mut Vec<DiffSpan>, op: DiffOp, text: &str) {
+    match spans.last_mut() {
+        Some(last) if last.op == op => last.text.push_str(text),
+        _ => spans.push(span(op, text)),
+    }
+}
+
+/// Split a line into word runs, whitespace runs, and single punctuation
+/// characters. Concatenating the tokens reproduces the line.
+fn tokenize_words(line: &str) -> Vec<&str> {
+    #[derive(PartialEq)]
+    enum Class {
+        Word,
+        Space,
+        Punct,
+    }
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            Class::Word
+        } else if c.is_whitespace() {
+            Class::Space
+        } else {
+            Class::Punct
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut previous: Option<Class> = None;
+    for (index, c) in line.char_indices() {
+        let current = class(c);
+        let continues = match &previous {
+            Some(prev) => *prev == current && current != Class::Punct,
+            None => true,
+        };
+        if !continues {
+            tokens.push(&line[start..index]);
+            start = index;
+        }
+        previous = Some(current);
+    }
+    if start < line.len() {
+        tokens.push(&line[start..]);
+    }
+    tokens
+}
+
+/// Shortest edit script between two token sequences.
+fn myers<T: PartialEq>(old: &[T], new: &[T]) -> Vec<DiffOp> {
+    let n = old.len() as isize;
+    let m = new.len() as isize;
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max as isize {
+        trace.push(v.clone());
+        let mut k = -d;
+        while k <= d {
+            let index = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+                v[index + 1]
+            } else {
+                v[index - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index] = x;
+            if x >= n && y >= m {
+                trace.push(v.clone());
+                break 'search;
+            }
+            k += 2;
+        }
+    }
+
+    // Walk the trace backwards from (n, m) to recover the edit script.
+    let mut ops = Vec::with_capacity(max);
+    let (mut x, mut y) = (n, m);
+    for d in (0..trace.len() as isize - 1).rev() {
+        let v = &trace[d as usize];
+        let k = x - y;
+        let index = (k + offset) as usize;
+        let previous_k = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_x = v[(previous_k + offset) as usize];
+        let previous_y = previous_x - previous_k;
+        while x > previous_x && y > previous_y {
+            ops.push(DiffOp::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            if x == previous_x {
+                ops.push(DiffOp::Insert);
+            } else {
+                ops.push(DiffOp::Delete);
+            }
+        }
+        x = previous_x;
+        y = previous_y;
+    }
+    ops.reverse();
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(spans: &[DiffSpan]) -> String {
+        spans
+            .iter()
+            .map(|span| match span.op {
+                DiffOp::Equal => span.text.clone(),
+                DiffOp::Delete => format!("[-{}-]", span.text),
+                DiffOp::Insert => format!("{{+{}+}}", span.text),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn identical_texts_are_all_equal() {
+        let diff = diff_texts("a\nb", "a\nb");
+        assert!(diff.identical);
+        assert_eq!((diff.insertions, diff.deletions), (0, 0));
+        assert!(diff.lines.iter().all(|line| line.op == DiffOp::Equal));
+    }
+
+    #[test]
+    fn edited_line_gets_word_spans() {
+        let old = "host = example.com\nport = 8080\ndebug = false";
+        let new = "host = example.com\nport = 9090\ndebug = false\nverbose = true";
+        let diff = diff_texts(old, new);
+
+        let ops: Vec<DiffOp> = diff.lines.iter().map(|line| line.op).collect();
+        assert_eq!(
+            ops,
+            vec![
+                DiffOp::Equal,
+                DiffOp::Delete,
+                DiffOp::Insert,
+                DiffOp::Equal,
+                DiffOp::Insert
+            ]
+        );
+        assert_eq!(render(&diff.lines[1].spans), "port = [-8080-]");
+        assert_eq!(render(&diff.lines[2].spans), "port = {+9090+}");
+        assert_eq!(diff.lines[2].new_line_number, Some(2));
+        assert_eq!(diff.lines[4].new_line_number, Some(4));
+        assert_eq!((diff.insertions, diff.deletions), (2, 1));
+    }
+
+    #[test]
+    fn unpaired_lines_are_whole_line_changes() {
+        let diff = diff_texts("keep\ndrop one\ndrop two", "keep\ndrop it");
+        assert_eq!(render(&diff.lines[1].spans), "drop [-one-]");
+        assert_eq!(render(&diff.lines[2].spans), "[-drop two-]");
+        assert_eq!(render(&diff.lines[3].spans), "drop {+it+}");
+    }
+
+    #[test]
+    fn myers_finds_shortest_script() {
+        let old: Vec<char> = "ABCABBA".chars().collect();
+        let new: Vec<char> = "CBABAC".chars().collect();
+        let ops = myers(&old, &new);
+        let edits = ops.iter().filter(|op| **op != DiffOp::Equal).count();
+        assert_eq!(edits, 5);
+        assert!(myers::<char>(&[], &[]).is_empty());
+        assert_eq!(myers(&['a'], &[]), vec![DiffOp::Delete]);
+    }
+
+    #[test]
+    fn tokenizer_round_trips() {
+        let line = "let x=foo(1, 2);  // done";
+        assert_eq!(tokenize_words(line).concat(), line);
+        assert_eq!(tokenize_words("a  b"), vec!["a", "  ", "b"]);
+    }
+}
@@ -0,0 +1,177 @@
+//! End-of-day recap of the clipboard history.
+//!
+//! A digest covers the items last copied on one local day. Unlike the
+//! activity aggregates it names content, such as link URLs and color values,
+//! so it is meant for a local notification rather than export.
+
+use crate::database::{Database, DigestItemRow};
+use crate::interface::{ClipKittyError, DailyDigest, DigestApp, DigestLink};
+use chrono::{Days, NaiveDate, NaiveTime, TimeZone, Utc};
+use std::collections::HashMap;
+
+const MAX_NOTABLE_LINKS: usize = 5;
+const MAX_NEW_COLORS: usize = 12;
+
+/// Summarize the items copied on `date` (`YYYY-MM-DD`, local time).
+pub(crate) fn daily_digest(
+    db: &Database,
+    date: &str,
+    utc_offset_seconds: i32,
+) -> Result<DailyDigest, ClipKittyError> {
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| ClipKittyError::InvalidInput(format!("`{date}` is not a YYYY-MM-DD date")))?;
+    let offset = chrono::Duration::seconds(utc_offset_seconds.into());
+    let local_midnight =
+        |day: NaiveDate| Utc.from_utc_datetime(&day.and_time(NaiveTime::MIN)) - offset;
+    let next_day = day
+        .checked_add_days(Days::new(1))
+        .ok_or_else(|| ClipKittyError::InvalidInput(format!("`{date}` is out of range")))?;
+    let rows = db.fetch_digest_items(local_midnight(day), local_midnight(next_day))?;
+    Ok(summarize(day, &rows))
+}
+
+fn summarize(day: NaiveDate, rows: &[DigestItemRow]) -> DailyDigest {
+    let mut links: Vec<DigestLink> = rows
+        .iter()
+        .filter_map(|row| {
+            Some(DigestLink {
+                item_id: row.item_id.clone(),
+                url: row.link_url.clone()?,
+                title: row.link_title.clone(),
+                copy_count: row.copies.max(1),
+            })
+        })
+        .collect();
+    // Stable, so equally copied links stay newest first.
+    links.sort_by_key(|link| std::cmp::Reverse(link.copy_count));
+    links.truncate(MAX_NOTABLE_LINKS);
+
+    let new_colors = rows
+        .iter()
+        .filter(|row| row.content_type == "color" && !row.copied_before)
+        .map(|row| row.content_prefix.clone())
+        .take(MAX_NEW_COLORS)
+        .collect();
+
+    DailyDigest {
+        date: day.to_string(),
+        item_count: rows.len() as u64,
+        image_count: rows
+            .iter()
+            .filter(|row| row.content_type == "image")
+            .count() as u64,
+        notable_links: links,
+        top_source_app: top_source_app(rows),
+        new_colors,
+    }
+}
+
+/// The app most items came from; ties go to the app seen most recently.
+fn top_source_app(rows: &[DigestItemRow]) -> Option<DigestApp> {
+    let mut apps: Vec<DigestApp> = Vec::new();
+    let mut index_by_key: HashMap<(Option<&str>, Option<&str>), usize> = HashMap::new();
+    for row in rows {
+        let bundle_id = row
+            .source_app_bundle_id
+            .as_deref()
+            .filter(|id| !id.is_empty());
+        let name = row.source_app.as_deref().filter(|name| !name.is_empty());
+        if bundle_id.is_none() && name.is_none() {
+            continue;
+        }
+        // Apps with a bundle ID are one app under any name.
+        let key = (bundle_id, if bundle_id.is_some() { None } else { name });
+        let index = *index_by_key.entry(key).or_insert_with(|| {
+            apps.push(DigestApp {
+                bundle_id: bundle_id.map(str::to_string),
+                name: name.map(str::to_string),
+                item_count: 0,
+            });
+            apps.len() - 1
+        });
+        apps[index].item_count += 1;
+    }
+    // `max_by_key` keeps the last maximum, so scan oldest app first.
+    apps.into_iter().rev().max_by_key(|app| app.item_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StoredItem;
+
+    #[test]
+    fn digest_covers_one_local_day() {
+        let db = Database::open_in_memory().unwrap();
+        // 2024-05-01 at UTC+2: from 2024-04-30 22:00 UTC to 2024-05-01 22:00 UTC.
+        let offset = 2 * 3600;
+        let at = |hour: u32| {
+            Utc.with_ymd_and_hms(2024, 4, 30, 22, 0, 0)
+                .unwrap()
+                .timestamp()
+                + i64::from(hour) * 3600
+        };
+        let insert = |mut item: StoredItem, timestamp_unix: i64| {
+            item.timestamp_unix = timestamp_unix;
+            let row_id = db.insert_item(&item).unwrap();
+            let copied_at = Utc.timestamp_opt(timestamp_unix, 0).unwrap();
+            db.insert_copy_event(row_id, copied_at, None, None).unwrap();
+            row_id
+        };
+        let text = |value: &str, app: Option<&str>, bundle_id: Option<&str>| {
+            StoredItem::new_text(
+                value.into(),
+                app.map(str::to_string),
+                bundle_id.map(str::to_string),
+            )
+        };
+
+        insert(text("before the day", Some("Notes"), None), at(0) - 1);
+        let docs = insert(
+            text("https://docs.rs", None, Some("com.apple.Safari")),
+            at(1),
+        );
+        db.insert_copy_event(docs, Utc.timestamp_opt(at(3), 0).unwrap(), None, None)
+            .unwrap();
+        insert(
+            text(
+                "https://example.com",
+                Some("Safari"),
+                Some("com.apple.Safari"),
+            ),
+            at(2),
+        );
+        insert(text("#ff8800", Some("Figma"), None), at(4));
+        let reused = insert(text("#00ff00", None, None), at(5));
+        db.insert_copy_event(
+            reused,
+            Utc.timestamp_opt(at(0) - 3600, 0).unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+        insert(
+            StoredItem::new_image_with_thumbnail(vec![1, 2, 3], None, None, None, false),
+            at(6),
+        );
+        insert(text("after the day", Some("Notes"), None), at(24));
+
+        let digest = daily_digest(&db, "2024-05-01", offset).unwrap();
+        assert_eq!(digest.date, "2024-05-01");
+        assert_eq!(digest.item_count, 5);
+        assert_eq!(digest.image_count, 1);
+        let urls: Vec<_> = digest
+            .notable_links
+            .iter()
+            .map(|link| link.url.as_str())
+            .collect();
+        assert_eq!(urls, ["https://docs.rs", "https://example.com"]);
+        assert_eq!(digest.notable_links[0].copy_count, 2);
+        assert_eq!(digest.new_colors, ["#ff8800"]);
+        let top = digest.top_source_app.unwrap();
+        assert_eq!(top.bundle_id.as_deref(), Some("com.apple.Safari"));
+        assert_eq!(top.item_count, 2);
+
+        assert!(daily_digest(&db, "May 1", offset).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Low disk space detection for the store's volume.
+//!
+//! Free space is sampled at most once per `CHECK_INTERVAL` on the write path.
+//! Crossing below the threshold flips the store into low-disk mode; it only
+//! flips back once space recovers past the threshold plus a margin, so a
+//! volume hovering around the limit doesn't flap between modes.
+
+use parking_lot::Mutex;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Free space below which the store enters low-disk mode.
+pub(crate) const DEFAULT_LOW_DISK_THRESHOLD_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Fraction of the current database size kept when entering low-disk mode.
+pub(crate) const LOW_DISK_RETENTION_RATIO: f64 = 0.75;
+
+/// Extra headroom, as a fraction of the threshold, required to leave low-disk mode.
+const RECOVERY_MARGIN: f64 = 0.25;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DiskTransition {
+    Unchanged,
+    BecameLow { available_bytes: u64 },
+    Recovered { available_bytes: u64 },
+}
+
+#[derive(Default)]
+struct MonitorState {
+    last_checked: Option<Instant>,
+    low: bool,
+}
+
+pub(crate) struct DiskSpaceMonitor {
+    /// Directory whose volume is sampled; None for in-memory stores.
+    volume: Option<PathBuf>,
+    threshold_bytes: AtomicU64,
+    state: Mutex<MonitorState>,
+}
+
+impl DiskSpaceMonitor {
+    pub(crate) fn new(volume: Option<PathBuf>) -> Self {
+        Self {
+            volume,
+            threshold_bytes: AtomicU64::new(DEFAULT_LOW_DISK_THRESHOLD_BYTES),
+            state: Mutex::new(MonitorState::default()),
+        }
+    }
+
+    pub(crate) fn threshold_bytes(&self) -> u64 {
+        self.threshold_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Change the threshold; the next `refresh` re-samples immediately.
+    pub(crate) fn set_threshold_bytes(&self, threshold_bytes: u64) {
+        self.threshold_bytes
+            .store(threshold_bytes, Ordering::Relaxed);
+        self.state.lock().last_checked = None;
+    }
+
+    pub(crate) fn is_low(&self) -> bool {
+        self.state.lock().low
+    }
+
+    /// Sample free space if the last sample is stale. Sampling failures leave
+    /// the mode unchanged: an unreadable volume is no reason to stop capturing.
+    pub(crate) fn refresh(&self) -> DiskTransition {
+        let Some(volume) = &self.volume else {
+            return DiskTransition::Unchanged;
+        };
+        {
+            let mut state = self.state.lock();
+            if state
+                .last_checked
+                .is_some_and(|checked| checked.elapsed() < CHECK_INTERVAL)
+            {
+                return DiskTransition::Unchanged;
+            }
+            state.last_checked = Some(Instant::now());
+        }
+        match fs4::available_space(volume) {
+            Ok(available_bytes) => self.observe(available_bytes),
+            Err(_) => DiskTransition::Unchanged,
+        }
+    }
+
+    /// Apply one free-space sample.
+    pub(crate) fn observe(&self, available_bytes: u64) -> DiskTransition {
+        let threshold = self.threshold_bytes();
+        let recovery = threshold.saturating_add((threshold as f64 * RECOVERY_MARGIN) as u64);
+        let mut state = self.state.lock();
+        if !state.low && available_bytes < threshold {
+            state.low = true;
+            DiskTransition::BecameLow { available_bytes }
+        } else if state.low && available_bytes >= recovery {
+            state.low = false;
+            DiskTransition::Recovered { available_bytes }
+        } else {
+            DiskTransition::Unchanged
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enters_and_leaves_low_mode_with_hysteresis() {
+        let monitor = DiskSpaceMonitor::new(None);
+        monitor.set_threshold_bytes(1_000);
+
+        assert_eq!(monitor.observe(5_000), DiskTransition::Unchanged);
+        assert_eq!(
+            monitor.observe(900),
+            DiskTransition::BecameLow {
+                available_bytes: 900
+            }
+        );
+        assert!(monitor.is_low());
+        assert_eq!(monitor.observe(800), DiskTransition::Unchanged);
+        // Back above the threshold but inside the recovery margin.
+        assert_eq!(monitor.observe(1_100), DiskTransition::Unchanged);
+        assert_eq!(
+            monitor.observe(1_250),
+            DiskTransition::Recovered {
+                available_bytes: 1_250
+            }
+        );
+        assert!(!monitor.is_low());
+    }
+
+    #[test]
+    fn in_memory_monitor_never_samples() {
+        let monitor = DiskSpaceMonitor::new(None);
+        assert_eq!(monitor.refresh(), DiskTransition::Unchanged);
+        assert!(!monitor.is_low());
+    }
+
+    #[test]
+    fn samples_a_real_volume() {
+        let dir = tempfile::tempdir().unwrap();
+        let monitor = DiskSpaceMonitor::new(Some(dir.path().to_path_buf()));
+        monitor.set_threshold_bytes(u64::MAX / 2);
+        assert!(matches!(
+            monitor.refresh(),
+            DiskTransition::BecameLow { .. }
+        ));
+        // Cached until the check interval elapses.
+        assert_eq!(monitor.refresh(), DiskTransition::Unchanged);
+    }
+}
@@ -0,0 +1,583 @@
+//! Subject, sender, and date of copied email files.
+//!
+//! Mail and most clients export messages as `.eml` files, RFC 5322 text
+//! whose headers are read here with their encoded words decoded. Outlook
+//! exports `.msg` files, compound files whose top-level property streams
+//! hold the same fields. Either way the files get random names, so the
+//! subject and sender are what search needs.
+
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use base64::Engine;
+use chrono::DateTime;
+
+use crate::interface::EmailInfo;
+
+/// Leading bytes of an `.eml` file read for its headers.
+const MAX_HEADER_BYTES: u64 = 64 * 1024;
+/// Largest `.msg` file read; the fields live in small streams, but finding
+/// them takes the file's allocation tables.
+const MAX_MSG_BYTES: u64 = 32 * 1024 * 1024;
+
+const UTIS: &[&str] = &[
+    "com.apple.mail.email",
+    "public.email-message",
+    "com.microsoft.outlook.msg",
+];
+
+/// Whether the file with this UTI and filename is an exported email.
+pub(crate) fn is_email(uti: &str, filename: &str) -> bool {
+    UTIS.contains(&uti)
+        || filename.rsplit_once('.').is_some_and(|(_, extension)| {
+            extension.eq_ignore_ascii_case("eml") || extension.eq_ignore_ascii_case("msg")
+        })
+}
+
+/// Subject, sender, and date of the email at `path`.
+pub(crate) fn probe(path: &Path) -> Option<EmailInfo> {
+    let mut file = File::open(path).ok()?;
+    let mut magic = [0u8; 8];
+    let is_compound = file.read_exact(&mut magic).is_ok() && magic == compound_file::SIGNATURE;
+    file.rewind().ok()?;
+    let mut data = Vec::new();
+    if is_compound {
+        if file.metadata().ok()?.len() > MAX_MSG_BYTES {
+            return None;
+        }
+        file.read_to_end(&mut data).ok()?;
+        parse_msg(&data)
+    } else {
+        file.take(MAX_HEADER_BYTES).read_to_end(&mut data).ok()?;
+        parse_eml(&data)
+    }
+}
+
+/// `info` as one metadata line, as in "Lease renewal · Jane Doe".
+pub(crate) fn summary(info: &EmailInfo) -> String {
+    [info.subject.clone(), info.sender.clone()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+/// An email's subject and sender as search text, one per line.
+pub(crate) fn index_text(subject: Option<&str>, sender: Option<&str>) -> Option<String> {
+    let lines: Vec<&str> = [subject, sender].into_iter().flatten().collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+fn found(info: EmailInfo) -> Option<EmailInfo> {
+    (info.subject.is_some() || info.sender.is_some() || info.sent_at_unix.is_some()).then_some(info)
+}
+
+fn non_empty(text: String) -> Option<String> {
+    let trimmed = text.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// .eml
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn parse_eml(data: &[u8]) -> Option<EmailInfo> {
+    let text = String::from_utf8_lossy(data);
+    let mut info = EmailInfo {
+        subject: None,
+        sender: None,
+        sent_at_unix: None,
+    };
+    for (name, value) in unfolded_headers(&text) {
+        match name.to_ascii_lowercase().as_str() {
+            "subject" if info.subject.is_none() => info.subject = non_empty(decode_words(&value)),
+            "from" if info.sender.is_none() => info.sender = non_empty(sender(&value)),
+            "date" if info.sent_at_unix.is_none() => {
+                info.sent_at_unix = DateTime::parse_from_rfc2822(value.trim())
+                    .ok()
+                    .map(|date| date.timestamp());
+            }
+            _ => {}
+        }
+    }
+    found(info)
+}
+
+/// Header fields before the blank line that ends them, with continuation
+/// lines joined. Stops at the first line that is not a header.
+fn unfolded_headers(text: &str) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in text.lines() {
+        if line.is_empty() {
+            break;
+        }
+        if line.starts_with([' ', '\t']) {
+            match headers.last_mut() {
+                Some((_, value)) => value.push_str(line),
+                None => break,
+            }
+            continue;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            break;
+        };
+        if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c.is_control()) {
+            break;
+        }
+        headers.push((name.to_string(), value.to_string()));
+    }
+    headers
+}
+
+/// A `From` value with encoded words decoded and the display name unquoted,
+/// as in `Jane Doe <jane@example.com>`.
+fn sender(value: &str) -> String {
+    decode_words(value).replace('"', "")
+}
+
+/// Decode RFC 2047 encoded words like `=?UTF-8?Q?Caf=C3=A9?=`. Whitespace
+/// between adjacent encoded words is dropped, as the RFC requires.
+fn decode_words(value: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let (before, candidate) = rest.split_at(start);
+        let word = encoded_word(candidate);
+        let Some((text, len)) = word else {
+            decoded.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            after_word = false;
+            continue;
+        };
+        if !(after_word && before.trim().is_empty()) {
+            decoded.push_str(before);
+        }
+        decoded.push_str(&text);
+        rest = &candidate[len..];
+        after_word = true;
+    }
+    decoded.push_str(rest);
+    decoded.trim().to_string()
+}
+
+/// The text of the encoded word at the start of `text` and its length.
+fn encoded_word(text: &str) -> Option<(String, usize)> {
+    let body = text.strip_prefix("=?")?;
+    let (charset, body) = body.split_once('?')?;
+    let (encoding, body) = body.split_once('?')?;
+    let end = body.find("?=")?;
+    let payload = &body[..end];
+    let len = 2 + charset.len() + 1 + encoding.len() + 1 + end + 2;
+    let bytes = match encoding {
+        "B" | "b" => base64::engine::general_purpose::STANDARD
+            .decode(payload)
+            .ok()?,
+        "Q" | "q" => quoted_printable(payload),
+        _ => return None,
+    };
+    // Drop an RFC 2231 language suffix like `UTF-8*en`.
+    let charset = charset.split('*').next()?.to_ascii_lowercase();
+    let text = match charset.as_str() {
+        "iso-8859-1" | "latin1" | "windows-1252" => bytes.iter().map(|&b| b as char).collect(),
+        _ => String::from_utf8_lossy(&bytes).into_owned(),
+    };
+    Some((text, len))
+}
+
+fn quoted_printable(payload: &str) -> Vec<u8> {
+    let bytes = payload.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut at = 0;
+    while at < bytes.len() {
+        match bytes[at] {
+            b'_' => decoded.push(b' '),
+            b'=' => {
+                let hex = payload
+                    .get(at + 1..at + 3)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match hex {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        at += 2;
+                    }
+                    None => decoded.push(b'='),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        at += 1;
+    }
+    decoded
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// .msg
+// ─────────────────────────────────────────────────────────────────────────────
+
+const PROPERTY_SUBJECT: u16 = 0x0037;
+const PROPERTY_SENDER_NAME: u16 = 0x0C1A;
+const PROPERTY_SENDER_EMAIL: u16 = 0x0C1F;
+const PROPERTY_SENDER_SMTP: u16 = 0x5D01;
+const PROPERTY_SUBMIT_TIME: u16 = 0x0039;
+const PROPERTY_DELIVERY_TIME: u16 = 0x0E06;
+/// Property types of the string streams: UTF-16LE and 8-bit.
+const TYPE_UNICODE: u16 = 0x001F;
+const TYPE_STRING8: u16 = 0x001E;
+const TYPE_TIME: u16 = 0x0040;
+/// Seconds from 1601-01-01, where FILETIME counts from, to the Unix epoch.
+const FILETIME_UNIX_OFFSET: i64 = 11_644_473_600;
+
+fn parse_msg(data: &[u8]) -> Option<EmailInfo> {
+    let file = compound_file::CompoundFile::parse(data)?;
+    let string = |property: u16| {
+        if let Some(stream) =
+            file.root_stream(&format!("__substg1.0_{property:04X}{TYPE_UNICODE:04X}"))
+        {
+            let units: Vec<u16> = stream
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            return non_empty(String::from_utf16_lossy(&units).replace('\0', ""));
+        }
+        let stream = file.root_stream(&format!("__substg1.0_{property:04X}{TYPE_STRING8:04X}"))?;
+        non_empty(String::from_utf8_lossy(&stream).replace('\0', ""))
+    };
+    let name = string(PROPERTY_SENDER_NAME);
+    let address = string(PROPERTY_SENDER_SMTP).or_else(|| string(PROPERTY_SENDER_EMAIL));
+    let sender = match (name, address) {
+        (Some(name), Some(address)) if name != address => Some(format!("{name} <{address}>")),
+        (name, address) => name.or(address),
+    };
+    let sent_at_unix = file
+        .root_stream("__properties_version1.0")
+        .and_then(|properties| {
+            message_time(&properties, PROPERTY_SUBMIT_TIME)
+                .or_else(|| message_time(&properties, PROPERTY_DELIVERY_TIME))
+        });
+    found(EmailInfo {
+        subject: string(PROPERTY_SUBJECT),
+        sender,
+        sent_at_unix,
+    })
+}
+
+/// A time property of the top-level message's property stream: a 32-byte
+/// header, then 16-byte entries of tag, flags, and an inline value.
+fn message_time(properties: &[u8], property: u16) -> Option<i64> {
+    let tag = u32::from(property) << 16 | u32::from(TYPE_TIME);
+    properties.get(32..)?.chunks_exact(16).find_map(|entry| {
+        (u32::from_le_bytes(entry[..4].try_into().ok()?) == tag).then_some(())?;
+        let filetime = u64::from_le_bytes(entry[8..16].try_into().ok()?);
+        Some((filetime / 10_000_000) as i64 - FILETIME_UNIX_OFFSET)
+    })
+}
+
+/// Just enough of the compound file format to read a message's top-level
+/// streams.
+mod compound_file {
+    pub(super) const SIGNATURE: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+    const END_OF_CHAIN: u32 = 0xFFFF_FFFE;
+    const NO_ENTRY: u32 = 0xFFFF_FFFF;
+    const DIRECTORY_ENTRY_BYTES: usize = 128;
+    const HEADER_DIFAT_ENTRIES: usize = 109;
+    const STREAM: u8 = 2;
+
+    struct Entry {
+        name: String,
+        kind: u8,
+        left: u32,
+        right: u32,
+        child: u32,
+        start: u32,
+        size: u64,
+    }
+
+    pub(super) struct CompoundFile<'a> {
+        data: &'a [u8],
+        sector_shift: u32,
+        mini_sector_shift: u32,
+        mini_cutoff: u64,
+        fat: Vec<u32>,
+        mini_fat: Vec<u32>,
+        mini_stream: Vec<u8>,
+        entries: Vec<Entry>,
+    }
+
+    impl<'a> CompoundFile<'a> {
+        pub(super) fn parse(data: &'a [u8]) -> Option<Self> {
+            if !data.starts_with(&SIGNATURE) {
+                return None;
+            }
+            let sector_shift = u32::from(le_u16(data, 0x1E)?);
+            let mini_sector_shift = u32::from(le_u16(data, 0x20)?);
+            if !(7..=16).contains(&sector_shift) || mini_sector_shift >= sector_shift {
+                return None;
+            }
+            let mut file = CompoundFile {
+                data,
+                sector_shift,
+                mini_sector_shift,
+                mini_cutoff: u64::from(le_u32(data, 0x38)?),
+                fat: Vec::new(),
+                mini_fat: Vec::new(),
+                mini_stream: Vec::new(),
+                entries: Vec::new(),
+            };
+
+            let mut fat_sectors: Vec<u32> = (0..HEADER_DIFAT_ENTRIES)
+                .filter_map(|index| le_u32(data, 0x4C + index * 4))
+                .collect();
+            let mut difat_sector = le_u32(data, 0x44)?;
+            let per_sector = file.sector_len() / 4;
+            let mut guard = 0;
+            while difat_sector != END_OF_CHAIN && difat_sector != NO_ENTRY && guard < 1 << 16 {
+                let sector = file.sector(difat_sector)?;
+                fat_sectors
+                    .extend((0..per_sector - 1).filter_map(|index| le_u32(sector, index * 4)));
+                difat_sector = le_u32(sector, (per_sector - 1) * 4)?;
+                guard += 1;
+            }
+            let mut fat = Vec::new();
+            for sector in fat_sectors
+                .into_iter()
+                .filter(|&sector| sector < END_OF_CHAIN - 2)
+            {
+                let sector = file.sector(sector)?;
+                fat.extend((0..per_sector).filter_map(|index| le_u32(sector, index * 4)));
+            }
+            file.fat = fat;
+
+            let directory = file.chain(le_u32(data, 0x30)?, None)?;
+            file.entries = directory
+                .chunks_exact(DIRECTORY_ENTRY_BYTES)
+                .map(|entry| {
+                    let name_len = (le_u16(entry, 64).unwrap_or(0) as usize).min(64);
+                    let units: Vec<u16> = entry[..name_len.saturating_sub(2)]
+                        .chunks_exact(2)
+                        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                        .collect();
+                    Entry {
+                        name: String::from_utf16_lossy(&units),
+                        kind: entry[66],
+                        left: le_u32(entry, 68).unwrap_or(NO_ENTRY),
+                        right: le_u32(entry, 72).unwrap_or(NO_ENTRY),
+                        child: le_u32(entry, 76).unwrap_or(NO_ENTRY),
+                        start: le_u32(entry, 116).unwrap_or(END_OF_CHAIN),
+                        size: u64::from(le_u32(entry, 120).unwrap_or(0)),
+                    }
+                })
+                .collect();
+
+            let root = file.entries.first()?;
+            file.mini_stream = file.chain(root.start, Some(root.size))?;
+            let mini_fat = file.chain(le_u32(data, 0x3C)?, None).unwrap_or_default();
+            file.mini_fat = mini_fat
+                .chunks_exact(4)
+                .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .collect();
+            Some(file)
+        }
+
+        /// The stream named `name` directly inside the root storage.
+        pub(super) fn root_stream(&self, name: &str) -> Option<Vec<u8>> {
+            let mut pending = vec![self.entries.first()?.child];
+            let mut visited = 0;
+            while let Some(index) = pending.pop() {
+                let entry = self.entries.get(index as usize)?;
+                visited += 1;
+                if visited > self.entries.len() {
+                    return None;
+                }
+                if entry.kind == STREAM && entry.name.eq_ignore_ascii_case(name) {
+                    return if entry.size < self.mini_cutoff {
+                        self.mini_chain(entry.start, entry.size)
+                    } else {
+                        self.chain(entry.start, Some(entry.size))
+                    };
+                }
+                pending.extend(
+                    [entry.left, entry.right]
+                        .into_iter()
+                        .filter(|&sibling| sibling != NO_ENTRY),
+                );
+            }
+            None
+        }
+
+        fn sector_len(&self) -> usize {
+            1 << self.sector_shift
+        }
+
+        fn sector(&self, sector: u32) -> Option<&'a [u8]> {
+            let start = (sector as usize + 1).checked_mul(self.sector_len())?;
+            self.data.get(start..start + self.sector_len())
+        }
+
+        /// The bytes of the sector chain starting at `start`, cut to `size`.
+        fn chain(&self, start: u32, size: Option<u64>) -> Option<Vec<u8>> {
+            let mut bytes = Vec::new();
+            let mut sector = start;
+            while sector != END_OF_CHAIN {
+                if bytes.len() > self.data.len() {
+                    return None;
+                }
+                bytes.extend_from_slice(self.sector(sector)?);
+                sector = *self.fat.get(sector as usize)?;
+            }
+            if let Some(size) = size {
+                bytes.truncate(size as usize);
+            }
+            Some(bytes)
+        }
+
+        fn mini_chain(&self, start: u32, size: u64) -> Option<Vec<u8>> {
+            let mini_len = 1usize << self.mini_sector_shift;
+            let mut bytes = Vec::new();
+            let mut sector = start;
+            while sector != END_OF_CHAIN && (bytes.len() as u64) < size {
+                let at = sector as usize * mini_len;
+                bytes.extend_from_slice(self.mini_stream.get(at..at + mini_len)?);
+                sector = *self.mini_fat.get(sector as usize)?;
+            }
+            bytes.truncate(size as usize);
+            Some(bytes)
+        }
+    }
+
+    fn le_u16(data: &[u8], at: usize) -> Option<u16> {
+        Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+    }
+
+    fn le_u32(data: &[u8], at: usize) -> Option<u32> {
+        Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_decoded_headers_from_an_eml_file() {
+        let eml = b"Received: from mail.example.com\r\n\
+            \tby mx.example.net; Mon, 4 Mar 2024 09:00:00 +0000\r\n\
+            From: \"Jane Doe\" <jane@example.com>\r\n\
+            Subject: =?UTF-8?Q?Lease_renewal_=E2=80=93?=\r\n =?UTF-8?B?IGFwYXJ0bWVudCA0Qg==?=\r\n\
+            Date: Mon, 4 Mar 2024 09:00:00 +0100\r\n\
+            \r\n\
+            Subject: not a header\r\n";
+        assert_eq!(
+            parse_eml(eml),
+            Some(EmailInfo {
+                subject: Some("Lease renewal \u{2013} apartment 4B".into()),
+                sender: Some("Jane Doe <jane@example.com>".into()),
+                sent_at_unix: Some(1_709_539_200),
+            })
+        );
+    }
+
+    #[test]
+    fn text_that_is_not_a_message_has_no_info() {
+        assert_eq!(parse_eml(b"just some notes\nabout nothing"), None);
+        assert_eq!(parse_eml(b"X-Mailer: test\r\n\r\n"), None);
+    }
+
+    #[test]
+    fn recognizes_email_files_by_type_or_extension() {
+        assert!(is_email("com.apple.mail.email", "message"));
+        assert!(is_email("public.data", "Re_ invoice.EML"));
+        assert!(is_email("public.data", "export.msg"));
+        assert!(!is_email("public.plain-text", "notes.txt"));
+    }
+
+    /// A compound file with a FAT sector, two directory sectors, a mini FAT
+    /// sector, and a mini stream sector, holding up to seven `streams` of
+    /// under 64 bytes each at the root.
+    fn msg_file(streams: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        const FREE: u32 = 0xFFFF_FFFF;
+        const END: u32 = 0xFFFF_FFFE;
+        let mut header = vec![0u8; 512];
+        header[..8].copy_from_slice(&compound_file::SIGNATURE);
+        header[0x1E..0x20].copy_from_slice(&9u16.to_le_bytes());
+        header[0x20..0x22].copy_from_slice(&6u16.to_le_bytes());
+        header[0x2C..0x30].copy_from_slice(&1u32.to_le_bytes());
+        header[0x30..0x34].copy_from_slice(&1u32.to_le_bytes());
+        header[0x38..0x3C].copy_from_slice(&4096u32.to_le_bytes());
+        header[0x3C..0x40].copy_from_slice(&3u32.to_le_bytes());
+        header[0x44..0x48].copy_from_slice(&END.to_le_bytes());
+        header[0x4C..0x50].copy_from_slice(&0u32.to_le_bytes());
+        for index in 1..109 {
+            header[0x4C + index * 4..0x50 + index * 4].copy_from_slice(&FREE.to_le_bytes());
+        }
+
+        // Sectors: 0 FAT, 1 and 2 directory, 3 mini FAT, 4 mini stream.
+        let mut fat = vec![0u8; 512];
+        for (sector, next) in [(0, 0xFFFF_FFFD), (1, 2), (2, END), (3, END), (4, END)] {
+            fat[sector * 4..sector * 4 + 4].copy_from_slice(&next.to_le_bytes());
+        }
+        for sector in 5..128 {
+            fat[sector * 4..sector * 4 + 4].copy_from_slice(&FREE.to_le_bytes());
+        }
+
+        let entry = |name: &str, kind: u8, right: u32, child: u32, start: u32, size: u32| {
+            let mut entry = vec![0u8; 128];
+            let units: Vec<u16> = name.encode_utf16().chain([0]).collect();
+            for (index, unit) in units.iter().enumerate() {
+                entry[index * 2..index * 2 + 2].copy_from_slice(&unit.to_le_bytes());
+            }
+            entry[64..66].copy_from_slice(&(units.len() as u16 * 2).to_le_bytes());
+            entry[66] = kind;
+            entry[68..72].copy_from_slice(&FREE.to_le_bytes());
+            entry[72..76].copy_from_slice(&right.to_le_bytes());
+            entry[76..80].copy_from_slice(&child.to_le_bytes());
+            entry[116..120].copy_from_slice(&start.to_le_bytes());
+            entry[120..124].copy_from_slice(&size.to_le_bytes());
+            entry
+        };
+        let mut directory = entry("Root Entry", 5, FREE, 1, 4, streams.len() as u32 * 64);
+        let mut mini_fat = vec![0xFFu8; 512];
+        let mut mini_stream = vec![0u8; 512];
+        for (index, (name, data)) in streams.iter().enumerate() {
+            let right = if index + 1 < streams.len() {
+                index as u32 + 2
+            } else {
+                FREE
+            };
+            directory.extend(entry(name, 2, right, FREE, index as u32, data.len() as u32));
+            mini_fat[index * 4..index * 4 + 4].copy_from_slice(&END.to_le_bytes());
+            mini_stream[index * 64..index * 64 + data.len()].copy_from_slice(data);
+        }
+        directory.resize(1024, 0);
+        [header, fat, directory, mini_fat, mini_stream].concat()
+    }
+
+    fn utf16(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn reads_subject_sender_and_date_from_a_msg_file() {
+        let mut properties = vec![0u8; 32];
+        properties.extend_from_slice(&0x0039_0040u32.to_le_bytes());
+        properties.extend_from_slice(&[0; 4]);
+        let filetime = (1_709_539_200 + FILETIME_UNIX_OFFSET) as u64 * 10_000_000;
+        properties.extend_from_slice(&filetime.to_le_bytes());
+        let file = msg_file(&[
+            ("__substg1.0_0037001F", utf16("Quarterly numbers")),
+            ("__substg1.0_0C1A001F", utf16("Sam Lee")),
+            ("__substg1.0_5D01001F", utf16("sam@example.com")),
+            ("__properties_version1.0", properties),
+        ]);
+        assert_eq!(
+            parse_msg(&file),
+            Some(EmailInfo {
+                subject: Some("Quarterly numbers".into()),
+                sender: Some("Sam Lee <sam@example.com>".into()),
+                sent_at_unix: Some(1_709_539_200),
+            })
+        );
+    }
+}
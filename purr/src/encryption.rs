@@ -0,0 +1,297 @@
+//! Encryption at rest for clipboard payloads.
+//!
+//! With a 256-bit key from the host app (typically kept in the Keychain),
+//! item text and images are sealed with AES-256-GCM before they reach SQLite
+//! and opened again on fetch: the payload columns `text_items.value`,
+//! `image_items.data`, and the RTF and HTML columns of `rich_text_items`, the
+//! denormalized `items.content` and image descriptions, thumbnails, and
+//! recognized image text, link URLs, titles, and descriptions, snippet
+//! bodies, and the payloads of sync events and snapshots. Text columns hold
+//! sealed text (see [`ContentCipher::seal_text`]) so they keep their type;
+//! SQL that reads them goes through the `unseal` function registered on
+//! every connection, and the sync tables write theirs through `seal`.
+//!
+//! The Tantivy search index holds item text in the clear, so an encrypted
+//! store keeps it in memory only. Not covered: detected entities, snippet
+//! titles, and file names, paths, and previews.
+//!
+//! A sealed verifier in `key_values` marks a store as encrypted and rejects
+//! wrong keys up front. Unlocking a store that has no verifier yet seals its
+//! existing payloads in place, so an unencrypted store migrates on first open.
+
+use crate::database::{Database, DatabaseError, DatabaseResult};
+use crate::interface::ClipKittyError;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rusqlite::types::{Value, ValueRef};
+
+const SEALED_MAGIC: &[u8] = b"ckenc1";
+const SEALED_TEXT_PREFIX: &str = "ckenc1:";
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+pub(crate) const ENCRYPTION_NAMESPACE: &str = "encryption";
+pub(crate) const VERIFIER_KEY: &str = "verifier";
+const VERIFIER_PLAINTEXT: &[u8] = b"clipkitty encrypted store";
+
+/// Seals and opens payloads as `magic || nonce || ciphertext`.
+pub(crate) struct ContentCipher {
+    cipher: Aes256Gcm,
+}
+
+impl ContentCipher {
+    pub(crate) fn new(key: &[u8]) -> Result<Self, ClipKittyError> {
+        if key.len() != KEY_LEN {
+            return Err(ClipKittyError::InvalidInput(format!(
+                "encryption key must be {KEY_LEN} bytes, got {}",
+                key.len()
+            )));
+        }
+        Ok(Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+        })
+    }
+
+    pub(crate) fn seal(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .expect("AES-GCM encryption only fails for oversized inputs");
+        let mut sealed = Vec::with_capacity(SEALED_MAGIC.len() + NONCE_LEN + ciphertext.len());
+        sealed.extend_from_slice(SEALED_MAGIC);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        sealed
+    }
+
+    pub(crate) fn open(&self, sealed: &[u8]) -> DatabaseResult<Vec<u8>> {
+        let unreadable =
+            || DatabaseError::InconsistentData("encrypted payload is unreadable".into());
+        let body = sealed.strip_prefix(SEALED_MAGIC).ok_or_else(unreadable)?;
+        if body.len() < NONCE_LEN {
+            return Err(unreadable());
+        }
+        let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| unreadable())
+    }
+
+    /// Seal text as `ckenc1:` and the base64 of the sealed bytes, for columns
+    /// that must keep holding text.
+    pub(crate) fn seal_text(&self, plaintext: &str) -> String {
+        format!(
+            "{SEALED_TEXT_PREFIX}{}",
+            STANDARD.encode(self.seal(plaintext.as_bytes()))
+        )
+    }
+
+    pub(crate) fn open_text(&self, sealed: &str) -> DatabaseResult<String> {
+        let unreadable = || DatabaseError::InconsistentData("encrypted text is unreadable".into());
+        let encoded = sealed
+            .strip_prefix(SEALED_TEXT_PREFIX)
+            .ok_or_else(unreadable)?;
+        let sealed = STANDARD.decode(encoded).map_err(|_| unreadable())?;
+        String::from_utf8(self.open(&sealed)?).map_err(|_| unreadable())
+    }
+}
+
+pub(crate) fn is_sealed(stored: &[u8]) -> bool {
+    stored.starts_with(SEALED_MAGIC)
+}
+
+pub(crate) fn is_sealed_text(stored: &str) -> bool {
+    stored.starts_with(SEALED_TEXT_PREFIX)
+}
+
+/// The SQL `seal` function: seals plain text with `cipher`, and passes
+/// sealed text, other values, and everything before unlocking through.
+pub(crate) fn seal_value(cipher: Option<&ContentCipher>, value: ValueRef<'_>) -> Value {
+    match (cipher, value) {
+        (Some(cipher), ValueRef::Text(text))
+            if !text.starts_with(SEALED_TEXT_PREFIX.as_bytes()) =>
+        {
+            Value::Text(cipher.seal_text(&String::from_utf8_lossy(text)))
+        }
+        (_, ValueRef::Null) => Value::Null,
+        (_, ValueRef::Integer(integer)) => Value::Integer(integer),
+        (_, ValueRef::Real(real)) => Value::Real(real),
+        (_, ValueRef::Text(text)) => Value::Text(String::from_utf8_lossy(text).into_owned()),
+        (_, ValueRef::Blob(blob)) => Value::Blob(blob.to_vec()),
+    }
+}
+
+/// The SQL `unseal` function: opens sealed text and blobs, and passes every
+/// other value through unchanged.
+pub(crate) fn unseal_value(
+    cipher: Option<&ContentCipher>,
+    value: ValueRef<'_>,
+) -> DatabaseResult<Value> {
+    let missing_key = || {
+        DatabaseError::InconsistentData(
+            "payload is encrypted but the store was opened without a key".into(),
+        )
+    };
+    Ok(match value {
+        ValueRef::Text(text) if text.starts_with(SEALED_TEXT_PREFIX.as_bytes()) => {
+            let text = std::str::from_utf8(text).map_err(|_| {
+                DatabaseError::InconsistentData("encrypted text is unreadable".into())
+            })?;
+            Value::Text(cipher.ok_or_else(missing_key)?.open_text(text)?)
+        }
+        ValueRef::Blob(blob) if is_sealed(blob) => {
+            Value::Blob(cipher.ok_or_else(missing_key)?.open(blob)?)
+        }
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(integer) => Value::Integer(integer),
+        ValueRef::Real(real) => Value::Real(real),
+        ValueRef::Text(text) => Value::Text(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(blob) => Value::Blob(blob.to_vec()),
+    })
+}
+
+pub(crate) fn is_encrypted(db: &Database) -> Result<bool, ClipKittyError> {
+    Ok(db
+        .get_key_value(ENCRYPTION_NAMESPACE, VERIFIER_KEY)?
+        .is_some())
+}
+
+/// Whether `db` can read the payloads of `other`: both are unencrypted, or
+/// `other` is sealed with the key `db` was unlocked with.
+pub(crate) fn shares_key(db: &Database, other: &Database) -> Result<bool, ClipKittyError> {
+    let verifier = other.get_key_value(ENCRYPTION_NAMESPACE, VERIFIER_KEY)?;
+    Ok(match (db.cipher(), verifier) {
+        (None, None) => true,
+        (Some(cipher), Some(verifier)) => {
+            cipher.open(&verifier).ok().as_deref() == Some(VERIFIER_PLAINTEXT)
+        }
+        _ => false,
+    })
+}
+
+/// Check `key` against the store, migrating an unencrypted store on first
+/// use, and make `db` seal and open payloads with it from now on.
+pub(crate) fn unlock(db: &mut Database, key: &[u8]) -> Result<(), ClipKittyError> {
+    let cipher = ContentCipher::new(key)?;
+    match db.get_key_value(ENCRYPTION_NAMESPACE, VERIFIER_KEY)? {
+        Some(verifier) => {
+            if cipher.open(&verifier).ok().as_deref() != Some(VERIFIER_PLAINTEXT) {
+                return Err(ClipKittyError::InvalidInput(
+                    "encryption key does not match this store".into(),
+                ));
+            }
+        }
+        None => db.seal_payloads(&cipher, &cipher.seal(VERIFIER_PLAINTEXT))?,
+    }
+    db.set_cipher(cipher);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::ClipboardContent;
+    use crate::models::StoredItem;
+
+    #[test]
+    fn unlock_migrates_plain_payloads_and_rejects_wrong_keys() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("clipboard.db");
+        let image = StoredItem::new_image_with_thumbnail(vec![1, 2, 3, 4], None, None, None, false);
+        let row_id = {
+            let db = Database::open(&path).unwrap();
+            db.insert_item(&image).unwrap()
+        };
+
+        let key = [7u8; KEY_LEN];
+        let mut db = Database::open(&path).unwrap();
+        unlock(&mut db, &key).unwrap();
+        let stored: Vec<u8> = db
+            .get_conn()
+            .unwrap()
+            .query_row(
+                "SELECT data FROM image_items WHERE itemId = ?1",
+                [row_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(is_sealed(&stored));
+        let fetched = db.fetch_items_by_ids(&[row_id]).unwrap();
+        assert!(matches!(
+            &fetched[0].content,
+            ClipboardContent::Image { data, .. } if data == &[1, 2, 3, 4]
+        ));
+        drop(db);
+
+        let mut reopened = Database::open(&path).unwrap();
+        assert!(is_encrypted(&reopened).unwrap());
+        assert!(matches!(
+            unlock(&mut reopened, &[8u8; KEY_LEN]),
+            Err(ClipKittyError::InvalidInput(_))
+        ));
+        assert!(unlock(&mut reopened, &[7u8; 16]).is_err());
+        unlock(&mut reopened, &key).unwrap();
+        assert_eq!(
+            reopened.fetch_image_data(row_id).unwrap(),
+            Some(vec![1, 2, 3, 4])
+        );
+    }
+
+    #[test]
+    fn sealed_stores_keep_item_text_off_disk_but_list_it() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("clipboard.db");
+        let secret = "launch code 0000 for the lighthouse";
+        {
+            let db = Database::open(&path).unwrap();
+            db.insert_item(&StoredItem::new_text(secret.into(), None, None))
+                .unwrap();
+        }
+
+        let mut db = Database::open(&path).unwrap();
+        unlock(&mut db, &[7u8; KEY_LEN]).unwrap();
+        db.insert_item(&StoredItem::new_text(
+            "second lighthouse note".into(),
+            None,
+            None,
+        ))
+        .unwrap();
+
+        let stored: Vec<String> = db
+            .get_conn()
+            .unwrap()
+            .prepare("SELECT content FROM items")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert!(stored.iter().all(|content| is_sealed_text(content)));
+
+        let mut previews: Vec<String> = db
+            .fetch_quick_list_rows(10)
+            .unwrap()
+            .into_iter()
+            .map(|row| row.1)
+            .collect();
+        previews.sort();
+        assert_eq!(previews, [secret, "second lighthouse note"]);
+        let latest = db.fetch_latest_text().unwrap().unwrap();
+        assert!(previews.contains(&latest));
+
+        // The migration vacuums, so no page of the file still holds the
+        // plaintext it replaced.
+        drop(db);
+        for file in ["clipboard.db", "clipboard.db-wal"] {
+            let bytes = std::fs::read(temp.path().join(file)).unwrap_or_default();
+            assert!(
+                !bytes
+                    .windows(secret.len())
+                    .any(|window| window == secret.as_bytes()),
+                "{file} holds the plaintext"
+            );
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Apps whose clipboard content is never saved.
+//!
+//! The list lives in the store, so every capture path enforces the same
+//! rules: saves from an excluded bundle ID are dropped before anything is
+//! written. Bundle IDs compare ASCII case-insensitively, as macOS does.
+
+use crate::database::{Database, DatabaseError};
+use crate::interface::ClipKittyError;
+
+const EXCLUSIONS_NAMESPACE: &str = "capture";
+const EXCLUDED_BUNDLE_IDS_KEY: &str = "excluded_bundle_ids";
+
+/// Replace the list. Blank entries and case-insensitive repeats are dropped.
+pub(crate) fn set(db: &Database, bundle_ids: Vec<String>) -> Result<(), ClipKittyError> {
+    let mut kept: Vec<String> = Vec::new();
+    for bundle_id in bundle_ids {
+        let bundle_id = bundle_id.trim();
+        if !bundle_id.is_empty() && !kept.iter().any(|id| id.eq_ignore_ascii_case(bundle_id)) {
+            kept.push(bundle_id.to_string());
+        }
+    }
+    kept.sort_by_key(|id| id.to_ascii_lowercase());
+    let encoded = serde_json::to_vec(&kept).expect("a list of strings always serializes");
+    Ok(db.put_key_value(EXCLUSIONS_NAMESPACE, EXCLUDED_BUNDLE_IDS_KEY, &encoded)?)
+}
+
+pub(crate) fn get(db: &Database) -> Result<Vec<String>, ClipKittyError> {
+    let Some(bytes) = db.get_key_value(EXCLUSIONS_NAMESPACE, EXCLUDED_BUNDLE_IDS_KEY)? else {
+        return Ok(Vec::new());
+    };
+    serde_json::from_slice(&bytes).map_err(|_| {
+        DatabaseError::InconsistentData("excluded bundle IDs are unreadable".into()).into()
+    })
+}
+
+/// Whether content copied from `bundle_id` must be dropped.
+pub(crate) fn is_excluded(db: &Database, bundle_id: Option<&str>) -> Result<bool, ClipKittyError> {
+    let Some(bundle_id) = bundle_id.map(str::trim).filter(|id| !id.is_empty()) else {
+        return Ok(false);
+    };
+    Ok(get(db)?
+        .iter()
+        .any(|excluded| excluded.eq_ignore_ascii_case(bundle_id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_is_normalized_and_matched_case_insensitively() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(!is_excluded(&db, Some("com.agilebits.onepassword7")).unwrap());
+
+        set(
+            &db,
+            vec![
+                " com.agilebits.onepassword7 ".into(),
+                "".into(),
+                "com.apple.keychainaccess".into(),
+                "COM.AGILEBITS.ONEPASSWORD7".into(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            get(&db).unwrap(),
+            ["com.agilebits.onepassword7", "com.apple.keychainaccess"]
+        );
+        assert!(is_excluded(&db, Some("com.AgileBits.OnePassword7")).unwrap());
+        assert!(!is_excluded(&db, Some("com.apple.Safari")).unwrap());
+        assert!(!is_excluded(&db, None).unwrap());
+    }
+}
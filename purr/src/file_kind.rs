@@ -0,0 +1,113 @@
+//! Broad file kinds, and `kind:` words that filter search to them.
+//!
+//! A kind is the document kind icons use folded into fewer groups: PDFs,
+//! spreadsheets, slides, and text are all documents.
+
+use crate::database::TagRestriction;
+use crate::icon_service::document_kind;
+use crate::interface::{DocumentKind, FileKind};
+use crate::search::filter_words;
+
+/// Prefix of the query word naming a file kind, as in `kind:archive`.
+const KIND_PREFIX: &str = "kind:";
+
+/// The kind of the file with this UTI and filename.
+#[uniffi::export]
+pub fn file_kind(uti: String, filename: String) -> FileKind {
+    kind_of(&uti, &filename)
+}
+
+pub(crate) fn kind_of(uti: &str, filename: &str) -> FileKind {
+    match document_kind(uti, filename) {
+        DocumentKind::Pdf
+        | DocumentKind::Spreadsheet
+        | DocumentKind::Presentation
+        | DocumentKind::WordProcessing
+        | DocumentKind::PlainText => FileKind::Document,
+        DocumentKind::Image => FileKind::Image,
+        DocumentKind::Audio => FileKind::Audio,
+        DocumentKind::Video => FileKind::Video,
+        DocumentKind::Archive => FileKind::Archive,
+        DocumentKind::SourceCode => FileKind::Code,
+        DocumentKind::Application => FileKind::App,
+        DocumentKind::Folder => FileKind::Folder,
+        DocumentKind::Generic => FileKind::Other,
+    }
+}
+
+/// The kind a `kind:<kind>` word names, if it names a known one.
+pub(crate) fn kind_of_word(word: &str) -> Option<FileKind> {
+    let name = word.get(..KIND_PREFIX.len())?;
+    name.eq_ignore_ascii_case(KIND_PREFIX)
+        .then(|| FileKind::from_database_str(&word[KIND_PREFIX.len()..].to_lowercase()).ok())
+        .flatten()
+}
+
+/// The word `kind_of_word` reads back as `kind`.
+pub(crate) fn kind_word(kind: FileKind) -> String {
+    format!("{KIND_PREFIX}{}", kind.database_str())
+}
+
+/// Take the last `kind:<kind>` word naming a known kind out of `query` and
+/// restrict `tags` to items holding a file of that kind. Words naming no
+/// kind, and quoted or escaped ones, stay in the query text. Returns the
+/// remaining query text.
+pub(crate) fn extract_kind_filter(query: &str, tags: &mut TagRestriction) -> String {
+    let words = filter_words(query);
+    let is_kind = |&(word, can_filter): &(&str, bool)| can_filter && kind_of_word(word).is_some();
+    if !words.iter().any(is_kind) {
+        return query.to_string();
+    }
+    let mut remaining = Vec::new();
+    for (word, can_filter) in words {
+        match kind_of_word(word).filter(|_| can_filter) {
+            Some(kind) => tags.file_kind = Some(kind),
+            None => remaining.push(word),
+        }
+    }
+    remaining.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_document_kinds_into_file_kinds() {
+        for (uti, filename, kind) in [
+            ("com.adobe.pdf", "a.pdf", FileKind::Document),
+            (
+                "org.openxmlformats.spreadsheetml.sheet",
+                "q3.xlsx",
+                FileKind::Document,
+            ),
+            ("public.zip-archive", "backup.zip", FileKind::Archive),
+            ("public.data", "main.rs", FileKind::Code),
+            ("com.apple.application-bundle", "Mail.app", FileKind::App),
+            ("public.folder", "Downloads", FileKind::Folder),
+            ("public.data", "blob.bin", FileKind::Other),
+        ] {
+            assert_eq!(kind_of(uti, filename), kind, "{filename}");
+        }
+    }
+
+    #[test]
+    fn kind_words_become_a_filter() {
+        let mut tags = TagRestriction::default();
+        let text = extract_kind_filter("invoice Kind:Archive kind:pets 2024", &mut tags);
+        assert_eq!(text, "invoice kind:pets 2024");
+        assert_eq!(tags.file_kind, Some(FileKind::Archive));
+
+        let mut untouched = TagRestriction::default();
+        assert_eq!(
+            extract_kind_filter("kindness  matters", &mut untouched),
+            "kindness  matters"
+        );
+        assert!(untouched.is_empty());
+
+        let mut literal = TagRestriction::default();
+        let text = extract_kind_filter("\"kind:pdf\" -kind:image \\kind:audio", &mut literal);
+        assert_eq!(text, "\"kind:pdf\" -kind:image \\kind:audio");
+        assert!(literal.is_empty());
+    }
+}
@@ -0,0 +1,193 @@
+//! Item history export to JSON or CSV.
+//!
+//! Items are read a page at a time with a keyset cursor and written through
+//! a buffered file, so memory use does not grow with the history. Only item
+//! text leaves the store; image and file payloads are represented by their
+//! stored description.
+
+use crate::activity::csv_field;
+use crate::database::{Database, DatabaseError, HistoryExportRow};
+use crate::interface::{
+    ClipKittyError, HistoryExportFormat, HistoryExportSummary, ItemQueryFilter,
+};
+use crate::search_result_builder::split_filter;
+use serde_json::json;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+const BATCH_SIZE: usize = 500;
+const CSV_HEADER: &str = "item_id,timestamp,content_type,source_app,source_app_bundle_id,content";
+
+/// Write the items matching `filter` to `path`. A failed export removes the
+/// partial file.
+pub(crate) fn export_history(
+    db: &Database,
+    path: &Path,
+    format: HistoryExportFormat,
+    filter: &ItemQueryFilter,
+) -> Result<HistoryExportSummary, ClipKittyError> {
+    let result = write_history(db, path, format, filter);
+    if result.is_err() {
+        let _ = std::fs::remove_file(path);
+    }
+    result
+}
+
+fn write_history(
+    db: &Database,
+    path: &Path,
+    format: HistoryExportFormat,
+    filter: &ItemQueryFilter,
+) -> Result<HistoryExportSummary, ClipKittyError> {
+    let (content_type, tags) = split_filter(filter);
+    let mut out = BufWriter::new(File::create(path).map_err(DatabaseError::from)?);
+    match format {
+        HistoryExportFormat::Json => out.write_all(b"[").map_err(DatabaseError::from)?,
+        HistoryExportFormat::Csv => writeln!(out, "{CSV_HEADER}").map_err(DatabaseError::from)?,
+    }
+
+    let mut item_count = 0u64;
+    let mut cursor: Option<(String, i64)> = None;
+    loop {
+        let after = cursor
+            .as_ref()
+            .map(|(timestamp, id)| (timestamp.as_str(), *id));
+        let page = db.fetch_history_export_page(content_type.as_ref(), &tags, after, BATCH_SIZE)?;
+        for row in &page {
+            match format {
+                HistoryExportFormat::Json => {
+                    if item_count > 0 {
+                        out.write_all(b",").map_err(DatabaseError::from)?;
+                    }
+                    out.write_all(b"\n  ").map_err(DatabaseError::from)?;
+                    serde_json::to_writer(&mut out, &json_item(row))
+                        .map_err(|error| DatabaseError::from(std::io::Error::from(error)))?;
+                }
+                HistoryExportFormat::Csv => {
+                    writeln!(out, "{}", csv_row(row)).map_err(DatabaseError::from)?
+                }
+            }
+            item_count += 1;
+        }
+        match page.last() {
+            Some(last) if page.len() == BATCH_SIZE => {
+                cursor = Some((last.stored_timestamp.clone(), last.row_id));
+            }
+            _ => break,
+        }
+    }
+
+    if format == HistoryExportFormat::Json {
+        let close: &[u8] = if item_count > 0 { b"\n]\n" } else { b"]\n" };
+        out.write_all(close).map_err(DatabaseError::from)?;
+    }
+    let file = out
+        .into_inner()
+        .map_err(|error| DatabaseError::from(error.into_error()))?;
+    let bytes_written = file.metadata().map_err(DatabaseError::from)?.len();
+    Ok(HistoryExportSummary {
+        item_count,
+        bytes_written,
+    })
+}
+
+fn json_item(row: &HistoryExportRow) -> serde_json::Value {
+    json!({
+        "item_id": row.item_id,
+        "timestamp": row.timestamp.to_rfc3339(),
+        "content_type": row.content_type,
+        "source_app": row.source_app,
+        "source_app_bundle_id": row.source_app_bundle_id,
+        "content": row.content,
+    })
+}
+
+fn csv_row(row: &HistoryExportRow) -> String {
+    let timestamp = row.timestamp.to_rfc3339();
+    [
+        row.item_id.as_str(),
+        timestamp.as_str(),
+        row.content_type.as_str(),
+        row.source_app.as_deref().unwrap_or_default(),
+        row.source_app_bundle_id.as_deref().unwrap_or_default(),
+        row.content.as_str(),
+    ]
+    .map(csv_field)
+    .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::{AppMetadata, ContentTypeFilter};
+    use crate::models::StoredItem;
+
+    #[test]
+    fn json_export_pages_through_items_sharing_a_timestamp() {
+        let db = Database::open_in_memory().unwrap();
+        let item_count = BATCH_SIZE + 2;
+        for index in 0..item_count {
+            let mut item = StoredItem::new_text(format!("item {index}"), None, None);
+            item.timestamp_unix = 1_700_000_000;
+            db.insert_item(&item).unwrap();
+        }
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("history.json");
+
+        let summary =
+            export_history(&db, &path, HistoryExportFormat::Json, &ItemQueryFilter::All).unwrap();
+        assert_eq!(summary.item_count, item_count as u64);
+        assert_eq!(
+            summary.bytes_written,
+            std::fs::metadata(&path).unwrap().len()
+        );
+        let items: Vec<serde_json::Value> =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        let ids: std::collections::HashSet<_> =
+            items.iter().map(|item| item["item_id"].clone()).collect();
+        assert_eq!(ids.len(), item_count);
+        assert_eq!(items[0]["content"], format!("item {}", item_count - 1));
+        assert_eq!(items[0]["timestamp"], "2023-11-14T22:13:20+00:00");
+    }
+
+    #[test]
+    fn csv_export_applies_the_filter_and_registered_app_names() {
+        let db = Database::open_in_memory().unwrap();
+        db.insert_item(&StoredItem::new_text(
+            "hello, \"world\"".into(),
+            Some("Old".into()),
+            Some("com.example.notes".into()),
+        ))
+        .unwrap();
+        db.insert_item(&StoredItem::new_image_with_thumbnail(
+            vec![1, 2, 3],
+            None,
+            None,
+            None,
+            false,
+        ))
+        .unwrap();
+        db.upsert_app_metadata(&[AppMetadata {
+            bundle_id: "com.example.notes".into(),
+            display_name: "Notes".into(),
+            icon_hash: None,
+            category: None,
+            is_installed: true,
+        }])
+        .unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("history.csv");
+        let filter = ItemQueryFilter::ContentType {
+            content_type: ContentTypeFilter::Text,
+        };
+
+        let summary = export_history(&db, &path, HistoryExportFormat::Csv, &filter).unwrap();
+        assert_eq!(summary.item_count, 1);
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[1].ends_with(",text,Notes,com.example.notes,\"hello, \"\"world\"\"\""));
+        assert_eq!(lines.len(), 2);
+    }
+}
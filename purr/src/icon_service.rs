@@ -0,0 +1,473 @@
+//! Icon Selection
+//!
+//! Picks the list icon for an item so every client renders the same
+//! iconography: per-language code icons for snippets, rendered or
+//! document-kind icons for files (from UTI, falling back to the extension), and
+//! app-badged symbols for plain items that carry a source app.
+
+use crate::interface::{ClipboardContent, CodeLanguage, DocumentKind, IconType, ItemIcon};
+use crate::models::StoredItem;
+
+/// Only the head of the text is inspected, so full items and the truncated
+/// prefixes loaded for list rows pick the same language.
+const CODE_DETECTION_PREFIX_CHARS: usize = 512;
+
+/// Minimum number of distinct signals before text is treated as code.
+const MIN_CODE_SIGNALS: usize = 2;
+
+/// Everything icon selection needs, borrowed from either a full item or a
+/// lightweight list row.
+pub(crate) struct IconSource<'a> {
+    pub db_type: &'a str,
+    pub text: &'a str,
+    pub color_rgba: Option<u32>,
+    pub thumbnail: Option<Vec<u8>>,
+    pub source_app_bundle_id: Option<&'a str>,
+    /// `(uti, filename)` of the first file in a file item.
+    pub first_file: Option<(&'a str, &'a str)>,
+}
+
+/// Select the icon for a stored item.
+pub(crate) fn icon_for(item: &StoredItem) -> ItemIcon {
+    let first_file = match &item.content {
+        ClipboardContent::File { files, .. } => files
+            .first()
+            .map(|file| (file.uti.as_str(), file.filename.as_str())),
+        _ => None,
+    };
+    select_icon(IconSource {
+        db_type: item.content.database_type(),
+        text: item.text_content(),
+        color_rgba: item.color_rgba,
+        thumbnail: item.thumbnail.clone(),
+        source_app_bundle_id: item.source_app_bundle_id.as_deref(),
+        first_file,
+    })
+}
+
+/// Select the icon from raw fields.
+pub(crate) fn select_icon(source: IconSource<'_>) -> ItemIcon {
+    let base = match source.db_type {
+        "text" => match detect_code_language(source.text) {
+            Some(language) => return ItemIcon::Code { language },
+            None => ItemIcon::from_database("text", None, None),
+        },
+        "file" => match (source.thumbnail, source.first_file) {
+            (Some(bytes), _) => return ItemIcon::Thumbnail { bytes },
+            (None, Some((uti, filename))) => {
+                return ItemIcon::Document {
+                    kind: document_kind(uti, filename),
+                }
+            }
+            (None, None) => ItemIcon::from_database("file", None, None),
+        },
+        other => ItemIcon::from_database(other, source.color_rgba, source.thumbnail),
+    };
+
+    match (base, source.source_app_bundle_id) {
+        (ItemIcon::Symbol { icon_type }, Some(bundle_id))
+            if !bundle_id.is_empty()
+                && matches!(
+                    icon_type,
+                    IconType::Text
+                        | IconType::Image
+                        | IconType::Color
+                        | IconType::PhoneNumber
+                        | IconType::PostalAddress
+                        | IconType::Markdown
+                ) =>
+        {
+            ItemIcon::AppBadged {
+                icon_type,
+                bundle_id: bundle_id.to_string(),
+            }
+        }
+        (base, _) => base,
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Code language detection
+// ─────────────────────────────────────────────────────────────────────────────
+
+const LANGUAGE_SIGNALS: &[(CodeLanguage, &[&str])] = &[
+    (
+        CodeLanguage::Rust,
+        &[
+            "fn ",
+            "let mut ",
+            "impl ",
+            "pub fn ",
+            "#[derive",
+            "use std::",
+            "-> ",
+            "::",
+        ],
+    ),
+    (
+        CodeLanguage::Swift,
+        &[
+            "func ",
+            "import SwiftUI",
+            "import Foundation",
+            "guard let ",
+            "@State",
+            "var body",
+        ],
+    ),
+    (
+        CodeLanguage::Python,
+        &["def ", "elif ", "self.", "__init__", "import ", "print("],
+    ),
+    (
+        CodeLanguage::JavaScript,
+        &[
+            "function ",
+            "const ",
+            "=> ",
+            "console.log",
+            "require(",
+            "===",
+            "export default",
+        ],
+    ),
+    (
+        CodeLanguage::CFamily,
+        &[
+            "#include",
+            "int main(",
+            "std::",
+            "printf(",
+            "nullptr",
+            "void ",
+        ],
+    ),
+    (
+        CodeLanguage::Sql,
+        &[
+            "SELECT ",
+            " FROM ",
+            " WHERE ",
+            "INSERT INTO",
+            "CREATE TABLE",
+            "JOIN ",
+        ],
+    ),
+    (
+        CodeLanguage::Shell,
+        &["sudo ", "echo ", "export ", "| grep", "&& ", "$("],
+    ),
+];
+
+/// Detect the language of a code snippet, or `None` for prose.
+pub(crate) fn detect_code_language(text: &str) -> Option<CodeLanguage> {
+    let head: String = text
+        .trim_start()
+        .chars()
+        .take(CODE_DETECTION_PREFIX_CHARS)
+        .collect();
+    let head = head.as_str();
+    if head.is_empty() {
+        return None;
+    }
+
+    if let Some(language) = shebang_language(head) {
+        return Some(language);
+    }
+    if looks_like_json(head) {
+        return Some(CodeLanguage::Json);
+    }
+    let lower = head.to_ascii_lowercase();
+    if lower.starts_with("<!doctype html") || lower.starts_with("<html") || lower.contains("</div>")
+    {
+        return Some(CodeLanguage::Html);
+    }
+    if !has_code_structure(head) {
+        return None;
+    }
+
+    LANGUAGE_SIGNALS
+        .iter()
+        .map(|(language, signals)| {
+            let hits = signals
+                .iter()
+                .filter(|signal| head.contains(*signal))
+                .count();
+            (*language, hits)
+        })
+        .filter(|(_, hits)| *hits >= MIN_CODE_SIGNALS)
+        // Ties resolve to the earlier entry in LANGUAGE_SIGNALS.
+        .fold(
+            None,
+            |best: Option<(CodeLanguage, usize)>, candidate| match best {
+                Some(best) if best.1 >= candidate.1 => Some(best),
+                _ => Some(candidate),
+            },
+        )
+        .map(|(language, _)| language)
+}
+
+fn shebang_language(head: &str) -> Option<CodeLanguage> {
+    let first_line = head.lines().next()?.strip_prefix("#!")?;
+    if first_line.contains("python") {
+        Some(CodeLanguage::Python)
+    } else if first_line.contains("node") {
+        Some(CodeLanguage::JavaScript)
+    } else {
+        Some(CodeLanguage::Shell)
+    }
+}
+
+fn looks_like_json(head: &str) -> bool {
+    let trimmed = head.trim_end();
+    let structural = (trimmed.starts_with('{') && trimmed.contains("\":"))
+        || (trimmed.starts_with('[') && trimmed[1..].trim_start().starts_with('{'));
+    structural && trimmed.contains('"')
+}
+
+/// Prose rarely ends lines with statement terminators or braces.
+fn has_code_structure(head: &str) -> bool {
+    head.lines().any(|line| {
+        let line = line.trim_end();
+        line.ends_with(';') || line.ends_with('{') || line.ends_with('}') || line.ends_with(':')
+    }) || head.contains("()")
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Document kinds
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Map a file's UTI to a document kind, using the filename extension when the
+/// UTI is missing or only a generic data type.
+pub(crate) fn document_kind(uti: &str, filename: &str) -> DocumentKind {
+    match document_kind_for_uti(uti) {
+        DocumentKind::Generic => document_kind_for_extension(filename),
+        kind => kind,
+    }
+}
+
+fn document_kind_for_uti(uti: &str) -> DocumentKind {
+    match uti {
+        "com.adobe.pdf" => DocumentKind::Pdf,
+        "public.folder" | "public.directory" | "com.apple.mount-point" => DocumentKind::Folder,
+        "com.apple.application-bundle" | "com.apple.application" => DocumentKind::Application,
+        "public.zip-archive"
+        | "org.gnu.gnu-zip-archive"
+        | "public.tar-archive"
+        | "org.7-zip.7-zip-archive"
+        | "com.rarlab.rar-archive"
+        | "com.apple.disk-image-udif" => DocumentKind::Archive,
+        "org.openxmlformats.spreadsheetml.sheet"
+        | "com.microsoft.excel.xls"
+        | "com.apple.iwork.numbers.sffnumbers"
+        | "com.apple.numbers"
+        | "public.comma-separated-values-text" => DocumentKind::Spreadsheet,
+        "org.openxmlformats.presentationml.presentation"
+        | "com.microsoft.powerpoint.ppt"
+        | "com.apple.iwork.keynote.sffkey"
+        | "com.apple.keynote.key" => DocumentKind::Presentation,
+        "org.openxmlformats.wordprocessingml.document"
+        | "com.microsoft.word.doc"
+        | "com.apple.iwork.pages.sffpages"
+        | "com.apple.pages.pages"
+        | "public.rtf" => DocumentKind::WordProcessing,
+        "public.plain-text" | "public.utf8-plain-text" | "net.daringfireball.markdown" => {
+            DocumentKind::PlainText
+        }
+        "public.json"
+        | "public.xml"
+        | "public.shell-script"
+        | "public.python-script"
+        | "com.netscape.javascript-source" => DocumentKind::SourceCode,
+        other if other.ends_with("-source") || other == "public.source-code" => {
+            DocumentKind::SourceCode
+        }
+        other if other.starts_with("public.image") || IMAGE_UTIS.contains(&other) => {
+            DocumentKind::Image
+        }
+        other if other.contains("audio") || other == "public.mp3" => DocumentKind::Audio,
+        other if other.contains("movie") || other == "public.mpeg-4" || other == "public.avi" => {
+            DocumentKind::Video
+        }
+        _ => DocumentKind::Generic,
+    }
+}
+
+const IMAGE_UTIS: &[&str] = &[
+    "public.jpeg",
+    "public.png",
+    "public.heic",
+    "public.tiff",
+    "com.compuserve.gif",
+    "org.webmproject.webp",
+    "public.svg-image",
+];
+
+fn document_kind_for_extension(filename: &str) -> DocumentKind {
+    let Some((_, extension)) = filename.rsplit_once('.') else {
+        return DocumentKind::Generic;
+    };
+    match extension.to_ascii_lowercase().as_str() {
+        "pdf" => DocumentKind::Pdf,
+        "png" | "jpg" | "jpeg" | "gif" | "heic" | "webp" | "tiff" | "svg" => DocumentKind::Image,
+        "mp3" | "m4a" | "wav" | "aiff" | "flac" | "ogg" => DocumentKind::Audio,
+        "mp4" | "mov" | "m4v" | "avi" | "mkv" | "webm" => DocumentKind::Video,
+        "zip" | "gz" | "tgz" | "tar" | "7z" | "rar" | "dmg" => DocumentKind::Archive,
+        "xlsx" | "xls" | "numbers" | "csv" => DocumentKind::Spreadsheet,
+        "pptx" | "ppt" | "key" => DocumentKind::Presentation,
+        "docx" | "doc" | "pages" | "rtf" => DocumentKind::WordProcessing,
+        "txt" | "md" | "markdown" | "log" => DocumentKind::PlainText,
+        "rs" | "swift" | "py" | "js" | "ts" | "c" | "h" | "cpp" | "m" | "go" | "java" | "sh"
+        | "json" | "xml" | "html" | "css" | "sql" => DocumentKind::SourceCode,
+        "app" => DocumentKind::Application,
+        _ => DocumentKind::Generic,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_source<'a>(text: &'a str, bundle_id: Option<&'a str>) -> IconSource<'a> {
+        IconSource {
+            db_type: "text",
+            text,
+            color_rgba: None,
+            thumbnail: None,
+            source_app_bundle_id: bundle_id,
+            first_file: None,
+        }
+    }
+
+    #[test]
+    fn detects_common_languages() {
+        let rust = "pub fn main() {\n    let mut x = 1;\n}";
+        assert_eq!(detect_code_language(rust), Some(CodeLanguage::Rust));
+
+        let python = "def greet(name):\n    print(name)\n";
+        assert_eq!(detect_code_language(python), Some(CodeLanguage::Python));
+
+        let sql = "SELECT id FROM items WHERE id = 1;";
+        assert_eq!(detect_code_language(sql), Some(CodeLanguage::Sql));
+
+        assert_eq!(
+            detect_code_language("#!/bin/bash\necho hi"),
+            Some(CodeLanguage::Shell)
+        );
+        assert_eq!(
+            detect_code_language("{\"name\": \"clip\"}"),
+            Some(CodeLanguage::Json)
+        );
+        assert_eq!(
+            detect_code_language("<!DOCTYPE html><html></html>"),
+            Some(CodeLanguage::Html)
+        );
+    }
+
+    #[test]
+    fn prose_is_not_code() {
+        assert_eq!(
+            detect_code_language("Let me know if the import works."),
+            None
+        );
+        assert_eq!(detect_code_language("Meeting notes: see you at 5"), None);
+        assert_eq!(detect_code_language(""), None);
+    }
+
+    #[test]
+    fn text_with_source_app_is_badged() {
+        let icon = select_icon(text_source("hello world", Some("com.apple.Safari")));
+        assert_eq!(
+            icon,
+            ItemIcon::AppBadged {
+                icon_type: IconType::Text,
+                bundle_id: "com.apple.Safari".to_string(),
+            }
+        );
+
+        let icon = select_icon(text_source("hello world", None));
+        assert_eq!(
+            icon,
+            ItemIcon::Symbol {
+                icon_type: IconType::Text
+            }
+        );
+    }
+
+    #[test]
+    fn code_icon_wins_over_badge() {
+        let icon = select_icon(text_source(
+            "const x = () => 1;\nconsole.log(x());",
+            Some("com.microsoft.VSCode"),
+        ));
+        assert_eq!(
+            icon,
+            ItemIcon::Code {
+                language: CodeLanguage::JavaScript
+            }
+        );
+    }
+
+    #[test]
+    fn document_kind_prefers_uti_then_extension() {
+        assert_eq!(document_kind("com.adobe.pdf", "report"), DocumentKind::Pdf);
+        assert_eq!(
+            document_kind("public.data", "Budget.XLSX"),
+            DocumentKind::Spreadsheet
+        );
+        assert_eq!(document_kind("public.png", "shot"), DocumentKind::Image);
+        assert_eq!(document_kind("", "archive.tar.gz"), DocumentKind::Archive);
+        assert_eq!(document_kind("", "README"), DocumentKind::Generic);
+    }
+
+    #[test]
+    fn file_items_without_thumbnail_use_document_icon() {
+        let item = StoredItem::new_file(
+            "/tmp/Makefile".to_string(),
+            "Makefile".to_string(),
+            12,
+            "public.plain-text".to_string(),
+            Vec::new(),
+            crate::interface::FilePreviewSnapshot::not_captured(),
+            None,
+            None,
+        );
+        assert_eq!(
+            icon_for(&item),
+            ItemIcon::Document {
+                kind: DocumentKind::PlainText
+            }
+        );
+
+        let item = StoredItem::new_file(
+            "/tmp/notes.md".to_string(),
+            "notes.md".to_string(),
+            12,
+            "net.daringfireball.markdown".to_string(),
+            Vec::new(),
+            crate::interface::FilePreviewSnapshot::not_captured(),
+            None,
+            None,
+        );
+        assert!(matches!(icon_for(&item), ItemIcon::Thumbnail { .. }));
+    }
+
+    #[test]
+    fn color_swatch_is_not_badged() {
+        let icon = select_icon(IconSource {
+            db_type: "color",
+            text: "#FF0000",
+            color_rgba: Some(0xFF0000FF),
+            thumbnail: None,
+            source_app_bundle_id: Some("com.figma.Desktop"),
+            first_file: None,
+        });
+        assert_eq!(
+            icon,
+            ItemIcon::ColorSwatch {
+                rgba: 0xFF0000FF,
+                palette: None,
+            }
+        );
+    }
+}
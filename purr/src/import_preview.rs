@@ -0,0 +1,218 @@
+//! Dry runs for imports.
+//!
+//! Every import path classifies incoming items with the same rules before
+//! writing anything, so the preview a user confirms matches what the import
+//! then does: an item is new, a duplicate (the store already has its ID with
+//! the same content, or its content under another ID), or a conflict (the
+//! store has its ID with different content).
+
+use crate::database::Database;
+use crate::interface::{
+    ClipKittyError, ClipboardContent, FilePreviewSnapshot, FileTextPreviewSnapshot, ImportPreview,
+    LinkMetadataPayload, LinkMetadataState,
+};
+use crate::models::StoredItem;
+use std::collections::HashSet;
+
+/// What an import does with one incoming item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ImportDisposition {
+    New,
+    Duplicate,
+    Conflict,
+}
+
+/// Classifies a stream of incoming items against the store and tallies the
+/// result. Items repeated within the stream count as duplicates of their
+/// first occurrence, as they would once that one is inserted.
+pub(crate) struct ImportClassifier<'a> {
+    db: &'a Database,
+    seen_item_ids: HashSet<String>,
+    seen_hashes: HashSet<String>,
+    preview: ImportPreview,
+}
+
+impl<'a> ImportClassifier<'a> {
+    pub(crate) fn new(db: &'a Database) -> Self {
+        Self {
+            db,
+            seen_item_ids: HashSet::new(),
+            seen_hashes: HashSet::new(),
+            preview: ImportPreview {
+                new_items: 0,
+                duplicates: 0,
+                conflicts: 0,
+                estimated_growth_bytes: 0,
+            },
+        }
+    }
+
+    pub(crate) fn classify(
+        &mut self,
+        item: &StoredItem,
+    ) -> Result<ImportDisposition, ClipKittyError> {
+        let disposition = self.disposition(item)?;
+        match disposition {
+            ImportDisposition::New => {
+                self.preview.new_items += 1;
+                self.preview.estimated_growth_bytes += estimated_size(item);
+            }
+            ImportDisposition::Duplicate => self.preview.duplicates += 1,
+            ImportDisposition::Conflict => self.preview.conflicts += 1,
+        }
+        self.seen_item_ids.insert(item.item_id.clone());
+        self.seen_hashes.insert(item.content_hash.clone());
+        Ok(disposition)
+    }
+
+    /// Count an item the import would remove from the store.
+    #[cfg(feature = "sync")]
+    pub(crate) fn record_removal(&mut self) {
+        self.preview.conflicts += 1;
+    }
+
+    pub(crate) fn finish(self) -> ImportPreview {
+        self.preview
+    }
+
+    fn disposition(&self, item: &StoredItem) -> Result<ImportDisposition, ClipKittyError> {
+        if self.seen_item_ids.contains(&item.item_id)
+            || self.seen_hashes.contains(&item.content_hash)
+        {
+            return Ok(ImportDisposition::Duplicate);
+        }
+        if let Some(row_id) = self.db.fetch_row_id_by_item_id(&item.item_id)? {
+            let local = self.db.fetch_items_by_ids(&[row_id])?;
+            return Ok(match local.first() {
+                Some(local) if local.content_hash != item.content_hash => {
+                    ImportDisposition::Conflict
+                }
+                _ => ImportDisposition::Duplicate,
+            });
+        }
+        if self.db.find_by_hash(&item.content_hash)?.is_some() {
+            return Ok(ImportDisposition::Duplicate);
+        }
+        Ok(ImportDisposition::New)
+    }
+}
+
+/// Approximate bytes `item` occupies once stored: its text and every binary
+/// payload. Row overhead and index growth are left out.
+pub(crate) fn estimated_size(item: &StoredItem) -> u64 {
+    let metadata = item.source_app.as_deref().map_or(0, str::len)
+        + item.source_app_bundle_id.as_deref().map_or(0, str::len)
+        + item.thumbnail.as_ref().map_or(0, Vec::len);
+    let content = match &item.content {
+        ClipboardContent::Text { value }
+        | ClipboardContent::Color { value }
+        | ClipboardContent::PhoneNumber { value }
+        | ClipboardContent::PostalAddress { value } => value.len(),
+        ClipboardContent::RichText {
+            value,
+            rtf_data,
+            html,
+        } => {
+            value.len()
+                + rtf_data.as_ref().map_or(0, Vec::len)
+                + html.as_deref().map_or(0, str::len)
+        }
+        ClipboardContent::Markdown { value, source } => value.len() + source.len(),
+        ClipboardContent::Link {
+            url,
+            metadata_state,
+        } => url.len() + link_metadata_size(metadata_state),
+        ClipboardContent::Image {
+            data, description, ..
+        } => data.len() + description.len(),
+        ClipboardContent::File {
+            display_name,
+            files,
+        } => {
+            display_name.len()
+                + files
+                    .iter()
+                    .map(|file| {
+                        file.path.len()
+                            + file.filename.len()
+                            + file.uti.len()
+                            + file.bookmark_data.len()
+                            + match &file.preview {
+                                FilePreviewSnapshot::Unavailable { .. } => 0,
+                                FilePreviewSnapshot::Text {
+                                    text:
+                                        FileTextPreviewSnapshot::Complete { sample }
+                                        | FileTextPreviewSnapshot::Truncated { sample },
+                                } => sample.len(),
+                                FilePreviewSnapshot::Image { preview_data } => preview_data.len(),
+                            }
+                    })
+                    .sum::<usize>()
+        }
+    };
+    (metadata + content) as u64
+}
+
+fn link_metadata_size(state: &LinkMetadataState) -> usize {
+    let LinkMetadataState::Loaded { payload } = state else {
+        return 0;
+    };
+    let text = |value: Option<&String>| value.map_or(0, String::len);
+    match payload {
+        LinkMetadataPayload::TitleOnly { title, description } => {
+            title.len() + text(description.as_ref())
+        }
+        LinkMetadataPayload::ImageOnly {
+            image_data,
+            description,
+        } => image_data.len() + text(description.as_ref()),
+        LinkMetadataPayload::TitleAndImage {
+            title,
+            image_data,
+            description,
+        } => title.len() + image_data.len() + text(description.as_ref()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_against_store_and_earlier_items() {
+        let db = Database::open_in_memory().unwrap();
+        let existing = StoredItem::new_text("already here".into(), None, None);
+        db.insert_item(&existing).unwrap();
+
+        let mut classifier = ImportClassifier::new(&db);
+        let fresh = StoredItem::new_text("brand new".into(), None, None);
+        assert_eq!(classifier.classify(&fresh).unwrap(), ImportDisposition::New);
+        // Same content under another ID, and the new item again.
+        let same_content = StoredItem::new_text("already here".into(), None, None);
+        assert_eq!(
+            classifier.classify(&same_content).unwrap(),
+            ImportDisposition::Duplicate
+        );
+        assert_eq!(
+            classifier.classify(&fresh).unwrap(),
+            ImportDisposition::Duplicate
+        );
+        // Same ID with edited content.
+        let mut edited = StoredItem::new_text("edited elsewhere".into(), None, None);
+        edited.item_id = existing.item_id.clone();
+        assert_eq!(
+            classifier.classify(&edited).unwrap(),
+            ImportDisposition::Conflict
+        );
+
+        assert_eq!(
+            classifier.finish(),
+            ImportPreview {
+                new_items: 1,
+                duplicates: 2,
+                conflicts: 1,
+                estimated_growth_bytes: "brand new".len() as u64,
+            }
+        );
+    }
+}
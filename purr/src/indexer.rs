@@ -4,19 +4,22 @@
 //! For queries under 3 characters, returns empty (handled by search.rs streaming fallback).
 
 use crate::candidate::{
-    ChunkMatchContext, SearchCandidate, SearchMatchContext, WholeItemMatchContext,
+    ChunkMatchContext, ScoredWordMatches, SearchCandidate, SearchMatchContext,
+    WholeItemMatchContext,
 };
+use crate::interface::{IndexCommitPolicy, TagFilter};
 #[cfg(not(feature = "perf-log"))]
-use crate::ranking::compute_bucket_score;
+use crate::ranking::compute_bucket_score_with_matches;
 #[cfg(feature = "perf-log")]
 use crate::ranking::{
     compute_bucket_score_with_perf, RankingPerfBreakdown, LARGE_DOC_THRESHOLD_BYTES,
 };
 use crate::ranking::{
-    fold_str, prepare_document_for_ranking, PrefixPreferenceQuery, PreparedQuery, QualityTier,
-    ScoringContext,
+    expand_compatibility, fold_str, for_each_camel_case_part, prepare_document_for_ranking,
+    FrecencyBand, PersonalizationBand, PrefixPreferenceQuery, PreparedQuery, QualityTier,
+    ScoringContext, TokenWordMatch,
 };
-use crate::search::{self, SearchQuery};
+use crate::search::{self, QueryOperators, SearchQuery};
 pub(crate) use crate::search_admission::CHUNK_PARENT_THRESHOLD_BYTES;
 use crate::search_admission::{
     verify_tail_word_evidence, PhaseOneAdmissionPolicy, PhaseOneBlendedScore, PhaseTwoHead,
@@ -30,35 +33,82 @@ use tokio_util::sync::CancellationToken;
 /// History: v3 = initial trigram, v4 = content_words WithFreqsAndPositions,
 ///          v5 = previous i64 item_id, v6 = string item_id,
 ///          v7 = diacritic folding in trigram + content_words analyzers,
-///          v8 = "Image: " prefix baked into image descriptions
-pub const INDEX_VERSION: &str = "v8";
+///          v8 = "Image: " prefix baked into image descriptions,
+///          v9 = user tags field,
+///          v10 = stored row fields for index-only search,
+///          v11 = recognized image text as its own search unit,
+///          v12 = CJK characters as their own content_words tokens,
+///          v13 = compatibility (NFKD) folding and ligature expansion,
+///          v14 = camelCase parts as extra content_words tokens
+pub const INDEX_VERSION: &str = "v14";
 
 const CHUNK_TARGET_BYTES: usize = 16 * 1024;
 const CHUNK_OVERLAP_BYTES: usize = 2 * 1024;
 const CHUNK_BOUNDARY_SLACK_BYTES: usize = 1024;
 const RAW_RECALL_BATCHES: [usize; 5] = [256, 512, 1024, 2048, 4096];
+/// Phase 1 recall depth for small corpora and selective queries.
+const MIN_RECALL_LIMIT: usize = 300;
+/// Phase 1 recall depth for huge corpora queried with common words.
+const MAX_RECALL_LIMIT: usize = 3000;
+/// Corpus size, in indexed units, at which recall may reach its full depth.
+const LARGE_CORPUS_UNITS: f64 = 100_000.0;
+/// Corpus size, in indexed units, from which Phase 1 collects segments in
+/// parallel. Below it a sequential pass is faster than the fan-out.
+const PARALLEL_RECALL_MIN_UNITS: u64 = 20_000;
+/// Per-segment budgets cover this multiple of a segment's share of the limit,
+/// so segments holding more than their share of the best hits still fit.
+const SEGMENT_BUDGET_SLACK: f64 = 2.0;
+/// Smallest per-segment budget: small, fresh segments hold recent items, which
+/// the blended score favours.
+const MIN_SEGMENT_BUDGET: usize = 256;
+
+/// Present in the index directory while writes are waiting on a deferred
+/// commit. Finding it on open means those writes were lost, so the index is
+/// rebuilt from the database.
+const PENDING_COMMIT_MARKER: &str = ".pending_commit";
+
+/// Suffixes of the directories beside the index that a rebuild fills and
+/// that hold the replaced index until it is removed.
+const REBUILD_DIR_SUFFIX: &str = "rebuild";
+const RETIRED_DIR_SUFFIX: &str = "retired";
 use parking_lot::{Mutex, RwLock};
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
 #[cfg(test)]
 use tantivy::collector::TopDocs;
 use tantivy::collector::{Collector, SegmentCollector, TopNComputer};
 use tantivy::directory::MmapDirectory;
+use tantivy::indexer::NoMergePolicy;
 use tantivy::query::{
     BooleanQuery, BoostQuery, ConstScoreQuery, FuzzyTermQuery, Occur, PhrasePrefixQuery,
-    PhraseQuery, TermQuery,
+    PhraseQuery, RegexQuery, TermQuery,
 };
 use tantivy::schema::*;
 use tantivy::tokenizer::{
-    LowerCaser, NgramTokenizer, RemoveLongFilter, SimpleTokenizer, TextAnalyzer, TokenFilter,
-    TokenStream, Tokenizer,
+    LowerCaser, NgramTokenizer, RemoveLongFilter, TextAnalyzer, Token, TokenFilter, TokenStream,
+    Tokenizer,
 };
 use tantivy::{
     DocAddress, DocId, Index, IndexReader, IndexWriter, ReloadPolicy, Score, SegmentReader, Term,
 };
 use thiserror::Error;
 
+/// Saved snippets share the index with history under document IDs with
+/// this prefix.
+pub(crate) const SNIPPET_ID_PREFIX: &str = "snippet:";
+
+/// Which documents a search recalls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DocumentScope {
+    History,
+    Snippets,
+}
+
 #[derive(Debug, Clone, Copy)]
 struct ChunkSlice {
     index: u32,
@@ -66,6 +116,17 @@ struct ChunkSlice {
     end: usize,
 }
 
+/// What a search unit document holds.
+#[derive(Debug, Clone, Copy)]
+enum SearchUnit {
+    /// The whole indexed text of an item.
+    WholeItem,
+    /// One chunk of a large item's indexed text.
+    Chunk(ChunkSlice),
+    /// Text recognized in an image, kept apart from its description.
+    RecognizedText,
+}
+
 /// Token filter that assigns incrementing positions to tokens.
 /// NgramTokenizer sets all positions to 0, which breaks PhraseQuery.
 /// This filter fixes that so PhraseQuery can match contiguous ngrams.
@@ -172,6 +233,131 @@ impl<T: TokenStream> TokenStream for DiacriticFoldTokenStream<T> {
     }
 }
 
+/// Runs the inner tokenizer over text with its compatibility expansions
+/// spelled out ('ﬁ' -> "fi"), so trigrams span the letters `fold_str` gives
+/// the query. Everything else folds per token in `DiacriticFoldFilter`.
+#[derive(Clone)]
+struct CompatibilityExpansionTokenizer<T> {
+    inner: T,
+    expanded: String,
+}
+
+impl<T> CompatibilityExpansionTokenizer<T> {
+    fn new(inner: T) -> Self {
+        Self {
+            inner,
+            expanded: String::new(),
+        }
+    }
+}
+
+impl<T: Tokenizer> Tokenizer for CompatibilityExpansionTokenizer<T> {
+    type TokenStream<'a> = T::TokenStream<'a>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        match expand_compatibility(text) {
+            Cow::Borrowed(_) => self.inner.token_stream(text),
+            Cow::Owned(expanded) => {
+                self.expanded = expanded;
+                self.inner.token_stream(&self.expanded)
+            }
+        }
+    }
+}
+
+/// tantivy's `SimpleTokenizer`, except that each CJK character is a token of
+/// its own, as in `search::tokenize_words`. Chinese and Japanese text has no
+/// spaces, so a sentence would otherwise index as one unmatchable term.
+///
+/// A camelCase identifier is followed by its parts, the first sharing the
+/// identifier's position and the rest taking the next ones, so "access
+/// token" is a phrase inside `refreshAccessToken`. The next word comes after
+/// the last part, keeping each term's positions increasing.
+#[derive(Clone, Default)]
+struct WordTokenizer {
+    token: Token,
+    parts: Vec<(usize, usize)>,
+}
+
+struct WordTokenStream<'a> {
+    text: &'a str,
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    token: &'a mut Token,
+    /// Byte ranges of the current identifier's parts still to emit, last first.
+    parts: &'a mut Vec<(usize, usize)>,
+    next_position: usize,
+}
+
+impl Tokenizer for WordTokenizer {
+    type TokenStream<'a> = WordTokenStream<'a>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        self.token.reset();
+        self.parts.clear();
+        WordTokenStream {
+            text,
+            chars: text.char_indices().peekable(),
+            token: &mut self.token,
+            parts: &mut self.parts,
+            next_position: 0,
+        }
+    }
+}
+
+impl WordTokenStream<'_> {
+    fn emit(&mut self, start: usize, end: usize) {
+        self.token.offset_from = start;
+        self.token.offset_to = end;
+        self.token.position = self.next_position;
+        self.token.text.push_str(&self.text[start..end]);
+    }
+}
+
+impl TokenStream for WordTokenStream<'_> {
+    fn advance(&mut self) -> bool {
+        self.token.text.clear();
+        if let Some((start, end)) = self.parts.pop() {
+            self.emit(start, end);
+            self.next_position += 1;
+            return true;
+        }
+        while let Some((start, c)) = self.chars.next() {
+            if !c.is_alphanumeric() {
+                continue;
+            }
+            let mut end = start + c.len_utf8();
+            if !search::is_cjk_char(c) {
+                while let Some(&(offset, next)) = self.chars.peek() {
+                    if !next.is_alphanumeric() || search::is_cjk_char(next) {
+                        break;
+                    }
+                    self.chars.next();
+                    end = offset + next.len_utf8();
+                }
+            }
+            for_each_camel_case_part(&self.text[start..end], |part_start, part_end| {
+                self.parts.push((start + part_start, start + part_end));
+            });
+            self.parts.reverse();
+            self.emit(start, end);
+            // The first part, if any, shares the identifier's position.
+            if self.parts.is_empty() {
+                self.next_position += 1;
+            }
+            return true;
+        }
+        false
+    }
+
+    fn token(&self) -> &Token {
+        self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.token
+    }
+}
+
 /// Error type for indexer operations
 #[derive(Error, Debug)]
 pub enum IndexerError {
@@ -181,6 +367,8 @@ pub enum IndexerError {
     Directory(#[from] tantivy::directory::error::OpenDirectoryError),
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("Index is open read-only")]
+    ReadOnly,
 }
 
 pub type IndexerResult<T> = Result<T, IndexerError>;
@@ -200,10 +388,32 @@ impl OwnedPrefixPreferenceQuery {
     }
 }
 
+/// Ranking signals learned from how the user pastes an item.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct ItemPersonalization {
+    /// Preference of the current paste target for the item.
+    pub(crate) target: PersonalizationBand,
+    pub(crate) frecency: FrecencyBand,
+}
+
+/// Resolves personalization for the item IDs recalled by Phase 1.
+/// Items missing from the returned map rank without personalization.
+pub(crate) type PersonalizationResolver<'a> =
+    &'a (dyn Fn(&[&str]) -> HashMap<String, ItemPersonalization> + Sync);
+
 #[derive(Debug, Clone, Copy)]
 struct PhaseTwoQuery<'a> {
     query: &'a PreparedQuery,
     prefix_preference: Option<PrefixPreferenceQuery<'a>>,
+    personalization: Option<&'a HashMap<String, ItemPersonalization>>,
+}
+
+impl PhaseTwoQuery<'_> {
+    fn personalization_for(&self, item_id: &str) -> ItemPersonalization {
+        self.personalization
+            .and_then(|bands| bands.get(item_id).copied())
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -309,6 +519,7 @@ fn prepare_prefix_preference(query: &SearchQuery) -> Option<OwnedPrefixPreferenc
         SearchQuery::PreferPrefix {
             raw_text,
             stripped_text,
+            ..
         } => Some(OwnedPrefixPreferenceQuery {
             raw_query_folded: fold_str(raw_text),
             stripped_query_folded: fold_str(stripped_text),
@@ -466,6 +677,17 @@ fn chunk_slices(content: &str) -> Vec<ChunkSlice> {
     chunks
 }
 
+/// Apply the recognized-text weight to matches in an image's OCR unit.
+fn weigh_match_source(
+    candidate: &SearchCandidate,
+    mut bucket: crate::ranking::BucketScore,
+) -> crate::ranking::BucketScore {
+    if candidate.matched_recognized_text() {
+        bucket.quality_tier = bucket.quality_tier.for_recognized_text();
+    }
+    bucket
+}
+
 #[cfg(not(feature = "perf-log"))]
 fn score_phase_two_candidate(
     candidate: &SearchCandidate,
@@ -474,17 +696,23 @@ fn score_phase_two_candidate(
 ) -> PhaseTwoCandidateScore {
     let content = candidate.content();
     let document = prepare_document_for_ranking(content);
+    let personalization = phase_two_query.personalization_for(&candidate.id);
 
-    let bucket = compute_bucket_score(&ScoringContext {
+    let (bucket, matches) = compute_bucket_score_with_matches(&ScoringContext {
         document: &document,
         query: phase_two_query.query,
         prefix_preference: phase_two_query.prefix_preference,
         timestamp: candidate.timestamp,
         now,
+        personalization: personalization.target,
+        frecency: personalization.frecency,
     });
 
+    let bucket = weigh_match_source(candidate, bucket);
+    let matched = !matches!(bucket.quality_tier, QualityTier::NoMatch);
     PhaseTwoCandidateScore {
-        bucket: (!matches!(bucket.quality_tier, QualityTier::NoMatch)).then_some(bucket),
+        bucket: matched.then_some(bucket),
+        matches: matches.filter(|_| matched),
     }
 }
 
@@ -498,17 +726,23 @@ fn score_phase_two_candidate(
     let content = candidate.content();
     let document = prepare_document_for_ranking(content);
     let prep_ns = prep_start.elapsed().as_nanos() as u64;
+    let personalization = phase_two_query.personalization_for(&candidate.id);
 
-    let (bucket, ranking) = compute_bucket_score_with_perf(&ScoringContext {
+    let (bucket, matches, ranking) = compute_bucket_score_with_perf(&ScoringContext {
         document: &document,
         query: phase_two_query.query,
         prefix_preference: phase_two_query.prefix_preference,
         timestamp: candidate.timestamp,
         now,
+        personalization: personalization.target,
+        frecency: personalization.frecency,
     });
 
+    let bucket = weigh_match_source(candidate, bucket);
+    let matched = !matches!(bucket.quality_tier, QualityTier::NoMatch);
     PhaseTwoCandidateScore {
-        bucket: (!matches!(bucket.quality_tier, QualityTier::NoMatch)).then_some(bucket),
+        bucket: matched.then_some(bucket),
+        matches: matches.filter(|_| matched),
         perf: PhaseTwoCandidatePerf {
             doc_bytes: content.len(),
             prep_ns,
@@ -519,12 +753,16 @@ fn score_phase_two_candidate(
 
 struct PhaseTwoCandidateScore {
     bucket: Option<crate::ranking::BucketScore>,
+    /// Token matches recorded for a matched small document.
+    matches: Option<Vec<TokenWordMatch>>,
     #[cfg(feature = "perf-log")]
     perf: PhaseTwoCandidatePerf,
 }
 
 struct PhaseTwoRun {
     scored: Vec<(crate::ranking::BucketScore, usize)>,
+    /// Recorded token matches by candidate index, for highlighting.
+    matches: Vec<(usize, Vec<TokenWordMatch>)>,
     #[cfg(feature = "perf-log")]
     perf: PhaseTwoPerfTotals,
 }
@@ -562,6 +800,22 @@ struct CollapsedDocHit {
 struct CollapsedTopDocs {
     limit: usize,
     now: i64,
+    /// Units in the whole index when each segment keeps only its budget of
+    /// hits; `None` keeps every segment hit for the merge.
+    segment_budget_units: Option<u64>,
+}
+
+impl CollapsedTopDocs {
+    /// Hits a segment of `segment_units` keeps for the merge: its share of
+    /// the limit with slack, at least `MIN_SEGMENT_BUDGET`, at most the limit.
+    fn segment_budget(&self, segment_units: u64) -> usize {
+        let Some(total_units) = self.segment_budget_units.filter(|&units| units > 0) else {
+            return usize::MAX;
+        };
+        let share = segment_units as f64 / total_units as f64;
+        let budget = (self.limit as f64 * share * SEGMENT_BUDGET_SLACK).ceil() as usize;
+        budget.max(MIN_SEGMENT_BUDGET).min(self.limit)
+    }
 }
 
 // Collect the best Phase 1 hit per parent item within a segment so large-document
@@ -572,11 +826,17 @@ struct CollapsedTopDocsSegmentCollector {
     timestamp_reader: tantivy::fastfield::Column<i64>,
     parent_len_reader: tantivy::fastfield::Column<i64>,
     now: i64,
+    budget: usize,
     docs_by_item: HashMap<u64, CollapsedDocHit>,
 }
 
 impl CollapsedTopDocsSegmentCollector {
-    fn new(segment_ord: u32, segment_reader: &SegmentReader, now: i64) -> tantivy::Result<Self> {
+    fn new(
+        segment_ord: u32,
+        segment_reader: &SegmentReader,
+        now: i64,
+        budget: usize,
+    ) -> tantivy::Result<Self> {
         let item_id_str_col = segment_reader
             .fast_fields()
             .str("item_id")?
@@ -593,6 +853,7 @@ impl CollapsedTopDocsSegmentCollector {
                 .i64("parent_len")
                 .expect("parent_len fast field"),
             now,
+            budget,
             docs_by_item: HashMap::new(),
         })
     }
@@ -622,7 +883,22 @@ impl SegmentCollector for CollapsedTopDocsSegmentCollector {
     }
 
     fn harvest(self) -> Self::Fruit {
-        self.docs_by_item.into_values().collect()
+        if self.docs_by_item.len() <= self.budget {
+            return self.docs_by_item.into_values().collect();
+        }
+        let mut top_docs: TopNComputer<PhaseOneBlendedScore, CollapsedDocAddress> =
+            TopNComputer::new(self.budget);
+        for hit in self.docs_by_item.into_values() {
+            top_docs.push(hit.score, hit.address);
+        }
+        top_docs
+            .into_sorted_vec()
+            .into_iter()
+            .map(|doc| CollapsedDocHit {
+                score: doc.feature,
+                address: doc.doc,
+            })
+            .collect()
     }
 }
 
@@ -641,7 +917,12 @@ impl Collector for CollapsedTopDocs {
         segment_local_id: u32,
         segment: &SegmentReader,
     ) -> tantivy::Result<Self::Child> {
-        CollapsedTopDocsSegmentCollector::new(segment_local_id, segment, self.now)
+        CollapsedTopDocsSegmentCollector::new(
+            segment_local_id,
+            segment,
+            self.now,
+            self.segment_budget(u64::from(segment.num_docs())),
+        )
     }
 
     fn requires_scoring(&self) -> bool {
@@ -678,6 +959,31 @@ impl Collector for CollapsedTopDocs {
     }
 }
 
+/// Phase 1 collection with one rayon task per segment, each keeping its
+/// budget of hits, merged as `Searcher::search` would.
+fn collect_segments_in_parallel(
+    searcher: &tantivy::Searcher,
+    query: &dyn tantivy::query::Query,
+    collector: &CollapsedTopDocs,
+) -> tantivy::Result<Vec<PhaseOneHit>> {
+    use rayon::prelude::*;
+
+    let weight = query.weight(tantivy::query::EnableScoring::enabled_from_searcher(
+        searcher,
+    ))?;
+    let segment_fruits = crate::power::install(|| {
+        searcher
+            .segment_readers()
+            .par_iter()
+            .enumerate()
+            .map(|(segment_ord, segment_reader)| {
+                collector.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader)
+            })
+            .collect::<tantivy::Result<Vec<_>>>()
+    })?;
+    collector.merge_fruits(segment_fruits)
+}
+
 fn collapsed_hit_is_better(candidate: &CollapsedDocHit, current: &CollapsedDocHit) -> bool {
     candidate.score > current.score
         || (candidate.score == current.score && candidate.address < current.address)
@@ -692,6 +998,9 @@ fn run_phase_two_head(
 ) -> Result<PhaseTwoRun, IndexerError> {
     use rayon::prelude::*;
 
+    let _span =
+        tracing::debug_span!("search.phase_two", candidates = head.indices().len()).entered();
+
     const CANCELLATION_CHECK_CHUNK_SIZE: usize = 32;
 
     let head_candidates: Vec<(usize, SearchCandidate)> = head
@@ -700,37 +1009,44 @@ fn run_phase_two_head(
         .map(|index| (index, candidates[index].clone()))
         .collect();
 
-    let chunk_results: Vec<PhaseTwoRun> = head_candidates
-        .par_chunks(CANCELLATION_CHECK_CHUNK_SIZE)
-        .enumerate()
-        .map(|(_chunk_index, chunk)| {
-            let mut scored = Vec::with_capacity(chunk.len());
-            #[cfg(feature = "perf-log")]
-            let mut perf = PhaseTwoPerfTotals::default();
-
-            for (_offset, candidate) in chunk.iter().enumerate() {
-                if token.is_cancelled() {
-                    break;
-                }
-                #[cfg(test)]
-                test_support::on_phase_two_candidate(
-                    _chunk_index * CANCELLATION_CHECK_CHUNK_SIZE + _offset,
-                );
-                let outcome = score_phase_two_candidate(&candidate.1, phase_two_query, now);
+    let chunk_results: Vec<PhaseTwoRun> = crate::power::install(|| {
+        head_candidates
+            .par_chunks(CANCELLATION_CHECK_CHUNK_SIZE)
+            .enumerate()
+            .map(|(_chunk_index, chunk)| {
+                let mut scored = Vec::with_capacity(chunk.len());
+                let mut matches = Vec::new();
                 #[cfg(feature = "perf-log")]
-                perf.record(outcome.perf, outcome.bucket.is_some());
-                if let Some(bucket) = outcome.bucket {
-                    scored.push((bucket, candidate.0));
+                let mut perf = PhaseTwoPerfTotals::default();
+
+                for (_offset, candidate) in chunk.iter().enumerate() {
+                    if token.is_cancelled() {
+                        break;
+                    }
+                    #[cfg(test)]
+                    test_support::on_phase_two_candidate(
+                        _chunk_index * CANCELLATION_CHECK_CHUNK_SIZE + _offset,
+                    );
+                    let outcome = score_phase_two_candidate(&candidate.1, phase_two_query, now);
+                    #[cfg(feature = "perf-log")]
+                    perf.record(outcome.perf, outcome.bucket.is_some());
+                    if let Some(bucket) = outcome.bucket {
+                        scored.push((bucket, candidate.0));
+                    }
+                    if let Some(tokens) = outcome.matches {
+                        matches.push((candidate.0, tokens));
+                    }
                 }
-            }
 
-            PhaseTwoRun {
-                scored,
-                #[cfg(feature = "perf-log")]
-                perf,
-            }
-        })
-        .collect();
+                PhaseTwoRun {
+                    scored,
+                    matches,
+                    #[cfg(feature = "perf-log")]
+                    perf,
+                }
+            })
+            .collect()
+    });
 
     if token.is_cancelled() {
         return Err(IndexerError::Tantivy(tantivy::TantivyError::InternalError(
@@ -739,28 +1055,73 @@ fn run_phase_two_head(
     }
 
     let mut scored = Vec::new();
+    let mut matches = Vec::new();
     #[cfg(feature = "perf-log")]
     let mut perf = PhaseTwoPerfTotals::default();
 
     for mut chunk_result in chunk_results {
         scored.append(&mut chunk_result.scored);
+        matches.append(&mut chunk_result.matches);
         #[cfg(feature = "perf-log")]
         perf.merge(chunk_result.perf);
     }
 
     Ok(PhaseTwoRun {
         scored,
+        matches,
         #[cfg(feature = "perf-log")]
         perf,
     })
 }
 
+/// Writes awaiting a deferred commit, and the policy deciding when to commit.
+#[derive(Default)]
+struct CommitState {
+    policy: IndexCommitPolicy,
+    /// Bumped on every policy change so a superseded flush thread exits.
+    generation: u64,
+    pending_writes: u32,
+    oldest_pending: Option<Instant>,
+    /// Set while the store is being moved, so the flush thread leaves the
+    /// index files alone.
+    frozen: bool,
+}
+
+impl CommitState {
+    fn is_due(&self) -> bool {
+        self.pending_writes >= self.policy.max_pending_writes
+            || self.oldest_pending.is_some_and(|since| {
+                since.elapsed() >= Duration::from_millis(self.policy.max_delay_ms)
+            })
+    }
+}
+
+/// Items written to the live index while a rebuild runs, so the rebuilt
+/// index can catch up before it replaces the live one.
+#[derive(Default)]
+struct RebuildJournal {
+    item_ids: HashSet<String>,
+    /// Every document was deleted; the rebuilt index must drop its own too.
+    cleared: bool,
+}
+
 /// Tantivy-based indexer with trigram tokenization
 pub struct Indexer {
-    index: Index,
+    /// Replaced as a whole when a rebuild swaps in.
+    index: RwLock<Index>,
     writer: Mutex<Option<IndexWriter>>,
     writer_memory_budget: usize,
     reader: RwLock<IndexReader>,
+    commit_state: Mutex<CommitState>,
+    /// Index directory; None for in-memory indexes.
+    directory: Option<PathBuf>,
+    /// Opened with writes lost to a crash. The pending-commit marker stays
+    /// until a rebuild replaces the index.
+    recovering: AtomicBool,
+    /// Present while a rebuild runs.
+    rebuild_journal: Mutex<Option<RebuildJournal>>,
+    /// Opened by `open_read_only`: never takes the writer lock.
+    read_only: bool,
     item_id_field: Field,
     content_field: Field,
     content_words_field: Field,
@@ -769,6 +1130,38 @@ pub struct Indexer {
     chunk_index_field: Field,
     chunk_start_field: Field,
     chunk_end_field: Field,
+    tags_field: Field,
+    row_field: Field,
+    thumbnail_field: Field,
+    recognized_text_field: Field,
+}
+
+impl Drop for Indexer {
+    fn drop(&mut self) {
+        // A clean shutdown keeps deferred writes; the marker covers crashes.
+        let _ = self.flush();
+    }
+}
+
+/// What list rendering needs about an item, stored with each of its search
+/// units so index-only search can decorate rows without reading SQLite.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct IndexedRow {
+    pub(crate) content_hash: String,
+    pub(crate) db_type: String,
+    /// Leading `items.content`, as much as SQL search metadata reads.
+    pub(crate) content_prefix: String,
+    pub(crate) source_app: Option<String>,
+    pub(crate) source_app_bundle_id: Option<String>,
+    pub(crate) color_rgba: Option<u32>,
+    pub(crate) first_file_uti: Option<String>,
+    pub(crate) first_file_name: Option<String>,
+    /// Kept in its own bytes field rather than the JSON.
+    #[serde(skip)]
+    pub(crate) thumbnail: Option<Vec<u8>>,
+    /// Indexed as the item's recognized text unit, not stored in the row.
+    #[serde(skip)]
+    pub(crate) ocr_text: Option<String>,
 }
 
 #[cfg(test)]
@@ -825,6 +1218,17 @@ pub(crate) enum IndexInspection {
 
 impl Indexer {
     pub(crate) fn inspect(path: &Path) -> IndexerResult<IndexInspection> {
+        let inspection = Self::inspect_committed(path)?;
+        if matches!(inspection, IndexInspection::Ready { .. })
+            && path.join(PENDING_COMMIT_MARKER).exists()
+        {
+            return Ok(IndexInspection::RebuildRequired);
+        }
+        Ok(inspection)
+    }
+
+    /// Inspect the last commit alone, ignoring writes lost after it.
+    fn inspect_committed(path: &Path) -> IndexerResult<IndexInspection> {
         if !path.exists() {
             return Ok(IndexInspection::Missing);
         }
@@ -851,12 +1255,25 @@ impl Indexer {
 
     /// Create a new indexer at the given path.
     /// Automatically detects schema mismatches and rebuilds the index if needed.
+    /// An index that only lost writes to a crash stays open, serving what it
+    /// has until a rebuild replaces it.
     pub fn new(path: &Path) -> IndexerResult<Self> {
-        if matches!(Self::inspect(path)?, IndexInspection::RebuildRequired) {
+        if matches!(
+            Self::inspect_committed(path)?,
+            IndexInspection::RebuildRequired
+        ) {
             std::fs::remove_dir_all(path)?;
         }
 
         let schema = Self::build_schema();
+        let (index, reader) = Self::open_dir(path, &schema)?;
+        let mut indexer = Self::from_parts(index, reader, schema, 50_000_000);
+        indexer.directory = Some(path.to_path_buf());
+        indexer.recovering = AtomicBool::new(path.join(PENDING_COMMIT_MARKER).exists());
+        Ok(indexer)
+    }
+
+    fn open_dir(path: &Path, schema: &Schema) -> IndexerResult<(Index, IndexReader)> {
         std::fs::create_dir_all(path)?;
         let dir = MmapDirectory::open(path)?;
         let index = Index::open_or_create(dir, schema.clone())?;
@@ -866,13 +1283,48 @@ impl Indexer {
             .reader_builder()
             .reload_policy(ReloadPolicy::Manual)
             .try_into()?;
+        Ok((index, reader))
+    }
 
-        Ok(Self::from_parts(index, reader, schema, 50_000_000))
+    /// Open the index at `path` for searching alongside the process that
+    /// writes it. The writer lock is never taken, writes fail with
+    /// `ReadOnly`, and searches pick up the writer's commits shortly after
+    /// they land. A missing or outdated index is left for the writer.
+    pub fn open_read_only(path: &Path) -> IndexerResult<Self> {
+        let unavailable = |message: &str| {
+            IndexerError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                message.to_string(),
+            ))
+        };
+        match Self::inspect_committed(path)? {
+            IndexInspection::Missing => return Err(unavailable("no search index to read")),
+            IndexInspection::RebuildRequired => {
+                return Err(unavailable("the search index awaits a rebuild"))
+            }
+            IndexInspection::Ready { .. } => {}
+        }
+        let index = Index::open(MmapDirectory::open(path)?)?;
+        Self::register_tokenizers(&index);
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()?;
+        let mut indexer = Self::from_parts(index, reader, Self::build_schema(), 0);
+        indexer.directory = Some(path.to_path_buf());
+        indexer.read_only = true;
+        Ok(indexer)
     }
 
     /// Create an in-memory indexer (for testing)
     #[cfg(test)]
     pub fn new_in_memory() -> IndexerResult<Self> {
+        Self::create_in_ram()
+    }
+
+    /// An index that lives in memory only, e.g. to absorb the index writes
+    /// of an import into a database whose index is rebuilt afterwards.
+    pub(crate) fn create_in_ram() -> IndexerResult<Self> {
         let schema = Self::build_schema();
         let index = Index::create_in_ram(schema.clone());
         Self::register_tokenizers(&index);
@@ -900,10 +1352,19 @@ impl Indexer {
             chunk_index_field: schema.get_field("chunk_index").unwrap(),
             chunk_start_field: schema.get_field("chunk_start").unwrap(),
             chunk_end_field: schema.get_field("chunk_end").unwrap(),
-            index,
+            tags_field: schema.get_field("tags").unwrap(),
+            row_field: schema.get_field("row").unwrap(),
+            thumbnail_field: schema.get_field("thumbnail").unwrap(),
+            recognized_text_field: schema.get_field("recognized_text").unwrap(),
+            index: RwLock::new(index),
             writer: Mutex::new(None),
             writer_memory_budget,
             reader: RwLock::new(reader),
+            commit_state: Mutex::new(CommitState::default()),
+            directory: None,
+            recovering: AtomicBool::new(false),
+            rebuild_journal: Mutex::new(None),
+            read_only: false,
         }
     }
 
@@ -911,15 +1372,60 @@ impl Indexer {
         &self,
         operation: impl FnOnce(&mut IndexWriter) -> IndexerResult<T>,
     ) -> IndexerResult<T> {
+        if self.read_only {
+            return Err(IndexerError::ReadOnly);
+        }
         let mut writer_slot = self.writer.lock();
         if writer_slot.is_none() {
-            *writer_slot = Some(self.index.writer(self.writer_memory_budget)?);
+            // Writers live until the next commit, so each one picks up the
+            // power state current when it was opened.
+            let index = self.index.read();
+            let writer = if crate::power::is_low_power() {
+                let writer: IndexWriter =
+                    index.writer_with_num_threads(1, self.writer_memory_budget)?;
+                writer.set_merge_policy(Box::new(NoMergePolicy));
+                writer
+            } else {
+                index.writer(self.writer_memory_budget)?
+            };
+            *writer_slot = Some(writer);
         }
         operation(writer_slot.as_mut().expect("writer initialized above"))
     }
 
+    /// Run a document write through the writer. The write counts as pending,
+    /// with the crash marker in place if its commit is deferred, before the
+    /// writer lock is released, so a commit on another thread cannot drop the
+    /// marker while the write sits uncommitted in the next writer.
+    fn write_documents<T>(
+        &self,
+        operation: impl FnOnce(&mut IndexWriter) -> IndexerResult<T>,
+    ) -> IndexerResult<T> {
+        self.with_writer(|writer| {
+            let result = operation(writer)?;
+            let mut state = self.commit_state.lock();
+            state.pending_writes += 1;
+            if !state.is_due() && state.oldest_pending.is_none() {
+                state.oldest_pending = Some(Instant::now());
+                if let Some(directory) = &self.directory {
+                    std::fs::write(directory.join(PENDING_COMMIT_MARKER), b"")?;
+                }
+            }
+            Ok(result)
+        })
+    }
+
     fn close_writer(&self, wait_for_merges: bool) -> IndexerResult<()> {
-        let writer = self.writer.lock().take();
+        let _span = tracing::info_span!("index.commit", wait_for_merges).entered();
+        let writer = {
+            let mut writer_slot = self.writer.lock();
+            // Everything pending is in this writer; writes counted after this
+            // point go to the next one.
+            let mut state = self.commit_state.lock();
+            state.pending_writes = 0;
+            state.oldest_pending = None;
+            writer_slot.take()
+        };
         let Some(mut writer) = writer else {
             return Ok(());
         };
@@ -934,10 +1440,136 @@ impl Indexer {
 
         commit_result?;
         close_result?;
+        self.remove_pending_marker();
         self.reader.write().reload()?;
         Ok(())
     }
 
+    /// Drop the marker unless new writes became pending meanwhile, or the
+    /// index still awaits a rebuild for writes lost earlier.
+    fn remove_pending_marker(&self) {
+        let state = self.commit_state.lock();
+        if state.pending_writes > 0 || self.recovering.load(AtomicOrdering::Relaxed) {
+            return;
+        }
+        if let Some(directory) = &self.directory {
+            let _ = std::fs::remove_file(directory.join(PENDING_COMMIT_MARKER));
+        }
+    }
+
+    fn sibling_dir(directory: &Path, suffix: &str) -> PathBuf {
+        let mut name = directory.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{suffix}"));
+        directory.with_file_name(name)
+    }
+
+    fn journal(&self, record: impl FnOnce(&mut RebuildJournal)) {
+        if let Some(journal) = self.rebuild_journal.lock().as_mut() {
+            record(journal);
+        }
+    }
+
+    /// Start a rebuild into a fresh index beside this one. Searches keep
+    /// reading this index, and writes keep landing in it, until
+    /// `finish_rebuild` swaps the new one in. One rebuild runs at a time.
+    pub(crate) fn begin_rebuild(&self) -> IndexerResult<Indexer> {
+        if self.read_only {
+            return Err(IndexerError::ReadOnly);
+        }
+        {
+            let mut journal = self.rebuild_journal.lock();
+            if journal.is_some() {
+                return Err(IndexerError::Io(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    "an index rebuild is already running",
+                )));
+            }
+            *journal = Some(RebuildJournal::default());
+        }
+        let staging = match &self.directory {
+            Some(directory) => {
+                let staging_dir = Self::sibling_dir(directory, REBUILD_DIR_SUFFIX);
+                if staging_dir.exists() {
+                    std::fs::remove_dir_all(&staging_dir)?;
+                }
+                let schema = Self::build_schema();
+                let (index, reader) = Self::open_dir(&staging_dir, &schema)?;
+                Ok(Self::from_parts(
+                    index,
+                    reader,
+                    schema,
+                    self.writer_memory_budget,
+                ))
+            }
+            None => Self::create_in_ram(),
+        };
+        if staging.is_err() {
+            self.abandon_rebuild();
+        }
+        staging
+    }
+
+    /// Swap in the index filled since `begin_rebuild`. Writes to this index
+    /// are held off meanwhile; `replay` first brings the new index up to date
+    /// with the items written here during the rebuild, given their IDs.
+    pub(crate) fn finish_rebuild<E: From<IndexerError>>(
+        &self,
+        staging: Indexer,
+        replay: impl FnOnce(&Indexer, Vec<String>) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let mut writer_slot = self.writer.lock();
+        let journal = self.rebuild_journal.lock().take().unwrap_or_default();
+        if journal.cleared {
+            staging.delete_all_documents()?;
+        }
+        replay(&staging, journal.item_ids.into_iter().collect())?;
+        staging.close_writer(true)?;
+
+        let (index, reader) = match &self.directory {
+            None => (staging.index.read().clone(), staging.reader.read().clone()),
+            Some(directory) => {
+                drop(staging);
+                let staging_dir = Self::sibling_dir(directory, REBUILD_DIR_SUFFIX);
+                let retired_dir = Self::sibling_dir(directory, RETIRED_DIR_SUFFIX);
+                if retired_dir.exists() {
+                    std::fs::remove_dir_all(&retired_dir).map_err(IndexerError::from)?;
+                }
+                // The live index keeps serving from its renamed directory
+                // until the swap below.
+                std::fs::rename(directory, &retired_dir).map_err(IndexerError::from)?;
+                if let Err(error) = std::fs::rename(&staging_dir, directory) {
+                    let _ = std::fs::rename(&retired_dir, directory);
+                    return Err(IndexerError::from(error).into());
+                }
+                Self::open_dir(directory, &Self::build_schema())?
+            }
+        };
+        // Uncommitted writes to the old index were replayed above.
+        *writer_slot = None;
+        *self.index.write() = index;
+        *self.reader.write() = reader;
+        {
+            let mut state = self.commit_state.lock();
+            state.pending_writes = 0;
+            state.oldest_pending = None;
+        }
+        self.recovering.store(false, AtomicOrdering::Relaxed);
+        drop(writer_slot);
+
+        if let Some(directory) = &self.directory {
+            let _ = std::fs::remove_dir_all(Self::sibling_dir(directory, RETIRED_DIR_SUFFIX));
+        }
+        Ok(())
+    }
+
+    /// Give up on a rebuild that failed before its swap.
+    pub(crate) fn abandon_rebuild(&self) {
+        *self.rebuild_journal.lock() = None;
+        if let Some(directory) = &self.directory {
+            let _ = std::fs::remove_dir_all(Self::sibling_dir(directory, REBUILD_DIR_SUFFIX));
+        }
+    }
+
     fn build_schema() -> Schema {
         let mut builder = Schema::builder();
         builder.add_text_field(
@@ -976,24 +1608,47 @@ impl Indexer {
         builder.add_i64_field("chunk_index", STORED);
         builder.add_i64_field("chunk_start", STORED);
         builder.add_i64_field("chunk_end", STORED);
+
+        // User tags, one canonical name per value. Every search unit of an
+        // item carries the item's tags.
+        builder.add_text_field(
+            "tags",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("raw")
+                    .set_index_option(IndexRecordOption::Basic),
+            ),
+        );
+
+        // Stored only: the item's `IndexedRow` as JSON, and its thumbnail.
+        builder.add_text_field("row", STORED);
+        builder.add_bytes_field("thumbnail", STORED);
+
+        // Set on the unit holding an image's recognized text, whose content
+        // is that text rather than the image description.
+        builder.add_bool_field("recognized_text", STORED);
         builder.build()
     }
 
     /// Register the custom analyzers with the index. Both fold diacritics so
     /// index terms agree with query-side `fold_str` (LowerCaser stays first:
-    /// `fold_char` on pre-lowercased text only strips marks).
+    /// folding pre-lowercased text only strips marks). The trigram analyzer
+    /// spells out ligatures before taking ngrams, since they fold to several
+    /// chars.
     /// NgramTokenizer assigns position=0 to all tokens, breaking PhraseQuery;
     /// IncrementPositionFilter fixes this by assigning incrementing positions.
     fn register_tokenizers(index: &Index) {
-        let trigram = TextAnalyzer::builder(NgramTokenizer::new(3, 3, false).unwrap())
-            .filter(LowerCaser)
-            .filter(DiacriticFoldFilter)
-            .filter(IncrementPositionFilter)
-            .build();
+        let trigram = TextAnalyzer::builder(CompatibilityExpansionTokenizer::new(
+            NgramTokenizer::new(3, 3, false).unwrap(),
+        ))
+        .filter(LowerCaser)
+        .filter(DiacriticFoldFilter)
+        .filter(IncrementPositionFilter)
+        .build();
         index.tokenizers().register("trigram", trigram);
 
-        // tantivy's "default" analyzer plus diacritic folding.
-        let words_folded = TextAnalyzer::builder(SimpleTokenizer::default())
+        // tantivy's "default" analyzer plus CJK splitting and diacritic folding.
+        let words_folded = TextAnalyzer::builder(WordTokenizer::default())
             .filter(RemoveLongFilter::limit(40))
             .filter(LowerCaser)
             .filter(DiacriticFoldFilter)
@@ -1001,6 +1656,7 @@ impl Indexer {
         index.tokenizers().register("words_folded", words_folded);
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn add_search_unit_document(
         &self,
         writer: &IndexWriter,
@@ -1008,8 +1664,14 @@ impl Indexer {
         content: &str,
         timestamp: i64,
         parent_len: usize,
-        chunk: Option<ChunkSlice>,
+        unit: SearchUnit,
+        tags: &[String],
+        row: Option<(&str, Option<&[u8]>)>,
     ) -> IndexerResult<()> {
+        let chunk = match unit {
+            SearchUnit::Chunk(chunk) => Some(chunk),
+            SearchUnit::WholeItem | SearchUnit::RecognizedText => None,
+        };
         let mut doc = tantivy::TantivyDocument::default();
         doc.add_text(self.item_id_field, item_id);
         doc.add_text(self.content_field, content);
@@ -1030,13 +1692,74 @@ impl Indexer {
                 .map(|chunk| chunk.end as i64)
                 .unwrap_or(parent_len as i64),
         );
+        for tag in tags {
+            doc.add_text(self.tags_field, tag);
+        }
+        if let Some((row, thumbnail)) = row {
+            doc.add_text(self.row_field, row);
+            if let Some(thumbnail) = thumbnail {
+                doc.add_bytes(self.thumbnail_field, thumbnail);
+            }
+        }
+        if matches!(unit, SearchUnit::RecognizedText) {
+            doc.add_bool(self.recognized_text_field, true);
+        }
         writer.add_document(doc)?;
         Ok(())
     }
 
     /// Add or update a document in the index
     pub fn add_document(&self, id: &str, content: &str, timestamp: i64) -> IndexerResult<()> {
-        self.with_writer(|writer| {
+        self.add_document_with_tags(id, content, timestamp, &[])
+    }
+
+    /// Add or update a document carrying user tags. Upserts replace the tags
+    /// too, so re-indexing a tagged item must pass them again.
+    pub fn add_document_with_tags(
+        &self,
+        id: &str,
+        content: &str,
+        timestamp: i64,
+        tags: &[String],
+    ) -> IndexerResult<()> {
+        self.add_search_units(id, content, timestamp, tags, None, None)
+    }
+
+    /// Add or update a document that also stores `row`, so index-only search
+    /// can render it. Documents added without one fall back to SQLite. The
+    /// row's recognized image text, if any, is indexed as a unit of its own.
+    pub(crate) fn add_document_with_row(
+        &self,
+        id: &str,
+        content: &str,
+        timestamp: i64,
+        tags: &[String],
+        row: &IndexedRow,
+    ) -> IndexerResult<()> {
+        let encoded = serde_json::to_string(row).expect("indexed rows always serialize");
+        self.add_search_units(
+            id,
+            content,
+            timestamp,
+            tags,
+            Some((&encoded, row.thumbnail.as_deref())),
+            row.ocr_text.as_deref(),
+        )
+    }
+
+    fn add_search_units(
+        &self,
+        id: &str,
+        content: &str,
+        timestamp: i64,
+        tags: &[String],
+        row: Option<(&str, Option<&[u8]>)>,
+        ocr_text: Option<&str>,
+    ) -> IndexerResult<()> {
+        self.write_documents(|writer| {
+            self.journal(|journal| {
+                journal.item_ids.insert(id.to_string());
+            });
             let parent_len = content.len();
 
             // Delete existing document with same ID (upsert semantics)
@@ -1051,35 +1774,268 @@ impl Indexer {
                         &content[chunk.start..chunk.end],
                         timestamp,
                         parent_len,
-                        Some(chunk),
+                        SearchUnit::Chunk(chunk),
+                        tags,
+                        row,
                     )?;
                 }
             } else {
-                self.add_search_unit_document(writer, id, content, timestamp, parent_len, None)?;
+                self.add_search_unit_document(
+                    writer,
+                    id,
+                    content,
+                    timestamp,
+                    parent_len,
+                    SearchUnit::WholeItem,
+                    tags,
+                    row,
+                )?;
+            }
+
+            if let Some(ocr_text) = ocr_text.filter(|text| !text.is_empty()) {
+                self.add_search_unit_document(
+                    writer,
+                    id,
+                    ocr_text,
+                    timestamp,
+                    ocr_text.len(),
+                    SearchUnit::RecognizedText,
+                    tags,
+                    row,
+                )?;
             }
 
             Ok(())
         })
     }
 
+    /// Finish a write. Under a deferred commit policy the commit waits until
+    /// enough writes are pending or the oldest has waited long enough.
     pub fn commit(&self) -> IndexerResult<()> {
+        if self.read_only {
+            return Err(IndexerError::ReadOnly);
+        }
+        if !self.commit_state.lock().is_due() {
+            return Ok(());
+        }
         self.close_writer(false)
     }
 
+    /// Commit pending writes, let merges finish, and delete the segment
+    /// files the index no longer uses.
+    pub fn collect_garbage(&self) -> IndexerResult<()> {
+        self.close_writer(true)?;
+        self.with_writer(|writer| {
+            writer.garbage_collect_files().wait()?;
+            Ok(())
+        })
+    }
+
+    /// Commit, merge every segment into one, and delete the files the merged
+    /// segments leave behind.
+    pub(crate) fn merge_segments(&self) -> IndexerResult<()> {
+        self.close_writer(true)?;
+        let segment_ids = self.index.read().searchable_segment_ids()?;
+        if segment_ids.len() > 1 {
+            self.with_writer(|writer| {
+                writer.merge(&segment_ids).wait()?;
+                Ok(())
+            })?;
+        }
+        self.collect_garbage()
+    }
+
+    /// Whether the index lives in memory only, with no directory on disk.
+    pub(crate) fn is_in_memory(&self) -> bool {
+        self.directory.is_none()
+    }
+
+    /// Size in bytes of the committed index files; 0 for an in-memory index.
+    pub(crate) fn disk_size(&self) -> IndexerResult<u64> {
+        match &self.directory {
+            Some(directory) => self.committed_size(directory),
+            None => Ok(0),
+        }
+    }
+
+    /// Commit any writes a deferred policy is holding back.
+    pub fn flush(&self) -> IndexerResult<()> {
+        if self.commit_state.lock().pending_writes == 0 {
+            return Ok(());
+        }
+        self.close_writer(false)
+    }
+
+    /// Stop (or resume) deferred commits from the flush thread. Explicit
+    /// commits still run.
+    pub(crate) fn set_commits_frozen(&self, frozen: bool) {
+        self.commit_state.lock().frozen = frozen;
+    }
+
+    /// Whether a rebuild has begun and not yet finished or been abandoned.
+    pub(crate) fn is_rebuilding(&self) -> bool {
+        self.rebuild_journal.lock().is_some()
+    }
+
+    /// Set when commits happen. A deferred policy starts a thread that
+    /// commits writes once the oldest has waited `max_delay_ms`; switching
+    /// back to immediate commits flushes what is pending.
+    pub(crate) fn set_commit_policy(
+        self: &Arc<Self>,
+        policy: IndexCommitPolicy,
+    ) -> IndexerResult<()> {
+        let generation = {
+            let mut state = self.commit_state.lock();
+            state.policy = policy.clone();
+            state.generation += 1;
+            state.generation
+        };
+        if policy.max_pending_writes <= 1 || policy.max_delay_ms == 0 {
+            return self.flush();
+        }
+        let indexer = Arc::downgrade(self);
+        let tick = Duration::from_millis(policy.max_delay_ms / 2).max(Duration::from_millis(10));
+        std::thread::Builder::new()
+            .name("purr-index-flush".into())
+            .spawn(move || Self::flush_when_due(indexer, generation, tick))?;
+        Ok(())
+    }
+
+    fn flush_when_due(indexer: Weak<Self>, generation: u64, tick: Duration) {
+        loop {
+            std::thread::sleep(tick);
+            let Some(indexer) = indexer.upgrade() else {
+                return;
+            };
+            let due = {
+                let state = indexer.commit_state.lock();
+                if state.generation != generation {
+                    return;
+                }
+                state.pending_writes > 0 && !state.frozen && state.is_due()
+            };
+            if due {
+                if let Err(error) = indexer.flush() {
+                    tracing::warn!(%error, "deferred index commit failed");
+                }
+            }
+        }
+    }
+
     pub fn prepare_for_suspend(&self) -> IndexerResult<()> {
         self.close_writer(true)
     }
 
     pub fn delete_document(&self, id: &str) -> IndexerResult<()> {
-        self.with_writer(|writer| {
+        self.write_documents(|writer| {
+            self.journal(|journal| {
+                journal.item_ids.insert(id.to_string());
+            });
             let id_term = tantivy::Term::from_field_text(self.item_id_field, id);
             writer.delete_term(id_term);
             Ok(())
         })
     }
 
+    /// Item IDs whose user tags satisfy `filter`. Tag names must be canonical.
+    pub(crate) fn item_ids_with_tags(&self, filter: &TagFilter) -> IndexerResult<HashSet<String>> {
+        let (names, occur) = match filter {
+            TagFilter::All { tags } => (tags, Occur::Must),
+            TagFilter::Any { tags } => (tags, Occur::Should),
+        };
+        let clauses: Vec<(Occur, Box<dyn tantivy::query::Query>)> = names
+            .iter()
+            .map(|name| {
+                let term = Term::from_field_text(self.tags_field, name);
+                let query: Box<dyn tantivy::query::Query> =
+                    Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+                (occur, query)
+            })
+            .collect();
+        if clauses.is_empty() {
+            return Ok(HashSet::new());
+        }
+        let searcher = self.reader.read().searcher();
+        let addresses = searcher.search(
+            &BooleanQuery::new(clauses),
+            &tantivy::collector::DocSetCollector,
+        )?;
+        let mut item_ids = HashSet::with_capacity(addresses.len());
+        for address in addresses {
+            let doc: tantivy::TantivyDocument = searcher.doc(address)?;
+            if let Some(item_id) = doc
+                .get_first(self.item_id_field)
+                .and_then(|value| value.as_str())
+            {
+                item_ids.insert(item_id.to_string());
+            }
+        }
+        Ok(item_ids)
+    }
+
+    /// The distinct item ID of every live document, snippets included.
+    pub(crate) fn indexed_item_ids(&self) -> IndexerResult<HashSet<String>> {
+        let searcher = self.reader.read().searcher();
+        let mut item_ids = HashSet::new();
+        for segment in searcher.segment_readers() {
+            let Some(column) = segment.fast_fields().str("item_id")? else {
+                continue;
+            };
+            let ords: HashSet<u64> = segment
+                .doc_ids_alive()
+                .flat_map(|doc| column.term_ords(doc))
+                .collect();
+            let mut item_id = String::new();
+            for ord in ords {
+                item_id.clear();
+                if column.ord_to_str(ord, &mut item_id)? {
+                    item_ids.insert(item_id.clone());
+                }
+            }
+        }
+        Ok(item_ids)
+    }
+
+    /// Stored rows for `item_ids`, keyed by item ID. Items indexed without a
+    /// row are left out.
+    pub(crate) fn fetch_indexed_rows(
+        &self,
+        item_ids: &[&str],
+    ) -> IndexerResult<HashMap<String, IndexedRow>> {
+        let searcher = self.reader.read().searcher();
+        let mut rows = HashMap::with_capacity(item_ids.len());
+        for &item_id in item_ids {
+            let term = Term::from_field_text(self.item_id_field, item_id);
+            let top = searcher.search(
+                &TermQuery::new(term, IndexRecordOption::Basic),
+                &tantivy::collector::TopDocs::with_limit(1),
+            )?;
+            let Some((_, address)) = top.into_iter().next() else {
+                continue;
+            };
+            let doc: tantivy::TantivyDocument = searcher.doc(address)?;
+            let Some(row) = doc
+                .get_first(self.row_field)
+                .and_then(|value| value.as_str())
+                .and_then(|json| serde_json::from_str::<IndexedRow>(json).ok())
+            else {
+                continue;
+            };
+            let thumbnail = doc
+                .get_first(self.thumbnail_field)
+                .and_then(|value| value.as_bytes())
+                .map(<[u8]>::to_vec);
+            rows.insert(item_id.to_string(), IndexedRow { thumbnail, ..row });
+        }
+        Ok(rows)
+    }
+
     pub fn delete_all_documents(&self) -> IndexerResult<()> {
-        self.with_writer(|writer| {
+        self.write_documents(|writer| {
+            self.journal(|journal| {
+                journal.item_ids.clear();
+                journal.cleared = true;
+            });
             writer.delete_all_documents()?;
             Ok(())
         })
@@ -1087,7 +2043,7 @@ impl Indexer {
 
     /// Tokenize text using the trigram tokenizer and return terms for the content field.
     fn trigram_terms(&self, text: &str) -> Vec<Term> {
-        let mut tokenizer = self.index.tokenizers().get("trigram").unwrap();
+        let mut tokenizer = self.index.read().tokenizers().get("trigram").unwrap();
         let mut stream = tokenizer.token_stream(text);
         let mut terms = Vec::new();
         while let Some(token) = stream.next() {
@@ -1138,13 +2094,103 @@ impl Indexer {
         query: &SearchQuery,
         limit: usize,
         token: &CancellationToken,
+    ) -> IndexerResult<Vec<SearchCandidate>> {
+        self.search_parsed_personalized(query, limit, token, None)
+    }
+
+    /// `search_parsed` with an optional personalization term in Phase 2.
+    pub(crate) fn search_parsed_personalized(
+        &self,
+        query: &SearchQuery,
+        limit: usize,
+        token: &CancellationToken,
+        personalize: Option<PersonalizationResolver<'_>>,
+    ) -> IndexerResult<Vec<SearchCandidate>> {
+        self.search_scoped(query, limit, token, personalize, DocumentScope::History)
+    }
+
+    /// Rank saved snippets against `query`. Candidate IDs keep their
+    /// `SNIPPET_ID_PREFIX`.
+    pub(crate) fn search_snippets(
+        &self,
+        query: &SearchQuery,
+        limit: usize,
+        token: &CancellationToken,
+    ) -> IndexerResult<Vec<SearchCandidate>> {
+        self.search_scoped(query, limit, token, None, DocumentScope::Snippets)
+    }
+
+    /// Search each `OR` branch of `query` in turn and interleave their
+    /// rankings, so every alternative gets places near the top.
+    fn search_scoped(
+        &self,
+        query: &SearchQuery,
+        limit: usize,
+        token: &CancellationToken,
+        personalize: Option<PersonalizationResolver<'_>>,
+        scope: DocumentScope,
+    ) -> IndexerResult<Vec<SearchCandidate>> {
+        let branches = &query.operators().branches;
+        if branches.is_empty() {
+            return self.search_recall_text(
+                query,
+                query.recall_text(),
+                limit,
+                token,
+                personalize,
+                scope,
+            );
+        }
+        let mut rankings = Vec::with_capacity(branches.len());
+        for branch in branches {
+            let ranking =
+                self.search_recall_text(query, branch, limit, token, personalize, scope)?;
+            rankings.push(ranking.into_iter());
+        }
+        let mut seen = HashSet::new();
+        let mut merged = Vec::new();
+        while merged.len() < limit {
+            let mut advanced = false;
+            for ranking in &mut rankings {
+                if let Some(candidate) = ranking.next() {
+                    advanced = true;
+                    if seen.insert(candidate.id.clone()) {
+                        merged.push(candidate);
+                    }
+                }
+            }
+            if !advanced {
+                break;
+            }
+        }
+        merged.truncate(limit);
+        Ok(merged)
+    }
+
+    fn search_recall_text(
+        &self,
+        query: &SearchQuery,
+        recall_text: &str,
+        limit: usize,
+        token: &CancellationToken,
+        personalize: Option<PersonalizationResolver<'_>>,
+        scope: DocumentScope,
     ) -> IndexerResult<Vec<SearchCandidate>> {
         #[cfg(feature = "perf-log")]
         let t0 = std::time::Instant::now();
-        let recall_text = query.recall_text();
         let prepared_query = PreparedQuery::new(recall_text);
         let phase_one_plan = self.plan_phase_one_query(&prepared_query);
-        let candidates = self.phase_one_recall(&phase_one_plan, limit)?;
+        let candidates = tracing::debug_span!(
+            "search.phase_one",
+            limit,
+            corpus_units = tracing::field::Empty,
+            recall_limit = tracing::field::Empty,
+            candidates = tracing::field::Empty,
+            saturated = tracing::field::Empty,
+            segments = tracing::field::Empty,
+            parallel = tracing::field::Empty,
+        )
+        .in_scope(|| self.phase_one_recall(&phase_one_plan, limit, scope, query.operators()))?;
         #[cfg(feature = "perf-log")]
         let t1 = std::time::Instant::now();
 
@@ -1166,17 +2212,26 @@ impl Indexer {
         #[cfg(test)]
         test_support::before_phase_two();
         let prefix_preference = prepare_prefix_preference(query);
+        let personalization = personalize.map(|resolve| {
+            let ids: Vec<&str> = candidates
+                .iter()
+                .map(|candidate| candidate.id.as_str())
+                .collect();
+            resolve(&ids)
+        });
         let phase_two_query = PhaseTwoQuery {
             query: &prepared_query,
             prefix_preference: prefix_preference
                 .as_ref()
                 .map(OwnedPrefixPreferenceQuery::as_borrowed),
+            personalization: personalization.as_ref(),
         };
         let now = Utc::now().timestamp();
         let phase_two_head = PhaseOneAdmissionPolicy::select_phase_two_head(&candidates);
         let head_indices: HashSet<usize> = phase_two_head.indices().iter().copied().collect();
         let PhaseTwoRun {
             mut scored,
+            matches: mut scored_matches,
             #[cfg(feature = "perf-log")]
                 perf: mut phase_two_perf,
         } = run_phase_two_head(phase_two_head, &candidates, phase_two_query, now, token)?;
@@ -1211,6 +2266,7 @@ impl Indexer {
         // Head rejects can only keep a bottom-of-tail slot, so they must not
         // drain the budget before genuine variants deeper in the tail are
         // verified.
+        let tail_span = tracing::debug_span!("search.tail_verify").entered();
         let tail_scan_order = (0..candidates.len())
             .filter(|index| !head_indices.contains(index))
             .chain((0..candidates.len()).filter(|index| head_indices.contains(index)));
@@ -1252,6 +2308,8 @@ impl Indexer {
             }
         }
 
+        drop(tail_span);
+
         // Rescue scoring: bucket-rank the top scan-verified tail candidates so
         // a fresh variant match lands where it would in a small history
         // instead of below every exact-word item. Rescued candidates Phase 2
@@ -1265,11 +2323,18 @@ impl Indexer {
                 token,
             )?;
             scored.extend(rescue_run.scored);
+            scored_matches.extend(rescue_run.matches);
             #[cfg(feature = "perf-log")]
             phase_two_perf.merge(rescue_run.perf);
         }
 
-        scored.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+        // Item ID breaks the remaining ties, so items with equal scores and
+        // timestamps, as when copied in the same second, keep one order from
+        // keystroke to keystroke.
+        scored.sort_unstable_by(|a, b| {
+            b.0.cmp(&a.0)
+                .then_with(|| candidates[a.1].id.cmp(&candidates[b.1].id))
+        });
         let scored_indices: HashSet<usize> = scored.iter().map(|(_, index)| *index).collect();
 
         #[cfg(feature = "perf-log")]
@@ -1310,6 +2375,15 @@ impl Indexer {
                 candidate.set_scoring_phase(crate::candidate::ScoringPhase::PhaseTwoScored);
             }
         }
+        let scored_query: std::sync::Arc<str> = recall_text.into();
+        for (index, tokens) in scored_matches {
+            if let Some(candidate) = candidate_slots[index].as_mut() {
+                candidate.set_scored_matches(ScoredWordMatches::new(
+                    std::sync::Arc::clone(&scored_query),
+                    tokens,
+                ));
+            }
+        }
         let mut ordered = Vec::new();
         ordered.extend(
             scored
@@ -1359,8 +2433,14 @@ impl Indexer {
             .get_first(self.chunk_index_field)
             .and_then(|value| value.as_i64())
             .unwrap_or(-1);
-
-        let match_context = if chunk_index >= 0 {
+        let recognized_text = doc
+            .get_first(self.recognized_text_field)
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+
+        let match_context = if recognized_text {
+            SearchMatchContext::RecognizedText(WholeItemMatchContext::new(content, parent_len))
+        } else if chunk_index >= 0 {
             let chunk_start = doc
                 .get_first(self.chunk_start_field)
                 .and_then(|value| value.as_i64())
@@ -1392,25 +2472,51 @@ impl Indexer {
     /// - adjacent word-sequence phrases over `content_words`
     ///
     /// Retrieves unit hits in increasing batches, then collapses them to one
-    /// candidate per parent item before Phase 2.
+    /// candidate per parent item before Phase 2. Batches deepen only until
+    /// they cover `recall_limit`, which scales with corpus size and with how
+    /// common the query's words are, and never exceeds `limit`. Large indexes
+    /// collect segments in parallel, each keeping only its budget of hits.
     fn phase_one_recall(
         &self,
         plan: &PhaseOneQueryPlan<'_>,
-        _limit: usize,
+        limit: usize,
+        scope: DocumentScope,
+        operators: &QueryOperators,
     ) -> IndexerResult<Vec<SearchCandidate>> {
         let reader = self.reader.read();
         let searcher = reader.searcher();
-        let final_query = self.build_phase_one_query(plan);
+        let final_query =
+            self.restrict_query(self.build_phase_one_query(plan), scope, operators)?;
         let now = Utc::now().timestamp();
+        let corpus_units = searcher.num_docs();
+        let common_word_units = plan
+            .word_field
+            .words
+            .iter()
+            .map(|word| {
+                let term = Term::from_field_text(self.content_words_field, &fold_str(word));
+                searcher.doc_freq(&term)
+            })
+            .try_fold(0u64, |max, doc_freq| {
+                doc_freq.map(|doc_freq| max.max(doc_freq))
+            })?;
+        let recall_limit = adaptive_recall_limit(corpus_units, common_word_units).min(limit);
+        let parallel = corpus_units >= PARALLEL_RECALL_MIN_UNITS;
         let mut collapsed = Vec::new();
+        let mut saturated = false;
 
         for raw_limit in RAW_RECALL_BATCHES {
             let top_collector = CollapsedTopDocs {
                 limit: raw_limit,
                 now,
+                segment_budget_units: parallel.then_some(corpus_units),
             };
 
-            let top_docs = searcher.search(final_query.as_ref(), &top_collector)?;
+            let top_docs = if parallel {
+                collect_segments_in_parallel(&searcher, final_query.as_ref(), &top_collector)?
+            } else {
+                searcher.search(final_query.as_ref(), &top_collector)?
+            };
             let last_score = top_docs.last().map(|hit| hit.score);
             let top_doc_count = top_docs.len();
             let mut batch_collapsed = Vec::with_capacity(top_doc_count);
@@ -1429,8 +2535,21 @@ impl Indexer {
             {
                 break;
             }
+            if raw_limit >= recall_limit {
+                // Deeper hits exist but the limit is spent. Recorded so traces
+                // show how often the adaptive limit, not the query, ends recall.
+                saturated = true;
+                break;
+            }
         }
 
+        let span = tracing::Span::current();
+        span.record("corpus_units", corpus_units);
+        span.record("recall_limit", recall_limit);
+        span.record("candidates", collapsed.len());
+        span.record("saturated", saturated);
+        span.record("segments", searcher.segment_readers().len());
+        span.record("parallel", parallel);
         Ok(collapsed)
     }
 
@@ -1799,6 +2918,61 @@ impl Indexer {
         }
     }
 
+    /// Restrict `query` to `scope`, to documents holding each quoted phrase,
+    /// and to documents without any excluded word or phrase, without
+    /// changing its scores.
+    fn restrict_query(
+        &self,
+        query: Box<dyn tantivy::query::Query>,
+        scope: DocumentScope,
+        operators: &QueryOperators,
+    ) -> IndexerResult<Box<dyn tantivy::query::Query>> {
+        let snippets =
+            RegexQuery::from_pattern(&format!("{SNIPPET_ID_PREFIX}.*"), self.item_id_field)?;
+        let occur = match scope {
+            DocumentScope::History => Occur::MustNot,
+            DocumentScope::Snippets => Occur::Must,
+        };
+        let mut clauses: Vec<(Occur, Box<dyn tantivy::query::Query>)> = vec![
+            (Occur::Must, query),
+            (
+                occur,
+                Box::new(ConstScoreQuery::new(Box::new(snippets), 0.0)),
+            ),
+        ];
+        for phrase in &operators.phrases {
+            if let Some(sequence) = self.word_sequence_query(phrase) {
+                clauses.push((Occur::Must, Box::new(ConstScoreQuery::new(sequence, 0.0))));
+            }
+        }
+        for excluded in &operators.excluded {
+            if let Some(sequence) = self.word_sequence_query(excluded) {
+                clauses.push((Occur::MustNot, sequence));
+            }
+        }
+        Ok(Box::new(BooleanQuery::new(clauses)))
+    }
+
+    /// Documents whose `content_words` hold `text`'s words in order, or
+    /// `None` when `text` has no words.
+    fn word_sequence_query(&self, text: &str) -> Option<Box<dyn tantivy::query::Query>> {
+        let mut tokenizer = self.index.read().tokenizers().get("words_folded")?;
+        let mut stream = tokenizer.token_stream(text);
+        let mut terms = Vec::new();
+        while let Some(token) = stream.next() {
+            let term = Term::from_field_text(self.content_words_field, &token.text);
+            terms.push((token.position, term));
+        }
+        match terms.len() {
+            0 => None,
+            1 => Some(Box::new(TermQuery::new(
+                terms.remove(0).1,
+                IndexRecordOption::Basic,
+            ))),
+            _ => Some(Box::new(PhraseQuery::new_with_offset(terms))),
+        }
+    }
+
     fn build_word_sequence_recall_query(
         &self,
         recall: &WordSequenceRecallPlan,
@@ -1956,19 +3130,86 @@ impl Indexer {
         // linger on disk until garbage collection removes them. Clearing history
         // must leave no recoverable plaintext, so force a GC after the commit.
         self.with_writer(|writer| {
+            self.journal(|journal| {
+                journal.item_ids.clear();
+                journal.cleared = true;
+            });
             writer.delete_all_documents()?;
             writer.commit()?;
             writer.garbage_collect_files().wait()?;
+            let mut state = self.commit_state.lock();
+            state.pending_writes = 0;
+            state.oldest_pending = None;
             Ok(())
         })?;
+        self.remove_pending_marker();
         self.reader.write().reload()?;
         Ok(())
     }
 
-    /// Get the number of documents in the index
+    /// Get the number of documents in the index as of the last commit
     pub fn num_docs(&self) -> u64 {
         self.reader.read().searcher().num_docs()
     }
+
+    /// Files making up the last commit, relative to the index directory.
+    /// `.managed.json` rides along so the copy's garbage collection still
+    /// knows which files it owns. Segments list every component they could
+    /// have, so files that were never written are skipped.
+    fn committed_files(&self, source: &Path) -> IndexerResult<Vec<PathBuf>> {
+        let mut files = vec![PathBuf::from("meta.json"), PathBuf::from(".managed.json")];
+        for segment in self.index.read().searchable_segment_metas()? {
+            files.extend(segment.list_files());
+        }
+        files.retain(|file| source.join(file).exists());
+        Ok(files)
+    }
+
+    /// Total size in bytes of the committed files in `source`, this index's
+    /// directory.
+    pub(crate) fn committed_size(&self, source: &Path) -> IndexerResult<u64> {
+        let mut total = 0;
+        for file in self.committed_files(source)? {
+            total += std::fs::metadata(source.join(file))?.len();
+        }
+        Ok(total)
+    }
+
+    /// Commit, then copy the committed files from `source`, this index's
+    /// directory, into `dest`. The writer slot stays locked during the copy
+    /// so no commit or merge changes the file set underneath it. `on_file`
+    /// receives each copied file's size.
+    pub(crate) fn copy_committed_to(
+        &self,
+        source: &Path,
+        dest: &Path,
+        mut on_file: impl FnMut(u64),
+    ) -> IndexerResult<()> {
+        self.close_writer(true)?;
+        let _writer_slot = self.writer.lock();
+        std::fs::create_dir_all(dest)?;
+        for file in self.committed_files(source)? {
+            on_file(std::fs::copy(source.join(&file), dest.join(&file))?);
+        }
+        Ok(())
+    }
+}
+
+/// Phase 1 recall depth for a corpus of `corpus_units` indexed units whose
+/// most common query word appears in `common_word_units` of them.
+///
+/// Small corpora and selective queries stop at `MIN_RECALL_LIMIT`: every
+/// relevant item already fits. Depth grows with corpus size, scaled by the
+/// square root of the common word's share, so only large histories searched
+/// with everyday words pay for the full `MAX_RECALL_LIMIT`.
+fn adaptive_recall_limit(corpus_units: u64, common_word_units: u64) -> usize {
+    if corpus_units == 0 {
+        return MIN_RECALL_LIMIT;
+    }
+    let corpus_scale = (corpus_units as f64 / LARGE_CORPUS_UNITS).min(1.0);
+    let word_share = (common_word_units as f64 / corpus_units as f64).min(1.0);
+    let extra = (MAX_RECALL_LIMIT - MIN_RECALL_LIMIT) as f64 * corpus_scale * word_share.sqrt();
+    MIN_RECALL_LIMIT + extra.round() as usize
 }
 
 #[cfg(test)]
@@ -2022,6 +3263,36 @@ mod tests {
         assert_eq!(results.len(), 1, "PhraseQuery should find exactly 1 doc");
     }
 
+    #[test]
+    fn test_equal_scores_order_by_item_id_on_every_search() {
+        let indexer = Indexer::new_in_memory().unwrap();
+        // Same content and timestamp, as with a burst of copies in one second,
+        // added out of ID order.
+        for n in [7, 2, 9, 0, 5, 3, 8, 1, 6, 4] {
+            indexer
+                .add_document(&format!("item-{n}"), "standup meeting notes", 1000)
+                .unwrap();
+        }
+        indexer.commit().unwrap();
+
+        let ids = |query: &str| -> Vec<String> {
+            indexer
+                .search(query, 100)
+                .unwrap()
+                .into_iter()
+                .map(|candidate| candidate.id)
+                .collect()
+        };
+        let first = ids("meeting notes");
+        let expected: Vec<String> = (0..10).map(|n| format!("item-{n}")).collect();
+        assert_eq!(first, expected);
+        for _ in 0..20 {
+            assert_eq!(ids("meeting notes"), first);
+        }
+        // A keystroke later the ties still fall the same way.
+        assert_eq!(ids("meeting note"), first);
+    }
+
     #[test]
     fn test_indexer_creation() {
         let indexer = Indexer::new_in_memory().unwrap();
@@ -2049,12 +3320,14 @@ mod tests {
         indexer.add_document("1", "hello world", 1000).unwrap();
         assert!(indexer
             .index
+            .read()
             .writer::<tantivy::TantivyDocument>(15_000_000)
             .is_err());
 
         indexer.prepare_for_suspend().unwrap();
         let external_writer = indexer
             .index
+            .read()
             .writer::<tantivy::TantivyDocument>(15_000_000)
             .unwrap();
         drop(external_writer);
@@ -2067,6 +3340,70 @@ mod tests {
         assert_eq!(results[0].id, "2");
     }
 
+    #[test]
+    fn test_deferred_commits_wait_for_pending_writes() {
+        let temp = tempfile::tempdir().unwrap();
+        let indexer = Arc::new(Indexer::new(temp.path()).unwrap());
+        indexer
+            .set_commit_policy(IndexCommitPolicy {
+                max_pending_writes: 3,
+                max_delay_ms: 3_600_000,
+            })
+            .unwrap();
+        let committed_docs = || indexer.reader.read().searcher().num_docs();
+        let marker = temp.path().join(PENDING_COMMIT_MARKER);
+
+        for i in 0..2 {
+            indexer
+                .add_document(&i.to_string(), "deferred", 1000)
+                .unwrap();
+            indexer.commit().unwrap();
+        }
+        assert_eq!(committed_docs(), 0);
+        assert!(marker.exists());
+        assert_eq!(
+            Indexer::inspect(temp.path()).unwrap(),
+            IndexInspection::RebuildRequired
+        );
+
+        indexer.add_document("2", "deferred", 1000).unwrap();
+        indexer.commit().unwrap();
+        assert_eq!(committed_docs(), 3);
+        assert!(!marker.exists());
+
+        // A write is marked pending before `commit` is called, and searches
+        // read the last commit without committing it.
+        indexer.add_document("3", "deferred", 1000).unwrap();
+        assert!(marker.exists());
+        indexer.commit().unwrap();
+        assert_eq!(indexer.search("deferred", 10).unwrap().len(), 3);
+        assert_eq!(indexer.num_docs(), 3);
+        assert!(marker.exists());
+        indexer.flush().unwrap();
+        assert_eq!(indexer.search("deferred", 10).unwrap().len(), 4);
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn test_deferred_commits_flush_after_max_delay() {
+        let indexer = Arc::new(Indexer::new_in_memory().unwrap());
+        indexer
+            .set_commit_policy(IndexCommitPolicy {
+                max_pending_writes: 100,
+                max_delay_ms: 20,
+            })
+            .unwrap();
+        indexer.add_document("1", "hello", 1000).unwrap();
+        indexer.commit().unwrap();
+        assert_eq!(indexer.reader.read().searcher().num_docs(), 0);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while indexer.reader.read().searcher().num_docs() == 0 {
+            assert!(Instant::now() < deadline, "deferred commit never ran");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
     #[test]
     fn test_upsert_semantics() {
         let indexer = Indexer::new_in_memory().unwrap();
@@ -2573,6 +3910,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn recall_limit_scales_with_corpus_size_and_word_commonness() {
+        assert_eq!(adaptive_recall_limit(0, 0), MIN_RECALL_LIMIT);
+        assert_eq!(adaptive_recall_limit(2_000, 2_000), MIN_RECALL_LIMIT + 54);
+        assert_eq!(adaptive_recall_limit(500_000, 0), MIN_RECALL_LIMIT);
+        assert_eq!(adaptive_recall_limit(500_000, 500_000), MAX_RECALL_LIMIT);
+        let rare = adaptive_recall_limit(100_000, 100);
+        let common = adaptive_recall_limit(100_000, 25_000);
+        assert!(
+            rare < common && common < MAX_RECALL_LIMIT,
+            "{rare} {common}"
+        );
+    }
+
+    #[test]
+    fn parallel_segment_recall_matches_sequential_pass() {
+        let indexer = Indexer::new_in_memory().unwrap();
+        let now = Utc::now().timestamp();
+        for segment in 0..4i64 {
+            for i in 0..100i64 {
+                indexer
+                    .add_document(
+                        &format!("s{segment}-{i}"),
+                        &format!("deploy log {segment} {i}"),
+                        now - (segment * 100 + i) * 60,
+                    )
+                    .unwrap();
+            }
+            indexer.commit().unwrap();
+        }
+        let reader = indexer.reader.read();
+        let searcher = reader.searcher();
+        assert!(searcher.segment_readers().len() > 1);
+        let prepared = PreparedQuery::new("deploy");
+        let query = indexer.build_phase_one_query(&indexer.plan_phase_one_query(&prepared));
+        let collector = |segment_budget_units| CollapsedTopDocs {
+            limit: 64,
+            now,
+            segment_budget_units,
+        };
+        let addresses = |hits: Vec<PhaseOneHit>| -> Vec<DocAddress> {
+            hits.into_iter().map(|hit| hit.doc_address).collect()
+        };
+
+        let sequential = searcher.search(query.as_ref(), &collector(None)).unwrap();
+        let parallel = collect_segments_in_parallel(
+            &searcher,
+            query.as_ref(),
+            &collector(Some(searcher.num_docs())),
+        )
+        .unwrap();
+        assert_eq!(sequential.len(), 64);
+        assert_eq!(addresses(sequential), addresses(parallel));
+
+        let budgets = CollapsedTopDocs {
+            limit: 4096,
+            now,
+            segment_budget_units: Some(500_000),
+        };
+        assert_eq!(budgets.segment_budget(250_000), 4096);
+        assert_eq!(budgets.segment_budget(50_000), 820);
+        assert_eq!(budgets.segment_budget(100), MIN_SEGMENT_BUDGET);
+        assert_eq!(collector(None).segment_budget(100), usize::MAX);
+    }
+
     #[test]
     fn repro_claim1_variant_dropped_with_300_exact_items() {
         // 300 exact-word items spread over 300 hours, plus a 5-minute-old
@@ -2871,7 +4273,14 @@ mod tests {
         // recall, or this test stops exercising the scan order at all.
         let prepared_query = PreparedQuery::new("man");
         let plan = indexer.plan_phase_one_query(&prepared_query);
-        let candidates = indexer.phase_one_recall(&plan, 500).unwrap();
+        let candidates = indexer
+            .phase_one_recall(
+                &plan,
+                500,
+                DocumentScope::History,
+                &QueryOperators::default(),
+            )
+            .unwrap();
         let noise_recalled = candidates
             .iter()
             .filter(|c| c.id.starts_with("noise-"))
@@ -2924,7 +4333,14 @@ mod tests {
 
         let prepared_query = PreparedQuery::new("man clip");
         let plan = indexer.plan_phase_one_query(&prepared_query);
-        let candidates = indexer.phase_one_recall(&plan, 50).unwrap();
+        let candidates = indexer
+            .phase_one_recall(
+                &plan,
+                50,
+                DocumentScope::History,
+                &QueryOperators::default(),
+            )
+            .unwrap();
         let recalled: Vec<&str> = candidates.iter().map(|c| c.id.as_str()).collect();
 
         assert!(recalled.contains(&"legit"), "true prefix match must recall");
@@ -3041,6 +4457,118 @@ mod tests {
         }
     }
 
+    #[test]
+    fn phrase_exclusion_and_or_operators_narrow_recall() {
+        let indexer = Indexer::new_in_memory().unwrap();
+        for (id, content) in [
+            ("compose", "docker-compose up --build"),
+            ("run", "docker run --rm alpine"),
+            ("podman", "podman run --rm alpine"),
+            ("phrase", "the exact phrase appears here"),
+            ("scattered", "phrase that is not exact"),
+        ] {
+            indexer.add_document(id, content, 1000).unwrap();
+        }
+        indexer.commit().unwrap();
+        let ids = |query: &str| {
+            let mut ids: Vec<String> = indexer
+                .search(query, 10)
+                .unwrap()
+                .into_iter()
+                .map(|candidate| candidate.id)
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(ids("docker -compose"), ["run"]);
+        assert_eq!(ids("\"exact phrase\""), ["phrase"]);
+        assert_eq!(ids("docker OR podman"), ["compose", "podman", "run"]);
+        assert_eq!(ids("docker OR podman -compose"), ["podman", "run"]);
+    }
+
+    #[test]
+    fn cjk_words_are_found_inside_unspaced_text() {
+        let indexer = Indexer::new_in_memory().unwrap();
+        for (id, content) in [
+            ("tower", "東京タワーの夜景がきれいでした"),
+            ("station", "東京駅で待ち合わせ"),
+            ("kyoto", "京都の紅葉"),
+        ] {
+            indexer.add_document(id, content, 1000).unwrap();
+        }
+        indexer.commit().unwrap();
+        let ids = |query: &str| {
+            let mut ids: Vec<String> = indexer
+                .search(query, 10)
+                .unwrap()
+                .into_iter()
+                .map(|candidate| candidate.id)
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(ids("東京"), ["station", "tower"]);
+        assert_eq!(ids("夜景"), ["tower"]);
+        assert_eq!(ids("東京タワー"), ["tower"]);
+        assert_eq!(ids("\"京都\""), ["kyoto"]);
+    }
+
+    #[test]
+    fn compatibility_forms_match_their_plain_letters() {
+        let indexer = Indexer::new_in_memory().unwrap();
+        for (id, content) in [
+            ("ligature", "the ﬁle was signed"),
+            ("fullwidth", "ＦＵＬＬＷＩＤＴＨ invoice"),
+            ("accent", "meet at the café"),
+        ] {
+            indexer.add_document(id, content, 1000).unwrap();
+        }
+        indexer.commit().unwrap();
+        let ids = |query: &str| -> Vec<String> {
+            indexer
+                .search(query, 10)
+                .unwrap()
+                .into_iter()
+                .map(|candidate| candidate.id)
+                .collect()
+        };
+
+        assert_eq!(ids("file was"), ["ligature"]);
+        assert_eq!(ids("ﬁle"), ["ligature"]);
+        assert_eq!(ids("fullwidth"), ["fullwidth"]);
+        assert_eq!(ids("cafe"), ["accent"]);
+    }
+
+    #[test]
+    fn camel_case_parts_are_words_of_their_own() {
+        let indexer = Indexer::new_in_memory().unwrap();
+        for (id, content) in [
+            ("camel", "let t = await refreshAccessToken(user);"),
+            ("snake", "refresh_token = load()"),
+            ("prose", "a token of thanks"),
+        ] {
+            indexer.add_document(id, content, 1000).unwrap();
+        }
+        indexer.commit().unwrap();
+        let ids = |query: &str| {
+            let mut ids: Vec<String> = indexer
+                .search(query, 10)
+                .unwrap()
+                .into_iter()
+                .map(|candidate| candidate.id)
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(ids("\"access token\""), ["camel"]);
+        assert_eq!(ids("\"refresh token\""), ["snake"]);
+        assert_eq!(ids("\"refreshAccessToken user\""), ["camel"]);
+        assert_eq!(ids("token -\"access token\""), ["prose", "snake"]);
+    }
+
     #[test]
     fn folded_match_counts_as_word_match_signal() {
         // Pins the encode_word_match_signals fold: a folded-exact hit must
@@ -17,6 +17,90 @@ pub enum IconType {
     Image,
     Color,
     File,
+    Code,
+    Document,
+    PhoneNumber,
+    PostalAddress,
+    Markdown,
+}
+
+/// Language family of a code snippet, used to pick a per-language code icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, uniffi::Enum)]
+pub enum CodeLanguage {
+    Rust,
+    Swift,
+    Python,
+    JavaScript,
+    Shell,
+    Sql,
+    Json,
+    Html,
+    CFamily,
+}
+
+/// Document kind of a file item, derived from its UTI (or extension as fallback).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, uniffi::Enum)]
+pub enum DocumentKind {
+    Pdf,
+    Image,
+    Audio,
+    Video,
+    Archive,
+    Spreadsheet,
+    Presentation,
+    WordProcessing,
+    PlainText,
+    SourceCode,
+    Folder,
+    Application,
+    Generic,
+}
+
+/// Broad kind of a file, derived from its UTI (or extension as fallback) and
+/// stored with it, so clients filter and group files without UTI tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, uniffi::Enum)]
+pub enum FileKind {
+    Document,
+    Image,
+    Video,
+    Audio,
+    Archive,
+    Code,
+    App,
+    Folder,
+    Other,
+}
+
+impl FileKind {
+    /// Key for this kind in the database and in `kind:` queries.
+    pub fn database_str(&self) -> &'static str {
+        match self {
+            FileKind::Document => "document",
+            FileKind::Image => "image",
+            FileKind::Video => "video",
+            FileKind::Audio => "audio",
+            FileKind::Archive => "archive",
+            FileKind::Code => "code",
+            FileKind::App => "app",
+            FileKind::Folder => "folder",
+            FileKind::Other => "other",
+        }
+    }
+
+    pub fn from_database_str(value: &str) -> Result<Self, String> {
+        match value {
+            "document" => Ok(FileKind::Document),
+            "image" => Ok(FileKind::Image),
+            "video" => Ok(FileKind::Video),
+            "audio" => Ok(FileKind::Audio),
+            "archive" => Ok(FileKind::Archive),
+            "code" => Ok(FileKind::Code),
+            "app" => Ok(FileKind::App),
+            "folder" => Ok(FileKind::Folder),
+            "other" => Ok(FileKind::Other),
+            other => Err(format!("unknown file kind `{other}`")),
+        }
+    }
 }
 
 /// File tracking status for clipboard file items
@@ -59,11 +143,12 @@ impl FileStatus {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, uniffi::Enum)]
 pub enum ContentTypeFilter {
     All,
-    Text,   // matches "text"
-    Images, // matches "image"
-    Links,  // matches "link"
-    Colors, // matches "color"
-    Files,  // matches "file"
+    Text,     // matches "text", "rich_text", and "markdown"
+    Images,   // matches "image"
+    Links,    // matches "link"
+    Colors,   // matches "color"
+    Files,    // matches "file"
+    Contacts, // matches "phone" and "address"
 }
 
 impl ContentTypeFilter {
@@ -71,11 +156,38 @@ impl ContentTypeFilter {
     pub fn database_types(&self) -> Option<&[&str]> {
         match self {
             ContentTypeFilter::All => None,
-            ContentTypeFilter::Text => Some(&["text"]),
+            ContentTypeFilter::Text => Some(&["text", "rich_text", "markdown"]),
             ContentTypeFilter::Images => Some(&["image"]),
             ContentTypeFilter::Links => Some(&["link"]),
             ContentTypeFilter::Colors => Some(&["color"]),
             ContentTypeFilter::Files => Some(&["file"]),
+            ContentTypeFilter::Contacts => Some(&["phone", "address"]),
+        }
+    }
+
+    /// Key for this filter in stored settings and rules.
+    pub fn database_str(&self) -> &'static str {
+        match self {
+            ContentTypeFilter::All => "all",
+            ContentTypeFilter::Text => "text",
+            ContentTypeFilter::Images => "images",
+            ContentTypeFilter::Links => "links",
+            ContentTypeFilter::Colors => "colors",
+            ContentTypeFilter::Files => "files",
+            ContentTypeFilter::Contacts => "contacts",
+        }
+    }
+
+    pub fn from_database_str(value: &str) -> Result<Self, String> {
+        match value {
+            "all" => Ok(ContentTypeFilter::All),
+            "text" => Ok(ContentTypeFilter::Text),
+            "images" => Ok(ContentTypeFilter::Images),
+            "links" => Ok(ContentTypeFilter::Links),
+            "colors" => Ok(ContentTypeFilter::Colors),
+            "files" => Ok(ContentTypeFilter::Files),
+            "contacts" => Ok(ContentTypeFilter::Contacts),
+            other => Err(format!("unknown content type filter `{other}`")),
         }
     }
 
@@ -120,20 +232,67 @@ pub enum ListPresentationProfile {
     Card,
 }
 
+/// User-tag filter for narrowing search results. Tag names match
+/// case-insensitively, with or without a leading `#`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Enum)]
+pub enum TagFilter {
+    /// Items carrying every listed tag.
+    All { tags: Vec<String> },
+    /// Items carrying at least one listed tag.
+    Any { tags: Vec<String> },
+}
+
 /// Mutually exclusive search filters for the browser.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, uniffi::Enum)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Enum)]
 pub enum ItemQueryFilter {
     All,
-    ContentType { content_type: ContentTypeFilter },
-    Tagged { tag: ItemTag },
+    ContentType {
+        content_type: ContentTypeFilter,
+    },
+    Tagged {
+        tag: ItemTag,
+    },
+    UserTagged {
+        filter: TagFilter,
+    },
+    /// Items copied from the app with this bundle ID.
+    SourceApp {
+        bundle_id: String,
+    },
 }
 
 /// Icon representation for list items
 #[derive(Debug, Clone, PartialEq, uniffi::Enum)]
 pub enum ItemIcon {
-    Symbol { icon_type: IconType },
-    ColorSwatch { rgba: u32 },
-    Thumbnail { bytes: Vec<u8> },
+    Symbol {
+        icon_type: IconType,
+    },
+    /// `palette` is a rendered PNG of every color for palettes and
+    /// gradients; `rgba` is then their first color.
+    ColorSwatch {
+        rgba: u32,
+        palette: Option<Vec<u8>>,
+    },
+    Thumbnail {
+        bytes: Vec<u8>,
+    },
+    Code {
+        language: CodeLanguage,
+    },
+    Document {
+        kind: DocumentKind,
+    },
+    /// Category symbol composited with the source app's icon as a badge.
+    AppBadged {
+        icon_type: IconType,
+        bundle_id: String,
+    },
+    /// Thumbnail bytes at this range of the shared thumbnail file, in place
+    /// of `Thumbnail` once `enable_shared_thumbnails` is on.
+    SharedThumbnail {
+        offset: u64,
+        length: u64,
+    },
 }
 
 impl Default for ItemIcon {
@@ -154,8 +313,12 @@ impl ItemIcon {
     ) -> Self {
         match db_type {
             "color" => {
+                // Palettes and gradients carry a rendered thumbnail.
                 if let Some(rgba) = color_rgba {
-                    ItemIcon::ColorSwatch { rgba }
+                    ItemIcon::ColorSwatch {
+                        rgba,
+                        palette: thumbnail,
+                    }
                 } else {
                     ItemIcon::Symbol {
                         icon_type: IconType::Color,
@@ -177,11 +340,32 @@ impl ItemIcon {
             "file" => ItemIcon::Symbol {
                 icon_type: IconType::File,
             },
+            "phone" => ItemIcon::Symbol {
+                icon_type: IconType::PhoneNumber,
+            },
+            "address" => ItemIcon::Symbol {
+                icon_type: IconType::PostalAddress,
+            },
+            "markdown" => ItemIcon::Symbol {
+                icon_type: IconType::Markdown,
+            },
             _ => ItemIcon::Symbol {
                 icon_type: IconType::Text,
             },
         }
     }
+
+    /// The symbol category this icon falls back to when a client cannot
+    /// render the richer representation.
+    pub fn base_icon_type(&self) -> IconType {
+        match self {
+            ItemIcon::Symbol { icon_type } | ItemIcon::AppBadged { icon_type, .. } => *icon_type,
+            ItemIcon::ColorSwatch { .. } => IconType::Color,
+            ItemIcon::Thumbnail { .. } | ItemIcon::SharedThumbnail { .. } => IconType::Image,
+            ItemIcon::Code { .. } => IconType::Code,
+            ItemIcon::Document { .. } => IconType::Document,
+        }
+    }
 }
 
 /// Legal payloads for a successful link metadata fetch.
@@ -414,6 +598,82 @@ pub struct FileEntry {
     pub preview: FilePreviewSnapshot,
 }
 
+/// Duration, codec, and dimensions of an audio or video file. Fields the
+/// file does not state, like dimensions of audio, are None.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct MediaInfo {
+    pub duration_ms: Option<u64>,
+    pub codec: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// Media info for one file of a file item.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct FileMediaInfo {
+    /// Position of the file in the item's `files`.
+    pub file_index: u64,
+    pub info: MediaInfo,
+    /// The info as one metadata line, like "1:35 · HEVC · 1920×1080".
+    pub summary: String,
+}
+
+/// Page count, title, and author of a PDF. Fields the document does not
+/// state are None.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct DocumentInfo {
+    pub page_count: Option<u32>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+}
+
+/// Document info for one file of a file item.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct FileDocumentInfo {
+    /// Position of the file in the item's `files`.
+    pub file_index: u64,
+    pub info: DocumentInfo,
+    /// The info as one metadata line, like "Lease Agreement · 12 pages".
+    pub summary: String,
+}
+
+/// Subject, sender, and send time of an exported email. Fields the message
+/// does not state are None.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct EmailInfo {
+    pub subject: Option<String>,
+    /// Display name and address, as in "Jane Doe <jane@example.com>".
+    pub sender: Option<String>,
+    pub sent_at_unix: Option<i64>,
+}
+
+/// Email info for one file of a file item.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct FileEmailInfo {
+    /// Position of the file in the item's `files`.
+    pub file_index: u64,
+    pub info: EmailInfo,
+    /// Subject and sender as one metadata line, like "Lease renewal · Jane Doe".
+    pub summary: String,
+}
+
+/// Entry names of a zip or tar archive, without directories. Listings stop
+/// at a fixed number of entries.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct ArchiveListing {
+    pub entries: Vec<String>,
+    /// Whether the archive has more entries than `entries` lists.
+    pub truncated: bool,
+}
+
+/// Archive listing for one file of a file item.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct FileArchiveListing {
+    /// Position of the file in the item's `files`.
+    pub file_index: u64,
+    pub listing: ArchiveListing,
+}
+
 /// Type-safe clipboard content representation
 #[derive(Debug, Clone, PartialEq, uniffi::Enum)]
 pub enum ClipboardContent {
@@ -436,6 +696,30 @@ pub enum ClipboardContent {
         display_name: String,
         files: Vec<FileEntry>,
     },
+    /// Formatted text as copied from apps like Pages or Safari. `value` is
+    /// the extracted plain text, which search, snippets, and previews use;
+    /// the original RTF and HTML are kept for pasting back with formatting.
+    RichText {
+        value: String,
+        rtf_data: Option<Vec<u8>>,
+        html: Option<String>,
+    },
+    /// A copied phone number, as written.
+    PhoneNumber {
+        value: String,
+    },
+    /// A copied postal address, as written, one or more lines.
+    PostalAddress {
+        value: String,
+    },
+    /// Markdown as copied from editors and chat apps. `value` is a plain-text
+    /// render without heading markers, emphasis, or link targets, which
+    /// search, snippets, and previews use; `source` is the Markdown itself,
+    /// kept for pasting back.
+    Markdown {
+        value: String,
+        source: String,
+    },
 }
 
 impl ClipboardContent {
@@ -447,6 +731,10 @@ impl ClipboardContent {
             ClipboardContent::Link { url, .. } => url,
             ClipboardContent::Image { description, .. } => description,
             ClipboardContent::File { display_name, .. } => display_name,
+            ClipboardContent::RichText { value, .. } => value,
+            ClipboardContent::PhoneNumber { value } => value,
+            ClipboardContent::PostalAddress { value } => value,
+            ClipboardContent::Markdown { value, .. } => value,
         }
     }
 
@@ -458,6 +746,10 @@ impl ClipboardContent {
             ClipboardContent::Link { .. } => IconType::Link,
             ClipboardContent::Image { .. } => IconType::Image,
             ClipboardContent::File { .. } => IconType::File,
+            ClipboardContent::RichText { .. } => IconType::Text,
+            ClipboardContent::PhoneNumber { .. } => IconType::PhoneNumber,
+            ClipboardContent::PostalAddress { .. } => IconType::PostalAddress,
+            ClipboardContent::Markdown { .. } => IconType::Markdown,
         }
     }
 
@@ -469,6 +761,10 @@ impl ClipboardContent {
             ClipboardContent::Link { .. } => "link",
             ClipboardContent::Image { .. } => "image",
             ClipboardContent::File { .. } => "file",
+            ClipboardContent::RichText { .. } => "rich_text",
+            ClipboardContent::PhoneNumber { .. } => "phone",
+            ClipboardContent::PostalAddress { .. } => "address",
+            ClipboardContent::Markdown { .. } => "markdown",
         }
     }
 }
@@ -629,6 +925,29 @@ pub enum MatchedExcerptResolution {
     },
 }
 
+/// A resolved excerpt expressed as changes from the excerpt shown for an
+/// earlier query, so typing one more character sends little per row.
+#[derive(Debug, Clone, PartialEq, uniffi::Enum)]
+pub enum MatchedExcerptPatch {
+    /// The excerpt text is unchanged: drop `removed` from the previous
+    /// highlights and insert `added`, keeping them ordered by start.
+    Highlights {
+        item_id: String,
+        removed: Vec<Utf16HighlightRange>,
+        added: Vec<Utf16HighlightRange>,
+        line_number: u64,
+    },
+    /// The excerpt text changed, or no excerpt is known for the earlier query.
+    Replaced {
+        item_id: String,
+        excerpt: MatchedExcerpt,
+    },
+    Unavailable {
+        item_id: String,
+        reason: ExcerptUnavailableReason,
+    },
+}
+
 /// Preview-only highlight decoration for the full item content.
 #[derive(Debug, Clone, PartialEq, Default, uniffi::Record)]
 pub struct PreviewDecoration {
@@ -644,6 +963,20 @@ pub struct PreviewPayload {
     pub decoration: Option<PreviewDecoration>,
 }
 
+/// A file in a file item that a query matched, so a grouped item can be
+/// expanded straight to it.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct FileMatch {
+    /// Position of the file in the item's `files`.
+    pub file_index: u64,
+    pub filename: String,
+    pub path: String,
+    /// Highlight ranges into `filename`.
+    pub filename_highlights: Vec<Utf16HighlightRange>,
+    /// Highlight ranges into `path`.
+    pub path_highlights: Vec<Utf16HighlightRange>,
+}
+
 /// Lightweight item metadata for list display
 #[derive(Debug, Clone, PartialEq, uniffi::Record)]
 pub struct ItemMetadata {
@@ -653,6 +986,8 @@ pub struct ItemMetadata {
     pub source_app_bundle_id: Option<String>,
     pub timestamp_unix: i64,
     pub tags: Vec<ItemTag>,
+    /// Pinned items list first and are never pruned.
+    pub is_pinned: bool,
 }
 
 /// Search match: metadata + match context
@@ -671,13 +1006,153 @@ pub struct SearchResult {
     pub first_preview_payload: Option<PreviewPayload>,
 }
 
+/// Where a section of `search_all_sources` results comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum SearchSource {
+    Pinned,
+    Bookmarked,
+    History,
+}
+
+/// Most matches returned per section of `search_all_sources`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Record)]
+pub struct SearchSectionLimits {
+    pub snippets: u32,
+    pub pinned: u32,
+    pub bookmarked: u32,
+    pub history: u32,
+}
+
+/// One section of `search_all_sources` results, ranked on its own.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct SearchSection {
+    pub source: SearchSource,
+    pub matches: Vec<ItemMatch>,
+    /// Matches in this section before its limit applied.
+    pub total_count: u64,
+}
+
+/// Search results grouped by source. Sections come in `SearchSource`
+/// order, empty ones are left out, and each item appears in one section.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct SectionedSearchResult {
+    /// Saved snippets matching the query, listed above every section.
+    pub snippets: Vec<Snippet>,
+    pub sections: Vec<SearchSection>,
+}
+
+/// One page of search results.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct SearchPage {
+    pub matches: Vec<ItemMatch>,
+    /// Results across all pages. For text queries this counts ranked
+    /// candidates before filters apply, so filtered searches can end sooner.
+    pub total_count: u64,
+    /// Opaque cursor for the next page; None on the last page.
+    pub next_cursor: Option<String>,
+    /// Preview payload for the first match, on the first page only.
+    pub first_preview_payload: Option<PreviewPayload>,
+}
+
+/// Order of a structured search's results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, uniffi::Enum)]
+pub enum SearchSort {
+    /// Best matches first, as typed searches rank.
+    Relevance,
+    /// Most recently copied first.
+    Newest,
+}
+
+/// A search assembled field by field, as a filter UI builds one. Values are
+/// taken as written, so unlike query text nothing needs quoting: a `-`, `"`,
+/// `#`, or `OR` in a value is just text. Without terms or phrases it lists
+/// recent items, as an empty query does.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct QueryBuilder {
+    /// Words to match, each as if typed.
+    pub terms: Vec<String>,
+    /// Phrases whose words must appear together, in order.
+    pub phrases: Vec<String>,
+    /// Words or phrases that rule an item out.
+    pub excluded: Vec<String>,
+    pub filter: ItemQueryFilter,
+    pub sort: SearchSort,
+    /// The previous page's `next_cursor`; None for the first page.
+    pub cursor: Option<String>,
+    pub page_size: u64,
+}
+
+/// Result of `collect_matches`.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct CollectedMatches {
+    /// The text item holding the collected excerpts; empty when nothing
+    /// matched. Collecting the same matches again returns the same item.
+    pub item_id: String,
+    pub match_count: u64,
+}
+
+/// How a term of typed query text is searched for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, uniffi::Enum)]
+pub enum QueryTermKind {
+    /// A word to match.
+    Word,
+    /// A quoted phrase whose words must appear together, in order.
+    Phrase,
+    /// A `-` word or phrase that rules an item out.
+    Excluded,
+}
+
+/// One term of typed query text, with quotes and escapes resolved.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Record)]
+pub struct ParsedQueryTerm {
+    pub kind: QueryTermKind,
+    pub value: String,
+    /// Joined to the term before it by `OR`, so either may match.
+    pub or_previous: bool,
+}
+
+/// Typed query text as search reads it (see `parse_query`). A backslash
+/// keeps a following `"`, `-`, `^`, `#`, or `\` literal, and `\OR` is the
+/// word OR rather than the operator. `#name` words and a `kind:` word are
+/// filters unless quoted or escaped; other colons, as in `app: config`, are
+/// plain text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Record)]
+pub struct ParsedQuery {
+    /// The query starts with `^`, so prefix matches rank first.
+    pub prefer_prefix: bool,
+    pub terms: Vec<ParsedQueryTerm>,
+    /// Canonical names of `#name` words. Search treats a name no tag has
+    /// as text.
+    pub tags: Vec<String>,
+    /// Kind named by the last `kind:` word naming a known kind.
+    pub file_kind: Option<FileKind>,
+}
+
 /// Terminal outcome for an explicit search operation.
+// UniFFI lowers variant fields by value, so the result can't be boxed.
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, PartialEq, uniffi::Enum)]
 pub enum SearchOutcome {
     Success { result: SearchResult },
     Cancelled,
 }
 
+/// `SearchResult` with its matches packed into one buffer, for the hot
+/// search path. The layout is documented in `packed_results.rs`.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct PackedSearchResult {
+    pub packed_matches: Vec<u8>,
+    pub total_count: u64,
+    pub first_preview_payload: Option<PreviewPayload>,
+}
+
+/// Terminal outcome for a search awaited as a packed result.
+#[derive(Debug, Clone, PartialEq, uniffi::Enum)]
+pub enum PackedSearchOutcome {
+    Success { result: PackedSearchResult },
+    Cancelled,
+}
+
 /// Explicit bootstrap plan for opening the store.
 #[derive(Debug, Clone, PartialEq, Eq, uniffi::Enum)]
 pub enum StoreBootstrapPlan {
@@ -692,6 +1167,786 @@ pub struct ClipboardItem {
     pub content: ClipboardContent,
 }
 
+/// Image item for grid browsing: a medium thumbnail and dimensions, never the full blob.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct ImageGridItem {
+    pub item_id: String,
+    pub timestamp_unix: i64,
+    pub thumbnail: Option<Vec<u8>>,
+    /// Original pixel dimensions; None when the format cannot be decoded in Rust (e.g. HEIC).
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub is_animated: bool,
+}
+
+/// One page of the image grid.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct ImageGridPage {
+    pub items: Vec<ImageGridItem>,
+    /// Total number of image items, independent of paging.
+    pub total_count: u64,
+    /// Opaque cursor for the next page; None on the last page.
+    pub next_cursor: Option<String>,
+}
+
+/// Condition of a smart collection. An item belongs to the collection when it
+/// satisfies every rule.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Enum)]
+pub enum SmartCollectionRule {
+    ContentType {
+        content_type: ContentTypeFilter,
+    },
+    /// Copied from any of the given apps.
+    SourceApps {
+        bundle_ids: Vec<String>,
+    },
+    /// Content contains the text, ignoring ASCII case.
+    ContainsText {
+        text: String,
+    },
+    /// Text item that looks like source code.
+    ContainsCode,
+    Tagged {
+        tag: ItemTag,
+    },
+}
+
+/// Persisted rule-backed query. Membership is resolved when browsed, never stored.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct SmartCollection {
+    pub collection_id: String,
+    pub name: String,
+    pub rules: Vec<SmartCollectionRule>,
+}
+
+/// Smart collection with its current item count.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct SmartCollectionSummary {
+    pub collection: SmartCollection,
+    pub item_count: u64,
+}
+
+/// One page of a smart collection, newest first.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct SmartCollectionPage {
+    pub matches: Vec<ItemMatch>,
+    pub total_count: u64,
+}
+
+/// A group of related items found by clustering history, e.g. "Kubernetes".
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct TopicSummary {
+    /// Stable while the topic survives maintenance; a full regrouping
+    /// issues new IDs.
+    pub topic_id: String,
+    /// The topic's most characteristic words, e.g. "Apartment rent".
+    pub label: String,
+    pub item_count: u64,
+}
+
+/// One page of a topic's items, newest first.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct TopicPage {
+    pub matches: Vec<ItemMatch>,
+    pub total_count: u64,
+}
+
+/// Edit operation of a diff line or span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// Run of text within a diff line sharing one operation.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct DiffSpan {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+/// One line of a diff. Edited lines carry word-level spans; other lines have a
+/// single span covering the whole line.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct DiffLine {
+    pub op: DiffOp,
+    /// 1-based line number in the first item; None for inserted lines.
+    pub old_line_number: Option<u32>,
+    /// 1-based line number in the second item; None for deleted lines.
+    pub new_line_number: Option<u32>,
+    pub spans: Vec<DiffSpan>,
+}
+
+/// Line and word diff between two text items.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct ItemDiff {
+    pub lines: Vec<DiffLine>,
+    pub insertions: u32,
+    pub deletions: u32,
+    pub identical: bool,
+}
+
+/// An item that stayed in a result list but changed position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Record)]
+pub struct ResultMove {
+    /// Index in the previous list.
+    pub from: u64,
+    /// Index in the current list.
+    pub to: u64,
+}
+
+/// Batch update turning one result list into the next, in the shape list
+/// views apply: `removed` indexes the previous list, `inserted` the current
+/// one, and each list is ascending. Items in neither stay put relative to
+/// each other.
+#[derive(Debug, Clone, PartialEq, Eq, Default, uniffi::Record)]
+pub struct ResultDiff {
+    pub removed: Vec<u64>,
+    pub inserted: Vec<u64>,
+    /// Ordered by `to`.
+    pub moved: Vec<ResultMove>,
+}
+
+/// Structured-data format `format_structured` recognizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum StructuredFormat {
+    Json,
+    Xml,
+    Yaml,
+}
+
+/// Layout `format_structured` produces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum StructuredStyle {
+    /// One value or element per line, indented two spaces per level.
+    Pretty,
+    /// Insignificant whitespace removed. YAML keeps its lines, since they
+    /// carry its structure, and drops blank lines and comments.
+    Minified,
+}
+
+/// A structured-data item laid out for display.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct FormattedStructure {
+    pub format: StructuredFormat,
+    pub text: String,
+}
+
+/// Built-in rewrite `transform` applies to an item's text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum Transformation {
+    /// Blank space around the text and at the end of each line removed.
+    TrimWhitespace,
+    /// Rich text and Markdown reduced to the plain text lists show.
+    StripFormatting,
+    Uppercase,
+    Lowercase,
+    /// Each word's first letter uppercased and the rest lowercased.
+    TitleCase,
+    /// `%XX` escapes decoded.
+    UrlDecode,
+    /// Standard or URL-safe base64 decoded, when it decodes to text.
+    Base64Decode,
+    /// JSON pretty-printed as `format_structured` does.
+    JsonPrettify,
+}
+
+impl Transformation {
+    pub fn to_database_str(&self) -> &'static str {
+        match self {
+            Transformation::TrimWhitespace => "trim_whitespace",
+            Transformation::StripFormatting => "strip_formatting",
+            Transformation::Uppercase => "uppercase",
+            Transformation::Lowercase => "lowercase",
+            Transformation::TitleCase => "title_case",
+            Transformation::UrlDecode => "url_decode",
+            Transformation::Base64Decode => "base64_decode",
+            Transformation::JsonPrettify => "json_prettify",
+        }
+    }
+
+    pub fn from_database_str(value: &str) -> Result<Self, String> {
+        match value {
+            "trim_whitespace" => Ok(Transformation::TrimWhitespace),
+            "strip_formatting" => Ok(Transformation::StripFormatting),
+            "uppercase" => Ok(Transformation::Uppercase),
+            "lowercase" => Ok(Transformation::Lowercase),
+            "title_case" => Ok(Transformation::TitleCase),
+            "url_decode" => Ok(Transformation::UrlDecode),
+            "base64_decode" => Ok(Transformation::Base64Decode),
+            "json_prettify" => Ok(Transformation::JsonPrettify),
+            other => Err(format!("unknown transformation `{other}`")),
+        }
+    }
+}
+
+/// One use of `transform` on an item, from `last_transforms`.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct AppliedTransform {
+    pub transformation: Transformation,
+    /// Paste target set with `set_paste_target` when it was applied.
+    pub target_bundle_id: Option<String>,
+    pub applied_at_unix: i64,
+}
+
+/// Result of `transform`.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct TransformedText {
+    pub text: String,
+    /// The item `text` was saved as, when the caller asked to save it.
+    pub saved_item_id: Option<String>,
+}
+
+/// Kind of value `detect_entities` finds inside an item's text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, uniffi::Enum)]
+pub enum EntityKind {
+    Url,
+    Email,
+    Color,
+    PhoneNumber,
+    FilePath,
+    IpAddress,
+}
+
+impl EntityKind {
+    pub fn database_str(&self) -> &'static str {
+        match self {
+            EntityKind::Url => "url",
+            EntityKind::Email => "email",
+            EntityKind::Color => "color",
+            EntityKind::PhoneNumber => "phone",
+            EntityKind::FilePath => "path",
+            EntityKind::IpAddress => "ip",
+        }
+    }
+
+    pub fn from_database_str(value: &str) -> Result<Self, String> {
+        match value {
+            "url" => Ok(EntityKind::Url),
+            "email" => Ok(EntityKind::Email),
+            "color" => Ok(EntityKind::Color),
+            "phone" => Ok(EntityKind::PhoneNumber),
+            "path" => Ok(EntityKind::FilePath),
+            "ip" => Ok(EntityKind::IpAddress),
+            other => Err(format!("unknown entity kind `{other}`")),
+        }
+    }
+}
+
+/// A URL, email address, color, or similar value found in an item's text,
+/// so the UI can offer to copy just that part. The range is in UTF-16 code
+/// units of the item's text; `value` is the text it covers.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct DetectedEntity {
+    pub kind: EntityKind,
+    pub value: String,
+    pub utf16_start: u64,
+    pub utf16_end: u64,
+}
+
+/// Kind of variable span a template placeholder replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum TemplatePlaceholderKind {
+    Email,
+    Date,
+    Number,
+    Uuid,
+}
+
+/// Placeholder in a snippet template, with the value it replaced so the
+/// snippet editor can offer it as the default.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct TemplatePlaceholder {
+    /// Name used in the body as `{name}`, e.g. `email` or `number_2`.
+    pub name: String,
+    pub kind: TemplatePlaceholderKind,
+    pub original: String,
+}
+
+/// Snippet template derived from a history item. Literal braces in `body` are
+/// doubled (`{{`, `}}`) to keep them apart from placeholders.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct SnippetTemplate {
+    pub body: String,
+    pub placeholders: Vec<TemplatePlaceholder>,
+}
+
+/// Saved snippet, kept apart from history. The body uses template syntax:
+/// `{name}` placeholders, with literal braces doubled.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct Snippet {
+    pub snippet_id: String,
+    pub title: String,
+    pub body: String,
+    /// Placeholder names in the body, in order of first use.
+    pub placeholders: Vec<String>,
+    pub updated_at_unix: i64,
+}
+
+/// Optional subsystems compiled into the core, from `capabilities()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Record)]
+pub struct Capabilities {
+    /// Payload encryption at rest, unlocked with a host-held key.
+    pub encryption: bool,
+    /// Semantic search over embeddings; not part of this core yet.
+    pub embeddings: bool,
+    /// Text recognized in images is indexed and searchable.
+    pub ocr_indexing: bool,
+    /// The `sync` feature: CloudKit sync calls are exported.
+    pub sync: bool,
+}
+
+/// Value for a named placeholder when expanding a snippet.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct SnippetVariable {
+    pub name: String,
+    pub value: String,
+}
+
+/// Storage condition reported to the `StoreObserver`.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Enum)]
+pub enum StorageWarning {
+    /// Free space fell below the threshold. Image capture is paused and older
+    /// history was pruned until space recovers.
+    LowDiskSpace {
+        available_bytes: u64,
+        threshold_bytes: u64,
+    },
+    /// Free space recovered; normal capture resumed.
+    DiskSpaceRecovered { available_bytes: u64 },
+    /// A write failed because the volume is full.
+    DiskFull,
+}
+
+/// Panic contained at an FFI entry point, for attaching to crash reports.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct InternalErrorRecord {
+    pub occurred_at_unix: i64,
+    /// Entry point that panicked, e.g. `save_text`.
+    pub operation: String,
+    /// Panic message and source location.
+    pub message: String,
+    pub backtrace: String,
+}
+
+/// Step of `move_store`, reported to a `StoreMoveProgress`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum StoreMoveStage {
+    CopyingDatabase,
+    CopyingIndex,
+    /// Checking the copies before they replace anything.
+    Verifying,
+    /// Copies verified and in place; the old files go when the store is dropped.
+    Finished,
+}
+
+/// A single-file backup written by `create_backup`.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct BackupInfo {
+    pub path: String,
+    pub created_at_unix: i64,
+    /// Items in the backup, including those in the trash.
+    pub item_count: u64,
+    /// Size of the backup file.
+    pub bytes: u64,
+}
+
+/// Result of `export_bundle`.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct BundleExportSummary {
+    pub item_count: u64,
+    /// Distinct binary payloads written to `attachments/`.
+    pub attachment_count: u64,
+    /// Bytes written, excluding the manifest.
+    pub total_bytes: u64,
+}
+
+/// Result of `import_bundle`.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct BundleImportSummary {
+    pub imported: u64,
+    /// Items skipped because the store already had their ID or content.
+    pub duplicates: u64,
+    /// Items skipped because the store has their ID with different content;
+    /// the local copy is kept.
+    pub conflicts: u64,
+}
+
+/// How `import_from` treats items the store already has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum ImportStrategy {
+    /// Only add new items; the local copy of anything else is left as is.
+    KeepLocal,
+    /// Also move duplicates to the newer of the two timestamps, and add
+    /// conflicting items under a fresh ID so neither version is lost.
+    Merge,
+}
+
+/// Result of `import_from`.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct DatabaseImportSummary {
+    /// Items added, including conflicting items `Merge` added under a new ID.
+    pub imported: u64,
+    /// Items the store already had by ID or content.
+    pub duplicates: u64,
+    /// Items whose ID the store has with different content.
+    pub conflicts: u64,
+}
+
+/// Dry run of an import: what it would do, without touching the store.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct ImportPreview {
+    /// Items the store doesn't have yet.
+    pub new_items: u64,
+    /// Items whose ID or content the store already has.
+    pub duplicates: u64,
+    /// Items whose ID the store has with different content, plus items the
+    /// import would delete.
+    pub conflicts: u64,
+    /// Approximate bytes the new items add to the database, excluding the
+    /// search index.
+    pub estimated_growth_bytes: u64,
+}
+
+/// Half-open time range `[start_unix, end_unix)` in Unix seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Record)]
+pub struct TimeRange {
+    pub start_unix: i64,
+    pub end_unix: i64,
+}
+
+/// Copy counts per local day and hour, for an activity heatmap.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct ActivityHeatmap {
+    /// One row per day, oldest first and ending today.
+    pub days: Vec<HeatmapDay>,
+    /// Largest single cell, for scaling colors.
+    pub max_count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct HeatmapDay {
+    /// Local date as `YYYY-MM-DD`.
+    pub date: String,
+    /// Items copied in each hour of the day, 24 entries starting at midnight.
+    pub hour_counts: Vec<u32>,
+}
+
+/// Usage totals for a statistics view, from `get_stats`.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct UsageStats {
+    pub total_items: u64,
+    /// Logged copies, counting duplicates copied again.
+    pub total_copies: u64,
+    pub total_pastes: u64,
+    /// Most items first.
+    pub by_content_type: Vec<ContentTypeUsage>,
+    /// Apps with at least one item, most items first.
+    pub by_source_app: Vec<SourceAppUsage>,
+    /// Local days, oldest first, ending today; empty days included.
+    pub by_day: Vec<DailyUsage>,
+    /// Most reused items: most pasted first, then most recently pasted.
+    pub most_pasted: Vec<ItemPasteStats>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct ContentTypeUsage {
+    /// Database content type ("text", "link", ...).
+    pub content_type: String,
+    pub item_count: u64,
+    /// Pastes of items of this type.
+    pub paste_count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct SourceAppUsage {
+    pub bundle_id: Option<String>,
+    pub name: Option<String>,
+    pub item_count: u64,
+    /// Pastes of items copied from this app.
+    pub paste_count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct DailyUsage {
+    /// Local date as `YYYY-MM-DD`.
+    pub date: String,
+    pub copy_count: u64,
+    pub paste_count: u64,
+}
+
+/// How often one item has been pasted, anywhere.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct ItemPasteStats {
+    pub item_id: String,
+    pub paste_count: u64,
+    pub last_pasted_at_unix: i64,
+}
+
+/// End-of-day recap of one local day's items, for `daily_digest`.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct DailyDigest {
+    /// Local date as `YYYY-MM-DD`.
+    pub date: String,
+    /// Items last copied that day.
+    pub item_count: u64,
+    pub image_count: u64,
+    /// Links copied that day, most copied first.
+    pub notable_links: Vec<DigestLink>,
+    /// App the most items were copied from, if any item has one.
+    pub top_source_app: Option<DigestApp>,
+    /// Colors copied that day for the first time.
+    pub new_colors: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct DigestLink {
+    pub item_id: String,
+    pub url: String,
+    /// Page title, once link metadata has loaded.
+    pub title: Option<String>,
+    /// Times the link was copied that day.
+    pub copy_count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct DigestApp {
+    pub bundle_id: Option<String>,
+    /// Registered display name, else the captured name.
+    pub name: Option<String>,
+    pub item_count: u64,
+}
+
+/// A typed preference value.
+#[derive(Debug, Clone, PartialEq, uniffi::Enum)]
+pub enum PrefValue {
+    Bool { value: bool },
+    Int { value: i64 },
+    Double { value: f64 },
+    Text { value: String },
+    Data { value: Vec<u8> },
+}
+
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct PrefEntry {
+    pub key: String,
+    pub value: PrefValue,
+}
+
+/// Display metadata for a source app, registered by the host app.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct AppMetadata {
+    pub bundle_id: String,
+    pub display_name: String,
+    /// Hash of the app icon, for the host's icon cache.
+    pub icon_hash: Option<String>,
+    /// App Store category, such as `public.app-category.developer-tools`.
+    pub category: Option<String>,
+    /// False once the app has been uninstalled; its entry is kept so
+    /// existing items still show a name.
+    pub is_installed: bool,
+}
+
+/// A source app and how many items were copied from it, for the app filter.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct SourceAppSummary {
+    /// None for apps captured by name only.
+    pub bundle_id: Option<String>,
+    /// Registered display name, else the latest captured name.
+    pub name: Option<String>,
+    pub item_count: u64,
+}
+
+/// A user tag and how many items carry it.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct TagSummary {
+    pub name: String,
+    pub item_count: u64,
+}
+
+/// File format written by `export_history`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum HistoryExportFormat {
+    /// One JSON array of item objects.
+    Json,
+    /// RFC 4180 CSV with a header row.
+    Csv,
+}
+
+/// Result of `export_history`.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct HistoryExportSummary {
+    pub item_count: u64,
+    pub bytes_written: u64,
+}
+
+/// An item whose reminder has come due.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct ItemReminder {
+    pub item_id: String,
+    pub fire_at_unix: i64,
+}
+
+/// How long history is kept, enforced by `run_maintenance`. Pinned and
+/// immutable items are always kept and count toward `max_item_count`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, uniffi::Record)]
+pub struct RetentionPolicy {
+    /// Age past which items are deleted, unless a content type rule says
+    /// otherwise.
+    pub max_age_seconds: Option<u64>,
+    /// Newest items to keep; older ones are deleted.
+    pub max_item_count: Option<u64>,
+    /// Ages for particular content types, e.g. images expiring sooner than
+    /// text. At most one rule per type, and not for `All`.
+    pub content_type_rules: Vec<ContentTypeRetention>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct ContentTypeRetention {
+    pub content_type: ContentTypeFilter,
+    pub max_age_seconds: u64,
+}
+
+/// What `run_maintenance` did.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct MaintenanceReport {
+    /// Items deleted under the retention policy, and trashed items deleted
+    /// for good after 30 days in the trash.
+    pub items_deleted: u64,
+    /// How much smaller compaction made the database file.
+    pub bytes_reclaimed: u64,
+}
+
+/// What `run_integrity_check` found. Nothing is repaired.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct IntegrityReport {
+    /// SQLite's description of each problem with the database file; empty
+    /// when the file is sound.
+    pub problems: Vec<String>,
+    /// Items whose content is missing, so they cannot be shown or pasted.
+    pub corrupted_item_ids: Vec<String>,
+    /// Link rows left behind by deleted items.
+    pub orphaned_link_rows: u64,
+    /// Image rows left behind by deleted items, often the bulk of the waste.
+    pub orphaned_image_rows: u64,
+}
+
+/// What `compact` did, with sizes in bytes.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct CompactReport {
+    pub database_bytes_before: u64,
+    pub database_bytes_after: u64,
+    pub index_bytes_before: u64,
+    pub index_bytes_after: u64,
+    /// Orphaned link and image rows deleted.
+    pub orphaned_rows_deleted: u64,
+}
+
+/// What `verify_index` repaired.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct IndexRepairReport {
+    /// Documents of items deleted or trashed in the database, which showed
+    /// up as phantom search results.
+    pub ghost_documents_removed: u64,
+    /// Items in the database that search could not find.
+    pub missing_documents_added: u64,
+}
+
+/// One row of the menu bar quick list.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct QuickListItem {
+    pub item_id: String,
+    /// Single-line preview, whitespace collapsed.
+    pub preview: String,
+    pub icon_type: IconType,
+    pub timestamp_unix: i64,
+    /// Short age at the time of the call: "now", "5m", "3h", "2d", "6w", "1y".
+    pub age: String,
+    pub is_pinned: bool,
+}
+
+/// Result of `quick_list`. Rows only change when `change_sequence` does;
+/// poll `change_sequence` to know when to ask again.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct QuickList {
+    pub change_sequence: u64,
+    pub items: Vec<QuickListItem>,
+}
+
+/// An item in the trash, waiting for `restore_item` or `empty_trash`.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct TrashedItem {
+    pub item: ClipboardItem,
+    pub deleted_at_unix: i64,
+}
+
+/// Entry of the recently-pasted list: an item with its latest paste.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct RecentPaste {
+    pub item_match: ItemMatch,
+    pub pasted_at_unix: i64,
+    /// App the item was most recently pasted into.
+    pub target_bundle_id: String,
+    /// Times the item has been pasted anywhere.
+    pub paste_count: u64,
+}
+
+/// When the search index commits writes. Each save is one write. A commit
+/// happens once `max_pending_writes` writes are pending or the oldest has
+/// waited `max_delay_ms`. Searches read the last commit, so under a deferred
+/// policy results can lag saves by up to `max_delay_ms`. The default commits
+/// every write.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct IndexCommitPolicy {
+    pub max_pending_writes: u32,
+    pub max_delay_ms: u64,
+}
+
+impl Default for IndexCommitPolicy {
+    fn default() -> Self {
+        Self {
+            max_pending_writes: 1,
+            max_delay_ms: 0,
+        }
+    }
+}
+
+/// One text to save with `save_texts_batch`.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct TextInput {
+    pub text: String,
+    pub source_app: Option<String>,
+    pub source_app_bundle_id: Option<String>,
+}
+
+/// One time an item was copied, including copies deduplicated into it.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct CopyOccurrence {
+    pub copied_at_unix: i64,
+    /// Registered display name, else the captured name.
+    pub source_app: Option<String>,
+    pub source_app_bundle_id: Option<String>,
+}
+
+/// Number of pastes into an app that shared one content type or source app.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct PasteTally {
+    pub value: String,
+    pub paste_count: u64,
+}
+
+/// Personalization priors learned for one paste target app.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct AppPastePriors {
+    pub target_bundle_id: String,
+    pub total_pastes: u64,
+    /// Database content types ("text", "link", ...), most pasted first.
+    pub content_types: Vec<PasteTally>,
+    /// Source app bundle IDs, most pasted first.
+    pub source_apps: Vec<PasteTally>,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // SYNC TYPES (exposed to Swift for SyncEngine)
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -826,12 +2081,56 @@ pub enum ClipKittyError {
     InvalidInput(String),
     #[error("Operation cancelled")]
     Cancelled,
+    #[error("Not enough disk space")]
+    StorageFull,
+    #[error("Internal error: {0}")]
+    Internal(String),
+    #[error("Store is in use on another device: {0}")]
+    StoreInUse(String),
+    #[error("Item is immutable: {0}")]
+    ItemImmutable(String),
+    #[error("Store is open read-only")]
+    ReadOnly,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // SERVICE INTERFACE
 // ═══════════════════════════════════════════════════════════════════════════════
 
+/// Callbacks from the store to the app, registered with `set_observer`.
+/// Called on the thread that triggered the event; implementations should
+/// hop to the main thread before touching UI. Item events let every window
+/// showing history stay current without searching again; changes applied
+/// from sync or imports are not reported item by item.
+#[uniffi::export(with_foreign)]
+pub trait StoreObserver: Send + Sync {
+    fn on_storage_warning(&self, warning: StorageWarning);
+    /// A capture or restore added an item to history.
+    fn on_item_added(&self, item_id: String);
+    /// An item's content, metadata, tags, pin, or timestamp changed, as when
+    /// it is copied again.
+    fn on_item_updated(&self, item_id: String);
+    /// An item left history, by deletion, clearing, pruning, or expiry.
+    fn on_item_deleted(&self, item_id: String);
+}
+
+/// Progress callbacks for `rebuild_index_in_background`, called on the
+/// rebuilding thread.
+#[uniffi::export(with_foreign)]
+pub trait IndexRebuildObserver: Send + Sync {
+    /// Called after each batch of items is indexed, starting from zero.
+    fn on_rebuild_progress(&self, indexed_items: u64, total_items: u64, percent: u8);
+    /// Called once the rebuilt index is serving searches, or with the error
+    /// that stopped the rebuild; the previous index then keeps serving.
+    fn on_rebuild_complete(&self, error: Option<String>);
+}
+
+/// Progress callback for `move_store`, called on the moving thread.
+#[uniffi::export(with_foreign)]
+pub trait StoreMoveProgress: Send + Sync {
+    fn on_move_progress(&self, stage: StoreMoveStage, copied_bytes: u64, total_bytes: u64);
+}
+
 /// The primary interface for accessing the Clipboard ClipboardStore.
 /// This matches the functionality exposed by the `ClipboardStore` object.
 #[uniffi::export(with_foreign)]
@@ -856,12 +2155,66 @@ pub trait ClipboardStoreApi: Send + Sync {
         presentation: ListPresentationProfile,
     ) -> Result<SearchResult, ClipKittyError>;
 
+    /// Search one page at a time: pass None for the first page, then each
+    /// page's `next_cursor`. Only the returned page is read from SQLite.
+    async fn search_page(
+        &self,
+        query: String,
+        filter: ItemQueryFilter,
+        cursor: Option<String>,
+        page_size: u64,
+        presentation: ListPresentationProfile,
+    ) -> Result<SearchPage, ClipKittyError>;
+
+    /// `search_page` for a `QueryBuilder` instead of query text.
+    async fn search_structured(
+        &self,
+        query: QueryBuilder,
+        presentation: ListPresentationProfile,
+    ) -> Result<SearchPage, ClipKittyError>;
+
+    /// Gather every item matching `query` and `filter` into one new text
+    /// item: an excerpt around each match, in ranked order, each followed by
+    /// its source app, capture time, and external ID. Nothing is saved when
+    /// nothing matches.
+    async fn collect_matches(
+        &self,
+        query: String,
+        filter: ItemQueryFilter,
+    ) -> Result<CollectedMatches, ClipKittyError>;
+
+    /// Search only items copied from the app with `bundle_id`.
+    async fn search_in_app(
+        &self,
+        query: String,
+        bundle_id: String,
+        presentation: ListPresentationProfile,
+    ) -> Result<SearchResult, ClipKittyError>;
+
+    /// Search pinned items, bookmarks, and history at once, grouped into
+    /// sections with their own limits, so one query surfaces a saved item
+    /// next to matching history. Cancels the in-flight search like `search`.
+    async fn search_all_sources(
+        &self,
+        query: String,
+        limits: SearchSectionLimits,
+        presentation: ListPresentationProfile,
+    ) -> Result<SectionedSearchResult, ClipKittyError>;
+
     /// Resolve deferred matched excerpts for visible rows.
     fn resolve_matched_excerpts(
         &self,
         requests: Vec<MatchedExcerptRequest>,
     ) -> Result<Vec<MatchedExcerptResolution>, ClipKittyError>;
 
+    /// Like `resolve_matched_excerpts`, but as patches against the excerpts
+    /// already resolved for `previous_query`, for visible rows while typing.
+    fn resolve_matched_excerpt_patches(
+        &self,
+        previous_query: String,
+        requests: Vec<MatchedExcerptRequest>,
+    ) -> Result<Vec<MatchedExcerptPatch>, ClipKittyError>;
+
     /// Load the preview payload for a single item given the search query.
     fn load_preview_payload(
         &self,
@@ -875,11 +2228,22 @@ pub trait ClipboardStoreApi: Send + Sync {
     /// Get the database size in bytes
     fn database_size(&self) -> i64;
 
+    /// Write the items matching `filter` to the file at `path` as JSON or
+    /// CSV, newest first, with timestamp, source app, and content type.
+    /// Items are read in batches, so memory stays flat for large histories.
+    fn export_history(
+        &self,
+        path: String,
+        format: HistoryExportFormat,
+        filter: ItemQueryFilter,
+    ) -> Result<HistoryExportSummary, ClipKittyError>;
+
     // ─────────────────────────────────────────────────────────────────────────────
     // Write Operations
     // ─────────────────────────────────────────────────────────────────────────────
 
-    /// Save a text item. Returns new item's stable ID, or empty string if duplicate.
+    /// Save a text item. Returns new item's stable ID, or empty string if duplicate
+    /// or copied from an excluded app (see `set_excluded_bundle_ids`).
     fn save_text(
         &self,
         text: String,
@@ -887,6 +2251,23 @@ pub trait ClipboardStoreApi: Send + Sync {
         source_app_bundle_id: Option<String>,
     ) -> Result<String, ClipKittyError>;
 
+    /// Save many texts at once, in one transaction with a single index
+    /// commit. Returns one ID per input, in order, following `save_text`:
+    /// empty for duplicates and for texts copied from excluded apps.
+    fn save_texts_batch(&self, inputs: Vec<TextInput>) -> Result<Vec<String>, ClipKittyError>;
+
+    /// Save formatted text with its original RTF and/or HTML. `plain` is what
+    /// search and previews use. Without RTF or HTML this saves plain text.
+    /// Returns the new item's stable ID, or empty string as for `save_text`.
+    fn save_rich_text(
+        &self,
+        plain: String,
+        rtf_data: Option<Vec<u8>>,
+        html: Option<String>,
+        source_app: Option<String>,
+        source_app_bundle_id: Option<String>,
+    ) -> Result<String, ClipKittyError>;
+
     /// Save an image item. Thumbnail should be generated by Swift (HEIC not supported by Rust).
     fn save_image(
         &self,
@@ -941,6 +2322,10 @@ pub trait ClipboardStoreApi: Send + Sync {
         description: String,
     ) -> Result<(), ClipKittyError>;
 
+    /// Store text recognized in an image (e.g. by Vision) and re-index it.
+    /// It is searchable apart from the description; blank text clears it.
+    fn update_image_ocr_text(&self, item_id: String, text: String) -> Result<(), ClipKittyError>;
+
     /// Update text item content in-place and re-index
     fn update_text_item(&self, item_id: String, text: String) -> Result<(), ClipKittyError>;
 
@@ -957,7 +2342,9 @@ pub trait ClipboardStoreApi: Send + Sync {
     // Delete Operations
     // ─────────────────────────────────────────────────────────────────────────────
 
-    /// Delete an item by ID from both database and index
+    /// Move an item to the trash: it leaves search and listings but can be
+    /// brought back with `restore_item` until `empty_trash` purges it.
+    /// The deletion syncs right away; a restored item syncs as a new one.
     fn delete_item(&self, item_id: String) -> Result<(), ClipKittyError>;
 
     /// Clear all items from database and index
@@ -974,6 +2361,16 @@ impl From<crate::database::DatabaseError> for ClipKittyError {
             crate::database::DatabaseError::InconsistentData(message) => {
                 ClipKittyError::DataInconsistency(message)
             }
+            crate::database::DatabaseError::Sqlite(rusqlite::Error::SqliteFailure(error, _))
+                if error.code == rusqlite::ErrorCode::DiskFull =>
+            {
+                ClipKittyError::StorageFull
+            }
+            crate::database::DatabaseError::Sqlite(rusqlite::Error::SqliteFailure(error, _))
+                if error.code == rusqlite::ErrorCode::ReadOnly =>
+            {
+                ClipKittyError::ReadOnly
+            }
             other => ClipKittyError::DatabaseError(other.to_string()),
         }
     }
@@ -981,7 +2378,10 @@ impl From<crate::database::DatabaseError> for ClipKittyError {
 
 impl From<crate::indexer::IndexerError> for ClipKittyError {
     fn from(e: crate::indexer::IndexerError) -> Self {
-        ClipKittyError::IndexError(e.to_string())
+        match e {
+            crate::indexer::IndexerError::ReadOnly => ClipKittyError::ReadOnly,
+            other => ClipKittyError::IndexError(other.to_string()),
+        }
     }
 }
 
@@ -0,0 +1,38 @@
+//! Locales items were written for.
+//!
+//! Demo and screenshot databases hold the same sample items in several
+//! languages, each tagged with its locale. Browse and search show untagged
+//! items and those tagged for the preferred locale or a broader one, so one
+//! database serves every language. Locales are BCP 47 or Apple identifiers
+//! and compare in lowercase with hyphens ("pt_BR" is "pt-br").
+
+use crate::database::Database;
+use crate::interface::ClipKittyError;
+
+const LOCALE_NAMESPACE: &str = "display";
+const PREFERRED_LOCALE_KEY: &str = "preferred_locale";
+
+/// `locale` lowercased, with hyphens and without Apple's `@` keywords, or
+/// `None` when blank.
+pub(crate) fn normalize(locale: &str) -> Option<String> {
+    let base = locale.split('@').next().unwrap_or_default().trim();
+    let normalized = base.replace('_', "-").to_ascii_lowercase();
+    (!normalized.is_empty()).then_some(normalized)
+}
+
+/// Set or clear the locale browse and search show tagged items for.
+pub(crate) fn set_preferred(db: &Database, locale: Option<&str>) -> Result<(), ClipKittyError> {
+    match locale.and_then(normalize) {
+        Some(locale) => {
+            db.put_key_value(LOCALE_NAMESPACE, PREFERRED_LOCALE_KEY, locale.as_bytes())?
+        }
+        None => db.delete_key_value(LOCALE_NAMESPACE, PREFERRED_LOCALE_KEY)?,
+    }
+    Ok(())
+}
+
+pub(crate) fn preferred(db: &Database) -> Result<Option<String>, ClipKittyError> {
+    Ok(db
+        .get_key_value(LOCALE_NAMESPACE, PREFERRED_LOCALE_KEY)?
+        .and_then(|bytes| String::from_utf8(bytes).ok()))
+}
@@ -5,25 +5,78 @@
 //!
 //! Types are exported via UniFFI proc-macros (#[derive(uniffi::Record/Enum)]).
 
+mod accessibility;
+mod activity;
+mod app_metadata;
+mod archive_info;
+mod backup;
 pub mod benchmark_fixture;
+mod browse_service;
+mod bundle;
 pub(crate) mod candidate;
+mod collection_service;
+mod compat;
 pub mod content_detection;
+mod context_service;
 pub mod database;
+mod database_import;
+pub(crate) mod diff;
+mod digest;
+mod disk_space;
+mod email_info;
+mod encryption;
+mod exclusions;
+mod file_kind;
+mod history_export;
+pub(crate) mod icon_service;
+mod import_preview;
 pub mod indexer;
 pub mod interface;
+mod item_locale;
+mod markdown;
+mod match_collection;
 pub(crate) mod match_presentation;
+mod media_info;
 pub mod models;
+mod packed_results;
+mod panic_guard;
+mod pdf_info;
+pub(crate) mod power;
+mod preferences;
+mod quick_list;
 pub mod ranking;
+mod relocation;
+mod reminders;
+mod repeat_copies;
+mod result_diff;
+mod retention;
 mod save_service;
 pub mod search;
 pub(crate) mod search_admission;
 mod search_result_builder;
 mod search_service;
+mod seed;
+mod sensitive_items;
+mod session;
+mod snippets;
 mod store;
+mod structured_data;
 #[cfg(feature = "sync")]
 pub(crate) mod sync_bridge;
+mod synced_folder;
+pub(crate) mod template;
+pub(crate) mod thumbnail;
+mod thumbnail_cache;
+mod time_format;
+mod topics;
+mod trace_export;
+mod transform;
+mod user_tags;
 
+pub use compat::{capabilities, interface_version, is_interface_version_supported};
 pub use interface::*;
+pub use search::{format_query, parse_query};
 pub use store::{inspect_store_bootstrap, ClipboardStore, SearchOperation};
+pub use time_format::{format_absolute_time, format_relative_time};
 
 uniffi::setup_scaffolding!("purr");
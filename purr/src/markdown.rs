@@ -0,0 +1,294 @@
+//! Markdown recognized on capture and rendered to plain text.
+//!
+//! The render is what lists, snippets, and search see, so `# Title` reads as
+//! "Title" and `[docs](https://…)` as "docs". Code inside fences and
+//! backticks is kept as written; only Markdown's own syntax is removed.
+
+/// Distinct kinds of Markdown syntax needed before text counts as Markdown;
+/// one heading-like `# ` line or a bare list is as likely to be a shell
+/// comment or notes.
+const MIN_MARKDOWN_SIGNALS: usize = 2;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Signal {
+    Heading,
+    List,
+    Fence,
+    Quote,
+    Inline,
+}
+
+/// Whether `text` is Markdown: several lines with at least two kinds of
+/// block or inline syntax among headings, list items, fenced code, block
+/// quotes, and links or strong emphasis.
+pub(crate) fn is_markdown(text: &str) -> bool {
+    let trimmed = text.trim();
+    if !trimmed.contains('\n') {
+        return false;
+    }
+    let mut signals: Vec<Signal> = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut fence_closed = false;
+    for line in trimmed.lines() {
+        let line = line.trim_start();
+        if let Some(open) = fence {
+            if line.starts_with(open) {
+                fence = None;
+                fence_closed = true;
+            }
+            continue;
+        }
+        if let Some(marker) = fence_marker(line) {
+            fence = Some(marker);
+            continue;
+        }
+        let signal = if heading_text(line).is_some() {
+            Some(Signal::Heading)
+        } else if list_marker_len(line).is_some() {
+            Some(Signal::List)
+        } else if line.starts_with("> ") {
+            Some(Signal::Quote)
+        } else if has_inline_syntax(line) {
+            Some(Signal::Inline)
+        } else {
+            None
+        };
+        if let Some(signal) = signal.filter(|signal| !signals.contains(signal)) {
+            signals.push(signal);
+        }
+    }
+    if fence_closed {
+        signals.push(Signal::Fence);
+    }
+    signals.len() >= MIN_MARKDOWN_SIGNALS
+}
+
+/// `text` as plain text: heading markers, quote markers, emphasis, link
+/// targets, and code fences removed; list items kept one per line with a
+/// bullet; everything in code kept verbatim.
+pub(crate) fn render_plain_text(text: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut fence: Option<&str> = None;
+    for raw in text.trim().lines() {
+        let line = raw.trim_start();
+        if let Some(open) = fence {
+            if line.starts_with(open) {
+                fence = None;
+            } else {
+                lines.push(raw.to_string());
+            }
+            continue;
+        }
+        if let Some(marker) = fence_marker(line) {
+            fence = Some(marker);
+            continue;
+        }
+        if is_rule(line) {
+            continue;
+        }
+        let mut line = line;
+        while let Some(rest) = line.strip_prefix('>') {
+            line = rest.trim_start();
+        }
+        let rendered = if let Some(heading) = heading_text(line) {
+            render_inline(heading)
+        } else if let Some(len) = list_marker_len(line) {
+            let marker = line[..len].trim_end();
+            let item = render_inline(&line[len..]);
+            if marker.ends_with(['.', ')']) {
+                format!("{marker} {item}")
+            } else {
+                format!("• {item}")
+            }
+        } else {
+            render_inline(line)
+        };
+        lines.push(rendered);
+    }
+    lines.join("\n")
+}
+
+/// The fence a line opens, "```" or "~~~".
+fn fence_marker(line: &str) -> Option<&'static str> {
+    ["```", "~~~"]
+        .into_iter()
+        .find(|marker| line.starts_with(marker))
+}
+
+/// The text of an ATX heading (`## Title ##` gives "Title").
+fn heading_text(line: &str) -> Option<&str> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = line[level..].strip_prefix(' ')?;
+    let text = rest.trim().trim_end_matches('#').trim_end();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Length of a list item's marker and the space after it: `- `, `* `, `+ `,
+/// `1. `, or `1) `.
+fn list_marker_len(line: &str) -> Option<usize> {
+    if ["- ", "* ", "+ "]
+        .iter()
+        .any(|marker| line.starts_with(marker))
+    {
+        return Some(2);
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if (1..=9).contains(&digits) {
+        let rest = &line[digits..];
+        if rest.starts_with(". ") || rest.starts_with(") ") {
+            return Some(digits + 2);
+        }
+    }
+    None
+}
+
+/// A thematic break: three or more `-`, `*`, or `_` alone on a line.
+fn is_rule(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|&mark| compact.chars().all(|c| c == mark))
+}
+
+fn has_inline_syntax(line: &str) -> bool {
+    (line.contains("](") && line.contains('['))
+        || render_emphasis(line, "**") != line
+        || render_emphasis(line, "__") != line
+}
+
+/// Render inline syntax outside code spans, whose backticks are dropped and
+/// contents kept as written.
+fn render_inline(line: &str) -> String {
+    let parts: Vec<&str> = line.split('`').collect();
+    // An unmatched backtick stays literal, along with everything after it.
+    let spans = if parts.len().is_multiple_of(2) {
+        parts.len() - 1
+    } else {
+        parts.len()
+    };
+    let mut rendered = String::with_capacity(line.len());
+    for (index, part) in parts.iter().enumerate() {
+        if index >= spans {
+            rendered.push('`');
+            rendered.push_str(part);
+        } else if index % 2 == 1 {
+            rendered.push_str(part);
+        } else {
+            let mut text = render_links(part);
+            for delimiter in ["**", "__", "~~", "*", "_"] {
+                text = render_emphasis(&text, delimiter);
+            }
+            rendered.push_str(&text);
+        }
+    }
+    rendered
+}
+
+/// `[label](target)` and `![alt](target)` become their label or alt text.
+fn render_links(text: &str) -> String {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find('[') {
+        let Some(close) = rest[open..].find("](").map(|offset| open + offset) else {
+            break;
+        };
+        let Some(end) = rest[close + 2..].find(')').map(|offset| close + 2 + offset) else {
+            break;
+        };
+        let before = &rest[..open];
+        rendered.push_str(before.strip_suffix('!').unwrap_or(before));
+        rendered.push_str(&rest[open + 1..close]);
+        rest = &rest[end + 1..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Remove `delimiter` pairs that wrap text: the opening one not after a word
+/// character nor before a space, the closing one the reverse. Keeps
+/// `snake_case` and `2 * 3 * 4` as written.
+fn render_emphasis(text: &str, delimiter: &str) -> String {
+    let is_word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    let is_space = |c: Option<char>| c.is_none_or(char::is_whitespace);
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    loop {
+        let opening = rest.match_indices(delimiter).find(|&(at, _)| {
+            let before = rest[..at].chars().last().or(rendered.chars().last());
+            !is_word(before) && !is_space(rest[at + delimiter.len()..].chars().next())
+        });
+        let Some((open, _)) = opening else {
+            break;
+        };
+        let inner_start = open + delimiter.len();
+        let closing = rest[inner_start..]
+            .match_indices(delimiter)
+            .map(|(at, _)| inner_start + at)
+            .find(|&at| {
+                at > inner_start
+                    && !is_space(rest[..at].chars().last())
+                    && !is_word(rest[at + delimiter.len()..].chars().next())
+            });
+        let Some(close) = closing else {
+            break;
+        };
+        rendered.push_str(&rest[..open]);
+        rendered.push_str(&rest[inner_start..close]);
+        rest = &rest[close + delimiter.len()..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const README: &str = "# Release notes\n\n\
+        Version **2.1** adds [sync](https://example.com/sync).\n\n\
+        ## Changes\n\
+        - Faster `search_text` lookups\n\
+        - Fixed _snake_case_ names\n\
+        1. Install\n\n\
+        ```sh\n\
+        # not a heading\n\
+        cargo build\n\
+        ```\n\
+        > Thanks to everyone who tested.";
+
+    #[test]
+    fn recognizes_markdown_with_several_kinds_of_syntax() {
+        assert!(is_markdown(README));
+        assert!(is_markdown("## Todo\n- milk\n- eggs"));
+        assert!(is_markdown("Run it:\n```\nmake\n```\nSee **docs**."));
+
+        assert!(!is_markdown("# Title"));
+        assert!(!is_markdown("- milk\n- eggs\n- bread"));
+        assert!(!is_markdown("# install deps\nbrew install jq"));
+        assert!(!is_markdown("Dear Sam,\nSee you at 5 * 2 = 10."));
+    }
+
+    #[test]
+    fn renders_plain_text_without_markdown_syntax() {
+        assert_eq!(
+            render_plain_text(README),
+            "Release notes\n\n\
+             Version 2.1 adds sync.\n\n\
+             Changes\n\
+             • Faster search_text lookups\n\
+             • Fixed snake_case names\n\
+             1. Install\n\n\
+             # not a heading\n\
+             cargo build\n\
+             Thanks to everyone who tested."
+        );
+        assert_eq!(
+            render_plain_text("![logo](a.png) 2 * 3 * 4 and a_b_c\n---\n`tick"),
+            "logo 2 * 3 * 4 and a_b_c\n`tick"
+        );
+    }
+}
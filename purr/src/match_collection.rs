@@ -0,0 +1,113 @@
+//! Every match of a query gathered into one text document.
+//!
+//! Each match contributes an excerpt around its highlights, followed by a
+//! line naming where it came from: source app, capture time, and the item's
+//! external ID, which keeps pointing at the original after it is restored,
+//! imported, or synced.
+
+use crate::database::Database;
+use crate::file_kind;
+use crate::interface::{ClipKittyError, ItemQueryFilter, ListPresentationProfile};
+use crate::models::StoredItem;
+use crate::search::{compute_matched_excerpt, SearchQuery};
+use crate::search_result_builder::split_filter;
+use crate::user_tags;
+
+/// Items read from the database at a time while composing.
+const BATCH_SIZE: usize = 200;
+
+/// The document collecting `item_ids`, the search results for `query`, in
+/// order, with the number of items it cites. None when nothing matched.
+pub(crate) fn compose(
+    db: &Database,
+    query: &str,
+    filter: &ItemQueryFilter,
+    item_ids: &[String],
+) -> Result<Option<(String, u64)>, ClipKittyError> {
+    // Highlight what search matched: the query without its `#tags`.
+    let (_, mut tags) = split_filter(filter);
+    let search_text = user_tags::extract_hashtags(db, query, &mut tags)?;
+    let search_text = file_kind::extract_kind_filter(&search_text, &mut tags);
+    let highlight_query = SearchQuery::parse(&search_text).raw_text().to_string();
+
+    let mut document = format!("Matches for \"{}\"", query.trim());
+    let mut count = 0u64;
+    for batch in item_ids.chunks(BATCH_SIZE) {
+        for item in db.fetch_items_by_item_ids(batch)? {
+            let excerpt = compute_matched_excerpt(
+                item.content.text_content(),
+                &highlight_query,
+                ListPresentationProfile::Card,
+            );
+            document.push_str("\n\n");
+            document.push_str(excerpt.text.trim());
+            document.push('\n');
+            document.push_str(&source_line(&item));
+            count += 1;
+        }
+    }
+    Ok((count > 0).then_some((document, count)))
+}
+
+/// E.g. "Source: Safari, 2026-03-05 14:03 UTC (01JNM3Z8YQ2V7K4T9C5W6XH0RB)".
+fn source_line(item: &StoredItem) -> String {
+    let captured = chrono::DateTime::from_timestamp(item.timestamp_unix, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default();
+    let mut line = String::from("Source: ");
+    if let Some(app) = item.source_app.as_deref().filter(|app| !app.is_empty()) {
+        line.push_str(app);
+        line.push_str(", ");
+    }
+    line.push_str(&captured);
+    line.push_str(&format!(" ({})", item.external_id));
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cites_each_match_after_its_excerpt() {
+        let db = Database::open_in_memory().unwrap();
+        let mut first = StoredItem::new_text(
+            "Tardigrades survive vacuum and radiation.".into(),
+            Some("Safari".into()),
+            None,
+        );
+        first.timestamp_unix = 1_772_719_380;
+        let mut second = StoredItem::new_text("Notes: tardigrades eat moss.".into(), None, None);
+        second.timestamp_unix = 1_772_719_440;
+        for item in [&mut first, &mut second] {
+            db.insert_item(item).unwrap();
+            item.external_id = db
+                .fetch_items_by_item_ids(std::slice::from_ref(&item.item_id))
+                .unwrap()[0]
+                .external_id
+                .clone();
+        }
+
+        let ids = [second.item_id.clone(), first.item_id.clone()];
+        let (document, count) = compose(&db, "tardigrades", &ItemQueryFilter::All, &ids)
+            .unwrap()
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(
+            document,
+            format!(
+                "Matches for \"tardigrades\"\n\n\
+                 Notes: tardigrades eat moss.\n\
+                 Source: 2026-03-05 14:04 UTC ({})\n\n\
+                 Tardigrades survive vacuum and radiation.\n\
+                 Source: Safari, 2026-03-05 14:03 UTC ({})",
+                second.external_id, first.external_id
+            )
+        );
+
+        assert_eq!(
+            compose(&db, "tardigrades", &ItemQueryFilter::All, &[]).unwrap(),
+            None
+        );
+    }
+}
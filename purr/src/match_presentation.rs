@@ -1,9 +1,9 @@
-use crate::candidate::{ScoringPhase, SearchMatchContext};
+use crate::candidate::{ScoredWordMatches, ScoringPhase, SearchMatchContext};
 use crate::database::{Database, SearchRowMetadata};
 use crate::interface::{
     BaselineExcerpt, ClipKittyError, ClipboardItem, ExcerptPlaceholder, ExcerptUnavailableReason,
-    ListPresentationProfile, MatchedExcerpt, MatchedExcerptRequest, MatchedExcerptResolution,
-    PreviewPayload,
+    ListPresentationProfile, MatchedExcerpt, MatchedExcerptPatch, MatchedExcerptRequest,
+    MatchedExcerptResolution, PreviewPayload, Utf16HighlightRange,
 };
 use crate::models::StoredItem;
 use crate::search::{self, HighlightAnalysis};
@@ -106,6 +106,7 @@ pub(crate) enum CachedMatchContext {
         content: Arc<str>,
         strategy: HighlightStrategy,
         readiness: HighlightReadiness,
+        scored_matches: Option<Arc<ScoredWordMatches>>,
     },
     ChunkRegion {
         parent_content_hash: String,
@@ -114,6 +115,16 @@ pub(crate) enum CachedMatchContext {
         chunk_end: usize,
         strategy: HighlightStrategy,
         readiness: HighlightReadiness,
+        scored_matches: Option<Arc<ScoredWordMatches>>,
+    },
+    /// Text recognized in an image. Excerpts come from it, but it is not the
+    /// item's content, so it never decorates the preview.
+    RecognizedText {
+        parent_content_hash: String,
+        content: Arc<str>,
+        strategy: HighlightStrategy,
+        readiness: HighlightReadiness,
+        scored_matches: Option<Arc<ScoredWordMatches>>,
     },
 }
 
@@ -122,6 +133,7 @@ impl CachedMatchContext {
         parent_content_hash: String,
         match_context: &SearchMatchContext,
         scoring_phase: ScoringPhase,
+        scored_matches: Option<Arc<ScoredWordMatches>>,
     ) -> Self {
         let strategy = match scoring_phase {
             ScoringPhase::PhaseTwoScored => HighlightStrategy::Full,
@@ -133,6 +145,7 @@ impl CachedMatchContext {
                 content: Arc::from(ctx.content()),
                 strategy,
                 readiness: HighlightReadiness::Pending,
+                scored_matches,
             },
             SearchMatchContext::Chunk(ctx) => Self::ChunkRegion {
                 parent_content_hash,
@@ -141,42 +154,88 @@ impl CachedMatchContext {
                 chunk_end: ctx.chunk_end(),
                 strategy,
                 readiness: HighlightReadiness::Pending,
+                scored_matches,
+            },
+            SearchMatchContext::RecognizedText(ctx) => Self::RecognizedText {
+                parent_content_hash,
+                content: Arc::from(ctx.content()),
+                strategy,
+                readiness: HighlightReadiness::Pending,
+                scored_matches,
             },
         }
     }
 
     fn content(&self) -> &str {
         match self {
-            Self::WholeContent { content, .. } => content,
+            Self::WholeContent { content, .. } | Self::RecognizedText { content, .. } => content,
             Self::ChunkRegion { chunk_content, .. } => chunk_content,
         }
     }
 
     fn strategy(&self) -> HighlightStrategy {
         match self {
-            Self::WholeContent { strategy, .. } | Self::ChunkRegion { strategy, .. } => *strategy,
+            Self::WholeContent { strategy, .. }
+            | Self::ChunkRegion { strategy, .. }
+            | Self::RecognizedText { strategy, .. } => *strategy,
         }
     }
 
-    fn analysis(&self) -> Option<Arc<HighlightAnalysis>> {
+    fn scored_matches(&self) -> Option<&ScoredWordMatches> {
         match self {
-            Self::WholeContent { readiness, .. } | Self::ChunkRegion { readiness, .. } => {
-                match readiness {
-                    HighlightReadiness::Ready(analysis) => Some(Arc::clone(analysis)),
-                    HighlightReadiness::Pending => None,
-                }
+            Self::WholeContent { scored_matches, .. }
+            | Self::ChunkRegion { scored_matches, .. }
+            | Self::RecognizedText { scored_matches, .. } => scored_matches.as_deref(),
+        }
+    }
+
+    /// Highlights under this context's strategy, reusing the token matches
+    /// Phase 2 recorded while scoring it.
+    fn analyze(&self, query: &str) -> Option<HighlightAnalysis> {
+        match self.strategy() {
+            HighlightStrategy::Full => search::analyze_scored_content_for_query(
+                self.content(),
+                query,
+                self.scored_matches(),
+            ),
+            HighlightStrategy::WordMatch => {
+                search::analyze_content_word_match(self.content(), query)
             }
         }
     }
 
+    fn analysis(&self) -> Option<Arc<HighlightAnalysis>> {
+        match self {
+            Self::WholeContent { readiness, .. }
+            | Self::ChunkRegion { readiness, .. }
+            | Self::RecognizedText { readiness, .. } => match readiness {
+                HighlightReadiness::Ready(analysis) => Some(Arc::clone(analysis)),
+                HighlightReadiness::Pending => None,
+            },
+        }
+    }
+
     fn set_analysis(&mut self, analysis: Arc<HighlightAnalysis>) {
         match self {
             Self::WholeContent {
-                readiness: slot, ..
+                readiness: slot,
+                scored_matches,
+                ..
             }
             | Self::ChunkRegion {
-                readiness: slot, ..
-            } => *slot = HighlightReadiness::Ready(analysis),
+                readiness: slot,
+                scored_matches,
+                ..
+            }
+            | Self::RecognizedText {
+                readiness: slot,
+                scored_matches,
+                ..
+            } => {
+                *slot = HighlightReadiness::Ready(analysis);
+                // Only needed until highlights are computed.
+                *scored_matches = None;
+            }
         }
     }
 
@@ -189,6 +248,10 @@ impl CachedMatchContext {
             | Self::ChunkRegion {
                 parent_content_hash: cached,
                 ..
+            }
+            | Self::RecognizedText {
+                parent_content_hash: cached,
+                ..
             } => cached == parent_content_hash,
         }
     }
@@ -370,6 +433,7 @@ impl HighlightAnalysisCache {
         parent_content_hash: String,
         match_context: &SearchMatchContext,
         scoring_phase: ScoringPhase,
+        scored_matches: Option<Arc<ScoredWordMatches>>,
     ) {
         let Some(query_key) = Self::normalized_query(query) else {
             return;
@@ -386,6 +450,7 @@ impl HighlightAnalysisCache {
                 parent_content_hash,
                 match_context,
                 scoring_phase,
+                scored_matches,
             ),
         );
     }
@@ -482,61 +547,118 @@ impl<'a> MatchPresentation<'a> {
             .collect();
 
         use rayon::prelude::*;
-        Ok(requests
-            .par_iter()
-            .map(|request| {
-                if request.query.trim().is_empty() {
-                    return MatchedExcerptResolution::Unavailable {
-                        item_id: request.item_id.clone(),
-                        reason: ExcerptUnavailableReason::EmptyQuery,
-                    };
-                }
-
-                let Some(metadata) = metadata_map.get(request.item_id.as_str()) else {
-                    return MatchedExcerptResolution::Unavailable {
-                        item_id: request.item_id.clone(),
-                        reason: ExcerptUnavailableReason::ItemMissing,
+        Ok(crate::power::install(|| {
+            requests
+                .par_iter()
+                .map(|request| {
+                    if request.query.trim().is_empty() {
+                        return MatchedExcerptResolution::Unavailable {
+                            item_id: request.item_id.clone(),
+                            reason: ExcerptUnavailableReason::EmptyQuery,
+                        };
+                    }
+
+                    let Some(metadata) = metadata_map.get(request.item_id.as_str()) else {
+                        return MatchedExcerptResolution::Unavailable {
+                            item_id: request.item_id.clone(),
+                            reason: ExcerptUnavailableReason::ItemMissing,
+                        };
                     };
-                };
-                if metadata.content_hash != request.content_hash {
-                    return MatchedExcerptResolution::Unavailable {
-                        item_id: request.item_id.clone(),
-                        reason: ExcerptUnavailableReason::ContentChanged,
+                    if metadata.content_hash != request.content_hash {
+                        return MatchedExcerptResolution::Unavailable {
+                            item_id: request.item_id.clone(),
+                            reason: ExcerptUnavailableReason::ContentChanged,
+                        };
+                    }
+
+                    let cached_context = self
+                        .cache
+                        .get_match_context(&request.query, request.item_id.as_str())
+                        .filter(|context| context.matches_parent_hash(&request.content_hash));
+                    let excerpt = if cached_context.is_some() {
+                        self.matched_excerpt_for_cached_match(
+                            &request.item_id,
+                            &request.query,
+                            request.presentation_profile,
+                        )
+                    } else if let Some(item) = item_map.get(request.item_id.as_str()) {
+                        self.matched_excerpt_for_item(
+                            &request.item_id,
+                            item.content.text_content(),
+                            &request.query,
+                            request.presentation_profile,
+                        )
+                    } else {
+                        return MatchedExcerptResolution::Unavailable {
+                            item_id: request.item_id.clone(),
+                            reason: ExcerptUnavailableReason::ItemMissing,
+                        };
                     };
-                }
 
-                let cached_context = self
-                    .cache
-                    .get_match_context(&request.query, request.item_id.as_str())
-                    .filter(|context| context.matches_parent_hash(&request.content_hash));
-                let excerpt = if cached_context.is_some() {
-                    self.matched_excerpt_for_cached_match(
-                        &request.item_id,
-                        &request.query,
-                        request.presentation_profile,
-                    )
-                } else if let Some(item) = item_map.get(request.item_id.as_str()) {
-                    self.matched_excerpt_for_item(
-                        &request.item_id,
-                        item.content.text_content(),
-                        &request.query,
-                        request.presentation_profile,
-                    )
-                } else {
-                    return MatchedExcerptResolution::Unavailable {
+                    MatchedExcerptResolution::Ready {
                         item_id: request.item_id.clone(),
-                        reason: ExcerptUnavailableReason::ItemMissing,
-                    };
-                };
+                        excerpt,
+                    }
+                })
+                .collect()
+        }))
+    }
 
-                MatchedExcerptResolution::Ready {
-                    item_id: request.item_id.clone(),
-                    excerpt,
+    /// Resolve `requests` as patches against the excerpts resolved for
+    /// `previous_query`. Only excerpts whose highlights are already cached
+    /// count as previous; anything else is sent whole.
+    pub(crate) fn resolve_matched_excerpt_patches(
+        &self,
+        previous_query: &str,
+        requests: Vec<MatchedExcerptRequest>,
+    ) -> Result<Vec<MatchedExcerptPatch>, ClipKittyError> {
+        let previous: Vec<Option<MatchedExcerpt>> = requests
+            .iter()
+            .map(|request| self.cached_matched_excerpt(previous_query, request))
+            .collect();
+        let resolutions = self.resolve_matched_excerpts(requests)?;
+        Ok(resolutions
+            .into_iter()
+            .zip(previous)
+            .map(|(resolution, previous)| match resolution {
+                MatchedExcerptResolution::Ready { item_id, excerpt } => match previous {
+                    Some(previous) if previous.text == excerpt.text => {
+                        let (removed, added) =
+                            diff_highlights(&previous.highlights, &excerpt.highlights);
+                        MatchedExcerptPatch::Highlights {
+                            item_id,
+                            removed,
+                            added,
+                            line_number: excerpt.line_number,
+                        }
+                    }
+                    _ => MatchedExcerptPatch::Replaced { item_id, excerpt },
+                },
+                MatchedExcerptResolution::Unavailable { item_id, reason } => {
+                    MatchedExcerptPatch::Unavailable { item_id, reason }
                 }
             })
             .collect())
     }
 
+    /// The excerpt for `query` if its highlights were already computed.
+    fn cached_matched_excerpt(
+        &self,
+        query: &str,
+        request: &MatchedExcerptRequest,
+    ) -> Option<MatchedExcerpt> {
+        let context = self
+            .cache
+            .get_match_context(query, &request.item_id)
+            .filter(|context| context.matches_parent_hash(&request.content_hash))?;
+        let analysis = context.analysis()?;
+        Some(search::create_matched_excerpt(
+            context.content(),
+            &analysis,
+            request.presentation_profile,
+        ))
+    }
+
     pub(crate) fn load_preview_payload(
         &self,
         item_id: String,
@@ -598,6 +720,7 @@ impl<'a> MatchPresentation<'a> {
         parent_content_hash: String,
         match_context: &SearchMatchContext,
         scoring_phase: ScoringPhase,
+        scored_matches: Option<Arc<ScoredWordMatches>>,
     ) {
         self.cache.insert_match_context(
             query,
@@ -605,6 +728,7 @@ impl<'a> MatchPresentation<'a> {
             parent_content_hash,
             match_context,
             scoring_phase,
+            scored_matches,
         );
     }
 
@@ -623,15 +747,14 @@ impl<'a> MatchPresentation<'a> {
         {
             return ExcerptPlaceholder::CompatibleCached {
                 source_query,
-                excerpt: search::create_matched_excerpt(
-                    context.content(),
-                    &analysis.highlights,
-                    profile,
-                ),
+                excerpt: search::create_matched_excerpt(context.content(), &analysis, profile),
             };
         }
 
-        if matches!(match_context, SearchMatchContext::Chunk(_)) {
+        if matches!(
+            match_context,
+            SearchMatchContext::Chunk(_) | SearchMatchContext::RecognizedText(_)
+        ) {
             return ExcerptPlaceholder::Provisional {
                 excerpt: BaselineExcerpt {
                     text: search::generate_preview_for_profile(match_context.content(), profile),
@@ -651,7 +774,7 @@ impl<'a> MatchPresentation<'a> {
         profile: ListPresentationProfile,
     ) -> MatchedExcerpt {
         if let Some((context, analysis)) = self.analysis_for_cached_match_context(item_id, query) {
-            search::create_matched_excerpt(context.content(), &analysis.highlights, profile)
+            search::create_matched_excerpt(context.content(), &analysis, profile)
         } else {
             MatchedExcerpt {
                 text: String::new(),
@@ -669,7 +792,7 @@ impl<'a> MatchPresentation<'a> {
         profile: ListPresentationProfile,
     ) -> MatchedExcerpt {
         if let Some(analysis) = self.analysis_for_item(item_id, content, query) {
-            search::create_matched_excerpt(content, &analysis.highlights, profile)
+            search::create_matched_excerpt(content, &analysis, profile)
         } else {
             search::compute_matched_excerpt(content, query, profile)
         }
@@ -706,7 +829,11 @@ impl<'a> MatchPresentation<'a> {
         let decoration = cached_match_context
             .and_then(|context| {
                 let analysis = context.analysis().or_else(|| {
-                    let analysis = search::analyze_content_for_query(context.content(), query)?;
+                    let analysis = search::analyze_scored_content_for_query(
+                        context.content(),
+                        query,
+                        context.scored_matches(),
+                    )?;
                     #[cfg(test)]
                     test_support::on_analysis_computed(item_id, query);
                     let analysis = Arc::new(analysis);
@@ -770,12 +897,7 @@ impl<'a> MatchPresentation<'a> {
             return Some((context, analysis));
         }
 
-        let analysis = match context.strategy() {
-            HighlightStrategy::Full => search::analyze_content_for_query(context.content(), query),
-            HighlightStrategy::WordMatch => {
-                search::analyze_content_word_match(context.content(), query)
-            }
-        }?;
+        let analysis = context.analyze(query)?;
         #[cfg(test)]
         test_support::on_analysis_computed(item_id, query);
         let analysis = Arc::new(analysis);
@@ -790,14 +912,31 @@ impl<'a> MatchPresentation<'a> {
     }
 }
 
+/// Ranges only in `previous`, and ranges only in `next`, each in order.
+fn diff_highlights(
+    previous: &[Utf16HighlightRange],
+    next: &[Utf16HighlightRange],
+) -> (Vec<Utf16HighlightRange>, Vec<Utf16HighlightRange>) {
+    let only_in = |ranges: &[Utf16HighlightRange], other: &[Utf16HighlightRange]| {
+        ranges
+            .iter()
+            .filter(|range| !other.contains(range))
+            .cloned()
+            .collect()
+    };
+    (only_in(previous, next), only_in(next, previous))
+}
+
 fn hydrate_clipboard_item_tags(
     db: &Database,
     item: &mut ClipboardItem,
 ) -> Result<(), ClipKittyError> {
-    let tags_by_id = db.get_tags_for_item_ids(&[item.item_metadata.item_id.clone()])?;
+    let ids = [item.item_metadata.item_id.clone()];
+    let tags_by_id = db.get_tags_for_item_ids(&ids)?;
     item.item_metadata.tags = tags_by_id
         .get(&item.item_metadata.item_id)
         .cloned()
         .unwrap_or_default();
-    Ok(())
+    item.item_metadata.is_pinned = !db.get_pinned_item_ids(&ids)?.is_empty();
+    crate::app_metadata::hydrate_source_apps(db, [&mut item.item_metadata])
 }
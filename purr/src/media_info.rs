@@ -0,0 +1,353 @@
+//! Duration, codec, and dimensions of copied audio and video files.
+//!
+//! The app supplies these from AVFoundation when it can; for MP4-family
+//! containers (MP4, MOV, M4A, M4V) and WAV, Rust reads them from the file's
+//! headers at capture so the list can show them either way. Only box and
+//! chunk headers are read, seeking past media data.
+
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::interface::MediaInfo;
+
+/// Largest `moov` box read into memory; real ones are a few hundred KB.
+const MAX_MOVIE_BOX_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Media info from the headers of the file at `path`, if it is a container
+/// this module reads.
+pub(crate) fn probe(path: &Path) -> Option<MediaInfo> {
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    probe_reader(&mut reader)
+}
+
+fn probe_reader<R: Read + Seek>(reader: &mut R) -> Option<MediaInfo> {
+    let mut magic = [0u8; 12];
+    reader.read_exact(&mut magic).ok()?;
+    reader.seek(SeekFrom::Start(0)).ok()?;
+    if &magic[..4] == b"RIFF" && &magic[8..] == b"WAVE" {
+        probe_wav(reader)
+    } else if matches!(
+        &magic[4..8],
+        b"ftyp" | b"moov" | b"wide" | b"mdat" | b"free"
+    ) {
+        probe_iso_media(reader)
+    } else {
+        None
+    }
+}
+
+/// `info` as one metadata line: duration, codec, and dimensions, whichever
+/// are known, as in "1:35 · HEVC · 1920×1080".
+pub(crate) fn summary(info: &MediaInfo) -> String {
+    let duration = info.duration_ms.map(|ms| {
+        let seconds = ms / 1000;
+        let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+        if hours > 0 {
+            format!("{hours}:{minutes:02}:{seconds:02}")
+        } else {
+            format!("{minutes}:{seconds:02}")
+        }
+    });
+    let dimensions = info
+        .width
+        .zip(info.height)
+        .map(|(width, height)| format!("{width}×{height}"));
+    [duration, info.codec.clone(), dimensions]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// MP4 family
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn probe_iso_media<R: Read + Seek>(reader: &mut R) -> Option<MediaInfo> {
+    loop {
+        let (kind, body_len) = read_box_header(reader)?;
+        if &kind == b"moov" {
+            if body_len > MAX_MOVIE_BOX_BYTES {
+                return None;
+            }
+            let mut movie = vec![0u8; body_len as usize];
+            reader.read_exact(&mut movie).ok()?;
+            return movie_info(&movie);
+        }
+        reader.seek(SeekFrom::Current(body_len as i64)).ok()?;
+    }
+}
+
+/// The type and body length of the box at the reader's position.
+fn read_box_header<R: Read + Seek>(reader: &mut R) -> Option<([u8; 4], u64)> {
+    let mut header = [0u8; 8];
+    reader.read_exact(&mut header).ok()?;
+    let kind = header[4..8].try_into().ok()?;
+    let body_len = match u32::from_be_bytes(header[..4].try_into().ok()?) {
+        0 => {
+            let here = reader.stream_position().ok()?;
+            let end = reader.seek(SeekFrom::End(0)).ok()?;
+            reader.seek(SeekFrom::Start(here)).ok()?;
+            end - here
+        }
+        1 => {
+            let mut large = [0u8; 8];
+            reader.read_exact(&mut large).ok()?;
+            u64::from_be_bytes(large).checked_sub(16)?
+        }
+        size => u64::from(size).checked_sub(8)?,
+    };
+    Some((kind, body_len))
+}
+
+/// The boxes directly inside `data`, as `(type, body)`.
+fn child_boxes(data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut rest = data;
+    std::iter::from_fn(move || {
+        let size = u32::from_be_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+        let kind = rest.get(4..8)?;
+        let (header, size) = match size {
+            0 => (8, rest.len()),
+            1 => (
+                16,
+                u64::from_be_bytes(rest.get(8..16)?.try_into().ok()?) as usize,
+            ),
+            size => (8, size),
+        };
+        let body = rest.get(header..size)?;
+        rest = &rest[size..];
+        Some((kind, body))
+    })
+}
+
+fn child<'a>(data: &'a [u8], kind: &[u8]) -> Option<&'a [u8]> {
+    child_boxes(data)
+        .find(|(found, _)| *found == kind)
+        .map(|(_, body)| body)
+}
+
+fn be_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn be_u64(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(data.get(at..at + 8)?.try_into().ok()?))
+}
+
+fn movie_info(movie: &[u8]) -> Option<MediaInfo> {
+    let header = child(movie, b"mvhd")?;
+    let (timescale, duration) = if header.first() == Some(&1) {
+        (be_u32(header, 20)?, be_u64(header, 24)?)
+    } else {
+        (be_u32(header, 12)?, u64::from(be_u32(header, 16)?))
+    };
+    let duration_ms = (timescale > 0).then(|| duration * 1000 / u64::from(timescale));
+
+    let mut video = None;
+    let mut audio_codec = None;
+    for (_, track) in child_boxes(movie).filter(|(kind, _)| *kind == b"trak") {
+        let Some(media) = child(track, b"mdia") else {
+            continue;
+        };
+        let handler = child(media, b"hdlr").and_then(|hdlr| hdlr.get(8..12));
+        let codec = child(media, b"minf")
+            .and_then(|minf| child(minf, b"stbl"))
+            .and_then(|stbl| child(stbl, b"stsd"))
+            .and_then(|stsd| stsd.get(12..16))
+            .map(codec_name);
+        match handler {
+            Some(b"vide") if video.is_none() => {
+                video = Some((codec, child(track, b"tkhd").and_then(track_dimensions)))
+            }
+            Some(b"soun") if audio_codec.is_none() => audio_codec = codec,
+            _ => {}
+        }
+    }
+    let (codec, dimensions) = match video {
+        Some((codec, dimensions)) => (codec, dimensions),
+        None => (audio_codec, None),
+    };
+    Some(MediaInfo {
+        duration_ms,
+        codec,
+        width: dimensions.map(|(width, _)| width),
+        height: dimensions.map(|(_, height)| height),
+    })
+}
+
+/// Display width and height from a track header, stored as 16.16 fixed point
+/// after the fields whose size depends on the header's version.
+fn track_dimensions(header: &[u8]) -> Option<(u32, u32)> {
+    let at = if header.first() == Some(&1) { 88 } else { 76 };
+    let (width, height) = (be_u32(header, at)? >> 16, be_u32(header, at + 4)? >> 16);
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+fn codec_name(fourcc: &[u8]) -> String {
+    match fourcc {
+        b"avc1" | b"avc3" => "H.264".into(),
+        b"hvc1" | b"hev1" => "HEVC".into(),
+        b"av01" => "AV1".into(),
+        b"vp09" => "VP9".into(),
+        b"apcn" | b"apch" | b"apcs" | b"apco" | b"ap4h" | b"ap4x" => "ProRes".into(),
+        b"mp4a" => "AAC".into(),
+        b"alac" => "ALAC".into(),
+        b"ac-3" => "AC-3".into(),
+        b"ec-3" => "E-AC-3".into(),
+        other => String::from_utf8_lossy(other).trim().to_string(),
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// WAV
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn probe_wav<R: Read + Seek>(reader: &mut R) -> Option<MediaInfo> {
+    reader.seek(SeekFrom::Start(12)).ok()?;
+    let mut format = None;
+    loop {
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header).ok()?;
+        let len = u32::from_le_bytes(header[4..].try_into().ok()?);
+        match &header[..4] {
+            b"fmt " => {
+                let mut body = vec![0u8; len.min(64) as usize];
+                reader.read_exact(&mut body).ok()?;
+                reader
+                    .seek(SeekFrom::Current(i64::from(len) - body.len() as i64))
+                    .ok()?;
+                let tag = u16::from_le_bytes(body.get(..2)?.try_into().ok()?);
+                let byte_rate = u32::from_le_bytes(body.get(8..12)?.try_into().ok()?);
+                format = Some((tag, byte_rate));
+            }
+            b"data" => {
+                let (tag, byte_rate) = format?;
+                let codec = match tag {
+                    1 | 0xFFFE => Some("PCM".to_string()),
+                    3 => Some("PCM float".to_string()),
+                    _ => None,
+                };
+                return Some(MediaInfo {
+                    duration_ms: (byte_rate > 0)
+                        .then(|| u64::from(len) * 1000 / u64::from(byte_rate)),
+                    codec,
+                    width: None,
+                    height: None,
+                });
+            }
+            _ => {
+                // Chunks are padded to an even length.
+                reader
+                    .seek(SeekFrom::Current(i64::from(len) + i64::from(len % 2)))
+                    .ok()?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn mp4_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut bytes = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        bytes.extend_from_slice(kind);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    fn track(handler: &[u8; 4], codec: &[u8; 4], dimensions: (u32, u32)) -> Vec<u8> {
+        let mut tkhd = vec![0u8; 84];
+        tkhd[76..80].copy_from_slice(&(dimensions.0 << 16).to_be_bytes());
+        tkhd[80..84].copy_from_slice(&(dimensions.1 << 16).to_be_bytes());
+        let mut hdlr = vec![0u8; 24];
+        hdlr[8..12].copy_from_slice(handler);
+        let mut stsd = vec![0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 16];
+        stsd.extend_from_slice(codec);
+        stsd.extend_from_slice(&[0; 8]);
+        let stbl = mp4_box(b"stbl", &mp4_box(b"stsd", &stsd));
+        let minf = mp4_box(b"minf", &stbl);
+        let mdia = mp4_box(b"mdia", &[mp4_box(b"hdlr", &hdlr), minf].concat());
+        mp4_box(b"trak", &[mp4_box(b"tkhd", &tkhd), mdia].concat())
+    }
+
+    #[test]
+    fn reads_duration_codec_and_size_from_a_movie() {
+        let mut mvhd = vec![0u8; 100];
+        mvhd[12..16].copy_from_slice(&600u32.to_be_bytes());
+        mvhd[16..20].copy_from_slice(&(600u32 * 95 + 300).to_be_bytes());
+        let moov = mp4_box(
+            b"moov",
+            &[
+                mp4_box(b"mvhd", &mvhd),
+                track(b"soun", b"mp4a", (0, 0)),
+                track(b"vide", b"hvc1", (1920, 1080)),
+            ]
+            .concat(),
+        );
+        // Media data before the movie box is skipped, not read.
+        let file = [
+            mp4_box(b"ftyp", b"qt  \0\0\0\0"),
+            mp4_box(b"mdat", &[7; 4096]),
+            moov,
+        ]
+        .concat();
+
+        assert_eq!(
+            probe_reader(&mut Cursor::new(file)),
+            Some(MediaInfo {
+                duration_ms: Some(95_500),
+                codec: Some("HEVC".into()),
+                width: Some(1920),
+                height: Some(1080),
+            })
+        );
+    }
+
+    #[test]
+    fn reads_duration_and_encoding_from_a_wav_file() {
+        let mut wav = b"RIFF\0\0\0\0WAVE".to_vec();
+        wav.extend_from_slice(b"LIST\x03\0\0\0abc\0");
+        wav.extend_from_slice(b"fmt \x10\0\0\0");
+        wav.extend_from_slice(&[1, 0, 2, 0]);
+        wav.extend_from_slice(&44_100u32.to_le_bytes());
+        wav.extend_from_slice(&176_400u32.to_le_bytes());
+        wav.extend_from_slice(&[4, 0, 16, 0]);
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(176_400u32 * 3).to_le_bytes());
+
+        assert_eq!(
+            probe_reader(&mut Cursor::new(wav)),
+            Some(MediaInfo {
+                duration_ms: Some(3_000),
+                codec: Some("PCM".into()),
+                width: None,
+                height: None,
+            })
+        );
+        assert_eq!(
+            probe_reader(&mut Cursor::new(b"%PDF-1.7\n...".to_vec())),
+            None
+        );
+    }
+
+    #[test]
+    fn summarizes_whatever_is_known() {
+        let info = MediaInfo {
+            duration_ms: Some(3_725_000),
+            codec: Some("H.264".into()),
+            width: Some(1280),
+            height: Some(720),
+        };
+        assert_eq!(summary(&info), "1:02:05 · H.264 · 1280×720");
+        let audio = MediaInfo {
+            duration_ms: Some(9_999),
+            codec: None,
+            width: None,
+            height: None,
+        };
+        assert_eq!(summary(&audio), "0:09");
+    }
+}
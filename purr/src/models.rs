@@ -23,6 +23,9 @@ use sha2::{Digest, Sha256};
 pub struct StoredItem {
     pub id: Option<i64>,
     pub item_id: String,
+    /// Opaque ULID for deep links and other references from outside the
+    /// store. Unlike the row ID it survives restores, imports, and sync.
+    pub external_id: String,
     pub content: ClipboardContent,
     pub content_hash: String,
     pub timestamp_unix: i64,
@@ -32,6 +35,12 @@ pub struct StoredItem {
     pub thumbnail: Option<Vec<u8>>,
     /// Parsed color RGBA for color content (stored for quick display)
     pub color_rgba: Option<u32>,
+    /// Text the host recognized in an image (stored in image_items.ocrText).
+    /// Indexed as its own search unit, apart from the description.
+    pub ocr_text: Option<String>,
+    /// Text read from inside a file item's files, like a PDF's title and
+    /// author (stored per file in file_entries). Indexed with the filenames.
+    pub file_contents_text: Option<String>,
 }
 
 impl StoredItem {
@@ -43,21 +52,62 @@ impl StoredItem {
     ) -> Self {
         let content_hash = Self::hash_string(&text);
         let content = crate::content_detection::detect_content(&text);
-        let color_rgba = if let ClipboardContent::Color { ref value } = content {
-            crate::content_detection::parse_color_to_rgba(value)
-        } else {
-            None
+        // Palettes keep their first color for quick display and get a rendered
+        // thumbnail so the list shows every color.
+        let (color_rgba, thumbnail) = match &content {
+            ClipboardContent::Color { value } => {
+                match crate::content_detection::parse_palette(value) {
+                    Some(palette) => (
+                        palette.colors.first().copied(),
+                        crate::thumbnail::render_palette(&palette),
+                    ),
+                    None => (crate::content_detection::parse_color_to_rgba(value), None),
+                }
+            }
+            _ => (None, None),
         };
         Self {
             id: None,
             item_id: uuid::Uuid::new_v4().to_string(),
+            external_id: ulid::Ulid::new().to_string(),
             content,
             content_hash,
             timestamp_unix: chrono::Utc::now().timestamp(),
             source_app,
             source_app_bundle_id,
-            thumbnail: None,
+            thumbnail,
             color_rgba,
+            ocr_text: None,
+            file_contents_text: None,
+        }
+    }
+
+    /// Create a rich text item. Rich and plain copies of the same text hash
+    /// differently, so copying one never dedupes into the other.
+    pub fn new_rich_text(
+        plain: String,
+        rtf_data: Option<Vec<u8>>,
+        html: Option<String>,
+        source_app: Option<String>,
+        source_app_bundle_id: Option<String>,
+    ) -> Self {
+        Self {
+            id: None,
+            item_id: uuid::Uuid::new_v4().to_string(),
+            external_id: ulid::Ulid::new().to_string(),
+            content_hash: Self::hash_string(&format!("rich_text:{plain}")),
+            content: ClipboardContent::RichText {
+                value: plain,
+                rtf_data,
+                html,
+            },
+            timestamp_unix: chrono::Utc::now().timestamp(),
+            source_app,
+            source_app_bundle_id,
+            thumbnail: None,
+            color_rgba: None,
+            ocr_text: None,
+            file_contents_text: None,
         }
     }
 
@@ -74,6 +124,7 @@ impl StoredItem {
         Self {
             id: None,
             item_id: uuid::Uuid::new_v4().to_string(),
+            external_id: ulid::Ulid::new().to_string(),
             content: ClipboardContent::Image {
                 data: image_data,
                 description: "Image".to_string(),
@@ -85,6 +136,8 @@ impl StoredItem {
             source_app_bundle_id,
             thumbnail,
             color_rgba: None,
+            ocr_text: None,
+            file_contents_text: None,
         }
     }
 
@@ -203,10 +256,12 @@ impl StoredItem {
                 preview: preview_snapshots[i].clone(),
             })
             .collect();
+        let thumbnail = crate::thumbnail::render_file_thumbnail(&files);
 
         Self {
             id: None,
             item_id: uuid::Uuid::new_v4().to_string(),
+            external_id: ulid::Ulid::new().to_string(),
             content: ClipboardContent::File {
                 display_name,
                 files,
@@ -215,8 +270,10 @@ impl StoredItem {
             timestamp_unix: chrono::Utc::now().timestamp(),
             source_app,
             source_app_bundle_id,
-            thumbnail: None,
+            thumbnail,
             color_rgba: None,
+            ocr_text: None,
+            file_contents_text: None,
         }
     }
 
@@ -234,6 +291,10 @@ impl StoredItem {
                 text.push('\n');
                 text.push_str(&file.path);
             }
+            if let Some(contents) = &self.file_contents_text {
+                text.push('\n');
+                text.push_str(contents);
+            }
             Some(text)
         } else {
             None
@@ -253,11 +314,7 @@ impl StoredItem {
 
     /// Get the ItemIcon for display
     pub fn item_icon(&self) -> ItemIcon {
-        ItemIcon::from_database(
-            self.content.database_type(),
-            self.color_rgba,
-            self.thumbnail.clone(),
-        )
+        crate::icon_service::icon_for(self)
     }
 
     /// Display text (truncated, normalized whitespace) for preview
@@ -274,6 +331,7 @@ impl StoredItem {
             source_app_bundle_id: self.source_app_bundle_id.clone(),
             timestamp_unix: self.timestamp_unix,
             tags: Vec::new(),
+            is_pinned: false,
         }
     }
 
@@ -286,6 +344,7 @@ impl StoredItem {
             source_app_bundle_id: self.source_app_bundle_id.clone(),
             timestamp_unix: self.timestamp_unix,
             tags: Vec::new(),
+            is_pinned: false,
         }
     }
 
@@ -306,6 +365,15 @@ impl StoredItem {
         }
     }
 
+    /// A fresh external ID whose time part is `timestamp_unix`, so IDs minted
+    /// for existing items sort like the items do.
+    pub fn external_id_at(timestamp_unix: i64) -> String {
+        let millis = u64::try_from(timestamp_unix)
+            .unwrap_or(0)
+            .saturating_mul(1000);
+        ulid::Ulid::from_parts(millis, rand::random()).to_string()
+    }
+
     /// Hash a string using Rust's default hasher
     pub fn hash_string(s: &str) -> String {
         let mut hasher = DefaultHasher::new();
@@ -424,14 +492,29 @@ mod tests {
     #[test]
     fn test_item_icon_for_color() {
         let item = StoredItem::new_text("#FF5733".to_string(), None, None);
-        if let ItemIcon::ColorSwatch { rgba } = item.item_icon() {
+        if let ItemIcon::ColorSwatch { rgba, palette } = item.item_icon() {
             // #FF5733 with full alpha
             assert_eq!(rgba, 0xFF5733FF);
+            assert!(palette.is_none());
         } else {
             panic!("Expected ColorSwatch icon");
         }
     }
 
+    #[test]
+    fn test_item_icon_for_palette() {
+        let item = StoredItem::new_text("#FF0000, #00FF00".to_string(), None, None);
+        assert!(matches!(item.content, ClipboardContent::Color { .. }));
+        assert_eq!(item.color_rgba, Some(0xFF0000FF));
+        assert!(matches!(
+            item.item_icon(),
+            ItemIcon::ColorSwatch {
+                rgba: 0xFF0000FF,
+                palette: Some(_),
+            }
+        ));
+    }
+
     #[test]
     fn test_stored_item_multi_file_display_text() {
         // 2 files: "a.txt, b.txt"
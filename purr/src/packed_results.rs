@@ -0,0 +1,460 @@
+//! Packed transport for search matches.
+//!
+//! Lowering a few thousand `ItemMatch` records through UniFFI allocates and
+//! converts every string, enum, and nested record on both sides of the FFI.
+//! `SearchOperation::await_packed_result` instead hands Swift one byte buffer
+//! in the layout below, which `PackedMatchDecoder.swift` reads in a single
+//! pass. Only the matches are packed; the rest of the result is a record.
+//!
+//! # Layout (version 4)
+//!
+//! Integers are little-endian. A `str` is a `u32` byte length followed by
+//! UTF-8; an `opt<T>` is a `u8` (0 absent, 1 present) followed by `T` when
+//! present; a `list<T>` is a `u32` count followed by the items. Enum tags
+//! are `u8` values from the tables below, not declaration order.
+//!
+//! ```text
+//! buffer       = "CKPM" u8:version u32:count match*
+//! match        = metadata presentation
+//! metadata     = str:item_id icon opt<str>:source_app opt<str>:source_app_bundle_id
+//!                i64:timestamp_unix list<u8:item_tag> u8:is_pinned
+//! icon         = 0 u8:icon_type | 1 u32:rgba opt<list<u8>>:palette | 2 list<u8>:bytes
+//!              | 3 u8:code_language | 4 u8:document_kind | 5 u8:icon_type str:bundle_id
+//!              | 6 u64:offset u64:length
+//! presentation = 0 baseline | 1 matched
+//!              | 2 request placeholder | 3 baseline:fallback u8:unavailable_reason
+//! baseline     = str:text
+//! matched      = str:text list<range> u64:line_number
+//! range        = u64:utf16_start u64:utf16_end u8:highlight_kind
+//! request      = str:item_id str:query u8:profile str:content_hash
+//! placeholder  = 0 baseline | 1 str:source_query matched | 2 baseline
+//! ```
+//!
+//! | table              | values                                                  |
+//! |--------------------|---------------------------------------------------------|
+//! | icon_type          | text 0, link 1, image 2, color 3, file 4, code 5, document 6, phoneNumber 7, postalAddress 8, markdown 9 |
+//! | code_language      | rust 0, swift 1, python 2, javaScript 3, shell 4, sql 5, json 6, html 7, cFamily 8 |
+//! | document_kind      | pdf 0, image 1, audio 2, video 3, archive 4, spreadsheet 5, presentation 6, wordProcessing 7, plainText 8, sourceCode 9, folder 10, application 11, generic 12 |
+//! | item_tag           | bookmark 0                                              |
+//! | highlight_kind     | exact 0, prefix 1, prefixTail 2, subwordPrefix 3, substring 4, fuzzy 5, subsequence 6 |
+//! | profile            | compactRow 0, card 1                                    |
+//! | unavailable_reason | itemMissing 0, contentChanged 1, emptyQuery 2           |
+//!
+//! Adding a variant or field bumps `PACKED_MATCHES_VERSION`; the decoder
+//! rejects versions it does not know. A test checks the decoder's version and
+//! tag tables against these, so update both in the same change.
+
+use crate::interface::{
+    BaselineExcerpt, CodeLanguage, DocumentKind, ExcerptPlaceholder, ExcerptUnavailableReason,
+    HighlightKind, IconType, ItemIcon, ItemMatch, ItemMetadata, ItemTag, ListPresentationProfile,
+    MatchedExcerpt, MatchedExcerptRequest, RowPresentation,
+};
+
+const PACKED_MATCHES_MAGIC: &[u8; 4] = b"CKPM";
+pub(crate) const PACKED_MATCHES_VERSION: u8 = 4;
+
+/// Encode `matches` in the layout documented on this module.
+pub(crate) fn pack_matches(matches: &[ItemMatch]) -> Vec<u8> {
+    let mut writer = PackedWriter {
+        // Rows are dominated by their excerpt text; this skips most regrowth.
+        buffer: Vec::with_capacity(9 + matches.len() * 160),
+    };
+    writer.buffer.extend_from_slice(PACKED_MATCHES_MAGIC);
+    writer.u8(PACKED_MATCHES_VERSION);
+    writer.len(matches.len());
+    for item_match in matches {
+        writer.metadata(&item_match.item_metadata);
+        writer.presentation(&item_match.presentation);
+    }
+    writer.buffer
+}
+
+struct PackedWriter {
+    buffer: Vec<u8>,
+}
+
+impl PackedWriter {
+    fn u8(&mut self, value: u8) {
+        self.buffer.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn i64(&mut self, value: i64) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn len(&mut self, len: usize) {
+        self.u32(u32::try_from(len).expect("packed field longer than 4 GiB"));
+    }
+
+    fn bytes(&mut self, value: &[u8]) {
+        self.len(value.len());
+        self.buffer.extend_from_slice(value);
+    }
+
+    fn str(&mut self, value: &str) {
+        self.bytes(value.as_bytes());
+    }
+
+    fn opt_str(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => {
+                self.u8(1);
+                self.str(value);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn metadata(&mut self, metadata: &ItemMetadata) {
+        self.str(&metadata.item_id);
+        self.icon(&metadata.icon);
+        self.opt_str(metadata.source_app.as_deref());
+        self.opt_str(metadata.source_app_bundle_id.as_deref());
+        self.i64(metadata.timestamp_unix);
+        self.len(metadata.tags.len());
+        for tag in &metadata.tags {
+            self.u8(match tag {
+                ItemTag::Bookmark => 0,
+            });
+        }
+        self.u8(metadata.is_pinned.into());
+    }
+
+    fn icon(&mut self, icon: &ItemIcon) {
+        match icon {
+            ItemIcon::Symbol { icon_type } => {
+                self.u8(0);
+                self.u8(icon_type_tag(*icon_type));
+            }
+            ItemIcon::ColorSwatch { rgba, palette } => {
+                self.u8(1);
+                self.u32(*rgba);
+                match palette {
+                    Some(palette) => {
+                        self.u8(1);
+                        self.bytes(palette);
+                    }
+                    None => self.u8(0),
+                }
+            }
+            ItemIcon::Thumbnail { bytes } => {
+                self.u8(2);
+                self.bytes(bytes);
+            }
+            ItemIcon::Code { language } => {
+                self.u8(3);
+                self.u8(code_language_tag(*language));
+            }
+            ItemIcon::Document { kind } => {
+                self.u8(4);
+                self.u8(document_kind_tag(*kind));
+            }
+            ItemIcon::AppBadged {
+                icon_type,
+                bundle_id,
+            } => {
+                self.u8(5);
+                self.u8(icon_type_tag(*icon_type));
+                self.str(bundle_id);
+            }
+            ItemIcon::SharedThumbnail { offset, length } => {
+                self.u8(6);
+                self.u64(*offset);
+                self.u64(*length);
+            }
+        }
+    }
+
+    fn presentation(&mut self, presentation: &RowPresentation) {
+        match presentation {
+            RowPresentation::Baseline { excerpt } => {
+                self.u8(0);
+                self.baseline(excerpt);
+            }
+            RowPresentation::Matched { excerpt } => {
+                self.u8(1);
+                self.matched(excerpt);
+            }
+            RowPresentation::Deferred {
+                request,
+                placeholder,
+            } => {
+                self.u8(2);
+                self.request(request);
+                self.placeholder(placeholder);
+            }
+            RowPresentation::Unavailable { fallback, reason } => {
+                self.u8(3);
+                self.baseline(fallback);
+                self.u8(match reason {
+                    ExcerptUnavailableReason::ItemMissing => 0,
+                    ExcerptUnavailableReason::ContentChanged => 1,
+                    ExcerptUnavailableReason::EmptyQuery => 2,
+                });
+            }
+        }
+    }
+
+    fn baseline(&mut self, excerpt: &BaselineExcerpt) {
+        self.str(&excerpt.text);
+    }
+
+    fn matched(&mut self, excerpt: &MatchedExcerpt) {
+        self.str(&excerpt.text);
+        self.len(excerpt.highlights.len());
+        for range in &excerpt.highlights {
+            self.u64(range.utf16_start);
+            self.u64(range.utf16_end);
+            self.u8(highlight_kind_tag(range.kind));
+        }
+        self.u64(excerpt.line_number);
+    }
+
+    fn request(&mut self, request: &MatchedExcerptRequest) {
+        self.str(&request.item_id);
+        self.str(&request.query);
+        self.u8(match request.presentation_profile {
+            ListPresentationProfile::CompactRow => 0,
+            ListPresentationProfile::Card => 1,
+        });
+        self.str(&request.content_hash);
+    }
+
+    fn placeholder(&mut self, placeholder: &ExcerptPlaceholder) {
+        match placeholder {
+            ExcerptPlaceholder::Baseline { excerpt } => {
+                self.u8(0);
+                self.baseline(excerpt);
+            }
+            ExcerptPlaceholder::CompatibleCached {
+                source_query,
+                excerpt,
+            } => {
+                self.u8(1);
+                self.str(source_query);
+                self.matched(excerpt);
+            }
+            ExcerptPlaceholder::Provisional { excerpt } => {
+                self.u8(2);
+                self.baseline(excerpt);
+            }
+        }
+    }
+}
+
+fn icon_type_tag(icon_type: IconType) -> u8 {
+    match icon_type {
+        IconType::Text => 0,
+        IconType::Link => 1,
+        IconType::Image => 2,
+        IconType::Color => 3,
+        IconType::File => 4,
+        IconType::Code => 5,
+        IconType::Document => 6,
+        IconType::PhoneNumber => 7,
+        IconType::PostalAddress => 8,
+        IconType::Markdown => 9,
+    }
+}
+
+fn code_language_tag(language: CodeLanguage) -> u8 {
+    match language {
+        CodeLanguage::Rust => 0,
+        CodeLanguage::Swift => 1,
+        CodeLanguage::Python => 2,
+        CodeLanguage::JavaScript => 3,
+        CodeLanguage::Shell => 4,
+        CodeLanguage::Sql => 5,
+        CodeLanguage::Json => 6,
+        CodeLanguage::Html => 7,
+        CodeLanguage::CFamily => 8,
+    }
+}
+
+fn document_kind_tag(kind: DocumentKind) -> u8 {
+    match kind {
+        DocumentKind::Pdf => 0,
+        DocumentKind::Image => 1,
+        DocumentKind::Audio => 2,
+        DocumentKind::Video => 3,
+        DocumentKind::Archive => 4,
+        DocumentKind::Spreadsheet => 5,
+        DocumentKind::Presentation => 6,
+        DocumentKind::WordProcessing => 7,
+        DocumentKind::PlainText => 8,
+        DocumentKind::SourceCode => 9,
+        DocumentKind::Folder => 10,
+        DocumentKind::Application => 11,
+        DocumentKind::Generic => 12,
+    }
+}
+
+fn highlight_kind_tag(kind: HighlightKind) -> u8 {
+    match kind {
+        HighlightKind::Exact => 0,
+        HighlightKind::Prefix => 1,
+        HighlightKind::PrefixTail => 2,
+        HighlightKind::SubwordPrefix => 3,
+        HighlightKind::Substring => 4,
+        HighlightKind::Fuzzy => 5,
+        HighlightKind::Subsequence => 6,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::Utf16HighlightRange;
+
+    #[test]
+    fn packs_a_match_in_the_documented_layout() {
+        let matches = [ItemMatch {
+            item_metadata: ItemMetadata {
+                item_id: "id".into(),
+                icon: ItemIcon::AppBadged {
+                    icon_type: IconType::Link,
+                    bundle_id: "b".into(),
+                },
+                source_app: Some("S".into()),
+                source_app_bundle_id: None,
+                timestamp_unix: -2,
+                tags: vec![ItemTag::Bookmark],
+                is_pinned: true,
+            },
+            presentation: RowPresentation::Matched {
+                excerpt: MatchedExcerpt {
+                    text: "hé".into(),
+                    highlights: vec![Utf16HighlightRange {
+                        utf16_start: 0,
+                        utf16_end: 2,
+                        kind: HighlightKind::Prefix,
+                    }],
+                    line_number: 1,
+                },
+            },
+        }];
+
+        let mut expected = b"CKPM\x04".to_vec();
+        expected.extend_from_slice(&1u32.to_le_bytes());
+        expected.extend_from_slice(&[2, 0, 0, 0, b'i', b'd']);
+        expected.extend_from_slice(&[5, 1, 1, 0, 0, 0, b'b']);
+        expected.extend_from_slice(&[1, 1, 0, 0, 0, b'S', 0]);
+        expected.extend_from_slice(&(-2i64).to_le_bytes());
+        expected.extend_from_slice(&[1, 0, 0, 0, 0, 1]);
+        expected.extend_from_slice(&[1, 3, 0, 0, 0, b'h', 0xc3, 0xa9]);
+        expected.extend_from_slice(&1u32.to_le_bytes());
+        expected.extend_from_slice(&0u64.to_le_bytes());
+        expected.extend_from_slice(&2u64.to_le_bytes());
+        expected.push(1);
+        expected.extend_from_slice(&1u64.to_le_bytes());
+
+        assert_eq!(pack_matches(&matches), expected);
+    }
+
+    /// The Swift case list passed to the decoder's `tag("<field>", [...])`.
+    fn swift_tag_cases<'a>(decoder: &'a str, field: &str) -> Vec<&'a str> {
+        let start = format!("tag(\"{field}\", [");
+        let list = &decoder[decoder.find(&start).expect(field) + start.len()..];
+        list[..list.find(']').unwrap()]
+            .split(',')
+            .map(|case| case.trim().trim_start_matches('.'))
+            .filter(|case| !case.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn swift_decoder_matches_the_version_and_tag_tables() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../Sources/ClipKittyRustWrapper/PackedMatchDecoder.swift");
+        let decoder = std::fs::read_to_string(path).unwrap();
+        assert!(decoder.contains(&format!(
+            "supportedVersion: UInt8 = {PACKED_MATCHES_VERSION}\n"
+        )));
+
+        let check = |field: &str, tags: &[(u8, &str)]| {
+            let mut expected = tags.to_vec();
+            expected.sort();
+            let expected: Vec<&str> = expected.into_iter().map(|(_, case)| case).collect();
+            assert_eq!(swift_tag_cases(&decoder, field), expected, "{field}");
+        };
+        check(
+            "icon_type",
+            &[
+                (icon_type_tag(IconType::Text), "text"),
+                (icon_type_tag(IconType::Link), "link"),
+                (icon_type_tag(IconType::Image), "image"),
+                (icon_type_tag(IconType::Color), "color"),
+                (icon_type_tag(IconType::File), "file"),
+                (icon_type_tag(IconType::Code), "code"),
+                (icon_type_tag(IconType::Document), "document"),
+                (icon_type_tag(IconType::PhoneNumber), "phoneNumber"),
+                (icon_type_tag(IconType::PostalAddress), "postalAddress"),
+                (icon_type_tag(IconType::Markdown), "markdown"),
+            ],
+        );
+        check(
+            "code_language",
+            &[
+                (code_language_tag(CodeLanguage::Rust), "rust"),
+                (code_language_tag(CodeLanguage::Swift), "swift"),
+                (code_language_tag(CodeLanguage::Python), "python"),
+                (code_language_tag(CodeLanguage::JavaScript), "javaScript"),
+                (code_language_tag(CodeLanguage::Shell), "shell"),
+                (code_language_tag(CodeLanguage::Sql), "sql"),
+                (code_language_tag(CodeLanguage::Json), "json"),
+                (code_language_tag(CodeLanguage::Html), "html"),
+                (code_language_tag(CodeLanguage::CFamily), "cFamily"),
+            ],
+        );
+        check(
+            "document_kind",
+            &[
+                (document_kind_tag(DocumentKind::Pdf), "pdf"),
+                (document_kind_tag(DocumentKind::Image), "image"),
+                (document_kind_tag(DocumentKind::Audio), "audio"),
+                (document_kind_tag(DocumentKind::Video), "video"),
+                (document_kind_tag(DocumentKind::Archive), "archive"),
+                (document_kind_tag(DocumentKind::Spreadsheet), "spreadsheet"),
+                (
+                    document_kind_tag(DocumentKind::Presentation),
+                    "presentation",
+                ),
+                (
+                    document_kind_tag(DocumentKind::WordProcessing),
+                    "wordProcessing",
+                ),
+                (document_kind_tag(DocumentKind::PlainText), "plainText"),
+                (document_kind_tag(DocumentKind::SourceCode), "sourceCode"),
+                (document_kind_tag(DocumentKind::Folder), "folder"),
+                (document_kind_tag(DocumentKind::Application), "application"),
+                (document_kind_tag(DocumentKind::Generic), "generic"),
+            ],
+        );
+        check(
+            "highlight_kind",
+            &[
+                (highlight_kind_tag(HighlightKind::Exact), "exact"),
+                (highlight_kind_tag(HighlightKind::Prefix), "prefix"),
+                (highlight_kind_tag(HighlightKind::PrefixTail), "prefixTail"),
+                (
+                    highlight_kind_tag(HighlightKind::SubwordPrefix),
+                    "subwordPrefix",
+                ),
+                (highlight_kind_tag(HighlightKind::Substring), "substring"),
+                (highlight_kind_tag(HighlightKind::Fuzzy), "fuzzy"),
+                (
+                    highlight_kind_tag(HighlightKind::Subsequence),
+                    "subsequence",
+                ),
+            ],
+        );
+    }
+}
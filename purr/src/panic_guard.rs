@@ -0,0 +1,161 @@
+//! Panic containment for FFI entry points.
+//!
+//! A panic unwinding into Swift aborts the app with no Rust context. Entry
+//! points run through `contain`, which turns a panic into
+//! `ClipKittyError::Internal` and records a breadcrumb (operation, message,
+//! location, backtrace) the app can attach to its crash reports. The backtrace
+//! is captured by a panic hook, since the unwound payload no longer has it;
+//! the hook only pays for it on threads inside `contain`. This relies on the
+//! release profile unwinding rather than aborting on panic.
+
+use crate::database::Database;
+use crate::interface::ClipKittyError;
+use futures::FutureExt;
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::future::{poll_fn, Future};
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::pin;
+use std::sync::Once;
+
+static HOOK_INSTALLED: Once = Once::new();
+
+struct PanicCapture {
+    location: Option<String>,
+    backtrace: String,
+}
+
+thread_local! {
+    static LAST_PANIC: RefCell<Option<PanicCapture>> = const { RefCell::new(None) };
+    /// How many `contain` calls are running on this thread.
+    static CONTAINING: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Marks the current thread as inside `contain` until dropped.
+struct Containing;
+
+impl Containing {
+    fn enter() -> Self {
+        CONTAINING.with(|depth| depth.set(depth.get() + 1));
+        Containing
+    }
+}
+
+impl Drop for Containing {
+    fn drop(&mut self) {
+        CONTAINING.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// Run `operation`, converting a panic into `ClipKittyError::Internal`.
+pub(crate) fn contain<T>(
+    db: &Database,
+    operation: &'static str,
+    body: impl FnOnce() -> Result<T, ClipKittyError>,
+) -> Result<T, ClipKittyError> {
+    install_hook();
+    let result = {
+        let _containing = Containing::enter();
+        panic::catch_unwind(AssertUnwindSafe(body))
+    };
+    match result {
+        Ok(result) => result,
+        Err(payload) => Err(record_panic(db, operation, payload.as_ref())),
+    }
+}
+
+/// `contain` for async entry points: a panic while polling `body` becomes
+/// `ClipKittyError::Internal`.
+pub(crate) async fn contain_async<T>(
+    db: &Database,
+    operation: &'static str,
+    body: impl Future<Output = Result<T, ClipKittyError>>,
+) -> Result<T, ClipKittyError> {
+    install_hook();
+    let mut body = pin!(body);
+    let polled = AssertUnwindSafe(poll_fn(|cx| {
+        let _containing = Containing::enter();
+        body.as_mut().poll(cx)
+    }))
+    .catch_unwind()
+    .await;
+    match polled {
+        Ok(result) => result,
+        Err(payload) => Err(record_panic(db, operation, payload.as_ref())),
+    }
+}
+
+/// Chain a hook that stashes the backtrace for `contain`, keeping the
+/// previous hook's behaviour (stderr report, test harness capture). Panics
+/// outside `contain` pass straight through without a capture.
+fn install_hook() {
+    HOOK_INSTALLED.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if CONTAINING.with(Cell::get) == 0 {
+                previous(info);
+                return;
+            }
+            let capture = PanicCapture {
+                location: info
+                    .location()
+                    .map(|location| format!("{}:{}", location.file(), location.line())),
+                backtrace: Backtrace::force_capture().to_string(),
+            };
+            LAST_PANIC.with(|slot| *slot.borrow_mut() = Some(capture));
+            previous(info);
+        }));
+    });
+}
+
+fn record_panic(db: &Database, operation: &str, payload: &(dyn Any + Send)) -> ClipKittyError {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string());
+    let capture = LAST_PANIC.with(|slot| slot.borrow_mut().take());
+    let (message, backtrace) = match capture {
+        Some(PanicCapture {
+            location: Some(location),
+            backtrace,
+        }) => (format!("{message} at {location}"), backtrace),
+        Some(PanicCapture { backtrace, .. }) => (message, backtrace),
+        None => (message, String::new()),
+    };
+    // Best-effort: the panic may have come from the database itself.
+    let _ = db.insert_crash_breadcrumb(operation, &message, &backtrace);
+    ClipKittyError::Internal(format!("{operation}: {message}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contained_panics_become_errors_with_breadcrumbs() {
+        let db = Database::open_in_memory().unwrap();
+        let result: Result<(), _> = contain(&db, "sync_call", || panic!("sync failure"));
+        assert!(
+            matches!(result, Err(ClipKittyError::Internal(message)) if message.contains("sync failure"))
+        );
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let result: Result<(), _> = runtime.block_on(contain_async(&db, "async_call", async {
+            tokio::task::yield_now().await;
+            panic!("async failure")
+        }));
+        assert!(
+            matches!(result, Err(ClipKittyError::Internal(message)) if message.contains("async failure"))
+        );
+
+        let breadcrumbs = db.fetch_crash_breadcrumbs(10).unwrap();
+        assert_eq!(breadcrumbs.len(), 2);
+        assert_eq!(breadcrumbs[0].operation, "async_call");
+        assert!(!breadcrumbs[0].backtrace.is_empty());
+        assert_eq!(CONTAINING.with(Cell::get), 0);
+    }
+}
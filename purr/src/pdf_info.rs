@@ -0,0 +1,551 @@
+//! Page count, title, and author of copied PDF files.
+//!
+//! Read in Rust at capture from the document info dictionary and the page
+//! tree, so a PDF is findable by its internal title rather than only by a
+//! filename like `scan_0042.pdf`. Objects packed into compressed object
+//! streams (PDF 1.5 and later) are unpacked; encrypted documents give their
+//! page count only. The app renders page one with PDFKit for the thumbnail.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+use crate::interface::DocumentInfo;
+
+/// Largest PDF read at capture; bigger ones are left to the app.
+const MAX_PDF_BYTES: u64 = 64 * 1024 * 1024;
+/// Largest decompressed object stream unpacked.
+const MAX_OBJECT_STREAM_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Page count, title, and author of the PDF at `path`, if it is one.
+pub(crate) fn probe(path: &Path) -> Option<DocumentInfo> {
+    let file = std::fs::File::open(path).ok()?;
+    if file.metadata().ok()?.len() > MAX_PDF_BYTES {
+        return None;
+    }
+    let mut data = Vec::new();
+    file.take(MAX_PDF_BYTES).read_to_end(&mut data).ok()?;
+    parse(&data)
+}
+
+/// `info` as one metadata line, as in "Lease Agreement · Jane Doe · 12 pages".
+pub(crate) fn summary(info: &DocumentInfo) -> String {
+    let pages = info.page_count.map(|count| match count {
+        1 => "1 page".to_string(),
+        count => format!("{count} pages"),
+    });
+    [info.title.clone(), info.author.clone(), pages]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+/// A document's title and author as search text, one per line.
+pub(crate) fn index_text(title: Option<&str>, author: Option<&str>) -> Option<String> {
+    let lines: Vec<&str> = [title, author].into_iter().flatten().collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+fn parse(data: &[u8]) -> Option<DocumentInfo> {
+    if !data.starts_with(b"%PDF-") {
+        return None;
+    }
+    let objects = collect_objects(data);
+    // Trailers and cross-reference stream dictionaries stay uncompressed; the
+    // last one written describes the document as it is now.
+    let page_count = reference_after_last(data, b"/Root")
+        .and_then(|root| objects.get(&root))
+        .and_then(|root| reference(value_of(root, b"/Pages")?))
+        .and_then(|pages| objects.get(&pages))
+        .and_then(|pages| integer(value_of(pages, b"/Count")?))
+        .or_else(|| {
+            objects
+                .values()
+                .filter(|body| is_page_tree(body))
+                .filter_map(|body| integer(value_of(body, b"/Count")?))
+                .max()
+        });
+    let encrypted = rfind(data, b"/Encrypt").is_some();
+    let info = (!encrypted)
+        .then(|| reference_after_last(data, b"/Info"))
+        .flatten()
+        .and_then(|info| objects.get(&info));
+    let text_entry = |key: &[u8]| {
+        let value = value_of(info?, key)?;
+        let value = match reference(value) {
+            Some(target) => objects.get(&target)?.as_ref(),
+            None => value,
+        };
+        text_string(value)
+    };
+    let title = text_entry(b"/Title");
+    let author = text_entry(b"/Author");
+    (page_count.is_some() || title.is_some() || author.is_some()).then_some(DocumentInfo {
+        page_count,
+        title,
+        author,
+    })
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Objects
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Every object's body by object number. Later definitions replace earlier
+/// ones, as incremental updates do; objects in object streams fill gaps.
+fn collect_objects(data: &[u8]) -> HashMap<u32, Cow<'_, [u8]>> {
+    let mut objects = HashMap::new();
+    let mut at = 0;
+    while let Some(found) = find(&data[at..], b"obj") {
+        let keyword = at + found;
+        at = keyword + 3;
+        if data.get(at).is_some_and(|&byte| is_regular(byte)) {
+            continue;
+        }
+        let Some(number) = object_number_before(data, keyword) else {
+            continue;
+        };
+        let end = find(&data[at..], b"endobj").map_or(data.len(), |end| at + end);
+        objects.insert(number, Cow::Borrowed(&data[at..end]));
+        at = end;
+    }
+
+    let streams: Vec<ObjectStream> = objects
+        .values()
+        .filter(|body| value_of(body, b"/Type").is_some_and(|value| value.starts_with(b"/ObjStm")))
+        .filter_map(|body| unpack_object_stream(body))
+        .collect();
+    for (number, body) in streams.iter().flat_map(ObjectStream::objects) {
+        objects
+            .entry(number)
+            .or_insert_with(|| Cow::Owned(body.to_vec()));
+    }
+    objects
+}
+
+/// The number of the object whose `obj` keyword starts at `keyword`, read
+/// back over `<number> <generation> `.
+fn object_number_before(data: &[u8], keyword: usize) -> Option<u32> {
+    let mut end = keyword;
+    let skip_space = |mut end: usize| {
+        while end > 0 && data[end - 1].is_ascii_whitespace() {
+            end -= 1;
+        }
+        end
+    };
+    let skip_digits = |mut end: usize| {
+        let stop = end;
+        while end > 0 && data[end - 1].is_ascii_digit() {
+            end -= 1;
+        }
+        (end < stop).then_some(end)
+    };
+    end = skip_space(end);
+    end = skip_digits(end)?;
+    let number_end = skip_space(end);
+    if number_end == end {
+        return None;
+    }
+    let number_start = skip_digits(number_end)?;
+    std::str::from_utf8(&data[number_start..number_end])
+        .ok()?
+        .parse()
+        .ok()
+}
+
+/// A decompressed object stream: `count` objects, numbered in a header of
+/// number and offset pairs, with offsets counted from `first`.
+struct ObjectStream {
+    count: usize,
+    first: usize,
+    data: Vec<u8>,
+}
+
+impl ObjectStream {
+    fn objects(&self) -> Vec<(u32, &[u8])> {
+        let Some(header) = self.data.get(..self.first) else {
+            return Vec::new();
+        };
+        let pairs: Vec<u64> = numbers(header).take(self.count.saturating_mul(2)).collect();
+        // Offsets past the end of memory come from a damaged header; skip them.
+        let entries: Vec<(u32, usize)> = pairs
+            .chunks_exact(2)
+            .filter_map(|pair| {
+                let offset = usize::try_from(pair[1]).ok()?;
+                Some((pair[0] as u32, self.first.checked_add(offset)?))
+            })
+            .collect();
+        entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &(number, start))| {
+                let end = entries
+                    .get(index + 1)
+                    .map_or(self.data.len(), |&(_, next)| next);
+                Some((number, self.data.get(start..end.max(start))?))
+            })
+            .collect()
+    }
+}
+
+fn unpack_object_stream(body: &[u8]) -> Option<ObjectStream> {
+    let filter = value_of(body, b"/Filter")?;
+    if !filter.starts_with(b"/FlateDecode") && !filter.starts_with(b"[/FlateDecode]") {
+        return None;
+    }
+    let mut start = find(body, b"stream")? + b"stream".len();
+    if body.get(start) == Some(&b'\r') {
+        start += 1;
+    }
+    if body.get(start) == Some(&b'\n') {
+        start += 1;
+    }
+    let end = rfind(body, b"endstream").unwrap_or(body.len()).max(start);
+    let mut data = Vec::new();
+    flate2::read::ZlibDecoder::new(&body[start..end])
+        .take(MAX_OBJECT_STREAM_BYTES)
+        .read_to_end(&mut data)
+        .ok()?;
+    Some(ObjectStream {
+        count: value_of(body, b"/N").and_then(integer)? as usize,
+        first: value_of(body, b"/First").and_then(integer)? as usize,
+        data,
+    })
+}
+
+fn numbers(data: &[u8]) -> impl Iterator<Item = u64> + '_ {
+    data.split(|byte| byte.is_ascii_whitespace())
+        .filter_map(|word| std::str::from_utf8(word).ok()?.parse().ok())
+}
+
+fn is_page_tree(body: &[u8]) -> bool {
+    value_of(body, b"/Type")
+        .is_some_and(|value| value.starts_with(b"/Pages") && !is_regular_at(value, 6))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Values
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// The bytes following `key` in `dict`, from the start of its value.
+fn value_of<'a>(dict: &'a [u8], key: &[u8]) -> Option<&'a [u8]> {
+    let mut at = 0;
+    loop {
+        let found = at + find(&dict[at..], key)?;
+        at = found + key.len();
+        if !is_regular_at(dict, at) {
+            return Some(skip_whitespace(&dict[at..]));
+        }
+    }
+}
+
+/// The object `key` refers to in the last place it appears in `data`.
+fn reference_after_last(data: &[u8], key: &[u8]) -> Option<u32> {
+    let mut end = data.len();
+    while let Some(found) = rfind(&data[..end], key) {
+        if let Some(target) = value_of(&data[found..], key).and_then(reference) {
+            return Some(target);
+        }
+        end = found;
+    }
+    None
+}
+
+/// The object number of an indirect reference like `12 0 R`.
+fn reference(value: &[u8]) -> Option<u32> {
+    let mut words = value
+        .split(|byte| byte.is_ascii_whitespace())
+        .filter(|word| !word.is_empty());
+    let number = std::str::from_utf8(words.next()?).ok()?.parse().ok()?;
+    words.next()?.iter().all(u8::is_ascii_digit).then_some(())?;
+    words.next()?.starts_with(b"R").then_some(number)
+}
+
+fn integer(value: &[u8]) -> Option<u32> {
+    let digits = value
+        .iter()
+        .take_while(|byte| byte.is_ascii_digit())
+        .count();
+    let number: u32 = std::str::from_utf8(&value[..digits]).ok()?.parse().ok()?;
+    // A reference is not a count.
+    reference(value).is_none().then_some(number)
+}
+
+/// A literal `(...)` or hex `<...>` string as text.
+fn text_string(value: &[u8]) -> Option<String> {
+    let bytes = match value.first()? {
+        b'(' => literal_string(&value[1..]),
+        b'<' if value.get(1) != Some(&b'<') => hex_string(&value[1..]),
+        _ => return None,
+    };
+    let text = decode_text(&bytes);
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn literal_string(data: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut depth = 0;
+    let mut at = 0;
+    while let Some(&byte) = data.get(at) {
+        at += 1;
+        match byte {
+            b'\\' => {
+                let Some(&escaped) = data.get(at) else {
+                    break;
+                };
+                at += 1;
+                match escaped {
+                    b'n' => bytes.push(b'\n'),
+                    b'r' => bytes.push(b'\r'),
+                    b't' => bytes.push(b'\t'),
+                    b'b' => bytes.push(8),
+                    b'f' => bytes.push(12),
+                    b'0'..=b'7' => {
+                        let mut code = u32::from(escaped - b'0');
+                        for _ in 0..2 {
+                            match data.get(at) {
+                                Some(&digit @ b'0'..=b'7') => {
+                                    code = code * 8 + u32::from(digit - b'0');
+                                    at += 1;
+                                }
+                                _ => break,
+                            }
+                        }
+                        bytes.push(code as u8);
+                    }
+                    // A backslash before a line break continues the line.
+                    b'\r' => {
+                        if data.get(at) == Some(&b'\n') {
+                            at += 1;
+                        }
+                    }
+                    b'\n' => {}
+                    other => bytes.push(other),
+                }
+            }
+            b'(' => {
+                depth += 1;
+                bytes.push(byte);
+            }
+            b')' if depth == 0 => break,
+            b')' => {
+                depth -= 1;
+                bytes.push(byte);
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    bytes
+}
+
+fn hex_string(data: &[u8]) -> Vec<u8> {
+    let mut digits: Vec<u8> = data
+        .iter()
+        .take_while(|&&byte| byte != b'>')
+        .filter_map(|&byte| (byte as char).to_digit(16).map(|digit| digit as u8))
+        .collect();
+    // An odd final digit is followed by an implied 0.
+    if digits.len() % 2 == 1 {
+        digits.push(0);
+    }
+    digits
+        .chunks_exact(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect()
+}
+
+/// Text strings are UTF-16BE or UTF-8 after a byte order mark, and
+/// otherwise PDFDocEncoding, read here as Latin-1.
+fn decode_text(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = utf16
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else if let Some(utf8) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        String::from_utf8_lossy(utf8).into_owned()
+    } else {
+        bytes.iter().map(|&byte| byte as char).collect()
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Bytes
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Whether `byte` can continue a name or keyword, unlike whitespace and
+/// delimiters.
+fn is_regular(byte: u8) -> bool {
+    !byte.is_ascii_whitespace() && !b"()<>[]{}/%".contains(&byte)
+}
+
+fn is_regular_at(data: &[u8], at: usize) -> bool {
+    data.get(at).is_some_and(|&byte| is_regular(byte))
+}
+
+fn skip_whitespace(data: &[u8]) -> &[u8] {
+    let start = data
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+        .unwrap_or(data.len());
+    &data[start..]
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .rposition(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn pdf(objects: &[&[u8]], trailer: &str) -> Vec<u8> {
+        let mut data = b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        for (index, body) in objects.iter().enumerate() {
+            data.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+            data.extend_from_slice(body);
+            data.extend_from_slice(b"\nendobj\n");
+        }
+        data.extend_from_slice(format!("trailer\n{trailer}\n%%EOF\n").as_bytes());
+        data
+    }
+
+    #[test]
+    fn reads_title_author_and_page_count_from_the_info_dictionary() {
+        let data = pdf(
+            &[
+                b"<< /Type /Catalog /Pages 2 0 R >>",
+                b"<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>",
+                b"<< /Type /Page /Parent 2 0 R >>",
+                b"<< /Type /Page /Parent 2 0 R >>",
+                b"<< /Title (Lease Agreement \\(2024\\)) /Author <FEFF004A006F00E9> >>",
+            ],
+            "<< /Size 6 /Root 1 0 R /Info 5 0 R >>",
+        );
+        assert_eq!(
+            parse(&data),
+            Some(DocumentInfo {
+                page_count: Some(2),
+                title: Some("Lease Agreement (2024)".into()),
+                author: Some("Jo\u{e9}".into()),
+            })
+        );
+    }
+
+    /// A PDF whose objects 1 and 2 are packed in an object stream with this
+    /// `header`, followed by `packed`.
+    fn pdf_with_object_stream(header: &str, packed: &str) -> Vec<u8> {
+        let packed = format!("{header}{packed}");
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(packed.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let mut stream = format!(
+            "<< /Type /ObjStm /N 2 /First {} /Filter /FlateDecode /Length {} >>\nstream\n",
+            header.len(),
+            compressed.len()
+        )
+        .into_bytes();
+        stream.extend_from_slice(&compressed);
+        stream.extend_from_slice(b"\nendstream");
+        let mut data = b"%PDF-1.7\n".to_vec();
+        data.extend_from_slice(b"3 0 obj\n");
+        data.extend_from_slice(&stream);
+        data.extend_from_slice(b"\nendobj\n4 0 obj\n<< /Title (Quarterly Report) >>\nendobj\n");
+        data.extend_from_slice(
+            b"5 0 obj\n<< /Type /XRef /Root 1 0 R /Info 4 0 R >>\nstream\nendstream\nendobj\n",
+        );
+        data
+    }
+
+    #[test]
+    fn reads_objects_packed_in_compressed_object_streams() {
+        let catalog = "<< /Type /Catalog /Pages 2 0 R >>";
+        let data = pdf_with_object_stream(
+            &format!("1 0 2 {} ", catalog.len()),
+            &format!("{catalog}<< /Type /Pages /Kids [] /Count 14 >>"),
+        );
+
+        let info = parse(&data).unwrap();
+        assert_eq!(info.page_count, Some(14));
+        assert_eq!(info.title.as_deref(), Some("Quarterly Report"));
+        assert_eq!(info.author, None);
+    }
+
+    #[test]
+    fn skips_object_stream_offsets_past_the_end_of_memory() {
+        let data = pdf_with_object_stream(
+            &format!("1 0 2 {} ", u64::MAX),
+            "<< /Type /Catalog /Pages 2 0 R >><< /Type /Pages /Count 14 >>",
+        );
+
+        let info = parse(&data).unwrap();
+        assert_eq!(info.page_count, None);
+        assert_eq!(info.title.as_deref(), Some("Quarterly Report"));
+    }
+
+    #[test]
+    fn later_updates_replace_earlier_info() {
+        let mut data = pdf(
+            &[
+                b"<< /Type /Catalog /Pages 2 0 R >>",
+                b"<< /Type /Pages /Count 1 >>",
+                b"<< /Title (Draft) >>",
+            ],
+            "<< /Root 1 0 R /Info 3 0 R >>",
+        );
+        data.extend_from_slice(
+            b"4 0 obj\n<< /Title (Final) >>\nendobj\ntrailer\n<< /Root 1 0 R /Info 4 0 R >>\n",
+        );
+        assert_eq!(parse(&data).unwrap().title.as_deref(), Some("Final"));
+    }
+
+    #[test]
+    fn encrypted_documents_give_only_their_page_count() {
+        let data = pdf(
+            &[
+                b"<< /Type /Catalog /Pages 2 0 R >>",
+                b"<< /Type /Pages /Count 3 >>",
+                b"<< /Title <9f3a11> >>",
+            ],
+            "<< /Root 1 0 R /Info 3 0 R /Encrypt 4 0 R >>",
+        );
+        assert_eq!(
+            parse(&data),
+            Some(DocumentInfo {
+                page_count: Some(3),
+                title: None,
+                author: None,
+            })
+        );
+    }
+
+    #[test]
+    fn ignores_files_that_are_not_pdfs() {
+        assert_eq!(parse(b"PK\x03\x04 not a pdf"), None);
+        assert_eq!(parse(b"%PDF-1.4\n"), None);
+    }
+
+    #[test]
+    fn summarizes_known_fields() {
+        let info = DocumentInfo {
+            page_count: Some(1),
+            title: Some("Lease".into()),
+            author: None,
+        };
+        assert_eq!(summary(&info), "Lease · 1 page");
+    }
+}
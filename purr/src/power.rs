@@ -0,0 +1,62 @@
+//! Low-power mode.
+//!
+//! While the device is in low-power mode, parallel work runs on a small
+//! dedicated rayon pool instead of the global one, the index writer uses a
+//! single thread and skips segment merges, and search stops decorating rows
+//! ahead of the first one. Merges and maintenance catch up after the mode ends.
+
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const LOW_POWER_THREADS: usize = 1;
+
+static LOW_POWER: AtomicBool = AtomicBool::new(false);
+
+static LOW_POWER_POOL: Lazy<Option<rayon::ThreadPool>> = Lazy::new(|| {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(LOW_POWER_THREADS)
+        .thread_name(|i| format!("clipkitty-low-power-{}", i))
+        .start_handler(|_| {
+            use thread_priority::*;
+            let _ = set_current_thread_priority(ThreadPriority::Min);
+        })
+        .build()
+        .ok()
+});
+
+pub(crate) fn set_low_power(low_power: bool) {
+    LOW_POWER.store(low_power, Ordering::Relaxed);
+}
+
+pub(crate) fn is_low_power() -> bool {
+    LOW_POWER.load(Ordering::Relaxed)
+}
+
+/// Run rayon work on the pool matching the current power state.
+pub(crate) fn install<R: Send>(op: impl FnOnce() -> R + Send) -> R {
+    install_for(is_low_power(), op)
+}
+
+fn install_for<R: Send>(low_power: bool, op: impl FnOnce() -> R + Send) -> R {
+    match LOW_POWER_POOL.as_ref() {
+        Some(pool) if low_power => pool.install(op),
+        _ => op(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn low_power_work_runs_on_the_small_pool() {
+        assert_eq!(
+            install_for(true, rayon::current_num_threads),
+            LOW_POWER_THREADS
+        );
+        assert_eq!(
+            install_for(false, rayon::current_num_threads),
+            rayon::current_num_threads()
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Namespaced app preferences kept in the store's database.
+//!
+//! Settings live beside the history they govern, so they move with the
+//! database file: `move_store`, sync-safe snapshots, and file backups all
+//! carry them. Values are stored as tagged JSON in the shared `key_values`
+//! table, under a `pref:` prefix that keeps app namespaces apart from the
+//! store's own slots.
+
+use crate::database::{Database, DatabaseError};
+use crate::interface::{ClipKittyError, PrefEntry, PrefValue};
+use serde_json::{json, Value};
+
+const NAMESPACE_PREFIX: &str = "pref:";
+
+fn storage_namespace(namespace: &str) -> Result<String, ClipKittyError> {
+    if namespace.trim().is_empty() {
+        return Err(ClipKittyError::InvalidInput(
+            "preference namespace is empty".into(),
+        ));
+    }
+    Ok(format!("{NAMESPACE_PREFIX}{namespace}"))
+}
+
+pub(crate) fn set(
+    db: &Database,
+    namespace: &str,
+    key: &str,
+    value: &PrefValue,
+) -> Result<(), ClipKittyError> {
+    if key.is_empty() {
+        return Err(ClipKittyError::InvalidInput(
+            "preference key is empty".into(),
+        ));
+    }
+    let encoded = encode(value).to_string();
+    Ok(db.put_key_value(&storage_namespace(namespace)?, key, encoded.as_bytes())?)
+}
+
+pub(crate) fn get(
+    db: &Database,
+    namespace: &str,
+    key: &str,
+) -> Result<Option<PrefValue>, ClipKittyError> {
+    db.get_key_value(&storage_namespace(namespace)?, key)?
+        .map(|bytes| decode(key, &bytes))
+        .transpose()
+}
+
+pub(crate) fn remove(db: &Database, namespace: &str, key: &str) -> Result<(), ClipKittyError> {
+    Ok(db.delete_key_value(&storage_namespace(namespace)?, key)?)
+}
+
+/// Every preference in `namespace`, sorted by key.
+pub(crate) fn list(db: &Database, namespace: &str) -> Result<Vec<PrefEntry>, ClipKittyError> {
+    db.fetch_key_values(&storage_namespace(namespace)?)?
+        .into_iter()
+        .map(|(key, bytes)| {
+            let value = decode(&key, &bytes)?;
+            Ok(PrefEntry { key, value })
+        })
+        .collect()
+}
+
+fn encode(value: &PrefValue) -> Value {
+    match value {
+        PrefValue::Bool { value } => json!({ "type": "bool", "value": value }),
+        PrefValue::Int { value } => json!({ "type": "int", "value": value }),
+        PrefValue::Double { value } => json!({ "type": "double", "value": value }),
+        PrefValue::Text { value } => json!({ "type": "text", "value": value }),
+        PrefValue::Data { value } => json!({ "type": "data", "value": value }),
+    }
+}
+
+fn decode(key: &str, bytes: &[u8]) -> Result<PrefValue, ClipKittyError> {
+    let invalid =
+        || DatabaseError::InconsistentData(format!("preference `{key}` has an unreadable value"));
+    let stored: Value = serde_json::from_slice(bytes).map_err(|_| invalid())?;
+    let value = &stored["value"];
+    let decoded = match stored["type"].as_str() {
+        Some("bool") => value.as_bool().map(|value| PrefValue::Bool { value }),
+        Some("int") => value.as_i64().map(|value| PrefValue::Int { value }),
+        Some("double") => value.as_f64().map(|value| PrefValue::Double { value }),
+        Some("text") => value.as_str().map(|value| PrefValue::Text {
+            value: value.to_string(),
+        }),
+        Some("data") => serde_json::from_value(value.clone())
+            .ok()
+            .map(|value| PrefValue::Data { value }),
+        _ => None,
+    };
+    decoded.ok_or_else(|| invalid().into())
+}
+
+/// The typed getters' error when a stored value has another type.
+pub(crate) fn type_mismatch(key: &str, expected: &str, found: &PrefValue) -> ClipKittyError {
+    let found = match found {
+        PrefValue::Bool { .. } => "bool",
+        PrefValue::Int { .. } => "int",
+        PrefValue::Double { .. } => "double",
+        PrefValue::Text { .. } => "text",
+        PrefValue::Data { .. } => "data",
+    };
+    ClipKittyError::InvalidInput(format!("preference `{key}` holds {found}, not {expected}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_round_trip_per_namespace() {
+        let db = Database::open_in_memory().unwrap();
+        let values = [
+            ("launch_at_login", PrefValue::Bool { value: true }),
+            ("max_items", PrefValue::Int { value: -3 }),
+            ("window_alpha", PrefValue::Double { value: 0.85 }),
+            (
+                "hotkey",
+                PrefValue::Text {
+                    value: "⌥Space".into(),
+                },
+            ),
+            (
+                "blob",
+                PrefValue::Data {
+                    value: vec![0, 255, 7],
+                },
+            ),
+        ];
+        for (key, value) in &values {
+            set(&db, "general", key, value).unwrap();
+        }
+        for (key, value) in &values {
+            assert_eq!(get(&db, "general", key).unwrap().as_ref(), Some(value));
+        }
+        assert_eq!(get(&db, "appearance", "max_items").unwrap(), None);
+        assert_eq!(list(&db, "general").unwrap().len(), values.len());
+        assert_eq!(list(&db, "general").unwrap()[0].key, "blob");
+
+        remove(&db, "general", "blob").unwrap();
+        assert_eq!(get(&db, "general", "blob").unwrap(), None);
+        assert!(set(&db, "", "key", &PrefValue::Bool { value: false }).is_err());
+    }
+
+    #[test]
+    fn app_namespaces_cannot_reach_store_slots() {
+        let db = Database::open_in_memory().unwrap();
+        db.put_key_value("ui", "state", b"opaque").unwrap();
+        assert_eq!(get(&db, "ui", "state").unwrap(), None);
+    }
+}
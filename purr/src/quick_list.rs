@@ -0,0 +1,136 @@
+//! Read model for the menu bar quick list.
+//!
+//! The menu bar extra redraws its short list every time it opens, so the
+//! rows are kept in memory and reused until the database's change sequence
+//! moves. A call that hits the cache reads one counter from SQLite and
+//! formats the ages; nothing else.
+
+use crate::database::{Database, QuickListRow};
+use crate::interface::{ClipKittyError, QuickList, QuickListItem};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+
+struct CachedRows {
+    change_sequence: u64,
+    /// Limit the rows were fetched with; smaller limits reuse them.
+    limit: usize,
+    rows: Vec<QuickListRow>,
+}
+
+#[derive(Default)]
+pub(crate) struct QuickListCache {
+    cached: Mutex<Option<CachedRows>>,
+}
+
+impl QuickListCache {
+    pub(crate) fn list(
+        &self,
+        db: &Database,
+        limit: usize,
+        now: DateTime<Utc>,
+    ) -> Result<QuickList, ClipKittyError> {
+        let change_sequence = db.change_sequence()?;
+        let mut cached = self.cached.lock();
+        let fresh = cached
+            .as_ref()
+            .is_some_and(|rows| rows.change_sequence == change_sequence && rows.limit >= limit);
+        if !fresh {
+            *cached = Some(CachedRows {
+                change_sequence,
+                limit,
+                rows: db.fetch_quick_list_rows(limit)?,
+            });
+        }
+        let rows = &cached.as_ref().expect("filled above").rows;
+        let items = rows
+            .iter()
+            .take(limit)
+            .map(
+                |(item_id, preview, icon_type, timestamp_unix, is_pinned)| QuickListItem {
+                    item_id: item_id.clone(),
+                    preview: preview.clone(),
+                    icon_type: *icon_type,
+                    timestamp_unix: *timestamp_unix,
+                    age: short_age(now.timestamp() - timestamp_unix),
+                    is_pinned: *is_pinned,
+                },
+            )
+            .collect();
+        Ok(QuickList {
+            change_sequence,
+            items,
+        })
+    }
+}
+
+/// Age in the largest whole unit, for a narrow menu column.
+fn short_age(seconds: i64) -> String {
+    const UNITS: [(i64, &str); 5] = [
+        (365 * 24 * 3600, "y"),
+        (7 * 24 * 3600, "w"),
+        (24 * 3600, "d"),
+        (3600, "h"),
+        (60, "m"),
+    ];
+    UNITS.iter().find(|(unit, _)| seconds >= *unit).map_or_else(
+        || "now".to_string(),
+        |(unit, suffix)| format!("{}{suffix}", seconds / unit),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::interface::IconType;
+    use crate::save_service::{self, InsertOutcome};
+
+    fn save(db: &Database, indexer: &Indexer, text: &str) -> i64 {
+        match save_service::save_text(db, indexer, text.into(), None, None).unwrap() {
+            InsertOutcome::Inserted { new_id, .. } => new_id,
+            InsertOutcome::Deduplicated { existing_id, .. } => existing_id,
+        }
+    }
+
+    #[test]
+    fn rows_are_reused_until_the_change_sequence_moves() {
+        let db = Database::open_in_memory().unwrap();
+        let indexer = Indexer::new_in_memory().unwrap();
+        let cache = QuickListCache::default();
+        let note = save(&db, &indexer, "  meeting\n  notes  ");
+        db.update_timestamp(note, Utc::now() - chrono::Duration::hours(3))
+            .unwrap();
+        save(&db, &indexer, "https://example.com");
+
+        let first = cache.list(&db, 10, Utc::now()).unwrap();
+        let previews: Vec<&str> = first
+            .items
+            .iter()
+            .map(|item| item.preview.as_str())
+            .collect();
+        assert_eq!(previews, ["https://example.com", "meeting notes"]);
+        assert_eq!(first.items[0].icon_type, IconType::Link);
+        assert_eq!(first.items[0].age, "now");
+        assert_eq!(first.items[1].age, "3h");
+
+        let again = cache.list(&db, 1, Utc::now()).unwrap();
+        assert_eq!(again.change_sequence, first.change_sequence);
+        assert_eq!(again.items, first.items[..1]);
+
+        db.set_pinned(note, true).unwrap();
+        let pinned = cache.list(&db, 10, Utc::now()).unwrap();
+        assert!(pinned.change_sequence > first.change_sequence);
+        assert!(pinned.items[0].is_pinned);
+        assert_eq!(pinned.items[0].preview, "meeting notes");
+    }
+
+    #[test]
+    fn ages_use_the_largest_whole_unit() {
+        assert_eq!(short_age(-5), "now");
+        assert_eq!(short_age(59), "now");
+        assert_eq!(short_age(60), "1m");
+        assert_eq!(short_age(2 * 86_400 + 5), "2d");
+        assert_eq!(short_age(20 * 86_400), "2w");
+        assert_eq!(short_age(800 * 86_400), "2y");
+    }
+}
@@ -15,20 +15,22 @@ mod alignment;
 mod folding;
 mod matching;
 mod policy;
+mod scratch;
 
 use crate::search::is_word_token;
 #[cfg(feature = "perf-log")]
 use std::time::Instant;
 
 use self::alignment::{alignment_exactness_signals, choose_best_alignment, trim_match_candidates};
-pub(crate) use self::folding::fold_str;
+pub(crate) use self::folding::{expand_compatibility, fold_into, fold_str, raw_char_span};
 pub use self::matching::edit_distance_bounded;
 #[cfg(test)]
 use self::matching::subsequence_match;
 pub(crate) use self::matching::{
     classify_fuzzy_edit, does_word_match, does_word_match_fast, does_word_match_fast_raw,
-    max_edit_distance, prefix_match_for_query_word, query_allows_fuzzy_recall, FuzzyEditKind,
-    PrefixMatch, WordMatchKind, NON_FINAL_PREFIX_MIN_QUERY_CHARS,
+    for_each_camel_case_part, max_edit_distance, prefix_match_for_query_word,
+    query_allows_fuzzy_recall, FuzzyEditKind, PrefixMatch, WordMatchKind,
+    NON_FINAL_PREFIX_MIN_QUERY_CHARS,
 };
 use self::policy::{compute_quality_detail, compute_quality_tier, compute_recency_bucket};
 #[cfg(test)]
@@ -38,8 +40,9 @@ use self::policy::{
     recency_bucket_last_week_max_age_secs,
 };
 pub use self::policy::{
-    BucketScore, CoverageBand, MatchClassBand, PhraseShapeBand, PrefixPreferenceBand,
-    PrefixPreferenceQuery, QualityDetail, QualityTier, RecencyBucket, LARGE_DOC_THRESHOLD_BYTES,
+    BucketScore, CoverageBand, FrecencyBand, MatchClassBand, PersonalizationBand, PhraseShapeBand,
+    PrefixPreferenceBand, PrefixPreferenceQuery, QualityDetail, QualityTier, RecencyBucket,
+    LARGE_DOC_THRESHOLD_BYTES,
 };
 
 /// Canonical parsed representation of the text that ranking consumes.
@@ -66,8 +69,8 @@ impl PreparedQuery {
         let tokens = crate::search::tokenize_words(raw_text)
             .into_iter()
             .map(|(_, _, raw)| PreparedQueryToken {
-                folded: fold_str(&raw),
-                raw,
+                folded: fold_str(raw),
+                raw: raw.to_string(),
             })
             .collect();
         let folded_text = if crate::search::is_symbol_bearing_query(raw_text) {
@@ -119,6 +122,10 @@ pub struct ScoringContext<'a> {
     pub timestamp: i64,
     /// Current time (unix seconds)
     pub now: i64,
+    /// Learned preference of the paste target for this document
+    pub personalization: PersonalizationBand,
+    /// How often and how lately this document was pasted
+    pub frecency: FrecencyBand,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -127,12 +134,30 @@ pub(crate) struct TokenSpan {
     end: usize,
 }
 
+/// Token positions `first..=last` holding the camelCase parts of one
+/// identifier, so that "refresh token" can align with `refreshAccessToken`.
+#[derive(Debug, Clone, Copy)]
+struct SplitIdentifier {
+    first: usize,
+    last: usize,
+}
+
 #[derive(Debug)]
 pub struct SmallPreparedDocument<'a> {
     content: &'a str,
     content_folded: String,
     token_spans: Vec<TokenSpan>,
-    folded_tokens: Vec<String>,
+    identifiers: Vec<SplitIdentifier>,
+    folded_tokens: FoldedTokens,
+}
+
+/// Where a small document's folded tokens live. ASCII folding keeps byte
+/// offsets, so the tokens are slices of `content_folded` at the raw spans;
+/// otherwise they are folded back to back into one arena string.
+#[derive(Debug)]
+enum FoldedTokens {
+    SameSpans,
+    Arena { text: String, spans: Vec<TokenSpan> },
 }
 
 #[derive(Debug)]
@@ -145,6 +170,7 @@ enum LargeFastCaseMode {
 pub struct LargeFastPreparedDocument<'a> {
     content: &'a str,
     token_spans: Vec<TokenSpan>,
+    identifiers: Vec<SplitIdentifier>,
     case_mode: LargeFastCaseMode,
 }
 
@@ -205,7 +231,31 @@ impl<'a> SmallPreparedDocument<'a> {
     }
 
     fn folded_token(&self, index: usize) -> &str {
-        self.folded_tokens[index].as_str()
+        match &self.folded_tokens {
+            FoldedTokens::SameSpans => {
+                raw_token_from(&self.content_folded, self.token_spans[index])
+            }
+            FoldedTokens::Arena { text, spans } => raw_token_from(text, spans[index]),
+        }
+    }
+
+    /// The whole identifier, raw and folded. Its parts are adjacent in both
+    /// the content and the fold arena.
+    fn identifier_tokens(&self, identifier: SplitIdentifier) -> (&'a str, &str) {
+        let span = identifier_span(&self.token_spans, identifier);
+        let folded = match &self.folded_tokens {
+            FoldedTokens::SameSpans => raw_token_from(&self.content_folded, span),
+            FoldedTokens::Arena { text, spans } => {
+                raw_token_from(text, identifier_span(spans, identifier))
+            }
+        };
+        (raw_token_from(self.content, span), folded)
+    }
+}
+
+impl<'a> LargeFastPreparedDocument<'a> {
+    fn identifier_token(&self, identifier: SplitIdentifier) -> &'a str {
+        raw_token_from(self.content, identifier_span(&self.token_spans, identifier))
     }
 }
 
@@ -213,6 +263,13 @@ fn raw_token_from(content: &str, span: TokenSpan) -> &str {
     &content[span.start..span.end]
 }
 
+fn identifier_span(spans: &[TokenSpan], identifier: SplitIdentifier) -> TokenSpan {
+    TokenSpan {
+        start: spans[identifier.first].start,
+        end: spans[identifier.last].end,
+    }
+}
+
 fn for_each_prepared_token<'a>(
     content: &'a str,
     token_spans: &[TokenSpan],
@@ -225,13 +282,14 @@ fn for_each_prepared_token<'a>(
     }
 }
 
-pub(crate) fn prepare_document_for_ranking(content: &str) -> PreparedDocument<'_> {
-    let token_spans = tokenize_for_ranking(content);
+pub fn prepare_document_for_ranking(content: &str) -> PreparedDocument<'_> {
+    let (token_spans, identifiers) = tokenize_for_ranking(content);
 
     if content.len() > LARGE_DOC_THRESHOLD_BYTES {
         PreparedDocument::LargeFast(LargeFastPreparedDocument {
             content,
             token_spans,
+            identifiers,
             case_mode: if content.is_ascii() {
                 LargeFastCaseMode::Ascii
             } else {
@@ -240,14 +298,28 @@ pub(crate) fn prepare_document_for_ranking(content: &str) -> PreparedDocument<'_
         })
     } else {
         let content_folded = fold_str(content);
-        let folded_tokens = token_spans
-            .iter()
-            .map(|span| fold_str(&content[span.start..span.end]))
-            .collect();
+        let folded_tokens = if content.is_ascii() {
+            FoldedTokens::SameSpans
+        } else {
+            let mut text = String::with_capacity(content_folded.len());
+            let spans = token_spans
+                .iter()
+                .map(|&span| {
+                    let start = text.len();
+                    fold_into(raw_token_from(content, span), &mut text);
+                    TokenSpan {
+                        start,
+                        end: text.len(),
+                    }
+                })
+                .collect();
+            FoldedTokens::Arena { text, spans }
+        };
         PreparedDocument::Small(SmallPreparedDocument {
             content,
             content_folded,
             token_spans,
+            identifiers,
             folded_tokens,
         })
     }
@@ -267,16 +339,49 @@ pub(crate) struct RankingPerfBreakdown {
     pub exactness_ns: u64,
 }
 
-fn tokenize_for_ranking(content: &str) -> Vec<TokenSpan> {
+/// Tokens as `search::tokenize_words` splits them, except that camelCase
+/// identifiers take one position per part. Each split identifier is listed
+/// so a query word matching none of its parts can still match it whole.
+fn tokenize_for_ranking(content: &str) -> (Vec<TokenSpan>, Vec<SplitIdentifier>) {
+    let mut tokens = RankingTokens::default();
     if content.is_ascii() {
-        tokenize_for_ranking_ascii(content.as_bytes())
+        tokenize_for_ranking_ascii(content, &mut tokens);
     } else {
-        tokenize_for_ranking_unicode(content)
+        tokenize_for_ranking_unicode(content, &mut tokens);
     }
+    (tokens.spans, tokens.identifiers)
+}
+
+#[derive(Default)]
+struct RankingTokens {
+    spans: Vec<TokenSpan>,
+    identifiers: Vec<SplitIdentifier>,
 }
 
-fn tokenize_for_ranking_ascii(bytes: &[u8]) -> Vec<TokenSpan> {
-    let mut tokens = Vec::new();
+impl RankingTokens {
+    fn push(&mut self, content: &str, start: usize, end: usize, is_word: bool) {
+        let first = self.spans.len();
+        if is_word {
+            for_each_camel_case_part(&content[start..end], |part_start, part_end| {
+                self.spans.push(TokenSpan {
+                    start: start + part_start,
+                    end: start + part_end,
+                });
+            });
+        }
+        if self.spans.len() == first {
+            self.spans.push(TokenSpan { start, end });
+        } else {
+            self.identifiers.push(SplitIdentifier {
+                first,
+                last: self.spans.len() - 1,
+            });
+        }
+    }
+}
+
+fn tokenize_for_ranking_ascii(content: &str, tokens: &mut RankingTokens) {
+    let bytes = content.as_bytes();
     let mut i = 0usize;
     while i < bytes.len() {
         if bytes[i].is_ascii_whitespace() {
@@ -295,13 +400,11 @@ fn tokenize_for_ranking_ascii(bytes: &[u8]) -> Vec<TokenSpan> {
             i += 1;
         }
 
-        tokens.push(TokenSpan { start, end: i });
+        tokens.push(content, start, i, is_word);
     }
-    tokens
 }
 
-fn tokenize_for_ranking_unicode(content: &str) -> Vec<TokenSpan> {
-    let mut tokens = Vec::new();
+fn tokenize_for_ranking_unicode(content: &str, tokens: &mut RankingTokens) {
     let mut chars = content.char_indices().peekable();
 
     while let Some((start, ch)) = chars.next() {
@@ -312,18 +415,33 @@ fn tokenize_for_ranking_unicode(content: &str) -> Vec<TokenSpan> {
         let is_word = ch.is_alphanumeric();
         let mut end = start + ch.len_utf8();
 
+        let splits_per_char = is_word && crate::search::is_cjk_char(ch);
+
         while let Some(&(next_start, next_ch)) = chars.peek() {
-            if next_ch.is_whitespace() || next_ch.is_alphanumeric() != is_word {
+            if splits_per_char
+                || next_ch.is_whitespace()
+                || next_ch.is_alphanumeric() != is_word
+                || (is_word && crate::search::is_cjk_char(next_ch))
+            {
                 break;
             }
             end = next_start + next_ch.len_utf8();
             chars.next();
         }
 
-        tokens.push(TokenSpan { start, end });
+        tokens.push(content, start, end, is_word);
     }
+}
 
-    tokens
+/// A document token that matched a query word while a small document was
+/// scored. Offsets are bytes into the scored content; `query_word` is the
+/// first query word, in query order, that the token matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TokenWordMatch {
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    pub(crate) query_word: usize,
+    pub(crate) kind: WordMatchKind,
 }
 
 /// Per-query-word match result
@@ -656,18 +774,28 @@ struct RankingBreakdown {
 }
 
 impl RankingBreakdown {
-    fn into_bucket_score(self, timestamp: i64) -> BucketScore {
+    fn into_bucket_score(
+        self,
+        timestamp: i64,
+        personalization: PersonalizationBand,
+        frecency: FrecencyBand,
+    ) -> BucketScore {
         BucketScore {
             quality_tier: self.quality_signals.quality_tier(),
+            frecency,
             recency_bucket: self.recency_bucket,
+            personalization,
             quality_detail: self.quality_signals.quality_detail(),
             recency: timestamp,
         }
     }
 }
 
-fn build_ranking_breakdown(ctx: &ScoringContext<'_>) -> RankingBreakdown {
-    let word_matches = match_query_words(ctx.document, ctx.query);
+fn build_ranking_breakdown(
+    ctx: &ScoringContext<'_>,
+    recorded: Option<&mut Vec<TokenWordMatch>>,
+) -> RankingBreakdown {
+    let word_matches = match_query_words(ctx.document, ctx.query, recorded);
     let quality_signals = compute_document_quality_signals(
         ctx.document,
         ctx.query,
@@ -684,9 +812,10 @@ fn build_ranking_breakdown(ctx: &ScoringContext<'_>) -> RankingBreakdown {
 #[cfg(feature = "perf-log")]
 fn build_ranking_breakdown_with_perf(
     ctx: &ScoringContext<'_>,
+    recorded: Option<&mut Vec<TokenWordMatch>>,
 ) -> (RankingBreakdown, RankingPerfBreakdown) {
     let match_start = Instant::now();
-    let (word_matches, mut perf) = match_query_words_with_perf(ctx.document, ctx.query);
+    let (word_matches, mut perf) = match_query_words_with_perf(ctx.document, ctx.query, recorded);
     perf.match_query_words_ns = match_start.elapsed().as_nanos() as u64;
 
     let quality_start = Instant::now();
@@ -715,35 +844,83 @@ fn build_ranking_breakdown_with_perf(
 /// that avoids the full small-document fuzzy pipeline.
 pub fn compute_bucket_score(ctx: &ScoringContext<'_>) -> BucketScore {
     if ctx.query.tokens.is_empty() {
-        return BucketScore {
-            quality_tier: QualityTier::NoMatch,
-            recency_bucket: compute_recency_bucket(ctx.timestamp, ctx.now),
-            quality_detail: QualityDetail::default(),
-            recency: ctx.timestamp,
-        };
+        return empty_query_bucket_score(ctx);
     }
 
-    build_ranking_breakdown(ctx).into_bucket_score(ctx.timestamp)
+    build_ranking_breakdown(ctx, None).into_bucket_score(
+        ctx.timestamp,
+        ctx.personalization,
+        ctx.frecency,
+    )
+}
+
+/// `compute_bucket_score`, also returning the token matches found while
+/// scoring a small document, in document order, so highlighting can reuse
+/// them. Large documents are matched only partially and return `None`.
+#[cfg_attr(feature = "perf-log", allow(dead_code))]
+pub(crate) fn compute_bucket_score_with_matches(
+    ctx: &ScoringContext<'_>,
+) -> (BucketScore, Option<Vec<TokenWordMatch>>) {
+    if ctx.query.tokens.is_empty() {
+        return (empty_query_bucket_score(ctx), None);
+    }
+
+    let mut recorded = match_recorder(ctx.document);
+    let breakdown = build_ranking_breakdown(ctx, recorded.as_mut());
+    (
+        breakdown.into_bucket_score(ctx.timestamp, ctx.personalization, ctx.frecency),
+        recorded.map(finish_recorded_matches),
+    )
 }
 
 #[cfg(feature = "perf-log")]
 pub(crate) fn compute_bucket_score_with_perf(
     ctx: &ScoringContext<'_>,
-) -> (BucketScore, RankingPerfBreakdown) {
+) -> (
+    BucketScore,
+    Option<Vec<TokenWordMatch>>,
+    RankingPerfBreakdown,
+) {
     if ctx.query.tokens.is_empty() {
         return (
-            BucketScore {
-                quality_tier: QualityTier::NoMatch,
-                recency_bucket: compute_recency_bucket(ctx.timestamp, ctx.now),
-                quality_detail: QualityDetail::default(),
-                recency: ctx.timestamp,
-            },
+            empty_query_bucket_score(ctx),
+            None,
             RankingPerfBreakdown::default(),
         );
     }
 
-    let (breakdown, perf) = build_ranking_breakdown_with_perf(ctx);
-    (breakdown.into_bucket_score(ctx.timestamp), perf)
+    let mut recorded = match_recorder(ctx.document);
+    let (breakdown, perf) = build_ranking_breakdown_with_perf(ctx, recorded.as_mut());
+    (
+        breakdown.into_bucket_score(ctx.timestamp, ctx.personalization, ctx.frecency),
+        recorded.map(finish_recorded_matches),
+        perf,
+    )
+}
+
+fn empty_query_bucket_score(ctx: &ScoringContext<'_>) -> BucketScore {
+    BucketScore {
+        quality_tier: QualityTier::NoMatch,
+        frecency: ctx.frecency,
+        recency_bucket: compute_recency_bucket(ctx.timestamp, ctx.now),
+        personalization: ctx.personalization,
+        quality_detail: QualityDetail::default(),
+        recency: ctx.timestamp,
+    }
+}
+
+/// Only small documents compare every query word with every token.
+fn match_recorder(document: &PreparedDocument<'_>) -> Option<Vec<TokenWordMatch>> {
+    (!document.is_fast_mode()).then(Vec::new)
+}
+
+/// Order recorded matches by token, keeping each token's first query word.
+/// Matches arrive query word by query word, so a stable sort leaves the
+/// lowest query word first within each token.
+fn finish_recorded_matches(mut recorded: Vec<TokenWordMatch>) -> Vec<TokenWordMatch> {
+    recorded.sort_by_key(|token_match| token_match.start);
+    recorded.dedup_by_key(|token_match| token_match.start);
+    recorded
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -1001,6 +1178,7 @@ enum MatchQueryPlan {
 fn build_match_query_plan(
     document: &PreparedDocument<'_>,
     query: &PreparedQuery,
+    mut recorded: Option<&mut Vec<TokenWordMatch>>,
 ) -> MatchQueryPlan {
     if document.is_fast_mode() && query.tokens.len() == 1 {
         let token = &query.tokens[0];
@@ -1034,8 +1212,13 @@ fn build_match_query_plan(
         .map(|(index, token)| {
             let prefix_match =
                 prefix_match_for_query_word(query.tokens.len(), index, query.last_word_is_prefix());
-            let (candidates, raw_count) =
-                collect_match_candidates(&token.raw, &token.folded, document, prefix_match);
+            let (candidates, raw_count) = collect_match_candidates(
+                &token.raw,
+                &token.folded,
+                document,
+                prefix_match,
+                recorded.as_deref_mut().map(|recorded| (index, recorded)),
+            );
             raw_candidate_count += raw_count;
             trimmed_candidate_count += candidates.len();
             candidates
@@ -1052,8 +1235,12 @@ fn build_match_query_plan(
     }
 }
 
-fn match_query_words(document: &PreparedDocument<'_>, query: &PreparedQuery) -> Vec<WordMatch> {
-    match build_match_query_plan(document, query) {
+fn match_query_words(
+    document: &PreparedDocument<'_>,
+    query: &PreparedQuery,
+    recorded: Option<&mut Vec<TokenWordMatch>>,
+) -> Vec<WordMatch> {
+    match build_match_query_plan(document, query, recorded) {
         MatchQueryPlan::SingleFast { word_match, .. } => vec![word_match],
         MatchQueryPlan::Aligned {
             defaults,
@@ -1067,9 +1254,10 @@ fn match_query_words(document: &PreparedDocument<'_>, query: &PreparedQuery) ->
 fn match_query_words_with_perf(
     document: &PreparedDocument<'_>,
     query: &PreparedQuery,
+    recorded: Option<&mut Vec<TokenWordMatch>>,
 ) -> (Vec<WordMatch>, RankingPerfBreakdown) {
     let collect_start = Instant::now();
-    let plan = build_match_query_plan(document, query);
+    let plan = build_match_query_plan(document, query, recorded);
     let collect_candidates_ns = collect_start.elapsed().as_nanos() as u64;
 
     match plan {
@@ -1172,19 +1360,62 @@ fn match_single_query_word_fast_with_count(
         return (best_exact, raw_candidate_count);
     }
 
+    // Whole identifiers, for a word matching none of their parts.
+    if let PreparedDocument::LargeFast(doc) = document {
+        for &identifier in &doc.identifiers {
+            let classify = |token: &str, dpos: usize| {
+                classify_fast_match_candidate(
+                    query_word,
+                    query_word_folded,
+                    token,
+                    dpos,
+                    prefix_match,
+                )
+            };
+            let matches_part = (identifier.first..=identifier.last).any(|dpos| {
+                classify(raw_token_from(doc.content, doc.token_spans[dpos]), dpos).is_some()
+            });
+            if matches_part {
+                continue;
+            }
+            let Some(candidate) = classify(doc.identifier_token(identifier), identifier.first)
+            else {
+                continue;
+            };
+            raw_candidate_count += 1;
+            if matches!(candidate.state, WordMatchState::Exact { .. }) {
+                return (candidate, raw_candidate_count);
+            }
+            if best_prefix
+                .is_none_or(|prefix: WordMatch| prefix.doc_word_pos() > candidate.doc_word_pos())
+            {
+                best_prefix = Some(candidate);
+            }
+        }
+    }
+
     (
         best_prefix.unwrap_or_else(|| WordMatch::unmatched(query_word)),
         raw_candidate_count,
     )
 }
 
+/// Match one query word against the document. `recorded`, when given,
+/// carries the query word's index and collects every token it matched.
 fn collect_match_candidates(
     query_word: &str,
     query_word_folded: &str,
     document: &PreparedDocument<'_>,
     prefix_match: PrefixMatch,
+    recorded: Option<(usize, &mut Vec<TokenWordMatch>)>,
 ) -> (Vec<WordMatch>, usize) {
-    collect_match_candidates_impl(query_word, query_word_folded, document, prefix_match)
+    collect_match_candidates_impl(
+        query_word,
+        query_word_folded,
+        document,
+        prefix_match,
+        recorded,
+    )
 }
 
 fn collect_match_candidates_impl(
@@ -1192,36 +1423,57 @@ fn collect_match_candidates_impl(
     query_word_folded: &str,
     document: &PreparedDocument<'_>,
     prefix_match: PrefixMatch,
+    mut recorded: Option<(usize, &mut Vec<TokenWordMatch>)>,
 ) -> (Vec<WordMatch>, usize) {
     let candidates: Vec<WordMatch> = match document {
-        PreparedDocument::Small(doc) => (0..doc.token_spans.len())
-            .filter_map(|dpos| {
-                let dw_raw = doc.raw_token(dpos);
-                let dw_folded = doc.folded_token(dpos);
-                let wmk = does_word_match(query_word_folded, dw_folded, dw_raw, prefix_match);
-                match wmk {
-                    WordMatchKind::Exact => Some(WordMatch::exact(query_word, dpos)),
-                    WordMatchKind::Prefix { .. } => Some(WordMatch::prefix(query_word, dpos)),
-                    WordMatchKind::SubwordPrefix { .. } => {
-                        Some(WordMatch::subword_prefix(query_word, dpos))
+        PreparedDocument::Small(doc) => {
+            let mut record = |span: TokenSpan, wmk: WordMatchKind| {
+                if let Some((query_word, recorded)) = recorded.as_mut() {
+                    if wmk != WordMatchKind::None {
+                        recorded.push(TokenWordMatch {
+                            start: span.start,
+                            end: span.end,
+                            query_word: *query_word,
+                            kind: wmk,
+                        });
                     }
-                    WordMatchKind::InfixSubstring { .. } => {
-                        Some(WordMatch::infix_substring(query_word, dpos))
-                    }
-                    WordMatchKind::Fuzzy(dist) => Some(WordMatch::fuzzy(
-                        query_word,
-                        dpos,
-                        dist,
-                        classify_fuzzy_typo(query_word_folded, dw_folded, dist),
-                    )),
-                    WordMatchKind::Subsequence(gaps) => {
-                        Some(WordMatch::subsequence(query_word, dpos, gaps))
-                    }
-                    WordMatchKind::None => None,
                 }
-            })
-            .collect(),
-        PreparedDocument::LargeFast(_) => {
+            };
+            let mut candidates: Vec<WordMatch> = (0..doc.token_spans.len())
+                .filter_map(|dpos| {
+                    let dw_folded = doc.folded_token(dpos);
+                    let wmk = does_word_match(
+                        query_word_folded,
+                        dw_folded,
+                        doc.raw_token(dpos),
+                        prefix_match,
+                    );
+                    record(doc.token_spans[dpos], wmk);
+                    word_match_candidate(query_word, query_word_folded, dw_folded, dpos, wmk)
+                })
+                .collect();
+            // A query word matching none of an identifier's parts may still
+            // match it whole, as "refreshtoken" matches `refreshToken`.
+            let mut whole_matches = Vec::new();
+            for &identifier in &doc.identifiers {
+                if has_candidate_within(&candidates, identifier) {
+                    continue;
+                }
+                let (dw_raw, dw_folded) = doc.identifier_tokens(identifier);
+                let wmk = does_word_match(query_word_folded, dw_folded, dw_raw, prefix_match);
+                record(identifier_span(&doc.token_spans, identifier), wmk);
+                whole_matches.extend(word_match_candidate(
+                    query_word,
+                    query_word_folded,
+                    dw_folded,
+                    identifier.first,
+                    wmk,
+                ));
+            }
+            candidates.extend(whole_matches);
+            candidates
+        }
+        PreparedDocument::LargeFast(doc) => {
             let mut candidates = Vec::new();
             document.for_each_fast_token(|dpos, doc_token| {
                 if let Some(candidate) = classify_fast_match_candidate(
@@ -1235,6 +1487,21 @@ fn collect_match_candidates_impl(
                 }
                 true
             });
+            let whole_matches: Vec<WordMatch> = doc
+                .identifiers
+                .iter()
+                .filter(|&&identifier| !has_candidate_within(&candidates, identifier))
+                .filter_map(|&identifier| {
+                    classify_fast_match_candidate(
+                        query_word,
+                        query_word_folded,
+                        doc.identifier_token(identifier),
+                        identifier.first,
+                        prefix_match,
+                    )
+                })
+                .collect();
+            candidates.extend(whole_matches);
             candidates
         }
     };
@@ -1243,6 +1510,42 @@ fn collect_match_candidates_impl(
     (trim_match_candidates(candidates), raw_candidate_count)
 }
 
+fn word_match_candidate(
+    query_word: &str,
+    query_word_folded: &str,
+    dw_folded: &str,
+    dpos: usize,
+    wmk: WordMatchKind,
+) -> Option<WordMatch> {
+    match wmk {
+        WordMatchKind::Exact => Some(WordMatch::exact(query_word, dpos)),
+        WordMatchKind::Prefix { .. } => Some(WordMatch::prefix(query_word, dpos)),
+        WordMatchKind::SubwordPrefix { .. } => Some(WordMatch::subword_prefix(query_word, dpos)),
+        WordMatchKind::InfixSubstring { .. } => Some(WordMatch::infix_substring(query_word, dpos)),
+        WordMatchKind::Fuzzy(dist) => Some(WordMatch::fuzzy(
+            query_word,
+            dpos,
+            dist,
+            classify_fuzzy_typo(query_word_folded, dw_folded, dist),
+        )),
+        WordMatchKind::Subsequence(gaps) => Some(WordMatch::subsequence(query_word, dpos, gaps)),
+        WordMatchKind::None => None,
+    }
+}
+
+/// Whether a candidate, in position order, lies on one of `identifier`'s parts.
+fn has_candidate_within(candidates: &[WordMatch], identifier: SplitIdentifier) -> bool {
+    let next = candidates.partition_point(|candidate| {
+        candidate
+            .doc_word_pos()
+            .is_some_and(|dpos| dpos < identifier.first)
+    });
+    candidates
+        .get(next)
+        .and_then(|candidate| candidate.doc_word_pos())
+        .is_some_and(|dpos| dpos <= identifier.last)
+}
+
 fn classify_fast_match_candidate(
     query_word: &str,
     query_word_folded: &str,
@@ -1464,6 +1767,8 @@ mod tests {
             prefix_preference,
             timestamp,
             now,
+            personalization: PersonalizationBand::None,
+            frecency: FrecencyBand::None,
         })
     }
 
@@ -1477,9 +1782,67 @@ mod tests {
             prefix_preference: None,
             timestamp,
             now,
+            personalization: PersonalizationBand::None,
+            frecency: FrecencyBand::None,
         })
     }
 
+    #[test]
+    fn personalization_orders_between_recency_bucket_and_quality_detail() {
+        let personalized = BucketScore {
+            quality_tier: QualityTier::Dense,
+            frecency: FrecencyBand::None,
+            recency_bucket: RecencyBucket::LastDay,
+            personalization: PersonalizationBand::Preferred,
+            quality_detail: QualityDetail::default(),
+            recency: 0,
+        };
+        let better_detail = BucketScore {
+            personalization: PersonalizationBand::None,
+            quality_detail: QualityDetail {
+                coverage: CoverageBand::Full,
+                ..QualityDetail::default()
+            },
+            recency: 1,
+            ..personalized
+        };
+        let newer_bucket = BucketScore {
+            recency_bucket: RecencyBucket::LastHour,
+            personalization: PersonalizationBand::None,
+            ..personalized
+        };
+
+        assert!(personalized > better_detail);
+        assert!(newer_bucket > personalized);
+    }
+
+    #[test]
+    fn frecency_orders_between_quality_tier_and_recency_bucket() {
+        let reused = BucketScore {
+            quality_tier: QualityTier::Dense,
+            frecency: FrecencyBand::Reused,
+            recency_bucket: RecencyBucket::LastMonth,
+            personalization: PersonalizationBand::None,
+            quality_detail: QualityDetail::default(),
+            recency: 0,
+        };
+        let fresh_one_off = BucketScore {
+            frecency: FrecencyBand::None,
+            recency_bucket: RecencyBucket::LastHour,
+            personalization: PersonalizationBand::Preferred,
+            recency: 1,
+            ..reused
+        };
+        let better_tier = BucketScore {
+            quality_tier: QualityTier::ContentPrefix,
+            frecency: FrecencyBand::None,
+            ..reused
+        };
+
+        assert!(reused > fresh_one_off);
+        assert!(better_tier > reused);
+    }
+
     fn dwm(query_word: &str, doc_word: &str, prefix_match: PrefixMatch) -> WordMatchKind {
         does_word_match(
             &fold_str(query_word),
@@ -1509,7 +1872,7 @@ mod tests {
             raw_query.push(' ');
         }
         let query = PreparedQuery::new(&raw_query);
-        match_query_words(&document, &query)
+        match_query_words(&document, &query, None)
     }
 
     fn wm_unmatched(query_word: &str) -> WordMatch {
@@ -1906,14 +2269,31 @@ mod tests {
 
     #[test]
     fn test_match_subword_prefix() {
-        let doc_words = vec!["responseCode"];
-        let matches = match_words(&["code"], &doc_words, false);
+        let doc_words = vec!["sha256sum"];
+        let matches = match_words(&["sum"], &doc_words, false);
         assert!(matches!(
             matches[0].state,
             WordMatchState::SubwordPrefix { .. }
         ));
     }
 
+    #[test]
+    fn test_match_camel_case_parts() {
+        let doc_words = vec!["refreshAccessToken"];
+        let matches = match_words(&["access", "token"], &doc_words, false);
+        assert!(matches!(matches[0].state, WordMatchState::Exact { .. }));
+        assert!(matches!(matches[1].state, WordMatchState::Exact { .. }));
+        assert_eq!(matches[0].doc_word_pos(), Some(1));
+        assert_eq!(matches[1].doc_word_pos(), Some(2));
+
+        // A word spanning parts still matches the identifier as a whole.
+        let matches = match_words(&["refreshacc"], &doc_words, true);
+        assert!(matches!(matches[0].state, WordMatchState::Prefix { .. }));
+        assert_eq!(matches[0].doc_word_pos(), Some(0));
+        let matches = match_words(&["refreshaccesstoken"], &doc_words, false);
+        assert!(matches!(matches[0].state, WordMatchState::Exact { .. }));
+    }
+
     #[test]
     fn test_match_infix_substring() {
         let doc_words = vec!["import"];
@@ -1969,7 +2349,7 @@ mod tests {
         assert!(document.is_fast_mode());
 
         let query = PreparedQuery::new("error");
-        let matches = match_query_words(&document, &query);
+        let matches = match_query_words(&document, &query, None);
         assert!(matches!(matches[0].state, WordMatchState::Exact { .. }));
     }
 
@@ -2000,7 +2380,7 @@ mod tests {
         assert!(document.is_fast_mode());
 
         let query = PreparedQuery::new("ошибка ");
-        let matches = match_query_words(&document, &query);
+        let matches = match_query_words(&document, &query, None);
         assert!(matches!(matches[0].state, WordMatchState::Exact { .. }));
     }
 
@@ -2012,7 +2392,7 @@ mod tests {
         assert!(document.is_fast_mode());
 
         let query = PreparedQuery::new("ошиб");
-        let matches = match_query_words(&document, &query);
+        let matches = match_query_words(&document, &query, None);
         assert!(matches!(matches[0].state, WordMatchState::Prefix { .. }));
     }
 
@@ -2223,6 +2603,26 @@ mod tests {
 
     // ── bucket score ordering tests ──────────────────────────────
 
+    #[test]
+    fn test_camel_case_parts_cover_multi_word_queries() {
+        let now = 1_700_000_000i64;
+        let camel = score_raw_query(
+            "let t = await refreshAccessToken(user);",
+            "access token",
+            now,
+            now,
+        );
+        let snake = score_raw_query(
+            "let t = await refresh_access_token(user);",
+            "access token",
+            now,
+            now,
+        );
+
+        assert_eq!(camel.quality_detail.coverage, CoverageBand::Full);
+        assert_eq!(camel.quality_tier, snake.quality_tier);
+    }
+
     #[test]
     fn test_symbol_bearing_literal_sequences_are_structural_quality() {
         let now = 1_700_000_000i64;
@@ -1,49 +1,83 @@
 //! Diacritic folding: the single text-equivalence used by indexing, phase-1
 //! signals, tail admission, phase-2 matching, and highlighting. Folding is
-//! lowercasing plus stripping canonical combining marks ('é' -> 'e', 'Ü' -> 'u'),
+//! lowercasing plus compatibility (NFKD) decomposition with combining marks
+//! stripped ('é' -> 'e', 'Ü' -> 'u', full-width 'Ａ' -> 'a', '²' -> '2'),
 //! matching NSString's caseInsensitive + diacriticInsensitive semantics.
 //!
 //! Load-bearing invariant: folding is char-count preserving — exactly one
-//! output char per input char — so char-indexed spans computed on folded text
-//! are valid on the original text. Byte offsets are NOT preserved.
+//! output char per input char — except for compatibility expansions, chars
+//! whose decomposition is several letters ('ﬁ' -> "fi", '㎏' -> "kg").
+//! Char-indexed spans computed on folded text are valid on the original text
+//! once mapped through `raw_char_span`, which is the identity for text
+//! without expansions. Byte offsets are NOT preserved.
 //!
-//! Out of scope by design (would break the 1:1 invariant):
-//! - multi-char case folds and ligatures: 'ß', 'œ', 'æ', 'ﬁ' stay as-is, so
-//!   "strasse" does not find "straße"
-//! - compatibility (NFKD) decompositions: '²', full-width forms stay as-is
+//! Out of scope by design:
+//! - multi-char case folds without a decomposition: 'ß', 'œ', 'æ' stay as-is,
+//!   so "strasse" does not find "straße"
+//! - decompositions that are not all letters and digits: '½', '⑴', '…'
 //! - Hangul syllables: NFD yields Jamo letters, not combining marks, so they
 //!   are left untouched; CJK has no decomposition and is unaffected
 
-use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+use std::borrow::Cow;
+use unicode_normalization::char::{decompose_canonical, decompose_compatible, is_combining_mark};
 
-/// Fold one char: lowercase + canonical-decomposition base char. ASCII takes a
-/// table-free fast path; chars whose decomposition tail is not all combining
-/// marks (e.g. Hangul) and chars without a single-char lowercase are returned
-/// unchanged to preserve the 1:1 invariant.
-pub(crate) fn fold_char(c: char) -> char {
-    if c.is_ascii() {
-        return c.to_ascii_lowercase();
+/// Longest compatibility decomposition in Unicode (U+FDFA).
+const MAX_DECOMPOSITION: usize = 18;
+
+/// How a non-ASCII char folds, from its compatibility decomposition.
+enum Decomposition {
+    /// A base char followed only by combining marks, or no decomposition.
+    Base(char),
+    /// Several letters or digits, combining marks removed.
+    Expansion([char; MAX_DECOMPOSITION], usize),
+}
+
+fn decompose(c: char) -> Decomposition {
+    let mut chars = [c; MAX_DECOMPOSITION];
+    let mut len = 0;
+    let mut letters = [c; MAX_DECOMPOSITION];
+    let mut letter_count = 0;
+    decompose_compatible(c, |decomposed| {
+        if len < MAX_DECOMPOSITION {
+            chars[len] = decomposed;
+            len += 1;
+        }
+        if !is_combining_mark(decomposed) && letter_count < MAX_DECOMPOSITION {
+            letters[letter_count] = decomposed;
+            letter_count += 1;
+        }
+    });
+
+    if chars[1..len.max(1)]
+        .iter()
+        .all(|&part| is_combining_mark(part))
+    {
+        return Decomposition::Base(chars[0]);
+    }
+    if letter_count > 1
+        && letters[..letter_count].iter().all(|l| l.is_alphanumeric())
+        && !is_canonical_composite(c)
+    {
+        return Decomposition::Expansion(letters, letter_count);
     }
+    Decomposition::Base(c)
+}
 
-    let mut base = None;
-    let mut tail_is_marks = true;
+/// Whether `c` canonically decomposes into several letters, as Hangul
+/// syllables do into Jamo. Those are the same text spelled differently, not
+/// a compatibility form, so they stay whole.
+fn is_canonical_composite(c: char) -> bool {
+    let mut letters = 0;
     decompose_canonical(c, |decomposed| {
-        if base.is_none() {
-            base = Some(decomposed);
-        } else if !is_combining_mark(decomposed) {
-            tail_is_marks = false;
+        if !is_combining_mark(decomposed) {
+            letters += 1;
         }
     });
-
-    let candidate = match base {
-        Some(base) if tail_is_marks => base,
-        _ => c,
-    };
-    lowercase_single(candidate)
+    letters > 1
 }
 
 /// Lowercase only when it stays a single char ('İ' would expand to "i\u{307}"
-/// via `to_lowercase`; its decomposition path above already yields 'i').
+/// via `to_lowercase`; its decomposition already yields 'i').
 fn lowercase_single(c: char) -> char {
     let mut lowered = c.to_lowercase();
     match (lowered.next(), lowered.next()) {
@@ -52,19 +86,113 @@ fn lowercase_single(c: char) -> char {
     }
 }
 
+/// Fold one char onto `out`: lowercase + compatibility-decomposition base
+/// char, or the letters of an expansion. ASCII takes a table-free fast path;
+/// other chars whose decomposition tail is not all combining marks (e.g.
+/// Hangul) and chars without a single-char lowercase are pushed unchanged.
+fn push_folded(c: char, out: &mut String) {
+    if c.is_ascii() {
+        out.push(c.to_ascii_lowercase());
+        return;
+    }
+    match decompose(c) {
+        Decomposition::Base(base) => out.push(lowercase_single(base)),
+        Decomposition::Expansion(letters, len) => out.extend(
+            letters[..len]
+                .iter()
+                .map(|&letter| lowercase_single(letter)),
+        ),
+    }
+}
+
+/// Chars that `c` folds to.
+fn folded_len(c: char) -> usize {
+    if c.is_ascii() {
+        return 1;
+    }
+    match decompose(c) {
+        Decomposition::Base(_) => 1,
+        Decomposition::Expansion(_, len) => len,
+    }
+}
+
 /// Fold a string char-by-char. Same allocation shape as `str::to_lowercase`,
 /// which this replaces at every comparison site.
 pub(crate) fn fold_str(s: &str) -> String {
     if s.is_ascii() {
         s.to_ascii_lowercase()
     } else {
-        s.chars().map(fold_char).collect()
+        let mut folded = String::with_capacity(s.len());
+        fold_into(s, &mut folded);
+        folded
+    }
+}
+
+/// Fold `s` onto the end of `out`, so callers folding token after token can
+/// reuse one buffer.
+pub(crate) fn fold_into(s: &str, out: &mut String) {
+    if s.is_ascii() {
+        let start = out.len();
+        out.push_str(s);
+        out[start..].make_ascii_lowercase();
+    } else {
+        for c in s.chars() {
+            push_folded(c, out);
+        }
+    }
+}
+
+/// `s` with its compatibility expansions spelled out and every other char
+/// left alone, for tokenizers that must see the letters `fold_str` produces
+/// before folding per token. Borrows when nothing expands.
+pub(crate) fn expand_compatibility(s: &str) -> Cow<'_, str> {
+    if s.is_ascii() || !s.chars().any(|c| folded_len(c) > 1) {
+        return Cow::Borrowed(s);
+    }
+    let mut expanded = String::with_capacity(s.len() + 8);
+    for c in s.chars() {
+        match decompose(c) {
+            Decomposition::Expansion(letters, len) => expanded.extend(&letters[..len]),
+            _ => expanded.push(c),
+        }
+    }
+    Cow::Owned(expanded)
+}
+
+/// Map the char span `start..end` of `fold_str(raw)` onto `raw`'s chars. A
+/// span that starts or ends inside an expansion covers its whole source char.
+pub(crate) fn raw_char_span(raw: &str, start: usize, end: usize) -> (usize, usize) {
+    if raw.is_ascii() {
+        return (start, end);
     }
+    let (mut raw_start, mut raw_end) = (0, 0);
+    let mut folded = 0;
+    for c in raw.chars() {
+        if folded >= end {
+            break;
+        }
+        let folded_end = folded + folded_len(c);
+        if folded_end <= start {
+            raw_start += 1;
+        }
+        raw_end += 1;
+        folded = folded_end;
+    }
+    (raw_start, raw_end.max(raw_start))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{fold_char, fold_str};
+    use super::{expand_compatibility, fold_into, fold_str, raw_char_span};
+
+    /// Fold a char that must stay one char.
+    fn fold_char(c: char) -> char {
+        let folded = fold_str(&c.to_string());
+        let mut chars = folded.chars();
+        let single = chars.next().unwrap();
+        assert_eq!(chars.next(), None, "{c:?} expanded to {folded:?}");
+        single
+    }
 
     #[test]
     fn fold_char_strips_canonical_marks() {
@@ -89,7 +217,6 @@ mod tests {
         assert_eq!(fold_char('ẞ'), 'ß');
         assert_eq!(fold_char('œ'), 'œ');
         assert_eq!(fold_char('æ'), 'æ');
-        assert_eq!(fold_char('ﬁ'), 'ﬁ');
         // Hangul NFD is Jamo letters, not marks: must not fold
         assert_eq!(fold_char('한'), '한');
         assert_eq!(fold_char('北'), '北');
@@ -100,9 +227,40 @@ mod tests {
     #[test]
     fn fold_str_preserves_char_count() {
         let corpus =
-            "Résumé ÜBER café Zürich naïve ế ά й ё ß straße œuf ﬁle 한국어 北京 plain ascii 123";
+            "Résumé ÜBER café Zürich naïve ế ά й ё ß straße œuf Ｆｕｌｌ 한국어 北京 plain ascii 123";
         assert_eq!(fold_str(corpus).chars().count(), corpus.chars().count());
         assert_eq!(fold_str("Résumé"), "resume");
         assert_eq!(fold_str("über"), "uber");
+
+        let mut folded = String::from("x");
+        fold_into("Résumé", &mut folded);
+        fold_into("ABC", &mut folded);
+        assert_eq!(folded, "xresumeabc");
+    }
+
+    #[test]
+    fn compatibility_forms_fold_to_their_letters() {
+        assert_eq!(fold_char('Ａ'), 'a');
+        assert_eq!(fold_char('²'), '2');
+        assert_eq!(fold_char('ſ'), 's');
+        assert_eq!(fold_str("ﬁle"), "file");
+        assert_eq!(fold_str("Ĳssel ㎏"), "ijssel kg");
+        assert_eq!(fold_str("½ …"), "½ …");
+        assert_eq!(expand_compatibility("ﬁné"), "finé");
+        assert!(matches!(
+            expand_compatibility("café"),
+            std::borrow::Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn raw_char_spans_cover_whole_expansions() {
+        // "ﬁle" folds to "file": folded chars 0..2 are the one raw 'ﬁ'.
+        assert_eq!(raw_char_span("ﬁle", 0, 2), (0, 1));
+        assert_eq!(raw_char_span("ﬁle", 0, 1), (0, 1));
+        assert_eq!(raw_char_span("ﬁle", 1, 4), (0, 3));
+        assert_eq!(raw_char_span("ﬁle", 2, 4), (1, 3));
+        assert_eq!(raw_char_span("café", 1, 4), (1, 4));
+        assert_eq!(raw_char_span("plain", 2, 5), (2, 5));
     }
 }
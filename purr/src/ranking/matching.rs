@@ -1,4 +1,5 @@
 use super::folding::fold_str;
+use super::scratch::with_chars;
 use strsim::osa_distance;
 use triple_accel::levenshtein::{levenshtein_simd_k_with_opts, RDAMERAU_COSTS};
 
@@ -78,8 +79,9 @@ pub(crate) fn prefix_match_for_query_word(
 /// Check if a query word matches a document word using the same criteria
 /// as ranking: exact -> prefix -> subword-prefix -> infix substring -> fuzzy
 /// -> subsequence. `qw_folded` and `dw_folded` must already be folded via
-/// `fold_str` (lowercase + char-count-preserving diacritic strip); `dw_raw`
-/// preserves original casing for camelCase/digit boundary detection.
+/// `fold_str` (lowercase + compatibility/diacritic strip); `dw_raw`
+/// preserves original casing for camelCase/digit boundary detection. Spans
+/// count folded chars; `raw_char_span` maps them onto `dw_raw`.
 pub(crate) fn does_word_match(
     qw_folded: &str,
     dw_folded: &str,
@@ -191,15 +193,34 @@ fn classify_contained_match(
     dw_folded: &str,
     dw_raw: &str,
 ) -> Option<WordMatchKind> {
-    let query_chars: Vec<char> = qw_folded.chars().collect();
-    let doc_folded_chars: Vec<char> = dw_folded.chars().collect();
+    if qw_folded.is_ascii() && dw_folded.is_ascii() && dw_raw.is_ascii() {
+        return classify_contained_units(
+            qw_folded.as_bytes(),
+            dw_folded.as_bytes(),
+            dw_raw.as_bytes(),
+        );
+    }
+    with_chars(
+        [qw_folded, dw_folded, dw_raw],
+        |[query_chars, doc_folded_chars, doc_raw_chars]| {
+            classify_contained_units(query_chars, doc_folded_chars, doc_raw_chars)
+        },
+    )
+}
+
+/// Contained-match classification over code units: bytes for ASCII words,
+/// where each byte is a char, and decoded chars otherwise.
+fn classify_contained_units<T: Copy + Eq + Into<char>>(
+    query_chars: &[T],
+    doc_folded_chars: &[T],
+    doc_raw_chars: &[T],
+) -> Option<WordMatchKind> {
     if query_chars.len() < 3 || query_chars.len() >= doc_folded_chars.len() {
         return None;
     }
 
-    // fold_str is 1:1 per char so this never trips for folded input; kept as
-    // defense against callers passing differently normalized text.
-    let doc_raw_chars: Vec<char> = dw_raw.chars().collect();
+    // fold_str is 1:1 per char except for compatibility expansions ('ﬁ'),
+    // whose words skip contained matching rather than misplace the boundary.
     if doc_raw_chars.len() != doc_folded_chars.len() {
         return None;
     }
@@ -213,7 +234,7 @@ fn classify_contained_match(
                 start,
                 len: query_chars.len(),
             };
-            return Some(if is_subword_boundary(&doc_raw_chars, start) {
+            return Some(if is_subword_boundary(doc_raw_chars, start) {
                 WordMatchKind::SubwordPrefix { span }
             } else {
                 WordMatchKind::InfixSubstring { span }
@@ -224,27 +245,60 @@ fn classify_contained_match(
     None
 }
 
-fn is_subword_boundary(doc_raw_chars: &[char], start: usize) -> bool {
+fn is_subword_boundary<T: Copy + Into<char>>(doc_raw_chars: &[T], start: usize) -> bool {
     if start == 0 || start >= doc_raw_chars.len() {
         return false;
     }
 
-    let prev = doc_raw_chars[start - 1];
-    let curr = doc_raw_chars[start];
-    let next = doc_raw_chars.get(start + 1).copied();
+    let prev: char = doc_raw_chars[start - 1].into();
+    let curr: char = doc_raw_chars[start].into();
+    let next: Option<char> = doc_raw_chars.get(start + 1).map(|&unit| unit.into());
 
-    (prev.is_lowercase() && curr.is_uppercase())
+    is_case_boundary(prev, curr, next)
         || (prev.is_alphabetic() && curr.is_numeric())
         || (prev.is_numeric() && curr.is_alphabetic())
+}
+
+/// A camelCase boundary before `curr`: "refresh|Token", or "HTTP|Server"
+/// where an uppercase run gives way to a capitalized word.
+fn is_case_boundary(prev: char, curr: char, next: Option<char>) -> bool {
+    (prev.is_lowercase() && curr.is_uppercase())
         || (prev.is_uppercase() && curr.is_uppercase() && next.is_some_and(|ch| ch.is_lowercase()))
 }
 
+/// Visit the byte ranges of `word`'s camelCase parts: "refreshAccessToken"
+/// gives "refresh", "Access", "Token". Digits stay with their letters
+/// ("utf8", "sha256"), and a word without a case boundary visits nothing.
+/// snake_case needs no help: `_` already separates tokens.
+pub(crate) fn for_each_camel_case_part(word: &str, mut visit: impl FnMut(usize, usize)) {
+    let mut chars = word.char_indices().peekable();
+    let mut part_start = 0;
+    let mut prev = None;
+    while let Some((offset, curr)) = chars.next() {
+        let next = chars.peek().map(|&(_, ch)| ch);
+        if prev.is_some_and(|prev| is_case_boundary(prev, curr, next)) {
+            visit(part_start, offset);
+            part_start = offset;
+        }
+        prev = Some(curr);
+    }
+    if part_start > 0 {
+        visit(part_start, word.len());
+    }
+}
+
 /// Check if all characters in `query` appear in order in `target`.
 /// Returns the number of gaps (non-contiguous segments - 1) if matched, None otherwise.
 pub(super) fn subsequence_match(query: &str, target: &str) -> Option<u8> {
-    let q_chars: Vec<char> = query.chars().collect();
-    let t_chars: Vec<char> = target.chars().collect();
+    if query.is_ascii() && target.is_ascii() {
+        return subsequence_match_units(query.as_bytes(), target.as_bytes());
+    }
+    with_chars([query, target], |[q_chars, t_chars]| {
+        subsequence_match_units(q_chars, t_chars)
+    })
+}
 
+fn subsequence_match_units<T: Copy + Eq>(q_chars: &[T], t_chars: &[T]) -> Option<u8> {
     // Min 4 chars to avoid spurious matches (<=3 too short for meaningful subsequence)
     if q_chars.len() <= 3 {
         return None;
@@ -266,7 +320,7 @@ pub(super) fn subsequence_match(query: &str, target: &str) -> Option<u8> {
     let mut gaps = 0u8;
     let mut prev_matched = false;
 
-    for &tc in &t_chars {
+    for &tc in t_chars {
         if qi < q_chars.len() && tc == q_chars[qi] {
             if !prev_matched && qi > 0 {
                 gaps = gaps.saturating_add(1);
@@ -351,8 +405,12 @@ pub(crate) fn classify_fuzzy_edit(query: &str, target: &str, dist: u8) -> FuzzyE
 }
 
 fn is_adjacent_transposition(a: &str, b: &str) -> bool {
-    let a_chars: Vec<char> = a.chars().collect();
-    let b_chars: Vec<char> = b.chars().collect();
+    with_chars([a, b], |[a_chars, b_chars]| {
+        is_adjacent_transposition_chars(a_chars, b_chars)
+    })
+}
+
+fn is_adjacent_transposition_chars(a_chars: &[char], b_chars: &[char]) -> bool {
     if a_chars.len() != b_chars.len() || a_chars.len() < 2 {
         return false;
     }
@@ -388,8 +446,15 @@ fn is_adjacent_transposition(a: &str, b: &str) -> bool {
 /// differ by a single inserted or deleted character. `None` means it is not a
 /// one-char insertion/deletion relationship.
 fn classify_single_insert_delete(shorter: &str, longer: &str) -> Option<bool> {
-    let shorter_chars: Vec<char> = shorter.chars().collect();
-    let longer_chars: Vec<char> = longer.chars().collect();
+    with_chars([shorter, longer], |[shorter_chars, longer_chars]| {
+        classify_single_insert_delete_chars(shorter_chars, longer_chars)
+    })
+}
+
+fn classify_single_insert_delete_chars(
+    shorter_chars: &[char],
+    longer_chars: &[char],
+) -> Option<bool> {
     let (shorter_chars, longer_chars) = if shorter_chars.len() <= longer_chars.len() {
         (shorter_chars, longer_chars)
     } else {
@@ -478,8 +543,18 @@ fn edit_distance_bounded_ascii(a: &str, b: &str, max_dist: u8) -> Option<u8> {
 }
 
 fn edit_distance_bounded_unicode(a: &str, b: &str, max_dist: u8) -> Option<u8> {
-    let a_chars: Vec<char> = a.chars().collect();
-    let b_chars: Vec<char> = b.chars().collect();
+    with_chars([a, b], |[a_chars, b_chars]| {
+        edit_distance_bounded_chars(a, b, a_chars, b_chars, max_dist)
+    })
+}
+
+fn edit_distance_bounded_chars(
+    a: &str,
+    b: &str,
+    a_chars: &[char],
+    b_chars: &[char],
+    max_dist: u8,
+) -> Option<u8> {
     let max_d = max_dist as usize;
 
     if a_chars.is_empty() || b_chars.is_empty() {
@@ -8,13 +8,17 @@ pub const LARGE_DOC_THRESHOLD_BYTES: usize = 32 * 1024; // 32KB
 ///
 /// The field order here is the ranking policy:
 /// 1. foundational match quality
-/// 2. coarse recency band
-/// 3. detailed tie-break quality
-/// 4. raw timestamp
+/// 2. paste frecency
+/// 3. coarse recency band
+/// 4. optional per-app personalization
+/// 5. detailed tie-break quality
+/// 6. raw timestamp
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct BucketScore {
     pub quality_tier: QualityTier,
+    pub frecency: FrecencyBand,
     pub recency_bucket: RecencyBucket,
+    pub personalization: PersonalizationBand,
     pub quality_detail: QualityDetail,
     pub recency: i64,
 }
@@ -29,6 +33,19 @@ pub enum QualityTier {
     ContentPrefix = 3,
 }
 
+impl QualityTier {
+    /// The tier a match earns in text recognized from an image. Misreads and
+    /// stray interface text make it weaker evidence than the item's own
+    /// content, so it ranks one tier lower, but still counts as a match.
+    pub fn for_recognized_text(self) -> Self {
+        match self {
+            Self::ContentPrefix => Self::Dense,
+            Self::Dense => Self::Basic,
+            tier => tier,
+        }
+    }
+}
+
 /// Human-scale age bands used ahead of fine-grained ranking detail.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub enum RecencyBucket {
@@ -41,6 +58,27 @@ pub enum RecencyBucket {
     LastHour = 5,
 }
 
+/// How strongly the paste target's learned priors favour a candidate's content
+/// type and source app. Always `None` when search has no paste target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum PersonalizationBand {
+    #[default]
+    None = 0,
+    Familiar = 1,
+    Preferred = 2,
+}
+
+/// How often, and how lately, the user has pasted a candidate. Sits ahead of
+/// `recency_bucket` so a snippet pasted again and again outranks a newer
+/// one-off copy of similar text; a single paste never earns a band.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum FrecencyBand {
+    #[default]
+    None = 0,
+    Reused = 1,
+    Frequent = 2,
+}
+
 /// Coarse ranking detail used only after `quality_tier` and `recency_bucket`.
 ///
 /// The field order here is still the ranking policy. Each field is deliberately
@@ -0,0 +1,75 @@
+//! Per-thread scratch buffers for Phase 2 word matching.
+//!
+//! Phase 2 scores every candidate on every keystroke, and the char-level
+//! matchers compare each query word against each document word. Rayon keeps
+//! its workers alive across searches, so char buffers parked here are reused
+//! from one word pair, candidate, and keystroke to the next instead of being
+//! collected into a fresh `Vec<char>` each time.
+
+use std::cell::RefCell;
+
+/// Buffers that grew past this many chars (an unbroken run such as a pasted
+/// base64 blob) are released after use rather than held by the thread.
+const MAX_RETAINED_CHARS: usize = 64 * 1024;
+
+thread_local! {
+    static CHAR_BUFFERS: RefCell<Vec<Vec<char>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Run `visit` with the chars of each of `texts`, decoded into this thread's
+/// scratch buffers. A nested call from inside `visit` cannot borrow them
+/// again and falls back to allocating.
+pub(crate) fn with_chars<const N: usize, R>(
+    texts: [&str; N],
+    visit: impl FnOnce([&[char]; N]) -> R,
+) -> R {
+    CHAR_BUFFERS.with(|buffers| match buffers.try_borrow_mut() {
+        Ok(mut buffers) => {
+            if buffers.len() < N {
+                buffers.resize_with(N, Vec::new);
+            }
+            for (buffer, text) in buffers.iter_mut().zip(texts) {
+                buffer.clear();
+                buffer.extend(text.chars());
+            }
+            let result = visit(std::array::from_fn(|index| buffers[index].as_slice()));
+            for buffer in buffers.iter_mut() {
+                if buffer.capacity() > MAX_RETAINED_CHARS {
+                    *buffer = Vec::new();
+                }
+            }
+            result
+        }
+        Err(_) => {
+            let owned = texts.map(|text| text.chars().collect::<Vec<char>>());
+            visit(std::array::from_fn(|index| owned[index].as_slice()))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffers_are_reused_and_nested_calls_still_see_their_text() {
+        let first = with_chars(["héllo", "wörld"], |[a, b]| {
+            let nested = with_chars(["inner"], |[inner]| inner.iter().collect::<String>());
+            assert_eq!(nested, "inner");
+            (a.len(), b.iter().collect::<String>(), a.as_ptr() as usize)
+        });
+        assert_eq!((first.0, first.1.as_str()), (5, "wörld"));
+
+        let reused = with_chars(["abc"], |[a]| {
+            assert_eq!(a, ['a', 'b', 'c']);
+            a.as_ptr() as usize
+        });
+        assert_eq!(reused, first.2);
+
+        let huge = "x".repeat(MAX_RETAINED_CHARS + 1);
+        with_chars([huge.as_str()], |[chars]| {
+            assert_eq!(chars.len(), huge.len())
+        });
+        CHAR_BUFFERS.with(|buffers| assert_eq!(buffers.borrow()[0].capacity(), 0));
+    }
+}
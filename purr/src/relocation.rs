@@ -0,0 +1,247 @@
+//! Moving a store to another directory.
+//!
+//! The database is snapshotted with `VACUUM INTO` and the committed index is
+//! copied file by file into a staging directory beside the destination. Both
+//! copies are verified (SQLite `quick_check`, matching item and document
+//! counts) before being renamed into place, so an interrupted move never
+//! leaves a half-written store where the app will look for one. Images live
+//! inside the database, so there are no other blobs to carry. The originals
+//! stay until the caller removes them, once nothing has them open.
+
+use crate::database::{Database, DatabaseError};
+use crate::indexer::{IndexInspection, Indexer};
+use crate::interface::{ClipKittyError, StoreMoveProgress, StoreMoveStage};
+use crate::synced_folder;
+use std::path::{Path, PathBuf};
+
+/// Staging directory created inside the destination during a move.
+const STAGING_DIR: &str = ".clipkitty-move";
+
+/// On-disk location of a store.
+#[derive(Debug, Clone)]
+pub(crate) struct StoreFiles {
+    pub(crate) database: PathBuf,
+    pub(crate) index: PathBuf,
+}
+
+impl StoreFiles {
+    fn exists(&self) -> bool {
+        self.database.exists() || self.index.exists()
+    }
+
+    /// Best-effort removal of the database, its WAL side files, and the index.
+    pub(crate) fn remove(&self) {
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{suffix}", self.database.display()));
+        }
+        let _ = std::fs::remove_dir_all(&self.index);
+    }
+}
+
+/// Whether a store is serving calls or has been moved away.
+#[derive(Debug, Default)]
+pub(crate) enum RelocationState {
+    #[default]
+    Settled,
+    Moving,
+    Moved {
+        database: PathBuf,
+    },
+}
+
+impl RelocationState {
+    /// Reject calls while a move is running or after it has succeeded.
+    pub(crate) fn check(&self) -> Result<(), ClipKittyError> {
+        match self {
+            RelocationState::Settled => Ok(()),
+            RelocationState::Moving => Err(ClipKittyError::InvalidInput(
+                "the store is being moved".into(),
+            )),
+            RelocationState::Moved { database } => Err(ClipKittyError::InvalidInput(format!(
+                "the store moved to {}; reopen it there",
+                database.display()
+            ))),
+        }
+    }
+}
+
+/// Copy, verify, and swap the store at `from` into `to`. Writes must be
+/// stopped for the duration so the copies match the live store.
+pub(crate) fn move_store(
+    db: &Database,
+    indexer: &Indexer,
+    from: &StoreFiles,
+    to: &StoreFiles,
+    progress: Option<&dyn StoreMoveProgress>,
+) -> Result<(), ClipKittyError> {
+    let Some(directory) = to.database.parent() else {
+        return Err(ClipKittyError::InvalidInput(
+            "destination has no directory".into(),
+        ));
+    };
+    synced_folder::refuse_synced_path(&to.database)?;
+    std::fs::create_dir_all(directory).map_err(DatabaseError::from)?;
+    if same_directory(from, to) {
+        return Err(ClipKittyError::InvalidInput(
+            "the store is already in that directory".into(),
+        ));
+    }
+    if to.exists() {
+        return Err(ClipKittyError::InvalidInput(format!(
+            "{} already holds a clipboard history",
+            directory.display()
+        )));
+    }
+
+    indexer.commit()?;
+    let total_bytes = db.database_size()?.max(0) as u64 + indexer.disk_size()?;
+    if fs4::available_space(directory).is_ok_and(|available| available < total_bytes) {
+        return Err(ClipKittyError::StorageFull);
+    }
+
+    let staging = directory.join(STAGING_DIR);
+    // A leftover staging directory is from an interrupted move; its contents
+    // were never swapped in, so it is safe to discard.
+    let _ = std::fs::remove_dir_all(&staging);
+    std::fs::create_dir_all(&staging).map_err(DatabaseError::from)?;
+    let staged = StoreFiles {
+        database: staging.join(file_name(&to.database)?),
+        index: staging.join(file_name(&to.index)?),
+    };
+
+    let result = copy_and_verify(db, indexer, from, &staged, total_bytes, progress)
+        .and_then(|()| swap_into_place(&staged, to));
+    let _ = std::fs::remove_dir_all(&staging);
+    result?;
+    report(progress, StoreMoveStage::Finished, total_bytes, total_bytes);
+    Ok(())
+}
+
+fn copy_and_verify(
+    db: &Database,
+    indexer: &Indexer,
+    from: &StoreFiles,
+    staged: &StoreFiles,
+    total_bytes: u64,
+    progress: Option<&dyn StoreMoveProgress>,
+) -> Result<(), ClipKittyError> {
+    report(progress, StoreMoveStage::CopyingDatabase, 0, total_bytes);
+    db.snapshot_to(&staged.database)?;
+    let mut copied_bytes = std::fs::metadata(&staged.database)
+        .map_err(DatabaseError::from)?
+        .len();
+
+    // An encrypted store's index lives in memory and has nothing to copy.
+    let index_on_disk = !indexer.is_in_memory();
+    if index_on_disk {
+        report(
+            progress,
+            StoreMoveStage::CopyingIndex,
+            copied_bytes,
+            total_bytes,
+        );
+        indexer.copy_committed_to(&from.index, &staged.index, |file_bytes| {
+            copied_bytes += file_bytes;
+            report(
+                progress,
+                StoreMoveStage::CopyingIndex,
+                copied_bytes.min(total_bytes),
+                total_bytes,
+            );
+        })?;
+    }
+
+    report(
+        progress,
+        StoreMoveStage::Verifying,
+        total_bytes,
+        total_bytes,
+    );
+    let staged_items = Database::verify_snapshot(&staged.database)?;
+    let live_items = db.count_items()?;
+    if staged_items != live_items {
+        return Err(ClipKittyError::DataInconsistency(format!(
+            "copied database has {staged_items} items, expected {live_items}"
+        )));
+    }
+    if !index_on_disk {
+        return Ok(());
+    }
+    match Indexer::inspect(&staged.index)? {
+        IndexInspection::Ready { doc_count } if doc_count == indexer.num_docs() => Ok(()),
+        inspection => Err(ClipKittyError::IndexError(format!(
+            "copied index failed verification: {inspection:?}"
+        ))),
+    }
+}
+
+/// Rename the verified copies to their final names. Both renames stay within
+/// one directory tree, so each is atomic; a failed second rename takes the
+/// first back out so the destination never holds half a store. The copies
+/// are synced before the renames and the destination directory after, so a
+/// power loss cannot leave renamed but empty files once the originals go.
+fn swap_into_place(staged: &StoreFiles, to: &StoreFiles) -> Result<(), ClipKittyError> {
+    if let Some(staging) = staged.database.parent() {
+        sync_tree(staging).map_err(DatabaseError::from)?;
+    }
+    std::fs::rename(&staged.database, &to.database).map_err(DatabaseError::from)?;
+    // An in-memory index leaves no copy to move.
+    let moved_index = match staged.index.exists() {
+        true => std::fs::rename(&staged.index, &to.index),
+        false => Ok(()),
+    };
+    if let Err(error) = moved_index {
+        let _ = std::fs::remove_file(&to.database);
+        return Err(DatabaseError::from(error).into());
+    }
+    if let Some(directory) = to.database.parent() {
+        sync_entry(directory).map_err(DatabaseError::from)?;
+    }
+    Ok(())
+}
+
+/// `fsync` a directory's entries and everything beneath it.
+fn sync_tree(directory: &Path) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            sync_tree(&entry.path())?;
+        } else {
+            sync_entry(&entry.path())?;
+        }
+    }
+    sync_entry(directory)
+}
+
+/// `fsync` one file, or one directory's list of entries.
+fn sync_entry(path: &Path) -> std::io::Result<()> {
+    std::fs::File::open(path)?.sync_all()
+}
+
+fn same_directory(from: &StoreFiles, to: &StoreFiles) -> bool {
+    let canonical_parent =
+        |path: &Path| path.parent().and_then(|parent| parent.canonicalize().ok());
+    match (
+        canonical_parent(&from.database),
+        canonical_parent(&to.database),
+    ) {
+        (Some(from), Some(to)) => from == to,
+        _ => false,
+    }
+}
+
+fn file_name(path: &Path) -> Result<&std::ffi::OsStr, ClipKittyError> {
+    path.file_name()
+        .ok_or_else(|| ClipKittyError::InvalidInput(format!("{} has no file name", path.display())))
+}
+
+fn report(
+    progress: Option<&dyn StoreMoveProgress>,
+    stage: StoreMoveStage,
+    copied_bytes: u64,
+    total_bytes: u64,
+) {
+    if let Some(progress) = progress {
+        progress.on_move_progress(stage, copied_bytes, total_bytes);
+    }
+}
@@ -0,0 +1,93 @@
+//! Reminders that resurface an item at a chosen time.
+//!
+//! A copied address or coupon code can be set to come back when it is
+//! needed. Each item has at most one reminder, deleted along with the item.
+//! The host polls `due` and clears each reminder once it has shown it, so a
+//! reminder fires once even when the host polls late. Reminders travel with
+//! sync and with bundle exports.
+
+use crate::database::Database;
+use crate::interface::{ClipKittyError, ItemReminder};
+use chrono::{DateTime, Utc};
+
+fn timestamp(unix: i64) -> Result<DateTime<Utc>, ClipKittyError> {
+    DateTime::from_timestamp(unix, 0)
+        .ok_or_else(|| ClipKittyError::InvalidInput(format!("{unix} is not a valid time")))
+}
+
+pub(crate) fn set(db: &Database, row_id: i64, fire_at_unix: i64) -> Result<(), ClipKittyError> {
+    Ok(db.set_reminder(row_id, timestamp(fire_at_unix)?)?)
+}
+
+/// Clear the item's reminder. Returns whether it had one.
+pub(crate) fn clear(db: &Database, row_id: i64) -> Result<bool, ClipKittyError> {
+    Ok(db.clear_reminder(row_id)?)
+}
+
+/// Set or clear the reminder to match synced or imported state.
+#[cfg(feature = "sync")]
+pub(crate) fn restore(
+    db: &Database,
+    row_id: i64,
+    fire_at_unix: Option<i64>,
+) -> Result<(), ClipKittyError> {
+    match fire_at_unix {
+        Some(fire_at_unix) => set(db, row_id, fire_at_unix),
+        None => clear(db, row_id).map(|_| ()),
+    }
+}
+
+/// Reminders due at `now_unix`, earliest first.
+pub(crate) fn due(db: &Database, now_unix: i64) -> Result<Vec<ItemReminder>, ClipKittyError> {
+    Ok(db
+        .fetch_due_reminders(timestamp(now_unix)?)?
+        .into_iter()
+        .map(|(item_id, fire_at_unix)| ItemReminder {
+            item_id,
+            fire_at_unix,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StoredItem;
+
+    #[test]
+    fn due_reminders_come_back_until_cleared() {
+        let db = Database::open_in_memory().unwrap();
+        let insert = |text: &str| {
+            let item = StoredItem::new_text(text.into(), None, None);
+            (db.insert_item(&item).unwrap(), item.item_id)
+        };
+        let (coupon, coupon_id) = insert("SAVE20");
+        let (address, address_id) = insert("1 Infinite Loop");
+        let (later, _) = insert("dentist");
+
+        set(&db, address, 1_000).unwrap();
+        set(&db, coupon, 900).unwrap();
+        set(&db, later, 5_000).unwrap();
+        assert!(due(&db, 899).unwrap().is_empty());
+        let reminders = |now| -> Vec<(String, i64)> {
+            due(&db, now)
+                .unwrap()
+                .into_iter()
+                .map(|reminder| (reminder.item_id, reminder.fire_at_unix))
+                .collect()
+        };
+        assert_eq!(
+            reminders(1_000),
+            [(coupon_id, 900), (address_id.clone(), 1_000)]
+        );
+
+        assert!(clear(&db, coupon).unwrap());
+        assert!(!clear(&db, coupon).unwrap());
+        restore(&db, later, None).unwrap();
+        assert_eq!(reminders(10_000), [(address_id, 1_000)]);
+
+        db.delete_item(address).unwrap();
+        assert!(reminders(10_000).is_empty());
+        assert!(set(&db, later, i64::MAX).is_err());
+    }
+}
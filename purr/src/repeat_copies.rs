@@ -0,0 +1,123 @@
+//! Coalescing of rapid repeat text copies.
+//!
+//! Some apps write the same text to the pasteboard several times within
+//! milliseconds, as with copy-on-select. A repeat from the same app arriving
+//! within the window of its previous copy is merged into it outright: no
+//! timestamp bump, copy event, or index write. The window slides, so a burst
+//! stays merged for as long as it keeps arriving. Repeats after the window
+//! go through the usual duplicate handling.
+
+use parking_lot::Mutex;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// How close together two copies of the same text must be to merge.
+pub(crate) const DEFAULT_WINDOW: Duration = Duration::from_millis(500);
+
+struct LastCopy {
+    /// Hash of the text and its source, so the filter holds no copied content.
+    copy_hash: u64,
+    item_id: String,
+    at: Instant,
+}
+
+pub(crate) struct RepeatCopyFilter {
+    window_ms: AtomicU64,
+    last: Mutex<Option<LastCopy>>,
+}
+
+impl Default for RepeatCopyFilter {
+    fn default() -> Self {
+        Self {
+            window_ms: AtomicU64::new(DEFAULT_WINDOW.as_millis() as u64),
+            last: Mutex::new(None),
+        }
+    }
+}
+
+impl RepeatCopyFilter {
+    /// Change the window; zero turns coalescing off.
+    pub(crate) fn set_window(&self, window: Duration) {
+        self.window_ms
+            .store(window.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn window(&self) -> Duration {
+        Duration::from_millis(self.window_ms.load(Ordering::Relaxed))
+    }
+
+    /// The item a copy with `copy_hash` merges into, when it repeats the last
+    /// copy within the window, which then restarts.
+    pub(crate) fn repeat_of(&self, copy_hash: u64, now: Instant) -> Option<String> {
+        let window = self.window();
+        let mut last = self.last.lock();
+        let copy = last.as_mut()?;
+        if window.is_zero() || copy.copy_hash != copy_hash || now.duration_since(copy.at) > window {
+            return None;
+        }
+        copy.at = now;
+        Some(copy.item_id.clone())
+    }
+
+    /// Remember a copy with `copy_hash` that was saved as `item_id`.
+    pub(crate) fn record(&self, copy_hash: u64, item_id: &str, now: Instant) {
+        *self.last.lock() = Some(LastCopy {
+            copy_hash,
+            item_id: item_id.to_string(),
+            at: now,
+        });
+    }
+}
+
+/// Hash of a copy for `repeat_of` and `record`. The same text from another
+/// app is a separate copy.
+pub(crate) fn copy_hash(
+    text: &str,
+    source_app: Option<&str>,
+    source_app_bundle_id: Option<&str>,
+) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (text, source_app, source_app_bundle_id).hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_within_the_window_merge_into_the_first_copy() {
+        let filter = RepeatCopyFilter::default();
+        let start = Instant::now();
+        let selected = copy_hash("selected", Some("Terminal"), None);
+        assert_eq!(filter.repeat_of(selected, start), None);
+        filter.record(selected, "item-1", start);
+
+        let soon = start + Duration::from_millis(40);
+        assert_eq!(filter.repeat_of(selected, soon), Some("item-1".into()));
+        // The window slides with each repeat.
+        let later = soon + Duration::from_millis(480);
+        assert_eq!(filter.repeat_of(selected, later), Some("item-1".into()));
+        assert_eq!(
+            filter.repeat_of(selected, later + Duration::from_secs(2)),
+            None
+        );
+    }
+
+    #[test]
+    fn other_text_other_apps_and_a_zero_window_do_not_merge() {
+        let filter = RepeatCopyFilter::default();
+        let start = Instant::now();
+        let first = copy_hash("first", None, Some("com.apple.Safari"));
+        filter.record(first, "item-1", start);
+        let second = copy_hash("second", None, Some("com.apple.Safari"));
+        assert_eq!(filter.repeat_of(second, start), None);
+        let elsewhere = copy_hash("first", None, Some("com.apple.Notes"));
+        assert_eq!(filter.repeat_of(elsewhere, start), None);
+
+        filter.set_window(Duration::ZERO);
+        assert_eq!(filter.repeat_of(first, start), None);
+    }
+}
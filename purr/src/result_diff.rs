@@ -0,0 +1,170 @@
+//! Diffs between successive result lists.
+//!
+//! Live lists re-run the search on every keystroke and on store changes.
+//! Replacing the rows wholesale resets the scroll position, so the list
+//! applies the difference instead: items only in the previous list are
+//! removed, items only in the current one inserted, and of the items in both,
+//! the longest run that kept its relative order stays put while the rest move.
+//! Keeping that run as large as possible keeps the rows the user is looking
+//! at anchored.
+
+use crate::interface::{ResultDiff, ResultMove};
+use std::collections::{HashMap, VecDeque};
+
+/// The batch update that turns the `previous` list of item IDs into `current`.
+#[uniffi::export]
+pub fn diff_results(previous: Vec<String>, current: Vec<String>) -> ResultDiff {
+    diff(&previous, &current)
+}
+
+pub(crate) fn diff(previous: &[String], current: &[String]) -> ResultDiff {
+    // Repeated IDs pair up in order of appearance.
+    let mut previous_positions: HashMap<&str, VecDeque<usize>> = HashMap::new();
+    for (index, id) in previous.iter().enumerate() {
+        previous_positions
+            .entry(id.as_str())
+            .or_default()
+            .push_back(index);
+    }
+
+    let mut inserted = Vec::new();
+    // (previous index, current index) of kept items, in current order.
+    let mut kept = Vec::new();
+    for (index, id) in current.iter().enumerate() {
+        match previous_positions
+            .get_mut(id.as_str())
+            .and_then(VecDeque::pop_front)
+        {
+            Some(from) => kept.push((from, index)),
+            None => inserted.push(index as u64),
+        }
+    }
+
+    let mut removed: Vec<u64> = previous_positions
+        .into_values()
+        .flatten()
+        .map(|index| index as u64)
+        .collect();
+    removed.sort_unstable();
+
+    let in_place = longest_increasing_run(&kept);
+    let moved = kept
+        .iter()
+        .zip(in_place)
+        .filter(|(_, stays)| !stays)
+        .map(|(&(from, to), _)| ResultMove {
+            from: from as u64,
+            to: to as u64,
+        })
+        .collect();
+
+    ResultDiff {
+        removed,
+        inserted,
+        moved,
+    }
+}
+
+/// Marks the pairs on a longest run whose previous indices increase: the
+/// items that can stay where they are.
+fn longest_increasing_run(kept: &[(usize, usize)]) -> Vec<bool> {
+    // tails[k]: position in `kept` of the smallest previous index ending a
+    // run of length k + 1.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut predecessor = vec![None; kept.len()];
+    for (position, &(from, _)) in kept.iter().enumerate() {
+        let length = tails.partition_point(|&tail| kept[tail].0 < from);
+        if length > 0 {
+            predecessor[position] = Some(tails[length - 1]);
+        }
+        if length == tails.len() {
+            tails.push(position);
+        } else {
+            tails[length] = position;
+        }
+    }
+
+    let mut in_place = vec![false; kept.len()];
+    let mut next = tails.last().copied();
+    while let Some(position) = next {
+        in_place[position] = true;
+        next = predecessor[position];
+    }
+    in_place
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(ids: &str) -> Vec<String> {
+        ids.split_whitespace().map(str::to_string).collect()
+    }
+
+    /// Apply `diff` the way a list view does: deletions and move sources
+    /// against the previous list, then insertions and move targets in
+    /// ascending order against the current one.
+    fn apply(previous: &[String], current: &[String], diff: &ResultDiff) -> Vec<String> {
+        let mut leaving: Vec<u64> = diff.removed.clone();
+        leaving.extend(diff.moved.iter().map(|m| m.from));
+        let mut rows: Vec<Option<String>> = previous
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !leaving.contains(&(*index as u64)))
+            .map(|(_, id)| Some(id.clone()))
+            .collect();
+        let mut arriving: Vec<u64> = diff.inserted.clone();
+        arriving.extend(diff.moved.iter().map(|m| m.to));
+        arriving.sort_unstable();
+        for index in arriving {
+            rows.insert(index as usize, Some(current[index as usize].clone()));
+        }
+        rows.into_iter().flatten().collect()
+    }
+
+    #[test]
+    fn identical_lists_need_no_changes() {
+        let list = ids("a b c d");
+        assert_eq!(diff(&list, &list), ResultDiff::default());
+    }
+
+    #[test]
+    fn a_new_copy_at_the_top_is_one_insertion() {
+        let diff = diff(&ids("a b c d"), &ids("z a b c"));
+        assert_eq!(diff.inserted, vec![0]);
+        assert_eq!(diff.removed, vec![3]);
+        assert!(diff.moved.is_empty());
+    }
+
+    #[test]
+    fn an_item_rising_to_the_top_moves_alone() {
+        let diff = diff(&ids("a b c d e"), &ids("d a b c e"));
+        assert!(diff.inserted.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.moved, vec![ResultMove { from: 3, to: 0 }]);
+    }
+
+    #[test]
+    fn applying_the_diff_yields_the_current_list() {
+        let cases = [
+            ("a b c d e f", "f e d c b a"),
+            ("a b c d e f", "c x a f b y"),
+            ("a b c", ""),
+            ("", "a b c"),
+            ("a b a c", "a c a b"),
+            ("q w e r t y u i o p", "w q e t r y x o i p"),
+        ];
+        for (previous, current) in cases {
+            let (previous, current) = (ids(previous), ids(current));
+            let diff = diff(&previous, &current);
+            assert_eq!(apply(&previous, &current, &diff), current, "{diff:?}");
+            let kept = current.len() - diff.inserted.len();
+            assert_eq!(kept, previous.len() - diff.removed.len());
+        }
+    }
+
+    #[test]
+    fn reversal_keeps_one_item_in_place() {
+        let diff = diff(&ids("a b c d"), &ids("d c b a"));
+        assert_eq!(diff.moved.len(), 3);
+    }
+}
@@ -0,0 +1,228 @@
+//! How long history is kept.
+//!
+//! The retention policy lives in the store with the other capture settings,
+//! and `enforce` applies it: items older than their content type's rule, or
+//! the policy's default age, go first, then the oldest past the item count.
+//! Pinned and immutable items are never deleted, and trashed items are left
+//! to `empty_trash`, which `run_maintenance` also runs for items trashed
+//! more than `TRASH_MAX_AGE` ago. The host runs it from `run_maintenance`,
+//! as it does pruning.
+
+use crate::database::{Database, DatabaseError};
+use crate::indexer::Indexer;
+use crate::interface::{ClipKittyError, ContentTypeFilter, ContentTypeRetention, RetentionPolicy};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+const RETENTION_NAMESPACE: &str = "retention";
+const POLICY_KEY: &str = "policy";
+/// A century; anything longer is keeping items forever.
+const MAX_AGE_SECONDS: u64 = 100 * 365 * 24 * 3600;
+/// How long trashed items wait before maintenance deletes them for good.
+pub(crate) const TRASH_MAX_AGE: Duration = Duration::days(30);
+
+/// `RetentionPolicy` as stored, with content types by `database_str`.
+#[derive(Serialize, Deserialize)]
+struct StoredPolicy {
+    max_age_seconds: Option<u64>,
+    max_item_count: Option<u64>,
+    content_type_ages: Vec<(String, u64)>,
+}
+
+/// Replace the policy. The default policy keeps everything.
+pub(crate) fn set_policy(db: &Database, policy: RetentionPolicy) -> Result<(), ClipKittyError> {
+    validate(&policy)?;
+    if policy == RetentionPolicy::default() {
+        return Ok(db.delete_key_value(RETENTION_NAMESPACE, POLICY_KEY)?);
+    }
+    let stored = StoredPolicy {
+        max_age_seconds: policy.max_age_seconds,
+        max_item_count: policy.max_item_count,
+        content_type_ages: policy
+            .content_type_rules
+            .iter()
+            .map(|rule| {
+                (
+                    rule.content_type.database_str().to_string(),
+                    rule.max_age_seconds,
+                )
+            })
+            .collect(),
+    };
+    let encoded = serde_json::to_vec(&stored).expect("a retention policy always serializes");
+    Ok(db.put_key_value(RETENTION_NAMESPACE, POLICY_KEY, &encoded)?)
+}
+
+pub(crate) fn policy(db: &Database) -> Result<RetentionPolicy, ClipKittyError> {
+    let Some(bytes) = db.get_key_value(RETENTION_NAMESPACE, POLICY_KEY)? else {
+        return Ok(RetentionPolicy::default());
+    };
+    let unreadable = || DatabaseError::InconsistentData("retention policy is unreadable".into());
+    let stored: StoredPolicy = serde_json::from_slice(&bytes).map_err(|_| unreadable())?;
+    let content_type_rules = stored
+        .content_type_ages
+        .into_iter()
+        .map(|(content_type, max_age_seconds)| {
+            Ok(ContentTypeRetention {
+                content_type: ContentTypeFilter::from_database_str(&content_type)
+                    .map_err(|_| unreadable())?,
+                max_age_seconds,
+            })
+        })
+        .collect::<Result<_, DatabaseError>>()?;
+    Ok(RetentionPolicy {
+        max_age_seconds: stored.max_age_seconds,
+        max_item_count: stored.max_item_count,
+        content_type_rules,
+    })
+}
+
+fn validate(policy: &RetentionPolicy) -> Result<(), ClipKittyError> {
+    let ages = policy.max_age_seconds.into_iter().chain(
+        policy
+            .content_type_rules
+            .iter()
+            .map(|rule| rule.max_age_seconds),
+    );
+    for age in ages {
+        if !(1..=MAX_AGE_SECONDS).contains(&age) {
+            return Err(ClipKittyError::InvalidInput(format!(
+                "retention ages must be between 1 and {MAX_AGE_SECONDS} seconds"
+            )));
+        }
+    }
+    if policy.max_item_count == Some(0) {
+        return Err(ClipKittyError::InvalidInput(
+            "retention item count must be at least 1".into(),
+        ));
+    }
+    for (index, rule) in policy.content_type_rules.iter().enumerate() {
+        if rule.content_type == ContentTypeFilter::All {
+            return Err(ClipKittyError::InvalidInput(
+                "a content type rule needs a content type; use max_age_seconds for all items"
+                    .into(),
+            ));
+        }
+        if policy.content_type_rules[..index]
+            .iter()
+            .any(|earlier| earlier.content_type == rule.content_type)
+        {
+            return Err(ClipKittyError::InvalidInput(format!(
+                "more than one retention rule for {}",
+                rule.content_type.database_str()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Delete every item the policy no longer keeps at `now` and return their
+/// item IDs.
+pub(crate) fn enforce(
+    db: &Database,
+    indexer: &Indexer,
+    now: DateTime<Utc>,
+) -> Result<Vec<String>, ClipKittyError> {
+    let policy = policy(db)?;
+    if policy == RetentionPolicy::default() {
+        return Ok(Vec::new());
+    }
+    let candidates = db.fetch_retention_candidates()?;
+    // Pinned and immutable items take up room under the count but are kept.
+    let protected = db.count_items()?.saturating_sub(candidates.len() as u64);
+    let mut room = policy
+        .max_item_count
+        .map(|count| count.saturating_sub(protected));
+    let mut expired = Vec::new();
+    for (row_id, item_id, content_type, timestamp) in candidates {
+        let max_age_seconds = policy
+            .content_type_rules
+            .iter()
+            .find(|rule| rule.content_type.matches_db_type(&content_type))
+            .map_or(policy.max_age_seconds, |rule| Some(rule.max_age_seconds));
+        let too_old = max_age_seconds
+            .is_some_and(|seconds| now - timestamp > Duration::seconds(seconds as i64));
+        let keep = !too_old
+            && match room.as_mut() {
+                None => true,
+                Some(0) => false,
+                Some(left) => {
+                    *left -= 1;
+                    true
+                }
+            };
+        if !keep {
+            expired.push((row_id, item_id));
+        }
+    }
+    for (row_id, item_id) in &expired {
+        db.delete_item(*row_id)?;
+        indexer.delete_document(item_id)?;
+    }
+    if !expired.is_empty() {
+        indexer.commit()?;
+    }
+    Ok(expired.into_iter().map(|(_, item_id)| item_id).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save_service::{self, InsertOutcome};
+
+    fn save(db: &Database, indexer: &Indexer, text: &str, age: Duration) -> i64 {
+        let row_id = match save_service::save_text(db, indexer, text.into(), None, None).unwrap() {
+            InsertOutcome::Inserted { new_id, .. } => new_id,
+            InsertOutcome::Deduplicated { existing_id, .. } => existing_id,
+        };
+        db.update_timestamp(row_id, Utc::now() - age).unwrap();
+        row_id
+    }
+
+    #[test]
+    fn policy_deletes_by_age_type_and_count() {
+        let db = Database::open_in_memory().unwrap();
+        let indexer = Indexer::new_in_memory().unwrap();
+        let policy = RetentionPolicy {
+            max_age_seconds: Some(90 * 24 * 3600),
+            max_item_count: Some(3),
+            content_type_rules: vec![ContentTypeRetention {
+                content_type: ContentTypeFilter::Links,
+                max_age_seconds: 24 * 3600,
+            }],
+        };
+        set_policy(&db, policy.clone()).unwrap();
+        assert_eq!(super::policy(&db).unwrap(), policy);
+
+        let pinned = save(&db, &indexer, "old pinned note", Duration::days(400));
+        db.set_pinned(pinned, true).unwrap();
+        let ancient = save(&db, &indexer, "old note", Duration::days(100));
+        let stale_link = save(&db, &indexer, "https://example.com/a", Duration::days(2));
+        let recent_link = save(&db, &indexer, "https://example.com/b", Duration::hours(1));
+        let overflow = save(&db, &indexer, "first note", Duration::days(10));
+        let kept = save(&db, &indexer, "second note", Duration::days(5));
+
+        let expired = enforce(&db, &indexer, Utc::now()).unwrap();
+        assert_eq!(expired.len(), 3);
+        for gone in [ancient, stale_link, overflow] {
+            assert!(db.fetch_items_by_ids(&[gone]).unwrap().is_empty());
+        }
+        let left = db.fetch_items_by_ids(&[pinned, recent_link, kept]).unwrap();
+        assert_eq!(left.len(), 3);
+        assert!(enforce(&db, &indexer, Utc::now()).unwrap().is_empty());
+
+        let mut invalid = policy.clone();
+        invalid.content_type_rules[0].content_type = ContentTypeFilter::All;
+        assert!(set_policy(&db, invalid).is_err());
+        invalid = policy.clone();
+        invalid
+            .content_type_rules
+            .push(invalid.content_type_rules[0].clone());
+        assert!(set_policy(&db, invalid).is_err());
+        invalid = policy;
+        invalid.max_item_count = Some(0);
+        assert!(set_policy(&db, invalid).is_err());
+        set_policy(&db, RetentionPolicy::default()).unwrap();
+        assert_eq!(super::policy(&db).unwrap(), RetentionPolicy::default());
+    }
+}
@@ -1,10 +1,13 @@
 use crate::database::Database;
+use crate::icon_service::document_kind;
 use crate::indexer::Indexer;
 use crate::interface::{
-    ClipKittyError, FilePreviewSnapshot, ItemTag, LinkMetadataPayload, LinkMetadataState,
+    ArchiveListing, ClipKittyError, DocumentInfo, DocumentKind, EmailInfo, FilePreviewSnapshot,
+    ItemTag, LinkMetadataPayload, LinkMetadataState, MediaInfo,
 };
 use crate::models::StoredItem;
-use chrono::Utc;
+use crate::{archive_info, email_info, media_info, pdf_info, sensitive_items, snippets};
+use chrono::{DateTime, TimeZone, Utc};
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // Outcome types — callers use these to decide what sync events to emit.
@@ -18,6 +21,8 @@ pub(crate) enum InsertOutcome {
         existing_id: i64,
         item_id: String,
         touched_at_unix: i64,
+        /// The item's timestamp before it was touched.
+        previous_at: DateTime<Utc>,
     },
     /// A new item was inserted.
     Inserted {
@@ -79,8 +84,111 @@ pub(crate) fn save_text(
     source_app: Option<String>,
     source_app_bundle_id: Option<String>,
 ) -> Result<InsertOutcome, ClipKittyError> {
+    let is_secret = crate::content_detection::is_probably_secret(&text);
     let item = StoredItem::new_text(text, source_app, source_app_bundle_id);
-    dedupe_or_insert_and_index(db, indexer, item)
+    insert_text_item(db, indexer, item, is_secret)
+}
+
+fn insert_text_item(
+    db: &Database,
+    indexer: &Indexer,
+    item: StoredItem,
+    is_secret: bool,
+) -> Result<InsertOutcome, ClipKittyError> {
+    let outcome = dedupe_or_insert_and_index(db, indexer, item)?;
+    if is_secret {
+        let row_id = match &outcome {
+            InsertOutcome::Deduplicated { existing_id, .. } => *existing_id,
+            InsertOutcome::Inserted { new_id, .. } => *new_id,
+        };
+        sensitive_items::schedule_expiry(db, row_id, Utc::now())?;
+    }
+    Ok(outcome)
+}
+
+/// Save many texts in one database transaction and one index commit.
+/// Outcomes are returned in input order; a text repeated within the batch
+/// deduplicates against its first occurrence.
+pub(crate) fn save_texts_batch(
+    db: &Database,
+    indexer: &Indexer,
+    inputs: Vec<(String, Option<String>, Option<String>)>,
+) -> Result<Vec<InsertOutcome>, ClipKittyError> {
+    let mut secrets = Vec::with_capacity(inputs.len());
+    let items: Vec<StoredItem> = inputs
+        .into_iter()
+        .map(|(text, source_app, source_app_bundle_id)| {
+            secrets.push(crate::content_detection::is_probably_secret(&text));
+            StoredItem::new_text(text, source_app, source_app_bundle_id)
+        })
+        .collect();
+    let now = Utc::now();
+    let rows = db.insert_items_deduplicated(&items, now)?;
+
+    let mut touched: Vec<i64> = rows.iter().map(|(id, _)| *id).collect();
+    touched.sort_unstable();
+    touched.dedup();
+    let stored = db.fetch_items_by_ids(&touched)?;
+    for item in &stored {
+        let Some(id) = item.id else { continue };
+        indexer.add_document_with_row(
+            &item.item_id,
+            &index_text(item),
+            item.timestamp_unix,
+            &db.fetch_user_tags(id)?,
+            &Database::indexed_row(item),
+        )?;
+    }
+    indexer.commit()?;
+
+    let mut outcomes = Vec::with_capacity(items.len());
+    for ((item, (row_id, previous_at)), is_secret) in items.into_iter().zip(rows).zip(secrets) {
+        if is_secret {
+            sensitive_items::schedule_expiry(db, row_id, now)?;
+        }
+        let outcome = if let Some(previous_at) = previous_at {
+            let item_id = stored
+                .iter()
+                .find(|s| s.id == Some(row_id))
+                .map(|s| s.item_id.clone())
+                .unwrap_or_default();
+            InsertOutcome::Deduplicated {
+                existing_id: row_id,
+                item_id,
+                touched_at_unix: now.timestamp(),
+                previous_at,
+            }
+        } else {
+            InsertOutcome::Inserted {
+                new_id: row_id,
+                item_id: item.item_id.clone(),
+                item,
+            }
+        };
+        outcomes.push(outcome);
+    }
+    Ok(outcomes)
+}
+
+/// Save formatted text. Without RTF or HTML there is nothing to keep beyond
+/// the plain text, so it is saved as a text item.
+pub(crate) fn save_rich_text(
+    db: &Database,
+    indexer: &Indexer,
+    plain: String,
+    rtf_data: Option<Vec<u8>>,
+    html: Option<String>,
+    source_app: Option<String>,
+    source_app_bundle_id: Option<String>,
+) -> Result<InsertOutcome, ClipKittyError> {
+    let rtf_data = rtf_data.filter(|rtf| !rtf.is_empty());
+    let html = html.filter(|html| !html.trim().is_empty());
+    if rtf_data.is_none() && html.is_none() {
+        return save_text(db, indexer, plain, source_app, source_app_bundle_id);
+    }
+    let is_secret = crate::content_detection::is_probably_secret(&plain);
+    let item = StoredItem::new_rich_text(plain, rtf_data, html, source_app, source_app_bundle_id);
+    insert_text_item(db, indexer, item, is_secret)
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -96,7 +204,9 @@ pub(crate) fn save_file(
     source_app: Option<String>,
     source_app_bundle_id: Option<String>,
 ) -> Result<InsertOutcome, ClipKittyError> {
-    let item = StoredItem::new_file(
+    let probed = ProbedFiles::probe([(&path, &filename, &uti)]);
+    let preview = probed.preview(0, preview);
+    let mut item = StoredItem::new_file(
         path,
         filename,
         file_size,
@@ -106,7 +216,10 @@ pub(crate) fn save_file(
         source_app,
         source_app_bundle_id,
     );
-    dedupe_or_insert_and_index(db, indexer, item)
+    item.file_contents_text = probed.contents_text();
+    let outcome = dedupe_or_insert_and_index(db, indexer, item)?;
+    probed.store(db, &outcome)?;
+    Ok(outcome)
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -130,7 +243,19 @@ pub(crate) fn save_files(
         bookmark_data_list.len(),
         preview_snapshots.len(),
     )?;
-    let item = StoredItem::new_files(
+    let probed = ProbedFiles::probe(
+        paths
+            .iter()
+            .zip(&filenames)
+            .zip(&utis)
+            .map(|((path, filename), uti)| (path, filename, uti)),
+    );
+    let preview_snapshots = preview_snapshots
+        .into_iter()
+        .enumerate()
+        .map(|(index, preview)| probed.preview(index as u64, preview))
+        .collect();
+    let mut item = StoredItem::new_files(
         paths,
         filenames,
         file_sizes,
@@ -140,7 +265,117 @@ pub(crate) fn save_files(
         source_app,
         source_app_bundle_id,
     );
-    dedupe_or_insert_and_index(db, indexer, item)
+    item.file_contents_text = probed.contents_text();
+    let outcome = dedupe_or_insert_and_index(db, indexer, item)?;
+    probed.store(db, &outcome)?;
+    Ok(outcome)
+}
+
+/// What Rust read from inside the files of a file item at capture, by file
+/// index.
+#[derive(Default)]
+struct ProbedFiles {
+    /// From the headers of audio and video files.
+    media: Vec<(u64, MediaInfo)>,
+    /// From the info dictionary and page tree of PDFs.
+    documents: Vec<(u64, DocumentInfo)>,
+    /// From the headers of exported emails.
+    emails: Vec<(u64, EmailInfo)>,
+    /// From the entry tables of zip and tar archives.
+    archives: Vec<(u64, ArchiveListing)>,
+}
+
+impl ProbedFiles {
+    fn probe<'a>(files: impl IntoIterator<Item = (&'a String, &'a String, &'a String)>) -> Self {
+        let mut probed = Self::default();
+        for (index, (path, filename, uti)) in files.into_iter().enumerate() {
+            let index = index as u64;
+            let path = std::path::Path::new(path);
+            match document_kind(uti, filename) {
+                DocumentKind::Audio | DocumentKind::Video => {
+                    probed
+                        .media
+                        .extend(media_info::probe(path).map(|info| (index, info)));
+                }
+                DocumentKind::Pdf => {
+                    probed
+                        .documents
+                        .extend(pdf_info::probe(path).map(|info| (index, info)));
+                }
+                DocumentKind::Archive => {
+                    probed
+                        .archives
+                        .extend(archive_info::probe(path).map(|listing| (index, listing)));
+                }
+                _ if email_info::is_email(uti, filename) => {
+                    probed
+                        .emails
+                        .extend(email_info::probe(path).map(|info| (index, info)));
+                }
+                _ => {}
+            }
+        }
+        probed
+    }
+
+    /// Search text for the new item, matching what loading it back yields.
+    fn contents_text(&self) -> Option<String> {
+        let documents = self.documents.iter().map(|(index, info)| {
+            let text = pdf_info::index_text(info.title.as_deref(), info.author.as_deref());
+            (*index, text)
+        });
+        let emails = self.emails.iter().map(|(index, info)| {
+            let text = email_info::index_text(info.subject.as_deref(), info.sender.as_deref());
+            (*index, text)
+        });
+        let archives = self
+            .archives
+            .iter()
+            .map(|(index, listing)| (*index, archive_info::index_text(&listing.entries)));
+        let mut texts: Vec<(u64, String)> = documents
+            .chain(emails)
+            .chain(archives)
+            .filter_map(|(index, text)| Some((index, text?)))
+            .collect();
+        texts.sort_by_key(|(index, _)| *index);
+        let lines: Vec<String> = texts.into_iter().map(|(_, text)| text).collect();
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    /// The preview to capture for the file at `index`: an archive the app
+    /// could not preview shows its entry names instead.
+    fn preview(&self, index: u64, preview: FilePreviewSnapshot) -> FilePreviewSnapshot {
+        let listing = self
+            .archives
+            .iter()
+            .find(|(file_index, _)| *file_index == index);
+        match (preview, listing) {
+            (FilePreviewSnapshot::Unavailable { .. }, Some((_, listing))) => {
+                archive_info::preview(listing)
+            }
+            (preview, _) => preview,
+        }
+    }
+
+    /// Record what was read on a newly inserted item. A duplicate already has
+    /// it from its own capture, or from the app since.
+    fn store(self, db: &Database, outcome: &InsertOutcome) -> Result<(), ClipKittyError> {
+        if let InsertOutcome::Inserted { new_id, .. } = outcome {
+            for (file_index, info) in self.media {
+                db.set_file_media_info(*new_id, file_index, &info, None)?;
+            }
+            for (file_index, info) in self.documents {
+                db.set_file_document_info(*new_id, file_index, &info, None)?;
+            }
+            for (file_index, info) in self.emails {
+                db.set_file_email_info(*new_id, file_index, &info)?;
+            }
+            for (file_index, listing) in self.archives {
+                db.set_file_archive_listing(*new_id, file_index, &listing)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 fn validate_file_metadata_lengths(
@@ -254,8 +489,47 @@ pub(crate) fn update_image_description(
     // items carry "File:". It is persisted and indexed verbatim.
     db.update_image_description(item_id, &description)?;
     if let Some(item) = get_stored_item(db, item_id)? {
+        let tags = db.fetch_user_tags(item_id)?;
         if indexer
-            .add_document(&item.item_id, &description, item.timestamp_unix)
+            .add_document_with_row(
+                &item.item_id,
+                &description,
+                item.timestamp_unix,
+                &tags,
+                &Database::indexed_row(&item),
+            )
+            .is_err()
+        {
+            return Ok(ReindexOutcome::IndexFailed);
+        }
+        let _ = indexer.commit();
+    }
+    Ok(ReindexOutcome::Indexed)
+}
+
+/// Store the text recognized in an image and re-index it. Blank text clears
+/// it. Fails for items that are not images.
+pub(crate) fn update_image_ocr_text(
+    db: &Database,
+    indexer: &Indexer,
+    item_id: i64,
+    text: String,
+) -> Result<ReindexOutcome, ClipKittyError> {
+    if !db.update_image_ocr_text(item_id, non_empty(text).as_deref())? {
+        return Err(ClipKittyError::InvalidInput(format!(
+            "item {item_id} is not an image"
+        )));
+    }
+    if let Some(item) = get_stored_item(db, item_id)? {
+        let tags = db.fetch_user_tags(item_id)?;
+        if indexer
+            .add_document_with_row(
+                &item.item_id,
+                item.text_content(),
+                item.timestamp_unix,
+                &tags,
+                &Database::indexed_row(&item),
+            )
             .is_err()
         {
             return Ok(ReindexOutcome::IndexFailed);
@@ -275,8 +549,15 @@ pub(crate) fn update_text_item(
 
     db.update_text_item(item_id, &text, &content_hash)?;
     if let Some(item) = get_stored_item(db, item_id)? {
+        let tags = db.fetch_user_tags(item_id)?;
         if indexer
-            .add_document(&item.item_id, &text, item.timestamp_unix)
+            .add_document_with_row(
+                &item.item_id,
+                &text,
+                item.timestamp_unix,
+                &tags,
+                &Database::indexed_row(&item),
+            )
             .is_err()
         {
             return Ok(ReindexOutcome::IndexFailed);
@@ -296,8 +577,15 @@ pub(crate) fn update_timestamp(
     let timestamp_unix = now.timestamp();
 
     if let Some(item) = get_stored_item(db, item_id)? {
+        let tags = db.fetch_user_tags(item_id)?;
         let index_result = indexer
-            .add_document(&item.item_id, &index_text(&item), timestamp_unix)
+            .add_document_with_row(
+                &item.item_id,
+                &index_text(&item),
+                timestamp_unix,
+                &tags,
+                &Database::indexed_row(&item),
+            )
             .and_then(|_| indexer.commit());
         if index_result.is_err() {
             return Ok(TouchOutcome::IndexFailed { timestamp_unix });
@@ -317,24 +605,81 @@ pub(crate) fn remove_tag(db: &Database, item_id: i64, tag: ItemTag) -> Result<()
     Ok(())
 }
 
-pub(crate) fn delete_item(
+/// Re-index an item with its current content and user tags.
+pub(crate) fn reindex_item(
+    db: &Database,
+    indexer: &Indexer,
+    item_id: i64,
+) -> Result<ReindexOutcome, ClipKittyError> {
+    if let Some(item) = get_stored_item(db, item_id)? {
+        let tags = db.fetch_user_tags(item_id)?;
+        let index_result = indexer
+            .add_document_with_row(
+                &item.item_id,
+                &index_text(&item),
+                item.timestamp_unix,
+                &tags,
+                &Database::indexed_row(&item),
+            )
+            .and_then(|_| indexer.commit());
+        if index_result.is_err() {
+            return Ok(ReindexOutcome::IndexFailed);
+        }
+    }
+    Ok(ReindexOutcome::Indexed)
+}
+
+/// Move an item to the trash, taking it out of the search index.
+pub(crate) fn trash_item(
     db: &Database,
     indexer: &Indexer,
     item_id: i64,
 ) -> Result<(), ClipKittyError> {
-    // Fetch the string item_id before deleting from DB (needed for index deletion).
     let string_item_id = get_stored_item(db, item_id)?.map(|item| item.item_id);
-    db.delete_item(item_id)?;
-    if let Some(sid) = string_item_id {
-        indexer.delete_document(&sid)?;
-        indexer.commit()?;
+    if db.trash_item(item_id, Utc::now())? {
+        if let Some(sid) = string_item_id {
+            indexer.delete_document(&sid)?;
+            indexer.commit()?;
+        }
     }
     Ok(())
 }
 
+/// Take an item out of the trash, under `new_item_id`, and back into the
+/// search index.
+pub(crate) fn restore_item(
+    db: &Database,
+    indexer: &Indexer,
+    item_id: i64,
+    new_item_id: &str,
+) -> Result<ReindexOutcome, ClipKittyError> {
+    db.restore_item(item_id, new_item_id)?;
+    reindex_item(db, indexer, item_id)
+}
+
 pub(crate) fn clear(db: &Database, indexer: &Indexer) -> Result<(), ClipKittyError> {
+    let kept: Vec<i64> = db
+        .fetch_immutable_ids()?
+        .into_iter()
+        .map(|(row_id, _)| row_id)
+        .collect();
     db.clear_all()?;
     indexer.clear()?;
+    // Immutable items and snippets survive the clear. Re-adding them after a
+    // full wipe, rather than deleting the other documents one by one, keeps
+    // the garbage collection that scrubs cleared text from the index files.
+    let mut tags = db.fetch_all_user_tags()?;
+    for item in db.fetch_items_by_ids(&kept)? {
+        indexer.add_document_with_row(
+            &item.item_id,
+            &index_text(&item),
+            item.timestamp_unix,
+            &tags.remove(&item.item_id).unwrap_or_default(),
+            &Database::indexed_row(&item),
+        )?;
+    }
+    snippets::index_all(db, indexer)?;
+    indexer.commit()?;
     Ok(())
 }
 
@@ -373,13 +718,29 @@ fn dedupe_or_insert_and_index(
         if let Some(id) = existing.id {
             let now = Utc::now();
             db.update_timestamp(id, now)?;
-            indexer.add_document(&existing.item_id, &index_text(&existing), now.timestamp())?;
+            db.insert_copy_event(
+                id,
+                now,
+                item.source_app.as_deref(),
+                item.source_app_bundle_id.as_deref(),
+            )?;
+            indexer.add_document_with_row(
+                &existing.item_id,
+                &index_text(&existing),
+                now.timestamp(),
+                &db.fetch_user_tags(id)?,
+                &Database::indexed_row(&existing),
+            )?;
             indexer.commit()?;
 
             return Ok(InsertOutcome::Deduplicated {
                 existing_id: id,
                 item_id: existing.item_id.clone(),
                 touched_at_unix: now.timestamp(),
+                previous_at: Utc
+                    .timestamp_opt(existing.timestamp_unix, 0)
+                    .single()
+                    .unwrap_or(now),
             });
         }
     }
@@ -387,7 +748,23 @@ fn dedupe_or_insert_and_index(
     let index_text = index_text(&item);
     let stable_item_id = item.item_id.clone();
     let id = db.insert_item(&item)?;
-    indexer.add_document(&item.item_id, &index_text, item.timestamp_unix)?;
+    let copied_at = Utc
+        .timestamp_opt(item.timestamp_unix, 0)
+        .single()
+        .unwrap_or_else(Utc::now);
+    db.insert_copy_event(
+        id,
+        copied_at,
+        item.source_app.as_deref(),
+        item.source_app_bundle_id.as_deref(),
+    )?;
+    indexer.add_document_with_row(
+        &item.item_id,
+        &index_text,
+        item.timestamp_unix,
+        &[],
+        &Database::indexed_row(&item),
+    )?;
     indexer.commit()?;
 
     Ok(InsertOutcome::Inserted {
@@ -6,19 +6,24 @@
 //! what's highlighted matches what's ranked (exact, prefix, substring, fuzzy edit-distance).
 //! Short queries (< 3 chars) use a streaming fallback.
 
-use crate::indexer::Indexer;
+use crate::candidate::ScoredWordMatches;
+use crate::indexer::{Indexer, PersonalizationResolver};
 use crate::interface::ClipKittyError;
 use crate::interface::{
-    HighlightKind, ListPresentationProfile, MatchedExcerpt, PreviewDecoration, Utf16HighlightRange,
+    HighlightKind, ListPresentationProfile, MatchedExcerpt, ParsedQuery, ParsedQueryTerm,
+    PreviewDecoration, QueryBuilder, QueryTermKind, Utf16HighlightRange,
 };
 use crate::ranking::{
-    does_word_match, does_word_match_fast, does_word_match_fast_raw, fold_str,
-    prefix_match_for_query_word, WordMatchKind, LARGE_DOC_THRESHOLD_BYTES,
+    does_word_match, does_word_match_fast, does_word_match_fast_raw, fold_into, fold_str,
+    for_each_camel_case_part, prefix_match_for_query_word, raw_char_span, TokenWordMatch,
+    WordMatchKind, LARGE_DOC_THRESHOLD_BYTES,
 };
+use crate::user_tags;
 use tokio_util::sync::CancellationToken;
 
-/// Maximum results to return from search.
-pub(crate) const MAX_RESULTS: usize = 2000;
+/// Ceiling on Phase 1 recall. The indexer picks the actual depth from corpus
+/// size and query selectivity, up to this.
+pub(crate) const MAX_RESULTS: usize = 3000;
 
 pub(crate) const MIN_TRIGRAM_QUERY_LEN: usize = 3;
 
@@ -69,6 +74,10 @@ pub(crate) struct ExcerptPolicy {
     pub(crate) leading_context_chars: usize,
     /// Maximum hard line breaks kept before the selected match; None = unbounded.
     pub(crate) max_leading_line_breaks: Option<usize>,
+    /// When every highlight ends before this char, the excerpt starts at the
+    /// document start rather than around the selected match: short content
+    /// reads best from its beginning when it shows all its matches anyway.
+    pub(crate) document_start_chars: usize,
 }
 
 impl ExcerptPolicy {
@@ -81,6 +90,7 @@ impl ExcerptPolicy {
                 leading_context_chars: SNIPPET_CONTEXT_CHARS,
                 // CollapseAll removes all newlines; a line cap is meaningless.
                 max_leading_line_breaks: None,
+                document_start_chars: SNIPPET_CONTEXT_CHARS * 2,
             },
             ListPresentationProfile::Card => Self {
                 whitespace_mode: WhitespaceMode::PreserveLineBreaks,
@@ -88,53 +98,465 @@ impl ExcerptPolicy {
                 context_chars: SNIPPET_CONTEXT_CHARS * 2, // 400
                 leading_context_chars: CARD_LEADING_CONTEXT_CHARS,
                 max_leading_line_breaks: Some(CARD_MAX_LEADING_LINE_BREAKS),
+                // Any further and the match could wrap past the visible lines.
+                document_start_chars: CARD_LEADING_CONTEXT_CHARS,
             },
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Combinations of `OR` alternatives searched at most.
+const MAX_QUERY_BRANCHES: usize = 8;
+
+/// Characters a backslash keeps literal in query text. `\OR` is also the
+/// word OR rather than the operator.
+const ESCAPABLE: &[char] = &['"', '-', '^', '#', '\\'];
+
+/// Operators split out of a query: `"quoted phrases"`, `-excluded` words or
+/// phrases, and `a OR b` alternatives.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub(crate) struct QueryOperators {
+    /// Phrases whose words must appear together, in order.
+    pub(crate) phrases: Vec<String>,
+    /// Words or phrases that rule a document out.
+    pub(crate) excluded: Vec<String>,
+    /// Recall texts to search in turn, one per combination of `OR`
+    /// alternatives. Empty when the query has no `OR`.
+    pub(crate) branches: Vec<String>,
+}
+
+/// Query text split into terms, with escapes resolved.
+struct QueryTerms {
+    terms: Vec<ParsedQueryTerm>,
+    /// Whether any backslash escape was resolved.
+    escaped: bool,
+}
+
+impl QueryTerms {
+    /// Split `text` into words, `"quoted phrases"`, and `-excluded` words or
+    /// phrases, folding each `OR` between two of the others into
+    /// `or_previous`. An unmatched quote, a `-` inside a word, and an `OR`
+    /// without a term on both sides are plain text, and a backslash keeps an
+    /// `ESCAPABLE` character that follows it literal.
+    fn parse(text: &str) -> Self {
+        struct Token {
+            kind: QueryTermKind,
+            value: String,
+            is_or: bool,
+        }
+
+        let mut tokens = Vec::new();
+        let mut escaped = false;
+        for RawTerm {
+            body: raw,
+            negated,
+            quoted,
+            ..
+        } in raw_terms(text)
+        {
+            let value = if !quoted && raw == "\\OR" {
+                "OR".to_string()
+            } else {
+                unescape(raw)
+            };
+            escaped |= value != raw;
+            let value = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+            let kind = if negated {
+                QueryTermKind::Excluded
+            } else if quoted {
+                QueryTermKind::Phrase
+            } else {
+                QueryTermKind::Word
+            };
+            tokens.push(Token {
+                kind,
+                is_or: kind == QueryTermKind::Word && raw == "OR",
+                value: value.to_string(),
+            });
+        }
+
+        let positive =
+            |token: Option<&Token>| token.is_some_and(|t| t.kind != QueryTermKind::Excluded);
+        let mut terms: Vec<ParsedQueryTerm> = Vec::with_capacity(tokens.len());
+        let mut joins_previous = false;
+        for index in 0..tokens.len() {
+            let token = &tokens[index];
+            if token.is_or
+                && index > 0
+                && positive(tokens.get(index - 1))
+                && positive(tokens.get(index + 1))
+            {
+                joins_previous = true;
+                continue;
+            }
+            terms.push(ParsedQueryTerm {
+                kind: token.kind,
+                value: token.value.clone(),
+                or_previous: joins_previous,
+            });
+            joins_previous = false;
+        }
+        Self { terms, escaped }
+    }
+}
+
+/// One term of query text as typed: a word or a `"quoted phrase"`, either
+/// of them possibly `-` negated.
+struct RawTerm<'a> {
+    /// The whole term, with its `-` and quotes.
+    typed: &'a str,
+    /// The word, or the text between the quotes, still escaped.
+    body: &'a str,
+    negated: bool,
+    quoted: bool,
+}
+
+/// Split `text` into terms. An unmatched quote and a `-` inside a word are
+/// part of a word.
+fn raw_terms(text: &str) -> Vec<RawTerm<'_>> {
+    let mut terms = Vec::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        let negated = rest.len() > 1
+            && rest.starts_with('-')
+            && rest[1..].starts_with(|c: char| c == '"' || c.is_alphanumeric());
+        let start = if negated { 1 } else { 0 };
+        let quoted = rest[start..]
+            .strip_prefix('"')
+            .and_then(|inner| closing_quote(inner).map(|end| (start + 1, start + 1 + end)));
+        let (body, end) = match quoted {
+            Some((open, close)) => (&rest[open..close], close + 1),
+            None => {
+                let end = rest[start..]
+                    .find(char::is_whitespace)
+                    .map_or(rest.len(), |end| start + end);
+                (&rest[start..end], end)
+            }
+        };
+        terms.push(RawTerm {
+            typed: &rest[..end],
+            body,
+            negated,
+            quoted: quoted.is_some(),
+        });
+        rest = rest[end..].trim_start();
+    }
+    terms
+}
+
+/// The terms of `query` as typed, each with whether it can be a filter word
+/// like `#tag` or `kind:image`: quoted, negated, and escaped terms can't.
+pub(crate) fn filter_words(query: &str) -> Vec<(&str, bool)> {
+    raw_terms(query)
+        .into_iter()
+        .map(|term| (term.typed, !term.negated && !term.quoted))
+        .collect()
+}
+
+/// Byte offset of the first `"` in `text` that no backslash escapes.
+fn closing_quote(text: &str) -> Option<usize> {
+    let mut chars = text.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Some(index),
+            '\\' if text[index + 1..].starts_with(ESCAPABLE) => {
+                chars.next();
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn unescape(raw: &str) -> String {
+    let mut value = String::with_capacity(raw.len());
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(&next) if c == '\\' && ESCAPABLE.contains(&next) => {
+                value.push(next);
+                chars.next();
+            }
+            _ => value.push(c),
+        }
+    }
+    value
+}
+
+/// Append `value` with its quotes and backslashes escaped, so `unescape`
+/// gives it back whether it ends up in a phrase or a word.
+fn push_escaped(text: &mut String, value: &str) {
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => text.push_str("\\\""),
+            // A backslash only escapes what follows it; double it where it
+            // would, and at the end, where a phrase's closing quote follows.
+            '\\' if chars.peek().is_none_or(|next| ESCAPABLE.contains(next)) => {
+                text.push_str("\\\\")
+            }
+            c => text.push(c),
+        }
+    }
+}
+
+impl QueryOperators {
+    /// Split the operators out of `text`, returning the words left to
+    /// search for (plain words, phrase words, and every `OR` alternative, in
+    /// order) alongside them. Text without operators or escapes comes back
+    /// unchanged.
+    fn parse(text: &str) -> (String, Self) {
+        let QueryTerms { terms, escaped } = QueryTerms::parse(text);
+        let mut operators = Self::default();
+        // Each group holds the alternatives for one position in the query.
+        let mut groups: Vec<Vec<&ParsedQueryTerm>> = Vec::new();
+        for term in &terms {
+            match term.kind {
+                QueryTermKind::Excluded => operators.excluded.push(term.value.clone()),
+                QueryTermKind::Word | QueryTermKind::Phrase => match groups.last_mut() {
+                    Some(group) if term.or_previous => group.push(term),
+                    _ => groups.push(vec![term]),
+                },
+            }
+        }
+        operators.phrases = groups
+            .iter()
+            .filter(|group| group.len() == 1 && group[0].kind == QueryTermKind::Phrase)
+            .map(|group| group[0].value.clone())
+            .collect();
+        if groups.iter().any(|group| group.len() > 1) {
+            let mut branches = vec![Vec::new()];
+            for group in &groups {
+                branches = branches
+                    .iter()
+                    .flat_map(|branch: &Vec<&str>| {
+                        group.iter().map(move |alternative| {
+                            let mut branch = branch.clone();
+                            branch.push(alternative.value.as_str());
+                            branch
+                        })
+                    })
+                    .take(MAX_QUERY_BRANCHES)
+                    .collect();
+            }
+            operators.branches = branches.iter().map(|branch| branch.join(" ")).collect();
+        }
+
+        if operators == Self::default() && !escaped {
+            return (text.to_string(), operators);
+        }
+        let words: Vec<&str> = groups
+            .concat()
+            .iter()
+            .map(|term| term.value.as_str())
+            .collect();
+        (words.join(" "), operators)
+    }
+}
+
+/// How the search reads `query`: its terms and operators with escapes
+/// resolved, so a search field can preview what a query will look for.
+#[uniffi::export]
+pub fn parse_query(query: String) -> ParsedQuery {
+    // Mirrors the search service, which takes filter words out before
+    // `SearchQuery::parse` reads the rest.
+    let mut tags = Vec::new();
+    let mut file_kind = None;
+    let mut remaining = Vec::new();
+    for (word, can_filter) in filter_words(&query) {
+        let tag = user_tags::canonical_name(word);
+        match crate::file_kind::kind_of_word(word) {
+            _ if can_filter && word.starts_with('#') && !tag.is_empty() => {
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+            Some(kind) if can_filter => file_kind = Some(kind),
+            _ => remaining.push(word),
+        }
+    }
+    let remaining = remaining.join(" ");
+
+    // A `^` with nothing to search after it is plain text.
+    if let Some(rest) = remaining.strip_prefix('^') {
+        let terms = QueryTerms::parse(rest.trim_start()).terms;
+        if terms
+            .iter()
+            .any(|term| term.kind != QueryTermKind::Excluded)
+        {
+            return ParsedQuery {
+                prefer_prefix: true,
+                terms,
+                tags,
+                file_kind,
+            };
+        }
+    }
+    ParsedQuery {
+        prefer_prefix: false,
+        terms: QueryTerms::parse(&remaining).terms,
+        tags,
+        file_kind,
+    }
+}
+
+/// Query text that `parse_query` reads back as `parsed`, quoting and
+/// escaping values as needed. Blank terms are dropped; a word with spaces
+/// in it becomes a phrase, and so does one that would read as a `kind:`
+/// filter. Tag and kind filters follow the terms.
+#[uniffi::export]
+pub fn format_query(parsed: ParsedQuery) -> String {
+    let mut text = String::new();
+    if parsed.prefer_prefix {
+        text.push('^');
+    }
+    let mut previous: Option<QueryTermKind> = None;
+    for term in &parsed.terms {
+        let value = term.value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        if let Some(previous) = previous {
+            let joins = term.or_previous
+                && term.kind != QueryTermKind::Excluded
+                && previous != QueryTermKind::Excluded;
+            text.push_str(if joins { " OR " } else { " " });
+        }
+        previous = Some(term.kind);
+        let is_word = !value.contains(char::is_whitespace);
+        match term.kind {
+            QueryTermKind::Word if is_word => match value {
+                "OR" => text.push_str("\\OR"),
+                "\\OR" => text.push_str("\\\\OR"),
+                _ if crate::file_kind::kind_of_word(value).is_some() => {
+                    text.push('"');
+                    push_escaped(&mut text, value);
+                    text.push('"');
+                }
+                _ => {
+                    if value.len() > 1 && value.starts_with(['-', '^', '#']) {
+                        text.push('\\');
+                    }
+                    push_escaped(&mut text, value);
+                }
+            },
+            QueryTermKind::Excluded if is_word && value.starts_with(char::is_alphanumeric) => {
+                text.push('-');
+                push_escaped(&mut text, value);
+            }
+            QueryTermKind::Excluded | QueryTermKind::Phrase | QueryTermKind::Word => {
+                if term.kind == QueryTermKind::Excluded {
+                    text.push('-');
+                }
+                text.push('"');
+                push_escaped(&mut text, value);
+                text.push('"');
+            }
+        }
+    }
+    let tags = parsed
+        .tags
+        .iter()
+        .map(|tag| user_tags::canonical_name(tag))
+        .filter(|tag| !tag.is_empty() && !tag.contains(char::is_whitespace))
+        .map(|tag| format!("#{tag}"));
+    let kind = parsed.file_kind.map(crate::file_kind::kind_word);
+    for filter in tags.chain(kind) {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(&filter);
+    }
+    text
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) enum SearchQuery {
     Plain {
         text: String,
+        operators: QueryOperators,
     },
     PreferPrefix {
         raw_text: String,
         stripped_text: String,
+        operators: QueryOperators,
     },
 }
 
 impl SearchQuery {
+    /// Parse a query as typed. A leading `^` prefers prefix matches, and
+    /// `QueryOperators::parse` takes the operators out of the rest. A query
+    /// of nothing but exclusions has no text left and browses like an
+    /// empty one.
     pub(crate) fn parse(query: &str) -> Self {
         let trimmed = query.trim();
         if let Some(rest) = trimmed.strip_prefix('^') {
-            let stripped = rest.trim_start();
+            let (stripped, operators) = QueryOperators::parse(rest.trim_start());
             if !stripped.is_empty() {
+                let raw_text = if operators == QueryOperators::default() {
+                    trimmed.to_string()
+                } else {
+                    format!("^{stripped}")
+                };
                 return Self::PreferPrefix {
-                    raw_text: trimmed.to_string(),
-                    stripped_text: stripped.to_string(),
+                    raw_text,
+                    stripped_text: stripped,
+                    operators,
                 };
             }
         }
 
+        let (text, operators) = QueryOperators::parse(trimmed);
+        Self::Plain { text, operators }
+    }
+
+    /// The query a `QueryBuilder` describes, taken field by field rather than
+    /// parsed, so its values never read as operators. Terms and phrases are
+    /// searched for as `parse` would leave them; blank values are dropped.
+    pub(crate) fn from_builder(builder: &QueryBuilder) -> Self {
+        let non_blank = |values: &[String]| -> Vec<String> {
+            values
+                .iter()
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        let phrases = non_blank(&builder.phrases);
+        let mut words = non_blank(&builder.terms);
+        words.extend(phrases.iter().cloned());
         Self::Plain {
-            text: trimmed.to_string(),
+            text: words.join(" "),
+            operators: QueryOperators {
+                phrases,
+                excluded: non_blank(&builder.excluded),
+                branches: Vec::new(),
+            },
         }
     }
 
     pub(crate) fn raw_text(&self) -> &str {
         match self {
-            Self::Plain { text } => text,
+            Self::Plain { text, .. } => text,
             Self::PreferPrefix { raw_text, .. } => raw_text,
         }
     }
 
     pub(crate) fn recall_text(&self) -> &str {
         match self {
-            Self::Plain { text } => text,
+            Self::Plain { text, .. } => text,
             Self::PreferPrefix { stripped_text, .. } => stripped_text,
         }
     }
+
+    pub(crate) fn operators(&self) -> &QueryOperators {
+        match self {
+            Self::Plain { operators, .. } | Self::PreferPrefix { operators, .. } => operators,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -153,6 +575,8 @@ pub(crate) struct HighlightRange {
 pub(crate) struct HighlightAnalysis {
     pub(crate) highlights: Vec<HighlightRange>,
     pub(crate) initial_scroll_highlight_index: Option<u64>,
+    /// Highlights that begin an occurrence of the whole query, in content order.
+    pub(crate) full_query_occurrences: Vec<usize>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -270,6 +694,7 @@ pub(crate) fn search_trigram_lazy(
     indexer: &Indexer,
     query: &SearchQuery,
     token: &CancellationToken,
+    personalize: Option<PersonalizationResolver<'_>>,
 ) -> Result<Vec<crate::candidate::SearchCandidate>, ClipKittyError> {
     if query.raw_text().is_empty() {
         return Ok(Vec::new());
@@ -278,11 +703,12 @@ pub(crate) fn search_trigram_lazy(
     // Bucket-ranked candidates from two-phase search
     #[cfg(feature = "perf-log")]
     let t0 = std::time::Instant::now();
-    let candidates = match indexer.search_parsed(query, MAX_RESULTS, token) {
-        Ok(candidates) => candidates,
-        Err(_) if token.is_cancelled() => return Err(ClipKittyError::Cancelled),
-        Err(error) => return Err(error.into()),
-    };
+    let candidates =
+        match indexer.search_parsed_personalized(query, MAX_RESULTS, token, personalize) {
+            Ok(candidates) => candidates,
+            Err(_) if token.is_cancelled() => return Err(ClipKittyError::Cancelled),
+            Err(error) => return Err(error.into()),
+        };
     #[cfg(feature = "perf-log")]
     eprintln!(
         "[perf] indexer_total={:.1}ms candidates={}",
@@ -322,17 +748,21 @@ fn token_span_bounds(
     (char_start + relative_start, char_start + relative_end)
 }
 
+/// Highlight the `doc_word` token at `char_start..char_end`. Match spans
+/// count folded chars, so they are mapped back onto the token's own chars.
 fn append_word_highlight(
     highlights: &mut Vec<(usize, usize, HighlightKind)>,
     char_start: usize,
     char_end: usize,
+    doc_word: &str,
     word_match_kind: WordMatchKind,
 ) {
     let (highlight_start, highlight_end) = match word_match_kind {
         WordMatchKind::Prefix { span }
         | WordMatchKind::SubwordPrefix { span }
         | WordMatchKind::InfixSubstring { span } => {
-            token_span_bounds(char_start, char_end, span.start, span.end())
+            let (span_start, span_end) = raw_char_span(doc_word, span.start, span.end());
+            token_span_bounds(char_start, char_end, span_start, span_end)
         }
         WordMatchKind::Exact
         | WordMatchKind::Fuzzy(_)
@@ -371,7 +801,7 @@ fn should_bridge_highlights(
 /// Context for highlighting a candidate document.
 pub(crate) struct HighlightContext<'a> {
     pub content: &'a str,
-    pub doc_words: &'a [(usize, usize, String)],
+    pub doc_words: &'a [(usize, usize, &'a str)],
     pub query_words: &'a [&'a str],
     pub last_word_is_prefix: bool,
 }
@@ -385,44 +815,141 @@ pub(crate) struct HighlightContext<'a> {
 ///
 /// For large documents (>32KB), uses fast matching (exact + prefix only)
 /// to avoid expensive fuzzy/subsequence matching.
+///
+/// A camelCase identifier is matched part by part, as ranking positions it:
+/// each part takes the first query word matching it, and a query word
+/// matching no part is tried on the whole identifier.
 pub(crate) fn highlight_candidate(ctx: &HighlightContext<'_>) -> FuzzyMatch {
     let mut word_highlights: Vec<(usize, usize, HighlightKind)> = Vec::new();
-    let mut matched_query_words = vec![false; ctx.query_words.len()];
 
     let query_folded: Vec<String> = ctx.query_words.iter().map(|w| fold_str(w)).collect();
     // Use fast matching for large documents
     let is_large_doc = ctx.content.len() > LARGE_DOC_THRESHOLD_BYTES;
+    let match_word = |qi: usize, dw_folded: &str, dw_raw: &str| {
+        let prefix_match =
+            prefix_match_for_query_word(query_folded.len(), qi, ctx.last_word_is_prefix);
+        if is_large_doc {
+            does_word_match_fast(&query_folded[qi], dw_folded, prefix_match)
+        } else {
+            does_word_match(&query_folded[qi], dw_folded, dw_raw, prefix_match)
+        }
+    };
+    // Only highlight word tokens directly. Punctuation tokens (match_weight=0)
+    // are included via the bridging pass when they fall between word highlights,
+    // preventing random punctuation elsewhere from being highlighted.
+    let mut highlight = |qi: usize, char_start: usize, char_end: usize, token: &str, wmk| {
+        if is_word_token(&query_folded[qi]) {
+            append_word_highlight(&mut word_highlights, char_start, char_end, token, wmk);
+        }
+    };
 
-    for (char_start, char_end, doc_word) in ctx.doc_words {
-        let doc_word_folded = fold_str(doc_word);
-        for (qi, qw) in query_folded.iter().enumerate() {
-            let prefix_match =
-                prefix_match_for_query_word(query_folded.len(), qi, ctx.last_word_is_prefix);
-            let wmk = if is_large_doc {
-                does_word_match_fast(qw, &doc_word_folded, prefix_match)
-            } else {
-                does_word_match(qw, &doc_word_folded, doc_word, prefix_match)
-            };
-            if wmk != WordMatchKind::None {
-                matched_query_words[qi] = true;
-                // Only highlight word tokens directly. Punctuation tokens (match_weight=0)
-                // are included via the bridging pass when they fall between word highlights,
-                // preventing random punctuation elsewhere from being highlighted.
-                if is_word_token(qw) {
-                    append_word_highlight(&mut word_highlights, *char_start, *char_end, wmk);
+    let mut doc_word_folded = String::new();
+    let mut parts: Vec<(usize, usize)> = Vec::new();
+    for &(char_start, char_end, doc_word) in ctx.doc_words {
+        doc_word_folded.clear();
+        fold_into(doc_word, &mut doc_word_folded);
+        parts.clear();
+        for_each_camel_case_part(doc_word, |start, end| parts.push((start, end)));
+
+        // Don't double-highlight from multiple query words
+        if parts.is_empty() {
+            if let Some((qi, wmk)) = (0..query_folded.len())
+                .map(|qi| (qi, match_word(qi, &doc_word_folded, doc_word)))
+                .find(|&(_, wmk)| wmk != WordMatchKind::None)
+            {
+                highlight(qi, char_start, char_end, doc_word, wmk);
+            }
+            continue;
+        }
+
+        let mut matched_a_part = vec![false; query_folded.len()];
+        let mut part_char_start = char_start;
+        let mut part_matches = Vec::with_capacity(parts.len());
+        for &(start, end) in &parts {
+            let part = &doc_word[start..end];
+            let part_folded = fold_str(part);
+            let part_char_end = part_char_start + part.chars().count();
+            let mut part_match = None;
+            for (qi, matched) in matched_a_part.iter_mut().enumerate() {
+                let wmk = match_word(qi, &part_folded, part);
+                if wmk != WordMatchKind::None {
+                    *matched = true;
+                    part_match.get_or_insert((qi, wmk));
+                }
+            }
+            part_matches.push((part_char_start, part_char_end, part, part_match));
+            part_char_start = part_char_end;
+        }
+        let whole_match = (0..query_folded.len())
+            .filter(|&qi| !matched_a_part[qi])
+            .map(|qi| (qi, match_word(qi, &doc_word_folded, doc_word)))
+            .find(|&(_, wmk)| wmk != WordMatchKind::None);
+
+        for (index, (start, end, part, part_match)) in part_matches.into_iter().enumerate() {
+            match (part_match, whole_match) {
+                (part_match, Some((qi, wmk)))
+                    if index == 0 && part_match.is_none_or(|(part_qi, _)| qi < part_qi) =>
+                {
+                    highlight(qi, char_start, char_end, doc_word, wmk);
                 }
-                break; // Don't double-highlight from multiple query words
+                (Some((qi, wmk)), _) => highlight(qi, start, end, part, wmk),
+                (None, _) => {}
             }
         }
     }
 
+    finish_word_highlights(ctx.content, word_highlights)
+}
+
+/// Highlight from the token matches Phase 2 recorded while scoring `content`
+/// for `query_words`, instead of matching every token again. Produces the
+/// same ranges as `highlight_candidate` for a small document.
+fn highlight_scored_matches(
+    content: &str,
+    scored: &[TokenWordMatch],
+    query_words: &[&str],
+) -> FuzzyMatch {
+    let mut word_highlights: Vec<(usize, usize, HighlightKind)> = Vec::new();
+    let mut byte_pos = 0;
+    let mut char_pos = 0;
+    for token_match in scored {
+        let (Some(gap), Some(token)) = (
+            content.get(byte_pos..token_match.start),
+            content.get(token_match.start..token_match.end),
+        ) else {
+            continue;
+        };
+        char_pos += gap.chars().count();
+        byte_pos = token_match.start;
+        let char_end = char_pos + token.chars().count();
+        if query_words
+            .get(token_match.query_word)
+            .is_some_and(|query_word| is_word_token(query_word))
+        {
+            append_word_highlight(
+                &mut word_highlights,
+                char_pos,
+                char_end,
+                token,
+                token_match.kind,
+            );
+        }
+    }
+
+    finish_word_highlights(content, word_highlights)
+}
+
+fn finish_word_highlights(
+    content: &str,
+    mut word_highlights: Vec<(usize, usize, HighlightKind)>,
+) -> FuzzyMatch {
     // Sort by start position
     word_highlights.sort_unstable_by_key(|&(s, _, _)| s);
 
     // Bridge gaps between adjacent highlighted ranges where intervening chars are all
     // non-whitespace punctuation or ranges are directly adjacent (e.g. "://" in URLs,
     // "." in domains, "/" in paths). Inherit the first range's kind.
-    let content_chars: Vec<char> = ctx.content.chars().collect();
+    let content_chars: Vec<char> = content.chars().collect();
     let mut bridged: Vec<(usize, usize, HighlightKind)> = Vec::with_capacity(word_highlights.len());
     for wh in &word_highlights {
         if let Some(last) = bridged.last_mut() {
@@ -550,6 +1077,92 @@ pub(crate) fn find_densest_highlight(
     Some(indexed[best_left].0)
 }
 
+/// The highlight to center on: the densest cluster, unless the query occurs
+/// whole elsewhere but not within it. An exact occurrence of everything the
+/// user typed is the match they are looking for, even when a cluster of
+/// partial matches is denser.
+pub(crate) fn find_anchor_highlight(
+    highlights: &[HighlightRange],
+    full_query_occurrences: &[usize],
+    window_size: u64,
+) -> Option<usize> {
+    let densest = find_densest_highlight(highlights, window_size)?;
+    let Some(&first_occurrence) = full_query_occurrences.first() else {
+        return Some(densest);
+    };
+    let cluster_start = highlights[densest].start;
+    let cluster_holds_occurrence = full_query_occurrences.iter().any(|&idx| {
+        let start = highlights[idx].start;
+        start >= cluster_start && start < cluster_start + window_size
+    });
+    if cluster_holds_occurrence {
+        Some(densest)
+    } else {
+        Some(first_occurrence)
+    }
+}
+
+/// Indices of the `Exact` highlights that begin an occurrence of the whole
+/// query: the same tokens, ignoring case and whitespace, with the last one
+/// ending where a content token ends.
+pub(crate) fn full_query_occurrences(
+    content: &str,
+    query: &str,
+    highlights: &[HighlightRange],
+) -> Vec<usize> {
+    let query_tokens: Vec<String> = tokenize_words(query)
+        .into_iter()
+        .map(|(_, _, token)| fold_str(token))
+        .collect();
+    if query_tokens.is_empty() {
+        return Vec::new();
+    }
+    // Room for the query with generous whitespace, plus the char after it.
+    let span_chars = query.chars().count() * 2 + 1;
+
+    let mut starts: Vec<(u64, usize)> = highlights
+        .iter()
+        .enumerate()
+        .filter(|(_, h)| h.kind == HighlightKind::Exact)
+        .map(|(idx, h)| (h.start, idx))
+        .collect();
+    starts.sort_unstable();
+
+    let mut byte_offsets = content
+        .char_indices()
+        .map(|(byte, _)| byte)
+        .enumerate()
+        .peekable();
+    let mut occurrences = Vec::new();
+    for (start, idx) in starts {
+        while byte_offsets
+            .next_if(|&(char_idx, _)| (char_idx as u64) < start)
+            .is_some()
+        {}
+        let Some(&(_, byte_start)) = byte_offsets.peek() else {
+            break;
+        };
+        let rest = &content[byte_start..];
+        let span_end = rest
+            .char_indices()
+            .nth(span_chars)
+            .map_or(rest.len(), |(byte, _)| byte);
+        let tokens = tokenize_words(&rest[..span_end]);
+        let ends_on_token_boundary = tokens.len() > query_tokens.len()
+            || (span_end == rest.len() && tokens.len() == query_tokens.len());
+        if ends_on_token_boundary
+            && tokens
+                .iter()
+                .zip(&query_tokens)
+                .all(|(&(_, _, token), query_token)| fold_str(token) == *query_token)
+        {
+            occurrences.push(idx);
+        }
+    }
+    occurrences.sort_unstable();
+    occurrences
+}
+
 fn highlight_match_score(kind: HighlightKind) -> u64 {
     match kind {
         HighlightKind::Exact => 6,
@@ -599,14 +1212,16 @@ pub(crate) fn generate_snippet(
         context_chars: SNIPPET_CONTEXT_CHARS,
         leading_context_chars: SNIPPET_CONTEXT_CHARS,
         max_leading_line_breaks: None,
+        document_start_chars: max_len,
     };
-    generate_snippet_with_policy(content, highlights, &policy)
+    generate_snippet_with_policy(content, highlights, &[], &policy)
 }
 
 /// Generate a text snippet using a presentation-profile-driven policy.
 pub(crate) fn generate_snippet_with_policy(
     content: &str,
     highlights: &[HighlightRange],
+    full_query_occurrences: &[usize],
     policy: &ExcerptPolicy,
 ) -> (String, Vec<HighlightRange>, u64) {
     let max_len = policy.max_chars;
@@ -624,7 +1239,8 @@ pub(crate) fn generate_snippet_with_policy(
     }
 
     let density_window = policy.context_chars as u64;
-    let center_idx = find_densest_highlight(highlights, density_window).unwrap_or(0);
+    let center_idx =
+        find_anchor_highlight(highlights, full_query_occurrences, density_window).unwrap_or(0);
     let center_highlight = &highlights[center_idx];
     let match_start_char = center_highlight.start as usize;
     let match_end_char = center_highlight.end as usize;
@@ -646,9 +1262,15 @@ pub(crate) fn generate_snippet_with_policy(
         (remaining_space - context_before).min(content_char_len.saturating_sub(match_end_char));
 
     let mut snippet_start_char = match_start_char - context_before;
-    let snippet_end_char = (match_end_char + context_after).min(content_char_len);
+    let mut snippet_end_char = (match_end_char + context_after).min(content_char_len);
 
-    if snippet_start_char > 0 {
+    let fits_from_start = highlights
+        .iter()
+        .all(|h| (h.end as usize) < policy.document_start_chars);
+    if fits_from_start {
+        snippet_start_char = 0;
+        snippet_end_char = max_len.min(content_char_len);
+    } else if snippet_start_char > 0 {
         let search_start_char = snippet_start_char.saturating_sub(10);
         let search_range: String = content
             .chars()
@@ -760,12 +1382,16 @@ fn clamp_leading_line_breaks(
 /// Create a matched excerpt from full-content scalar highlights, using a presentation profile.
 pub(crate) fn create_matched_excerpt(
     content: &str,
-    highlights: &[HighlightRange],
+    analysis: &HighlightAnalysis,
     profile: ListPresentationProfile,
 ) -> MatchedExcerpt {
     let policy = ExcerptPolicy::for_profile(profile);
-    let (text, adjusted_highlights, line_number) =
-        generate_snippet_with_policy(content, highlights, &policy);
+    let (text, adjusted_highlights, line_number) = generate_snippet_with_policy(
+        content,
+        &analysis.highlights,
+        &analysis.full_query_occurrences,
+        &policy,
+    );
     let highlights = scalar_highlights_to_utf16(&text, &adjusted_highlights);
 
     MatchedExcerpt {
@@ -793,6 +1419,13 @@ pub(crate) fn create_preview_decoration(
     }
 }
 
+/// Every highlight of `query` in `content`, as UTF-16 ranges.
+pub(crate) fn utf16_highlights_for_query(content: &str, query: &str) -> Vec<Utf16HighlightRange> {
+    analyze_content_for_query(content, query)
+        .map(|analysis| scalar_highlights_to_utf16(content, &analysis.highlights))
+        .unwrap_or_default()
+}
+
 pub(crate) fn create_preview_decoration_with_char_offset(
     content: &str,
     analysis: &HighlightAnalysis,
@@ -826,11 +1459,11 @@ fn short_query_highlights(content: &str, query: &str, prefer_prefix: bool) -> Ve
         return Vec::new();
     }
 
-    // fold_str is 1:1 per char, so a char index computed on the folded string
-    // is valid in the original content (byte indices are not interchangeable).
+    // Char indices on the folded string map onto the original content through
+    // `raw_char_span` (byte indices are not interchangeable).
     let content_folded = fold_str(content);
     let query_folded = fold_str(trimmed);
-    let query_char_len = trimmed.chars().count();
+    let query_char_len = query_folded.chars().count();
 
     let start = if prefer_prefix && content_folded.starts_with(&query_folded) {
         Some(0)
@@ -841,9 +1474,10 @@ fn short_query_highlights(content: &str, query: &str, prefer_prefix: bool) -> Ve
     };
 
     start
-        .map(|start| HighlightRange {
+        .map(|start| raw_char_span(content, start, start + query_char_len))
+        .map(|(start, end)| HighlightRange {
             start: start as u64,
-            end: (start + query_char_len) as u64,
+            end: end as u64,
             kind: if start == 0 && prefer_prefix {
                 HighlightKind::Prefix
             } else {
@@ -854,7 +1488,11 @@ fn short_query_highlights(content: &str, query: &str, prefer_prefix: bool) -> Ve
         .collect()
 }
 
-fn compute_scalar_highlights(content: &str, query: &str) -> Vec<HighlightRange> {
+fn compute_scalar_highlights(
+    content: &str,
+    query: &str,
+    scored: Option<&ScoredWordMatches>,
+) -> Vec<HighlightRange> {
     let trimmed = query.trim();
     if trimmed.is_empty() {
         return Vec::new();
@@ -871,11 +1509,13 @@ fn compute_scalar_highlights(content: &str, query: &str) -> Vec<HighlightRange>
         }
     }
 
-    let query_words_owned = tokenize_words(trimmed);
-    let query_words: Vec<&str> = query_words_owned
-        .iter()
-        .map(|(_, _, w)| w.as_str())
+    let query_words: Vec<&str> = tokenize_words(trimmed)
+        .into_iter()
+        .map(|(_, _, word)| word)
         .collect();
+    if let Some(scored) = scored.and_then(|scored| scored.for_query(trimmed)) {
+        return highlight_scored_matches(content, scored, &query_words).highlight_ranges;
+    }
     let last_word_is_prefix = trimmed.ends_with(|c: char| c.is_alphanumeric());
 
     let doc_words = tokenize_words(content);
@@ -892,18 +1532,34 @@ fn compute_scalar_highlights(content: &str, query: &str) -> Vec<HighlightRange>
 }
 
 pub(crate) fn analyze_content_for_query(content: &str, query: &str) -> Option<HighlightAnalysis> {
+    analyze_scored_content_for_query(content, query, None)
+}
+
+/// `analyze_content_for_query`, reusing the token matches Phase 2 recorded
+/// for this content when they were scored for the same query text.
+pub(crate) fn analyze_scored_content_for_query(
+    content: &str,
+    query: &str,
+    scored: Option<&ScoredWordMatches>,
+) -> Option<HighlightAnalysis> {
     let trimmed = query.trim();
     if trimmed.is_empty() {
         return None;
     }
 
-    let highlights = compute_scalar_highlights(content, trimmed);
-    let initial_scroll_highlight_index =
-        find_densest_highlight(&highlights, SNIPPET_CONTEXT_CHARS as u64).map(|idx| idx as u64);
+    let highlights = compute_scalar_highlights(content, trimmed, scored);
+    let full_query_occurrences = full_query_occurrences(content, trimmed, &highlights);
+    let initial_scroll_highlight_index = find_anchor_highlight(
+        &highlights,
+        &full_query_occurrences,
+        SNIPPET_CONTEXT_CHARS as u64,
+    )
+    .map(|idx| idx as u64);
 
     Some(HighlightAnalysis {
         highlights,
         initial_scroll_highlight_index,
+        full_query_occurrences,
     })
 }
 
@@ -921,12 +1577,18 @@ pub(crate) fn analyze_content_word_match(content: &str, query: &str) -> Option<H
     }
 
     let highlights = compute_word_match_highlights(content, trimmed);
-    let initial_scroll_highlight_index =
-        find_densest_highlight(&highlights, SNIPPET_CONTEXT_CHARS as u64).map(|idx| idx as u64);
+    let full_query_occurrences = full_query_occurrences(content, trimmed, &highlights);
+    let initial_scroll_highlight_index = find_anchor_highlight(
+        &highlights,
+        &full_query_occurrences,
+        SNIPPET_CONTEXT_CHARS as u64,
+    )
+    .map(|idx| idx as u64);
 
     Some(HighlightAnalysis {
         highlights,
         initial_scroll_highlight_index,
+        full_query_occurrences,
     })
 }
 
@@ -944,10 +1606,9 @@ fn compute_word_match_highlights(content: &str, query: &str) -> Vec<HighlightRan
         }
     }
 
-    let query_words_owned = tokenize_words(query);
-    let query_words: Vec<&str> = query_words_owned
-        .iter()
-        .map(|(_, _, w)| w.as_str())
+    let query_words: Vec<&str> = tokenize_words(query)
+        .into_iter()
+        .map(|(_, _, word)| word)
         .collect();
     let last_word_is_prefix = query.ends_with(|c: char| c.is_alphanumeric());
 
@@ -956,24 +1617,29 @@ fn compute_word_match_highlights(content: &str, query: &str) -> Vec<HighlightRan
     let use_full_matching = content.len() <= LARGE_DOC_THRESHOLD_BYTES;
 
     let mut highlights: Vec<(usize, usize, HighlightKind)> = Vec::new();
+    let mut doc_word_folded = String::new();
 
-    for (char_start, char_end, doc_word) in &doc_words {
+    for &(char_start, char_end, doc_word) in &doc_words {
         if !is_word_token(doc_word) {
             continue;
         }
-        let doc_word_folded = use_full_matching.then(|| fold_str(doc_word));
+        if use_full_matching {
+            doc_word_folded.clear();
+            fold_into(doc_word, &mut doc_word_folded);
+        }
         for (qi, qw_folded) in query_folded.iter().enumerate() {
-            if !is_word_token(&query_words[qi]) {
+            if !is_word_token(query_words[qi]) {
                 continue;
             }
             let prefix_match =
                 prefix_match_for_query_word(query_folded.len(), qi, last_word_is_prefix);
-            let wmk = match &doc_word_folded {
-                Some(dw_folded) => does_word_match(qw_folded, dw_folded, doc_word, prefix_match),
-                None => does_word_match_fast_raw(qw_folded, doc_word, prefix_match),
+            let wmk = if use_full_matching {
+                does_word_match(qw_folded, &doc_word_folded, doc_word, prefix_match)
+            } else {
+                does_word_match_fast_raw(qw_folded, doc_word, prefix_match)
             };
             if wmk != WordMatchKind::None {
-                append_word_highlight(&mut highlights, *char_start, *char_end, wmk);
+                append_word_highlight(&mut highlights, char_start, char_end, doc_word, wmk);
                 break;
             }
         }
@@ -1000,7 +1666,7 @@ pub(crate) fn compute_matched_excerpt(
     let trimmed = query.trim();
     if trimmed.is_empty() {
         let policy = ExcerptPolicy::for_profile(profile);
-        let (text, _, _) = generate_snippet_with_policy(content, &[], &policy);
+        let (text, _, _) = generate_snippet_with_policy(content, &[], &[], &policy);
         return MatchedExcerpt {
             text,
             highlights: Vec::new(),
@@ -1010,34 +1676,57 @@ pub(crate) fn compute_matched_excerpt(
 
     let analysis =
         analyze_content_for_query(content, trimmed).expect("non-empty query should analyze");
-    create_matched_excerpt(content, &analysis.highlights, profile)
+    create_matched_excerpt(content, &analysis, profile)
+}
+
+/// Whether `c` is a Chinese or Japanese character. Those scripts don't put
+/// spaces between words, so each such character is a word token of its own
+/// (Korean spaces its words and tokenizes like Latin text).
+pub(crate) fn is_cjk_char(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3040}'..='\u{30FF}'
+            | '\u{31F0}'..='\u{31FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FF66}'..='\u{FF9D}'
+            | '\u{20000}'..='\u{2FA1F}'
+    )
 }
 
 /// Tokenize text into tokens with char offsets.
 /// Produces both alphanumeric word tokens and non-whitespace punctuation tokens.
 /// Whitespace is skipped (acts as a separator).
 /// Punctuation tokens allow matching symbols like "://", ".", "/" in URLs/paths.
-pub(crate) fn tokenize_words(content: &str) -> Vec<(usize, usize, String)> {
-    let chars: Vec<char> = content.chars().collect();
+/// Each CJK character is its own word token (see `is_cjk_char`).
+/// Tokens borrow from `content`, so tokenizing allocates only the token list.
+pub(crate) fn tokenize_words(content: &str) -> Vec<(usize, usize, &str)> {
     let mut tokens = Vec::new();
-    let mut i = 0;
-    while i < chars.len() {
-        if chars[i].is_whitespace() {
-            i += 1;
+    let mut chars = content.char_indices().peekable();
+    let mut char_index = 0;
+    while let Some((byte_start, first)) = chars.next() {
+        let start = char_index;
+        char_index += 1;
+        if first.is_whitespace() {
             continue;
         }
-        let start = i;
-        if chars[i].is_alphanumeric() {
-            while i < chars.len() && chars[i].is_alphanumeric() {
-                i += 1;
-            }
-        } else {
-            while i < chars.len() && !chars[i].is_alphanumeric() && !chars[i].is_whitespace() {
-                i += 1;
+        let is_word = first.is_alphanumeric();
+        let mut byte_end = byte_start + first.len_utf8();
+        while let Some(&(offset, next)) = chars.peek() {
+            let continues = if is_word {
+                next.is_alphanumeric() && !is_cjk_char(first) && !is_cjk_char(next)
+            } else {
+                !next.is_alphanumeric() && !next.is_whitespace()
+            };
+            if !continues {
+                break;
             }
+            chars.next();
+            char_index += 1;
+            byte_end = offset + next.len_utf8();
         }
-        let token: String = chars[start..i].iter().collect();
-        tokens.push((start, i, token));
+        tokens.push((start, char_index, &content[byte_start..byte_end]));
     }
     tokens
 }
@@ -1156,7 +1845,7 @@ pub fn generate_preview(content: &str, max_chars: usize) -> String {
 pub fn generate_preview_for_profile(content: &str, profile: ListPresentationProfile) -> String {
     let trimmed = content.trim_start();
     let policy = ExcerptPolicy::for_profile(profile);
-    let (preview, _, _) = generate_snippet_with_policy(trimmed, &[], &policy);
+    let (preview, _, _) = generate_snippet_with_policy(trimmed, &[], &[], &policy);
     preview
 }
 
@@ -1170,6 +1859,179 @@ pub fn format_excerpt(content: &str, profile: ListPresentationProfile) -> String
 mod tests {
     use super::*;
 
+    #[test]
+    fn query_operators_are_split_from_the_search_words() {
+        let query = SearchQuery::parse(r#"docker -compose "exact phrase" -"skip this""#);
+        assert_eq!(query.raw_text(), "docker exact phrase");
+        assert_eq!(query.operators().phrases, ["exact phrase"]);
+        assert_eq!(query.operators().excluded, ["compose", "skip this"]);
+        assert!(query.operators().branches.is_empty());
+
+        let query = SearchQuery::parse("error OR warning log");
+        assert_eq!(query.recall_text(), "error warning log");
+        assert_eq!(query.operators().branches, ["error log", "warning log"]);
+
+        let query = SearchQuery::parse("^ssh OR scp");
+        assert_eq!(query.raw_text(), "^ssh scp");
+        assert_eq!(query.recall_text(), "ssh scp");
+
+        for literal in ["docker-compose up", "OR gate", "say \"hi", "a - b", "x OR"] {
+            let query = SearchQuery::parse(literal);
+            assert_eq!(query.raw_text(), literal);
+            assert_eq!(query.operators(), &QueryOperators::default());
+        }
+        assert_eq!(SearchQuery::parse("-secret").raw_text(), "");
+    }
+
+    fn assert_terms(query: &str, expected: &[(QueryTermKind, &str, bool)]) {
+        let parsed = parse_query(query.to_string());
+        let terms: Vec<(QueryTermKind, &str, bool)> = parsed
+            .terms
+            .iter()
+            .map(|term| (term.kind, term.value.as_str(), term.or_previous))
+            .collect();
+        assert_eq!(terms, expected, "{query:?}");
+    }
+
+    #[test]
+    fn escapes_keep_operator_characters_literal() {
+        use QueryTermKind::{Excluded, Phrase, Word};
+        assert_terms(
+            "app: config",
+            &[(Word, "app:", false), (Word, "config", false)],
+        );
+        assert_terms(r"\-v flag", &[(Word, "-v", false), (Word, "flag", false)]);
+        assert_terms(
+            r"cats \OR dogs",
+            &[
+                (Word, "cats", false),
+                (Word, "OR", false),
+                (Word, "dogs", false),
+            ],
+        );
+        assert_terms(
+            r#""say \"hi\"" -"a \\ b""#,
+            &[(Phrase, r#"say "hi""#, false), (Excluded, r"a \ b", false)],
+        );
+        assert_terms(r"C:\Users\me", &[(Word, r"C:\Users\me", false)]);
+        assert_terms(
+            "a OR -b OR c",
+            &[
+                (Word, "a", false),
+                (Word, "OR", false),
+                (Excluded, "b", false),
+                (Word, "OR", false),
+                (Word, "c", false),
+            ],
+        );
+        assert_terms(
+            r#"x OR "y z""#,
+            &[(Word, "x", false), (Phrase, "y z", true)],
+        );
+
+        assert!(parse_query("^ssh".into()).prefer_prefix);
+        assert!(!parse_query(r"\^ssh".into()).prefer_prefix);
+        assert!(!parse_query("^ -x".into()).prefer_prefix);
+
+        let query = SearchQuery::parse(r"\-v \^x");
+        assert_eq!(query.raw_text(), "-v ^x");
+        assert_eq!(query.operators(), &QueryOperators::default());
+        assert_eq!(SearchQuery::parse(r"C:\Users").raw_text(), r"C:\Users");
+    }
+
+    #[test]
+    fn formatted_queries_parse_back_the_same() {
+        for query in [
+            "docker compose",
+            r#"docker -compose "exact phrase" -"skip this""#,
+            "error OR warning OR \"fatal error\" log",
+            "^ssh OR scp",
+            r"\-v \^x \OR",
+            r#""say \"hi\"" -"a \\ b" -OR"#,
+            r"C:\Users\ a\\-b trailing\",
+            "x OR",
+            "app: config",
+            "^",
+            r"\#work",
+            r#""kind:pdf""#,
+            r#""kind:document" kind:pdf"#,
+            "#Work notes kind:Image #work",
+            r"^\#x -#y",
+        ] {
+            let parsed = parse_query(query.to_string());
+            let formatted = format_query(parsed.clone());
+            assert_eq!(
+                parse_query(formatted.clone()),
+                parsed,
+                "{query:?} -> {formatted:?}"
+            );
+        }
+
+        let built = ParsedQuery {
+            prefer_prefix: false,
+            terms: vec![
+                ParsedQueryTerm {
+                    kind: QueryTermKind::Word,
+                    value: "-flag".into(),
+                    or_previous: false,
+                },
+                ParsedQueryTerm {
+                    kind: QueryTermKind::Excluded,
+                    value: "\"quoted\"".into(),
+                    or_previous: false,
+                },
+                ParsedQueryTerm {
+                    kind: QueryTermKind::Word,
+                    value: "OR".into(),
+                    or_previous: false,
+                },
+            ],
+            tags: Vec::new(),
+            file_kind: None,
+        };
+        let formatted = format_query(built.clone());
+        assert_eq!(formatted, r#"\-flag -"\"quoted\"" \OR"#);
+        assert_eq!(parse_query(formatted), built);
+    }
+
+    #[test]
+    fn tag_and_kind_words_are_filters_unless_quoted_or_escaped() {
+        use crate::interface::FileKind;
+
+        let parsed = parse_query("#Work notes kind:image #work".into());
+        assert_eq!(parsed.tags, vec!["work".to_string()]);
+        assert_eq!(parsed.file_kind, Some(FileKind::Image));
+        assert_eq!(parsed.terms.len(), 1);
+        assert_eq!(format_query(parsed), "notes #work kind:image");
+
+        let escaped = parse_query(r"\#work".into());
+        assert!(escaped.tags.is_empty());
+        assert_eq!(escaped.terms[0].kind, QueryTermKind::Word);
+        assert_eq!(escaped.terms[0].value, "#work");
+        assert_eq!(format_query(escaped), r"\#work");
+
+        let quoted = parse_query(r#""kind:pdf" "kind:document""#.into());
+        assert_eq!(quoted.file_kind, None);
+        assert!(quoted
+            .terms
+            .iter()
+            .all(|term| term.kind == QueryTermKind::Phrase));
+
+        let word = ParsedQuery {
+            prefer_prefix: false,
+            terms: vec![ParsedQueryTerm {
+                kind: QueryTermKind::Word,
+                value: "kind:document".into(),
+                or_previous: false,
+            }],
+            tags: Vec::new(),
+            file_kind: None,
+        };
+        let formatted = format_query(word);
+        assert_eq!(formatted, r#""kind:document""#);
+        assert_eq!(parse_query(formatted).file_kind, None);
+    }
+
     #[test]
     fn test_indices_to_ranges() {
         let indices = vec![0, 1, 2, 5, 6, 10];
@@ -1343,19 +2205,19 @@ mod tests {
     fn test_tokenize_words() {
         // Whitespace-separated words
         let words = tokenize_words("hello world");
-        assert_eq!(words, vec![(0, 5, "hello".into()), (6, 11, "world".into())]);
+        assert_eq!(words, vec![(0, 5, "hello"), (6, 11, "world")]);
 
         // Punctuation produces separate tokens
         let words = tokenize_words("urlparser.parse(input)");
         assert_eq!(
             words,
             vec![
-                (0, 9, "urlparser".into()),
-                (9, 10, ".".into()),
-                (10, 15, "parse".into()),
-                (15, 16, "(".into()),
-                (16, 21, "input".into()),
-                (21, 22, ")".into()),
+                (0, 9, "urlparser"),
+                (9, 10, "."),
+                (10, 15, "parse"),
+                (15, 16, "("),
+                (16, 21, "input"),
+                (21, 22, ")"),
             ]
         );
 
@@ -1364,11 +2226,11 @@ mod tests {
         assert_eq!(
             words,
             vec![
-                (0, 3, "one".into()),
-                (3, 5, "--".into()),
-                (5, 8, "two".into()),
-                (8, 11, "...".into()),
-                (11, 16, "three".into()),
+                (0, 3, "one"),
+                (3, 5, "--"),
+                (5, 8, "two"),
+                (8, 11, "..."),
+                (11, 16, "three"),
             ]
         );
 
@@ -1377,11 +2239,31 @@ mod tests {
         assert_eq!(
             words,
             vec![
-                (0, 5, "https".into()),
-                (5, 8, "://".into()),
-                (8, 14, "github".into()),
-                (14, 15, ".".into()),
-                (15, 18, "com".into()),
+                (0, 5, "https"),
+                (5, 8, "://"),
+                (8, 14, "github"),
+                (14, 15, "."),
+                (15, 18, "com"),
+            ]
+        );
+
+        // Offsets count chars while the tokens slice the original bytes
+        let words = tokenize_words("café → naïve");
+        assert_eq!(words, vec![(0, 4, "café"), (5, 6, "→"), (7, 12, "naïve")]);
+
+        // CJK characters are words of their own, apart from adjoining Latin
+        let words = tokenize_words("東京タワーv2、서울");
+        assert_eq!(
+            words,
+            vec![
+                (0, 1, "東"),
+                (1, 2, "京"),
+                (2, 3, "タ"),
+                (3, 4, "ワ"),
+                (4, 5, "ー"),
+                (5, 7, "v2"),
+                (7, 8, "、"),
+                (8, 10, "서울"),
             ]
         );
     }
@@ -1461,7 +2343,7 @@ mod tests {
 
     #[test]
     fn test_short_query_match_data_prefers_prefix() {
-        let highlights = compute_scalar_highlights("Alpha beta", "al");
+        let highlights = compute_scalar_highlights("Alpha beta", "al", None);
         assert_eq!(highlights.len(), 1);
         assert_eq!(highlights[0].start, 0);
         assert_eq!(highlights[0].end, 2);
@@ -1470,7 +2352,7 @@ mod tests {
 
     #[test]
     fn test_short_query_match_data_finds_anywhere_substring() {
-        let highlights = compute_scalar_highlights("zz Alpha beta", "ph");
+        let highlights = compute_scalar_highlights("zz Alpha beta", "ph", None);
         assert_eq!(highlights.len(), 1);
         assert_eq!(highlights[0].start, 5);
         assert_eq!(highlights[0].end, 7);
@@ -1520,7 +2402,7 @@ mod tests {
     #[test]
     fn test_literal_symbol_query_highlights_complete_sequence() {
         assert_eq!(
-            compute_scalar_highlights("/unit-testing-best-practices", "/unit"),
+            compute_scalar_highlights("/unit-testing-best-practices", "/unit", None),
             vec![HighlightRange {
                 start: 0,
                 end: 5,
@@ -1550,10 +2432,9 @@ mod tests {
     fn test_highlight_url_query_tokenized_from_raw() {
         // Simulate what search_trigram does: tokenize "http://github" into query words
         let query = "http://github";
-        let query_words_owned = tokenize_words(query);
-        let query_words: Vec<&str> = query_words_owned
-            .iter()
-            .map(|(_, _, w)| w.as_str())
+        let query_words: Vec<&str> = tokenize_words(query)
+            .into_iter()
+            .map(|(_, _, word)| word)
             .collect();
         // Punctuation tokens are now real tokens in the query
         assert_eq!(query_words, vec!["http", "://", "github"]);
@@ -1610,6 +2491,81 @@ mod tests {
         assert_eq!(words, vec!["github.com"]);
     }
 
+    fn scored_matches(content: &str, query: &str) -> ScoredWordMatches {
+        use crate::ranking::{
+            compute_bucket_score_with_matches, prepare_document_for_ranking, FrecencyBand,
+            PersonalizationBand, PreparedQuery, ScoringContext,
+        };
+        let document = prepare_document_for_ranking(content);
+        let (_, tokens) = compute_bucket_score_with_matches(&ScoringContext {
+            document: &document,
+            query: &PreparedQuery::new(query),
+            prefix_preference: None,
+            timestamp: 0,
+            now: 0,
+            personalization: PersonalizationBand::None,
+            frecency: FrecencyBand::None,
+        });
+        ScoredWordMatches::new(
+            query.into(),
+            tokens.expect("small documents record matches"),
+        )
+    }
+
+    #[test]
+    fn test_scored_matches_highlight_like_rematching() {
+        let cases = [
+            ("hello world", "hello"),
+            ("the functoin returns", "function ret"),
+            ("café naïve résumé", "cafe resum"),
+            ("visit https://github.com/jul-sh", "github.com jul"),
+            ("getUserName and get_user_name", "user name"),
+            ("fix fix fix the fixture", "fix"),
+            ("🐱 kitty → clip kitty", "kitty clip"),
+            ("let t = refreshAccessToken(user);", "access token"),
+            ("refreshAccessToken or refreshToken", "refreshacc token"),
+            ("HTTPServer httpServer", "server httpserver"),
+        ];
+        for (content, query) in cases {
+            let scored = scored_matches(content, query);
+            assert_eq!(
+                compute_scalar_highlights(content, query, Some(&scored)),
+                compute_scalar_highlights(content, query, None),
+                "{query:?} in {content:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_scored_matches_for_another_query_are_ignored() {
+        let scored = scored_matches("alpha beta", "beta");
+        assert_eq!(
+            analyze_scored_content_for_query("alpha beta", "alpha", Some(&scored))
+                .map(|analysis| analysis.highlights),
+            Some(vec![hr(0, 5)]),
+        );
+    }
+
+    #[test]
+    fn test_cjk_words_highlight_inside_unspaced_text() {
+        let highlights = analyze_content_for_query("東京タワーの夜景", "夜景")
+            .map(|analysis| analysis.highlights);
+        assert_eq!(highlights, Some(vec![hr(6, 8)]));
+    }
+
+    #[test]
+    fn test_ligature_highlights_point_at_the_original_text() {
+        let highlights = |content: &str, query: &str| {
+            analyze_content_for_query(content, query).map(|analysis| analysis.highlights)
+        };
+        assert_eq!(highlights("a ﬁle here", "file"), Some(vec![hr(2, 5)]));
+        let prefix = HighlightRange {
+            kind: HighlightKind::Prefix,
+            ..hr(0, 1)
+        };
+        assert_eq!(highlights("ﬁle", "fi"), Some(vec![prefix]));
+    }
+
     // ── Densest highlight cluster tests ──────────────────────────
 
     #[test]
@@ -1782,10 +2738,88 @@ error: Build failed due to failed dependency";
         );
     }
 
+    // A copied chat message short enough to show whole: the row used to open
+    // mid-sentence with an ellipsis to center a match it had room for anyway.
+    #[test]
+    fn test_short_message_excerpt_starts_at_document_start() {
+        let content = "Hey! Quick update on the move: movers arrive Saturday at 9, \
+            keys go to the landlord on Monday, and the storage unit is paid through \
+            the end of the month. I booked the van for Sunday in case anything is \
+            left over. Can you forward me the new wifi password when you get it? \
+            Thanks so much!";
+        assert!(content.chars().count() < SNIPPET_CONTEXT_CHARS * 2);
+
+        let row = compute_matched_excerpt(content, "wifi", ListPresentationProfile::CompactRow);
+
+        assert!(
+            row.text.starts_with("Hey! Quick update"),
+            "Short message should read from its start, got: {}",
+            row.text
+        );
+        assert!(row.text.contains("wifi password"));
+        assert_eq!(row.highlights.len(), 1);
+    }
+
+    // Searching "quarterly report" in a status email: the excerpt centered on a
+    // dense run of "report"/"reporting" mentions instead of the sentence that
+    // contained the exact phrase.
+    #[test]
+    fn test_exact_full_query_occurrence_beats_denser_partial_cluster() {
+        let content = format!(
+            "Report status: report A done, report B done, reports C and D in review, \
+             reporting dashboards updated, quarter close reports pending, reporter list \
+             synced.{}Attached is the quarterly report you asked for on Friday.",
+            " Nothing else of note this week.".repeat(10)
+        );
+        let query = "quarterly report";
+
+        let analysis = analyze_content_for_query(&content, query).unwrap();
+        assert_eq!(analysis.full_query_occurrences.len(), 1);
+        let occurrence = &analysis.highlights[analysis.full_query_occurrences[0]];
+        let phrase_start = content[..content.find("quarterly report").unwrap()]
+            .chars()
+            .count() as u64;
+        assert_eq!(occurrence.start, phrase_start);
+        assert_eq!(
+            analysis.initial_scroll_highlight_index,
+            Some(analysis.full_query_occurrences[0] as u64)
+        );
+        assert_ne!(
+            find_densest_highlight(&analysis.highlights, SNIPPET_CONTEXT_CHARS as u64),
+            Some(analysis.full_query_occurrences[0]),
+            "The partial cluster should be denser, or this test proves nothing"
+        );
+
+        for profile in [
+            ListPresentationProfile::CompactRow,
+            ListPresentationProfile::Card,
+        ] {
+            let excerpt = compute_matched_excerpt(&content, query, profile);
+            assert!(
+                excerpt.text.contains("quarterly report you asked for"),
+                "{:?} excerpt should show the exact phrase, got: {}",
+                profile,
+                excerpt.text
+            );
+        }
+    }
+
+    #[test]
+    fn test_full_query_occurrences_need_every_token_whole() {
+        let content = "api keys rotate monthly; the api key for staging is in the vault";
+        let highlights = compute_scalar_highlights(content, "API key", None);
+        let occurrences = full_query_occurrences(content, "API key", &highlights);
+
+        assert_eq!(occurrences.len(), 1);
+        let start = highlights[occurrences[0]].start as usize;
+        assert_eq!(&content[start..start + 7], "api key");
+        assert!(content[start..].starts_with("api key for"));
+    }
+
     #[test]
     fn test_prefix_highlight_does_not_outrank_earlier_exact_match() {
         let content = "func top level\n\nlet x = 1;\n\nfunction later match";
-        let highlights = compute_scalar_highlights(content, "func");
+        let highlights = compute_scalar_highlights(content, "func", None);
 
         assert!(highlights.len() >= 3);
 
@@ -1818,6 +2852,7 @@ error: Build failed due to failed dependency";
             &HighlightAnalysis {
                 initial_scroll_highlight_index: Some(0),
                 highlights: highlights.clone(),
+                full_query_occurrences: Vec::new(),
             },
         );
         assert_eq!(preview.initial_scroll_highlight_index, Some(0));
@@ -1839,6 +2874,7 @@ error: Build failed due to failed dependency";
         let analysis = HighlightAnalysis {
             highlights,
             initial_scroll_highlight_index: Some(100),
+            full_query_occurrences: Vec::new(),
         };
 
         let preview = create_preview_decoration(&content, &analysis);
@@ -1862,6 +2898,7 @@ error: Build failed due to failed dependency";
         let analysis = HighlightAnalysis {
             highlights,
             initial_scroll_highlight_index: Some(75),
+            full_query_occurrences: Vec::new(),
         };
 
         let preview = create_preview_decoration_with_char_offset(&content, &analysis, 500);
@@ -1891,11 +2928,34 @@ error: Build failed due to failed dependency";
 
     #[test]
     fn test_highlight_match_kind_subword_prefix() {
-        let fm = hc(1, "responseCode", 1000, 1.0, &["code"], false);
+        let fm = hc(1, "sha256sum", 1000, 1.0, &["sum"], false);
         assert_eq!(fm.highlight_ranges.len(), 1);
         let highlight = &fm.highlight_ranges[0];
         assert_eq!(highlight.kind, HighlightKind::SubwordPrefix);
-        assert_eq!((highlight.start, highlight.end), (8, 12));
+        assert_eq!((highlight.start, highlight.end), (6, 9));
+    }
+
+    #[test]
+    fn test_highlight_camel_case_parts() {
+        let fm = hc(
+            1,
+            "refreshAccessToken",
+            1000,
+            1.0,
+            &["access", "token"],
+            false,
+        );
+        assert_eq!(fm.highlight_ranges.len(), 1);
+        let highlight = &fm.highlight_ranges[0];
+        assert_eq!(highlight.kind, HighlightKind::Exact);
+        assert_eq!((highlight.start, highlight.end), (7, 18));
+
+        let fm = hc(1, "refreshAccessToken", 1000, 1.0, &["refreshacc"], true);
+        assert_eq!(fm.highlight_ranges[0].kind, HighlightKind::Prefix);
+        assert_eq!(
+            (fm.highlight_ranges[0].start, fm.highlight_ranges[0].end),
+            (0, 10)
+        );
     }
 
     #[test]
@@ -1,9 +1,10 @@
 use crate::candidate::SearchCandidate;
 use crate::ranking::{
-    does_word_match, fold_str, prefix_match_for_query_word, PrefixMatch, WordMatchKind,
+    does_word_match, fold_into, fold_str, prefix_match_for_query_word, PrefixMatch, WordMatchKind,
     LARGE_DOC_THRESHOLD_BYTES, NON_FINAL_PREFIX_MIN_QUERY_CHARS,
 };
-use crate::search::{is_word_token, tokenize_words};
+use crate::search::{is_cjk_char, is_word_token, tokenize_words};
+use once_cell::sync::Lazy;
 
 pub(crate) const CHUNK_PARENT_THRESHOLD_BYTES: usize = 128 * 1024;
 pub(crate) const PROXIMITY_BOOST_SCALE: f32 = 1000.0;
@@ -64,7 +65,7 @@ impl PhaseOneBlendedScore {
     /// - **BM25 remainder**: whatever is left after stripping the above bands,
     ///   optionally reduced by a size penalty for large parents.
     ///
-    /// Recency is computed independently from the timestamp.
+    /// Recency is looked up from the timestamp's age in [`RECENCY_AGE_THRESHOLDS`].
     pub(crate) fn decode(raw_score: f32, timestamp: i64, parent_len: usize, now: i64) -> Self {
         let base = (raw_score as f64).max(0.001);
 
@@ -92,7 +93,7 @@ impl PhaseOneBlendedScore {
 
         let evidence_density_score =
             compute_evidence_density_score(parent_len, word_match_count, proximity_tier_score);
-        let recency_score = compute_recency(timestamp, now);
+        let recency_score = quantized_recency((now - timestamp).max(0));
 
         Self {
             literal_sequence_match,
@@ -100,7 +101,7 @@ impl PhaseOneBlendedScore {
             weak_word_match_count,
             proximity_tier: proximity_tier_score,
             evidence_density_score,
-            recency_score: (recency_score * 10.0).round() as u16,
+            recency_score,
             bm25_remainder: (adjusted_remainder * 100.0)
                 .round()
                 .clamp(0.0, u16::MAX as f64) as u16,
@@ -143,16 +144,49 @@ fn compute_evidence_density_score(
         .clamp(floor, MAX_EVIDENCE_DENSITY_SCORE as f64) as u16
 }
 
+const RECENCY_MAX_HOURS: f64 = 400.0;
+const MAX_RECENCY_SCORE: u16 = 2550;
+
 /// Logarithmic recency curve: 0–255 range, decaying over ~400 hours.
-fn compute_recency(timestamp: i64, now: i64) -> f64 {
-    let age_secs = (now - timestamp).max(0) as f64;
+fn compute_recency(age_secs: i64) -> f64 {
     let k: f64 = 20.0;
-    let max_hours: f64 = 400.0;
-    let age_hours = age_secs / 3600.0;
-    let denom = (1.0 + k * max_hours).ln();
+    let age_hours = age_secs.max(0) as f64 / 3600.0;
+    let denom = (1.0 + k * RECENCY_MAX_HOURS).ln();
     (255.0 * (1.0 - (1.0 + k * age_hours).ln() / denom)).max(0.0)
 }
 
+fn recency_score_from_curve(age_secs: i64) -> u16 {
+    (compute_recency(age_secs) * 10.0).round() as u16
+}
+
+/// `RECENCY_AGE_THRESHOLDS[v - 1]` is the oldest age, in seconds, that still
+/// scores at least `v`. The curve only falls with age, so the score of an age
+/// is the number of thresholds at or above it, found without evaluating the
+/// curve for every collected document.
+static RECENCY_AGE_THRESHOLDS: Lazy<Vec<i64>> = Lazy::new(|| {
+    let horizon = (RECENCY_MAX_HOURS * 3600.0) as i64;
+    (1..=MAX_RECENCY_SCORE)
+        .map(|score| {
+            // Largest age in [0, horizon] whose score is still >= `score`.
+            let (mut low, mut high) = (-1i64, horizon);
+            while low < high {
+                let mid = low + (high - low + 1) / 2;
+                if recency_score_from_curve(mid) >= score {
+                    low = mid;
+                } else {
+                    high = mid - 1;
+                }
+            }
+            low
+        })
+        .collect()
+});
+
+/// Recency score (0–2550, the curve scaled 10x) for a document `age_secs` old.
+fn quantized_recency(age_secs: i64) -> u16 {
+    RECENCY_AGE_THRESHOLDS.partition_point(|&threshold| threshold >= age_secs) as u16
+}
+
 fn phase_one_size_penalty(parent_len: usize) -> f64 {
     match parent_len {
         0..=CHUNK_PARENT_THRESHOLD_BYTES => 0.0,
@@ -464,16 +498,18 @@ pub(crate) fn verify_tail_word_evidence(
         return TailEvidence::BudgetExhausted;
     }
 
+    let mut dw_folded = String::new();
     for (_, _, doc_word) in tokenize_words(content) {
-        if !is_word_token(&doc_word) {
+        if !is_word_token(doc_word) {
             continue;
         }
-        let dw_folded = fold_str(&doc_word);
+        dw_folded.clear();
+        fold_into(doc_word, &mut dw_folded);
         for (index, word) in query.words.iter().enumerate() {
             if matched[index] {
                 continue;
             }
-            if does_word_match(&word.word_folded, &dw_folded, &doc_word, word.prefix_match)
+            if does_word_match(&word.word_folded, &dw_folded, doc_word, word.prefix_match)
                 != WordMatchKind::None
             {
                 matched[index] = true;
@@ -537,6 +573,14 @@ fn byte_is_alphanumeric(byte: u8) -> bool {
     byte.is_ascii_alphanumeric() || byte >= 0x80
 }
 
+/// Whether a match whose edge char is `inside` ends a token there, given
+/// the char just `outside` it (`None` at the edge of the text). CJK
+/// characters are tokens of their own, so either side being one splits.
+fn is_token_boundary(outside: Option<char>, inside: Option<char>) -> bool {
+    let extends = outside.is_some_and(|c| c.is_alphanumeric() && !is_cjk_char(c));
+    !extends || inside.is_some_and(is_cjk_char)
+}
+
 fn folded_substring_evidence(
     content_folded: &str,
     word: &TailVerifyWord,
@@ -544,14 +588,14 @@ fn folded_substring_evidence(
 ) -> bool {
     for (start, _) in content_folded.match_indices(word.word_folded.as_str()) {
         let end = start + word.word_folded.len();
-        let at_token_start = content_folded[..start]
-            .chars()
-            .next_back()
-            .map_or(true, |c| !c.is_alphanumeric());
-        let at_token_end = content_folded[end..]
-            .chars()
-            .next()
-            .map_or(true, |c| !c.is_alphanumeric());
+        let at_token_start = is_token_boundary(
+            content_folded[..start].chars().next_back(),
+            word.word_folded.chars().next(),
+        );
+        let at_token_end = is_token_boundary(
+            content_folded[end..].chars().next(),
+            word.word_folded.chars().next_back(),
+        );
         if hit_accepted(word, at_token_start, at_token_end, token_start_required) {
             return true;
         }
@@ -601,6 +645,24 @@ mod tests {
         assert!(three_words.evidence_density_score < MAX_EVIDENCE_DENSITY_SCORE);
     }
 
+    #[test]
+    fn quantized_recency_matches_the_curve() {
+        let horizon = (RECENCY_MAX_HOURS * 3600.0) as i64;
+        let ages = (0..=horizon + 7_200)
+            .step_by(97)
+            .chain(RECENCY_AGE_THRESHOLDS.iter().flat_map(|&t| [t, t + 1]))
+            .chain([-60, 0, 1, 59, 3_600, i64::MAX / 2]);
+        for age in ages {
+            assert_eq!(
+                quantized_recency(age),
+                recency_score_from_curve(age),
+                "age {age}s"
+            );
+        }
+        assert_eq!(quantized_recency(0), MAX_RECENCY_SCORE);
+        assert_eq!(quantized_recency(horizon + 1), 0);
+    }
+
     #[test]
     fn evidence_density_sorts_before_recency_for_weak_large_matches() {
         let old_compact =
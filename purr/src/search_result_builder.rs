@@ -1,7 +1,10 @@
-use crate::database::{Database, RowMetadata, SearchRowMetadata};
+use crate::app_metadata;
+use crate::context_service::{self, PersonalizationPriors};
+use crate::database::{Database, SearchRowMetadata, TagRestriction};
+use crate::indexer::PersonalizationResolver;
 use crate::interface::{
-    ClipKittyError, ContentTypeFilter, ItemMatch, ItemQueryFilter, ItemTag,
-    ListPresentationProfile, MatchedExcerptRequest, RowPresentation, SearchResult,
+    ClipKittyError, ContentTypeFilter, ItemMatch, ItemQueryFilter, ListPresentationProfile,
+    MatchedExcerptRequest, RowPresentation, SearchResult, SearchSort,
 };
 use crate::match_presentation::{HighlightAnalysisCache, MatchPresentation};
 use crate::models::StoredItem;
@@ -30,6 +33,9 @@ pub(crate) struct SearchResultAssembler<'a> {
     token: &'a CancellationToken,
     runtime: &'a tokio::runtime::Handle,
     presentation: ListPresentationProfile,
+    personalization: Option<&'a PersonalizationPriors>,
+    index_only: bool,
+    sort: SearchSort,
 }
 
 impl<'a> SearchResultAssembler<'a> {
@@ -46,30 +52,61 @@ impl<'a> SearchResultAssembler<'a> {
             token,
             runtime,
             presentation,
+            personalization: None,
+            index_only: false,
+            sort: SearchSort::Relevance,
         }
     }
 
+    /// Order trigram result pages by `sort` instead of by rank.
+    pub(crate) fn with_sort(mut self, sort: SearchSort) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Decorate trigram results from rows stored in the index, reading
+    /// SQLite only for items indexed without one.
+    pub(crate) fn with_index_only(mut self, index_only: bool) -> Self {
+        self.index_only = index_only;
+        self
+    }
+
+    /// Rank trigram results with the paste target's learned priors.
+    pub(crate) fn with_personalization(
+        mut self,
+        personalization: Option<&'a PersonalizationPriors>,
+    ) -> Self {
+        self.personalization = personalization;
+        self
+    }
+
     pub(crate) fn build_empty_query_result(
         &self,
-        filter: ItemQueryFilter,
+        filter: Option<&ContentTypeFilter>,
+        tags: &TagRestriction,
     ) -> Result<SearchResult, ClipKittyError> {
-        let (content_type_filter, tag_filter) = split_filter(filter);
-        let (mut items, total_count) = self.db.fetch_browse_row_metadata(
+        let _span = tracing::debug_span!("search.empty_query").entered();
+        let (matches, total_count) = self.empty_query_page(filter, tags, 0, 1000)?;
+        self.build_search_page("", matches, total_count, true)
+    }
+
+    /// One page of the recent items an empty query lists, pinned first, and
+    /// the number of items across all pages.
+    pub(crate) fn empty_query_page(
+        &self,
+        filter: Option<&ContentTypeFilter>,
+        tags: &TagRestriction,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<ItemMatch>, u64), ClipKittyError> {
+        let (items, total_count) = self.db.fetch_browse_row_metadata(
             None,
-            1000,
-            content_type_filter.as_ref(),
-            tag_filter.as_ref(),
+            offset,
+            limit,
+            filter,
+            tags,
             self.presentation,
         )?;
-        self.hydrate_item_metadata_tags(&mut items)?;
-        let first_preview_payload = self.presentation().load_first_preview_payload(
-            items
-                .first()
-                .map(|item| item.item_metadata.item_id.as_str()),
-            "",
-            self.token,
-            self.runtime,
-        )?;
         let matches = items
             .into_iter()
             .map(|item| ItemMatch {
@@ -79,29 +116,40 @@ impl<'a> SearchResultAssembler<'a> {
                 },
             })
             .collect();
-
-        Ok(SearchResult {
-            matches,
-            total_count,
-            first_preview_payload,
-        })
+        Ok((matches, total_count))
     }
 
     pub(crate) fn build_search_result(
         &self,
         query: &str,
-        mut matches: Vec<ItemMatch>,
+        matches: Vec<ItemMatch>,
     ) -> Result<SearchResult, ClipKittyError> {
         let total_count = matches.len() as u64;
-        self.hydrate_item_match_tags(&mut matches)?;
-        let first_preview_payload = self.presentation().load_first_preview_payload(
-            matches
-                .first()
-                .map(|item| item.item_metadata.item_id.as_str()),
-            query,
-            self.token,
-            self.runtime,
-        )?;
+        self.build_search_page(query, matches, total_count, true)
+    }
+
+    /// Hydrate one page of matches. Only the first page needs the preview
+    /// payload for the initial selection.
+    pub(crate) fn build_search_page(
+        &self,
+        query: &str,
+        mut matches: Vec<ItemMatch>,
+        total_count: u64,
+        with_preview: bool,
+    ) -> Result<SearchResult, ClipKittyError> {
+        let _span = tracing::debug_span!("search.assemble", matches = matches.len()).entered();
+        if !self.index_only {
+            self.hydrate_item_match_tags(&mut matches)?;
+        }
+        let first_preview_payload = match matches.first() {
+            Some(first) if with_preview => self.presentation().load_first_preview_payload(
+                Some(first.item_metadata.item_id.as_str()),
+                query,
+                self.token,
+                self.runtime,
+            )?,
+            _ => None,
+        };
 
         Ok(SearchResult {
             matches,
@@ -115,8 +163,9 @@ impl<'a> SearchResultAssembler<'a> {
         query: &str,
         mode: ShortQueryMode,
         filter: Option<&ContentTypeFilter>,
-        tag: Option<ItemTag>,
+        tags: &TagRestriction,
     ) -> Result<Vec<ItemMatch>, ClipKittyError> {
+        let _span = tracing::debug_span!("search.short_query").entered();
         if self.token.is_cancelled() {
             return Err(ClipKittyError::Cancelled);
         }
@@ -136,7 +185,7 @@ impl<'a> SearchResultAssembler<'a> {
         // separately (filtering-intuition-review Finding 1).
         let prefix_candidates =
             self.db
-                .search_prefix_query(trimmed, SHORT_QUERY_MAX_RESULTS, filter, tag.as_ref())?;
+                .search_prefix_query(trimmed, SHORT_QUERY_MAX_RESULTS, filter, tags)?;
 
         for (id, _, _) in prefix_candidates {
             if prefix_ids.insert(id) {
@@ -153,7 +202,7 @@ impl<'a> SearchResultAssembler<'a> {
             let recent_candidates = self.db.fetch_recent_items_for_short_query(
                 SHORT_QUERY_RECENT_WINDOW,
                 filter,
-                tag.as_ref(),
+                tags,
             )?;
             for (id, content, _) in recent_candidates {
                 if prefix_ids.contains(&id) {
@@ -178,38 +227,107 @@ impl<'a> SearchResultAssembler<'a> {
         indexer: &crate::indexer::Indexer,
         query: &search::SearchQuery,
         filter: Option<&ContentTypeFilter>,
-        tag: Option<ItemTag>,
+        tags: &TagRestriction,
     ) -> Result<Vec<ItemMatch>, ClipKittyError> {
+        let candidates = self.recall_trigram(indexer, query)?;
+        self.decorate_trigram_candidates(indexer, query, &candidates, filter, tags)
+    }
+
+    /// One page of trigram results, starting `offset` ranked candidates in.
+    /// Only candidates up to the end of the page are decorated, so SQLite
+    /// reads stay proportional to the page. Returns the page, the offset of
+    /// the next page if there is one, and the number of ranked candidates.
+    pub(crate) fn search_trigram_page(
+        &self,
+        indexer: &crate::indexer::Indexer,
+        query: &search::SearchQuery,
+        filter: Option<&ContentTypeFilter>,
+        tags: &TagRestriction,
+        offset: usize,
+        page_size: usize,
+    ) -> Result<(Vec<ItemMatch>, Option<usize>, usize), ClipKittyError> {
+        let mut candidates = self.recall_trigram(indexer, query)?;
+        if self.sort == SearchSort::Newest {
+            candidates.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then_with(|| a.id.cmp(&b.id)));
+        }
+        let total = candidates.len();
+        let mut matches = Vec::with_capacity(page_size);
+        let mut position = offset.min(total);
+        // Filters drop candidates only after decoration, so keep decorating
+        // page-sized windows until the page is full.
+        while matches.len() < page_size && position < total {
+            let window = &candidates[position..(position + page_size).min(total)];
+            let decorated =
+                self.decorate_trigram_candidates(indexer, query, window, filter, tags)?;
+            let wanted = page_size - matches.len();
+            if decorated.len() > wanted {
+                let last = &decorated[wanted - 1].item_metadata.item_id;
+                position += window
+                    .iter()
+                    .position(|candidate| &candidate.id == last)
+                    .map_or(window.len(), |index| index + 1);
+                matches.extend(decorated.into_iter().take(wanted));
+                break;
+            }
+            matches.extend(decorated);
+            position += window.len();
+        }
+        let next_offset = (position < total).then_some(position);
+        Ok((matches, next_offset, total))
+    }
+
+    /// Ranked trigram candidates, personalized for the paste target if set.
+    fn recall_trigram(
+        &self,
+        indexer: &crate::indexer::Indexer,
+        query: &search::SearchQuery,
+    ) -> Result<Vec<crate::candidate::SearchCandidate>, ClipKittyError> {
         if self.token.is_cancelled() {
             return Err(ClipKittyError::Cancelled);
         }
 
-        let candidates = search::search_trigram_lazy(indexer, query, self.token)?;
+        let db = self.db;
+        let priors = self.personalization;
+        let now = chrono::Utc::now().timestamp();
+        let resolve =
+            move |ids: &[&str]| context_service::resolve_personalization(db, priors, ids, now);
+        search::search_trigram_lazy(
+            indexer,
+            query,
+            self.token,
+            Some(&resolve as PersonalizationResolver<'_>),
+        )
+    }
+
+    fn decorate_trigram_candidates(
+        &self,
+        indexer: &crate::indexer::Indexer,
+        query: &search::SearchQuery,
+        candidates: &[crate::candidate::SearchCandidate],
+        filter: Option<&ContentTypeFilter>,
+        tags: &TagRestriction,
+    ) -> Result<Vec<ItemMatch>, ClipKittyError> {
         if candidates.is_empty() {
             return Ok(Vec::new());
         }
+        let _span =
+            tracing::debug_span!("search.decorate", candidates = candidates.len()).entered();
 
         let ids: Vec<&str> = candidates
             .iter()
             .map(|candidate| candidate.id.as_str())
             .collect();
-        let metadata_rows = self
-            .db
-            .fetch_search_row_metadata_by_string_ids(&ids, self.presentation)?;
+        let metadata_rows = if self.index_only {
+            self.index_row_metadata(indexer, candidates)?
+        } else {
+            self.db
+                .fetch_search_row_metadata_by_string_ids(&ids, self.presentation)?
+        };
         if self.token.is_cancelled() {
             return Err(ClipKittyError::Cancelled);
         }
 
-        let tagged_ids = if let Some(tag) = tag {
-            Some(
-                self.db
-                    .filter_string_ids_by_tag(&ids, tag)?
-                    .into_iter()
-                    .collect::<HashSet<_>>(),
-            )
-        } else {
-            None
-        };
+        let tagged_ids = self.tagged_candidate_ids(indexer, &ids, tags)?;
 
         let metadata_map: HashMap<String, SearchRowMetadata> = metadata_rows
             .into_iter()
@@ -220,6 +338,17 @@ impl<'a> SearchResultAssembler<'a> {
                 None => true,
             })
             .filter(|metadata| metadata_matches_filter(metadata, filter))
+            .filter(|metadata| match &tags.source_app_bundle_id {
+                Some(bundle_id) => {
+                    metadata
+                        .row_metadata
+                        .item_metadata
+                        .source_app_bundle_id
+                        .as_ref()
+                        == Some(bundle_id)
+                }
+                None => true,
+            })
             .map(|metadata| {
                 (
                     metadata.row_metadata.item_metadata.item_id.clone(),
@@ -233,6 +362,12 @@ impl<'a> SearchResultAssembler<'a> {
         crate::match_presentation::test_support::before_eager_matches();
         let mut results = Vec::with_capacity(metadata_map.len());
         let mut eager_index = 0usize;
+        // Decorating short rows ahead of visibility is speculative work.
+        let eager_short_window = if crate::power::is_low_power() {
+            0
+        } else {
+            EAGER_SHORT_MATCH_WINDOW
+        };
         for candidate in candidates {
             if self.token.is_cancelled() {
                 return Err(ClipKittyError::Cancelled);
@@ -246,11 +381,12 @@ impl<'a> SearchResultAssembler<'a> {
                 metadata.content_hash.clone(),
                 candidate.match_context(),
                 candidate.scoring_phase(),
+                candidate.scored_matches().cloned(),
             );
 
             let is_short = candidate.content().len() <= SHORT_CONTENT_THRESHOLD;
             let item_match = if eager_index < EAGER_MATCH_DATA_COUNT
-                || (is_short && eager_index < EAGER_SHORT_MATCH_WINDOW)
+                || (is_short && eager_index < eager_short_window)
             {
                 #[cfg(test)]
                 crate::match_presentation::test_support::on_eager_match(eager_index);
@@ -299,6 +435,80 @@ impl<'a> SearchResultAssembler<'a> {
         Ok(results)
     }
 
+    /// The candidates satisfying `tags`, or None when nothing is restricted.
+    /// User tags are resolved through the index, system tags through SQL.
+    fn tagged_candidate_ids(
+        &self,
+        indexer: &crate::indexer::Indexer,
+        ids: &[&str],
+        tags: &TagRestriction,
+    ) -> Result<Option<HashSet<String>>, ClipKittyError> {
+        if tags.is_empty() {
+            return Ok(None);
+        }
+        let mut allowed: HashSet<String> = match tags.system {
+            Some(tag) => self
+                .db
+                .filter_string_ids_by_tag(ids, tag)?
+                .into_iter()
+                .collect(),
+            None => ids.iter().map(|id| id.to_string()).collect(),
+        };
+        for filter in &tags.user {
+            let tagged = indexer.item_ids_with_tags(filter)?;
+            allowed.retain(|id| tagged.contains(id));
+        }
+        if let Some(locale) = &tags.locale {
+            let suited: HashSet<String> = self
+                .db
+                .filter_string_ids_by_locale(ids, locale)?
+                .into_iter()
+                .collect();
+            allowed.retain(|id| suited.contains(id));
+        }
+        if let Some(kind) = tags.file_kind {
+            let holding: HashSet<String> = self
+                .db
+                .filter_string_ids_by_file_kind(ids, kind)?
+                .into_iter()
+                .collect();
+            allowed.retain(|id| holding.contains(id));
+        }
+        Ok(Some(allowed))
+    }
+
+    /// Search metadata from rows stored in the index, falling back to SQLite
+    /// for candidates indexed without one.
+    fn index_row_metadata(
+        &self,
+        indexer: &crate::indexer::Indexer,
+        candidates: &[crate::candidate::SearchCandidate],
+    ) -> Result<Vec<SearchRowMetadata>, ClipKittyError> {
+        let ids: Vec<&str> = candidates
+            .iter()
+            .map(|candidate| candidate.id.as_str())
+            .collect();
+        let mut rows = indexer.fetch_indexed_rows(&ids)?;
+        let mut metadata = Vec::with_capacity(candidates.len());
+        let mut missing = Vec::new();
+        for candidate in candidates {
+            match rows.remove(&candidate.id) {
+                Some(row) => metadata.push(Database::search_row_metadata_from_index(
+                    &candidate.id,
+                    candidate.timestamp,
+                    row,
+                    self.presentation,
+                )),
+                None => missing.push(candidate.id.as_str()),
+            }
+        }
+        metadata.extend(
+            self.db
+                .fetch_search_row_metadata_by_string_ids(&missing, self.presentation)?,
+        );
+        Ok(metadata)
+    }
+
     fn assemble_short_query_matches(
         &self,
         ordered_ids: &[i64],
@@ -345,28 +555,15 @@ impl<'a> SearchResultAssembler<'a> {
             .map(|item| item.item_metadata.item_id.clone())
             .collect();
         let tags_by_id = self.db.get_tags_for_item_ids(&ids)?;
-        for item in matches {
-            item.item_metadata.tags = tags_by_id
-                .get(&item.item_metadata.item_id)
-                .cloned()
-                .unwrap_or_default();
-        }
-        Ok(())
-    }
-
-    fn hydrate_item_metadata_tags(&self, items: &mut [RowMetadata]) -> Result<(), ClipKittyError> {
-        let ids: Vec<String> = items
-            .iter()
-            .map(|item| item.item_metadata.item_id.clone())
-            .collect();
-        let tags_by_id = self.db.get_tags_for_item_ids(&ids)?;
-        for item in items {
+        let pinned = self.db.get_pinned_item_ids(&ids)?;
+        for item in matches.iter_mut() {
             item.item_metadata.tags = tags_by_id
                 .get(&item.item_metadata.item_id)
                 .cloned()
                 .unwrap_or_default();
+            item.item_metadata.is_pinned = pinned.contains(&item.item_metadata.item_id);
         }
-        Ok(())
+        app_metadata::hydrate_source_apps(self.db, matches.iter_mut().map(|m| &mut m.item_metadata))
     }
 
     fn presentation(&self) -> MatchPresentation<'_> {
@@ -389,11 +586,35 @@ pub(crate) fn uses_short_query_path(parsed_query: &search::SearchQuery) -> bool
 }
 
 pub(crate) fn split_filter(
-    filter: ItemQueryFilter,
-) -> (Option<ContentTypeFilter>, Option<ItemTag>) {
+    filter: &ItemQueryFilter,
+) -> (Option<ContentTypeFilter>, TagRestriction) {
     match filter {
-        ItemQueryFilter::All => (None, None),
-        ItemQueryFilter::ContentType { content_type } => (Some(content_type), None),
-        ItemQueryFilter::Tagged { tag } => (None, Some(tag)),
+        ItemQueryFilter::All => (None, TagRestriction::default()),
+        ItemQueryFilter::ContentType { content_type } => {
+            (Some(*content_type), TagRestriction::default())
+        }
+        ItemQueryFilter::Tagged { tag } => (
+            None,
+            TagRestriction {
+                system: Some(*tag),
+                ..TagRestriction::default()
+            },
+        ),
+        ItemQueryFilter::UserTagged { filter } => (
+            None,
+            TagRestriction {
+                user: crate::user_tags::canonical_filter(filter)
+                    .into_iter()
+                    .collect(),
+                ..TagRestriction::default()
+            },
+        ),
+        ItemQueryFilter::SourceApp { bundle_id } => (
+            None,
+            TagRestriction {
+                source_app_bundle_id: Some(bundle_id.clone()),
+                ..TagRestriction::default()
+            },
+        ),
     }
 }
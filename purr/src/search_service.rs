@@ -1,17 +1,23 @@
+use crate::context_service::PersonalizationPriors;
 use crate::database::Database;
 use crate::indexer::Indexer;
 use crate::interface::{
-    ClipKittyError, ItemMatch, ItemQueryFilter, ListPresentationProfile, MatchedExcerptRequest,
-    MatchedExcerptResolution, PreviewPayload, SearchResult,
+    ClipKittyError, ClipboardContent, FileMatch, ItemMatch, ItemQueryFilter,
+    ListPresentationProfile, MatchedExcerptPatch, MatchedExcerptRequest, MatchedExcerptResolution,
+    PreviewPayload, QueryBuilder, SearchPage, SearchResult, SearchSort,
 };
 use crate::match_presentation::{HighlightAnalysisCache, MatchPresentation};
+use crate::panic_guard;
 use crate::search;
 use crate::search_result_builder::{uses_short_query_path, SearchResultAssembler, ShortQueryMode};
 use std::sync::Arc;
 use tokio_util::sync::CancellationToken;
 
-#[cfg(test)]
-use crate::interface::{ContentTypeFilter, ItemTag};
+use crate::database::TagRestriction;
+use crate::file_kind;
+use crate::interface::ContentTypeFilter;
+use crate::item_locale;
+use crate::user_tags;
 
 #[cfg(test)]
 #[allow(unused_imports)]
@@ -26,6 +32,50 @@ pub(crate) struct SearchContext {
     pub(crate) runtime: tokio::runtime::Handle,
     pub(crate) token: CancellationToken,
     pub(crate) presentation: ListPresentationProfile,
+    /// App the result will be pasted into, for personalized ranking.
+    pub(crate) paste_target: Option<String>,
+    /// Render trigram results from stored index rows; see
+    /// `ClipboardStore::set_index_only_search`.
+    pub(crate) index_only: bool,
+}
+
+/// Largest page `execute_search_page` will return.
+const MAX_SEARCH_PAGE: u64 = 500;
+
+/// Position after the last match of a page: `"{offset}:{fingerprint}"`. The
+/// fingerprint ties the cursor to the query and filter that produced it.
+struct SearchPageCursor {
+    offset: usize,
+    fingerprint: u64,
+}
+
+impl SearchPageCursor {
+    fn fingerprint(query: &(impl std::hash::Hash + ?Sized), filter: &ItemQueryFilter) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        query.hash(&mut hasher);
+        filter.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn encode(&self) -> String {
+        format!("{}:{:x}", self.offset, self.fingerprint)
+    }
+
+    fn decode(cursor: &str, fingerprint: u64) -> Result<Self, ClipKittyError> {
+        let invalid = || ClipKittyError::InvalidInput(format!("invalid search cursor `{cursor}`"));
+        let (offset, cursor_fingerprint) = cursor.split_once(':').ok_or_else(invalid)?;
+        let decoded = Self {
+            offset: offset.parse().map_err(|_| invalid())?,
+            fingerprint: u64::from_str_radix(cursor_fingerprint, 16).map_err(|_| invalid())?,
+        };
+        if decoded.fingerprint != fingerprint {
+            return Err(ClipKittyError::InvalidInput(
+                "search cursor belongs to a different query or filter".into(),
+            ));
+        }
+        Ok(decoded)
+    }
 }
 
 pub(crate) async fn execute_search(
@@ -33,6 +83,15 @@ pub(crate) async fn execute_search(
     query: String,
     filter: ItemQueryFilter,
 ) -> Result<SearchResult, ClipKittyError> {
+    let span = tracing::info_span!("search", query_chars = query.chars().count(), filter = ?filter);
+    let (content_type_filter, mut tags) = crate::search_result_builder::split_filter(&filter);
+    let query = span.in_scope(|| {
+        panic_guard::contain(&context.db, "search", || {
+            tags.locale = item_locale::preferred(&context.db)?;
+            let query = user_tags::extract_hashtags(&context.db, &query, &mut tags)?;
+            Ok(file_kind::extract_kind_filter(&query, &mut tags))
+        })
+    })?;
     let parsed_query = search::SearchQuery::parse(&query);
     if context.token.is_cancelled() {
         return Err(ClipKittyError::Cancelled);
@@ -41,14 +100,18 @@ pub(crate) async fn execute_search(
     let presentation = context.presentation;
 
     if parsed_query.raw_text().is_empty() {
-        return SearchResultAssembler::new(
-            &context.db,
-            &context.cache,
-            &context.token,
-            &context.runtime,
-            presentation,
-        )
-        .build_empty_query_result(filter);
+        return span.in_scope(|| {
+            panic_guard::contain(&context.db, "search", || {
+                SearchResultAssembler::new(
+                    &context.db,
+                    &context.cache,
+                    &context.token,
+                    &context.runtime,
+                    presentation,
+                )
+                .build_empty_query_result(content_type_filter.as_ref(), &tags)
+            })
+        });
     }
 
     let SearchContext {
@@ -58,26 +121,34 @@ pub(crate) async fn execute_search(
         runtime,
         token,
         presentation,
+        paste_target,
+        index_only,
     } = context;
     let parsed_query_owned = parsed_query.clone();
-    let filter_copy = filter;
     let runtime_for_closure = runtime.clone();
     let db_for_closure = Arc::clone(&db);
     let indexer_for_closure = Arc::clone(&indexer);
     let cache_for_closure = Arc::clone(&cache);
     let token_for_closure = token.clone();
+    let span_for_closure = span.clone();
 
     let handle = runtime.spawn_blocking(move || {
-        execute_search_sync(
-            &db_for_closure,
-            &indexer_for_closure,
-            &cache_for_closure,
-            &parsed_query_owned,
-            filter_copy,
-            &token_for_closure,
-            &runtime_for_closure,
-            presentation,
-        )
+        let _entered = span_for_closure.entered();
+        panic_guard::contain(&db_for_closure, "search", || {
+            execute_search_sync(
+                &db_for_closure,
+                &indexer_for_closure,
+                &cache_for_closure,
+                &parsed_query_owned,
+                content_type_filter.as_ref(),
+                &tags,
+                &token_for_closure,
+                &runtime_for_closure,
+                presentation,
+                paste_target.as_deref(),
+                index_only,
+            )
+        })
     });
 
     let matches = match handle.await {
@@ -86,8 +157,194 @@ pub(crate) async fn execute_search(
         Err(_join_error) => return Err(ClipKittyError::Cancelled),
     };
 
-    SearchResultAssembler::new(&db, &cache, &token, &runtime, presentation)
-        .build_search_result(parsed_query.raw_text(), matches)
+    span.in_scope(|| {
+        panic_guard::contain(&db, "search", || {
+            SearchResultAssembler::new(&db, &cache, &token, &runtime, presentation)
+                .with_index_only(index_only)
+                .build_search_result(parsed_query.raw_text(), matches)
+        })
+    })
+}
+
+/// What a page searches for.
+enum PageQuery {
+    /// Query text as typed, with its `#tags` and operators.
+    Text(String),
+    /// A `QueryBuilder`'s fields, which need no parsing.
+    Built(search::SearchQuery),
+}
+
+/// Run one page of a search. Every page re-ranks the query, which is cheap
+/// next to decoration, and decorates only the candidates the page needs.
+pub(crate) async fn execute_search_page(
+    context: SearchContext,
+    query: String,
+    filter: ItemQueryFilter,
+    cursor: Option<String>,
+    page_size: u64,
+) -> Result<SearchPage, ClipKittyError> {
+    let fingerprint = SearchPageCursor::fingerprint(&query, &filter);
+    let page = PageRequest {
+        filter,
+        cursor,
+        page_size,
+        sort: SearchSort::Relevance,
+        fingerprint,
+    };
+    execute_page(context, PageQuery::Text(query), page).await
+}
+
+/// `execute_search_page` for a `QueryBuilder`.
+pub(crate) async fn execute_structured_search_page(
+    context: SearchContext,
+    query: QueryBuilder,
+) -> Result<SearchPage, ClipKittyError> {
+    let parsed_query = search::SearchQuery::from_builder(&query);
+    let fingerprint = SearchPageCursor::fingerprint(&(&parsed_query, query.sort), &query.filter);
+    let page = PageRequest {
+        filter: query.filter,
+        cursor: query.cursor,
+        page_size: query.page_size,
+        sort: query.sort,
+        fingerprint,
+    };
+    execute_page(context, PageQuery::Built(parsed_query), page).await
+}
+
+struct PageRequest {
+    filter: ItemQueryFilter,
+    cursor: Option<String>,
+    page_size: u64,
+    sort: SearchSort,
+    fingerprint: u64,
+}
+
+async fn execute_page(
+    context: SearchContext,
+    query: PageQuery,
+    page: PageRequest,
+) -> Result<SearchPage, ClipKittyError> {
+    let PageRequest {
+        filter,
+        cursor,
+        page_size,
+        sort,
+        fingerprint,
+    } = page;
+    let offset = match cursor {
+        Some(cursor) => SearchPageCursor::decode(&cursor, fingerprint)?.offset,
+        None => 0,
+    };
+    let page_size = page_size.clamp(1, MAX_SEARCH_PAGE) as usize;
+    let query_chars = match &query {
+        PageQuery::Text(text) => text.chars().count(),
+        PageQuery::Built(parsed) => parsed.raw_text().chars().count(),
+    };
+    let span = tracing::info_span!(
+        "search_page",
+        query_chars,
+        filter = ?filter,
+        offset
+    );
+    let SearchContext {
+        db,
+        indexer,
+        cache,
+        runtime,
+        token,
+        presentation,
+        paste_target,
+        index_only,
+    } = context;
+    let runtime_for_closure = runtime.clone();
+
+    let handle = runtime.spawn_blocking(move || {
+        let _entered = span.entered();
+        panic_guard::contain(&db, "search_page", || {
+            let (content_type_filter, mut tags) =
+                crate::search_result_builder::split_filter(&filter);
+            tags.locale = item_locale::preferred(&db)?;
+            let parsed_query = match query {
+                PageQuery::Text(query) => {
+                    let query = user_tags::extract_hashtags(&db, &query, &mut tags)?;
+                    let query = file_kind::extract_kind_filter(&query, &mut tags);
+                    search::SearchQuery::parse(&query)
+                }
+                PageQuery::Built(parsed_query) => parsed_query,
+            };
+            let is_trigram =
+                !parsed_query.raw_text().is_empty() && !uses_short_query_path(&parsed_query);
+            let personalization = match paste_target.as_deref() {
+                Some(target) if is_trigram => PersonalizationPriors::load(&db, target)?,
+                _ => None,
+            };
+            let assembler =
+                SearchResultAssembler::new(&db, &cache, &token, &runtime_for_closure, presentation)
+                    .with_personalization(personalization.as_ref())
+                    .with_index_only(index_only && is_trigram)
+                    .with_sort(sort);
+            let content_type_filter = content_type_filter.as_ref();
+
+            let (matches, next_offset, total_count) = if is_trigram {
+                let (matches, next_offset, total) = assembler.search_trigram_page(
+                    &indexer,
+                    &parsed_query,
+                    content_type_filter,
+                    &tags,
+                    offset,
+                    page_size,
+                )?;
+                (matches, next_offset, total as u64)
+            } else {
+                let (matches, total) = if parsed_query.raw_text().is_empty() {
+                    assembler.empty_query_page(content_type_filter, &tags, offset, page_size)?
+                } else {
+                    // Short queries are capped well below a page; slice them.
+                    let mut all =
+                        search_short(&assembler, &parsed_query, content_type_filter, &tags)?;
+                    if sort == SearchSort::Newest {
+                        all.sort_by(|a, b| {
+                            b.item_metadata
+                                .timestamp_unix
+                                .cmp(&a.item_metadata.timestamp_unix)
+                                .then_with(|| a.item_metadata.item_id.cmp(&b.item_metadata.item_id))
+                        });
+                    }
+                    let total = all.len() as u64;
+                    (
+                        all.into_iter().skip(offset).take(page_size).collect(),
+                        total,
+                    )
+                };
+                let end = offset + matches.len();
+                (matches, (end < total as usize).then_some(end), total)
+            };
+
+            let page = assembler.build_search_page(
+                parsed_query.raw_text(),
+                matches,
+                total_count,
+                offset == 0,
+            )?;
+            Ok(SearchPage {
+                matches: page.matches,
+                total_count,
+                next_cursor: next_offset.map(|offset| {
+                    SearchPageCursor {
+                        offset,
+                        fingerprint,
+                    }
+                    .encode()
+                }),
+                first_preview_payload: page.first_preview_payload,
+            })
+        })
+    });
+
+    match handle.await {
+        Ok(result) => result,
+        Err(_join_error) => Err(ClipKittyError::Cancelled),
+    }
 }
 
 pub(crate) fn resolve_matched_excerpts(
@@ -98,6 +355,15 @@ pub(crate) fn resolve_matched_excerpts(
     MatchPresentation::new(db, cache).resolve_matched_excerpts(requests)
 }
 
+pub(crate) fn resolve_matched_excerpt_patches(
+    db: &Database,
+    cache: &HighlightAnalysisCache,
+    previous_query: &str,
+    requests: Vec<MatchedExcerptRequest>,
+) -> Result<Vec<MatchedExcerptPatch>, ClipKittyError> {
+    MatchPresentation::new(db, cache).resolve_matched_excerpt_patches(previous_query, requests)
+}
+
 pub(crate) fn load_preview_payload(
     db: &Database,
     cache: &HighlightAnalysisCache,
@@ -107,6 +373,39 @@ pub(crate) fn load_preview_payload(
     MatchPresentation::new(db, cache).load_preview_payload(item_id, query)
 }
 
+/// The files of a file item that `query` matches by filename or path, in
+/// the item's order. Search indexes every filename and path of an item as
+/// one document; this attributes the hit to the files it came from.
+pub(crate) fn matched_files(
+    db: &Database,
+    item_id: &str,
+    query: &str,
+) -> Result<Vec<FileMatch>, ClipKittyError> {
+    let item = db
+        .fetch_items_by_item_ids(&[item_id.to_string()])?
+        .into_iter()
+        .next()
+        .ok_or_else(|| ClipKittyError::InvalidInput(format!("unknown item `{item_id}`")))?;
+    let ClipboardContent::File { files, .. } = item.content else {
+        return Ok(Vec::new());
+    };
+    Ok(files
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, file)| {
+            let filename_highlights = search::utf16_highlights_for_query(&file.filename, query);
+            let path_highlights = search::utf16_highlights_for_query(&file.path, query);
+            (!filename_highlights.is_empty() || !path_highlights.is_empty()).then_some(FileMatch {
+                file_index: index as u64,
+                filename: file.filename,
+                path: file.path,
+                filename_highlights,
+                path_highlights,
+            })
+        })
+        .collect())
+}
+
 #[cfg(test)]
 #[allow(dead_code)]
 pub(crate) fn search_short_query_sync(
@@ -117,7 +416,7 @@ pub(crate) fn search_short_query_sync(
     token: &CancellationToken,
     runtime: &tokio::runtime::Handle,
     filter: Option<&ContentTypeFilter>,
-    tag: Option<ItemTag>,
+    tags: &TagRestriction,
 ) -> Result<Vec<ItemMatch>, ClipKittyError> {
     SearchResultAssembler::new(
         db,
@@ -126,7 +425,7 @@ pub(crate) fn search_short_query_sync(
         runtime,
         ListPresentationProfile::CompactRow,
     )
-    .search_short_query(query, mode, filter, tag)
+    .search_short_query(query, mode, filter, tags)
 }
 
 #[cfg(test)]
@@ -139,7 +438,7 @@ pub(crate) fn search_trigram_query_sync(
     token: &CancellationToken,
     runtime: &tokio::runtime::Handle,
     filter: Option<&ContentTypeFilter>,
-    tag: Option<ItemTag>,
+    tags: &TagRestriction,
 ) -> Result<Vec<ItemMatch>, ClipKittyError> {
     SearchResultAssembler::new(
         db,
@@ -148,44 +447,55 @@ pub(crate) fn search_trigram_query_sync(
         runtime,
         ListPresentationProfile::CompactRow,
     )
-    .search_trigram_query(indexer, query, filter, tag)
+    .search_trigram_query(indexer, query, filter, tags)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn execute_search_sync(
     db: &Database,
     indexer: &Indexer,
     cache: &HighlightAnalysisCache,
     parsed_query: &search::SearchQuery,
-    filter: ItemQueryFilter,
+    content_type_filter: Option<&ContentTypeFilter>,
+    tags: &TagRestriction,
     token: &CancellationToken,
     runtime: &tokio::runtime::Handle,
     presentation: ListPresentationProfile,
+    paste_target: Option<&str>,
+    index_only: bool,
 ) -> Result<Vec<ItemMatch>, ClipKittyError> {
-    let assembler = SearchResultAssembler::new(db, cache, token, runtime, presentation);
-    let (content_type_filter, tag_filter) = crate::search_result_builder::split_filter(filter);
-
+    let personalization = match paste_target {
+        Some(target) => PersonalizationPriors::load(db, target)?,
+        None => None,
+    };
+    let assembler = SearchResultAssembler::new(db, cache, token, runtime, presentation)
+        .with_personalization(personalization.as_ref())
+        .with_index_only(index_only);
     if uses_short_query_path(parsed_query) {
-        return match parsed_query {
-            search::SearchQuery::Plain { text } => assembler.search_short_query(
-                text,
-                ShortQueryMode::PrefixThenContains,
-                content_type_filter.as_ref(),
-                tag_filter,
-            ),
-            search::SearchQuery::PreferPrefix { stripped_text, .. } => assembler
-                .search_short_query(
-                    stripped_text,
-                    ShortQueryMode::PrefixOnly,
-                    content_type_filter.as_ref(),
-                    tag_filter,
-                ),
-        };
+        return search_short(&assembler, parsed_query, content_type_filter, tags);
     }
 
-    assembler.search_trigram_query(
-        indexer,
-        parsed_query,
-        content_type_filter.as_ref(),
-        tag_filter,
-    )
+    assembler.search_trigram_query(indexer, parsed_query, content_type_filter, tags)
+}
+
+fn search_short(
+    assembler: &SearchResultAssembler<'_>,
+    parsed_query: &search::SearchQuery,
+    content_type_filter: Option<&ContentTypeFilter>,
+    tags: &TagRestriction,
+) -> Result<Vec<ItemMatch>, ClipKittyError> {
+    match parsed_query {
+        search::SearchQuery::Plain { text, .. } => assembler.search_short_query(
+            text,
+            ShortQueryMode::PrefixThenContains,
+            content_type_filter,
+            tags,
+        ),
+        search::SearchQuery::PreferPrefix { stripped_text, .. } => assembler.search_short_query(
+            stripped_text,
+            ShortQueryMode::PrefixOnly,
+            content_type_filter,
+            tags,
+        ),
+    }
 }
@@ -0,0 +1,894 @@
+//! Sample history for onboarding and screenshots.
+//!
+//! Each supported language has its own set of text items, dated back from
+//! the moment of seeding so the list reads like real history. Seeding again
+//! replaces the items an earlier seed inserted, so switching languages never
+//! leaves the previous set behind. Seeded items are saved locally only;
+//! nothing is sent to sync.
+
+use crate::database::{Database, DatabaseError};
+use crate::indexer::Indexer;
+use crate::interface::ClipKittyError;
+use crate::save_service::{self, InsertOutcome};
+use chrono::{DateTime, Duration, Utc};
+
+const SEED_NAMESPACE: &str = "seed";
+const SEEDED_ITEM_IDS_KEY: &str = "demo_item_ids";
+
+struct DemoItem {
+    content: &'static str,
+    source_app: &'static str,
+    bundle_id: &'static str,
+    /// Seconds from the moment of seeding; negative is in the past.
+    offset: i64,
+}
+
+/// Items for a BCP 47 or Apple locale identifier ("de-DE", "pt_BR",
+/// "zh-Hant-TW"). Languages without a set here get English.
+fn demo_items(locale: &str) -> &'static [DemoItem] {
+    let mut parts = locale.split(['-', '_']);
+    let language = parts.next().unwrap_or_default().to_ascii_lowercase();
+    match language.as_str() {
+        "es" => DEMO_ITEMS_ES,
+        "ja" => DEMO_ITEMS_JA,
+        "ko" => DEMO_ITEMS_KO,
+        "fr" => DEMO_ITEMS_FR,
+        "de" => DEMO_ITEMS_DE,
+        "pt" => DEMO_ITEMS_PT_BR,
+        "ru" => DEMO_ITEMS_RU,
+        "zh" => {
+            let traditional = parts.any(|part| {
+                ["hant", "tw", "hk", "mo"].contains(&part.to_ascii_lowercase().as_str())
+            });
+            if traditional {
+                DEMO_ITEMS_ZH_HANT
+            } else {
+                DEMO_ITEMS_ZH_HANS
+            }
+        }
+        _ => DEMO_ITEMS,
+    }
+}
+
+fn seeded_item_ids(db: &Database) -> Result<Vec<String>, ClipKittyError> {
+    let Some(bytes) = db.get_key_value(SEED_NAMESPACE, SEEDED_ITEM_IDS_KEY)? else {
+        return Ok(Vec::new());
+    };
+    serde_json::from_slice(&bytes).map_err(|_| {
+        DatabaseError::InconsistentData("seeded item IDs are unreadable".into()).into()
+    })
+}
+
+/// Replace earlier demo items with `locale`'s, dated back from `now`.
+/// Returns the IDs of the inserted items. A demo text already in history
+/// is moved to the top as any repeated copy is, and stays the user's.
+pub(crate) fn seed(
+    db: &Database,
+    indexer: &Indexer,
+    locale: &str,
+    now: DateTime<Utc>,
+) -> Result<Vec<String>, ClipKittyError> {
+    for item_id in seeded_item_ids(db)? {
+        if let Some(row_id) = db.fetch_row_id_by_item_id(&item_id)? {
+            db.delete_item(row_id)?;
+            indexer.delete_document(&item_id)?;
+        }
+    }
+    indexer.commit()?;
+
+    let mut seeded = Vec::new();
+    for item in demo_items(locale) {
+        let outcome = save_service::save_text(
+            db,
+            indexer,
+            item.content.to_string(),
+            Some(item.source_app.to_string()),
+            Some(item.bundle_id.to_string()),
+        )?;
+        if let InsertOutcome::Inserted {
+            new_id, item_id, ..
+        } = outcome
+        {
+            db.update_timestamp(new_id, now + Duration::seconds(item.offset))?;
+            save_service::reindex_item(db, indexer, new_id)?;
+            seeded.push(item_id);
+        }
+    }
+    let encoded = serde_json::to_vec(&seeded).expect("a list of strings always serializes");
+    db.put_key_value(SEED_NAMESPACE, SEEDED_ITEM_IDS_KEY, &encoded)?;
+    Ok(seeded)
+}
+
+const DEMO_ITEMS: &[DemoItem] = &[
+    // --- Scene 3: Old items ---
+    DemoItem {
+        content: "Apartment walkthrough notes: 437 Riverside Dr #12, hardwood floors throughout, south-facing windows with park views, original crown molding, in-unit washer/dryer, $2850/mo, super lives on-site, contact Marcus Realty about lease terms and move-in date flexibility...",
+        source_app: "Notes",
+        bundle_id: "com.apple.Notes",
+        offset: -180 * 24 * 60 * 60, // 180 days ago
+    },
+    DemoItem {
+        content: "riverside_park_picnic_directions.txt",
+        source_app: "Notes",
+        bundle_id: "com.apple.Notes",
+        offset: -3600,
+    },
+    DemoItem {
+        content: "driver_config.yaml",
+        source_app: "TextEdit",
+        bundle_id: "com.apple.TextEdit",
+        offset: -3550,
+    },
+    DemoItem {
+        content: "river_animation_keyframes.css",
+        source_app: "TextEdit",
+        bundle_id: "com.apple.TextEdit",
+        offset: -3500,
+    },
+    DemoItem {
+        content: "derive_key_from_password(salt: Data, iterations: Int) -> Data { ... }",
+        source_app: "Automator",
+        bundle_id: "com.apple.Automator",
+        offset: -3400,
+    },
+    DemoItem {
+        content: "return fetchData().then(res => res.json()).catch(handleError)...",
+        source_app: "TextEdit",
+        bundle_id: "com.apple.TextEdit",
+        offset: -3200,
+    },
+    DemoItem {
+        content: "README.md",
+        source_app: "Finder",
+        bundle_id: "com.apple.finder",
+        offset: -3100,
+    },
+    DemoItem {
+        content: "RFC 2616 HTTP/1.1 Specification full text...",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -3000,
+    },
+    DemoItem {
+        content: r#"grep -rn "TODO\|FIXME" ./src"#,
+        source_app: "Terminal",
+        bundle_id: "com.apple.Terminal",
+        offset: -2900,
+    },
+    DemoItem {
+        content: "border-radius: 8px;",
+        source_app: "TextEdit",
+        bundle_id: "com.apple.TextEdit",
+        offset: -2800,
+    },
+    // Deploy command for search demo (fuzzy match target)
+    DemoItem {
+        content: "# Deploy API server to production\ndocker build -t api-server:latest . && \\\ndocker push registry.company.com/api-server:latest && \\\nkubectl set image deployment/api \\\n  api=registry.company.com/api-server:latest \\\n  -n production",
+        source_app: "Terminal",
+        bundle_id: "com.apple.Terminal",
+        offset: -90 * 24 * 60 * 60, // ~90 days ago (middle of history)
+    },
+    DemoItem {
+        content: "Architecture diagram with service mesh",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -1300,
+    },
+    DemoItem {
+        content: "#border-container { margin: 0; padding: 16px; display: flex; flex-direction: column; ...",
+        source_app: "TextEdit",
+        bundle_id: "com.apple.TextEdit",
+        offset: -1200,
+    },
+    DemoItem {
+        content: "catalog_api_response.json",
+        source_app: "Mail",
+        bundle_id: "com.apple.mail",
+        offset: -1100,
+    },
+    DemoItem {
+        content: "catch (error) { logger.error(error); Sentry.captureException(error); ...",
+        source_app: "TextEdit",
+        bundle_id: "com.apple.TextEdit",
+        offset: -1000,
+    },
+    DemoItem {
+        content: "concatenate_strings(a, b)",
+        source_app: "TextEdit",
+        bundle_id: "com.apple.TextEdit",
+        offset: -900,
+    },
+    DemoItem {
+        content: r#"categories: [{ id: 1, name: "Electronics", subcategories: [...] }]"#,
+        source_app: "TextEdit",
+        bundle_id: "com.apple.TextEdit",
+        offset: -800,
+    },
+    DemoItem {
+        content: "#FF5733",
+        source_app: "Freeform",
+        bundle_id: "com.apple.freeform",
+        offset: -200,  // Orange - shows in first 10 items
+    },
+    DemoItem {
+        content: "#2DD4BF",
+        source_app: "Preview",
+        bundle_id: "com.apple.Preview",
+        offset: -350,  // Teal - shows in first 10 items
+    },
+    DemoItem {
+        content: "The quick brown fox jumps over the lazy dog",
+        source_app: "Notes",
+        bundle_id: "com.apple.Notes",
+        offset: -120,
+    },
+    DemoItem {
+        content: "https://developer.apple.com/documentation/swiftui",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -60,
+    },
+    DemoItem {
+        content: "#!/bin/bash\nset -euo pipefail\necho \"Deploying to prod...\"",
+        source_app: "TextEdit",
+        bundle_id: "com.apple.TextEdit",
+        offset: -40,
+    },
+    DemoItem {
+        content: "ClipKitty\n• Copy it once, find it forever\n• Unlimited clipboard history\n• Search that forgives typos\n• Preview before pasting\n• Private by default",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -10,
+    },
+];
+
+// ============================================================================
+// Spanish (es)
+// ============================================================================
+const DEMO_ITEMS_ES: &[DemoItem] = &[
+    // Old item (apartment notes equivalent)
+    DemoItem {
+        content: "Notas del recorrido del apartamento: Calle Riverside 437 #12, pisos de madera en todo el lugar, ventanas orientadas al sur con vistas al parque, molduras originales, lavadora/secadora en la unidad, $2850/mes, el portero vive en el edificio, contactar a Marcus Realty...",
+        source_app: "Notas",
+        bundle_id: "com.apple.Notes",
+        offset: -180 * 24 * 60 * 60,
+    },
+    // Deploy command for fuzzy search demo
+    DemoItem {
+        content: "# Enviar servidor API a producción\ndocker build -t api-server:latest . && \\\ndocker push registry.company.com/api-server:latest && \\\nkubectl set image deployment/api \\\n  api=registry.company.com/api-server:latest \\\n  -n production",
+        source_app: "Terminal",
+        bundle_id: "com.apple.Terminal",
+        offset: -90 * 24 * 60 * 60,
+    },
+    // Recent items for screenshot visibility
+    DemoItem {
+        content: "~/Documentos/proyectos/app-web/src/componentes/autenticacion",
+        source_app: "Finder",
+        bundle_id: "com.apple.finder",
+        offset: -300,
+    },
+    DemoItem {
+        content: "// TODO: Refactorizar esta función para mejorar el rendimiento\n// Considerar usar memo para evitar renderizados innecesarios",
+        source_app: "Visual Studio Code",
+        bundle_id: "com.microsoft.VSCode",
+        offset: -240,
+    },
+    DemoItem {
+        content: "https://es.react.dev/reference/react/useState",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -180,
+    },
+    DemoItem {
+        content: "Recordatorio: Reunión con el equipo mañana a las 10:00 - revisar los requisitos de la nueva función",
+        source_app: "Notas",
+        bundle_id: "com.apple.Notes",
+        offset: -120,
+    },
+    DemoItem {
+        content: "git commit -m \"Corregir validación del formulario de inicio de sesión\"",
+        source_app: "Terminal",
+        bundle_id: "com.apple.Terminal",
+        offset: -80,
+    },
+    DemoItem {
+        content: "¡Hola mundo!",
+        source_app: "TextEdit",
+        bundle_id: "com.apple.TextEdit",
+        offset: -45,
+    },
+    DemoItem {
+        content: "npm run build && npm test",
+        source_app: "Terminal",
+        bundle_id: "com.apple.Terminal",
+        offset: -30,
+    },
+    // ClipKitty bullet points (most recent text item)
+    DemoItem {
+        content: "ClipKitty\n• Cópialo una vez, encuéntralo siempre\n• La búsqueda inteligente perdona tus errores\n• Ve bloques de código completos antes de pegar\n• ⌥Espacio para invocar, teclado primero\n• Tus datos nunca salen de tu Mac",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -10,
+    },
+];
+
+// ============================================================================
+// Simplified Chinese (zh-Hans)
+// ============================================================================
+const DEMO_ITEMS_ZH_HANS: &[DemoItem] = &[
+    DemoItem {
+        content: "公寓看房笔记：滨江大道437号12室，全屋硬木地板，朝南窗户可观公园景色，原装石膏线，室内洗衣烘干机，$2850/月，管理员住在楼内，联系Marcus房产咨询租约条款...",
+        source_app: "备忘录",
+        bundle_id: "com.apple.Notes",
+        offset: -180 * 24 * 60 * 60,
+    },
+    DemoItem {
+        content: "# 推送API服务器到生产环境\ndocker build -t api-server:latest . && \\\ndocker push registry.company.com/api-server:latest && \\\nkubectl set image deployment/api \\\n  api=registry.company.com/api-server:latest \\\n  -n production",
+        source_app: "终端",
+        bundle_id: "com.apple.Terminal",
+        offset: -90 * 24 * 60 * 60,
+    },
+    // Recent items for screenshot visibility
+    DemoItem {
+        content: "~/文档/项目/网页应用/src/组件/身份验证",
+        source_app: "访达",
+        bundle_id: "com.apple.finder",
+        offset: -300,
+    },
+    DemoItem {
+        content: "// TODO: 重构此函数以提高性能\n// 考虑使用 memo 来避免不必要的重新渲染",
+        source_app: "Visual Studio Code",
+        bundle_id: "com.microsoft.VSCode",
+        offset: -240,
+    },
+    DemoItem {
+        content: "https://zh-hans.react.dev/reference/react/useState",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -180,
+    },
+    DemoItem {
+        content: "提醒：明天上午10:00与团队开会 - 审查新功能需求",
+        source_app: "备忘录",
+        bundle_id: "com.apple.Notes",
+        offset: -120,
+    },
+    DemoItem {
+        content: "git commit -m \"修复登录表单验证问题\"",
+        source_app: "终端",
+        bundle_id: "com.apple.Terminal",
+        offset: -80,
+    },
+    DemoItem {
+        content: "你好，世界！",
+        source_app: "文本编辑",
+        bundle_id: "com.apple.TextEdit",
+        offset: -45,
+    },
+    DemoItem {
+        content: "npm run build && npm test",
+        source_app: "终端",
+        bundle_id: "com.apple.Terminal",
+        offset: -30,
+    },
+    DemoItem {
+        content: "ClipKitty\n• 复制一次，永久查找\n• 智能搜索容忍拼写错误\n• 粘贴前查看完整代码块\n• ⌥空格唤出，键盘优先\n• 数据永不离开你的 Mac",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -10,
+    },
+];
+
+// ============================================================================
+// Traditional Chinese (zh-Hant)
+// ============================================================================
+const DEMO_ITEMS_ZH_HANT: &[DemoItem] = &[
+    DemoItem {
+        content: "公寓看房筆記：濱江大道437號12室，全屋硬木地板，朝南窗戶可觀公園景色，原裝石膏線，室內洗衣烘乾機，$2850/月，管理員住在樓內，聯繫Marcus房產諮詢租約條款...",
+        source_app: "備忘錄",
+        bundle_id: "com.apple.Notes",
+        offset: -180 * 24 * 60 * 60,
+    },
+    DemoItem {
+        content: "# 推送API伺服器到生產環境\ndocker build -t api-server:latest . && \\\ndocker push registry.company.com/api-server:latest && \\\nkubectl set image deployment/api \\\n  api=registry.company.com/api-server:latest \\\n  -n production",
+        source_app: "終端機",
+        bundle_id: "com.apple.Terminal",
+        offset: -90 * 24 * 60 * 60,
+    },
+    // Recent items for screenshot visibility
+    DemoItem {
+        content: "~/文件/專案/網頁應用程式/src/元件/身份驗證",
+        source_app: "尋找器",
+        bundle_id: "com.apple.finder",
+        offset: -300,
+    },
+    DemoItem {
+        content: "// TODO: 重構此函式以提升效能\n// 考慮使用 memo 來避免不必要的重新渲染",
+        source_app: "Visual Studio Code",
+        bundle_id: "com.microsoft.VSCode",
+        offset: -240,
+    },
+    DemoItem {
+        content: "https://zh-hant.react.dev/reference/react/useState",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -180,
+    },
+    DemoItem {
+        content: "提醒：明天上午10:00與團隊開會 - 審查新功能需求",
+        source_app: "備忘錄",
+        bundle_id: "com.apple.Notes",
+        offset: -120,
+    },
+    DemoItem {
+        content: "git commit -m \"修正登入表單驗證問題\"",
+        source_app: "終端機",
+        bundle_id: "com.apple.Terminal",
+        offset: -80,
+    },
+    DemoItem {
+        content: "你好，世界！",
+        source_app: "文字編輯",
+        bundle_id: "com.apple.TextEdit",
+        offset: -45,
+    },
+    DemoItem {
+        content: "npm run build && npm test",
+        source_app: "終端機",
+        bundle_id: "com.apple.Terminal",
+        offset: -30,
+    },
+    DemoItem {
+        content: "ClipKitty\n• 複製一次，永遠找得到\n• 智慧搜尋容忍拼字錯誤\n• 貼上前檢視完整程式碼區塊\n• ⌥Space 喚出，鍵盤優先\n• 資料永遠不會離開你的 Mac",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -10,
+    },
+];
+
+// ============================================================================
+// Japanese (ja)
+// ============================================================================
+const DEMO_ITEMS_JA: &[DemoItem] = &[
+    DemoItem {
+        content: "アパート内覧メモ：リバーサイドドライブ437番地12号室、全室フローリング、南向きの窓から公園を一望、オリジナルの装飾モールディング、室内洗濯乾燥機、$2850/月、管理人常駐、Marcus不動産に契約条件を問い合わせ...",
+        source_app: "メモ",
+        bundle_id: "com.apple.Notes",
+        offset: -180 * 24 * 60 * 60,
+    },
+    DemoItem {
+        content: "# APIサーバーを本番環境にプッシュ\ndocker build -t api-server:latest . && \\\ndocker push registry.company.com/api-server:latest && \\\nkubectl set image deployment/api \\\n  api=registry.company.com/api-server:latest \\\n  -n production",
+        source_app: "ターミナル",
+        bundle_id: "com.apple.Terminal",
+        offset: -90 * 24 * 60 * 60,
+    },
+    // Recent items for screenshot visibility
+    DemoItem {
+        content: "~/書類/プロジェクト/ウェブアプリ/src/コンポーネント/認証",
+        source_app: "Finder",
+        bundle_id: "com.apple.finder",
+        offset: -300,
+    },
+    DemoItem {
+        content: "// TODO: パフォーマンス向上のためこの関数をリファクタリング\n// 不要な再レンダリングを避けるため memo の使用を検討",
+        source_app: "Visual Studio Code",
+        bundle_id: "com.microsoft.VSCode",
+        offset: -240,
+    },
+    DemoItem {
+        content: "https://ja.react.dev/reference/react/useState",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -180,
+    },
+    DemoItem {
+        content: "リマインダー：明日午前10:00にチームミーティング - 新機能の要件をレビュー",
+        source_app: "メモ",
+        bundle_id: "com.apple.Notes",
+        offset: -120,
+    },
+    DemoItem {
+        content: "git commit -m \"ログインフォームのバリデーションを修正\"",
+        source_app: "ターミナル",
+        bundle_id: "com.apple.Terminal",
+        offset: -80,
+    },
+    DemoItem {
+        content: "こんにちは、世界！",
+        source_app: "テキストエディット",
+        bundle_id: "com.apple.TextEdit",
+        offset: -45,
+    },
+    DemoItem {
+        content: "npm run build && npm test",
+        source_app: "ターミナル",
+        bundle_id: "com.apple.Terminal",
+        offset: -30,
+    },
+    DemoItem {
+        content: "ClipKitty\n• 一度コピーすれば、いつでも見つかる\n• スマート検索でタイプミスも許容\n• ペースト前にコードブロック全体を確認\n• ⌥Spaceで呼び出し、キーボード操作\n• データがMacの外に出ることはありません",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -10,
+    },
+];
+
+// ============================================================================
+// Korean (ko)
+// ============================================================================
+const DEMO_ITEMS_KO: &[DemoItem] = &[
+    DemoItem {
+        content: "아파트 투어 메모: 리버사이드 드라이브 437번지 12호, 전체 원목 바닥, 공원 전망의 남향 창문, 오리지널 크라운 몰딩, 세탁기/건조기 내장, $2850/월, 관리인 상주, Marcus 부동산에 임대 조건 문의...",
+        source_app: "메모",
+        bundle_id: "com.apple.Notes",
+        offset: -180 * 24 * 60 * 60,
+    },
+    DemoItem {
+        content: "# API 서버를 프로덕션에 푸시\ndocker build -t api-server:latest . && \\\ndocker push registry.company.com/api-server:latest && \\\nkubectl set image deployment/api \\\n  api=registry.company.com/api-server:latest \\\n  -n production",
+        source_app: "터미널",
+        bundle_id: "com.apple.Terminal",
+        offset: -90 * 24 * 60 * 60,
+    },
+    // Recent items for screenshot visibility
+    DemoItem {
+        content: "~/문서/프로젝트/웹앱/src/컴포넌트/인증",
+        source_app: "Finder",
+        bundle_id: "com.apple.finder",
+        offset: -300,
+    },
+    DemoItem {
+        content: "// TODO: 성능 향상을 위해 이 함수 리팩토링\n// 불필요한 재렌더링을 피하기 위해 memo 사용 고려",
+        source_app: "Visual Studio Code",
+        bundle_id: "com.microsoft.VSCode",
+        offset: -240,
+    },
+    DemoItem {
+        content: "https://ko.react.dev/reference/react/useState",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -180,
+    },
+    DemoItem {
+        content: "알림: 내일 오전 10:00 팀 회의 - 새 기능 요구사항 검토",
+        source_app: "메모",
+        bundle_id: "com.apple.Notes",
+        offset: -120,
+    },
+    DemoItem {
+        content: "git commit -m \"로그인 폼 유효성 검사 수정\"",
+        source_app: "터미널",
+        bundle_id: "com.apple.Terminal",
+        offset: -80,
+    },
+    DemoItem {
+        content: "안녕하세요, 세상!",
+        source_app: "텍스트 편집기",
+        bundle_id: "com.apple.TextEdit",
+        offset: -45,
+    },
+    DemoItem {
+        content: "npm run build && npm test",
+        source_app: "터미널",
+        bundle_id: "com.apple.Terminal",
+        offset: -30,
+    },
+    DemoItem {
+        content: "ClipKitty\n• 한 번 복사하면 영원히 검색 가능\n• 스마트 검색으로 오타도 문제없음\n• 붙여넣기 전에 전체 코드 블록 확인\n• ⌥Space로 호출, 키보드 중심\n• 데이터가 Mac 밖으로 나가지 않음",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -10,
+    },
+];
+
+// ============================================================================
+// French (fr)
+// ============================================================================
+const DEMO_ITEMS_FR: &[DemoItem] = &[
+    DemoItem {
+        content: "Notes de visite d'appartement : 437 Riverside Dr #12, parquet dans tout l'appartement, fenêtres orientées sud avec vue sur le parc, moulures d'origine, lave-linge/sèche-linge intégré, $2850/mois, gardien sur place, contacter Marcus Realty...",
+        source_app: "Notes",
+        bundle_id: "com.apple.Notes",
+        offset: -180 * 24 * 60 * 60,
+    },
+    DemoItem {
+        content: "# Envoyer le serveur API en production\ndocker build -t api-server:latest . && \\\ndocker push registry.company.com/api-server:latest && \\\nkubectl set image deployment/api \\\n  api=registry.company.com/api-server:latest \\\n  -n production",
+        source_app: "Terminal",
+        bundle_id: "com.apple.Terminal",
+        offset: -90 * 24 * 60 * 60,
+    },
+    // Recent items for screenshot visibility
+    DemoItem {
+        content: "~/Documents/projets/app-web/src/composants/authentification",
+        source_app: "Finder",
+        bundle_id: "com.apple.finder",
+        offset: -300,
+    },
+    DemoItem {
+        content: "// TODO: Refactoriser cette fonction pour améliorer les performances\n// Envisager d'utiliser memo pour éviter les rendus inutiles",
+        source_app: "Visual Studio Code",
+        bundle_id: "com.microsoft.VSCode",
+        offset: -240,
+    },
+    DemoItem {
+        content: "https://fr.react.dev/reference/react/useState",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -180,
+    },
+    DemoItem {
+        content: "Rappel : Réunion d'équipe demain à 10h00 - réviser les exigences de la nouvelle fonctionnalité",
+        source_app: "Notes",
+        bundle_id: "com.apple.Notes",
+        offset: -120,
+    },
+    DemoItem {
+        content: "git commit -m \"Corriger la validation du formulaire de connexion\"",
+        source_app: "Terminal",
+        bundle_id: "com.apple.Terminal",
+        offset: -80,
+    },
+    DemoItem {
+        content: "Bonjour le monde !",
+        source_app: "TextEdit",
+        bundle_id: "com.apple.TextEdit",
+        offset: -45,
+    },
+    DemoItem {
+        content: "npm run build && npm test",
+        source_app: "Terminal",
+        bundle_id: "com.apple.Terminal",
+        offset: -30,
+    },
+    DemoItem {
+        content: "ClipKitty\n• Copiez une fois, retrouvez toujours\n• La recherche intelligente pardonne les fautes\n• Visualisez les blocs de code avant de coller\n• ⌥Espace pour invoquer, clavier d'abord\n• Vos données ne quittent jamais votre Mac",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -10,
+    },
+];
+
+// ============================================================================
+// German (de)
+// ============================================================================
+const DEMO_ITEMS_DE: &[DemoItem] = &[
+    DemoItem {
+        content: "Wohnungsbesichtigung Notizen: Riverside Dr 437 #12, durchgehend Parkettboden, Südfenster mit Parkblick, originale Stuckleisten, Waschmaschine/Trockner in der Wohnung, $2850/Monat, Hausmeister vor Ort, Marcus Realty kontaktieren...",
+        source_app: "Notizen",
+        bundle_id: "com.apple.Notes",
+        offset: -180 * 24 * 60 * 60,
+    },
+    DemoItem {
+        content: "# API-Server in Produktion schicken\ndocker build -t api-server:latest . && \\\ndocker push registry.company.com/api-server:latest && \\\nkubectl set image deployment/api \\\n  api=registry.company.com/api-server:latest \\\n  -n production",
+        source_app: "Terminal",
+        bundle_id: "com.apple.Terminal",
+        offset: -90 * 24 * 60 * 60,
+    },
+    // Recent items for screenshot visibility
+    DemoItem {
+        content: "~/Dokumente/projekte/web-app/src/komponenten/authentifizierung",
+        source_app: "Finder",
+        bundle_id: "com.apple.finder",
+        offset: -300,
+    },
+    DemoItem {
+        content: "// TODO: Diese Funktion refaktorieren um die Leistung zu verbessern\n// Verwendung von memo in Betracht ziehen um unnötige Renderings zu vermeiden",
+        source_app: "Visual Studio Code",
+        bundle_id: "com.microsoft.VSCode",
+        offset: -240,
+    },
+    DemoItem {
+        content: "https://de.react.dev/reference/react/useState",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -180,
+    },
+    DemoItem {
+        content: "Erinnerung: Teammeeting morgen um 10:00 Uhr - Anforderungen für neue Funktion überprüfen",
+        source_app: "Notizen",
+        bundle_id: "com.apple.Notes",
+        offset: -120,
+    },
+    DemoItem {
+        content: "git commit -m \"Login-Formular-Validierung korrigieren\"",
+        source_app: "Terminal",
+        bundle_id: "com.apple.Terminal",
+        offset: -80,
+    },
+    DemoItem {
+        content: "Hallo Welt!",
+        source_app: "TextEdit",
+        bundle_id: "com.apple.TextEdit",
+        offset: -45,
+    },
+    DemoItem {
+        content: "npm run build && npm test",
+        source_app: "Terminal",
+        bundle_id: "com.apple.Terminal",
+        offset: -30,
+    },
+    DemoItem {
+        content: "ClipKitty\n• Einmal kopieren, für immer finden\n• Intelligente Suche verzeiht Tippfehler\n• Code-Blöcke vor dem Einfügen ansehen\n• ⌥Leertaste zum Aufrufen, Tastatur zuerst\n• Deine Daten verlassen nie deinen Mac",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -10,
+    },
+];
+
+// ============================================================================
+// Brazilian Portuguese (pt-BR)
+// ============================================================================
+const DEMO_ITEMS_PT_BR: &[DemoItem] = &[
+    DemoItem {
+        content: "Notas da visita ao apartamento: Riverside Dr 437 #12, piso de madeira em todo o imóvel, janelas voltadas para o sul com vista para o parque, molduras originais, lavadora/secadora no apartamento, $2850/mês, zelador no local, contatar Marcus Realty...",
+        source_app: "Notas",
+        bundle_id: "com.apple.Notes",
+        offset: -180 * 24 * 60 * 60,
+    },
+    DemoItem {
+        content: "# Enviar servidor API para produção\ndocker build -t api-server:latest . && \\\ndocker push registry.company.com/api-server:latest && \\\nkubectl set image deployment/api \\\n  api=registry.company.com/api-server:latest \\\n  -n production",
+        source_app: "Terminal",
+        bundle_id: "com.apple.Terminal",
+        offset: -90 * 24 * 60 * 60,
+    },
+    // Recent items for screenshot visibility
+    DemoItem {
+        content: "~/Documentos/projetos/app-web/src/componentes/autenticacao",
+        source_app: "Finder",
+        bundle_id: "com.apple.finder",
+        offset: -300,
+    },
+    DemoItem {
+        content: "// TODO: Refatorar esta função para melhorar o desempenho\n// Considerar usar memo para evitar renderizações desnecessárias",
+        source_app: "Visual Studio Code",
+        bundle_id: "com.microsoft.VSCode",
+        offset: -240,
+    },
+    DemoItem {
+        content: "https://pt-br.react.dev/reference/react/useState",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -180,
+    },
+    DemoItem {
+        content: "Lembrete: Reunião com a equipe amanhã às 10h00 - revisar requisitos da nova funcionalidade",
+        source_app: "Notas",
+        bundle_id: "com.apple.Notes",
+        offset: -120,
+    },
+    DemoItem {
+        content: "git commit -m \"Corrigir validação do formulário de login\"",
+        source_app: "Terminal",
+        bundle_id: "com.apple.Terminal",
+        offset: -80,
+    },
+    DemoItem {
+        content: "Olá, mundo!",
+        source_app: "TextEdit",
+        bundle_id: "com.apple.TextEdit",
+        offset: -45,
+    },
+    DemoItem {
+        content: "npm run build && npm test",
+        source_app: "Terminal",
+        bundle_id: "com.apple.Terminal",
+        offset: -30,
+    },
+    DemoItem {
+        content: "ClipKitty\n• Copie uma vez, encontre para sempre\n• A busca inteligente perdoa erros de digitação\n• Veja blocos de código completos antes de colar\n• ⌥Espaço para chamar, teclado primeiro\n• Seus dados nunca saem do seu Mac",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -10,
+    },
+];
+
+// ============================================================================
+// Russian (ru)
+// ============================================================================
+const DEMO_ITEMS_RU: &[DemoItem] = &[
+    DemoItem {
+        content: "Заметки с осмотра квартиры: Riverside Dr 437 #12, паркет во всех комнатах, окна на юг с видом на парк, оригинальная лепнина, стиральная/сушильная машина в квартире, $2850/мес, консьерж на месте, связаться с Marcus Realty...",
+        source_app: "Заметки",
+        bundle_id: "com.apple.Notes",
+        offset: -180 * 24 * 60 * 60,
+    },
+    DemoItem {
+        content: "# Отправить API-сервер в продакшн\ndocker build -t api-server:latest . && \\\ndocker push registry.company.com/api-server:latest && \\\nkubectl set image deployment/api \\\n  api=registry.company.com/api-server:latest \\\n  -n production",
+        source_app: "Терминал",
+        bundle_id: "com.apple.Terminal",
+        offset: -90 * 24 * 60 * 60,
+    },
+    // Recent items for screenshot visibility
+    DemoItem {
+        content: "~/Документы/проекты/веб-приложение/src/компоненты/аутентификация",
+        source_app: "Finder",
+        bundle_id: "com.apple.finder",
+        offset: -300,
+    },
+    DemoItem {
+        content: "// TODO: Рефакторинг этой функции для улучшения производительности\n// Рассмотреть использование memo для предотвращения ненужных рендеров",
+        source_app: "Visual Studio Code",
+        bundle_id: "com.microsoft.VSCode",
+        offset: -240,
+    },
+    DemoItem {
+        content: "https://ru.react.dev/reference/react/useState",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -180,
+    },
+    DemoItem {
+        content: "Напоминание: Встреча команды завтра в 10:00 - обсудить требования к новой функции",
+        source_app: "Заметки",
+        bundle_id: "com.apple.Notes",
+        offset: -120,
+    },
+    DemoItem {
+        content: "git commit -m \"Исправить валидацию формы входа\"",
+        source_app: "Терминал",
+        bundle_id: "com.apple.Terminal",
+        offset: -80,
+    },
+    DemoItem {
+        content: "Привет, мир!",
+        source_app: "TextEdit",
+        bundle_id: "com.apple.TextEdit",
+        offset: -45,
+    },
+    DemoItem {
+        content: "npm run build && npm test",
+        source_app: "Терминал",
+        bundle_id: "com.apple.Terminal",
+        offset: -30,
+    },
+    DemoItem {
+        content: "ClipKitty\n• Скопируйте один раз — находите всегда\n• Умный поиск прощает опечатки\n• Просматривайте блоки кода перед вставкой\n• ⌥Пробел для вызова, клавиатура в приоритете\n• Ваши данные никогда не покидают Mac",
+        source_app: "Safari",
+        bundle_id: "com.apple.Safari",
+        offset: -10,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeding_again_replaces_the_earlier_set() {
+        let db = Database::open_in_memory().unwrap();
+        let indexer = Indexer::new_in_memory().unwrap();
+        save_service::save_text(&db, &indexer, "¡Hola mundo!".into(), None, None).unwrap();
+        let now = Utc::now();
+
+        let english = seed(&db, &indexer, "en-US", now).unwrap();
+        assert_eq!(english.len(), DEMO_ITEMS.len());
+        assert_eq!(indexer.search("walkthrough", 10).unwrap().len(), 1);
+        let apartment = db.fetch_row_id_by_item_id(&english[0]).unwrap().unwrap();
+        let stored = db.fetch_items_by_ids(&[apartment]).unwrap();
+        assert_eq!(
+            stored[0].timestamp_unix,
+            now.timestamp() + DEMO_ITEMS[0].offset
+        );
+
+        let spanish = seed(&db, &indexer, "es_MX", now).unwrap();
+        // The user's own "¡Hola mundo!" is left out of the seeded set.
+        assert_eq!(spanish.len(), DEMO_ITEMS_ES.len() - 1);
+        assert!(db.fetch_row_id_by_item_id(&english[0]).unwrap().is_none());
+        assert!(indexer.search("walkthrough", 10).unwrap().is_empty());
+        seed(&db, &indexer, "es", now).unwrap();
+        assert_eq!(indexer.search("Hola mundo", 10).unwrap().len(), 1);
+
+        for (locale, expected) in [
+            ("zh-Hant-TW", DEMO_ITEMS_ZH_HANT),
+            ("zh_HK", DEMO_ITEMS_ZH_HANT),
+            ("zh-Hans-CN", DEMO_ITEMS_ZH_HANS),
+            ("pt-PT", DEMO_ITEMS_PT_BR),
+            ("it", DEMO_ITEMS),
+        ] {
+            assert_eq!(
+                demo_items(locale)[0].content,
+                expected[0].content,
+                "{locale}"
+            );
+        }
+    }
+}
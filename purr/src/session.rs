@@ -0,0 +1,111 @@
+//! Temporary capture sessions.
+//!
+//! Between `begin` and `commit` or `discard`, captures are recorded as
+//! session items: searchable like any other, but not yet part of history.
+//! Discarding deletes the items the session inserted and puts the items it
+//! touched back at their earlier timestamps, with the copies it logged
+//! forgotten. Committing keeps everything. The session lives in the
+//! database, so one left open by a crash is discarded on the next launch.
+
+use crate::database::{Database, DatabaseError};
+use crate::indexer::Indexer;
+use crate::interface::ClipKittyError;
+use crate::save_service::{self, InsertOutcome};
+use chrono::{DateTime, Utc};
+
+const SESSION_NAMESPACE: &str = "session";
+const STARTED_AT_KEY: &str = "started_at";
+
+/// A capture the session made: the row and, for an item that existed
+/// before the session, its timestamp then.
+pub(crate) type SessionCapture = (i64, Option<DateTime<Utc>>);
+
+/// When the open session began, or None outside a session.
+pub(crate) fn started_at(db: &Database) -> Result<Option<DateTime<Utc>>, ClipKittyError> {
+    let Some(bytes) = db.get_key_value(SESSION_NAMESPACE, STARTED_AT_KEY)? else {
+        return Ok(None);
+    };
+    let started_at = std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|text| DateTime::parse_from_rfc3339(text).ok())
+        .ok_or_else(|| DatabaseError::InconsistentData("session start is unreadable".into()))?;
+    Ok(Some(started_at.with_timezone(&Utc)))
+}
+
+pub(crate) fn begin(db: &Database, now: DateTime<Utc>) -> Result<(), ClipKittyError> {
+    if started_at(db)?.is_some() {
+        return Err(ClipKittyError::InvalidInput(
+            "a session is already open".into(),
+        ));
+    }
+    db.clear_session_items()?;
+    Ok(db.put_key_value(
+        SESSION_NAMESPACE,
+        STARTED_AT_KEY,
+        now.to_rfc3339().as_bytes(),
+    )?)
+}
+
+/// Record a capture if a session is open. Returns whether it was recorded.
+pub(crate) fn record(db: &Database, outcome: &InsertOutcome) -> Result<bool, ClipKittyError> {
+    if started_at(db)?.is_none() {
+        return Ok(false);
+    }
+    match outcome {
+        InsertOutcome::Inserted { new_id, .. } => db.record_session_item(*new_id, None)?,
+        InsertOutcome::Deduplicated {
+            existing_id,
+            previous_at,
+            ..
+        } => db.record_session_item(*existing_id, Some(*previous_at))?,
+    }
+    Ok(true)
+}
+
+/// Close the session, keeping its captures. Returns them.
+pub(crate) fn commit(db: &Database) -> Result<Vec<SessionCapture>, ClipKittyError> {
+    require_open(db)?;
+    let captures = db.fetch_session_items()?;
+    close(db)?;
+    Ok(captures)
+}
+
+/// Close the session, undoing its captures. Returns the item IDs it deleted.
+pub(crate) fn discard(db: &Database, indexer: &Indexer) -> Result<Vec<String>, ClipKittyError> {
+    let started_at = require_open(db)?;
+    let captures = db.fetch_session_items()?;
+    let row_ids: Vec<i64> = captures.iter().map(|(row_id, _)| *row_id).collect();
+    let items = db.fetch_items_by_ids(&row_ids)?;
+    let mut deleted = Vec::new();
+    for (row_id, previous_at) in captures {
+        match previous_at {
+            Some(previous_at) => {
+                db.update_timestamp(row_id, previous_at)?;
+                db.delete_copy_events_since(row_id, started_at)?;
+                save_service::reindex_item(db, indexer, row_id)?;
+            }
+            None => {
+                let Some(item) = items.iter().find(|item| item.id == Some(row_id)) else {
+                    continue;
+                };
+                db.delete_item(row_id)?;
+                indexer.delete_document(&item.item_id)?;
+                deleted.push(item.item_id.clone());
+            }
+        }
+    }
+    if !deleted.is_empty() {
+        indexer.commit()?;
+    }
+    close(db)?;
+    Ok(deleted)
+}
+
+fn require_open(db: &Database) -> Result<DateTime<Utc>, ClipKittyError> {
+    started_at(db)?.ok_or_else(|| ClipKittyError::InvalidInput("no session is open".into()))
+}
+
+fn close(db: &Database) -> Result<(), ClipKittyError> {
+    db.clear_session_items()?;
+    Ok(db.delete_key_value(SESSION_NAMESPACE, STARTED_AT_KEY)?)
+}
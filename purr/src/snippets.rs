@@ -0,0 +1,324 @@
+//! Saved snippets.
+//!
+//! Snippets are text kept on purpose, apart from history: nothing prunes,
+//! expires, or trashes them. They share the search index with history under
+//! `SNIPPET_ID_PREFIX` document IDs. History searches leave them out, and
+//! the sectioned search lists them above every history section. Bodies use
+//! the syntax `templatize` produces, which `expand` fills in: `{name}`
+//! placeholders, with literal braces doubled.
+
+use crate::database::{Database, SnippetRow};
+use crate::indexer::{Indexer, SNIPPET_ID_PREFIX};
+use crate::interface::{ClipKittyError, Snippet, SnippetVariable};
+use crate::search::SearchQuery;
+use chrono::{DateTime, NaiveDate, Utc};
+use tokio_util::sync::CancellationToken;
+
+/// Filled with the local date, unless the caller passes a value.
+const DATE_PLACEHOLDER: &str = "date";
+/// Filled with the newest text in history, unless the caller passes a value.
+const CLIPBOARD_PLACEHOLDER: &str = "clipboard";
+
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+fn parse(body: &str) -> Result<Vec<Segment<'_>>, ClipKittyError> {
+    let mut segments = Vec::new();
+    let mut rest = body;
+    while let Some(index) = rest.find(['{', '}']) {
+        if index > 0 {
+            segments.push(Segment::Text(&rest[..index]));
+        }
+        let tail = &rest[index..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            segments.push(Segment::Text(&tail[..1]));
+            rest = &tail[2..];
+            continue;
+        }
+        let name = tail
+            .strip_prefix('{')
+            .and_then(|after| after.split_once('}'))
+            .map(|(name, _)| name)
+            .filter(|name| {
+                !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+            });
+        let Some(name) = name else {
+            return Err(ClipKittyError::InvalidInput(format!(
+                "stray `{}` in snippet body; double it for a literal brace",
+                &tail[..1]
+            )));
+        };
+        segments.push(Segment::Placeholder(name));
+        rest = &tail[name.len() + 2..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+    Ok(segments)
+}
+
+fn placeholder_names(body: &str) -> Result<Vec<String>, ClipKittyError> {
+    let mut names: Vec<String> = Vec::new();
+    for segment in parse(body)? {
+        if let Segment::Placeholder(name) = segment {
+            if !names.iter().any(|existing| existing == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    Ok(names)
+}
+
+fn to_snippet((snippet_id, title, body, updated_at): SnippetRow) -> Snippet {
+    Snippet {
+        placeholders: placeholder_names(&body).unwrap_or_default(),
+        snippet_id,
+        title,
+        body,
+        updated_at_unix: updated_at.timestamp(),
+    }
+}
+
+fn unknown_snippet(snippet_id: &str) -> ClipKittyError {
+    ClipKittyError::InvalidInput(format!("unknown snippet `{snippet_id}`"))
+}
+
+fn index(indexer: &Indexer, row: &SnippetRow) -> Result<(), ClipKittyError> {
+    let (snippet_id, title, body, updated_at) = row;
+    indexer.add_document(
+        &format!("{SNIPPET_ID_PREFIX}{snippet_id}"),
+        &format!("{title}\n{body}"),
+        updated_at.timestamp(),
+    )?;
+    Ok(())
+}
+
+pub(crate) fn create(
+    db: &Database,
+    indexer: &Indexer,
+    title: String,
+    body: String,
+    now: DateTime<Utc>,
+) -> Result<Snippet, ClipKittyError> {
+    let title = title.trim().to_string();
+    if title.is_empty() {
+        return Err(ClipKittyError::InvalidInput(
+            "a snippet needs a title".into(),
+        ));
+    }
+    parse(&body)?;
+    let row = (uuid::Uuid::new_v4().to_string(), title, body, now);
+    db.insert_snippet(&row.0, &row.1, &row.2, now)?;
+    index(indexer, &row)?;
+    indexer.commit()?;
+    Ok(to_snippet(row))
+}
+
+/// All snippets, ordered by title.
+pub(crate) fn list(db: &Database) -> Result<Vec<Snippet>, ClipKittyError> {
+    Ok(db.fetch_snippets()?.into_iter().map(to_snippet).collect())
+}
+
+pub(crate) fn delete(
+    db: &Database,
+    indexer: &Indexer,
+    snippet_id: &str,
+) -> Result<(), ClipKittyError> {
+    if !db.delete_snippet(snippet_id)? {
+        return Err(unknown_snippet(snippet_id));
+    }
+    indexer.delete_document(&format!("{SNIPPET_ID_PREFIX}{snippet_id}"))?;
+    indexer.commit()?;
+    Ok(())
+}
+
+/// The snippet's body with its placeholders filled. `variables` win over
+/// the built-in `{date}` (as `today`) and `{clipboard}`; any other
+/// placeholder without a value is an error.
+pub(crate) fn expand(
+    db: &Database,
+    snippet_id: &str,
+    variables: &[SnippetVariable],
+    today: NaiveDate,
+) -> Result<String, ClipKittyError> {
+    let (_, _, body, _) = db
+        .fetch_snippets()?
+        .into_iter()
+        .find(|(id, ..)| id == snippet_id)
+        .ok_or_else(|| unknown_snippet(snippet_id))?;
+    let mut expanded = String::with_capacity(body.len());
+    let mut clipboard: Option<String> = None;
+    for segment in parse(&body)? {
+        let name = match segment {
+            Segment::Text(text) => {
+                expanded.push_str(text);
+                continue;
+            }
+            Segment::Placeholder(name) => name,
+        };
+        if let Some(variable) = variables.iter().find(|variable| variable.name == name) {
+            expanded.push_str(&variable.value);
+        } else if name == DATE_PLACEHOLDER {
+            expanded.push_str(&today.format("%Y-%m-%d").to_string());
+        } else if name == CLIPBOARD_PLACEHOLDER {
+            if clipboard.is_none() {
+                clipboard = Some(db.fetch_latest_text()?.unwrap_or_default());
+            }
+            expanded.push_str(clipboard.as_deref().unwrap_or_default());
+        } else {
+            return Err(ClipKittyError::InvalidInput(format!(
+                "no value for placeholder `{name}`"
+            )));
+        }
+    }
+    Ok(expanded)
+}
+
+/// Snippets matching `query`, best first.
+pub(crate) fn search(
+    db: &Database,
+    indexer: &Indexer,
+    query: &str,
+    limit: usize,
+    token: &CancellationToken,
+) -> Result<Vec<Snippet>, ClipKittyError> {
+    let query = SearchQuery::parse(query);
+    if limit == 0 || query.raw_text().is_empty() {
+        return Ok(Vec::new());
+    }
+    let candidates = indexer.search_snippets(&query, limit, token)?;
+    let mut rows = db.fetch_snippets()?;
+    Ok(candidates
+        .iter()
+        .filter_map(|candidate| candidate.id.strip_prefix(SNIPPET_ID_PREFIX))
+        .filter_map(|snippet_id| {
+            let position = rows.iter().position(|(id, ..)| id == snippet_id)?;
+            Some(to_snippet(rows.swap_remove(position)))
+        })
+        .take(limit)
+        .collect())
+}
+
+/// Add every snippet to `indexer`, which the caller commits.
+pub(crate) fn index_all(db: &Database, indexer: &Indexer) -> Result<(), ClipKittyError> {
+    for row in db.fetch_snippets()? {
+        index(indexer, &row)?;
+    }
+    Ok(())
+}
+
+/// The index document ID of every snippet.
+pub(crate) fn document_ids(db: &Database) -> Result<Vec<String>, ClipKittyError> {
+    Ok(db
+        .fetch_snippets()?
+        .into_iter()
+        .map(|(snippet_id, ..)| format!("{SNIPPET_ID_PREFIX}{snippet_id}"))
+        .collect())
+}
+
+/// Bring the snippet documents among `document_ids` up to date, removing
+/// those whose snippet is gone.
+pub(crate) fn reindex(
+    db: &Database,
+    indexer: &Indexer,
+    document_ids: &[String],
+) -> Result<(), ClipKittyError> {
+    if document_ids.is_empty() {
+        return Ok(());
+    }
+    let rows = db.fetch_snippets()?;
+    for document_id in document_ids {
+        let snippet_id = document_id.trim_start_matches(SNIPPET_ID_PREFIX);
+        match rows.iter().find(|(id, ..)| id == snippet_id) {
+            Some(row) => index(indexer, row)?,
+            None => indexer.delete_document(document_id)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save_service;
+
+    fn variable(name: &str, value: &str) -> SnippetVariable {
+        SnippetVariable {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+
+    #[test]
+    fn snippets_expand_placeholders_and_search_apart_from_history() {
+        let db = Database::open_in_memory().unwrap();
+        let indexer = Indexer::new_in_memory().unwrap();
+        let now = Utc::now();
+        save_service::save_text(&db, &indexer, "invoice 4471 sent".into(), None, None).unwrap();
+        let reply = create(
+            &db,
+            &indexer,
+            " Invoice reply ".into(),
+            "Hi {name}, invoice {{{number}}} is attached ({date}). Re: {clipboard}".into(),
+            now,
+        )
+        .unwrap();
+        create(&db, &indexer, "Address".into(), "1 Main St".into(), now).unwrap();
+        assert_eq!(reply.title, "Invoice reply");
+        assert_eq!(reply.placeholders, ["name", "number", "date", "clipboard"]);
+
+        let titles: Vec<String> = list(&db).unwrap().into_iter().map(|s| s.title).collect();
+        assert_eq!(titles, ["Address", "Invoice reply"]);
+
+        let today = NaiveDate::from_ymd_opt(2026, 3, 5).unwrap();
+        let expanded = expand(
+            &db,
+            &reply.snippet_id,
+            &[variable("name", "Ada"), variable("number", "4471")],
+            today,
+        )
+        .unwrap();
+        assert_eq!(
+            expanded,
+            "Hi Ada, invoice {4471} is attached (2026-03-05). Re: invoice 4471 sent"
+        );
+        let overridden = expand(
+            &db,
+            &reply.snippet_id,
+            &[
+                variable("name", "Ada"),
+                variable("number", "1"),
+                variable("date", "today"),
+            ],
+            today,
+        )
+        .unwrap();
+        assert!(overridden.contains("(today)"));
+        assert!(expand(&db, &reply.snippet_id, &[], today).is_err());
+        assert!(create(&db, &indexer, "Broken".into(), "a {b".into(), now).is_err());
+        assert!(create(&db, &indexer, " ".into(), "body".into(), now).is_err());
+
+        let token = CancellationToken::new();
+        let history = indexer.search("invoice", 10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert!(!history[0].id.starts_with(SNIPPET_ID_PREFIX));
+        let found = search(&db, &indexer, "invoice", 10, &token).unwrap();
+        assert_eq!(found, std::slice::from_ref(&reply));
+
+        indexer.clear().unwrap();
+        index_all(&db, &indexer).unwrap();
+        indexer.commit().unwrap();
+        assert_eq!(
+            search(&db, &indexer, "invoice", 10, &token).unwrap().len(),
+            1
+        );
+
+        delete(&db, &indexer, &reply.snippet_id).unwrap();
+        assert!(search(&db, &indexer, "invoice", 10, &token)
+            .unwrap()
+            .is_empty());
+        assert!(delete(&db, &indexer, &reply.snippet_id).is_err());
+    }
+}
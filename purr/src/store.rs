@@ -1,19 +1,57 @@
 //! ClipboardStore - Thin UniFFI-facing facade over search/save services.
 
+use crate::activity;
+use crate::app_metadata;
+use crate::backup;
+use crate::bundle::{self, VerifiedBundle};
 use crate::database::Database;
-use crate::indexer::{IndexInspection, Indexer};
+use crate::database_import::{self, SourceDatabase};
+use crate::disk_space::{DiskSpaceMonitor, DiskTransition, LOW_DISK_RETENTION_RATIO};
+use crate::encryption;
+use crate::exclusions;
+use crate::history_export;
+use crate::import_preview::{ImportClassifier, ImportDisposition};
+use crate::indexer::{IndexInspection, Indexer, SNIPPET_ID_PREFIX};
 use crate::interface::{
-    ClipKittyError, ClipboardItem, ClipboardStoreApi, FilePreviewSnapshot, ItemQueryFilter,
-    ItemTag, ListPresentationProfile, MatchedExcerptRequest, MatchedExcerptResolution,
-    PreviewPayload, SearchOutcome, SearchResult, StoreBootstrapPlan,
+    ActivityHeatmap, AppMetadata, AppPastePriors, AppliedTransform, BackupInfo,
+    BundleExportSummary, BundleImportSummary, ClipKittyError, ClipboardContent, ClipboardItem,
+    ClipboardStoreApi, CollectedMatches, CompactReport, CopyOccurrence, DailyDigest,
+    DatabaseImportSummary, DetectedEntity, DocumentInfo, FileArchiveListing, FileDocumentInfo,
+    FileEmailInfo, FileMatch, FileMediaInfo, FilePreviewSnapshot, FormattedStructure,
+    HistoryExportFormat, HistoryExportSummary, ImageGridPage, ImportPreview, ImportStrategy,
+    IndexCommitPolicy, IndexRebuildObserver, IndexRepairReport, IntegrityReport,
+    InternalErrorRecord, ItemDiff, ItemMatch, ItemQueryFilter, ItemReminder, ItemTag,
+    ListPresentationProfile, MaintenanceReport, MatchedExcerptPatch, MatchedExcerptRequest,
+    MatchedExcerptResolution, MediaInfo, PackedSearchOutcome, PackedSearchResult, PrefEntry,
+    PrefValue, PreviewPayload, QueryBuilder, QuickList, RecentPaste, RetentionPolicy,
+    SearchOutcome, SearchPage, SearchResult, SearchSection, SearchSectionLimits, SearchSource,
+    SectionedSearchResult, SmartCollection, SmartCollectionPage, SmartCollectionRule,
+    SmartCollectionSummary, Snippet, SnippetTemplate, SnippetVariable, SourceAppSummary,
+    StorageWarning, StoreBootstrapPlan, StoreMoveProgress, StoreObserver, StructuredStyle,
+    TagSummary, TextInput, TimeRange, TopicPage, TopicSummary, Transformation, TransformedText,
+    TrashedItem, UsageStats,
 };
+use crate::preferences;
+use crate::quick_list::QuickListCache;
+use crate::relocation::{RelocationState, StoreFiles};
+use crate::repeat_copies::RepeatCopyFilter;
 #[cfg(feature = "sync")]
 use crate::sync_bridge::{snapshot_from_stored_item_with_bookmark, RealSyncEmitter, SyncEmitter};
-use crate::{match_presentation, save_service, search_service};
+use crate::synced_folder::{self, SyncedSnapshot};
+use crate::thumbnail_cache::ThumbnailCache;
+use crate::{
+    browse_service, collection_service, context_service, digest, email_info, item_locale,
+    match_collection, match_presentation, media_info, panic_guard, pdf_info, power, relocation,
+    reminders, repeat_copies, retention, save_service, search_service, seed, sensitive_items,
+    session, snippets, structured_data, topics, transform, user_tags,
+};
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Once};
+use std::time::{Duration, Instant};
 use tokio::sync::Notify;
 use tokio_util::sync::CancellationToken;
 
@@ -27,6 +65,18 @@ static FALLBACK_RUNTIME: Lazy<tokio::runtime::Runtime> = Lazy::new(|| {
 
 static RAYON_INIT: Once = Once::new();
 
+/// Items fetched and indexed per step of an index rebuild.
+const REBUILD_BATCH_ITEMS: usize = 500;
+
+/// Key-value slot holding the app's saved UI state.
+const UI_STATE_NAMESPACE: &str = "ui";
+const UI_STATE_KEY: &str = "state";
+/// UI state is a query, filters, and an anchor; anything larger is a bug.
+const MAX_UI_STATE_BYTES: usize = 64 * 1024;
+
+/// Most transforms `last_transforms` returns.
+const LAST_TRANSFORMS_LIMIT: usize = 20;
+
 fn init_rayon() {
     RAYON_INIT.call_once(|| {
         let num_threads = std::thread::available_parallelism()
@@ -56,6 +106,23 @@ pub struct ClipboardStore {
     /// search: beginning a search cancels the previous one by calling cancel() on this
     /// token, so each UI surface must funnel interactive searches through a single owner.
     active_search_token: Arc<Mutex<Option<CancellationToken>>>,
+    /// App the next paste will land in; searches rank with its learned priors.
+    paste_target: Arc<Mutex<Option<String>>>,
+    /// Decorate trigram results from stored index rows instead of SQLite.
+    index_only_search: Arc<AtomicBool>,
+    /// Cache file row thumbnails are handed off through, when enabled.
+    shared_thumbnails: Arc<Mutex<Option<Arc<ThumbnailCache>>>>,
+    /// Menu bar rows, kept until the change sequence moves.
+    quick_list: Arc<QuickListCache>,
+    disk_space: Arc<DiskSpaceMonitor>,
+    /// Merges text copied again within moments into the first copy.
+    repeat_copies: Arc<RepeatCopyFilter>,
+    observer: Arc<Mutex<Option<Arc<dyn StoreObserver>>>>,
+    /// Database and index location; None for in-memory stores.
+    files: Option<StoreFiles>,
+    relocation: Arc<Mutex<RelocationState>>,
+    /// Published snapshot in a synced folder, in sync-safe mode.
+    synced_snapshot: Option<SyncedSnapshot>,
 }
 
 struct SearchCompletionCell {
@@ -98,6 +165,19 @@ impl Drop for SearchOperation {
     }
 }
 
+impl Drop for ClipboardStore {
+    fn drop(&mut self) {
+        if let Some(snapshot) = &self.synced_snapshot {
+            snapshot.release();
+        }
+        if let (RelocationState::Moved { .. }, Some(files)) =
+            (&*self.relocation.lock(), &self.files)
+        {
+            files.remove();
+        }
+    }
+}
+
 impl ClipboardStore {
     #[cfg(test)]
     pub(crate) fn new_in_memory() -> Result<Self, ClipKittyError> {
@@ -114,6 +194,16 @@ impl ClipboardStore {
             #[cfg(feature = "sync")]
             sync_emitter,
             active_search_token: Arc::new(Mutex::new(None)),
+            paste_target: Arc::new(Mutex::new(None)),
+            index_only_search: Arc::new(AtomicBool::new(false)),
+            shared_thumbnails: Arc::new(Mutex::new(None)),
+            quick_list: Arc::new(QuickListCache::default()),
+            disk_space: Arc::new(DiskSpaceMonitor::new(None)),
+            repeat_copies: Arc::new(RepeatCopyFilter::default()),
+            observer: Arc::new(Mutex::new(None)),
+            files: None,
+            relocation: Arc::new(Mutex::new(RelocationState::default())),
+            synced_snapshot: None,
         })
     }
 
@@ -156,25 +246,76 @@ impl ClipboardStore {
     }
 
     fn open_at_path(path: &Path) -> Result<Self, ClipKittyError> {
-        let db = Database::open(path).map_err(ClipKittyError::from)?;
+        Self::open_at_path_with_key(path, None)
+    }
+
+    fn open_at_path_with_key(path: &Path, key: Option<&[u8]>) -> Result<Self, ClipKittyError> {
+        let mut db = Database::open(path).map_err(ClipKittyError::from)?;
+        match key {
+            Some(key) => encryption::unlock(&mut db, key)?,
+            None if encryption::is_encrypted(&db)? => {
+                return Err(ClipKittyError::InvalidInput(
+                    "this store is encrypted; open it with new_encrypted".into(),
+                ));
+            }
+            None => {}
+        }
         let index_path = Self::index_path_for_database(path);
         Self::remove_stale_index_dirs(&index_path);
-        let indexer = Indexer::new(&index_path)?;
+        // The index holds item text in the clear, so an encrypted store keeps
+        // it in memory, filled from the database on every open, and removes
+        // any index an unencrypted past left on disk.
+        let indexer = if key.is_some() {
+            let _ = std::fs::remove_dir_all(&index_path);
+            Indexer::create_in_ram()?
+        } else {
+            Indexer::new(&index_path)?
+        };
+        let store = Self::assemble(db, indexer, path, index_path);
+        if key.is_some() {
+            store.fill_index(&store.indexer, &mut |_, _| {})?;
+        }
+        // A session left open by a crash was never confirmed.
+        if session::started_at(&store.db)?.is_some() {
+            session::discard(&store.db, &store.indexer)?;
+        }
+        Ok(store)
+    }
+
+    /// A store over an opened database and index at `path`.
+    fn assemble(db: Database, indexer: Indexer, path: &Path, index_path: PathBuf) -> Self {
         #[cfg(feature = "sync")]
         let sync_emitter = Arc::new(RealSyncEmitter::new(db.pool().clone()));
 
-        Ok(Self {
+        Self {
             db: Arc::new(db),
             indexer: Arc::new(indexer),
             analysis_cache: Arc::new(match_presentation::HighlightAnalysisCache::default()),
             #[cfg(feature = "sync")]
             sync_emitter,
             active_search_token: Arc::new(Mutex::new(None)),
-        })
+            paste_target: Arc::new(Mutex::new(None)),
+            index_only_search: Arc::new(AtomicBool::new(false)),
+            shared_thumbnails: Arc::new(Mutex::new(None)),
+            quick_list: Arc::new(QuickListCache::default()),
+            disk_space: Arc::new(DiskSpaceMonitor::new(path.parent().map(Path::to_path_buf))),
+            repeat_copies: Arc::new(RepeatCopyFilter::default()),
+            observer: Arc::new(Mutex::new(None)),
+            files: Some(StoreFiles {
+                database: path.to_path_buf(),
+                index: index_path,
+            }),
+            relocation: Arc::new(Mutex::new(RelocationState::default())),
+            synced_snapshot: None,
+        }
     }
 
     fn inspect_bootstrap(path: &Path) -> Result<StoreBootstrapPlan, ClipKittyError> {
         let db = Database::open(path).map_err(ClipKittyError::from)?;
+        // Encrypted stores fill their in-memory index when they open.
+        if encryption::is_encrypted(&db)? {
+            return Ok(StoreBootstrapPlan::Ready);
+        }
         let db_count = db.count_items()?;
         let needs_rebuild = match Indexer::inspect(&Self::index_path_for_database(path))? {
             IndexInspection::Missing => db_count > 0,
@@ -191,69 +332,291 @@ impl ClipboardStore {
         Ok(StoreBootstrapPlan::Ready)
     }
 
-    fn rebuild_index_contents(&self) -> Result<(), ClipKittyError> {
-        let items = self.db.fetch_all_items()?;
-        self.indexer.delete_all_documents()?;
+    fn rebuild_index_reporting(
+        &self,
+        on_progress: &mut dyn FnMut(u64, u64),
+    ) -> Result<(), ClipKittyError> {
+        self.contain("rebuild_index", || {
+            self.rebuild_index_contents(on_progress)?;
+            #[cfg(feature = "sync")]
+            {
+                use purr_sync::store::SyncStore;
+                use purr_sync::types::FLAG_INDEX_DIRTY;
+
+                let sync = SyncStore::new(self.db.pool());
+                sync.clear_index_queue()?;
+                sync.set_dirty_flag(FLAG_INDEX_DIRTY, false)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Rebuild the index beside the live one, which keeps serving searches
+    /// until the rebuilt index swaps in.
+    fn rebuild_index_contents(
+        &self,
+        on_progress: &mut dyn FnMut(u64, u64),
+    ) -> Result<(), ClipKittyError> {
+        let staging = {
+            // Checked under the lock `move_store` takes, so a move cannot
+            // start between the check and the rebuild registering.
+            let relocation = self.relocation.lock();
+            relocation.check()?;
+            self.indexer.begin_rebuild()?
+        };
+        let swapped = match self.fill_index(&staging, on_progress) {
+            Ok(()) => self.indexer.finish_rebuild(staging, |staging, item_ids| {
+                self.reindex_items(staging, &item_ids)
+            }),
+            Err(error) => Err(error),
+        };
+        if swapped.is_err() {
+            self.indexer.abandon_rebuild();
+        }
+        swapped
+    }
+
+    /// Bring `indexer` up to date with the stored state of `item_ids`,
+    /// removing items that no longer exist or are in the trash.
+    fn reindex_items(&self, indexer: &Indexer, item_ids: &[String]) -> Result<(), ClipKittyError> {
+        let (snippet_ids, item_ids): (Vec<String>, Vec<String>) = item_ids
+            .iter()
+            .cloned()
+            .partition(|id| id.starts_with(SNIPPET_ID_PREFIX));
+        snippets::reindex(&self.db, indexer, &snippet_ids)?;
+        let items = self.db.fetch_items_by_item_ids(&item_ids)?;
+        for item_id in &item_ids {
+            match items.iter().find(|item| &item.item_id == item_id) {
+                Some(item) => indexer.add_document_with_row(
+                    &item.item_id,
+                    &item
+                        .file_index_text()
+                        .unwrap_or_else(|| item.text_content().to_string()),
+                    item.timestamp_unix,
+                    &item
+                        .id
+                        .map(|id| self.db.fetch_user_tags(id))
+                        .transpose()?
+                        .unwrap_or_default(),
+                    &Database::indexed_row(item),
+                )?,
+                None => indexer.delete_document(item_id)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Index every stored item into `indexer`, `REBUILD_BATCH_ITEMS` at a
+    /// time, reporting indexed and total item counts after each batch.
+    fn fill_index(
+        &self,
+        indexer: &Indexer,
+        on_progress: &mut dyn FnMut(u64, u64),
+    ) -> Result<(), ClipKittyError> {
+        let row_ids = self.db.fetch_all_item_ids()?;
+        let total = row_ids.len() as u64;
+        let mut tags = self.db.fetch_all_user_tags()?;
+        let mut indexed = 0;
+        on_progress(indexed, total);
         use rayon::prelude::*;
-        let prepared: Vec<_> = items
-            .par_iter()
-            .map(|item| {
-                let text = item
-                    .file_index_text()
-                    .unwrap_or_else(|| item.text_content().to_string());
-                (item.item_id.as_str(), text, item.timestamp_unix)
-            })
-            .collect();
-        for (item_id, text, ts) in prepared {
-            self.indexer.add_document(item_id, &text, ts)?;
+        for batch in row_ids.chunks(REBUILD_BATCH_ITEMS) {
+            let items = self.db.fetch_items_by_ids(batch)?;
+            let prepared: Vec<_> = power::install(|| {
+                items
+                    .par_iter()
+                    .map(|item| {
+                        let text = item
+                            .file_index_text()
+                            .unwrap_or_else(|| item.text_content().to_string());
+                        (item, text, Database::indexed_row(item))
+                    })
+                    .collect()
+            });
+            for (item, text, row) in prepared {
+                let item_tags = tags.remove(&item.item_id).unwrap_or_default();
+                indexer.add_document_with_row(
+                    &item.item_id,
+                    &text,
+                    item.timestamp_unix,
+                    &item_tags,
+                    &row,
+                )?;
+            }
+            indexed += batch.len() as u64;
+            on_progress(indexed, total);
         }
-        self.indexer.commit()?;
+        snippets::index_all(&self.db, indexer)?;
+        indexer.commit()?;
         Ok(())
     }
 
+    /// Sample free space ahead of a write and react to mode changes.
+    /// Returns whether the store is in low-disk mode.
+    fn check_disk_space(&self) -> bool {
+        let transition = self.disk_space.refresh();
+        self.apply_disk_transition(transition);
+        self.disk_space.is_low()
+    }
+
+    fn apply_disk_transition(&self, transition: DiskTransition) {
+        match transition {
+            DiskTransition::Unchanged => {}
+            DiskTransition::BecameLow { available_bytes } => {
+                // Shrink history below its current footprint so upcoming captures
+                // have room. Best-effort: a failed prune must not block the write.
+                let current_size = self.db.database_size().unwrap_or(0);
+                let target_size = (current_size as f64 * LOW_DISK_RETENTION_RATIO) as i64;
+                let _ = ClipboardStoreApi::prune_to_size(self, target_size, 1.0);
+                self.notify_storage_warning(StorageWarning::LowDiskSpace {
+                    available_bytes,
+                    threshold_bytes: self.disk_space.threshold_bytes(),
+                });
+            }
+            DiskTransition::Recovered { available_bytes } => {
+                self.notify_storage_warning(StorageWarning::DiskSpaceRecovered { available_bytes });
+            }
+        }
+    }
+
+    /// Run an FFI entry point, turning a panic into `ClipKittyError::Internal`.
+    /// Refused while the store is being moved or after it has moved.
+    fn contain<T>(
+        &self,
+        operation: &'static str,
+        body: impl FnOnce() -> Result<T, ClipKittyError>,
+    ) -> Result<T, ClipKittyError> {
+        self.relocation.lock().check()?;
+        panic_guard::contain(&self.db, operation, body)
+    }
+
+    /// `contain` for async entry points.
+    async fn contain_async<T>(
+        &self,
+        operation: &'static str,
+        body: impl std::future::Future<Output = Result<T, ClipKittyError>>,
+    ) -> Result<T, ClipKittyError> {
+        self.relocation.lock().check()?;
+        panic_guard::contain_async(&self.db, operation, body).await
+    }
+
+    /// Pass a write result through, telling the observer when the disk is full.
+    fn observe_write<T>(&self, result: Result<T, ClipKittyError>) -> Result<T, ClipKittyError> {
+        if matches!(result, Err(ClipKittyError::StorageFull)) {
+            self.notify_storage_warning(StorageWarning::DiskFull);
+        }
+        result
+    }
+
+    fn notify_storage_warning(&self, warning: StorageWarning) {
+        self.notify(|observer| observer.on_storage_warning(warning));
+    }
+
+    fn notify_item_added(&self, item_id: &str) {
+        self.notify(|observer| observer.on_item_added(item_id.to_string()));
+    }
+
+    fn notify_item_updated(&self, item_id: &str) {
+        self.notify(|observer| observer.on_item_updated(item_id.to_string()));
+    }
+
+    fn notify_items_deleted(&self, item_ids: &[String]) {
+        self.notify(|observer| {
+            for item_id in item_ids {
+                observer.on_item_deleted(item_id.clone());
+            }
+        });
+    }
+
+    /// Call the observer, if one is registered, outside the lock so it can
+    /// replace itself.
+    fn notify(&self, event: impl FnOnce(&dyn StoreObserver)) {
+        let observer = self.observer.lock().clone();
+        if let Some(observer) = observer {
+            event(observer.as_ref());
+        }
+    }
+
+    /// Cancel the in-flight search and register a token for the next one.
+    fn replace_active_search_token(&self) -> CancellationToken {
+        let token = CancellationToken::new();
+        let mut active = self.active_search_token.lock();
+        if let Some(prev) = active.take() {
+            prev.cancel();
+        }
+        *active = Some(token.clone());
+        token
+    }
+
+    fn search_context(
+        &self,
+        token: CancellationToken,
+        presentation: ListPresentationProfile,
+    ) -> search_service::SearchContext {
+        search_service::SearchContext {
+            db: Arc::clone(&self.db),
+            indexer: Arc::clone(&self.indexer),
+            cache: Arc::clone(&self.analysis_cache),
+            runtime: self.runtime_handle(),
+            token,
+            presentation,
+            paste_target: self.paste_target.lock().clone(),
+            index_only: self.index_only_search.load(Ordering::Relaxed),
+        }
+    }
+
     fn begin_search_operation(
         &self,
         query: String,
         filter: ItemQueryFilter,
         presentation: ListPresentationProfile,
     ) -> Arc<SearchOperation> {
-        let token = CancellationToken::new();
+        let token = self.replace_active_search_token();
         let completion = Arc::new(SearchCompletionCell::new());
         let operation = Arc::new(SearchOperation {
             token: token.clone(),
             completion: completion.clone(),
         });
-        {
-            let mut active = self.active_search_token.lock();
-            if let Some(prev) = active.take() {
-                prev.cancel();
-            }
-            *active = Some(token.clone());
-        }
 
         let db = Arc::clone(&self.db);
         let indexer = Arc::clone(&self.indexer);
         let cache = Arc::clone(&self.analysis_cache);
+        let paste_target = self.paste_target.lock().clone();
+        let index_only = self.index_only_search.load(Ordering::Relaxed);
+        let shared_thumbnails = self.shared_thumbnails.lock().clone();
         let runtime = self.runtime_handle();
 
         let runtime_clone = runtime.clone();
         runtime.spawn(async move {
-            let result = search_service::execute_search(
-                search_service::SearchContext {
-                    db,
-                    indexer,
-                    cache,
-                    runtime: runtime_clone,
-                    token: token.clone(),
-                    presentation,
-                },
-                query,
-                filter,
+            // Contained here as well, so a panic still finishes `completion`
+            // instead of leaving `await_result` waiting forever.
+            let breadcrumb_db = Arc::clone(&db);
+            let result = panic_guard::contain_async(
+                &breadcrumb_db,
+                "search",
+                search_service::execute_search(
+                    search_service::SearchContext {
+                        db,
+                        indexer,
+                        cache,
+                        runtime: runtime_clone,
+                        token: token.clone(),
+                        presentation,
+                        paste_target,
+                        index_only,
+                    },
+                    query,
+                    filter,
+                ),
             )
             .await;
 
             let terminal = match result {
-                Ok(result) => Ok(SearchOutcome::Success { result }),
+                Ok(mut result) => {
+                    if let Some(cache) = shared_thumbnails {
+                        cache.share_icons(&mut result.matches);
+                    }
+                    Ok(SearchOutcome::Success { result })
+                }
                 Err(ClipKittyError::Cancelled) => Ok(SearchOutcome::Cancelled),
                 Err(error) => Err(error),
             };
@@ -269,1230 +632,5760 @@ impl ClipboardStore {
     #[uniffi::constructor]
     pub fn new(db_path: String) -> Result<Self, ClipKittyError> {
         init_rayon();
-        Self::open_at_path(&PathBuf::from(db_path))
+        let path = PathBuf::from(db_path);
+        synced_folder::refuse_synced_path(&path)?;
+        Self::open_at_path(&path)
     }
 
-    pub fn rebuild_index(&self) -> Result<(), ClipKittyError> {
-        self.rebuild_index_contents()?;
-        #[cfg(feature = "sync")]
-        {
-            use purr_sync::store::SyncStore;
-            use purr_sync::types::FLAG_INDEX_DIRTY;
+    /// Open a store whose text and image payloads are encrypted at rest with
+    /// `key` (32 bytes): item text and previews, images and thumbnails, and
+    /// recognized text. An unencrypted store at `db_path` is migrated in
+    /// place; a store encrypted with another key is refused.
+    ///
+    /// Link URLs and titles, snippet bodies, and sync events and snapshots
+    /// are sealed as well. The search index is kept in memory instead of next
+    /// to the database and is rebuilt from it on every open. Detected
+    /// entities and file names and previews stay in the clear; hosts that
+    /// need those protected must keep the store's directory on encrypted
+    /// storage.
+    #[uniffi::constructor]
+    pub fn new_encrypted(db_path: String, key: Vec<u8>) -> Result<Self, ClipKittyError> {
+        init_rayon();
+        let path = PathBuf::from(db_path);
+        synced_folder::refuse_synced_path(&path)?;
+        Self::open_at_path_with_key(&path, Some(&key))
+    }
 
-            let sync = SyncStore::new(self.db.pool());
-            sync.clear_index_queue()?;
-            sync.set_dirty_flag(FLAG_INDEX_DIRTY, false)?;
+    /// Open the store at `db_path` for reading while another process, like
+    /// the app, writes it: for helpers such as a Spotlight importer or a
+    /// widget. Neither the database nor the index writer lock is taken, and
+    /// calls that write fail with `ReadOnly`. Searches see the writer's
+    /// changes shortly after it commits them. Encrypted stores, and stores in
+    /// synced folders, are refused.
+    #[uniffi::constructor]
+    pub fn open_read_only(db_path: String) -> Result<Self, ClipKittyError> {
+        init_rayon();
+        let path = PathBuf::from(db_path);
+        synced_folder::refuse_synced_path(&path)?;
+        let db = Database::open_read_only(&path).map_err(ClipKittyError::from)?;
+        if encryption::is_encrypted(&db)? {
+            return Err(ClipKittyError::InvalidInput(
+                "this store is encrypted and can't be opened read-only".into(),
+            ));
         }
-        Ok(())
+        let index_path = Self::index_path_for_database(&path);
+        let indexer = Indexer::open_read_only(&index_path)?;
+        Ok(Self::assemble(db, indexer, &path, index_path))
     }
 
-    pub fn prepare_for_suspend(&self) {
-        if let Some(token) = self.active_search_token.lock().take() {
-            token.cancel();
+    /// Open a store whose history is shared through a synced folder. The live
+    /// database and index stay in `local_directory`; `snapshot_path` only
+    /// receives single-file snapshots from `publish_snapshot`. A newer snapshot
+    /// from another device replaces local history on open, after which
+    /// `inspect_store_bootstrap` on the local database asks for a rebuild.
+    #[uniffi::constructor]
+    pub fn open_sync_safe(
+        snapshot_path: String,
+        local_directory: String,
+    ) -> Result<Self, ClipKittyError> {
+        init_rayon();
+        let snapshot =
+            SyncedSnapshot::acquire(Path::new(&snapshot_path), Path::new(&local_directory))?;
+        let local_database = snapshot.local_database();
+        let opened = snapshot
+            .restore_if_newer(&Self::index_path_for_database(&local_database))
+            .and_then(|_| Self::open_at_path(&local_database));
+        match opened {
+            Ok(mut store) => {
+                store.synced_snapshot = Some(snapshot);
+                Ok(store)
+            }
+            Err(error) => {
+                snapshot.release();
+                Err(error)
+            }
         }
-        let _ = self.indexer.prepare_for_suspend();
-        let _ = self.db.checkpoint_for_suspend();
     }
 
-    pub fn start_search(
+    /// Export every item to a directory bundle (`items.jsonl`, `attachments/`,
+    /// `manifest.json`) that `import_bundle` restores losslessly.
+    pub fn export_bundle(&self, directory: String) -> Result<BundleExportSummary, ClipKittyError> {
+        self.contain("export_bundle", || {
+            bundle::export_bundle(&self.db, Path::new(&directory))
+        })
+    }
+
+    /// Write a single-file backup of the whole store to `dest_path`, which
+    /// must not exist. The database copy is consistent even while other calls
+    /// write; the search index is described in the backup but not copied.
+    pub fn create_backup(&self, dest_path: String) -> Result<BackupInfo, ClipKittyError> {
+        self.contain("create_backup", || {
+            backup::create(&self.db, &self.indexer, Path::new(&dest_path))
+        })
+    }
+
+    /// Replace the store's history with a backup written by `create_backup`,
+    /// once its checksums verify, and rebuild the search index from it.
+    /// Backups of a store encrypted with another key are refused.
+    pub fn restore_backup(&self, src_path: String) -> Result<BackupInfo, ClipKittyError> {
+        self.contain("restore_backup", || {
+            let restored = backup::restore(&self.db, Path::new(&src_path))?;
+            self.rebuild_index()?;
+            Ok(restored)
+        })
+    }
+
+    /// Dry run of `import_bundle`: verify the bundle and report what importing
+    /// it would add, skip, and leave in conflict.
+    pub fn preview_import_bundle(
         &self,
-        query: String,
-        filter: ItemQueryFilter,
-        presentation: ListPresentationProfile,
-    ) -> Arc<SearchOperation> {
-        self.begin_search_operation(query, filter, presentation)
+        directory: String,
+    ) -> Result<ImportPreview, ClipKittyError> {
+        self.contain("preview_import_bundle", || {
+            let bundle = VerifiedBundle::open(Path::new(&directory))?;
+            let mut classifier = ImportClassifier::new(&self.db);
+            bundle.for_each_item(|item, _| classifier.classify(&item).map(|_| ()))?;
+            Ok(classifier.finish())
+        })
     }
 
-    /// Format an excerpt for a given presentation profile.
-    /// Exposed to Swift so optimistic edit updates don't need local truncation rules.
-    pub fn format_excerpt(&self, content: String, presentation: ListPresentationProfile) -> String {
-        crate::search::format_excerpt(&content, presentation)
+    /// Import a bundle written by `export_bundle`, keeping item IDs, timestamps,
+    /// tags, reminders, and binary content. Items already in the store are skipped, and
+    /// the local copy wins when an item ID carries different content.
+    pub fn import_bundle(&self, directory: String) -> Result<BundleImportSummary, ClipKittyError> {
+        self.contain("import_bundle", || {
+            let bundle = VerifiedBundle::open(Path::new(&directory))?;
+            let mut classifier = ImportClassifier::new(&self.db);
+            let mut summary = BundleImportSummary {
+                imported: 0,
+                duplicates: 0,
+                conflicts: 0,
+            };
+            let result = bundle.for_each_item(|mut item, links| {
+                match classifier.classify(&item)? {
+                    ImportDisposition::New => {}
+                    ImportDisposition::Duplicate => {
+                        summary.duplicates += 1;
+                        return Ok(());
+                    }
+                    ImportDisposition::Conflict => {
+                        summary.conflicts += 1;
+                        return Ok(());
+                    }
+                }
+                bundle::insert_imported(&self.db, &self.indexer, &mut item, &links)?;
+                #[cfg(feature = "sync")]
+                self.emit_imported(&item, &links)?;
+                summary.imported += 1;
+                Ok(())
+            });
+            // Whatever was inserted before a failure stays searchable.
+            self.indexer.commit()?;
+            result.map(|()| summary)
+        })
     }
-}
 
-impl ClipboardStore {
-    /// Emit the appropriate sync event for an insert outcome.
-    #[cfg(feature = "sync")]
-    fn emit_for_insert(&self, outcome: &save_service::InsertOutcome) -> Result<(), ClipKittyError> {
-        match outcome {
-            save_service::InsertOutcome::Deduplicated {
-                item_id,
-                touched_at_unix,
-                ..
-            } => {
-                self.sync_emitter
-                    .emit_item_touched(item_id, *touched_at_unix)?;
-            }
-            save_service::InsertOutcome::Inserted { item_id, item, .. } => {
-                let snapshot = crate::sync_bridge::snapshot_from_stored_item(item);
-                self.sync_emitter.emit_item_created(item_id, snapshot)?;
-            }
-        }
-        Ok(())
+    /// Dry run of `import_from`: report what importing the database at
+    /// `db_path` would add, skip, and leave in conflict.
+    pub fn preview_import_from(&self, db_path: String) -> Result<ImportPreview, ClipKittyError> {
+        self.contain("preview_import_from", || {
+            let source = SourceDatabase::open(Path::new(&db_path))?;
+            let mut classifier = ImportClassifier::new(&self.db);
+            source.for_each_item(|item, _| classifier.classify(&item).map(|_| ()))?;
+            Ok(classifier.finish())
+        })
     }
 
-    /// Look up the stable string item_id for a row ID, for use in sync emission.
-    #[cfg(feature = "sync")]
-    fn resolve_item_id(&self, row_id: i64) -> Result<Option<String>, ClipKittyError> {
-        Ok(self.db.fetch_item_id_by_row_id(row_id)?)
+    /// Merge another ClipKitty database, such as an old `ClipKitty.sqlite`
+    /// or a snapshot, into this store. Items are deduplicated by ID and
+    /// content hash and keep their original timestamps, tags, reminders, and
+    /// immutability; `strategy` decides what happens to items the store
+    /// already has.
+    pub fn import_from(
+        &self,
+        db_path: String,
+        strategy: ImportStrategy,
+    ) -> Result<DatabaseImportSummary, ClipKittyError> {
+        self.contain("import_from", || {
+            let result = database_import::import_database(
+                &self.db,
+                &self.indexer,
+                Path::new(&db_path),
+                strategy,
+                None,
+                |event| {
+                    #[cfg(feature = "sync")]
+                    match event {
+                        database_import::ImportEvent::Inserted(item, links) => {
+                            self.emit_imported(item, links)?
+                        }
+                        database_import::ImportEvent::Touched {
+                            item_id,
+                            timestamp_unix,
+                        } => self
+                            .sync_emitter
+                            .emit_item_touched(item_id, timestamp_unix)?,
+                    }
+                    #[cfg(not(feature = "sync"))]
+                    let _ = event;
+                    Ok(())
+                },
+            );
+            // Whatever was inserted before a failure stays searchable.
+            self.indexer.commit()?;
+            result
+        })
     }
-}
 
-impl ClipboardStore {
-    /// Resolve a string item_id to its numeric row ID, returning an error if not found.
-    fn require_row_id(&self, item_id: &str) -> Result<i64, ClipKittyError> {
-        self.db
-            .fetch_row_id_by_item_id(item_id)?
-            .ok_or_else(|| ClipKittyError::InvalidInput(format!("item not found: {item_id}")))
+    /// Publish the current history to the synced folder (sync-safe mode only).
+    pub fn publish_snapshot(&self) -> Result<(), ClipKittyError> {
+        self.contain("publish_snapshot", || match &self.synced_snapshot {
+            Some(snapshot) => snapshot.publish(&self.db),
+            None => Err(ClipKittyError::InvalidInput(
+                "the store was not opened with open_sync_safe".into(),
+            )),
+        })
     }
-}
 
-#[uniffi::export]
-pub fn inspect_store_bootstrap(db_path: String) -> Result<StoreBootstrapPlan, ClipKittyError> {
-    init_rayon();
-    ClipboardStore::inspect_bootstrap(&PathBuf::from(db_path))
-}
+    pub fn rebuild_index(&self) -> Result<(), ClipKittyError> {
+        self.rebuild_index_reporting(&mut |_, _| {})
+    }
 
-#[uniffi::export]
-#[async_trait::async_trait]
-impl ClipboardStoreApi for ClipboardStore {
-    fn database_size(&self) -> i64 {
-        self.db.database_size().unwrap_or(0)
+    /// Rebuild the search index on a background thread, reporting progress
+    /// and completion to `observer`. Searches keep serving the current index
+    /// until the rebuilt one swaps in, so the app can open the store without
+    /// waiting and show progress instead.
+    pub fn rebuild_index_in_background(self: Arc<Self>, observer: Arc<dyn IndexRebuildObserver>) {
+        self.runtime_handle().spawn_blocking(move || {
+            let result = self.rebuild_index_reporting(&mut |indexed, total| {
+                let percent = (indexed * 100).checked_div(total).map_or(100, |p| p as u8);
+                observer.on_rebuild_progress(indexed, total, percent);
+            });
+            observer.on_rebuild_complete(result.err().map(|error| error.to_string()));
+        });
     }
 
-    fn save_text(
+    /// Register (or clear, with None) the observer for store events.
+    pub fn set_observer(
         &self,
-        text: String,
-        source_app: Option<String>,
-        source_app_bundle_id: Option<String>,
-    ) -> Result<String, ClipKittyError> {
-        let outcome = save_service::save_text(
-            &self.db,
-            &self.indexer,
-            text,
-            source_app,
-            source_app_bundle_id,
-        )?;
-        #[cfg(feature = "sync")]
-        self.emit_for_insert(&outcome)?;
-        Ok(outcome.ffi_id())
+        observer: Option<Arc<dyn StoreObserver>>,
+    ) -> Result<(), ClipKittyError> {
+        self.contain("set_observer", || {
+            *self.observer.lock() = observer;
+            Ok(())
+        })
     }
 
-    async fn search(
-        &self,
-        query: String,
-        presentation: ListPresentationProfile,
-    ) -> Result<SearchResult, ClipKittyError> {
-        match self
-            .begin_search_operation(query, ItemQueryFilter::All, presentation)
-            .await_result()
-            .await?
-        {
-            SearchOutcome::Success { result } => Ok(result),
-            SearchOutcome::Cancelled => Err(ClipKittyError::Cancelled),
-        }
+    /// Free space on the store's volume below which image capture pauses and
+    /// history is trimmed. Defaults to 1 GiB.
+    pub fn set_low_disk_threshold(&self, threshold_bytes: u64) -> Result<(), ClipKittyError> {
+        self.contain("set_low_disk_threshold", || {
+            self.disk_space.set_threshold_bytes(threshold_bytes);
+            Ok(())
+        })
     }
 
-    async fn search_filtered(
+    /// How soon after copying a text `save_text` treats the same text as the
+    /// same copy, for apps that write the pasteboard several times per
+    /// selection. Defaults to 500 ms; zero turns this off. Later repeats are
+    /// deduplicated as usual.
+    pub fn set_repeat_copy_window(&self, window_ms: u64) -> Result<(), ClipKittyError> {
+        self.contain("set_repeat_copy_window", || {
+            self.repeat_copies
+                .set_window(Duration::from_millis(window_ms));
+            Ok(())
+        })
+    }
+
+    /// Move the database and index into `new_directory`: copy, verify, then
+    /// swap into place. Returns the new database path. Once the move succeeds
+    /// this store refuses further calls; reopen one at the returned path. The
+    /// old files are removed when this store is released.
+    pub fn move_store(
         &self,
-        query: String,
-        filter: ItemQueryFilter,
-        presentation: ListPresentationProfile,
-    ) -> Result<SearchResult, ClipKittyError> {
-        if filter == ItemQueryFilter::All {
-            return self.search(query, presentation).await;
-        }
-        match self
-            .begin_search_operation(query, filter, presentation)
-            .await_result()
-            .await?
+        new_directory: String,
+        progress: Option<Arc<dyn StoreMoveProgress>>,
+    ) -> Result<String, ClipKittyError> {
+        let Some(files) = &self.files else {
+            return Err(ClipKittyError::InvalidInput(
+                "an in-memory store cannot be moved".into(),
+            ));
+        };
+        let database =
+            Path::new(&new_directory).join(files.database.file_name().unwrap_or_default());
+        let destination = StoreFiles {
+            index: Self::index_path_for_database(&database),
+            database,
+        };
         {
-            SearchOutcome::Success { result } => Ok(result),
-            SearchOutcome::Cancelled => Err(ClipKittyError::Cancelled),
+            let mut relocation = self.relocation.lock();
+            relocation.check()?;
+            if self.indexer.is_rebuilding() {
+                return Err(ClipKittyError::InvalidInput(
+                    "the index is being rebuilt; move the store once it finishes".into(),
+                ));
+            }
+            *relocation = RelocationState::Moving;
+            self.indexer.set_commits_frozen(true);
         }
+        let result = panic_guard::contain(&self.db, "move_store", || {
+            relocation::move_store(
+                &self.db,
+                &self.indexer,
+                files,
+                &destination,
+                progress.as_deref(),
+            )
+        });
+        *self.relocation.lock() = match result {
+            Ok(()) => RelocationState::Moved {
+                database: destination.database.clone(),
+            },
+            Err(_) => {
+                self.indexer.set_commits_frozen(false);
+                RelocationState::Settled
+            }
+        };
+        result.map(|()| destination.database.to_string_lossy().into_owned())
     }
 
-    fn fetch_by_ids(&self, item_ids: Vec<String>) -> Result<Vec<ClipboardItem>, ClipKittyError> {
-        let stored_items = self.db.fetch_items_by_item_ids(&item_ids)?;
-        let mut items: Vec<ClipboardItem> = stored_items
-            .into_iter()
-            .map(|item| item.to_clipboard_item())
-            .collect();
-        let tags_by_id = self.db.get_tags_for_item_ids(&item_ids)?;
-        for item in &mut items {
-            item.item_metadata.tags = tags_by_id
-                .get(&item.item_metadata.item_id)
-                .cloned()
-                .unwrap_or_default();
-        }
-        Ok(items)
+    /// Hint that the device entered or left low-power mode. While it is on,
+    /// search and indexing use fewer threads, index merges and compaction are
+    /// deferred, and result rows past the first are decorated only on demand.
+    pub fn set_power_state(&self, low_power: bool) -> Result<(), ClipKittyError> {
+        self.contain("set_power_state", || {
+            power::set_low_power(low_power);
+            Ok(())
+        })
     }
 
-    fn resolve_matched_excerpts(
-        &self,
-        requests: Vec<MatchedExcerptRequest>,
-    ) -> Result<Vec<MatchedExcerptResolution>, ClipKittyError> {
-        search_service::resolve_matched_excerpts(&self.db, &self.analysis_cache, requests)
+    /// Developer setting: append search, database, and index spans to `path`
+    /// as OTLP/JSON lines until `stop_trace_export` is called.
+    pub fn start_trace_export(&self, path: String) -> Result<(), ClipKittyError> {
+        crate::trace_export::start(Path::new(&path))
     }
 
-    fn load_preview_payload(
-        &self,
-        item_id: String,
-        query: String,
-    ) -> Result<Option<PreviewPayload>, ClipKittyError> {
-        search_service::load_preview_payload(&self.db, &self.analysis_cache, item_id, query)
+    /// Stop trace export and flush the file.
+    pub fn stop_trace_export(&self) -> Result<(), ClipKittyError> {
+        crate::trace_export::stop()
     }
 
-    fn save_files(
+    /// Most recent panics contained at FFI entry points, newest first.
+    pub fn recent_internal_errors(
         &self,
-        paths: Vec<String>,
-        filenames: Vec<String>,
-        file_sizes: Vec<u64>,
-        utis: Vec<String>,
-        bookmark_data_list: Vec<Vec<u8>>,
-        preview_snapshots: Vec<FilePreviewSnapshot>,
-        source_app: Option<String>,
-        source_app_bundle_id: Option<String>,
+        limit: u64,
+    ) -> Result<Vec<InternalErrorRecord>, ClipKittyError> {
+        Ok(self
+            .db
+            .fetch_crash_breadcrumbs(limit as usize)?
+            .into_iter()
+            .map(|row| InternalErrorRecord {
+                occurred_at_unix: row.occurred_at_unix,
+                operation: row.operation,
+                message: row.message,
+                backtrace: row.backtrace,
+            })
+            .collect())
+    }
+
+    pub fn prepare_for_suspend(&self) {
+        if let Some(token) = self.active_search_token.lock().take() {
+            token.cancel();
+        }
+        let _ = self.indexer.prepare_for_suspend();
+        let _ = self.db.checkpoint_for_suspend();
+        if let Some(snapshot) = &self.synced_snapshot {
+            let _ = snapshot.publish(&self.db);
+        }
+    }
+
+    pub fn start_search(
+        &self,
+        query: String,
+        filter: ItemQueryFilter,
+        presentation: ListPresentationProfile,
+    ) -> Result<Arc<SearchOperation>, ClipKittyError> {
+        self.contain("start_search", || {
+            Ok(self.begin_search_operation(query, filter, presentation))
+        })
+    }
+
+    /// Format an excerpt for a given presentation profile.
+    /// Exposed to Swift so optimistic edit updates don't need local truncation rules.
+    pub fn format_excerpt(
+        &self,
+        content: String,
+        presentation: ListPresentationProfile,
     ) -> Result<String, ClipKittyError> {
-        let outcome = save_service::save_files(
-            &self.db,
-            &self.indexer,
-            paths,
-            filenames,
-            file_sizes,
-            utis,
-            bookmark_data_list,
-            preview_snapshots,
-            source_app,
-            source_app_bundle_id,
-        )?;
-        #[cfg(feature = "sync")]
-        self.emit_for_insert(&outcome)?;
-        Ok(outcome.ffi_id())
+        self.contain("format_excerpt", || {
+            Ok(crate::search::format_excerpt(&content, presentation))
+        })
     }
 
-    fn save_file(
+    /// Page through image items for the photo grid, newest first.
+    /// Returns medium thumbnails and dimensions only, never the full image data.
+    pub fn browse_images(
+        &self,
+        cursor: Option<String>,
+        limit: u64,
+    ) -> Result<ImageGridPage, ClipKittyError> {
+        self.contain("browse_images", || {
+            browse_service::browse_images(&self.db, cursor, limit)
+        })
+    }
+
+    /// A JSON, XML, or YAML item pretty-printed or minified for display. The
+    /// stored item keeps the text as it was copied.
+    pub fn format_structured(
+        &self,
+        item_id: String,
+        style: StructuredStyle,
+    ) -> Result<FormattedStructure, ClipKittyError> {
+        self.contain("format_structured", || {
+            let item = self
+                .db
+                .fetch_items_by_item_ids(std::slice::from_ref(&item_id))?
+                .into_iter()
+                .next()
+                .ok_or_else(|| ClipKittyError::InvalidInput(format!("unknown item `{item_id}`")))?;
+            let text = match &item.content {
+                ClipboardContent::Text { value } | ClipboardContent::RichText { value, .. } => {
+                    value.as_str()
+                }
+                _ => "",
+            };
+            structured_data::format_structured(text, style).ok_or_else(|| {
+                ClipKittyError::InvalidInput(format!("item `{item_id}` is not JSON, XML, or YAML"))
+            })
+        })
+    }
+
+    /// An item's text with `transformation` applied, for pasting. The item
+    /// itself is left as copied; with `save_as_new_item` the result is also
+    /// saved to history as a new item. Each use is logged for
+    /// `last_transforms` against the current paste target.
+    pub fn transform(
+        &self,
+        item_id: String,
+        transformation: Transformation,
+        save_as_new_item: bool,
+    ) -> Result<TransformedText, ClipKittyError> {
+        self.contain("transform", || {
+            let item = self
+                .db
+                .fetch_items_by_item_ids(std::slice::from_ref(&item_id))?
+                .into_iter()
+                .next()
+                .ok_or_else(|| ClipKittyError::InvalidInput(format!("unknown item `{item_id}`")))?;
+            let text = transform::apply(&item.content, transformation).map_err(|reason| {
+                ClipKittyError::InvalidInput(format!("item `{item_id}` {reason}"))
+            })?;
+            if let Some(row_id) = item.id {
+                let target = self.paste_target.lock().clone();
+                self.db.insert_transform_event(
+                    row_id,
+                    transformation.to_database_str(),
+                    target.as_deref(),
+                    chrono::Utc::now(),
+                )?;
+            }
+            let saved_item_id = if save_as_new_item {
+                self.check_disk_space();
+                let outcome = self.observe_write(save_service::save_text(
+                    &self.db,
+                    &self.indexer,
+                    text.clone(),
+                    None,
+                    None,
+                ))?;
+                self.record_capture(&outcome)?;
+                match outcome {
+                    save_service::InsertOutcome::Deduplicated { item_id, .. }
+                    | save_service::InsertOutcome::Inserted { item_id, .. } => Some(item_id),
+                }
+            } else {
+                None
+            };
+            Ok(TransformedText {
+                text,
+                saved_item_id,
+            })
+        })
+    }
+
+    /// The latest transforms applied to an item, newest first, each with the
+    /// paste target set at the time, for choosing the default "paste as"
+    /// for an app.
+    pub fn last_transforms(
+        &self,
+        item_id: String,
+    ) -> Result<Vec<AppliedTransform>, ClipKittyError> {
+        self.contain("last_transforms", || {
+            let row_id = self.require_row_id(&item_id)?;
+            self.db
+                .fetch_transform_events(row_id, LAST_TRANSFORMS_LIMIT)?
+                .into_iter()
+                .map(|(transformation, target_bundle_id, applied_at_unix)| {
+                    Ok(AppliedTransform {
+                        transformation: Transformation::from_database_str(&transformation)
+                            .map_err(ClipKittyError::DataInconsistency)?,
+                        target_bundle_id,
+                        applied_at_unix,
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Every item holding the file at `path`, alone or grouped with other
+    /// files, newest first.
+    pub fn items_containing_file(
         &self,
         path: String,
-        filename: String,
-        file_size: u64,
-        uti: String,
-        bookmark_data: Vec<u8>,
-        preview: FilePreviewSnapshot,
-        source_app: Option<String>,
-        source_app_bundle_id: Option<String>,
-    ) -> Result<String, ClipKittyError> {
-        let outcome = save_service::save_file(
-            &self.db,
-            &self.indexer,
-            path,
-            filename,
-            file_size,
-            uti,
-            bookmark_data,
-            preview,
-            source_app,
-            source_app_bundle_id,
-        )?;
-        #[cfg(feature = "sync")]
-        self.emit_for_insert(&outcome)?;
-        Ok(outcome.ffi_id())
+        presentation: ListPresentationProfile,
+    ) -> Result<Vec<ItemMatch>, ClipKittyError> {
+        self.contain("items_containing_file", || {
+            let ids = self.db.fetch_item_ids_with_file(&path)?;
+            browse_service::baseline_matches(&self.db, &ids, presentation)
+        })
     }
 
-    fn save_image(
+    /// The files of a file item that `query` matched, with highlights in
+    /// each filename and path. Empty for other kinds of item.
+    pub fn matched_files(
         &self,
-        image_data: Vec<u8>,
-        thumbnail: Option<Vec<u8>>,
-        source_app: Option<String>,
-        source_app_bundle_id: Option<String>,
-        is_animated: bool,
-    ) -> Result<String, ClipKittyError> {
-        let outcome = save_service::save_image(
-            &self.db,
-            &self.indexer,
-            image_data,
-            thumbnail,
-            source_app,
-            source_app_bundle_id,
-            is_animated,
-        )?;
-        #[cfg(feature = "sync")]
-        self.emit_for_insert(&outcome)?;
-        Ok(outcome.ffi_id())
+        item_id: String,
+        query: String,
+    ) -> Result<Vec<FileMatch>, ClipKittyError> {
+        self.contain("matched_files", || {
+            search_service::matched_files(&self.db, &item_id, &query)
+        })
     }
 
-    fn update_link_metadata(
+    /// Record media info the app read for the file at `file_index` of a file
+    /// item, replacing any read at capture. A `frame_grab` image becomes the
+    /// file's preview and, for the first file, the item's list thumbnail.
+    pub fn set_media_info(
         &self,
         item_id: String,
-        title: Option<String>,
-        description: Option<String>,
-        image_data: Option<Vec<u8>>,
+        file_index: u64,
+        info: MediaInfo,
+        frame_grab: Option<Vec<u8>>,
     ) -> Result<(), ClipKittyError> {
-        let row_id = self.require_row_id(&item_id)?;
-        #[allow(unused_variables)]
-        let resolved =
-            save_service::update_link_metadata(&self.db, row_id, title, description, image_data)?;
-        #[cfg(feature = "sync")]
-        {
-            let snapshot = crate::sync_bridge::link_metadata_snapshot(&resolved);
-            self.sync_emitter
-                .emit_link_metadata_updated(&item_id, snapshot)?;
-        }
-        Ok(())
+        self.contain("set_media_info", || {
+            let row_id = self.require_row_id(&item_id)?;
+            if !self
+                .db
+                .set_file_media_info(row_id, file_index, &info, frame_grab.as_deref())?
+            {
+                return Err(ClipKittyError::InvalidInput(format!(
+                    "item `{item_id}` has no file at index {file_index}"
+                )));
+            }
+            self.notify_item_updated(&item_id);
+            Ok(())
+        })
     }
 
-    fn update_image_description(
+    /// Media info of each audio or video file in a file item, for its
+    /// metadata rows. Empty for other kinds of item.
+    pub fn media_info(&self, item_id: String) -> Result<Vec<FileMediaInfo>, ClipKittyError> {
+        self.contain("media_info", || {
+            let row_id = self.require_row_id(&item_id)?;
+            Ok(self
+                .db
+                .fetch_file_media_info(row_id)?
+                .into_iter()
+                .map(|(file_index, info)| FileMediaInfo {
+                    file_index,
+                    summary: media_info::summary(&info),
+                    info,
+                })
+                .collect())
+        })
+    }
+
+    /// Record document info the app read for the PDF at `file_index` of a
+    /// file item, replacing any read at capture, and index its title and
+    /// author. A `first_page` render becomes the file's preview and, for the
+    /// first file, the item's list thumbnail.
+    pub fn set_document_info(
         &self,
         item_id: String,
-        description: String,
+        file_index: u64,
+        info: DocumentInfo,
+        first_page: Option<Vec<u8>>,
     ) -> Result<(), ClipKittyError> {
-        let row_id = self.require_row_id(&item_id)?;
-        // Bake in the "Image: " label once, up front, so the sync event and the
-        // local store record the identical prefixed description across devices.
-        let description = crate::interface::format_image_description(&description);
-        #[cfg(feature = "sync")]
-        self.sync_emitter
-            .emit_image_description_updated(&item_id, &description)?;
-
-        #[allow(unused_variables)]
-        let reindex =
-            save_service::update_image_description(&self.db, &self.indexer, row_id, description)?;
+        self.contain("set_document_info", || {
+            let row_id = self.require_row_id(&item_id)?;
+            if !self
+                .db
+                .set_file_document_info(row_id, file_index, &info, first_page.as_deref())?
+            {
+                return Err(ClipKittyError::InvalidInput(format!(
+                    "item `{item_id}` has no file at index {file_index}"
+                )));
+            }
+            self.reindex_items(&self.indexer, std::slice::from_ref(&item_id))?;
+            self.indexer.commit()?;
+            self.notify_item_updated(&item_id);
+            Ok(())
+        })
+    }
 
-        #[cfg(feature = "sync")]
-        if matches!(reindex, save_service::ReindexOutcome::IndexFailed) {
-            let _ = self.sync_emitter.set_index_dirty();
-        }
-        Ok(())
+    /// Page count, title, and author of each PDF in a file item, for its
+    /// metadata rows. Empty for other kinds of item.
+    pub fn document_info(&self, item_id: String) -> Result<Vec<FileDocumentInfo>, ClipKittyError> {
+        self.contain("document_info", || {
+            let row_id = self.require_row_id(&item_id)?;
+            Ok(self
+                .db
+                .fetch_file_document_info(row_id)?
+                .into_iter()
+                .map(|(file_index, info)| FileDocumentInfo {
+                    file_index,
+                    summary: pdf_info::summary(&info),
+                    info,
+                })
+                .collect())
+        })
     }
 
-    fn update_text_item(&self, item_id: String, text: String) -> Result<(), ClipKittyError> {
-        let row_id = self.require_row_id(&item_id)?;
-        #[cfg(feature = "sync")]
-        self.sync_emitter.emit_text_edited(&item_id, &text)?;
+    /// Subject, sender, and send time of each exported email in a file item,
+    /// for its metadata rows. Empty for other kinds of item.
+    pub fn email_info(&self, item_id: String) -> Result<Vec<FileEmailInfo>, ClipKittyError> {
+        self.contain("email_info", || {
+            let row_id = self.require_row_id(&item_id)?;
+            Ok(self
+                .db
+                .fetch_file_email_info(row_id)?
+                .into_iter()
+                .map(|(file_index, info)| FileEmailInfo {
+                    file_index,
+                    summary: email_info::summary(&info),
+                    info,
+                })
+                .collect())
+        })
+    }
 
-        #[allow(unused_variables)]
-        let reindex = save_service::update_text_item(&self.db, &self.indexer, row_id, text)?;
+    /// Entry names of each zip or tar archive in a file item, for answering
+    /// which archive held a file. Empty for other kinds of item.
+    pub fn archive_listings(
+        &self,
+        item_id: String,
+    ) -> Result<Vec<FileArchiveListing>, ClipKittyError> {
+        self.contain("archive_listings", || {
+            let row_id = self.require_row_id(&item_id)?;
+            Ok(self
+                .db
+                .fetch_file_archive_listings(row_id)?
+                .into_iter()
+                .map(|(file_index, listing)| FileArchiveListing {
+                    file_index,
+                    listing,
+                })
+                .collect())
+        })
+    }
 
-        #[cfg(feature = "sync")]
-        if matches!(reindex, save_service::ReindexOutcome::IndexFailed) {
-            let _ = self.sync_emitter.set_index_dirty();
-        }
-        Ok(())
+    /// Line and word diff from the first text item to the second.
+    pub fn diff_items(&self, id_a: String, id_b: String) -> Result<ItemDiff, ClipKittyError> {
+        self.contain("diff_items", || {
+            let text_of = |item_id: &String| -> Result<String, ClipKittyError> {
+                let item = self
+                    .db
+                    .fetch_items_by_item_ids(std::slice::from_ref(item_id))?
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| {
+                        ClipKittyError::InvalidInput(format!("unknown item `{item_id}`"))
+                    })?;
+                match item.content {
+                    ClipboardContent::Text { value }
+                    | ClipboardContent::Color { value }
+                    | ClipboardContent::PhoneNumber { value }
+                    | ClipboardContent::PostalAddress { value }
+                    | ClipboardContent::RichText { value, .. } => Ok(value),
+                    ClipboardContent::Markdown { source, .. } => Ok(source),
+                    ClipboardContent::Link { url, .. } => Ok(url),
+                    ClipboardContent::Image { .. } | ClipboardContent::File { .. } => {
+                        Err(ClipKittyError::InvalidInput(format!(
+                            "item `{item_id}` has no text to diff"
+                        )))
+                    }
+                }
+            };
+            Ok(crate::diff::diff_texts(&text_of(&id_a)?, &text_of(&id_b)?))
+        })
     }
 
-    fn update_timestamp(&self, item_id: String) -> Result<(), ClipKittyError> {
-        let row_id = self.require_row_id(&item_id)?;
-        #[allow(unused_variables)]
-        let timestamp_unix = match save_service::update_timestamp(&self.db, &self.indexer, row_id)?
-        {
-            save_service::TouchOutcome::Indexed { timestamp_unix } => timestamp_unix,
-            save_service::TouchOutcome::IndexFailed { timestamp_unix } => {
-                #[cfg(feature = "sync")]
-                let _ = self.sync_emitter.set_index_dirty();
-                timestamp_unix
+    /// Join the text of items, in the order given, with `separator` between
+    /// them, and save the result to history as a new item. Returns its id, or
+    /// the id of an existing item with the same text.
+    pub fn merge_items(
+        &self,
+        item_ids: Vec<String>,
+        separator: String,
+    ) -> Result<String, ClipKittyError> {
+        self.contain("merge_items", || {
+            if item_ids.len() < 2 {
+                return Err(ClipKittyError::InvalidInput(
+                    "merging needs at least two items".into(),
+                ));
             }
-        };
+            // Keyed by id so an item listed twice is joined twice.
+            let items: HashMap<String, ClipboardContent> = self
+                .db
+                .fetch_items_by_item_ids(&item_ids)?
+                .into_iter()
+                .map(|item| (item.item_id, item.content))
+                .collect();
+            let texts = item_ids
+                .iter()
+                .map(|item_id| match items.get(item_id) {
+                    None => Err(ClipKittyError::InvalidInput(format!(
+                        "unknown item `{item_id}`"
+                    ))),
+                    Some(
+                        ClipboardContent::Text { value }
+                        | ClipboardContent::Color { value }
+                        | ClipboardContent::PhoneNumber { value }
+                        | ClipboardContent::PostalAddress { value }
+                        | ClipboardContent::RichText { value, .. },
+                    ) => Ok(value.as_str()),
+                    Some(ClipboardContent::Markdown { source, .. }) => Ok(source.as_str()),
+                    Some(ClipboardContent::Link { url, .. }) => Ok(url.as_str()),
+                    Some(ClipboardContent::Image { .. } | ClipboardContent::File { .. }) => {
+                        Err(ClipKittyError::InvalidInput(format!(
+                            "item `{item_id}` has no text to merge"
+                        )))
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
 
-        #[cfg(feature = "sync")]
-        self.sync_emitter
-            .emit_item_touched(&item_id, timestamp_unix)?;
-        Ok(())
+            self.check_disk_space();
+            let outcome = self.observe_write(save_service::save_text(
+                &self.db,
+                &self.indexer,
+                texts.join(&separator),
+                None,
+                None,
+            ))?;
+            self.record_capture(&outcome)?;
+            match outcome {
+                save_service::InsertOutcome::Deduplicated { item_id, .. }
+                | save_service::InsertOutcome::Inserted { item_id, .. } => Ok(item_id),
+            }
+        })
     }
 
-    fn add_tag(&self, item_id: String, tag: ItemTag) -> Result<(), ClipKittyError> {
-        let row_id = self.require_row_id(&item_id)?;
-        #[cfg(feature = "sync")]
-        self.sync_emitter.emit_bookmark_set(&item_id)?;
+    /// CSV of per-day copy and paste counts by app and content type, with no
+    /// item content. Days follow the device's current time zone.
+    pub fn export_activity_csv(&self, range: TimeRange) -> Result<String, ClipKittyError> {
+        self.contain("export_activity_csv", || {
+            let utc_offset_seconds = chrono::Local::now().offset().local_minus_utc();
+            activity::export_activity_csv(&self.db, &range, utc_offset_seconds)
+        })
+    }
 
-        save_service::add_tag(&self.db, row_id, tag)
+    /// Usage totals by content type, source app, and day (the last 30 days
+    /// in the device's current time zone), plus the most pasted items for a
+    /// "most reused" view. Pastes are those logged with `record_paste`.
+    pub fn get_stats(&self) -> Result<UsageStats, ClipKittyError> {
+        self.contain("get_stats", || {
+            let now = chrono::Local::now();
+            activity::usage_stats(&self.db, now.to_utc(), now.offset().local_minus_utc())
+        })
     }
 
-    fn remove_tag(&self, item_id: String, tag: ItemTag) -> Result<(), ClipKittyError> {
-        let row_id = self.require_row_id(&item_id)?;
-        #[cfg(feature = "sync")]
-        self.sync_emitter.emit_bookmark_cleared(&item_id)?;
+    /// Day × hour copy counts for the last `weeks` weeks (at most a year),
+    /// ending today, in the device's current time zone.
+    pub fn activity_heatmap(&self, weeks: u32) -> Result<ActivityHeatmap, ClipKittyError> {
+        self.contain("activity_heatmap", || {
+            let now = chrono::Local::now();
+            activity::activity_heatmap(
+                &self.db,
+                weeks,
+                now.to_utc(),
+                now.offset().local_minus_utc(),
+            )
+        })
+    }
 
-        save_service::remove_tag(&self.db, row_id, tag)
+    /// Recap of the items copied on `date` (`YYYY-MM-DD`) in the device's
+    /// current time zone, for an end-of-day notification.
+    pub fn daily_digest(&self, date: String) -> Result<DailyDigest, ClipKittyError> {
+        self.contain("daily_digest", || {
+            let utc_offset_seconds = chrono::Local::now().offset().local_minus_utc();
+            digest::daily_digest(&self.db, &date, utc_offset_seconds)
+        })
     }
 
-    fn delete_item(&self, item_id: String) -> Result<(), ClipKittyError> {
-        let row_id = self.require_row_id(&item_id)?;
-        #[cfg(feature = "sync")]
-        self.sync_emitter.emit_item_deleted(&item_id)?;
+    /// Replace the bundle IDs whose clipboard content is never saved. Saves
+    /// from these apps return an empty ID, as duplicates do.
+    pub fn set_excluded_bundle_ids(&self, bundle_ids: Vec<String>) -> Result<(), ClipKittyError> {
+        self.contain("set_excluded_bundle_ids", || {
+            exclusions::set(&self.db, bundle_ids)
+        })
+    }
 
-        save_service::delete_item(&self.db, &self.indexer, row_id)
+    pub fn get_excluded_bundle_ids(&self) -> Result<Vec<String>, ClipKittyError> {
+        self.contain("get_excluded_bundle_ids", || exclusions::get(&self.db))
     }
 
-    fn clear(&self) -> Result<(), ClipKittyError> {
-        #[cfg(feature = "sync")]
-        for row_id in self.db.fetch_all_item_ids()? {
-            if let Some(stable_id) = self.resolve_item_id(row_id)? {
-                self.sync_emitter.emit_item_deleted(&stable_id)?;
+    /// Keep text that looks like a credential (API keys, tokens, private
+    /// keys) for `ttl_seconds` after it was last copied, or None to keep it
+    /// like any other item. `expire_sensitive_items` does the deleting.
+    pub fn set_sensitive_item_ttl(&self, ttl_seconds: Option<u64>) -> Result<(), ClipKittyError> {
+        self.contain("set_sensitive_item_ttl", || {
+            sensitive_items::set_ttl(&self.db, ttl_seconds)
+        })
+    }
+
+    pub fn sensitive_item_ttl(&self) -> Result<Option<u64>, ClipKittyError> {
+        self.contain("sensitive_item_ttl", || sensitive_items::ttl(&self.db))
+    }
+
+    /// Delete sensitive items whose time is up, except pinned and immutable
+    /// ones. Returns how many were deleted.
+    pub fn expire_sensitive_items(&self) -> Result<u64, ClipKittyError> {
+        self.contain("expire_sensitive_items", || {
+            let deleted_ids = sensitive_items::expire(&self.db, &self.indexer, chrono::Utc::now())?;
+            #[cfg(feature = "sync")]
+            for item_id in &deleted_ids {
+                self.sync_emitter.emit_item_deleted(item_id)?;
             }
-        }
+            self.notify_items_deleted(&deleted_ids);
+            Ok(deleted_ids.len() as u64)
+        })
+    }
 
-        save_service::clear(&self.db, &self.indexer)
+    /// Replace the retention policy. `run_maintenance` enforces it.
+    pub fn set_retention_policy(&self, policy: RetentionPolicy) -> Result<(), ClipKittyError> {
+        self.contain("set_retention_policy", || {
+            retention::set_policy(&self.db, policy)
+        })
     }
 
-    fn prune_to_size(&self, max_bytes: i64, keep_ratio: f64) -> Result<u64, ClipKittyError> {
-        let outcome = save_service::prune_to_size(&self.db, &self.indexer, max_bytes, keep_ratio)?;
+    pub fn retention_policy(&self) -> Result<RetentionPolicy, ClipKittyError> {
+        self.contain("retention_policy", || retention::policy(&self.db))
+    }
 
-        #[cfg(feature = "sync")]
-        for item_id in &outcome.deleted_ids {
-            self.sync_emitter.emit_item_deleted(item_id)?;
-        }
+    /// Delete what the retention policy no longer keeps and items trashed
+    /// more than 30 days ago, then compact the database, delete the search index files it no longer uses, and bring
+    /// topics up to date. Slow; call it when the app is idle.
+    pub fn run_maintenance(&self) -> Result<MaintenanceReport, ClipKittyError> {
+        self.contain("run_maintenance", || {
+            let deleted_ids = retention::enforce(&self.db, &self.indexer, chrono::Utc::now())?;
+            #[cfg(feature = "sync")]
+            for item_id in &deleted_ids {
+                self.sync_emitter.emit_item_deleted(item_id)?;
+            }
+            self.notify_items_deleted(&deleted_ids);
+            let purged_ids = self
+                .db
+                .purge_trash(chrono::Utc::now() - retention::TRASH_MAX_AGE)?;
+            let size_before = self.db.database_size()?;
+            self.db.compact()?;
+            let bytes_reclaimed = size_before.saturating_sub(self.db.database_size()?).max(0);
+            self.indexer.collect_garbage()?;
+            topics::refresh(&self.db)?;
+            Ok(MaintenanceReport {
+                items_deleted: (deleted_ids.len() + purged_ids.len()) as u64,
+                bytes_reclaimed: bytes_reclaimed as u64,
+            })
+        })
+    }
 
-        Ok(outcome.bytes_freed)
+    /// Check the database file and item content for damage without changing
+    /// anything. Reads every page; call it when the app is idle.
+    pub fn run_integrity_check(&self) -> Result<IntegrityReport, ClipKittyError> {
+        self.contain("run_integrity_check", || {
+            let (orphaned_link_rows, orphaned_image_rows) =
+                self.db.count_orphaned_content_rows()?;
+            Ok(IntegrityReport {
+                problems: self.db.integrity_problems()?,
+                corrupted_item_ids: self.db.fetch_item_ids_missing_content()?,
+                orphaned_link_rows,
+                orphaned_image_rows,
+            })
+        })
     }
-}
 
-// ═══════════════════════════════════════════════════════════════════════════════
-// Sync internals — not exposed via FFI
-// ═══════════════════════════════════════════════════════════════════════════════
+    /// Delete orphaned link and image rows, rewrite the database file
+    /// without free pages, and merge the search index into one segment.
+    /// Unlike `run_maintenance`, deletes no items. Slow; call it when the
+    /// app is idle.
+    pub fn compact(&self) -> Result<CompactReport, ClipKittyError> {
+        self.contain("compact", || {
+            let database_bytes_before = self.db.database_size()?.max(0) as u64;
+            let index_bytes_before = self.indexer.disk_size()?;
+            let orphaned_rows_deleted = self.db.delete_orphaned_content_rows()?;
+            self.db.compact()?;
+            self.indexer.merge_segments()?;
+            Ok(CompactReport {
+                database_bytes_before,
+                database_bytes_after: self.db.database_size()?.max(0) as u64,
+                index_bytes_before,
+                index_bytes_after: self.indexer.disk_size()?,
+                orphaned_rows_deleted,
+            })
+        })
+    }
 
-#[cfg(feature = "sync")]
-impl ClipboardStore {
-    fn materialize_forked_snapshot(
-        &self,
-        snapshot: &purr_sync::types::ItemSnapshotData,
-    ) -> Result<String, ClipKittyError> {
-        use crate::sync_bridge::stored_item_from_snapshot;
+    /// Cross-check item IDs between the database and the search index,
+    /// removing documents whose item is gone or in the trash and indexing
+    /// items the index lacks. Cheaper than `rebuild_index` when only a few
+    /// items disagree.
+    pub fn verify_index(&self) -> Result<IndexRepairReport, ClipKittyError> {
+        self.contain("verify_index", || {
+            // Reads see the last commit; writes a deferred commit holds back
+            // would otherwise look missing.
+            self.indexer.flush()?;
+            let indexed = self.indexer.indexed_item_ids()?;
+            let mut expected: HashSet<String> =
+                self.db.fetch_live_item_ids()?.into_iter().collect();
+            expected.extend(snippets::document_ids(&self.db)?);
+            let ghosts: Vec<String> = indexed.difference(&expected).cloned().collect();
+            let missing: Vec<String> = expected.difference(&indexed).cloned().collect();
+            if !ghosts.is_empty() || !missing.is_empty() {
+                tracing::warn!(
+                    ghosts = ghosts.len(),
+                    missing = missing.len(),
+                    "search index disagreed with the database"
+                );
+                self.reindex_items(&self.indexer, &[ghosts.as_slice(), &missing].concat())?;
+                self.indexer.commit()?;
+            }
+            Ok(IndexRepairReport {
+                ghost_documents_removed: ghosts.len() as u64,
+                missing_documents_added: missing.len() as u64,
+            })
+        })
+    }
 
-        let fork_item_id = uuid::Uuid::new_v4().to_string();
-        let item = stored_item_from_snapshot(fork_item_id.clone(), snapshot)
-            .map_err(ClipKittyError::InvalidInput)?;
-        self.queue_search_upsert(&fork_item_id)?;
-        let row_id = self.db.insert_item(&item)?;
-        if snapshot.is_bookmarked {
-            self.db
-                .add_tag(row_id, crate::interface::ItemTag::Bookmark)?;
-        }
-        self.sync_emitter
-            .emit_item_created(&fork_item_id, snapshot.clone())?;
-        Ok(fork_item_id)
+    /// Take a deleted item back out of the trash.
+    ///
+    /// Its deletion has already synced, so it comes back under a new item
+    /// ID, which is returned; its external ID is kept.
+    pub fn restore_item(&self, item_id: String) -> Result<String, ClipKittyError> {
+        self.contain("restore_item", || {
+            let row_id = match self.db.fetch_row_id_by_item_id(&item_id)? {
+                Some(row_id) if self.db.is_trashed(row_id)? => row_id,
+                _ => {
+                    return Err(ClipKittyError::InvalidInput(format!(
+                        "item not in trash: {item_id}"
+                    )))
+                }
+            };
+            let new_item_id = uuid::Uuid::new_v4().to_string();
+            #[allow(unused_variables)]
+            let reindex =
+                save_service::restore_item(&self.db, &self.indexer, row_id, &new_item_id)?;
+            #[cfg(feature = "sync")]
+            {
+                if let Some(item) = self.db.fetch_items_by_ids(&[row_id])?.into_iter().next() {
+                    let item_ids = std::slice::from_ref(&new_item_id);
+                    let links = crate::database::ItemLinks {
+                        tags: self
+                            .db
+                            .get_tags_for_item_ids(item_ids)?
+                            .remove(&new_item_id)
+                            .unwrap_or_default(),
+                        reminder_at_unix: self
+                            .db
+                            .fetch_reminders_for_item_ids(item_ids)?
+                            .remove(&new_item_id),
+                        ..Default::default()
+                    };
+                    self.emit_imported(&item, &links)?;
+                }
+                if matches!(reindex, save_service::ReindexOutcome::IndexFailed) {
+                    let _ = self.sync_emitter.set_index_dirty();
+                }
+            }
+            self.notify_item_added(&new_item_id);
+            Ok(new_item_id)
+        })
     }
 
-    fn queue_search_upsert(&self, item_id: &str) -> Result<(), ClipKittyError> {
-        use purr_sync::store::SyncStore;
-        use purr_sync::types::FLAG_INDEX_DIRTY;
+    /// The first `limit` rows of history for the menu bar, pinned first.
+    /// Served from memory until the change sequence moves, so it is cheap
+    /// to call every time the menu opens.
+    pub fn quick_list(&self, limit: u32) -> Result<QuickList, ClipKittyError> {
+        self.contain("quick_list", || {
+            self.quick_list
+                .list(&self.db, limit as usize, chrono::Utc::now())
+        })
+    }
 
-        let sync = SyncStore::new(self.db.pool());
-        sync.enqueue_index_upsert(item_id)?;
-        sync.set_dirty_flag(FLAG_INDEX_DIRTY, true)?;
-        Ok(())
+    /// Counter that moves whenever history changes.
+    pub fn change_sequence(&self) -> Result<u64, ClipKittyError> {
+        self.contain("change_sequence", || Ok(self.db.change_sequence()?))
     }
 
-    fn queue_search_delete(&self, item_id: &str) -> Result<(), ClipKittyError> {
-        use purr_sync::store::SyncStore;
-        use purr_sync::types::FLAG_INDEX_DIRTY;
+    /// Items in the trash, most recently deleted first.
+    pub fn trashed_items(&self) -> Result<Vec<TrashedItem>, ClipKittyError> {
+        self.contain("trashed_items", || {
+            let trashed = self.db.fetch_trashed_items()?;
+            let row_ids: Vec<i64> = trashed.iter().map(|(row_id, _)| *row_id).collect();
+            let items = self.db.fetch_items_by_ids(&row_ids)?;
+            Ok(trashed
+                .into_iter()
+                .filter_map(|(row_id, deleted_at_unix)| {
+                    let item = items.iter().find(|item| item.id == Some(row_id))?;
+                    Some(TrashedItem {
+                        item: item.to_clipboard_item(),
+                        deleted_at_unix,
+                    })
+                })
+                .collect())
+        })
+    }
 
-        let sync = SyncStore::new(self.db.pool());
-        sync.enqueue_index_delete(item_id)?;
-        sync.set_dirty_flag(FLAG_INDEX_DIRTY, true)?;
-        Ok(())
+    /// Permanently delete items that have been in the trash for at least
+    /// `older_than_seconds`; 0 empties it. Returns how many were deleted.
+    pub fn empty_trash(&self, older_than_seconds: u64) -> Result<u64, ClipKittyError> {
+        self.contain("empty_trash", || {
+            // An age past what a timestamp can express leaves nothing old enough.
+            let Some(cutoff) = i64::try_from(older_than_seconds)
+                .ok()
+                .and_then(chrono::Duration::try_seconds)
+                .and_then(|age| chrono::Utc::now().checked_sub_signed(age))
+            else {
+                return Ok(0);
+            };
+            Ok(self.db.purge_trash(cutoff)?.len() as u64)
+        })
     }
 
-    /// Materialize a sync aggregate into the local items table.
-    ///
-    /// For Live aggregates: upsert into items table (insert if new, update if exists).
-    /// For Tombstoned aggregates: delete from items table.
-    /// Returns the current local row ID when the item remains live.
-    fn materialize_aggregate(
-        &self,
-        item_id: &str,
-        aggregate: &purr_sync::types::ItemAggregate,
-        index_dirty: bool,
-        fallback_local_item_id: Option<i64>,
-    ) -> Result<Option<i64>, ClipKittyError> {
-        use crate::sync_bridge::stored_item_from_snapshot;
-        use purr_sync::store::{ProjectionState, SyncStore};
-        use purr_sync::types::ItemAggregate;
-
-        let sync = SyncStore::new(self.db.pool());
-
-        // Resolve local row ID: look up by item_id in the items table,
-        // falling back to the caller-provided hint.
-        let local_item_id = self
-            .db
-            .fetch_row_id_by_item_id(item_id)?
-            .or(fallback_local_item_id);
-
-        match aggregate {
-            ItemAggregate::Live(live) => {
-                let item = stored_item_from_snapshot(item_id.to_string(), &live.snapshot)
-                    .map_err(ClipKittyError::InvalidInput)?;
+    /// Start a temporary session: captures from now on show up in searches
+    /// but stay out of history, and out of sync, until `commit_session`.
+    /// `discard_session` undoes them instead, as does relaunching with the
+    /// session still open.
+    pub fn begin_session(&self) -> Result<(), ClipKittyError> {
+        self.contain("begin_session", || {
+            session::begin(&self.db, chrono::Utc::now())
+        })
+    }
 
-                if index_dirty {
-                    self.queue_search_upsert(item_id)?;
-                }
+    pub fn is_session_open(&self) -> Result<bool, ClipKittyError> {
+        self.contain("is_session_open", || {
+            Ok(session::started_at(&self.db)?.is_some())
+        })
+    }
 
-                if let Some(local_id) = local_item_id {
-                    self.db.replace_item_preserving_id(local_id, &item)?;
-                    if live.snapshot.is_bookmarked {
-                        self.db
-                            .add_tag(local_id, crate::interface::ItemTag::Bookmark)?;
+    /// Keep the session's captures in history. Returns how many there were.
+    pub fn commit_session(&self) -> Result<u64, ClipKittyError> {
+        self.contain("commit_session", || {
+            let captures = session::commit(&self.db)?;
+            #[cfg(feature = "sync")]
+            {
+                let row_ids: Vec<i64> = captures.iter().map(|(row_id, _)| *row_id).collect();
+                for item in self.db.fetch_items_by_ids(&row_ids)? {
+                    let previous_at = captures
+                        .iter()
+                        .find(|(row_id, _)| item.id == Some(*row_id))
+                        .and_then(|(_, previous_at)| *previous_at);
+                    if previous_at.is_some() {
+                        self.sync_emitter
+                            .emit_item_touched(&item.item_id, item.timestamp_unix)?;
                     } else {
-                        self.db
-                            .remove_tag(local_id, crate::interface::ItemTag::Bookmark)?;
+                        let snapshot = crate::sync_bridge::snapshot_from_stored_item(&item);
+                        self.sync_emitter
+                            .emit_item_created(&item.item_id, snapshot)?;
                     }
-                    sync.upsert_projection(
-                        item_id,
-                        &ProjectionState::Materialized {
-                            versions: live.versions,
-                        },
-                    )?;
-                    return Ok(Some(local_id));
                 }
+            }
+            Ok(captures.len() as u64)
+        })
+    }
 
-                // No existing local item — insert fresh.
-                let new_id = self.db.insert_item(&item)?;
-                if live.snapshot.is_bookmarked {
-                    self.db
-                        .add_tag(new_id, crate::interface::ItemTag::Bookmark)?;
-                }
-                sync.upsert_projection(
-                    item_id,
-                    &ProjectionState::Materialized {
-                        versions: live.versions,
-                    },
-                )?;
-                Ok(Some(new_id))
+    /// Undo the session's captures: items it added are deleted and items it
+    /// copied again go back to where they were. Returns how many items were
+    /// deleted.
+    pub fn discard_session(&self) -> Result<u64, ClipKittyError> {
+        self.contain("discard_session", || {
+            let deleted_ids = session::discard(&self.db, &self.indexer)?;
+            self.notify_items_deleted(&deleted_ids);
+            Ok(deleted_ids.len() as u64)
+        })
+    }
+
+    /// Opaque ID for `clipkitty://` links, Spotlight entries, and other
+    /// references from outside the store. Unlike row IDs it survives
+    /// restores, imports, and sync.
+    pub fn external_id(&self, item_id: String) -> Result<String, ClipKittyError> {
+        self.contain("external_id", || {
+            let row_id = self.require_row_id(&item_id)?;
+            Ok(self.db.fetch_external_id(row_id)?)
+        })
+    }
+
+    /// The item ID behind an external ID, or None once the item is gone.
+    pub fn resolve_external_id(
+        &self,
+        external_id: String,
+    ) -> Result<Option<String>, ClipKittyError> {
+        self.contain("resolve_external_id", || {
+            Ok(self.db.fetch_item_id_by_external_id(&external_id)?)
+        })
+    }
+
+    /// Resurface the item at `fire_at_unix`, replacing any reminder it has.
+    pub fn set_reminder(&self, item_id: String, fire_at_unix: i64) -> Result<(), ClipKittyError> {
+        self.contain("set_reminder", || {
+            let row_id = self.require_row_id(&item_id)?;
+            reminders::set(&self.db, row_id, fire_at_unix)?;
+            #[cfg(feature = "sync")]
+            self.sync_emitter
+                .emit_reminder_set(&item_id, fire_at_unix)?;
+            Ok(())
+        })
+    }
+
+    pub fn clear_reminder(&self, item_id: String) -> Result<(), ClipKittyError> {
+        self.contain("clear_reminder", || {
+            let row_id = self.require_row_id(&item_id)?;
+            #[allow(unused_variables)]
+            let cleared = reminders::clear(&self.db, row_id)?;
+            #[cfg(feature = "sync")]
+            if cleared {
+                self.sync_emitter.emit_reminder_cleared(&item_id)?;
             }
-            ItemAggregate::Tombstoned(tomb) => {
-                if index_dirty {
-                    self.queue_search_delete(item_id)?;
-                }
-                if let Some(local_id) = local_item_id {
-                    self.db.delete_item(local_id)?;
-                }
-                sync.upsert_projection(
-                    item_id,
-                    &ProjectionState::Tombstoned {
-                        versions: tomb.versions,
-                    },
-                )?;
-                Ok(None)
+            Ok(())
+        })
+    }
+
+    /// Reminders due at `now_unix`, earliest first. They stay due until
+    /// cleared, so the host clears each one after showing it.
+    pub fn due_reminders(&self, now_unix: i64) -> Result<Vec<ItemReminder>, ClipKittyError> {
+        self.contain("due_reminders", || reminders::due(&self.db, now_unix))
+    }
+
+    /// Persist the app's UI state (query, filters, selection, scroll anchor)
+    /// as an opaque blob, replacing the previous one.
+    pub fn save_ui_state(&self, blob: Vec<u8>) -> Result<(), ClipKittyError> {
+        self.contain("save_ui_state", || {
+            if blob.len() > MAX_UI_STATE_BYTES {
+                return Err(ClipKittyError::InvalidInput(format!(
+                    "UI state is {} bytes; the limit is {MAX_UI_STATE_BYTES}",
+                    blob.len()
+                )));
             }
-        }
+            Ok(self
+                .db
+                .put_key_value(UI_STATE_NAMESPACE, UI_STATE_KEY, &blob)?)
+        })
     }
 
-    /// Re-materialize the current sync snapshot for an item into the read model.
-    ///
-    /// This lets duplicate/already-applied remote records heal the read model if
-    /// sync state was stored successfully but a prior materialization failed.
-    fn materialize_current_sync_state(
+    /// The blob last passed to `save_ui_state`, if any.
+    pub fn load_ui_state(&self) -> Result<Option<Vec<u8>>, ClipKittyError> {
+        self.contain("load_ui_state", || {
+            Ok(self.db.get_key_value(UI_STATE_NAMESPACE, UI_STATE_KEY)?)
+        })
+    }
+
+    /// Store a preference under `namespace`, replacing any previous value.
+    /// Preferences live in the database, so they travel with it.
+    pub fn set_pref(
         &self,
-        item_id: &str,
-        index_dirty: bool,
-        fallback_local_item_id: Option<i64>,
+        namespace: String,
+        key: String,
+        value: PrefValue,
+    ) -> Result<(), ClipKittyError> {
+        self.contain("set_pref", || {
+            preferences::set(&self.db, &namespace, &key, &value)
+        })
+    }
+
+    pub fn get_pref(
+        &self,
+        namespace: String,
+        key: String,
+    ) -> Result<Option<PrefValue>, ClipKittyError> {
+        self.contain("get_pref", || preferences::get(&self.db, &namespace, &key))
+    }
+
+    pub fn remove_pref(&self, namespace: String, key: String) -> Result<(), ClipKittyError> {
+        self.contain("remove_pref", || {
+            preferences::remove(&self.db, &namespace, &key)
+        })
+    }
+
+    /// Every preference in `namespace`, sorted by key.
+    pub fn list_prefs(&self, namespace: String) -> Result<Vec<PrefEntry>, ClipKittyError> {
+        self.contain("list_prefs", || preferences::list(&self.db, &namespace))
+    }
+
+    /// `get_pref` for a bool; fails if the stored value has another type.
+    pub fn get_bool_pref(
+        &self,
+        namespace: String,
+        key: String,
+    ) -> Result<Option<bool>, ClipKittyError> {
+        self.contain("get_bool_pref", || {
+            match preferences::get(&self.db, &namespace, &key)? {
+                None => Ok(None),
+                Some(PrefValue::Bool { value }) => Ok(Some(value)),
+                Some(other) => Err(preferences::type_mismatch(&key, "bool", &other)),
+            }
+        })
+    }
+
+    /// `get_pref` for an int; fails if the stored value has another type.
+    pub fn get_int_pref(
+        &self,
+        namespace: String,
+        key: String,
     ) -> Result<Option<i64>, ClipKittyError> {
-        use purr_sync::store::SyncStore;
+        self.contain("get_int_pref", || {
+            match preferences::get(&self.db, &namespace, &key)? {
+                None => Ok(None),
+                Some(PrefValue::Int { value }) => Ok(Some(value)),
+                Some(other) => Err(preferences::type_mismatch(&key, "int", &other)),
+            }
+        })
+    }
 
-        let sync = SyncStore::new(self.db.pool());
-        let Some(_) = sync.fetch_projection(item_id)? else {
-            return Ok(self
+    /// `get_pref` for a double; ints widen. Fails for other types.
+    pub fn get_double_pref(
+        &self,
+        namespace: String,
+        key: String,
+    ) -> Result<Option<f64>, ClipKittyError> {
+        self.contain("get_double_pref", || {
+            match preferences::get(&self.db, &namespace, &key)? {
+                None => Ok(None),
+                Some(PrefValue::Double { value }) => Ok(Some(value)),
+                Some(PrefValue::Int { value }) => Ok(Some(value as f64)),
+                Some(other) => Err(preferences::type_mismatch(&key, "double", &other)),
+            }
+        })
+    }
+
+    /// `get_pref` for text; fails if the stored value has another type.
+    pub fn get_string_pref(
+        &self,
+        namespace: String,
+        key: String,
+    ) -> Result<Option<String>, ClipKittyError> {
+        self.contain("get_string_pref", || {
+            match preferences::get(&self.db, &namespace, &key)? {
+                None => Ok(None),
+                Some(PrefValue::Text { value }) => Ok(Some(value)),
+                Some(other) => Err(preferences::type_mismatch(&key, "text", &other)),
+            }
+        })
+    }
+
+    /// Register display metadata for source apps, replacing earlier entries
+    /// for the same bundle IDs. Item lists show the registered name, so
+    /// re-registering after an app is renamed updates existing items.
+    pub fn register_app_metadata(&self, apps: Vec<AppMetadata>) -> Result<(), ClipKittyError> {
+        self.contain("register_app_metadata", || {
+            app_metadata::register(&self.db, &apps)
+        })
+    }
+
+    pub fn app_metadata(&self, bundle_id: String) -> Result<Option<AppMetadata>, ClipKittyError> {
+        self.contain("app_metadata", || {
+            Ok(self.db.fetch_app_metadata(&bundle_id)?)
+        })
+    }
+
+    /// Every app that items were copied from, with item counts, most items
+    /// first.
+    pub fn list_source_apps(&self) -> Result<Vec<SourceAppSummary>, ClipKittyError> {
+        self.contain("list_source_apps", || {
+            app_metadata::list_source_apps(&self.db)
+        })
+    }
+
+    /// Tag an item with a user tag. Names are case-insensitive and may be
+    /// given with a leading `#`; a query word `#name` then restricts search
+    /// to tagged items.
+    pub fn add_user_tag(&self, item_id: String, tag: String) -> Result<(), ClipKittyError> {
+        self.contain("add_user_tag", || {
+            let row_id = self.require_row_id(&item_id)?;
+            #[allow(unused_variables)]
+            let reindex = user_tags::add(&self.db, &self.indexer, row_id, &tag)?;
+            #[cfg(feature = "sync")]
+            if matches!(reindex, save_service::ReindexOutcome::IndexFailed) {
+                let _ = self.sync_emitter.set_index_dirty();
+            }
+            self.notify_item_updated(&item_id);
+            Ok(())
+        })
+    }
+
+    pub fn remove_user_tag(&self, item_id: String, tag: String) -> Result<(), ClipKittyError> {
+        self.contain("remove_user_tag", || {
+            let row_id = self.require_row_id(&item_id)?;
+            #[allow(unused_variables)]
+            let reindex = user_tags::remove(&self.db, &self.indexer, row_id, &tag)?;
+            #[cfg(feature = "sync")]
+            if matches!(reindex, save_service::ReindexOutcome::IndexFailed) {
+                let _ = self.sync_emitter.set_index_dirty();
+            }
+            self.notify_item_updated(&item_id);
+            Ok(())
+        })
+    }
+
+    /// Every user tag in use with its item count, sorted by name.
+    pub fn list_tags(&self) -> Result<Vec<TagSummary>, ClipKittyError> {
+        self.contain("list_tags", || user_tags::list(&self.db))
+    }
+
+    /// Pin an item so it lists before unpinned items and is never pruned.
+    pub fn pin_item(&self, item_id: String) -> Result<(), ClipKittyError> {
+        self.contain("pin_item", || {
+            let row_id = self.require_row_id(&item_id)?;
+            self.db.set_pinned(row_id, true)?;
+            self.notify_item_updated(&item_id);
+            Ok(())
+        })
+    }
+
+    pub fn unpin_item(&self, item_id: String) -> Result<(), ClipKittyError> {
+        self.contain("unpin_item", || {
+            let row_id = self.require_row_id(&item_id)?;
+            self.db.set_pinned(row_id, false)?;
+            self.notify_item_updated(&item_id);
+            Ok(())
+        })
+    }
+
+    /// Mark an item immutable (or clear the mark). While set, the item can't
+    /// be deleted or edited and is skipped by clearing and pruning. The mark
+    /// is local to this device; sync still applies remote changes.
+    pub fn set_immutable(&self, item_id: String, immutable: bool) -> Result<(), ClipKittyError> {
+        self.contain("set_immutable", || {
+            let row_id = self.require_row_id(&item_id)?;
+            Ok(self.db.set_immutable(row_id, immutable)?)
+        })
+    }
+
+    pub fn is_immutable(&self, item_id: String) -> Result<bool, ClipKittyError> {
+        self.contain("is_immutable", || {
+            let row_id = self.require_row_id(&item_id)?;
+            Ok(self.db.is_immutable(row_id)?)
+        })
+    }
+
+    /// IDs of every immutable item, newest first.
+    pub fn immutable_item_ids(&self) -> Result<Vec<String>, ClipKittyError> {
+        self.contain("immutable_item_ids", || {
+            Ok(self
                 .db
-                .fetch_row_id_by_item_id(item_id)?
-                .or(fallback_local_item_id));
-        };
-        let snapshot = sync.fetch_snapshot(item_id)?.ok_or_else(|| {
-            ClipKittyError::DataInconsistency(format!(
-                "sync projection for item `{item_id}` is missing snapshot state"
+                .fetch_immutable_ids()?
+                .into_iter()
+                .map(|(_, item_id)| item_id)
+                .collect())
+        })
+    }
+
+    /// A short description of the item for VoiceOver, e.g. "Image copied
+    /// from Safari two hours ago, keywords: wave, ocean, Hokusai".
+    pub fn accessibility_description(&self, item_id: String) -> Result<String, ClipKittyError> {
+        self.contain("accessibility_description", || {
+            let item = self
+                .db
+                .fetch_items_by_item_ids(std::slice::from_ref(&item_id))?
+                .into_iter()
+                .next()
+                .ok_or_else(|| ClipKittyError::InvalidInput(format!("unknown item `{item_id}`")))?;
+            Ok(crate::accessibility::describe(
+                &item,
+                chrono::Utc::now().timestamp(),
             ))
-        })?;
+        })
+    }
 
-        self.materialize_aggregate(
-            item_id,
-            &snapshot.aggregate,
-            index_dirty,
-            fallback_local_item_id,
-        )
+    /// URLs, email addresses, colors, phone numbers, file paths, and IP
+    /// addresses found in a text item, in source order, so the UI can offer
+    /// to copy just one of them. Other kinds of item have none.
+    pub fn item_entities(&self, item_id: String) -> Result<Vec<DetectedEntity>, ClipKittyError> {
+        self.contain("item_entities", || {
+            let row_id = self.require_row_id(&item_id)?;
+            Ok(self.db.fetch_entities(row_id)?)
+        })
     }
 
-    fn local_snapshots_for_full_resync(
-        &self,
-    ) -> Result<Vec<purr_sync::snapshot::ItemSnapshot>, ClipKittyError> {
-        use purr_sync::snapshot::ItemSnapshot;
-        use purr_sync::store::SyncStore;
-        use purr_sync::types::{ItemAggregate, LiveItemState, VersionVector, SYNC_SCHEMA_VERSION};
+    /// Turn a text item into a snippet template, replacing emails, dates,
+    /// numbers, and UUIDs with named placeholders.
+    pub fn templatize(&self, item_id: String) -> Result<SnippetTemplate, ClipKittyError> {
+        self.contain("templatize", || {
+            let item = self
+                .db
+                .fetch_items_by_item_ids(std::slice::from_ref(&item_id))?
+                .into_iter()
+                .next()
+                .ok_or_else(|| ClipKittyError::InvalidInput(format!("unknown item `{item_id}`")))?;
+            match item.content {
+                ClipboardContent::Text { value } | ClipboardContent::RichText { value, .. } => {
+                    Ok(crate::template::templatize_text(&value))
+                }
+                ClipboardContent::Markdown { source, .. } => {
+                    Ok(crate::template::templatize_text(&source))
+                }
+                ClipboardContent::Link { url, .. } => Ok(crate::template::templatize_text(&url)),
+                _ => Err(ClipKittyError::InvalidInput(format!(
+                    "item `{item_id}` is not text"
+                ))),
+            }
+        })
+    }
 
-        let items = self.db.fetch_all_items()?;
-        let item_ids: Vec<String> = items.iter().map(|item| item.item_id.clone()).collect();
-        let tags_by_item_id = self.db.get_tags_for_item_ids(&item_ids)?;
-        let sync = SyncStore::new(self.db.pool());
+    /// Fill history with sample items in `locale`'s language, for
+    /// onboarding and screenshots. Items from an earlier call are replaced.
+    /// Returns the IDs of the items added.
+    pub fn seed_demo_data(&self, locale: String) -> Result<Vec<String>, ClipKittyError> {
+        self.contain("seed_demo_data", || {
+            seed::seed(&self.db, &self.indexer, &locale, chrono::Utc::now())
+        })
+    }
 
-        items
-            .into_iter()
-            .map(|item| {
-                let item_id = item.item_id.clone();
-                let existing_snapshot = sync.fetch_snapshot(&item_id)?;
-                let is_bookmarked = tags_by_item_id
-                    .get(&item_id)
-                    .map(|tags| tags.contains(&ItemTag::Bookmark))
-                    .unwrap_or(false);
+    /// Tag an item with the locale it was written for, or clear the tag.
+    /// Browse and search hide tagged items from readers of other locales.
+    pub fn set_item_locale(
+        &self,
+        item_id: String,
+        locale: Option<String>,
+    ) -> Result<(), ClipKittyError> {
+        self.contain("set_item_locale", || {
+            let row_id = self.require_row_id(&item_id)?;
+            let locale = locale.as_deref().and_then(item_locale::normalize);
+            self.db.set_item_locale(row_id, locale.as_deref())?;
+            Ok(())
+        })
+    }
 
-                let mut versions = match existing_snapshot
-                    .as_ref()
-                    .map(|snapshot| &snapshot.aggregate)
-                {
-                    Some(ItemAggregate::Live(live)) => live.versions,
-                    Some(ItemAggregate::Tombstoned(_)) | None => VersionVector {
-                        content: 1,
-                        bookmark: if is_bookmarked { 1 } else { 0 },
-                        existence: 1,
-                        touch: 1,
-                        metadata: 1,
-                    },
-                };
-                if is_bookmarked && versions.bookmark == 0 {
-                    versions.bookmark = 1;
-                }
+    /// Set or clear the locale browse and search show tagged items for.
+    /// Items without a locale always show.
+    pub fn set_preferred_locale(&self, locale: Option<String>) -> Result<(), ClipKittyError> {
+        self.contain("set_preferred_locale", || {
+            item_locale::set_preferred(&self.db, locale.as_deref())
+        })
+    }
 
-                let aggregate = ItemAggregate::Live(LiveItemState {
-                    snapshot: snapshot_from_stored_item_with_bookmark(&item, is_bookmarked),
-                    versions,
-                });
-                let snapshot = ItemSnapshot {
-                    item_id: item_id.clone(),
-                    snapshot_revision: existing_snapshot
-                        .as_ref()
-                        .map(|snapshot| snapshot.snapshot_revision.max(1))
-                        .unwrap_or(1),
-                    schema_version: SYNC_SCHEMA_VERSION,
-                    covers_through_event: existing_snapshot
-                        .and_then(|snapshot| snapshot.covers_through_event),
-                    aggregate,
-                    uploaded: false,
-                    uploaded_at: None,
+    pub fn preferred_locale(&self) -> Result<Option<String>, ClipKittyError> {
+        self.contain("preferred_locale", || item_locale::preferred(&self.db))
+    }
+
+    /// Save a snippet. The body may use `{name}` placeholders, with literal
+    /// braces doubled, as `templatize` produces.
+    pub fn create_snippet(&self, title: String, body: String) -> Result<Snippet, ClipKittyError> {
+        self.contain("create_snippet", || {
+            snippets::create(&self.db, &self.indexer, title, body, chrono::Utc::now())
+        })
+    }
+
+    /// All saved snippets, ordered by title.
+    pub fn list_snippets(&self) -> Result<Vec<Snippet>, ClipKittyError> {
+        self.contain("list_snippets", || snippets::list(&self.db))
+    }
+
+    pub fn delete_snippet(&self, snippet_id: String) -> Result<(), ClipKittyError> {
+        self.contain("delete_snippet", || {
+            snippets::delete(&self.db, &self.indexer, &snippet_id)
+        })
+    }
+
+    /// A snippet's body with its placeholders filled from `variables`.
+    /// Without a value, `{date}` becomes today's local date and
+    /// `{clipboard}` the newest text in history.
+    pub fn expand_snippet(
+        &self,
+        snippet_id: String,
+        variables: Vec<SnippetVariable>,
+    ) -> Result<String, ClipKittyError> {
+        self.contain("expand_snippet", || {
+            snippets::expand(
+                &self.db,
+                &snippet_id,
+                &variables,
+                chrono::Local::now().date_naive(),
+            )
+        })
+    }
+
+    /// Log that an item was pasted into an app (and optionally a document,
+    /// such as a window title) so later suggestions can favour it there.
+    pub fn record_paste(
+        &self,
+        item_id: String,
+        target_bundle_id: String,
+        document_hint: Option<String>,
+    ) -> Result<(), ClipKittyError> {
+        self.contain("record_paste", || {
+            let row_id = self.require_row_id(&item_id)?;
+            context_service::record_paste(
+                &self.db,
+                row_id,
+                &target_bundle_id,
+                document_hint.as_deref(),
+            )
+        })
+    }
+
+    /// Items ordered by when they were last pasted (see `record_paste`),
+    /// as opposed to when they were copied.
+    pub fn recent_pastes(&self, limit: u64) -> Result<Vec<RecentPaste>, ClipKittyError> {
+        self.contain("recent_pastes", || {
+            context_service::recent_pastes(&self.db, limit as usize)
+        })
+    }
+
+    /// Every time an item was copied, oldest first. Copying content that is
+    /// already in history adds an occurrence instead of a new item.
+    pub fn get_copy_history(&self, item_id: String) -> Result<Vec<CopyOccurrence>, ClipKittyError> {
+        self.contain("get_copy_history", || {
+            let row_id = self.require_row_id(&item_id)?;
+            Ok(self
+                .db
+                .fetch_copy_history(row_id)?
+                .into_iter()
+                .map(|event| CopyOccurrence {
+                    copied_at_unix: event.copied_at_unix,
+                    source_app: event.source_app,
+                    source_app_bundle_id: event.source_app_bundle_id,
+                })
+                .collect())
+        })
+    }
+
+    /// Suggest history for the frontmost app, boosting items previously pasted
+    /// into or copied from it and the content kinds it usually receives.
+    pub fn suggest_for_context(
+        &self,
+        bundle_id: String,
+        document_hint: Option<String>,
+        limit: u64,
+    ) -> Result<Vec<ItemMatch>, ClipKittyError> {
+        self.contain("suggest_for_context", || {
+            context_service::suggest_for_context(
+                &self.db,
+                &bundle_id,
+                document_hint.as_deref(),
+                limit as usize,
+            )
+        })
+    }
+
+    /// Items copied or pasted repeatedly in the last `window_days`, most used
+    /// first, for a frequent-items section above recents. Pastes weigh more
+    /// than copies, and older uses count less.
+    pub fn frequently_used(
+        &self,
+        limit: u64,
+        window_days: u32,
+    ) -> Result<Vec<ItemMatch>, ClipKittyError> {
+        self.contain("frequently_used", || {
+            context_service::frequently_used(&self.db, limit as usize, window_days)
+        })
+    }
+
+    /// Set the app results will be pasted into, or None to search without
+    /// personalization. Applies to searches started afterwards.
+    pub fn set_paste_target(&self, bundle_id: Option<String>) {
+        *self.paste_target.lock() = bundle_id.filter(|id| !id.trim().is_empty());
+    }
+
+    /// Opt into index-only search for very large histories. Trigram queries
+    /// then render rows from fields stored in the search index and read
+    /// SQLite only for the first result's preview and for items indexed
+    /// before the fields existed. Rows come back without tags, pin state, or
+    /// registered app names; fetch those for the selected item. Applies to
+    /// searches started afterwards.
+    pub fn set_index_only_search(&self, enabled: bool) {
+        self.index_only_search.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Defer search index commits so rapid copies don't each pay for one.
+    /// Writes lost to a crash before their commit are recovered by the
+    /// index rebuild `inspect_store_bootstrap` asks for on the next launch.
+    pub fn set_index_commit_policy(&self, policy: IndexCommitPolicy) -> Result<(), ClipKittyError> {
+        self.contain("set_index_commit_policy", || {
+            Ok(self.indexer.set_commit_policy(policy)?)
+        })
+    }
+
+    /// Commit index writes held back by the commit policy. Call on shutdown.
+    pub fn flush_index(&self) -> Result<(), ClipKittyError> {
+        self.contain("flush_index", || Ok(self.indexer.flush()?))
+    }
+
+    /// Hand row thumbnails to the app through the file at `cache_path`
+    /// instead of copying their bytes: search results then carry
+    /// `ItemIcon::SharedThumbnail` ranges into that file, which the app maps
+    /// read-only. The file is truncated here and only grows afterwards.
+    /// Applies to searches started afterwards.
+    pub fn enable_shared_thumbnails(&self, cache_path: String) -> Result<(), ClipKittyError> {
+        self.contain("enable_shared_thumbnails", || {
+            let cache = ThumbnailCache::open(Path::new(&cache_path))?;
+            *self.shared_thumbnails.lock() = Some(Arc::new(cache));
+            Ok(())
+        })
+    }
+
+    /// Go back to copying thumbnail bytes into search results.
+    pub fn disable_shared_thumbnails(&self) {
+        *self.shared_thumbnails.lock() = None;
+    }
+
+    /// Inspect the per-app ranking priors learned from recorded pastes.
+    pub fn paste_priors(&self) -> Result<Vec<AppPastePriors>, ClipKittyError> {
+        self.contain("paste_priors", || {
+            context_service::list_priors(&self.db, None)
+        })
+    }
+
+    /// Forget learned ranking priors for one app, or for all apps when None.
+    pub fn reset_paste_priors(
+        &self,
+        target_bundle_id: Option<String>,
+    ) -> Result<(), ClipKittyError> {
+        self.contain("reset_paste_priors", || {
+            context_service::reset_priors(&self.db, target_bundle_id.as_deref())
+        })
+    }
+
+    /// Create a smart collection: a named rule set resolved each time it is browsed.
+    pub fn create_smart_collection(
+        &self,
+        name: String,
+        rules: Vec<SmartCollectionRule>,
+    ) -> Result<SmartCollection, ClipKittyError> {
+        self.contain("create_smart_collection", || {
+            collection_service::create_collection(&self.db, name, rules)
+        })
+    }
+
+    /// Replace the name and rules of an existing smart collection.
+    pub fn update_smart_collection(
+        &self,
+        collection_id: String,
+        name: String,
+        rules: Vec<SmartCollectionRule>,
+    ) -> Result<SmartCollection, ClipKittyError> {
+        self.contain("update_smart_collection", || {
+            collection_service::update_collection(&self.db, collection_id, name, rules)
+        })
+    }
+
+    pub fn delete_smart_collection(&self, collection_id: String) -> Result<(), ClipKittyError> {
+        self.contain("delete_smart_collection", || {
+            collection_service::delete_collection(&self.db, &collection_id)
+        })
+    }
+
+    /// List smart collections in creation order with their current item counts.
+    pub fn list_smart_collections(&self) -> Result<Vec<SmartCollectionSummary>, ClipKittyError> {
+        self.contain("list_smart_collections", || {
+            collection_service::list_collections(&self.db)
+        })
+    }
+
+    /// Browse a smart collection's current members, newest first.
+    pub fn browse_smart_collection(
+        &self,
+        collection_id: String,
+        offset: u64,
+        limit: u64,
+        presentation: ListPresentationProfile,
+    ) -> Result<SmartCollectionPage, ClipKittyError> {
+        self.contain("browse_smart_collection", || {
+            collection_service::browse_collection(
+                &self.db,
+                &collection_id,
+                offset,
+                limit,
+                presentation,
+            )
+        })
+    }
+
+    /// Topics history has been grouped into, largest first. `run_maintenance`
+    /// keeps them current.
+    pub fn list_topics(&self) -> Result<Vec<TopicSummary>, ClipKittyError> {
+        self.contain("list_topics", || topics::list_topics(&self.db))
+    }
+
+    /// Browse a topic's items, newest first.
+    pub fn items_in_topic(
+        &self,
+        topic_id: String,
+        offset: u64,
+        limit: u64,
+        presentation: ListPresentationProfile,
+    ) -> Result<TopicPage, ClipKittyError> {
+        self.contain("items_in_topic", || {
+            topics::browse_topic(&self.db, &topic_id, offset, limit, presentation)
+        })
+    }
+}
+
+impl ClipboardStore {
+    /// Emit the appropriate sync event for an insert outcome.
+    /// Hold a capture in the open session, or publish it to sync.
+    fn record_capture(&self, outcome: &save_service::InsertOutcome) -> Result<(), ClipKittyError> {
+        match outcome {
+            save_service::InsertOutcome::Inserted { item_id, .. } => {
+                self.notify_item_added(item_id)
+            }
+            save_service::InsertOutcome::Deduplicated { item_id, .. } => {
+                self.notify_item_updated(item_id)
+            }
+        }
+        if session::record(&self.db, outcome)? {
+            return Ok(());
+        }
+        #[cfg(feature = "sync")]
+        self.emit_for_insert(outcome)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "sync")]
+    fn emit_for_insert(&self, outcome: &save_service::InsertOutcome) -> Result<(), ClipKittyError> {
+        match outcome {
+            save_service::InsertOutcome::Deduplicated {
+                item_id,
+                touched_at_unix,
+                ..
+            } => {
+                self.sync_emitter
+                    .emit_item_touched(item_id, *touched_at_unix)?;
+            }
+            save_service::InsertOutcome::Inserted { item_id, item, .. } => {
+                let snapshot = crate::sync_bridge::snapshot_from_stored_item(item);
+                self.sync_emitter.emit_item_created(item_id, snapshot)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Emit the sync events for an imported item and the links it came with.
+    #[cfg(feature = "sync")]
+    fn emit_imported(
+        &self,
+        item: &crate::models::StoredItem,
+        links: &crate::database::ItemLinks,
+    ) -> Result<(), ClipKittyError> {
+        self.sync_emitter.emit_item_created(
+            &item.item_id,
+            crate::sync_bridge::snapshot_from_stored_item(item),
+        )?;
+        if links.tags.contains(&ItemTag::Bookmark) {
+            self.sync_emitter.emit_bookmark_set(&item.item_id)?;
+        }
+        if let Some(fire_at_unix) = links.reminder_at_unix {
+            self.sync_emitter
+                .emit_reminder_set(&item.item_id, fire_at_unix)?;
+        }
+        Ok(())
+    }
+}
+
+impl ClipboardStore {
+    /// Resolve a string item_id to its numeric row ID, returning an error if
+    /// not found. Trashed items are not found until restored.
+    fn require_row_id(&self, item_id: &str) -> Result<i64, ClipKittyError> {
+        match self.db.fetch_row_id_by_item_id(item_id)? {
+            Some(row_id) if !self.db.is_trashed(row_id)? => Ok(row_id),
+            _ => Err(ClipKittyError::InvalidInput(format!(
+                "item not found: {item_id}"
+            ))),
+        }
+    }
+
+    /// Like `require_row_id`, but refuse items marked immutable.
+    fn require_mutable_row_id(&self, item_id: &str) -> Result<i64, ClipKittyError> {
+        let row_id = self.require_row_id(item_id)?;
+        if self.db.is_immutable(row_id)? {
+            return Err(ClipKittyError::ItemImmutable(item_id.to_string()));
+        }
+        Ok(row_id)
+    }
+}
+
+#[uniffi::export]
+pub fn inspect_store_bootstrap(db_path: String) -> Result<StoreBootstrapPlan, ClipKittyError> {
+    init_rayon();
+    let path = PathBuf::from(db_path);
+    synced_folder::refuse_synced_path(&path)?;
+    ClipboardStore::inspect_bootstrap(&path)
+}
+
+#[uniffi::export]
+#[async_trait::async_trait]
+impl ClipboardStoreApi for ClipboardStore {
+    fn database_size(&self) -> i64 {
+        self.db.database_size().unwrap_or(0)
+    }
+
+    fn save_text(
+        &self,
+        text: String,
+        source_app: Option<String>,
+        source_app_bundle_id: Option<String>,
+    ) -> Result<String, ClipKittyError> {
+        self.contain("save_text", || {
+            if exclusions::is_excluded(&self.db, source_app_bundle_id.as_deref())? {
+                return Ok(String::new());
+            }
+            let now = Instant::now();
+            let copy_hash = repeat_copies::copy_hash(
+                &text,
+                source_app.as_deref(),
+                source_app_bundle_id.as_deref(),
+            );
+            // Reported like a duplicate, unless the first copy is already gone.
+            if let Some(item_id) = self.repeat_copies.repeat_of(copy_hash, now) {
+                match self.db.fetch_row_id_by_item_id(&item_id)? {
+                    Some(row_id) if !self.db.is_trashed(row_id)? => return Ok(String::new()),
+                    _ => {}
+                }
+            }
+            self.check_disk_space();
+            let outcome = self.observe_write(save_service::save_text(
+                &self.db,
+                &self.indexer,
+                text,
+                source_app,
+                source_app_bundle_id,
+            ))?;
+            self.record_capture(&outcome)?;
+            let (save_service::InsertOutcome::Inserted { item_id, .. }
+            | save_service::InsertOutcome::Deduplicated { item_id, .. }) = &outcome;
+            self.repeat_copies.record(copy_hash, item_id, now);
+            Ok(outcome.ffi_id())
+        })
+    }
+
+    fn save_texts_batch(&self, inputs: Vec<TextInput>) -> Result<Vec<String>, ClipKittyError> {
+        self.contain("save_texts_batch", || {
+            let mut kept = Vec::with_capacity(inputs.len());
+            let mut slots = Vec::with_capacity(inputs.len());
+            for input in inputs {
+                if exclusions::is_excluded(&self.db, input.source_app_bundle_id.as_deref())? {
+                    slots.push(None);
+                } else {
+                    slots.push(Some(kept.len()));
+                    kept.push((input.text, input.source_app, input.source_app_bundle_id));
+                }
+            }
+            if kept.is_empty() {
+                return Ok(vec![String::new(); slots.len()]);
+            }
+            self.check_disk_space();
+            let outcomes = self.observe_write(save_service::save_texts_batch(
+                &self.db,
+                &self.indexer,
+                kept,
+            ))?;
+            for outcome in &outcomes {
+                self.record_capture(outcome)?;
+            }
+            Ok(slots
+                .into_iter()
+                .map(|slot| slot.map_or_else(String::new, |i| outcomes[i].ffi_id()))
+                .collect())
+        })
+    }
+
+    fn save_rich_text(
+        &self,
+        plain: String,
+        rtf_data: Option<Vec<u8>>,
+        html: Option<String>,
+        source_app: Option<String>,
+        source_app_bundle_id: Option<String>,
+    ) -> Result<String, ClipKittyError> {
+        self.contain("save_rich_text", || {
+            if exclusions::is_excluded(&self.db, source_app_bundle_id.as_deref())? {
+                return Ok(String::new());
+            }
+            self.check_disk_space();
+            let outcome = self.observe_write(save_service::save_rich_text(
+                &self.db,
+                &self.indexer,
+                plain,
+                rtf_data,
+                html,
+                source_app,
+                source_app_bundle_id,
+            ))?;
+            self.record_capture(&outcome)?;
+            Ok(outcome.ffi_id())
+        })
+    }
+
+    async fn search(
+        &self,
+        query: String,
+        presentation: ListPresentationProfile,
+    ) -> Result<SearchResult, ClipKittyError> {
+        self.contain_async("search", async {
+            match self
+                .begin_search_operation(query, ItemQueryFilter::All, presentation)
+                .await_result()
+                .await?
+            {
+                SearchOutcome::Success { result } => Ok(result),
+                SearchOutcome::Cancelled => Err(ClipKittyError::Cancelled),
+            }
+        })
+        .await
+    }
+
+    async fn search_filtered(
+        &self,
+        query: String,
+        filter: ItemQueryFilter,
+        presentation: ListPresentationProfile,
+    ) -> Result<SearchResult, ClipKittyError> {
+        self.contain_async("search_filtered", async {
+            if filter == ItemQueryFilter::All {
+                return self.search(query, presentation).await;
+            }
+            match self
+                .begin_search_operation(query, filter, presentation)
+                .await_result()
+                .await?
+            {
+                SearchOutcome::Success { result } => Ok(result),
+                SearchOutcome::Cancelled => Err(ClipKittyError::Cancelled),
+            }
+        })
+        .await
+    }
+
+    async fn search_page(
+        &self,
+        query: String,
+        filter: ItemQueryFilter,
+        cursor: Option<String>,
+        page_size: u64,
+        presentation: ListPresentationProfile,
+    ) -> Result<SearchPage, ClipKittyError> {
+        self.contain_async("search_page", async {
+            // Page loads run beside the interactive search rather than cancel it.
+            let context = self.search_context(CancellationToken::new(), presentation);
+            let shared_thumbnails = self.shared_thumbnails.lock().clone();
+            let mut page =
+                search_service::execute_search_page(context, query, filter, cursor, page_size)
+                    .await?;
+            if let Some(cache) = shared_thumbnails {
+                cache.share_icons(&mut page.matches);
+            }
+            Ok(page)
+        })
+        .await
+    }
+
+    async fn search_structured(
+        &self,
+        query: QueryBuilder,
+        presentation: ListPresentationProfile,
+    ) -> Result<SearchPage, ClipKittyError> {
+        self.contain_async("search_structured", async {
+            // Runs beside the interactive search, as `search_page` does.
+            let context = self.search_context(CancellationToken::new(), presentation);
+            let shared_thumbnails = self.shared_thumbnails.lock().clone();
+            let mut page = search_service::execute_structured_search_page(context, query).await?;
+            if let Some(cache) = shared_thumbnails {
+                cache.share_icons(&mut page.matches);
+            }
+            Ok(page)
+        })
+        .await
+    }
+
+    async fn collect_matches(
+        &self,
+        query: String,
+        filter: ItemQueryFilter,
+    ) -> Result<CollectedMatches, ClipKittyError> {
+        self.contain_async("collect_matches", async {
+            if query.trim().is_empty() {
+                return Err(ClipKittyError::InvalidInput(
+                    "collect_matches needs a query".into(),
+                ));
+            }
+            let context =
+                self.search_context(CancellationToken::new(), ListPresentationProfile::Card);
+            let result =
+                search_service::execute_search(context, query.clone(), filter.clone()).await?;
+            let item_ids: Vec<String> = result
+                .matches
+                .into_iter()
+                .map(|item| item.item_metadata.item_id)
+                .collect();
+            self.contain("collect_matches", || {
+                let Some((document, match_count)) =
+                    match_collection::compose(&self.db, &query, &filter, &item_ids)?
+                else {
+                    return Ok(CollectedMatches {
+                        item_id: String::new(),
+                        match_count: 0,
+                    });
+                };
+                self.check_disk_space();
+                let outcome = self.observe_write(save_service::save_text(
+                    &self.db,
+                    &self.indexer,
+                    document,
+                    None,
+                    None,
+                ))?;
+                self.record_capture(&outcome)?;
+                let item_id = match outcome {
+                    save_service::InsertOutcome::Deduplicated { item_id, .. }
+                    | save_service::InsertOutcome::Inserted { item_id, .. } => item_id,
                 };
+                Ok(CollectedMatches {
+                    item_id,
+                    match_count,
+                })
+            })
+        })
+        .await
+    }
+
+    async fn search_in_app(
+        &self,
+        query: String,
+        bundle_id: String,
+        presentation: ListPresentationProfile,
+    ) -> Result<SearchResult, ClipKittyError> {
+        self.search_filtered(
+            query,
+            ItemQueryFilter::SourceApp { bundle_id },
+            presentation,
+        )
+        .await
+    }
+
+    async fn search_all_sources(
+        &self,
+        query: String,
+        limits: SearchSectionLimits,
+        presentation: ListPresentationProfile,
+    ) -> Result<SectionedSearchResult, ClipKittyError> {
+        self.contain_async("search_all_sources", async {
+            let token = self.replace_active_search_token();
+            let snippets = self.contain("search_all_sources", || {
+                snippets::search(
+                    &self.db,
+                    &self.indexer,
+                    &query,
+                    limits.snippets as usize,
+                    &token,
+                )
+            })?;
+            let (mut everything, mut bookmarked) = futures::try_join!(
+                search_service::execute_search(
+                    self.search_context(token.clone(), presentation),
+                    query.clone(),
+                    ItemQueryFilter::All,
+                ),
+                search_service::execute_search(
+                    self.search_context(token, presentation),
+                    query,
+                    ItemQueryFilter::Tagged {
+                        tag: ItemTag::Bookmark,
+                    },
+                ),
+            )?;
+            if let Some(cache) = self.shared_thumbnails.lock().clone() {
+                cache.share_icons(&mut everything.matches);
+                cache.share_icons(&mut bookmarked.matches);
+            }
+
+            let mut pinned = Vec::new();
+            let mut history = Vec::new();
+            for item in everything.matches {
+                if item.item_metadata.is_pinned {
+                    pinned.push(item);
+                } else {
+                    history.push(item);
+                }
+            }
+            let bookmarked: Vec<ItemMatch> = bookmarked
+                .matches
+                .into_iter()
+                .filter(|item| !item.item_metadata.is_pinned)
+                .collect();
+            let moved = pinned.len()
+                + history
+                    .iter()
+                    .filter(|item| item.item_metadata.tags.contains(&ItemTag::Bookmark))
+                    .count();
+            history.retain(|item| !item.item_metadata.tags.contains(&ItemTag::Bookmark));
+            let history_total = everything.total_count.saturating_sub(moved as u64);
+
+            let sections = [
+                (
+                    SearchSource::Pinned,
+                    pinned.len() as u64,
+                    pinned,
+                    limits.pinned,
+                ),
+                (
+                    SearchSource::Bookmarked,
+                    bookmarked.len() as u64,
+                    bookmarked,
+                    limits.bookmarked,
+                ),
+                (
+                    SearchSource::History,
+                    history_total,
+                    history,
+                    limits.history,
+                ),
+            ]
+            .into_iter()
+            .filter_map(|(source, total_count, mut matches, limit)| {
+                matches.truncate(limit as usize);
+                (!matches.is_empty()).then_some(SearchSection {
+                    source,
+                    matches,
+                    total_count,
+                })
+            })
+            .collect();
+            Ok(SectionedSearchResult { snippets, sections })
+        })
+        .await
+    }
+
+    fn fetch_by_ids(&self, item_ids: Vec<String>) -> Result<Vec<ClipboardItem>, ClipKittyError> {
+        self.contain("fetch_by_ids", || {
+            let stored_items = self.db.fetch_items_by_item_ids(&item_ids)?;
+            let mut items: Vec<ClipboardItem> = stored_items
+                .into_iter()
+                .map(|item| item.to_clipboard_item())
+                .collect();
+            let tags_by_id = self.db.get_tags_for_item_ids(&item_ids)?;
+            let pinned = self.db.get_pinned_item_ids(&item_ids)?;
+            for item in &mut items {
+                item.item_metadata.tags = tags_by_id
+                    .get(&item.item_metadata.item_id)
+                    .cloned()
+                    .unwrap_or_default();
+                item.item_metadata.is_pinned = pinned.contains(&item.item_metadata.item_id);
+            }
+            app_metadata::hydrate_source_apps(
+                &self.db,
+                items.iter_mut().map(|item| &mut item.item_metadata),
+            )?;
+            Ok(items)
+        })
+    }
+
+    fn export_history(
+        &self,
+        path: String,
+        format: HistoryExportFormat,
+        filter: ItemQueryFilter,
+    ) -> Result<HistoryExportSummary, ClipKittyError> {
+        self.contain("export_history", || {
+            history_export::export_history(&self.db, Path::new(&path), format, &filter)
+        })
+    }
+
+    fn resolve_matched_excerpts(
+        &self,
+        requests: Vec<MatchedExcerptRequest>,
+    ) -> Result<Vec<MatchedExcerptResolution>, ClipKittyError> {
+        self.contain("resolve_matched_excerpts", || {
+            search_service::resolve_matched_excerpts(&self.db, &self.analysis_cache, requests)
+        })
+    }
+
+    fn resolve_matched_excerpt_patches(
+        &self,
+        previous_query: String,
+        requests: Vec<MatchedExcerptRequest>,
+    ) -> Result<Vec<MatchedExcerptPatch>, ClipKittyError> {
+        self.contain("resolve_matched_excerpt_patches", || {
+            search_service::resolve_matched_excerpt_patches(
+                &self.db,
+                &self.analysis_cache,
+                &previous_query,
+                requests,
+            )
+        })
+    }
+
+    fn load_preview_payload(
+        &self,
+        item_id: String,
+        query: String,
+    ) -> Result<Option<PreviewPayload>, ClipKittyError> {
+        self.contain("load_preview_payload", || {
+            search_service::load_preview_payload(&self.db, &self.analysis_cache, item_id, query)
+        })
+    }
+
+    fn save_files(
+        &self,
+        paths: Vec<String>,
+        filenames: Vec<String>,
+        file_sizes: Vec<u64>,
+        utis: Vec<String>,
+        bookmark_data_list: Vec<Vec<u8>>,
+        preview_snapshots: Vec<FilePreviewSnapshot>,
+        source_app: Option<String>,
+        source_app_bundle_id: Option<String>,
+    ) -> Result<String, ClipKittyError> {
+        self.contain("save_files", || {
+            if exclusions::is_excluded(&self.db, source_app_bundle_id.as_deref())? {
+                return Ok(String::new());
+            }
+            self.check_disk_space();
+            let outcome = self.observe_write(save_service::save_files(
+                &self.db,
+                &self.indexer,
+                paths,
+                filenames,
+                file_sizes,
+                utis,
+                bookmark_data_list,
+                preview_snapshots,
+                source_app,
+                source_app_bundle_id,
+            ))?;
+            self.record_capture(&outcome)?;
+            Ok(outcome.ffi_id())
+        })
+    }
+
+    fn save_file(
+        &self,
+        path: String,
+        filename: String,
+        file_size: u64,
+        uti: String,
+        bookmark_data: Vec<u8>,
+        preview: FilePreviewSnapshot,
+        source_app: Option<String>,
+        source_app_bundle_id: Option<String>,
+    ) -> Result<String, ClipKittyError> {
+        self.contain("save_file", || {
+            if exclusions::is_excluded(&self.db, source_app_bundle_id.as_deref())? {
+                return Ok(String::new());
+            }
+            self.check_disk_space();
+            let outcome = self.observe_write(save_service::save_file(
+                &self.db,
+                &self.indexer,
+                path,
+                filename,
+                file_size,
+                uti,
+                bookmark_data,
+                preview,
+                source_app,
+                source_app_bundle_id,
+            ))?;
+            self.record_capture(&outcome)?;
+            Ok(outcome.ffi_id())
+        })
+    }
+
+    fn save_image(
+        &self,
+        image_data: Vec<u8>,
+        thumbnail: Option<Vec<u8>>,
+        source_app: Option<String>,
+        source_app_bundle_id: Option<String>,
+        is_animated: bool,
+    ) -> Result<String, ClipKittyError> {
+        self.contain("save_image", || {
+            if exclusions::is_excluded(&self.db, source_app_bundle_id.as_deref())? {
+                return Ok(String::new());
+            }
+            // Images are the bulk of history's footprint; skip them while low on disk.
+            if self.check_disk_space() {
+                return Ok(String::new());
+            }
+            let outcome = self.observe_write(save_service::save_image(
+                &self.db,
+                &self.indexer,
+                image_data,
+                thumbnail,
+                source_app,
+                source_app_bundle_id,
+                is_animated,
+            ))?;
+            self.record_capture(&outcome)?;
+            Ok(outcome.ffi_id())
+        })
+    }
+
+    fn update_link_metadata(
+        &self,
+        item_id: String,
+        title: Option<String>,
+        description: Option<String>,
+        image_data: Option<Vec<u8>>,
+    ) -> Result<(), ClipKittyError> {
+        self.contain("update_link_metadata", || {
+            let row_id = self.require_row_id(&item_id)?;
+            #[allow(unused_variables)]
+            let resolved = save_service::update_link_metadata(
+                &self.db,
+                row_id,
+                title,
+                description,
+                image_data,
+            )?;
+            #[cfg(feature = "sync")]
+            {
+                let snapshot = crate::sync_bridge::link_metadata_snapshot(&resolved);
+                self.sync_emitter
+                    .emit_link_metadata_updated(&item_id, snapshot)?;
+            }
+            self.notify_item_updated(&item_id);
+            Ok(())
+        })
+    }
+
+    fn update_image_description(
+        &self,
+        item_id: String,
+        description: String,
+    ) -> Result<(), ClipKittyError> {
+        self.contain("update_image_description", || {
+            let row_id = self.require_mutable_row_id(&item_id)?;
+            // Bake in the "Image: " label once, up front, so the sync event and the
+            // local store record the identical prefixed description across devices.
+            let description = crate::interface::format_image_description(&description);
+            #[cfg(feature = "sync")]
+            self.sync_emitter
+                .emit_image_description_updated(&item_id, &description)?;
+
+            #[allow(unused_variables)]
+            let reindex = save_service::update_image_description(
+                &self.db,
+                &self.indexer,
+                row_id,
+                description,
+            )?;
+
+            #[cfg(feature = "sync")]
+            if matches!(reindex, save_service::ReindexOutcome::IndexFailed) {
+                let _ = self.sync_emitter.set_index_dirty();
+            }
+            self.notify_item_updated(&item_id);
+            Ok(())
+        })
+    }
+
+    fn update_image_ocr_text(&self, item_id: String, text: String) -> Result<(), ClipKittyError> {
+        self.contain("update_image_ocr_text", || {
+            let row_id = self.require_row_id(&item_id)?;
+            #[allow(unused_variables)]
+            let reindex =
+                save_service::update_image_ocr_text(&self.db, &self.indexer, row_id, text)?;
+
+            #[cfg(feature = "sync")]
+            if matches!(reindex, save_service::ReindexOutcome::IndexFailed) {
+                let _ = self.sync_emitter.set_index_dirty();
+            }
+            self.notify_item_updated(&item_id);
+            Ok(())
+        })
+    }
+
+    fn update_text_item(&self, item_id: String, text: String) -> Result<(), ClipKittyError> {
+        self.contain("update_text_item", || {
+            let row_id = self.require_mutable_row_id(&item_id)?;
+            #[cfg(feature = "sync")]
+            self.sync_emitter.emit_text_edited(&item_id, &text)?;
+
+            #[allow(unused_variables)]
+            let reindex = save_service::update_text_item(&self.db, &self.indexer, row_id, text)?;
+
+            #[cfg(feature = "sync")]
+            if matches!(reindex, save_service::ReindexOutcome::IndexFailed) {
+                let _ = self.sync_emitter.set_index_dirty();
+            }
+            self.notify_item_updated(&item_id);
+            Ok(())
+        })
+    }
+
+    fn update_timestamp(&self, item_id: String) -> Result<(), ClipKittyError> {
+        self.contain("update_timestamp", || {
+            let row_id = self.require_row_id(&item_id)?;
+            #[allow(unused_variables)]
+            let timestamp_unix =
+                match save_service::update_timestamp(&self.db, &self.indexer, row_id)? {
+                    save_service::TouchOutcome::Indexed { timestamp_unix } => timestamp_unix,
+                    save_service::TouchOutcome::IndexFailed { timestamp_unix } => {
+                        #[cfg(feature = "sync")]
+                        let _ = self.sync_emitter.set_index_dirty();
+                        timestamp_unix
+                    }
+                };
+
+            #[cfg(feature = "sync")]
+            self.sync_emitter
+                .emit_item_touched(&item_id, timestamp_unix)?;
+            self.notify_item_updated(&item_id);
+            Ok(())
+        })
+    }
+
+    fn add_tag(&self, item_id: String, tag: ItemTag) -> Result<(), ClipKittyError> {
+        self.contain("add_tag", || {
+            let row_id = self.require_row_id(&item_id)?;
+            #[cfg(feature = "sync")]
+            self.sync_emitter.emit_bookmark_set(&item_id)?;
+
+            save_service::add_tag(&self.db, row_id, tag)?;
+            self.notify_item_updated(&item_id);
+            Ok(())
+        })
+    }
+
+    fn remove_tag(&self, item_id: String, tag: ItemTag) -> Result<(), ClipKittyError> {
+        self.contain("remove_tag", || {
+            let row_id = self.require_row_id(&item_id)?;
+            #[cfg(feature = "sync")]
+            self.sync_emitter.emit_bookmark_cleared(&item_id)?;
+
+            save_service::remove_tag(&self.db, row_id, tag)?;
+            self.notify_item_updated(&item_id);
+            Ok(())
+        })
+    }
+
+    fn delete_item(&self, item_id: String) -> Result<(), ClipKittyError> {
+        self.contain("delete_item", || {
+            let row_id = self.require_mutable_row_id(&item_id)?;
+            #[cfg(feature = "sync")]
+            self.sync_emitter.emit_item_deleted(&item_id)?;
+
+            save_service::trash_item(&self.db, &self.indexer, row_id)?;
+            self.notify_items_deleted(std::slice::from_ref(&item_id));
+            Ok(())
+        })
+    }
+
+    fn clear(&self) -> Result<(), ClipKittyError> {
+        self.contain("clear", || {
+            let kept: HashSet<i64> = self
+                .db
+                .fetch_immutable_ids()?
+                .into_iter()
+                .map(|(row_id, _)| row_id)
+                .collect();
+            let mut cleared_ids = Vec::new();
+            for row_id in self.db.fetch_all_item_ids()? {
+                if kept.contains(&row_id) {
+                    continue;
+                }
+                if let Some(stable_id) = self.db.fetch_item_id_by_row_id(row_id)? {
+                    cleared_ids.push(stable_id);
+                }
+            }
+            #[cfg(feature = "sync")]
+            for stable_id in &cleared_ids {
+                self.sync_emitter.emit_item_deleted(stable_id)?;
+            }
+
+            save_service::clear(&self.db, &self.indexer)?;
+            self.notify_items_deleted(&cleared_ids);
+            Ok(())
+        })
+    }
+
+    fn prune_to_size(&self, max_bytes: i64, keep_ratio: f64) -> Result<u64, ClipKittyError> {
+        self.contain("prune_to_size", || {
+            let outcome =
+                save_service::prune_to_size(&self.db, &self.indexer, max_bytes, keep_ratio)?;
+
+            #[cfg(feature = "sync")]
+            for item_id in &outcome.deleted_ids {
+                self.sync_emitter.emit_item_deleted(item_id)?;
+            }
+            self.notify_items_deleted(&outcome.deleted_ids);
+
+            Ok(outcome.bytes_freed)
+        })
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Sync internals — not exposed via FFI
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[cfg(feature = "sync")]
+impl ClipboardStore {
+    fn materialize_forked_snapshot(
+        &self,
+        snapshot: &purr_sync::types::ItemSnapshotData,
+    ) -> Result<String, ClipKittyError> {
+        use crate::sync_bridge::stored_item_from_snapshot;
+
+        let fork_item_id = uuid::Uuid::new_v4().to_string();
+        let mut item = stored_item_from_snapshot(fork_item_id.clone(), snapshot)
+            .map_err(ClipKittyError::InvalidInput)?;
+        item.external_id = crate::models::StoredItem::external_id_at(item.timestamp_unix);
+        let mut snapshot = snapshot.clone();
+        snapshot.external_id = Some(item.external_id.clone());
+        self.queue_search_upsert(&fork_item_id)?;
+        let row_id = self.db.insert_item(&item)?;
+        if snapshot.is_bookmarked {
+            self.db
+                .add_tag(row_id, crate::interface::ItemTag::Bookmark)?;
+        }
+        reminders::restore(&self.db, row_id, snapshot.reminder_at_unix)?;
+        self.sync_emitter
+            .emit_item_created(&fork_item_id, snapshot)?;
+        Ok(fork_item_id)
+    }
+
+    fn queue_search_upsert(&self, item_id: &str) -> Result<(), ClipKittyError> {
+        use purr_sync::store::SyncStore;
+        use purr_sync::types::FLAG_INDEX_DIRTY;
+
+        let sync = SyncStore::new(self.db.pool());
+        sync.enqueue_index_upsert(item_id)?;
+        sync.set_dirty_flag(FLAG_INDEX_DIRTY, true)?;
+        Ok(())
+    }
+
+    fn queue_search_delete(&self, item_id: &str) -> Result<(), ClipKittyError> {
+        use purr_sync::store::SyncStore;
+        use purr_sync::types::FLAG_INDEX_DIRTY;
+
+        let sync = SyncStore::new(self.db.pool());
+        sync.enqueue_index_delete(item_id)?;
+        sync.set_dirty_flag(FLAG_INDEX_DIRTY, true)?;
+        Ok(())
+    }
+
+    /// Materialize a sync aggregate into the local items table.
+    ///
+    /// For Live aggregates: upsert into items table (insert if new, update if exists).
+    /// For Tombstoned aggregates: delete from items table.
+    /// Returns the current local row ID when the item remains live.
+    fn materialize_aggregate(
+        &self,
+        item_id: &str,
+        aggregate: &purr_sync::types::ItemAggregate,
+        index_dirty: bool,
+        fallback_local_item_id: Option<i64>,
+    ) -> Result<Option<i64>, ClipKittyError> {
+        use crate::sync_bridge::stored_item_from_snapshot;
+        use purr_sync::store::{ProjectionState, SyncStore};
+        use purr_sync::types::ItemAggregate;
+
+        let sync = SyncStore::new(self.db.pool());
+
+        // Resolve local row ID: look up by item_id in the items table,
+        // falling back to the caller-provided hint.
+        let local_item_id = self
+            .db
+            .fetch_row_id_by_item_id(item_id)?
+            .or(fallback_local_item_id);
+
+        match aggregate {
+            ItemAggregate::Live(live) => {
+                let item = stored_item_from_snapshot(item_id.to_string(), &live.snapshot)
+                    .map_err(ClipKittyError::InvalidInput)?;
+
+                if index_dirty {
+                    self.queue_search_upsert(item_id)?;
+                }
+
+                if let Some(local_id) = local_item_id {
+                    self.db.replace_item_preserving_id(local_id, &item)?;
+                    if live.snapshot.is_bookmarked {
+                        self.db
+                            .add_tag(local_id, crate::interface::ItemTag::Bookmark)?;
+                    } else {
+                        self.db
+                            .remove_tag(local_id, crate::interface::ItemTag::Bookmark)?;
+                    }
+                    reminders::restore(&self.db, local_id, live.snapshot.reminder_at_unix)?;
+                    sync.upsert_projection(
+                        item_id,
+                        &ProjectionState::Materialized {
+                            versions: live.versions,
+                        },
+                    )?;
+                    return Ok(Some(local_id));
+                }
+
+                // No existing local item — insert fresh.
+                let new_id = self.db.insert_item(&item)?;
+                if live.snapshot.is_bookmarked {
+                    self.db
+                        .add_tag(new_id, crate::interface::ItemTag::Bookmark)?;
+                }
+                reminders::restore(&self.db, new_id, live.snapshot.reminder_at_unix)?;
+                sync.upsert_projection(
+                    item_id,
+                    &ProjectionState::Materialized {
+                        versions: live.versions,
+                    },
+                )?;
+                Ok(Some(new_id))
+            }
+            ItemAggregate::Tombstoned(tomb) => {
+                if index_dirty {
+                    self.queue_search_delete(item_id)?;
+                }
+                if let Some(local_id) = local_item_id {
+                    self.db.delete_item(local_id)?;
+                }
+                sync.upsert_projection(
+                    item_id,
+                    &ProjectionState::Tombstoned {
+                        versions: tomb.versions,
+                    },
+                )?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Re-materialize the current sync snapshot for an item into the read model.
+    ///
+    /// This lets duplicate/already-applied remote records heal the read model if
+    /// sync state was stored successfully but a prior materialization failed.
+    fn materialize_current_sync_state(
+        &self,
+        item_id: &str,
+        index_dirty: bool,
+        fallback_local_item_id: Option<i64>,
+    ) -> Result<Option<i64>, ClipKittyError> {
+        use purr_sync::store::SyncStore;
+
+        let sync = SyncStore::new(self.db.pool());
+        let Some(_) = sync.fetch_projection(item_id)? else {
+            return Ok(self
+                .db
+                .fetch_row_id_by_item_id(item_id)?
+                .or(fallback_local_item_id));
+        };
+        let snapshot = sync.fetch_snapshot(item_id)?.ok_or_else(|| {
+            ClipKittyError::DataInconsistency(format!(
+                "sync projection for item `{item_id}` is missing snapshot state"
+            ))
+        })?;
+
+        self.materialize_aggregate(
+            item_id,
+            &snapshot.aggregate,
+            index_dirty,
+            fallback_local_item_id,
+        )
+    }
+
+    fn local_snapshots_for_full_resync(
+        &self,
+    ) -> Result<Vec<purr_sync::snapshot::ItemSnapshot>, ClipKittyError> {
+        use purr_sync::snapshot::ItemSnapshot;
+        use purr_sync::store::SyncStore;
+        use purr_sync::types::{ItemAggregate, LiveItemState, VersionVector, SYNC_SCHEMA_VERSION};
+
+        let items = self.db.fetch_all_items()?;
+        let item_ids: Vec<String> = items.iter().map(|item| item.item_id.clone()).collect();
+        let tags_by_item_id = self.db.get_tags_for_item_ids(&item_ids)?;
+        let reminders_by_item_id = self.db.fetch_reminders_for_item_ids(&item_ids)?;
+        let sync = SyncStore::new(self.db.pool());
+
+        items
+            .into_iter()
+            .map(|item| {
+                let item_id = item.item_id.clone();
+                let existing_snapshot = sync.fetch_snapshot(&item_id)?;
+                let is_bookmarked = tags_by_item_id
+                    .get(&item_id)
+                    .map(|tags| tags.contains(&ItemTag::Bookmark))
+                    .unwrap_or(false);
+                let reminder_at_unix = reminders_by_item_id.get(&item_id).copied();
+
+                let mut versions = match existing_snapshot
+                    .as_ref()
+                    .map(|snapshot| &snapshot.aggregate)
+                {
+                    Some(ItemAggregate::Live(live)) => live.versions,
+                    Some(ItemAggregate::Tombstoned(_)) | None => VersionVector {
+                        content: 1,
+                        bookmark: if is_bookmarked { 1 } else { 0 },
+                        existence: 1,
+                        touch: 1,
+                        metadata: 1,
+                        reminder: if reminder_at_unix.is_some() { 1 } else { 0 },
+                    },
+                };
+                if is_bookmarked && versions.bookmark == 0 {
+                    versions.bookmark = 1;
+                }
+                if reminder_at_unix.is_some() && versions.reminder == 0 {
+                    versions.reminder = 1;
+                }
+
+                let mut live_snapshot =
+                    snapshot_from_stored_item_with_bookmark(&item, is_bookmarked);
+                live_snapshot.reminder_at_unix = reminder_at_unix;
+                let aggregate = ItemAggregate::Live(LiveItemState {
+                    snapshot: live_snapshot,
+                    versions,
+                });
+                let snapshot = ItemSnapshot {
+                    item_id: item_id.clone(),
+                    snapshot_revision: existing_snapshot
+                        .as_ref()
+                        .map(|snapshot| snapshot.snapshot_revision.max(1))
+                        .unwrap_or(1),
+                    schema_version: SYNC_SCHEMA_VERSION,
+                    covers_through_event: existing_snapshot
+                        .and_then(|snapshot| snapshot.covers_through_event),
+                    aggregate,
+                    uploaded: false,
+                    uploaded_at: None,
+                };
+
+                Ok(snapshot)
+            })
+            .collect()
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Sync FFI — methods exposed to Swift SyncEngine
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[cfg(feature = "sync")]
+#[uniffi::export]
+impl ClipboardStore {
+    /// Set the device ID used for locally-originated sync events.
+    /// Called by SyncEngine.start() with the stable UUID from UserDefaults.
+    pub fn set_sync_device_id(&self, device_id: String) {
+        self.sync_emitter.set_device_id(device_id);
+    }
+
+    /// Fetch pending local events that need uploading to CloudKit.
+    pub fn pending_local_events(
+        &self,
+    ) -> Result<Vec<crate::interface::SyncEventRecord>, ClipKittyError> {
+        self.contain("pending_local_events", || {
+            use crate::interface::SyncEventRecord;
+            use purr_sync::store::SyncStore;
+
+            let sync = SyncStore::new(self.db.pool());
+            let events = sync.fetch_pending_upload_events()?;
+            Ok(events
+                .into_iter()
+                .map(|e| {
+                    let payload_type = e.payload_type().to_string();
+                    let payload_data = e.payload_data();
+                    SyncEventRecord {
+                        event_id: e.event_id,
+                        item_id: e.item_id,
+                        origin_device_id: e.origin_device_id,
+                        schema_version: e.schema_version,
+                        recorded_at: e.recorded_at,
+                        payload_type,
+                        payload_data,
+                    }
+                })
+                .collect())
+        })
+    }
+
+    /// Fetch compacted snapshots that need uploading to CloudKit.
+    pub fn pending_snapshot_records(
+        &self,
+    ) -> Result<Vec<crate::interface::SyncSnapshotRecord>, ClipKittyError> {
+        self.contain("pending_snapshot_records", || {
+            use crate::interface::SyncSnapshotRecord;
+            use purr_sync::store::SyncStore;
+
+            let sync = SyncStore::new(self.db.pool());
+            let snapshots = sync.fetch_pending_upload_snapshots()?;
+            Ok(snapshots
+                .into_iter()
+                .map(|s| {
+                    let aggregate_data = s.aggregate_data();
+                    SyncSnapshotRecord {
+                        item_id: s.item_id,
+                        snapshot_revision: s.snapshot_revision,
+                        schema_version: s.schema_version,
+                        covers_through_event: s.covers_through_event,
+                        aggregate_data,
+                    }
+                })
+                .collect())
+        })
+    }
+
+    /// Mark events as uploaded after CloudKit confirms receipt.
+    pub fn mark_events_uploaded(&self, event_ids: Vec<String>) -> Result<(), ClipKittyError> {
+        self.contain("mark_events_uploaded", || {
+            use purr_sync::store::SyncStore;
+
+            let sync = SyncStore::new(self.db.pool());
+            let refs: Vec<&str> = event_ids.iter().map(|s| s.as_str()).collect();
+            sync.mark_events_uploaded(&refs)?;
+            Ok(())
+        })
+    }
+
+    /// Mark a snapshot as uploaded to CloudKit.
+    pub fn mark_snapshot_uploaded(&self, item_id: String) -> Result<(), ClipKittyError> {
+        self.contain("mark_snapshot_uploaded", || {
+            use purr_sync::store::SyncStore;
+
+            let sync = SyncStore::new(self.db.pool());
+            sync.mark_snapshot_uploaded(&item_id)?;
+            Ok(())
+        })
+    }
+
+    /// Dry run of applying remote snapshots: how many items are new, already
+    /// present, or differ from the local copy. Tombstones for items still in
+    /// the store count as conflicts. Nothing is written.
+    pub fn preview_remote_snapshots(
+        &self,
+        snapshot_records: Vec<crate::interface::SyncSnapshotRecord>,
+    ) -> Result<ImportPreview, ClipKittyError> {
+        self.contain("preview_remote_snapshots", || {
+            use crate::sync_bridge::stored_item_from_snapshot;
+            use purr_sync::snapshot::ItemSnapshot;
+            use purr_sync::types::ItemAggregate;
+
+            let mut classifier = ImportClassifier::new(&self.db);
+            for record in snapshot_records {
+                let snapshot = ItemSnapshot::from_stored(
+                    record.item_id,
+                    record.snapshot_revision,
+                    record.schema_version,
+                    record.covers_through_event,
+                    &record.aggregate_data,
+                    true,
+                    None,
+                )
+                .map_err(ClipKittyError::InvalidInput)?;
+                match &snapshot.aggregate {
+                    ItemAggregate::Live(live) => {
+                        let item = stored_item_from_snapshot(snapshot.item_id, &live.snapshot)
+                            .map_err(ClipKittyError::InvalidInput)?;
+                        classifier.classify(&item)?;
+                    }
+                    ItemAggregate::Tombstoned(_) => {
+                        if self
+                            .db
+                            .fetch_row_id_by_item_id(&snapshot.item_id)?
+                            .is_some()
+                        {
+                            classifier.record_removal();
+                        }
+                    }
+                }
+            }
+            Ok(classifier.finish())
+        })
+    }
+
+    /// Apply a batch of remote events and snapshots.
+    /// Returns a structured outcome indicating whether it's safe to advance
+    /// the CloudKit zone change token.
+    pub fn apply_remote_batch(
+        &self,
+        event_records: Vec<crate::interface::SyncEventRecord>,
+        snapshot_records: Vec<crate::interface::SyncSnapshotRecord>,
+    ) -> Result<crate::interface::SyncDownloadBatchOutcome, ClipKittyError> {
+        self.contain("apply_remote_batch", || {
+            use crate::interface::SyncDownloadBatchOutcome;
+            use purr_sync::event::ItemEvent;
+            use purr_sync::replay;
+            use purr_sync::snapshot::ItemSnapshot;
+
+            let mut known_local_item_ids: HashMap<String, Option<i64>> = HashMap::new();
+            for item_id in snapshot_records
+                .iter()
+                .map(|record| record.item_id.as_str())
+                .chain(event_records.iter().map(|record| record.item_id.as_str()))
+            {
+                known_local_item_ids
+                    .entry(item_id.to_string())
+                    .or_insert_with(|| self.db.fetch_row_id_by_item_id(item_id).ok().flatten());
+            }
+
+            // Apply snapshots first.
+            let mut snapshots_applied: usize = 0;
+            for record in &snapshot_records {
+                let snapshot = ItemSnapshot::from_stored(
+                    record.item_id.clone(),
+                    record.snapshot_revision,
+                    record.schema_version,
+                    record.covers_through_event.clone(),
+                    &record.aggregate_data,
+                    true,
+                    None,
+                )
+                .map_err(|e| ClipKittyError::InvalidInput(e))?;
+
+                let item_id = snapshot.item_id.clone();
+                let applied = replay::apply_remote_snapshots(self.db.pool(), &[snapshot])?;
+                let local_item_id = self.materialize_current_sync_state(
+                    &item_id,
+                    true,
+                    known_local_item_ids.get(&item_id).copied().flatten(),
+                )?;
+                known_local_item_ids.insert(item_id, local_item_id);
+                if applied > 0 {
+                    snapshots_applied += 1;
+                }
+            }
+
+            // Convert and apply events as a batch.
+            let events: Vec<ItemEvent> = event_records
+                .iter()
+                .map(|r| {
+                    ItemEvent::from_stored(
+                        r.event_id.clone(),
+                        r.item_id.clone(),
+                        r.origin_device_id.clone(),
+                        r.schema_version,
+                        r.recorded_at,
+                        &r.payload_type,
+                        &r.payload_data,
+                    )
+                    .map_err(|e| ClipKittyError::InvalidInput(e))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut batch_result = replay::apply_remote_event_batch(self.db.pool(), &events)?;
+
+            // Materialize Applied events into the read model.
+            // Re-fetch the applied events' aggregates from the sync store.
+            for event_record in &event_records {
+                match self.materialize_current_sync_state(
+                    &event_record.item_id,
+                    true,
+                    known_local_item_ids
+                        .get(&event_record.item_id)
+                        .copied()
+                        .flatten(),
+                ) {
+                    Ok(local_item_id) => {
+                        known_local_item_ids.insert(event_record.item_id.clone(), local_item_id);
+                    }
+                    Err(_) => {
+                        batch_result.materialization_failures += 1;
+                    }
+                }
+            }
+
+            // Handle fork plans.
+            for (_original_gid, plan) in &batch_result.fork_plans {
+                let _ = self.materialize_forked_snapshot(&plan.forked_snapshot)?;
+            }
+
+            // Build the download outcome.
+            let outcome = batch_result.download_outcome(snapshots_applied);
+            Ok(match outcome {
+                purr_sync::types::DownloadBatchOutcome::Applied {
+                    events_applied,
+                    snapshots_applied,
+                } => SyncDownloadBatchOutcome::Applied {
+                    events_applied: events_applied as u64,
+                    snapshots_applied: snapshots_applied as u64,
+                },
+                purr_sync::types::DownloadBatchOutcome::PartialFailure {
+                    applied_count,
+                    failed_count,
+                    should_retry,
+                } => SyncDownloadBatchOutcome::PartialFailure {
+                    applied_count: applied_count as u64,
+                    failed_count: failed_count as u64,
+                    should_retry,
+                },
+                purr_sync::types::DownloadBatchOutcome::FullResyncRequired => {
+                    SyncDownloadBatchOutcome::FullResyncRequired
+                }
+            })
+        })
+    }
+
+    /// Apply a single remote event received from CloudKit.
+    ///
+    /// When an event is Applied, the local items table is updated to reflect
+    /// the new state so the app's read model stays in sync.
+    pub fn apply_remote_event(
+        &self,
+        record: crate::interface::SyncEventRecord,
+    ) -> Result<crate::interface::SyncApplyOutcome, ClipKittyError> {
+        self.contain("apply_remote_event", || {
+            use crate::interface::SyncApplyOutcome;
+            use purr_sync::event::ItemEvent;
+            use purr_sync::replay;
+            use purr_sync::types::{ApplyResult, IgnoreReason};
+
+            let event = ItemEvent::from_stored(
+                record.event_id,
+                record.item_id.clone(),
+                record.origin_device_id,
+                record.schema_version,
+                record.recorded_at,
+                &record.payload_type,
+                &record.payload_data,
+            )
+            .map_err(|e| ClipKittyError::InvalidInput(e))?;
+
+            let fallback_local_item_id = self.db.fetch_row_id_by_item_id(&record.item_id)?;
+            let result = replay::apply_remote_event(self.db.pool(), &event)?;
+
+            match &result {
+                ApplyResult::Applied(_) => {
+                    let _ = self.materialize_current_sync_state(
+                        &record.item_id,
+                        true,
+                        fallback_local_item_id,
+                    )?;
+                    Ok(SyncApplyOutcome::Applied)
+                }
+                ApplyResult::Ignored(IgnoreReason::AlreadyApplied) => {
+                    let _ = self.materialize_current_sync_state(
+                        &record.item_id,
+                        true,
+                        fallback_local_item_id,
+                    )?;
+                    Ok(SyncApplyOutcome::Ignored)
+                }
+                ApplyResult::Ignored(_) => Ok(SyncApplyOutcome::Ignored),
+                ApplyResult::Deferred(_) => Ok(SyncApplyOutcome::Deferred),
+                ApplyResult::Forked(plan) => {
+                    let _ = self.materialize_forked_snapshot(&plan.forked_snapshot)?;
+
+                    Ok(SyncApplyOutcome::Forked {
+                        forked_snapshot_data: serde_json::to_string(&plan.forked_snapshot)
+                            .unwrap_or_default(),
+                    })
+                }
+            }
+        })
+    }
+
+    /// Apply a remote snapshot received from CloudKit.
+    /// Materializes the snapshot into the read model (items table + index).
+    pub fn apply_remote_snapshot(
+        &self,
+        record: crate::interface::SyncSnapshotRecord,
+    ) -> Result<bool, ClipKittyError> {
+        self.contain("apply_remote_snapshot", || {
+            use purr_sync::replay;
+            use purr_sync::snapshot::ItemSnapshot;
+
+            let snapshot = ItemSnapshot::from_stored(
+                record.item_id,
+                record.snapshot_revision,
+                record.schema_version,
+                record.covers_through_event,
+                &record.aggregate_data,
+                true,
+                None,
+            )
+            .map_err(|e| ClipKittyError::InvalidInput(e))?;
+
+            let item_id = snapshot.item_id.clone();
+            let fallback_local_item_id = self.db.fetch_row_id_by_item_id(&item_id)?;
+            let applied = replay::apply_remote_snapshots(self.db.pool(), &[snapshot])?;
+            let _ = self.materialize_current_sync_state(&item_id, true, fallback_local_item_id)?;
+            Ok(applied > 0)
+        })
+    }
+
+    /// Run compaction and retention for all items.
+    pub fn run_compaction(&self) -> Result<crate::interface::CompactionResult, ClipKittyError> {
+        self.contain("run_compaction", || {
+            use crate::interface::CompactionResult;
+            use purr_sync::compactor;
+
+            // Deferred, not skipped: the app schedules compaction periodically, so
+            // the next run after low-power mode ends does the work.
+            if power::is_low_power() {
+                return Ok(CompactionResult {
+                    items_compacted: 0,
+                    events_purged: 0,
+                    tombstones_purged: 0,
+                });
+            }
+
+            let items_compacted = compactor::compact_all(self.db.pool())? as u64;
+            // Old compacted events stay local until CloudKit deletion is confirmed so
+            // event cleanup and dedup pruning share one authoritative handoff.
+            let events_purged = 0;
+            let tombstones_purged = compactor::purge_tombstone_snapshots(self.db.pool())? as u64;
+
+            Ok(CompactionResult {
+                items_compacted,
+                events_purged,
+                tombstones_purged,
+            })
+        })
+    }
+
+    /// Perform a full resync from the provided snapshots.
+    /// Reconciles CloudKit's explicit live/tombstoned states with local items.
+    pub fn full_resync(
+        &self,
+        snapshot_records: Vec<crate::interface::SyncSnapshotRecord>,
+    ) -> Result<u64, ClipKittyError> {
+        self.contain("full_resync", || {
+            Ok(self
+                .full_resync_with_tail(snapshot_records, Vec::new())?
+                .checkpoints_applied)
+        })
+    }
+
+    /// Perform a full resync from checkpoints and tail events.
+    /// Clears sync state, applies checkpoints, replays tail events,
+    /// and materializes all live items into the read model.
+    pub fn full_resync_with_tail(
+        &self,
+        snapshot_records: Vec<crate::interface::SyncSnapshotRecord>,
+        tail_event_records: Vec<crate::interface::SyncEventRecord>,
+    ) -> Result<crate::interface::SyncFullResyncResult, ClipKittyError> {
+        self.contain("full_resync_with_tail", || {
+            use crate::interface::SyncFullResyncResult;
+            use purr_sync::event::ItemEvent;
+            use purr_sync::replay;
+            use purr_sync::snapshot::ItemSnapshot;
+            use purr_sync::store::SyncStore;
+            use std::collections::HashSet;
+
+            let local_snapshots_before_resync = self.local_snapshots_for_full_resync()?;
+
+            let remote_snapshots: Vec<ItemSnapshot> = snapshot_records
+                .into_iter()
+                .map(|r| {
+                    ItemSnapshot::from_stored(
+                        r.item_id,
+                        r.snapshot_revision,
+                        r.schema_version,
+                        r.covers_through_event,
+                        &r.aggregate_data,
+                        true,
+                        None,
+                    )
+                    .map_err(|e| ClipKittyError::InvalidInput(e))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let remote_item_ids: HashSet<String> = remote_snapshots
+                .iter()
+                .map(|snapshot| snapshot.item_id.clone())
+                .collect();
+            let mut local_base_snapshots: Vec<ItemSnapshot> = local_snapshots_before_resync
+                .into_iter()
+                .filter(|snapshot| !remote_item_ids.contains(&snapshot.item_id))
+                .map(|mut snapshot| {
+                    // Local bases are not CloudKit checkpoints. They exist so tail
+                    // events with explicit item IDs can apply, while plain absence
+                    // from CloudKit still cannot delete local data.
+                    snapshot.covers_through_event = None;
+                    snapshot
+                })
+                .collect();
+            let local_base_count = local_base_snapshots.len();
+            let mut resync_snapshots = remote_snapshots;
+            resync_snapshots.append(&mut local_base_snapshots);
+
+            let tail_events: Vec<ItemEvent> = tail_event_records
+                .into_iter()
+                .map(|r| {
+                    ItemEvent::from_stored(
+                        r.event_id,
+                        r.item_id,
+                        r.origin_device_id,
+                        r.schema_version,
+                        r.recorded_at,
+                        &r.payload_type,
+                        &r.payload_data,
+                    )
+                    .map_err(|e| ClipKittyError::InvalidInput(e))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut result = replay::full_resync(self.db.pool(), &resync_snapshots, &tail_events)?;
+            result.checkpoints_applied =
+                result.checkpoints_applied.saturating_sub(local_base_count);
+
+            // CloudKit only deletes local data via explicit tombstone snapshots/events.
+            // Absence from the full feed means "missing remote proof", not "deleted".
+            let sync = SyncStore::new(self.db.pool());
+            let all_snapshots = sync.fetch_all_snapshots()?;
+            for snapshot in &all_snapshots {
+                let _ =
+                    self.materialize_aggregate(&snapshot.item_id, &snapshot.aggregate, true, None)?;
+            }
+            for (_original_item_id, plan) in &result.fork_plans {
+                let _ = self.materialize_forked_snapshot(&plan.forked_snapshot)?;
+            }
+
+            Ok(SyncFullResyncResult {
+                checkpoints_applied: result.checkpoints_applied as u64,
+                tail_events_applied: result.tail_events_applied as u64,
+                tail_events_ignored: result.tail_events_ignored as u64,
+                tail_events_deferred: result.tail_events_deferred as u64,
+                tail_events_forked: result.tail_events_forked as u64,
+            })
+        })
+    }
+
+    /// Get the current sync device state.
+    pub fn get_sync_device_state(
+        &self,
+        device_id: String,
+    ) -> Result<crate::interface::SyncDeviceState, ClipKittyError> {
+        self.contain("get_sync_device_state", || {
+            use crate::interface::SyncDeviceState;
+            use purr_sync::store::SyncStore;
+            use purr_sync::types::{FLAG_INDEX_DIRTY, FLAG_NEEDS_FULL_RESYNC};
+
+            let sync = SyncStore::new(self.db.pool());
+            let token = sync.fetch_zone_change_token(&device_id)?;
+            let needs_resync = sync.get_dirty_flag(FLAG_NEEDS_FULL_RESYNC)?;
+            let index_dirty =
+                sync.get_dirty_flag(FLAG_INDEX_DIRTY)? || sync.count_index_queue_entries()? > 0;
+
+            Ok(SyncDeviceState {
+                device_id,
+                zone_change_token: token,
+                needs_full_resync: needs_resync,
+                index_dirty,
+            })
+        })
+    }
+
+    /// Update the device's zone change token after a successful fetch.
+    pub fn update_zone_change_token(
+        &self,
+        device_id: String,
+        token: Option<Vec<u8>>,
+    ) -> Result<(), ClipKittyError> {
+        self.contain("update_zone_change_token", || {
+            use purr_sync::store::SyncStore;
+
+            let sync = SyncStore::new(self.db.pool());
+            sync.upsert_device_state(&device_id, token.as_deref())?;
+            Ok(())
+        })
+    }
+
+    /// Fetch event IDs eligible for CloudKit deletion.
+    /// Only returns events that are compacted, old, AND covered by an uploaded checkpoint.
+    pub fn purgeable_cloud_event_ids(
+        &self,
+        max_age_days: u32,
+    ) -> Result<Vec<String>, ClipKittyError> {
+        self.contain("purgeable_cloud_event_ids", || {
+            use purr_sync::store::SyncStore;
+
+            let threshold = chrono::Utc::now().timestamp() - (max_age_days as i64 * 86400);
+            let sync = SyncStore::new(self.db.pool());
+            let ids = sync.fetch_checkpoint_safe_purgeable_events(threshold)?;
+            Ok(ids)
+        })
+    }
+
+    /// Delete local event records after their CloudKit counterparts have been deleted.
+    pub fn purge_cloud_events(&self, event_ids: Vec<String>) -> Result<u64, ClipKittyError> {
+        self.contain("purge_cloud_events", || {
+            use purr_sync::store::SyncStore;
+
+            let sync = SyncStore::new(self.db.pool());
+            let refs: Vec<&str> = event_ids.iter().map(|s| s.as_str()).collect();
+            let count = sync.delete_events_and_dedup_by_ids(&refs)?;
+            Ok(count as u64)
+        })
+    }
+
+    /// Clear the index_dirty flag (after a successful rebuild).
+    pub fn clear_index_dirty_flag(&self) -> Result<(), ClipKittyError> {
+        self.contain("clear_index_dirty_flag", || {
+            use purr_sync::store::SyncStore;
+            use purr_sync::types::FLAG_INDEX_DIRTY;
+
+            let sync = SyncStore::new(self.db.pool());
+            if sync.count_index_queue_entries()? == 0 {
+                sync.set_dirty_flag(FLAG_INDEX_DIRTY, false)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Enqueue every live item for derived search-index catch-up.
+    pub fn enqueue_full_index_rebuild(&self) -> Result<u64, ClipKittyError> {
+        self.contain("enqueue_full_index_rebuild", || {
+            use purr_sync::store::SyncStore;
+
+            let items = self.db.fetch_all_items()?;
+            let item_ids: Vec<String> = items.into_iter().map(|item| item.item_id).collect();
+            let sync = SyncStore::new(self.db.pool());
+            sync.replace_index_queue_with_full_rebuild(&item_ids)?;
+            Ok(item_ids.len() as u64)
+        })
+    }
+
+    /// Process a bounded number of queued search-index updates.
+    pub fn process_index_queue(
+        &self,
+        max_items: u32,
+    ) -> Result<crate::interface::IndexMaintenanceOutcome, ClipKittyError> {
+        self.contain("process_index_queue", || {
+            use crate::interface::IndexMaintenanceOutcome;
+            use purr_sync::store::SyncStore;
+            use purr_sync::types::{IndexQueueEntry, FLAG_INDEX_DIRTY};
+
+            let sync = SyncStore::new(self.db.pool());
+            if max_items == 0 {
+                let remaining = sync.count_index_queue_entries()?;
+                sync.set_dirty_flag(FLAG_INDEX_DIRTY, remaining > 0)?;
+                if remaining == 0 {
+                    return Ok(IndexMaintenanceOutcome::Completed { processed: 0 });
+                }
+                return Ok(IndexMaintenanceOutcome::MoreRemaining {
+                    processed: 0,
+                    remaining: remaining as u64,
+                });
+            }
+
+            let entries = sync.fetch_index_queue(max_items as usize)?;
+            if entries.is_empty() {
+                sync.set_dirty_flag(FLAG_INDEX_DIRTY, false)?;
+                return Ok(IndexMaintenanceOutcome::Completed { processed: 0 });
+            }
+
+            let mut processed_queue_keys = Vec::with_capacity(entries.len());
+            for entry in &entries {
+                match entry {
+                    IndexQueueEntry::Reset => {
+                        self.indexer.delete_all_documents()?;
+                        snippets::index_all(&self.db, &self.indexer)?;
+                    }
+                    IndexQueueEntry::Upsert { item_id } => {
+                        let item = self
+                            .db
+                            .fetch_items_by_item_ids(std::slice::from_ref(item_id))?
+                            .into_iter()
+                            .next();
+                        if let Some(item) = item {
+                            let text = item
+                                .file_index_text()
+                                .unwrap_or_else(|| item.text_content().to_string());
+                            let tags = match item.id {
+                                Some(row_id) => self.db.fetch_user_tags(row_id)?,
+                                None => Vec::new(),
+                            };
+                            self.indexer.add_document_with_row(
+                                &item.item_id,
+                                &text,
+                                item.timestamp_unix,
+                                &tags,
+                                &Database::indexed_row(&item),
+                            )?;
+                        } else {
+                            self.indexer.delete_document(item_id)?;
+                        }
+                    }
+                    IndexQueueEntry::Delete { item_id } => {
+                        self.indexer.delete_document(item_id)?;
+                    }
+                }
+                processed_queue_keys.push(entry.queue_key());
+            }
+
+            self.indexer.commit()?;
+            sync.remove_index_queue_entries(&processed_queue_keys)?;
+            let remaining = sync.count_index_queue_entries()?;
+            if remaining == 0 {
+                sync.set_dirty_flag(FLAG_INDEX_DIRTY, false)?;
+                return Ok(IndexMaintenanceOutcome::Completed {
+                    processed: processed_queue_keys.len() as u64,
+                });
+            }
+
+            sync.set_dirty_flag(FLAG_INDEX_DIRTY, true)?;
+            Ok(IndexMaintenanceOutcome::MoreRemaining {
+                processed: processed_queue_keys.len() as u64,
+                remaining: remaining as u64,
+            })
+        })
+    }
+}
+
+#[uniffi::export]
+impl SearchOperation {
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    pub async fn await_result(&self) -> Result<SearchOutcome, ClipKittyError> {
+        self.completion.wait().await
+    }
+
+    /// `await_result` with the matches packed into one buffer, which is
+    /// much cheaper to hand across the FFI than thousands of records.
+    pub async fn await_packed_result(&self) -> Result<PackedSearchOutcome, ClipKittyError> {
+        Ok(match self.completion.wait().await? {
+            SearchOutcome::Success { result } => PackedSearchOutcome::Success {
+                result: PackedSearchResult {
+                    packed_matches: crate::packed_results::pack_matches(&result.matches),
+                    total_count: result.total_count,
+                    first_preview_payload: result.first_preview_payload,
+                },
+            },
+            SearchOutcome::Cancelled => PackedSearchOutcome::Cancelled,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::{
+        ContentTypeFilter, FileTextPreviewSnapshot, IconType, ItemIcon, RowPresentation,
+        SearchSort, StoreMoveStage, StructuredFormat, TagFilter,
+    };
+    use crate::models::StoredItem;
+
+    fn insert_indexed_text_with_timestamp(
+        store: &ClipboardStore,
+        content: &str,
+        timestamp_unix: i64,
+    ) -> StoredItem {
+        let mut item = StoredItem::new_text(content.to_string(), None, None);
+        item.timestamp_unix = timestamp_unix;
+        let row_id = store.db.insert_item(&item).unwrap();
+        item.id = Some(row_id);
+        store
+            .indexer
+            .add_document(&item.item_id, item.content.text_content(), timestamp_unix)
+            .unwrap();
+        item
+    }
+
+    #[test]
+    fn index_version_v14_dir_name() {
+        // Pins the v14 migration trigger (camelCase parts): a revert of
+        // INDEX_VERSION would reuse an index without identifier parts.
+        let path = ClipboardStore::index_path_for_database(Path::new("/tmp/clipkitty/test.db"));
+        assert!(
+            path.ends_with("tantivy_index_v14"),
+            "expected v14 index dir, got {path:?}"
+        );
+    }
+
+    #[test]
+    fn open_at_path_sweeps_stale_index_dirs() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_path = temp.path().join("test.db");
+        let stale_dir = temp.path().join("tantivy_index_v6");
+        std::fs::create_dir_all(&stale_dir).unwrap();
+
+        let _store = ClipboardStore::open_at_path(&db_path).unwrap();
+
+        assert!(!stale_dir.exists(), "stale v6 index dir should be removed");
+        assert!(ClipboardStore::index_path_for_database(&db_path).exists());
+    }
+
+    #[derive(Default)]
+    struct RecordingMoveProgress {
+        stages: Mutex<Vec<StoreMoveStage>>,
+    }
+
+    impl StoreMoveProgress for RecordingMoveProgress {
+        fn on_move_progress(&self, stage: StoreMoveStage, copied_bytes: u64, total_bytes: u64) {
+            assert!(copied_bytes <= total_bytes);
+            let mut stages = self.stages.lock();
+            if stages.last() != Some(&stage) {
+                stages.push(stage);
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingRebuildObserver {
+        progress: Mutex<Vec<(u64, u64, u8)>>,
+        completion: Mutex<Option<Option<String>>>,
+        done: Notify,
+    }
+
+    impl IndexRebuildObserver for RecordingRebuildObserver {
+        fn on_rebuild_progress(&self, indexed_items: u64, total_items: u64, percent: u8) {
+            self.progress
+                .lock()
+                .push((indexed_items, total_items, percent));
+        }
+
+        fn on_rebuild_complete(&self, error: Option<String>) {
+            *self.completion.lock() = Some(error);
+            self.done.notify_one();
+        }
+    }
+
+    #[tokio::test]
+    async fn background_rebuild_reports_progress_per_batch() {
+        let temp = tempfile::tempdir().unwrap();
+        let store =
+            Arc::new(ClipboardStore::open_at_path(&temp.path().join("clipboard.db")).unwrap());
+        let texts: Vec<_> = (0..REBUILD_BATCH_ITEMS + 20)
+            .map(|i| TextInput {
+                text: format!("rebuild entry {i}"),
+                source_app: None,
+                source_app_bundle_id: None,
+            })
+            .collect();
+        store.save_texts_batch(texts).unwrap();
+
+        let observer = Arc::new(RecordingRebuildObserver::default());
+        Arc::clone(&store).rebuild_index_in_background(observer.clone());
+        observer.done.notified().await;
+
+        assert_eq!(*observer.completion.lock(), Some(None));
+        let total = REBUILD_BATCH_ITEMS as u64 + 20;
+        assert_eq!(
+            *observer.progress.lock(),
+            vec![
+                (0, total, 0),
+                (REBUILD_BATCH_ITEMS as u64, total, 96),
+                (total, total, 100),
+            ]
+        );
+        assert_eq!(store.indexer.num_docs(), total);
+    }
+
+    #[tokio::test]
+    async fn deleted_items_wait_in_the_trash_until_emptied() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let profile = ListPresentationProfile::CompactRow;
+        let kept = store.save_text("invoice march".into(), None, None).unwrap();
+        let trashed = store.save_text("invoice april".into(), None, None).unwrap();
+        let locked = store.save_text("invoice may".into(), None, None).unwrap();
+        store.set_immutable(locked.clone(), true).unwrap();
+
+        store.delete_item(trashed.clone()).unwrap();
+        assert!(matches!(
+            store.delete_item(locked.clone()),
+            Err(ClipKittyError::ItemImmutable(_))
+        ));
+        let result = store.search("invoice".into(), profile).await.unwrap();
+        assert_eq!(result.total_count, 2);
+        let recent = store.search(String::new(), profile).await.unwrap();
+        assert!(recent
+            .matches
+            .iter()
+            .all(|item| item.item_metadata.item_id != trashed));
+        assert_eq!(store.db.count_items().unwrap(), 2);
+        let in_trash = store.trashed_items().unwrap();
+        assert_eq!(in_trash.len(), 1);
+        assert_eq!(in_trash[0].item.item_metadata.item_id, trashed);
+        assert!(store
+            .fetch_by_ids(vec![trashed.clone()])
+            .unwrap()
+            .is_empty());
+        assert!(store.pin_item(trashed.clone()).is_err());
+
+        // The deletion may already have synced, so it comes back as a new item.
+        let restored = store.restore_item(trashed.clone()).unwrap();
+        assert_ne!(restored, trashed);
+        assert!(store.restore_item(trashed).is_err());
+        assert!(store.trashed_items().unwrap().is_empty());
+        let result = store.search("april".into(), profile).await.unwrap();
+        assert_eq!(result.matches[0].item_metadata.item_id, restored);
+
+        store.delete_item(restored.clone()).unwrap();
+        store.delete_item(kept.clone()).unwrap();
+        assert_eq!(store.empty_trash(3600).unwrap(), 0);
+        assert_eq!(store.empty_trash(u64::MAX).unwrap(), 0);
+        assert_eq!(store.empty_trash(0).unwrap(), 2);
+        assert!(store.fetch_by_ids(vec![restored, kept]).unwrap().is_empty());
+        assert!(store.trashed_items().unwrap().is_empty());
+    }
+
+    #[test]
+    fn maintenance_empties_trash_after_thirty_days() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let stale = store.save_text("stale draft".into(), None, None).unwrap();
+        let fresh = store.save_text("fresh draft".into(), None, None).unwrap();
+        store.delete_item(stale.clone()).unwrap();
+        store.delete_item(fresh.clone()).unwrap();
+        let long_ago = (chrono::Utc::now() - chrono::Duration::days(31))
+            .format("%Y-%m-%d %H:%M:%S%.f")
+            .to_string();
+        store
+            .db
+            .get_conn()
+            .unwrap()
+            .execute(
+                "UPDATE items SET deletedAt = ?1 WHERE item_id = ?2",
+                [&long_ago, &stale],
+            )
+            .unwrap();
+
+        assert_eq!(store.run_maintenance().unwrap().items_deleted, 1);
+        let in_trash: Vec<String> = store
+            .trashed_items()
+            .unwrap()
+            .into_iter()
+            .map(|trashed| trashed.item.item_metadata.item_id)
+            .collect();
+        assert_eq!(in_trash, [fresh]);
+    }
+
+    #[tokio::test]
+    async fn discarded_session_leaves_history_as_it_was() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_path = temp.path().join("clipboard.db");
+        let store = ClipboardStore::open_at_path(&db_path).unwrap();
+        let profile = ListPresentationProfile::CompactRow;
+        let kept = store
+            .save_text("quarterly notes".into(), None, None)
+            .unwrap();
+        let kept_row = store.require_row_id(&kept).unwrap();
+        let earlier = chrono::Utc::now() - chrono::Duration::days(3);
+        store.db.update_timestamp(kept_row, earlier).unwrap();
+
+        store.begin_session().unwrap();
+        assert!(store.begin_session().is_err());
+        let draft = store
+            .save_text("quarterly draft".into(), None, None)
+            .unwrap();
+        store
+            .save_text("quarterly notes".into(), None, None)
+            .unwrap();
+        let during = store.search("quarterly".into(), profile).await.unwrap();
+        assert_eq!(during.total_count, 2);
+        let touched = store.fetch_by_ids(vec![kept.clone()]).unwrap().remove(0);
+        assert!(touched.item_metadata.timestamp_unix > earlier.timestamp());
+
+        assert_eq!(store.discard_session().unwrap(), 1);
+        assert!(!store.is_session_open().unwrap());
+        assert!(store.fetch_by_ids(vec![draft]).unwrap().is_empty());
+        let kept_item = store.fetch_by_ids(vec![kept.clone()]).unwrap().remove(0);
+        assert_eq!(kept_item.item_metadata.timestamp_unix, earlier.timestamp());
+        let after = store.search("quarterly".into(), profile).await.unwrap();
+        assert_eq!(after.total_count, 1);
+
+        store.begin_session().unwrap();
+        let confirmed = store
+            .save_text("quarterly plan".into(), None, None)
+            .unwrap();
+        assert_eq!(store.commit_session().unwrap(), 1);
+        assert_eq!(store.fetch_by_ids(vec![confirmed]).unwrap().len(), 1);
+        assert!(store.discard_session().is_err());
+
+        // A session still open when the store closes is discarded on reopen.
+        store.begin_session().unwrap();
+        let abandoned = store
+            .save_text("quarterly scratch".into(), None, None)
+            .unwrap();
+        drop(store);
+        let store = ClipboardStore::open_at_path(&db_path).unwrap();
+        assert!(!store.is_session_open().unwrap());
+        assert!(store.fetch_by_ids(vec![abandoned]).unwrap().is_empty());
+        assert_eq!(store.db.count_items().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn read_only_store_searches_while_the_writer_keeps_writing() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_path = temp.path().join("clipboard.db");
+        let writer = ClipboardStore::open_at_path(&db_path).unwrap();
+        let profile = ListPresentationProfile::CompactRow;
+        writer
+            .save_text("lighthouse keeper".into(), None, None)
+            .unwrap();
+
+        let reader = ClipboardStore::open_read_only(db_path.to_string_lossy().to_string()).unwrap();
+        let found = reader.search("lighthouse".into(), profile).await.unwrap();
+        assert_eq!(found.total_count, 1);
+        assert!(matches!(
+            reader.save_text("from the widget".into(), None, None),
+            Err(ClipKittyError::ReadOnly)
+        ));
+        assert!(matches!(
+            reader.rebuild_index(),
+            Err(ClipKittyError::ReadOnly)
+        ));
+
+        // The writer is unaffected, and the reader catches up with it.
+        let added = writer
+            .save_text("lighthouse lamp".into(), None, None)
+            .unwrap();
+        assert_eq!(reader.fetch_by_ids(vec![added]).unwrap().len(), 1);
+        let mut count = 0;
+        for _ in 0..50 {
+            count = reader
+                .search("lighthouse".into(), profile)
+                .await
+                .unwrap()
+                .total_count;
+            if count == 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn read_only_store_refuses_synced_folders() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_path = temp.path().join("Dropbox").join("clipboard.db");
+        std::fs::create_dir(db_path.parent().unwrap()).unwrap();
+        drop(ClipboardStore::open_at_path(&db_path).unwrap());
+
+        assert!(matches!(
+            ClipboardStore::open_read_only(db_path.to_string_lossy().into_owned()),
+            Err(ClipKittyError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn backups_taken_during_writes_restore_and_search() {
+        let temp = tempfile::tempdir().unwrap();
+        let store =
+            Arc::new(ClipboardStore::open_at_path(&temp.path().join("clipboard.db")).unwrap());
+        store
+            .save_text("ferry schedule".into(), None, None)
+            .unwrap();
+        let backups = temp.path().join("backups");
+        std::fs::create_dir(&backups).unwrap();
+
+        let writer = {
+            let store = Arc::clone(&store);
+            std::thread::spawn(move || {
+                for i in 0..100 {
+                    store
+                        .save_text(format!("ferry log {i}"), None, None)
+                        .unwrap();
+                }
+            })
+        };
+        let mut created = Vec::new();
+        for n in 0..3 {
+            let path = backups.join(format!("backup-{n}.ckbackup"));
+            created.push(store.create_backup(path.display().to_string()).unwrap());
+        }
+        writer.join().unwrap();
+        let listed = crate::backup::list_backups(backups.display().to_string()).unwrap();
+        assert_eq!(listed.len(), 3);
+
+        // Each backup is one consistent moment of the history, and restoring
+        // it replaces what the other store held.
+        let other = ClipboardStore::open_at_path(&temp.path().join("other.db")).unwrap();
+        other
+            .save_text("only on this Mac".into(), None, None)
+            .unwrap();
+        let profile = ListPresentationProfile::CompactRow;
+        for backup in &created {
+            assert!((1..=101).contains(&backup.item_count));
+            let restored = other.restore_backup(backup.path.clone()).unwrap();
+            assert_eq!(restored.item_count, backup.item_count);
+            let found = other.search("ferry".into(), profile).await.unwrap();
+            assert_eq!(found.total_count, backup.item_count);
+            let gone = other.search("only on this".into(), profile).await.unwrap();
+            assert_eq!(gone.total_count, 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn sectioned_search_places_each_match_in_one_section() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let pinned = store.save_text("ledger pinned".into(), None, None).unwrap();
+        let bookmarked = store
+            .save_text("ledger bookmarked".into(), None, None)
+            .unwrap();
+        let both = store
+            .save_text("ledger pinned bookmark".into(), None, None)
+            .unwrap();
+        for i in 0..4 {
+            store
+                .save_text(format!("ledger history {i}"), None, None)
+                .unwrap();
+        }
+        store.pin_item(pinned.clone()).unwrap();
+        store.pin_item(both.clone()).unwrap();
+        store
+            .add_tag(bookmarked.clone(), ItemTag::Bookmark)
+            .unwrap();
+        store.add_tag(both.clone(), ItemTag::Bookmark).unwrap();
+
+        let snippet = store
+            .create_snippet(
+                "Ledger reply".into(),
+                "Thanks, the ledger is attached.".into(),
+            )
+            .unwrap();
+        let limits = SearchSectionLimits {
+            snippets: 5,
+            pinned: 5,
+            bookmarked: 5,
+            history: 3,
+        };
+        let result = store
+            .search_all_sources("ledger".into(), limits, ListPresentationProfile::CompactRow)
+            .await
+            .unwrap();
+
+        let sections: Vec<_> = result
+            .sections
+            .iter()
+            .map(|section| {
+                let mut ids: Vec<_> = section
+                    .matches
+                    .iter()
+                    .map(|item| item.item_metadata.item_id.clone())
+                    .collect();
+                ids.sort();
+                (section.source, ids, section.total_count)
+            })
+            .collect();
+        let mut pinned_ids = vec![pinned, both];
+        pinned_ids.sort();
+        assert_eq!(sections[0], (SearchSource::Pinned, pinned_ids, 2));
+        assert_eq!(sections[1], (SearchSource::Bookmarked, vec![bookmarked], 1));
+        assert_eq!(sections[2].0, SearchSource::History);
+        assert_eq!(sections[2].1.len(), 3);
+        assert_eq!(sections[2].2, 4);
+        assert_eq!(sections.len(), 3);
+        assert_eq!(result.snippets, [snippet]);
+
+        let empty = store
+            .search_all_sources(
+                "nothing like it".into(),
+                limits,
+                ListPresentationProfile::CompactRow,
+            )
+            .await
+            .unwrap();
+        assert!(empty.sections.is_empty());
+        assert!(empty.snippets.is_empty());
+    }
+
+    #[tokio::test]
+    async fn items_tagged_for_another_locale_are_hidden() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let profile = ListPresentationProfile::CompactRow;
+        let shared = store
+            .save_text("kitty photo shared".into(), None, None)
+            .unwrap();
+        let spanish = store
+            .save_text("kitty photo gato".into(), None, None)
+            .unwrap();
+        let chinese = store
+            .save_text("kitty photo 猫".into(), None, None)
+            .unwrap();
+        store
+            .set_item_locale(spanish.clone(), Some("es".into()))
+            .unwrap();
+        store
+            .set_item_locale(chinese.clone(), Some("zh-Hans".into()))
+            .unwrap();
+        assert!(store
+            .set_item_locale("missing".into(), Some("es".into()))
+            .is_err());
+
+        let visible = |result: SearchResult| {
+            let mut ids: Vec<String> = result
+                .matches
+                .into_iter()
+                .map(|item| item.item_metadata.item_id)
+                .collect();
+            ids.sort();
+            ids
+        };
+        let sorted = |mut ids: Vec<String>| {
+            ids.sort();
+            ids
+        };
+        for query in ["", "ki", "kitty photo"] {
+            store.set_preferred_locale(None).unwrap();
+            let all = store.search(query.into(), profile).await.unwrap();
+            assert_eq!(all.total_count, 3, "{query:?}");
+
+            store
+                .set_preferred_locale(Some("zh-Hans_CN@rg=twzzzz".into()))
+                .unwrap();
+            let result = store.search(query.into(), profile).await.unwrap();
+            assert_eq!(
+                visible(result),
+                sorted(vec![shared.clone(), chinese.clone()]),
+                "{query:?}"
+            );
+
+            store.set_preferred_locale(Some("fr".into())).unwrap();
+            let result = store.search(query.into(), profile).await.unwrap();
+            assert_eq!(visible(result), std::slice::from_ref(&shared), "{query:?}");
+        }
+        assert_eq!(store.preferred_locale().unwrap().as_deref(), Some("fr"));
+    }
+
+    #[tokio::test]
+    async fn rebuild_serves_the_old_index_until_it_swaps_in() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_path = temp.path().join("clipboard.db");
+        let index_path = ClipboardStore::index_path_for_database(&db_path);
+        let store = ClipboardStore::open_at_path(&db_path).unwrap();
+        let profile = ListPresentationProfile::CompactRow;
+        let ids = |result: SearchResult| -> Vec<String> {
+            result
+                .matches
+                .into_iter()
+                .map(|item| item.item_metadata.item_id)
+                .collect()
+        };
+        let alpha = store.save_text("alpha note".into(), None, None).unwrap();
+
+        let staging = store.indexer.begin_rebuild().unwrap();
+        store.fill_index(&staging, &mut |_, _| {}).unwrap();
+        // Writes made mid-rebuild land in the live index and are replayed.
+        let beta = store.save_text("beta note".into(), None, None).unwrap();
+        store.delete_item(alpha.clone()).unwrap();
+        let live = store.search("beta".into(), profile).await.unwrap();
+        assert_eq!(ids(live), vec![beta.clone()]);
+        assert!(store.indexer.begin_rebuild().is_err());
+
+        store
+            .indexer
+            .finish_rebuild(staging, |staging, item_ids| {
+                store.reindex_items(staging, &item_ids)
+            })
+            .unwrap();
+        let rebuilt = store.search("note".into(), profile).await.unwrap();
+        assert_eq!(ids(rebuilt), vec![beta]);
+
+        let siblings: Vec<_> = std::fs::read_dir(index_path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.starts_with("tantivy_index"))
+            .collect();
+        assert_eq!(siblings.len(), 1, "{siblings:?}");
+        store.save_text("gamma note".into(), None, None).unwrap();
+        store.rebuild_index().unwrap();
+        let result = store.search("note".into(), profile).await.unwrap();
+        assert_eq!(result.total_count, 2);
+    }
+
+    #[tokio::test]
+    async fn deferred_index_writes_lost_in_a_crash_are_rebuilt() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_path = temp.path().join("clipboard.db");
+        let path_string = db_path.to_string_lossy().into_owned();
+        let profile = ListPresentationProfile::CompactRow;
+        let deferred = IndexCommitPolicy {
+            max_pending_writes: 100,
+            max_delay_ms: 3_600_000,
+        };
+
+        let store = ClipboardStore::open_at_path(&db_path).unwrap();
+        store
+            .save_text("committed note".into(), None, None)
+            .unwrap();
+        store.set_index_commit_policy(deferred.clone()).unwrap();
+        store.save_text("pending note".into(), None, None).unwrap();
+        store.flush_index().unwrap();
+        drop(store);
+        assert_eq!(
+            inspect_store_bootstrap(path_string.clone()).unwrap(),
+            StoreBootstrapPlan::Ready
+        );
+
+        let store = ClipboardStore::open_at_path(&db_path).unwrap();
+        store.set_index_commit_policy(deferred).unwrap();
+        store.save_text("lost note".into(), None, None).unwrap();
+        // Crash before the deferred commit runs.
+        std::mem::forget(store);
+        assert_eq!(
+            inspect_store_bootstrap(path_string.clone()).unwrap(),
+            StoreBootstrapPlan::RebuildIndex
+        );
+
+        let store = ClipboardStore::open_at_path(&db_path).unwrap();
+        store.rebuild_index().unwrap();
+        let result = store.search("note".into(), profile).await.unwrap();
+        assert_eq!(result.total_count, 3);
+        drop(store);
+        assert_eq!(
+            inspect_store_bootstrap(path_string).unwrap(),
+            StoreBootstrapPlan::Ready
+        );
+    }
+
+    #[tokio::test]
+    async fn encrypted_stores_write_no_plaintext_to_disk() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("clipboard.db");
+        let key = vec![7u8; 32];
+        let link = "https://lighthouse.example/SUPERSECRETLINK";
+        {
+            // Saved before encryption, so the migration must seal it.
+            let store = ClipboardStore::open_at_path(&path).unwrap();
+            store.save_text(link.into(), None, None).unwrap();
+        }
+        let open = || {
+            ClipboardStore::new_encrypted(path.to_string_lossy().into_owned(), key.clone()).unwrap()
+        };
+        {
+            let store = open();
+            store
+                .save_text("SUPERSECRETPASSWORD for the lighthouse".into(), None, None)
+                .unwrap();
+            store
+                .create_snippet("keeper".into(), "SUPERSECRETSNIPPET body".into())
+                .unwrap();
+        }
+
+        let mut files = vec![temp.path().to_path_buf()];
+        while let Some(entry) = files.pop() {
+            if entry.is_dir() {
+                files.extend(
+                    std::fs::read_dir(&entry)
+                        .unwrap()
+                        .map(|e| e.unwrap().path()),
+                );
+                continue;
+            }
+            let bytes = std::fs::read(&entry).unwrap().to_ascii_lowercase();
+            for secret in [
+                "supersecretlink",
+                "supersecretpassword",
+                "supersecretsnippet",
+            ] {
+                assert!(
+                    !bytes
+                        .windows(secret.len())
+                        .any(|window| window == secret.as_bytes()),
+                    "{} holds `{secret}`",
+                    entry.display()
+                );
+            }
+        }
+        assert!(!ClipboardStore::index_path_for_database(&path).exists());
+
+        // Reopening fills the in-memory index again.
+        let store = open();
+        let found = store
+            .search(
+                "SUPERSECRETPASSWORD".into(),
+                ListPresentationProfile::CompactRow,
+            )
+            .await
+            .unwrap();
+        assert_eq!(found.matches.len(), 1);
+        assert_eq!(
+            store.list_snippets().unwrap()[0].body,
+            "SUPERSECRETSNIPPET body"
+        );
+        let links = store
+            .search(
+                "lighthouse.example".into(),
+                ListPresentationProfile::CompactRow,
+            )
+            .await
+            .unwrap();
+        let item = store
+            .fetch_by_ids(vec![links.matches[0].item_metadata.item_id.clone()])
+            .unwrap()
+            .remove(0);
+        assert!(matches!(item.content, ClipboardContent::Link { url, .. } if url == link));
+    }
+
+    #[tokio::test]
+    async fn move_store_relocates_database_and_index() {
+        let temp = tempfile::tempdir().unwrap();
+        let old_path = temp.path().join("old").join("clipboard.db");
+        std::fs::create_dir_all(old_path.parent().unwrap()).unwrap();
+        let store = ClipboardStore::open_at_path(&old_path).unwrap();
+        let id = store
+            .save_text("relocated history".into(), None, None)
+            .unwrap();
+
+        let progress = Arc::new(RecordingMoveProgress::default());
+        let new_directory = temp.path().join("external");
+        let new_path = store
+            .move_store(
+                new_directory.to_string_lossy().into_owned(),
+                Some(progress.clone()),
+            )
+            .unwrap();
+        assert_eq!(
+            *progress.stages.lock(),
+            vec![
+                StoreMoveStage::CopyingDatabase,
+                StoreMoveStage::CopyingIndex,
+                StoreMoveStage::Verifying,
+                StoreMoveStage::Finished,
+            ]
+        );
+        assert!(!new_directory.join(".clipkitty-move").exists());
+        assert!(matches!(
+            store.save_text("too late".into(), None, None),
+            Err(ClipKittyError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            store
+                .search("relocated".into(), ListPresentationProfile::CompactRow)
+                .await,
+            Err(ClipKittyError::InvalidInput(_))
+        ));
+
+        drop(store);
+        assert!(!old_path.exists());
+        assert!(!ClipboardStore::index_path_for_database(&old_path).exists());
+
+        let moved = ClipboardStore::open_at_path(Path::new(&new_path)).unwrap();
+        assert_eq!(moved.fetch_by_ids(vec![id.clone()]).unwrap().len(), 1);
+        let result = moved
+            .search("relocated".into(), ListPresentationProfile::CompactRow)
+            .await
+            .unwrap();
+        assert_eq!(result.matches[0].item_metadata.item_id, id);
+    }
+
+    #[test]
+    fn move_store_refuses_an_occupied_destination() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(temp.path().join("a")).unwrap();
+        let store =
+            ClipboardStore::open_at_path(&temp.path().join("a").join("clipboard.db")).unwrap();
+        let occupied = ClipboardStore::open_at_path(&temp.path().join("clipboard.db")).unwrap();
+        drop(occupied);
+
+        let result = store.move_store(temp.path().to_string_lossy().into_owned(), None);
+        assert!(matches!(result, Err(ClipKittyError::InvalidInput(_))));
+        let synced = temp.path().join("Dropbox");
+        let result = store.move_store(synced.to_string_lossy().into_owned(), None);
+        assert!(matches!(result, Err(ClipKittyError::InvalidInput(_))));
+        // A refused move leaves the store serving calls.
+        assert!(!store
+            .save_text("still here".into(), None, None)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn bundle_round_trip_keeps_every_item_type() {
+        let source = ClipboardStore::new_in_memory().unwrap();
+        let text = source
+            .save_text("bundle me".into(), Some("Notes".into()), None)
+            .unwrap();
+        source.add_tag(text.clone(), ItemTag::Bookmark).unwrap();
+        source.set_reminder(text.clone(), 1_700_000_000).unwrap();
+        let link = source
+            .save_text("https://example.com/bundle".into(), None, None)
+            .unwrap();
+        source
+            .update_link_metadata(
+                link.clone(),
+                Some("Example".into()),
+                None,
+                Some(vec![1, 2, 3]),
+            )
+            .unwrap();
+        let image = source
+            .save_image(vec![9; 32], Some(vec![8; 4]), None, None, true)
+            .unwrap();
+        let file = source
+            .save_file(
+                "/tmp/report.pdf".into(),
+                "report.pdf".into(),
+                2048,
+                "com.adobe.pdf".into(),
+                vec![7; 16],
+                FilePreviewSnapshot::Image {
+                    preview_data: vec![6; 8],
+                },
+                None,
+                None,
+            )
+            .unwrap();
+
+        let temp = tempfile::tempdir().unwrap();
+        let bundle = temp.path().join("bundle");
+        let exported = source
+            .export_bundle(bundle.to_string_lossy().into_owned())
+            .unwrap();
+        assert_eq!(exported.item_count, 4);
+        assert_eq!(exported.attachment_count, 5);
+
+        let target = ClipboardStore::new_in_memory().unwrap();
+        let preview = target
+            .preview_import_bundle(bundle.to_string_lossy().into_owned())
+            .unwrap();
+        assert_eq!(preview.new_items, 4);
+        assert!(preview.estimated_growth_bytes > 0);
+        assert_eq!(target.db.count_items().unwrap(), 0);
+        let imported = target
+            .import_bundle(bundle.to_string_lossy().into_owned())
+            .unwrap();
+        assert_eq!(
+            imported,
+            BundleImportSummary {
+                imported: 4,
+                duplicates: 0,
+                conflicts: 0,
+            }
+        );
+        let ids = vec![text.clone(), link, image, file];
+        let fetch_sorted = |store: &ClipboardStore| {
+            let mut items = store.fetch_by_ids(ids.clone()).unwrap();
+            items.sort_by(|a, b| a.item_metadata.item_id.cmp(&b.item_metadata.item_id));
+            items
+        };
+        assert_eq!(fetch_sorted(&target), fetch_sorted(&source));
+        let external_id = source.external_id(text.clone()).unwrap();
+        assert_eq!(
+            target.resolve_external_id(external_id).unwrap(),
+            Some(text.clone())
+        );
+        assert_eq!(
+            target.due_reminders(1_700_000_000).unwrap(),
+            [ItemReminder {
+                item_id: text,
+                fire_at_unix: 1_700_000_000,
+            }]
+        );
+
+        let again = target
+            .import_bundle(bundle.to_string_lossy().into_owned())
+            .unwrap();
+        assert_eq!(again.duplicates, 4);
+        let preview = target
+            .preview_import_bundle(bundle.to_string_lossy().into_owned())
+            .unwrap();
+        assert_eq!((preview.new_items, preview.duplicates), (0, 4));
+        assert_eq!(preview.estimated_growth_bytes, 0);
+    }
+
+    #[test]
+    fn import_from_merges_another_database() {
+        let target = ClipboardStore::new_in_memory().unwrap();
+        let now = chrono::Utc::now().timestamp();
+        let shared = insert_indexed_text_with_timestamp(&target, "shared snippet", now - 500);
+        let local = insert_indexed_text_with_timestamp(&target, "local version", now - 400);
+
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("ClipKitty.sqlite");
+        {
+            let source = Database::open(&path).unwrap();
+            let mut newer = StoredItem::new_text("shared snippet".into(), None, None);
+            newer.timestamp_unix = now - 100;
+            source.insert_item(&newer).unwrap();
+            let mut conflicting = StoredItem::new_text("remote version".into(), None, None);
+            conflicting.item_id = local.item_id.clone();
+            let row_id = source.insert_item(&conflicting).unwrap();
+            reminders::set(&source, row_id, now - 50).unwrap();
+            let mut fresh = StoredItem::new_text("only on the old Mac".into(), None, None);
+            fresh.timestamp_unix = now - 1_000;
+            let row_id = source.insert_item(&fresh).unwrap();
+            source.add_user_tag(row_id, "archive").unwrap();
+            source.set_immutable(row_id, true).unwrap();
+            reminders::set(&source, row_id, now - 60).unwrap();
+        }
+        let path = path.to_string_lossy().into_owned();
 
-                Ok(snapshot)
-            })
-            .collect()
-    }
-}
+        let preview = target.preview_import_from(path.clone()).unwrap();
+        assert_eq!(
+            (preview.new_items, preview.duplicates, preview.conflicts),
+            (1, 1, 1)
+        );
+        let summary = target
+            .import_from(path.clone(), ImportStrategy::Merge)
+            .unwrap();
+        assert_eq!(
+            summary,
+            DatabaseImportSummary {
+                imported: 2,
+                duplicates: 1,
+                conflicts: 1,
+            }
+        );
+        assert_eq!(target.db.count_items().unwrap(), 4);
+        let shared_row = target.db.fetch_items_by_ids(&[shared.id.unwrap()]).unwrap();
+        assert_eq!(shared_row[0].timestamp_unix, now - 100);
+        let fresh = target
+            .db
+            .find_by_hash(
+                &StoredItem::new_text("only on the old Mac".into(), None, None).content_hash,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(fresh.timestamp_unix, now - 1_000);
+        assert_eq!(target.list_tags().unwrap()[0].name, "archive");
+        // Links follow each item by external ID, including the re-IDed copy,
+        // though the source's row IDs collide with the store's.
+        assert!(target.db.is_immutable(fresh.id.unwrap()).unwrap());
+        let remote = target
+            .db
+            .find_by_hash(&StoredItem::new_text("remote version".into(), None, None).content_hash)
+            .unwrap()
+            .unwrap();
+        assert_ne!(remote.item_id, local.item_id);
+        let due: Vec<(String, i64)> = target
+            .due_reminders(now)
+            .unwrap()
+            .into_iter()
+            .map(|reminder| (reminder.item_id, reminder.fire_at_unix))
+            .collect();
+        assert_eq!(
+            due,
+            [
+                (fresh.item_id.clone(), now - 60),
+                (remote.item_id, now - 50)
+            ]
+        );
 
-// ═══════════════════════════════════════════════════════════════════════════════
-// Sync FFI — methods exposed to Swift SyncEngine
-// ═══════════════════════════════════════════════════════════════════════════════
+        let again = target.import_from(path, ImportStrategy::KeepLocal).unwrap();
+        assert_eq!(
+            (again.imported, again.duplicates, again.conflicts),
+            (0, 2, 1)
+        );
+        assert!(target
+            .import_from(
+                temp.path()
+                    .join("missing.sqlite")
+                    .to_string_lossy()
+                    .into_owned(),
+                ImportStrategy::Merge
+            )
+            .is_err());
+    }
 
-#[cfg(feature = "sync")]
-#[uniffi::export]
-impl ClipboardStore {
-    /// Set the device ID used for locally-originated sync events.
-    /// Called by SyncEngine.start() with the stable UUID from UserDefaults.
-    pub fn set_sync_device_id(&self, device_id: String) {
-        self.sync_emitter.set_device_id(device_id);
+    #[test]
+    fn saves_from_excluded_apps_are_dropped() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        store
+            .set_excluded_bundle_ids(vec!["com.agilebits.onepassword7".into()])
+            .unwrap();
+        let password = Some("com.AgileBits.onepassword7".to_string());
+        assert!(store
+            .save_text("hunter2".into(), None, password.clone())
+            .unwrap()
+            .is_empty());
+        assert!(store
+            .save_image(vec![1, 2, 3], None, None, password, false)
+            .unwrap()
+            .is_empty());
+        assert_eq!(store.db.count_items().unwrap(), 0);
+        assert!(!store
+            .save_text("hunter2".into(), None, Some("com.apple.Notes".into()))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            store.get_excluded_bundle_ids().unwrap(),
+            ["com.agilebits.onepassword7"]
+        );
     }
 
-    /// Fetch pending local events that need uploading to CloudKit.
-    pub fn pending_local_events(
-        &self,
-    ) -> Result<Vec<crate::interface::SyncEventRecord>, ClipKittyError> {
-        use crate::interface::SyncEventRecord;
-        use purr_sync::store::SyncStore;
+    #[tokio::test]
+    async fn rich_text_keeps_its_formats_and_searches_as_text() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let rtf = b"{\\rtf1 {\\b Quarterly} report}".to_vec();
+        let html = "<b>Quarterly</b> report".to_string();
+        let rich_id = store
+            .save_rich_text(
+                "Quarterly report".into(),
+                Some(rtf.clone()),
+                Some(html.clone()),
+                None,
+                None,
+            )
+            .unwrap();
+        // The same words copied as plain text are a separate item.
+        let plain_id = store
+            .save_text("Quarterly report".into(), None, None)
+            .unwrap();
+        assert_ne!(rich_id, plain_id);
+        // Without any formatting there is nothing to keep but the text, so
+        // it dedupes against the plain item.
+        assert!(store
+            .save_rich_text(
+                "Quarterly report".into(),
+                Some(Vec::new()),
+                None,
+                None,
+                None
+            )
+            .unwrap()
+            .is_empty());
+        assert_eq!(store.db.count_items().unwrap(), 2);
+
+        let fetched = store.fetch_by_ids(vec![rich_id.clone()]).unwrap();
+        assert_eq!(
+            fetched[0].content,
+            ClipboardContent::RichText {
+                value: "Quarterly report".into(),
+                rtf_data: Some(rtf),
+                html: Some(html),
+            }
+        );
 
-        let sync = SyncStore::new(self.db.pool());
-        let events = sync.fetch_pending_upload_events()?;
-        Ok(events
+        let text_only = ItemQueryFilter::ContentType {
+            content_type: ContentTypeFilter::Text,
+        };
+        let found = store
+            .search_filtered(
+                "quarterly".into(),
+                text_only,
+                ListPresentationProfile::CompactRow,
+            )
+            .await
+            .unwrap();
+        let mut found: Vec<_> = found
+            .matches
             .into_iter()
-            .map(|e| {
-                let payload_type = e.payload_type().to_string();
-                let payload_data = e.payload_data();
-                SyncEventRecord {
-                    event_id: e.event_id,
-                    item_id: e.item_id,
-                    origin_device_id: e.origin_device_id,
-                    schema_version: e.schema_version,
-                    recorded_at: e.recorded_at,
-                    payload_type,
-                    payload_data,
-                }
-            })
-            .collect())
+            .map(|m| m.item_metadata.item_id)
+            .collect();
+        found.sort();
+        let mut expected = vec![rich_id, plain_id];
+        expected.sort();
+        assert_eq!(found, expected);
     }
 
-    /// Fetch compacted snapshots that need uploading to CloudKit.
-    pub fn pending_snapshot_records(
-        &self,
-    ) -> Result<Vec<crate::interface::SyncSnapshotRecord>, ClipKittyError> {
-        use crate::interface::SyncSnapshotRecord;
-        use purr_sync::store::SyncStore;
+    #[tokio::test]
+    async fn contacts_filter_shows_phone_numbers_and_addresses() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let phone_id = store
+            .save_text("+44 20 7946 0958".into(), None, None)
+            .unwrap();
+        let address_id = store
+            .save_text("10 Downing Street\nLondon SW1A 2AA".into(), None, None)
+            .unwrap();
+        store
+            .save_text(
+                "Downing Street press notes, 0958 edition".into(),
+                None,
+                None,
+            )
+            .unwrap();
 
-        let sync = SyncStore::new(self.db.pool());
-        let snapshots = sync.fetch_pending_upload_snapshots()?;
-        Ok(snapshots
-            .into_iter()
-            .map(|s| {
-                let aggregate_data = s.aggregate_data();
-                SyncSnapshotRecord {
-                    item_id: s.item_id,
-                    snapshot_revision: s.snapshot_revision,
-                    schema_version: s.schema_version,
-                    covers_through_event: s.covers_through_event,
-                    aggregate_data,
-                }
-            })
-            .collect())
+        let fetched = store
+            .fetch_by_ids(vec![phone_id.clone(), address_id.clone()])
+            .unwrap();
+        let contents: Vec<_> = fetched.into_iter().map(|item| item.content).collect();
+        assert!(contents.contains(&ClipboardContent::PhoneNumber {
+            value: "+44 20 7946 0958".into()
+        }));
+        assert!(contents.contains(&ClipboardContent::PostalAddress {
+            value: "10 Downing Street\nLondon SW1A 2AA".into()
+        }));
+
+        let contacts = ItemQueryFilter::ContentType {
+            content_type: ContentTypeFilter::Contacts,
+        };
+        for (query, expected) in [("Downing", &address_id), ("0958", &phone_id)] {
+            let found = store
+                .search_filtered(
+                    query.into(),
+                    contacts.clone(),
+                    ListPresentationProfile::CompactRow,
+                )
+                .await
+                .unwrap();
+            let found: Vec<_> = found
+                .matches
+                .into_iter()
+                .map(|m| m.item_metadata.item_id)
+                .collect();
+            assert_eq!(found, vec![expected.clone()], "{query}");
+        }
     }
 
-    /// Mark events as uploaded after CloudKit confirms receipt.
-    pub fn mark_events_uploaded(&self, event_ids: Vec<String>) -> Result<(), ClipKittyError> {
-        use purr_sync::store::SyncStore;
+    #[tokio::test]
+    async fn markdown_lists_as_plain_text_under_the_text_filter() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let source = "# Release notes\n- **Faster** search\n- [Docs](https://example.com)";
+        let id = store.save_text(source.into(), None, None).unwrap();
+
+        let item = store.fetch_by_ids(vec![id.clone()]).unwrap().remove(0);
+        assert_eq!(
+            item.content,
+            ClipboardContent::Markdown {
+                value: "Release notes\n• Faster search\n• Docs".into(),
+                source: source.into(),
+            }
+        );
 
-        let sync = SyncStore::new(self.db.pool());
-        let refs: Vec<&str> = event_ids.iter().map(|s| s.as_str()).collect();
-        sync.mark_events_uploaded(&refs)?;
-        Ok(())
+        let text = ItemQueryFilter::ContentType {
+            content_type: ContentTypeFilter::Text,
+        };
+        let listed = store
+            .search_filtered(String::new(), text, ListPresentationProfile::CompactRow)
+            .await
+            .unwrap();
+        let [found] = listed.matches.as_slice() else {
+            panic!("expected one match, got {:?}", listed.matches);
+        };
+        assert_eq!(
+            found.item_metadata.icon,
+            ItemIcon::Symbol {
+                icon_type: IconType::Markdown
+            }
+        );
+        let RowPresentation::Baseline { excerpt } = &found.presentation else {
+            panic!("expected a baseline row, got {:?}", found.presentation);
+        };
+        assert_eq!(excerpt.text, "Release notes • Faster search • Docs");
     }
 
-    /// Mark a snapshot as uploaded to CloudKit.
-    pub fn mark_snapshot_uploaded(&self, item_id: String) -> Result<(), ClipKittyError> {
-        use purr_sync::store::SyncStore;
+    #[test]
+    fn matched_files_points_at_the_files_a_query_hit() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let paths = [
+            "/Users/me/Desktop/photo.jpg",
+            "/Users/me/Reports/q3-report.pdf",
+        ];
+        let item = StoredItem::new_files(
+            paths.iter().map(|path| path.to_string()).collect(),
+            vec!["photo.jpg".into(), "q3-report.pdf".into()],
+            vec![10, 20],
+            vec!["public.jpeg".into(), "com.adobe.pdf".into()],
+            vec![vec![1], vec![2]],
+            vec![
+                FilePreviewSnapshot::Unavailable {
+                    reason: crate::interface::FilePreviewUnavailableReason::NotCaptured,
+                };
+                2
+            ],
+            None,
+            None,
+        );
+        store.db.insert_item(&item).unwrap();
 
-        let sync = SyncStore::new(self.db.pool());
-        sync.mark_snapshot_uploaded(&item_id)?;
-        Ok(())
+        let matched = store
+            .matched_files(item.item_id.clone(), "report".into())
+            .unwrap();
+        let [file] = matched.as_slice() else {
+            panic!("expected one file, got {matched:?}");
+        };
+        assert_eq!(file.file_index, 1);
+        assert_eq!(file.filename, "q3-report.pdf");
+        let spans = |text: &str, ranges: &[crate::interface::Utf16HighlightRange]| {
+            ranges
+                .iter()
+                .map(|range| text[range.utf16_start as usize..range.utf16_end as usize].to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(spans(&file.filename, &file.filename_highlights), ["report"]);
+        assert!(spans(&file.path, &file.path_highlights).contains(&"report".to_string()));
+
+        let note = store.save_text("report".into(), None, None).unwrap();
+        assert!(store
+            .matched_files(note, "report".into())
+            .unwrap()
+            .is_empty());
+        assert!(store
+            .matched_files("missing".into(), "report".into())
+            .is_err());
     }
 
-    /// Apply a batch of remote events and snapshots.
-    /// Returns a structured outcome indicating whether it's safe to advance
-    /// the CloudKit zone change token.
-    pub fn apply_remote_batch(
-        &self,
-        event_records: Vec<crate::interface::SyncEventRecord>,
-        snapshot_records: Vec<crate::interface::SyncSnapshotRecord>,
-    ) -> Result<crate::interface::SyncDownloadBatchOutcome, ClipKittyError> {
-        use crate::interface::SyncDownloadBatchOutcome;
-        use purr_sync::event::ItemEvent;
-        use purr_sync::replay;
-        use purr_sync::snapshot::ItemSnapshot;
-        use std::collections::HashMap;
+    #[tokio::test]
+    async fn kind_words_filter_search_to_files_of_that_kind() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let save = |path: &str, uti: &str| {
+            let filename = path.rsplit('/').next().unwrap().to_string();
+            store
+                .save_files(
+                    vec![path.into()],
+                    vec![filename],
+                    vec![10],
+                    vec![uti.into()],
+                    vec![path.as_bytes().to_vec()],
+                    vec![FilePreviewSnapshot::Unavailable {
+                        reason: crate::interface::FilePreviewUnavailableReason::NotCaptured,
+                    }],
+                    None,
+                    None,
+                )
+                .unwrap()
+        };
+        let archive = save("/tmp/report-backup.zip", "public.zip-archive");
+        save("/tmp/report.pdf", "com.adobe.pdf");
+        store
+            .save_text("report backup notes".into(), None, None)
+            .unwrap();
 
-        let mut known_local_item_ids: HashMap<String, Option<i64>> = HashMap::new();
-        for item_id in snapshot_records
-            .iter()
-            .map(|record| record.item_id.as_str())
-            .chain(event_records.iter().map(|record| record.item_id.as_str()))
-        {
-            known_local_item_ids
-                .entry(item_id.to_string())
-                .or_insert_with(|| self.db.fetch_row_id_by_item_id(item_id).ok().flatten());
+        for query in ["kind:archive", "report kind:archive", "kind:ARCHIVE backup"] {
+            let found = store
+                .search_filtered(
+                    query.into(),
+                    ItemQueryFilter::All,
+                    ListPresentationProfile::CompactRow,
+                )
+                .await
+                .unwrap();
+            let found: Vec<_> = found
+                .matches
+                .into_iter()
+                .map(|m| m.item_metadata.item_id)
+                .collect();
+            assert_eq!(found, vec![archive.clone()], "{query}");
         }
+    }
 
-        // Apply snapshots first.
-        let mut snapshots_applied: usize = 0;
-        for record in &snapshot_records {
-            let snapshot = ItemSnapshot::from_stored(
-                record.item_id.clone(),
-                record.snapshot_revision,
-                record.schema_version,
-                record.covers_through_event.clone(),
-                &record.aggregate_data,
-                true,
+    #[test]
+    fn media_info_is_read_at_capture_and_replaced_by_the_app() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("memo.wav");
+        let mut wav = b"RIFF\0\0\0\0WAVEfmt \x10\0\0\0\x01\0\x01\0".to_vec();
+        wav.extend_from_slice(&8_000u32.to_le_bytes());
+        wav.extend_from_slice(&16_000u32.to_le_bytes());
+        wav.extend_from_slice(&[2, 0, 16, 0]);
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&80_000u32.to_le_bytes());
+        std::fs::write(&path, wav).unwrap();
+
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let path = path.to_string_lossy().to_string();
+        let id = store
+            .save_files(
+                vec![path.clone(), "/tmp/notes.txt".into()],
+                vec!["memo.wav".into(), "notes.txt".into()],
+                vec![80_044, 10],
+                vec![
+                    "com.microsoft.waveform-audio".into(),
+                    "public.plain-text".into(),
+                ],
+                vec![path.into_bytes(), b"notes".to_vec()],
+                vec![FilePreviewSnapshot::not_captured(); 2],
+                None,
                 None,
             )
-            .map_err(|e| ClipKittyError::InvalidInput(e))?;
-
-            let item_id = snapshot.item_id.clone();
-            let applied = replay::apply_remote_snapshots(self.db.pool(), &[snapshot])?;
-            let local_item_id = self.materialize_current_sync_state(
-                &item_id,
-                true,
-                known_local_item_ids.get(&item_id).copied().flatten(),
-            )?;
-            known_local_item_ids.insert(item_id, local_item_id);
-            if applied > 0 {
-                snapshots_applied += 1;
+            .unwrap();
+        let probed = store.media_info(id.clone()).unwrap();
+        assert_eq!(probed.len(), 1);
+        assert_eq!(probed[0].file_index, 0);
+        assert_eq!(probed[0].summary, "0:05 · PCM");
+
+        let frame = crate::thumbnail::encode_png(&image::RgbaImage::new(8, 6)).unwrap();
+        let info = MediaInfo {
+            duration_ms: Some(5_000),
+            codec: Some("Linear PCM".into()),
+            width: None,
+            height: None,
+        };
+        store
+            .set_media_info(id.clone(), 0, info.clone(), Some(frame.clone()))
+            .unwrap();
+        assert_eq!(store.media_info(id.clone()).unwrap()[0].info, info);
+        let item = store.fetch_by_ids(vec![id.clone()]).unwrap().remove(0);
+        assert!(matches!(
+            item.item_metadata.icon,
+            ItemIcon::Thumbnail { .. }
+        ));
+        let ClipboardContent::File { files, .. } = item.content else {
+            panic!("expected a file item");
+        };
+        assert_eq!(
+            files[0].preview,
+            FilePreviewSnapshot::Image {
+                preview_data: frame
             }
-        }
+        );
 
-        // Convert and apply events as a batch.
-        let events: Vec<ItemEvent> = event_records
-            .iter()
-            .map(|r| {
-                ItemEvent::from_stored(
-                    r.event_id.clone(),
-                    r.item_id.clone(),
-                    r.origin_device_id.clone(),
-                    r.schema_version,
-                    r.recorded_at,
-                    &r.payload_type,
-                    &r.payload_data,
-                )
-                .map_err(|e| ClipKittyError::InvalidInput(e))
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+        assert!(store.set_media_info(id, 2, info, None).is_err());
+    }
 
-        let mut batch_result = replay::apply_remote_event_batch(self.db.pool(), &events)?;
+    #[tokio::test]
+    async fn merge_items_joins_text_in_the_order_given() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let first = store.save_text("milk".into(), None, None).unwrap();
+        let second = store
+            .save_text("https://example.com/eggs".into(), None, None)
+            .unwrap();
+        let third = store.save_text("bread".into(), None, None).unwrap();
 
-        // Materialize Applied events into the read model.
-        // Re-fetch the applied events' aggregates from the sync store.
-        for event_record in &event_records {
-            match self.materialize_current_sync_state(
-                &event_record.item_id,
-                true,
-                known_local_item_ids
-                    .get(&event_record.item_id)
-                    .copied()
-                    .flatten(),
-            ) {
-                Ok(local_item_id) => {
-                    known_local_item_ids.insert(event_record.item_id.clone(), local_item_id);
-                }
-                Err(_) => {
-                    batch_result.materialization_failures += 1;
-                }
+        let merged = store
+            .merge_items(vec![third.clone(), first.clone(), second], "\n".into())
+            .unwrap();
+        let item = store.fetch_by_ids(vec![merged.clone()]).unwrap().remove(0);
+        assert_eq!(
+            item.content,
+            ClipboardContent::Text {
+                value: "bread\nmilk\nhttps://example.com/eggs".into()
             }
-        }
-
-        // Handle fork plans.
-        for (_original_gid, plan) in &batch_result.fork_plans {
-            let _ = self.materialize_forked_snapshot(&plan.forked_snapshot)?;
-        }
+        );
+        let found = store
+            .search("bread milk".into(), ListPresentationProfile::CompactRow)
+            .await
+            .unwrap();
+        assert!(found
+            .matches
+            .iter()
+            .any(|m| m.item_metadata.item_id == merged));
 
-        // Build the download outcome.
-        let outcome = batch_result.download_outcome(snapshots_applied);
-        Ok(match outcome {
-            purr_sync::types::DownloadBatchOutcome::Applied {
-                events_applied,
-                snapshots_applied,
-            } => SyncDownloadBatchOutcome::Applied {
-                events_applied: events_applied as u64,
-                snapshots_applied: snapshots_applied as u64,
-            },
-            purr_sync::types::DownloadBatchOutcome::PartialFailure {
-                applied_count,
-                failed_count,
-                should_retry,
-            } => SyncDownloadBatchOutcome::PartialFailure {
-                applied_count: applied_count as u64,
-                failed_count: failed_count as u64,
-                should_retry,
-            },
-            purr_sync::types::DownloadBatchOutcome::FullResyncRequired => {
-                SyncDownloadBatchOutcome::FullResyncRequired
+        let repeated = store
+            .merge_items(vec![first.clone(), third, first.clone()], " ".into())
+            .unwrap();
+        let item = store.fetch_by_ids(vec![repeated]).unwrap().remove(0);
+        assert_eq!(
+            item.content,
+            ClipboardContent::Text {
+                value: "milk bread milk".into()
             }
-        })
+        );
+
+        assert!(store.merge_items(vec![first.clone()], ", ".into()).is_err());
+        let error = store
+            .merge_items(vec![first, "missing".into()], ", ".into())
+            .unwrap_err();
+        assert!(error.to_string().contains("`missing`"));
     }
 
-    /// Apply a single remote event received from CloudKit.
-    ///
-    /// When an event is Applied, the local items table is updated to reflect
-    /// the new state so the app's read model stays in sync.
-    pub fn apply_remote_event(
-        &self,
-        record: crate::interface::SyncEventRecord,
-    ) -> Result<crate::interface::SyncApplyOutcome, ClipKittyError> {
-        use crate::interface::SyncApplyOutcome;
-        use purr_sync::event::ItemEvent;
-        use purr_sync::replay;
-        use purr_sync::types::{ApplyResult, IgnoreReason};
-
-        let event = ItemEvent::from_stored(
-            record.event_id,
-            record.item_id.clone(),
-            record.origin_device_id,
-            record.schema_version,
-            record.recorded_at,
-            &record.payload_type,
-            &record.payload_data,
+    #[tokio::test]
+    async fn pdfs_are_found_by_their_internal_title() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("scan_0042.pdf");
+        std::fs::write(
+            &path,
+            b"%PDF-1.4\n1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n\
+              2 0 obj\n<< /Type /Pages /Kids [] /Count 12 >>\nendobj\n\
+              3 0 obj\n<< /Title (Apartment lease) /Author (Jane Doe) >>\nendobj\n\
+              trailer\n<< /Root 1 0 R /Info 3 0 R >>\n%%EOF\n",
         )
-        .map_err(|e| ClipKittyError::InvalidInput(e))?;
+        .unwrap();
+
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let path = path.to_string_lossy().to_string();
+        let id = store
+            .save_file(
+                path.clone(),
+                "scan_0042.pdf".into(),
+                300,
+                "com.adobe.pdf".into(),
+                path.into_bytes(),
+                FilePreviewSnapshot::not_captured(),
+                None,
+                None,
+            )
+            .unwrap();
+        let found = store
+            .search("lease".into(), ListPresentationProfile::CompactRow)
+            .await
+            .unwrap();
+        assert_eq!(found.matches[0].item_metadata.item_id, id);
+        let info = store.document_info(id.clone()).unwrap();
+        assert_eq!(info.len(), 1);
+        assert_eq!(info[0].summary, "Apartment lease · Jane Doe · 12 pages");
+
+        let page = crate::thumbnail::encode_png(&image::RgbaImage::new(6, 8)).unwrap();
+        let renamed = DocumentInfo {
+            page_count: Some(12),
+            title: Some("Signed rental contract".into()),
+            author: None,
+        };
+        store
+            .set_document_info(id.clone(), 0, renamed.clone(), Some(page))
+            .unwrap();
+        assert_eq!(store.document_info(id.clone()).unwrap()[0].info, renamed);
+        let found = store
+            .search("rental".into(), ListPresentationProfile::CompactRow)
+            .await
+            .unwrap();
+        assert_eq!(found.matches[0].item_metadata.item_id, id);
+        assert!(matches!(
+            found.matches[0].item_metadata.icon,
+            ItemIcon::Thumbnail { .. }
+        ));
 
-        let fallback_local_item_id = self.db.fetch_row_id_by_item_id(&record.item_id)?;
-        let result = replay::apply_remote_event(self.db.pool(), &event)?;
+        assert!(store.set_document_info(id, 1, renamed, None).is_err());
+    }
 
-        match &result {
-            ApplyResult::Applied(_) => {
-                let _ = self.materialize_current_sync_state(
-                    &record.item_id,
-                    true,
-                    fallback_local_item_id,
-                )?;
-                Ok(SyncApplyOutcome::Applied)
+    #[tokio::test]
+    async fn exported_emails_are_found_by_subject_and_sender() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("8F2A6C1E.eml");
+        std::fs::write(
+            &path,
+            "From: Priya Natarajan <priya@example.com>\r\n\
+             Subject: Offsite venue shortlist\r\n\
+             Date: Tue, 5 Mar 2024 14:30:00 +0000\r\n\
+             \r\n\
+             Three options attached.\r\n",
+        )
+        .unwrap();
+
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let path = path.to_string_lossy().to_string();
+        let id = store
+            .save_file(
+                path.clone(),
+                "8F2A6C1E.eml".into(),
+                120,
+                "com.apple.mail.email".into(),
+                path.into_bytes(),
+                FilePreviewSnapshot::not_captured(),
+                None,
+                None,
+            )
+            .unwrap();
+        for query in ["venue shortlist", "priya"] {
+            let found = store
+                .search(query.into(), ListPresentationProfile::CompactRow)
+                .await
+                .unwrap();
+            assert_eq!(found.matches[0].item_metadata.item_id, id, "{query}");
+        }
+        let info = store.email_info(id).unwrap();
+        assert_eq!(info.len(), 1);
+        assert_eq!(info[0].info.sent_at_unix, Some(1_709_649_000));
+        assert_eq!(
+            info[0].summary,
+            "Offsite venue shortlist · Priya Natarajan <priya@example.com>"
+        );
+    }
+
+    #[tokio::test]
+    async fn archives_are_found_by_the_names_of_their_entries() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("March.zip");
+        std::fs::write(
+            &path,
+            crate::archive_info::tests::zip(&[
+                "March/",
+                "March/invoice-0042.pdf",
+                "March/notes.txt",
+            ]),
+        )
+        .unwrap();
+
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let path = path.to_string_lossy().to_string();
+        let id = store
+            .save_file(
+                path.clone(),
+                "March.zip".into(),
+                300,
+                "public.zip-archive".into(),
+                path.into_bytes(),
+                FilePreviewSnapshot::not_captured(),
+                None,
+                None,
+            )
+            .unwrap();
+        let found = store
+            .search("invoice-0042".into(), ListPresentationProfile::CompactRow)
+            .await
+            .unwrap();
+        assert_eq!(found.matches[0].item_metadata.item_id, id);
+
+        let listings = store.archive_listings(id.clone()).unwrap();
+        assert_eq!(
+            listings[0].listing.entries,
+            ["March/invoice-0042.pdf", "March/notes.txt"]
+        );
+        let row_id = store.require_row_id(&id).unwrap();
+        let item = store.db.fetch_items_by_ids(&[row_id]).unwrap().remove(0);
+        let ClipboardContent::File { files, .. } = item.content else {
+            panic!("expected a file item");
+        };
+        assert_eq!(
+            files[0].preview,
+            FilePreviewSnapshot::Text {
+                text: FileTextPreviewSnapshot::Complete {
+                    sample: "March/invoice-0042.pdf\nMarch/notes.txt".into()
+                }
             }
-            ApplyResult::Ignored(IgnoreReason::AlreadyApplied) => {
-                let _ = self.materialize_current_sync_state(
-                    &record.item_id,
-                    true,
-                    fallback_local_item_id,
-                )?;
-                Ok(SyncApplyOutcome::Ignored)
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_index_removes_ghosts_and_indexes_missing_items() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let profile = ListPresentationProfile::CompactRow;
+        store
+            .save_text("harbor schedule".into(), None, None)
+            .unwrap();
+        store
+            .create_snippet("Sig".into(), "harbor regards".into())
+            .unwrap();
+        assert_eq!(
+            store.verify_index().unwrap(),
+            IndexRepairReport {
+                ghost_documents_removed: 0,
+                missing_documents_added: 0,
             }
-            ApplyResult::Ignored(_) => Ok(SyncApplyOutcome::Ignored),
-            ApplyResult::Deferred(_) => Ok(SyncApplyOutcome::Deferred),
-            ApplyResult::Forked(plan) => {
-                let _ = self.materialize_forked_snapshot(&plan.forked_snapshot)?;
+        );
 
-                Ok(SyncApplyOutcome::Forked {
-                    forked_snapshot_data: serde_json::to_string(&plan.forked_snapshot)
-                        .unwrap_or_default(),
-                })
+        // A document left behind by a delete the index never saw, and an item
+        // whose indexing was lost.
+        store
+            .indexer
+            .add_document("ghost-item", "harbor phantom", 1)
+            .unwrap();
+        let missing = StoredItem::new_text("harbor manifest".into(), None, None);
+        store.db.insert_item(&missing).unwrap();
+        store.indexer.commit().unwrap();
+        assert!(store
+            .indexer
+            .indexed_item_ids()
+            .unwrap()
+            .contains("ghost-item"));
+
+        assert_eq!(
+            store.verify_index().unwrap(),
+            IndexRepairReport {
+                ghost_documents_removed: 1,
+                missing_documents_added: 1,
             }
-        }
+        );
+        let after = store.search("harbor".into(), profile).await.unwrap();
+        let ids: Vec<&str> = after
+            .matches
+            .iter()
+            .map(|item| item.item_metadata.item_id.as_str())
+            .collect();
+        assert!(ids.contains(&missing.item_id.as_str()));
+        assert!(!store
+            .indexer
+            .indexed_item_ids()
+            .unwrap()
+            .contains("ghost-item"));
+        assert_eq!(store.verify_index().unwrap().missing_documents_added, 0);
     }
 
-    /// Apply a remote snapshot received from CloudKit.
-    /// Materializes the snapshot into the read model (items table + index).
-    pub fn apply_remote_snapshot(
-        &self,
-        record: crate::interface::SyncSnapshotRecord,
-    ) -> Result<bool, ClipKittyError> {
-        use purr_sync::replay;
-        use purr_sync::snapshot::ItemSnapshot;
+    #[test]
+    fn integrity_check_finds_orphans_and_compact_removes_them() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let link = store
+            .save_text("https://example.com/docs".into(), None, None)
+            .unwrap();
+        let png = crate::thumbnail::encode_png(&image::RgbaImage::new(4, 4)).unwrap();
+        let image = store.save_image(png, None, None, None, false).unwrap();
+        store.save_text("kept".into(), None, None).unwrap();
+
+        let clean = store.run_integrity_check().unwrap();
+        assert!(clean.problems.is_empty());
+        assert!(clean.corrupted_item_ids.is_empty());
+        assert_eq!(
+            (clean.orphaned_link_rows, clean.orphaned_image_rows),
+            (0, 0)
+        );
 
-        let snapshot = ItemSnapshot::from_stored(
-            record.item_id,
-            record.snapshot_revision,
-            record.schema_version,
-            record.covers_through_event,
-            &record.aggregate_data,
-            true,
-            None,
+        // Databases written with foreign keys off kept child rows of deleted
+        // items, and lost child rows of live ones.
+        let conn = store.db.get_conn().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys=OFF;").unwrap();
+        conn.execute("DELETE FROM items WHERE item_id = ?1", [&link])
+            .unwrap();
+        conn.execute(
+            "DELETE FROM image_items WHERE itemId = (SELECT id FROM items WHERE item_id = ?1)",
+            [&image],
         )
-        .map_err(|e| ClipKittyError::InvalidInput(e))?;
+        .unwrap();
+        conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+        drop(conn);
+
+        let damaged = store.run_integrity_check().unwrap();
+        assert_eq!(damaged.corrupted_item_ids, vec![image]);
+        assert_eq!(
+            (damaged.orphaned_link_rows, damaged.orphaned_image_rows),
+            (1, 0)
+        );
 
-        let item_id = snapshot.item_id.clone();
-        let fallback_local_item_id = self.db.fetch_row_id_by_item_id(&item_id)?;
-        let applied = replay::apply_remote_snapshots(self.db.pool(), &[snapshot])?;
-        let _ = self.materialize_current_sync_state(&item_id, true, fallback_local_item_id)?;
-        Ok(applied > 0)
+        let report = store.compact().unwrap();
+        assert_eq!(report.orphaned_rows_deleted, 1);
+        assert!(report.database_bytes_after <= report.database_bytes_before);
+        assert_eq!(store.run_integrity_check().unwrap().orphaned_link_rows, 0);
     }
 
-    /// Run compaction and retention for all items.
-    pub fn run_compaction(&self) -> Result<crate::interface::CompactionResult, ClipKittyError> {
-        use crate::interface::CompactionResult;
-        use purr_sync::compactor;
+    #[test]
+    fn transform_saves_the_result_only_when_asked() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let id = store.save_text("  ship it  ".into(), None, None).unwrap();
 
-        let items_compacted = compactor::compact_all(self.db.pool())? as u64;
-        // Old compacted events stay local until CloudKit deletion is confirmed so
-        // event cleanup and dedup pruning share one authoritative handoff.
-        let events_purged = 0;
-        let tombstones_purged = compactor::purge_tombstone_snapshots(self.db.pool())? as u64;
+        let trimmed = store
+            .transform(id.clone(), Transformation::TrimWhitespace, false)
+            .unwrap();
+        assert_eq!(trimmed.text, "ship it");
+        assert_eq!(trimmed.saved_item_id, None);
 
-        Ok(CompactionResult {
-            items_compacted,
-            events_purged,
-            tombstones_purged,
-        })
-    }
+        let shouted = store
+            .transform(id.clone(), Transformation::Uppercase, true)
+            .unwrap();
+        let saved_id = shouted.saved_item_id.unwrap();
+        assert_ne!(saved_id, id);
+        let contents: Vec<_> = store
+            .fetch_by_ids(vec![id, saved_id])
+            .unwrap()
+            .into_iter()
+            .map(|item| item.content.text_content().to_string())
+            .collect();
+        assert!(contents.contains(&"  SHIP IT  ".to_string()));
+        assert!(contents.contains(&"  ship it  ".to_string()));
 
-    /// Perform a full resync from the provided snapshots.
-    /// Reconciles CloudKit's explicit live/tombstoned states with local items.
-    pub fn full_resync(
-        &self,
-        snapshot_records: Vec<crate::interface::SyncSnapshotRecord>,
-    ) -> Result<u64, ClipKittyError> {
-        Ok(self
-            .full_resync_with_tail(snapshot_records, Vec::new())?
-            .checkpoints_applied)
+        let err = store
+            .transform("missing".into(), Transformation::Lowercase, false)
+            .unwrap_err();
+        assert!(matches!(err, ClipKittyError::InvalidInput(_)));
     }
 
-    /// Perform a full resync from checkpoints and tail events.
-    /// Clears sync state, applies checkpoints, replays tail events,
-    /// and materializes all live items into the read model.
-    pub fn full_resync_with_tail(
-        &self,
-        snapshot_records: Vec<crate::interface::SyncSnapshotRecord>,
-        tail_event_records: Vec<crate::interface::SyncEventRecord>,
-    ) -> Result<crate::interface::SyncFullResyncResult, ClipKittyError> {
-        use crate::interface::SyncFullResyncResult;
-        use purr_sync::event::ItemEvent;
-        use purr_sync::replay;
-        use purr_sync::snapshot::ItemSnapshot;
-        use purr_sync::store::SyncStore;
-        use std::collections::HashSet;
+    #[test]
+    fn last_transforms_lists_each_use_with_its_paste_target() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let id = store
+            .save_text("**Draft** notes".into(), None, None)
+            .unwrap();
+        assert!(store.last_transforms(id.clone()).unwrap().is_empty());
 
-        let local_snapshots_before_resync = self.local_snapshots_for_full_resync()?;
+        store.set_paste_target(Some("com.apple.mail".into()));
+        store
+            .transform(id.clone(), Transformation::StripFormatting, false)
+            .unwrap();
+        store.set_paste_target(None);
+        store
+            .transform(id.clone(), Transformation::Uppercase, false)
+            .unwrap();
 
-        let remote_snapshots: Vec<ItemSnapshot> = snapshot_records
-            .into_iter()
-            .map(|r| {
-                ItemSnapshot::from_stored(
-                    r.item_id,
-                    r.snapshot_revision,
-                    r.schema_version,
-                    r.covers_through_event,
-                    &r.aggregate_data,
-                    true,
-                    None,
-                )
-                .map_err(|e| ClipKittyError::InvalidInput(e))
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-        let remote_item_ids: HashSet<String> = remote_snapshots
-            .iter()
-            .map(|snapshot| snapshot.item_id.clone())
-            .collect();
-        let mut local_base_snapshots: Vec<ItemSnapshot> = local_snapshots_before_resync
+        let applied: Vec<_> = store
+            .last_transforms(id)
+            .unwrap()
             .into_iter()
-            .filter(|snapshot| !remote_item_ids.contains(&snapshot.item_id))
-            .map(|mut snapshot| {
-                // Local bases are not CloudKit checkpoints. They exist so tail
-                // events with explicit item IDs can apply, while plain absence
-                // from CloudKit still cannot delete local data.
-                snapshot.covers_through_event = None;
-                snapshot
-            })
+            .map(|applied| (applied.transformation, applied.target_bundle_id))
             .collect();
-        let local_base_count = local_base_snapshots.len();
-        let mut resync_snapshots = remote_snapshots;
-        resync_snapshots.append(&mut local_base_snapshots);
+        assert_eq!(
+            applied,
+            [
+                (Transformation::Uppercase, None),
+                (
+                    Transformation::StripFormatting,
+                    Some("com.apple.mail".to_string())
+                ),
+            ]
+        );
+    }
 
-        let tail_events: Vec<ItemEvent> = tail_event_records
-            .into_iter()
-            .map(|r| {
-                ItemEvent::from_stored(
-                    r.event_id,
-                    r.item_id,
-                    r.origin_device_id,
-                    r.schema_version,
-                    r.recorded_at,
-                    &r.payload_type,
-                    &r.payload_data,
-                )
-                .map_err(|e| ClipKittyError::InvalidInput(e))
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+    #[test]
+    fn format_structured_leaves_the_stored_item_as_copied() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let json = r#"{"b":1,"a":[true]}"#;
+        let json_id = store.save_text(json.into(), None, None).unwrap();
+        let note_id = store.save_text("plain note".into(), None, None).unwrap();
 
-        let mut result = replay::full_resync(self.db.pool(), &resync_snapshots, &tail_events)?;
-        result.checkpoints_applied = result.checkpoints_applied.saturating_sub(local_base_count);
+        let formatted = store
+            .format_structured(json_id.clone(), StructuredStyle::Pretty)
+            .unwrap();
+        assert_eq!(formatted.format, StructuredFormat::Json);
+        assert_eq!(
+            formatted.text,
+            "{\n  \"b\": 1,\n  \"a\": [\n    true\n  ]\n}"
+        );
+        assert_eq!(
+            store.fetch_by_ids(vec![json_id]).unwrap()[0].content,
+            ClipboardContent::Text { value: json.into() }
+        );
+        assert!(matches!(
+            store.format_structured(note_id, StructuredStyle::Pretty),
+            Err(ClipKittyError::InvalidInput(_))
+        ));
+    }
 
-        // CloudKit only deletes local data via explicit tombstone snapshots/events.
-        // Absence from the full feed means "missing remote proof", not "deleted".
-        let sync = SyncStore::new(self.db.pool());
-        let all_snapshots = sync.fetch_all_snapshots()?;
-        for snapshot in &all_snapshots {
-            let _ =
-                self.materialize_aggregate(&snapshot.item_id, &snapshot.aggregate, true, None)?;
-        }
-        for (_original_item_id, plan) in &result.fork_plans {
-            let _ = self.materialize_forked_snapshot(&plan.forked_snapshot)?;
-        }
+    #[tokio::test]
+    async fn ocr_text_makes_images_searchable_below_typed_text() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let note_id = store
+            .save_text("Invoice total due".into(), None, None)
+            .unwrap();
+        let image_id = store
+            .save_image(vec![7; 32], None, None, None, false)
+            .unwrap();
+        store
+            .update_image_ocr_text(image_id.clone(), "  Invoice total due\nPay by card ".into())
+            .unwrap();
+        assert!(matches!(
+            store.update_image_ocr_text(note_id.clone(), "text".into()),
+            Err(ClipKittyError::InvalidInput(_))
+        ));
 
-        Ok(SyncFullResyncResult {
-            checkpoints_applied: result.checkpoints_applied as u64,
-            tail_events_applied: result.tail_events_applied as u64,
-            tail_events_ignored: result.tail_events_ignored as u64,
-            tail_events_deferred: result.tail_events_deferred as u64,
-            tail_events_forked: result.tail_events_forked as u64,
-        })
-    }
+        let search = |query: &'static str| {
+            let store = &store;
+            async move {
+                store
+                    .search(query.into(), ListPresentationProfile::CompactRow)
+                    .await
+                    .unwrap()
+                    .matches
+            }
+        };
+        // The image is newer, but a match in recognized text ranks below the
+        // same match in typed content.
+        let matches = search("invoice total").await;
+        let ids: Vec<_> = matches
+            .iter()
+            .map(|m| m.item_metadata.item_id.clone())
+            .collect();
+        assert_eq!(ids, [note_id.clone(), image_id.clone()]);
+        let crate::interface::RowPresentation::Matched { excerpt } = &matches[1].presentation
+        else {
+            panic!("expected an eager match");
+        };
+        assert!(excerpt.text.contains("Invoice total due"));
 
-    /// Get the current sync device state.
-    pub fn get_sync_device_state(
-        &self,
-        device_id: String,
-    ) -> Result<crate::interface::SyncDeviceState, ClipKittyError> {
-        use crate::interface::SyncDeviceState;
-        use purr_sync::store::SyncStore;
-        use purr_sync::types::{FLAG_INDEX_DIRTY, FLAG_NEEDS_FULL_RESYNC};
+        // Describing the image keeps its recognized text searchable.
+        store
+            .update_image_description(image_id.clone(), "a receipt".into())
+            .unwrap();
+        let ids: Vec<_> = search("card")
+            .await
+            .into_iter()
+            .map(|m| m.item_metadata.item_id)
+            .collect();
+        assert_eq!(ids, std::slice::from_ref(&image_id));
 
-        let sync = SyncStore::new(self.db.pool());
-        let token = sync.fetch_zone_change_token(&device_id)?;
-        let needs_resync = sync.get_dirty_flag(FLAG_NEEDS_FULL_RESYNC)?;
-        let index_dirty =
-            sync.get_dirty_flag(FLAG_INDEX_DIRTY)? || sync.count_index_queue_entries()? > 0;
-
-        Ok(SyncDeviceState {
-            device_id,
-            zone_change_token: token,
-            needs_full_resync: needs_resync,
-            index_dirty,
-        })
+        store.update_image_ocr_text(image_id, "   ".into()).unwrap();
+        assert!(search("card").await.is_empty());
+        assert_eq!(search("receipt").await.len(), 1);
     }
 
-    /// Update the device's zone change token after a successful fetch.
-    pub fn update_zone_change_token(
-        &self,
-        device_id: String,
-        token: Option<Vec<u8>>,
-    ) -> Result<(), ClipKittyError> {
-        use purr_sync::store::SyncStore;
-
-        let sync = SyncStore::new(self.db.pool());
-        sync.upsert_device_state(&device_id, token.as_deref())?;
-        Ok(())
+    #[test]
+    fn typed_pref_getters_check_the_stored_type() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let set = |key: &str, value: PrefValue| {
+            store.set_pref("general".into(), key.into(), value).unwrap()
+        };
+        set("sounds", PrefValue::Bool { value: true });
+        set("history_days", PrefValue::Int { value: 30 });
+
+        assert_eq!(
+            store
+                .get_bool_pref("general".into(), "sounds".into())
+                .unwrap(),
+            Some(true)
+        );
+        assert_eq!(
+            store
+                .get_double_pref("general".into(), "history_days".into())
+                .unwrap(),
+            Some(30.0)
+        );
+        assert_eq!(
+            store
+                .get_string_pref("general".into(), "missing".into())
+                .unwrap(),
+            None
+        );
+        assert!(matches!(
+            store.get_bool_pref("general".into(), "history_days".into()),
+            Err(ClipKittyError::InvalidInput(_))
+        ));
     }
 
-    /// Fetch event IDs eligible for CloudKit deletion.
-    /// Only returns events that are compacted, old, AND covered by an uploaded checkpoint.
-    pub fn purgeable_cloud_event_ids(
-        &self,
-        max_age_days: u32,
-    ) -> Result<Vec<String>, ClipKittyError> {
-        use purr_sync::store::SyncStore;
+    #[test]
+    fn ui_state_round_trips_and_rejects_oversized_blobs() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        assert_eq!(store.load_ui_state().unwrap(), None);
 
-        let threshold = chrono::Utc::now().timestamp() - (max_age_days as i64 * 86400);
-        let sync = SyncStore::new(self.db.pool());
-        let ids = sync.fetch_checkpoint_safe_purgeable_events(threshold)?;
-        Ok(ids)
+        store
+            .save_ui_state(b"{\"query\":\"inv\"}".to_vec())
+            .unwrap();
+        store
+            .save_ui_state(b"{\"query\":\"invoice\"}".to_vec())
+            .unwrap();
+        assert_eq!(
+            store.load_ui_state().unwrap().as_deref(),
+            Some(&b"{\"query\":\"invoice\"}"[..])
+        );
+
+        assert!(matches!(
+            store.save_ui_state(vec![0; MAX_UI_STATE_BYTES + 1]),
+            Err(ClipKittyError::InvalidInput(_))
+        ));
     }
 
-    /// Delete local event records after their CloudKit counterparts have been deleted.
-    pub fn purge_cloud_events(&self, event_ids: Vec<String>) -> Result<u64, ClipKittyError> {
-        use purr_sync::store::SyncStore;
+    #[tokio::test]
+    async fn pinned_items_list_first_and_survive_pruning() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let now = chrono::Utc::now().timestamp();
+        let snippet = insert_indexed_text_with_timestamp(&store, "ssh deploy@host", now - 100);
+        let recent = insert_indexed_text_with_timestamp(&store, "latest copy", now);
+        store.indexer.commit().unwrap();
+        store.pin_item(snippet.item_id.clone()).unwrap();
 
-        let sync = SyncStore::new(self.db.pool());
-        let refs: Vec<&str> = event_ids.iter().map(|s| s.as_str()).collect();
-        let count = sync.delete_events_and_dedup_by_ids(&refs)?;
-        Ok(count as u64)
-    }
+        let listed = store
+            .search(String::new(), ListPresentationProfile::CompactRow)
+            .await
+            .unwrap();
+        let listed: Vec<_> = listed
+            .matches
+            .iter()
+            .map(|m| (m.item_metadata.item_id.clone(), m.item_metadata.is_pinned))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                (snippet.item_id.clone(), true),
+                (recent.item_id.clone(), false)
+            ]
+        );
 
-    /// Clear the index_dirty flag (after a successful rebuild).
-    pub fn clear_index_dirty_flag(&self) -> Result<(), ClipKittyError> {
-        use purr_sync::store::SyncStore;
-        use purr_sync::types::FLAG_INDEX_DIRTY;
+        store.prune_to_size(1, 0.0).unwrap();
+        let remaining = store
+            .fetch_by_ids(vec![snippet.item_id.clone(), recent.item_id.clone()])
+            .unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert!(remaining[0].item_metadata.is_pinned);
 
-        let sync = SyncStore::new(self.db.pool());
-        if sync.count_index_queue_entries()? == 0 {
-            sync.set_dirty_flag(FLAG_INDEX_DIRTY, false)?;
-        }
-        Ok(())
+        store.unpin_item(snippet.item_id.clone()).unwrap();
+        let unpinned = store.fetch_by_ids(vec![snippet.item_id]).unwrap();
+        assert!(!unpinned[0].item_metadata.is_pinned);
     }
 
-    /// Enqueue every live item for derived search-index catch-up.
-    pub fn enqueue_full_index_rebuild(&self) -> Result<u64, ClipKittyError> {
-        use purr_sync::store::SyncStore;
+    #[tokio::test]
+    async fn hashtags_and_tag_filters_restrict_search_to_tagged_items() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let now = chrono::Utc::now().timestamp();
+        let tagged = insert_indexed_text_with_timestamp(&store, "quarterly report draft", now - 10);
+        let other = insert_indexed_text_with_timestamp(&store, "quarterly report final", now);
+        store.indexer.commit().unwrap();
+        store
+            .add_user_tag(tagged.item_id.clone(), "#Work".into())
+            .unwrap();
+        assert!(store
+            .add_user_tag(other.item_id.clone(), "two words".into())
+            .is_err());
+
+        let ids = |result: SearchResult| -> Vec<String> {
+            result
+                .matches
+                .into_iter()
+                .map(|m| m.item_metadata.item_id)
+                .collect()
+        };
+        let profile = ListPresentationProfile::CompactRow;
+        for query in ["#work quarterly", "#work", "#work qu"] {
+            let result = store.search(query.into(), profile).await.unwrap();
+            assert_eq!(ids(result), vec![tagged.item_id.clone()], "query {query:?}");
+        }
+        let filter = ItemQueryFilter::UserTagged {
+            filter: TagFilter::Any {
+                tags: vec!["WORK".into()],
+            },
+        };
+        let result = store
+            .search_filtered("report".into(), filter, profile)
+            .await
+            .unwrap();
+        assert_eq!(ids(result), vec![tagged.item_id.clone()]);
 
-        let items = self.db.fetch_all_items()?;
-        let item_ids: Vec<String> = items.into_iter().map(|item| item.item_id).collect();
-        let sync = SyncStore::new(self.db.pool());
-        sync.replace_index_queue_with_full_rebuild(&item_ids)?;
-        Ok(item_ids.len() as u64)
+        // Edits re-index the item without dropping its tags.
+        store
+            .update_text_item(tagged.item_id.clone(), "quarterly report v2".into())
+            .unwrap();
+        let result = store.search("#work report".into(), profile).await.unwrap();
+        assert_eq!(ids(result), vec![tagged.item_id.clone()]);
+        assert_eq!(
+            store.list_tags().unwrap(),
+            vec![TagSummary {
+                name: "work".into(),
+                item_count: 1
+            }]
+        );
+
+        store
+            .remove_user_tag(tagged.item_id.clone(), "work".into())
+            .unwrap();
+        assert!(store.list_tags().unwrap().is_empty());
+        // With no such tag left, `#work` is ordinary query text again.
+        let result = store.search("#work".into(), profile).await.unwrap();
+        assert!(result.matches.is_empty());
     }
 
-    /// Process a bounded number of queued search-index updates.
-    pub fn process_index_queue(
-        &self,
-        max_items: u32,
-    ) -> Result<crate::interface::IndexMaintenanceOutcome, ClipKittyError> {
-        use crate::interface::IndexMaintenanceOutcome;
-        use purr_sync::store::SyncStore;
-        use purr_sync::types::{IndexQueueEntry, FLAG_INDEX_DIRTY};
+    #[tokio::test]
+    async fn excerpt_patches_send_only_changed_highlights() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let id = store
+            .save_text("hello world, hello again".into(), None, None)
+            .unwrap();
+        let content_hash = store
+            .db
+            .fetch_items_by_item_ids(std::slice::from_ref(&id))
+            .unwrap()[0]
+            .content_hash
+            .clone();
+        let request = |query: &str| MatchedExcerptRequest {
+            item_id: id.clone(),
+            query: query.into(),
+            presentation_profile: ListPresentationProfile::CompactRow,
+            content_hash: content_hash.clone(),
+        };
+        let profile = ListPresentationProfile::CompactRow;
+        store.search("hel".into(), profile).await.unwrap();
+        let resolved = store
+            .resolve_matched_excerpts(vec![request("hel")])
+            .unwrap();
+        let MatchedExcerptResolution::Ready {
+            excerpt: before, ..
+        } = &resolved[0]
+        else {
+            panic!("expected an excerpt, got {resolved:?}");
+        };
 
-        let sync = SyncStore::new(self.db.pool());
-        if max_items == 0 {
-            let remaining = sync.count_index_queue_entries()?;
-            sync.set_dirty_flag(FLAG_INDEX_DIRTY, remaining > 0)?;
-            if remaining == 0 {
-                return Ok(IndexMaintenanceOutcome::Completed { processed: 0 });
-            }
-            return Ok(IndexMaintenanceOutcome::MoreRemaining {
-                processed: 0,
-                remaining: remaining as u64,
-            });
-        }
+        store.search("hell".into(), profile).await.unwrap();
+        let patches = store
+            .resolve_matched_excerpt_patches("hel".into(), vec![request("hell")])
+            .unwrap();
+        let resolved = store
+            .resolve_matched_excerpts(vec![request("hell")])
+            .unwrap();
+        let MatchedExcerptResolution::Ready { excerpt: after, .. } = &resolved[0] else {
+            panic!("expected an excerpt, got {resolved:?}");
+        };
+        let MatchedExcerptPatch::Highlights { removed, added, .. } = &patches[0] else {
+            panic!("expected a highlight patch, got {patches:?}");
+        };
+        assert!(!added.is_empty());
+        let mut patched: Vec<_> = before
+            .highlights
+            .iter()
+            .filter(|range| !removed.contains(range))
+            .chain(added)
+            .cloned()
+            .collect();
+        patched.sort_by_key(|range| range.utf16_start);
+        assert_eq!(patched, after.highlights);
+
+        // Without highlights cached for the earlier query, the excerpt is sent whole.
+        let patches = store
+            .resolve_matched_excerpt_patches("world".into(), vec![request("hell")])
+            .unwrap();
+        assert!(
+            matches!(&patches[0], MatchedExcerptPatch::Replaced { excerpt, .. } if excerpt == after)
+        );
+    }
 
-        let entries = sync.fetch_index_queue(max_items as usize)?;
-        if entries.is_empty() {
-            sync.set_dirty_flag(FLAG_INDEX_DIRTY, false)?;
-            return Ok(IndexMaintenanceOutcome::Completed { processed: 0 });
+    #[tokio::test]
+    async fn search_pages_follow_the_full_result_order() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let now = chrono::Utc::now().timestamp();
+        for n in 0..7 {
+            let mut item = StoredItem::new_text(
+                format!("release note {n}"),
+                None,
+                Some(if n % 2 == 0 { "com.a" } else { "com.b" }.into()),
+            );
+            item.timestamp_unix = now - n;
+            store.db.insert_item(&item).unwrap();
+            store
+                .indexer
+                .add_document(&item.item_id, item.content.text_content(), now - n)
+                .unwrap();
         }
+        store.indexer.commit().unwrap();
 
-        let mut processed_queue_keys = Vec::with_capacity(entries.len());
-        for entry in &entries {
-            match entry {
-                IndexQueueEntry::Reset => {
-                    self.indexer.delete_all_documents()?;
-                }
-                IndexQueueEntry::Upsert { item_id } => {
-                    let item = self
-                        .db
-                        .fetch_items_by_item_ids(&[item_id.clone()])?
-                        .into_iter()
-                        .next();
-                    if let Some(item) = item {
-                        let text = item
-                            .file_index_text()
-                            .unwrap_or_else(|| item.text_content().to_string());
-                        self.indexer
-                            .add_document(&item.item_id, &text, item.timestamp_unix)?;
-                    } else {
-                        self.indexer.delete_document(item_id)?;
+        let profile = ListPresentationProfile::CompactRow;
+        let ids = |matches: Vec<ItemMatch>| -> Vec<String> {
+            matches
+                .into_iter()
+                .map(|m| m.item_metadata.item_id)
+                .collect()
+        };
+        let all_pages = |query: &'static str, filter: ItemQueryFilter| {
+            let store = &store;
+            async move {
+                let mut pages = Vec::new();
+                let mut cursor = None;
+                loop {
+                    let page = store
+                        .search_page(query.into(), filter.clone(), cursor, 3, profile)
+                        .await
+                        .unwrap();
+                    assert_eq!(page.first_preview_payload.is_some(), pages.is_empty());
+                    pages.push(ids(page.matches));
+                    cursor = page.next_cursor;
+                    if cursor.is_none() {
+                        return pages;
                     }
                 }
-                IndexQueueEntry::Delete { item_id } => {
-                    self.indexer.delete_document(item_id)?;
-                }
             }
-            processed_queue_keys.push(entry.queue_key());
+        };
+
+        for query in ["release", "re", ""] {
+            let pages = all_pages(query, ItemQueryFilter::All).await;
+            assert_eq!(pages.iter().map(Vec::len).collect::<Vec<_>>(), [3, 3, 1]);
+            let full = store.search(query.into(), profile).await.unwrap();
+            assert_eq!(pages.concat(), ids(full.matches), "query {query:?}");
+        }
+
+        let filter = ItemQueryFilter::SourceApp {
+            bundle_id: "com.b".into(),
+        };
+        let pages = all_pages("release", filter.clone()).await;
+        let full = store
+            .search_filtered("release".into(), filter.clone(), profile)
+            .await
+            .unwrap();
+        assert_eq!(pages[0].len(), 3);
+        assert_eq!(pages.concat(), ids(full.matches));
+
+        let first = store
+            .search_page("release".into(), filter, None, 3, profile)
+            .await
+            .unwrap();
+        assert!(store
+            .search_page(
+                "notes".into(),
+                ItemQueryFilter::All,
+                first.next_cursor,
+                3,
+                profile
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn collect_matches_saves_one_document_citing_each_match() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        for text in [
+            "tardigrade facts: they survive vacuum",
+            "unrelated grocery list",
+            "a tardigrade drawing",
+        ] {
+            store
+                .save_text(text.into(), Some("Notes".into()), None)
+                .unwrap();
         }
 
-        self.indexer.commit()?;
-        sync.remove_index_queue_entries(&processed_queue_keys)?;
-        let remaining = sync.count_index_queue_entries()?;
-        if remaining == 0 {
-            sync.set_dirty_flag(FLAG_INDEX_DIRTY, false)?;
-            return Ok(IndexMaintenanceOutcome::Completed {
-                processed: processed_queue_keys.len() as u64,
-            });
+        let collected = store
+            .collect_matches("tardigrade".into(), ItemQueryFilter::All)
+            .await
+            .unwrap();
+        assert_eq!(collected.match_count, 2);
+        let document = store
+            .fetch_by_ids(vec![collected.item_id.clone()])
+            .unwrap()
+            .remove(0)
+            .content
+            .text_content()
+            .to_string();
+        assert!(document.starts_with("Matches for \"tardigrade\""));
+        assert!(document.contains("they survive vacuum"));
+        assert!(document.contains("a tardigrade drawing"));
+        assert!(!document.contains("grocery"));
+        assert_eq!(document.matches("Source: Notes, ").count(), 2);
+
+        let none = store
+            .collect_matches("axolotl".into(), ItemQueryFilter::All)
+            .await
+            .unwrap();
+        assert_eq!(none.item_id, "");
+        assert_eq!(none.match_count, 0);
+        assert!(store
+            .collect_matches("  ".into(), ItemQueryFilter::All)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn structured_search_takes_values_literally() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let now = chrono::Utc::now().timestamp();
+        let mut id_of = std::collections::HashMap::new();
+        for (age, text) in [
+            (3, "release notes final"),
+            (1, "release -notes draft"),
+            (2, "older release checklist"),
+        ] {
+            let mut item = StoredItem::new_text(text.into(), None, None);
+            item.timestamp_unix = now - age;
+            store.db.insert_item(&item).unwrap();
+            store
+                .indexer
+                .add_document(&item.item_id, text, now - age)
+                .unwrap();
+            id_of.insert(item.item_id, text);
         }
+        store.indexer.commit().unwrap();
 
-        sync.set_dirty_flag(FLAG_INDEX_DIRTY, true)?;
-        Ok(IndexMaintenanceOutcome::MoreRemaining {
-            processed: processed_queue_keys.len() as u64,
-            remaining: remaining as u64,
-        })
-    }
-}
+        let search = |terms: &[&str], phrases: &[&str], excluded: &[&str], sort| {
+            let query = QueryBuilder {
+                terms: terms.iter().map(|value| value.to_string()).collect(),
+                phrases: phrases.iter().map(|value| value.to_string()).collect(),
+                excluded: excluded.iter().map(|value| value.to_string()).collect(),
+                filter: ItemQueryFilter::All,
+                sort,
+                cursor: None,
+                page_size: 10,
+            };
+            let store = &store;
+            let id_of = &id_of;
+            async move {
+                let page = store
+                    .search_structured(query, ListPresentationProfile::CompactRow)
+                    .await
+                    .unwrap();
+                page.matches
+                    .iter()
+                    .map(|m| id_of[&m.item_metadata.item_id])
+                    .collect::<Vec<_>>()
+            }
+        };
 
-#[uniffi::export]
-impl SearchOperation {
-    pub fn cancel(&self) {
-        self.token.cancel();
+        let mut literal = search(&["-notes"], &[], &[], SearchSort::Relevance).await;
+        literal.sort();
+        assert_eq!(literal, ["release -notes draft", "release notes final"]);
+        assert_eq!(
+            search(&[], &["notes final"], &[], SearchSort::Relevance).await,
+            ["release notes final"]
+        );
+        assert_eq!(
+            search(
+                &["release"],
+                &[],
+                &["draft", "older"],
+                SearchSort::Relevance
+            )
+            .await,
+            ["release notes final"]
+        );
+        assert_eq!(
+            search(&["release"], &[], &[], SearchSort::Newest).await,
+            [
+                "release -notes draft",
+                "older release checklist",
+                "release notes final"
+            ]
+        );
     }
 
-    pub async fn await_result(&self) -> Result<SearchOutcome, ClipKittyError> {
-        self.completion.wait().await
+    #[tokio::test]
+    async fn shared_thumbnails_hand_off_ranges_of_the_cache_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache_path = temp.path().join("thumbnails.cache");
+        let store = ClipboardStore::new_in_memory().unwrap();
+        for (image, thumbnail) in [(1u8, 7u8), (2, 7), (3, 8)] {
+            store
+                .save_image(vec![image; 32], Some(vec![thumbnail; 4]), None, None, false)
+                .unwrap();
+        }
+        store
+            .enable_shared_thumbnails(cache_path.to_string_lossy().into_owned())
+            .unwrap();
+
+        let profile = ListPresentationProfile::CompactRow;
+        let result = store.search(String::new(), profile).await.unwrap();
+        let cache = std::fs::read(&cache_path).unwrap();
+        let mut ranges = Vec::new();
+        for item in &result.matches {
+            let ItemIcon::SharedThumbnail { offset, length } = item.item_metadata.icon else {
+                panic!(
+                    "expected a shared thumbnail, got {:?}",
+                    item.item_metadata.icon
+                );
+            };
+            ranges.push((offset, length));
+            let (start, end) = (offset as usize, (offset + length) as usize);
+            assert!(cache[start..end] == [7; 4] || cache[start..end] == [8; 4]);
+        }
+        ranges.sort_unstable();
+        ranges.dedup();
+        assert_eq!(ranges.len(), 2, "identical thumbnails share one range");
+
+        store.disable_shared_thumbnails();
+        let result = store.search(String::new(), profile).await.unwrap();
+        assert!(result
+            .matches
+            .iter()
+            .all(|item| matches!(item.item_metadata.icon, ItemIcon::Thumbnail { .. })));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::models::StoredItem;
+    #[tokio::test]
+    async fn index_only_search_renders_rows_from_the_index() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let saved = store
+            .save_text(
+                "deploy checklist".into(),
+                Some("Notes".into()),
+                Some("com.apple.Notes".into()),
+            )
+            .unwrap();
+        // Indexed without a stored row, as by an older build.
+        let legacy = insert_indexed_text_with_timestamp(
+            &store,
+            "deploy rollback notes",
+            chrono::Utc::now().timestamp() - 60,
+        );
+        store.indexer.commit().unwrap();
 
-    fn insert_indexed_text_with_timestamp(
-        store: &ClipboardStore,
-        content: &str,
-        timestamp_unix: i64,
-    ) -> StoredItem {
-        let mut item = StoredItem::new_text(content.to_string(), None, None);
-        item.timestamp_unix = timestamp_unix;
-        let row_id = store.db.insert_item(&item).unwrap();
-        item.id = Some(row_id);
+        let profile = ListPresentationProfile::CompactRow;
+        let rows = |result: SearchResult| -> Vec<(String, Option<String>)> {
+            result
+                .matches
+                .into_iter()
+                .map(|m| {
+                    (
+                        m.item_metadata.item_id,
+                        m.item_metadata.source_app_bundle_id,
+                    )
+                })
+                .collect()
+        };
+        let from_sqlite = rows(store.search("deploy".into(), profile).await.unwrap());
+        store.set_index_only_search(true);
+        let from_index = rows(store.search("deploy".into(), profile).await.unwrap());
+        assert_eq!(from_index, from_sqlite);
+        assert_eq!(
+            from_index,
+            [
+                (saved.clone(), Some("com.apple.Notes".into())),
+                (legacy.item_id.clone(), None)
+            ]
+        );
+
+        // With the SQL row gone, only the index can still render the item.
         store
-            .indexer
-            .add_document(&item.item_id, item.content.text_content(), timestamp_unix)
+            .db
+            .get_conn()
+            .unwrap()
+            .execute("DELETE FROM items WHERE item_id = ?1", [&saved])
             .unwrap();
-        item
+        let from_index = rows(store.search("deploy".into(), profile).await.unwrap());
+        assert_eq!(from_index[0].0, saved);
+        store.set_index_only_search(false);
+        let from_sqlite = rows(store.search("deploy".into(), profile).await.unwrap());
+        assert_eq!(from_sqlite, [(legacy.item_id, None)]);
     }
 
-    #[test]
-    fn index_version_v8_dir_name() {
-        // Pins the v8 migration trigger ("Image: " prefix baked into image
-        // descriptions): a revert of INDEX_VERSION would silently reuse an index
-        // built from the un-prefixed descriptions.
-        let path = ClipboardStore::index_path_for_database(Path::new("/tmp/clipkitty/test.db"));
-        assert!(
-            path.ends_with("tantivy_index_v8"),
-            "expected v8 index dir, got {path:?}"
+    #[tokio::test]
+    async fn search_in_app_keeps_only_items_from_that_app() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let now = chrono::Utc::now().timestamp();
+        let insert = |text: &str, bundle_id: Option<&str>, timestamp_unix: i64| {
+            let mut item = StoredItem::new_text(text.into(), None, bundle_id.map(str::to_string));
+            item.timestamp_unix = timestamp_unix;
+            store.db.insert_item(&item).unwrap();
+            store
+                .indexer
+                .add_document(&item.item_id, item.content.text_content(), timestamp_unix)
+                .unwrap();
+            item.item_id
+        };
+        let slack = insert(
+            "standup at ten",
+            Some("com.tinyspeck.slackmacgap"),
+            now - 10,
+        );
+        insert("standup notes", Some("com.apple.Notes"), now);
+        insert("standup, unknown app", None, now);
+        store.indexer.commit().unwrap();
+
+        let profile = ListPresentationProfile::CompactRow;
+        for query in ["standup", "st", ""] {
+            let result = store
+                .search_in_app(query.into(), "com.tinyspeck.slackmacgap".into(), profile)
+                .await
+                .unwrap();
+            let ids: Vec<String> = result
+                .matches
+                .into_iter()
+                .map(|m| m.item_metadata.item_id)
+                .collect();
+            assert_eq!(ids, vec![slack.clone()], "query {query:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn immutable_items_survive_delete_edit_clear_and_prune() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let now = chrono::Utc::now().timestamp();
+        let receipt = insert_indexed_text_with_timestamp(&store, "receipt 8841-22", now - 100);
+        let other = insert_indexed_text_with_timestamp(&store, "scratch note", now);
+        store.indexer.commit().unwrap();
+
+        store.set_immutable(receipt.item_id.clone(), true).unwrap();
+        assert!(store.is_immutable(receipt.item_id.clone()).unwrap());
+        assert_eq!(
+            store.immutable_item_ids().unwrap(),
+            vec![receipt.item_id.clone()]
         );
+        assert!(matches!(
+            store.delete_item(receipt.item_id.clone()),
+            Err(ClipKittyError::ItemImmutable(_))
+        ));
+        assert!(matches!(
+            store.update_text_item(receipt.item_id.clone(), "edited".into()),
+            Err(ClipKittyError::ItemImmutable(_))
+        ));
+
+        // Pruning takes the older items first but passes over the receipt.
+        store.prune_to_size(1, 0.0).unwrap();
+        assert_eq!(store.db.count_items().unwrap(), 1);
+        assert!(store
+            .db
+            .fetch_row_id_by_item_id(&other.item_id)
+            .unwrap()
+            .is_none());
+
+        insert_indexed_text_with_timestamp(&store, "receipt draft", now);
+        store.indexer.commit().unwrap();
+        store.clear().unwrap();
+        assert_eq!(store.db.count_items().unwrap(), 1);
+        let found = store
+            .search("receipt".to_string(), ListPresentationProfile::CompactRow)
+            .await
+            .unwrap();
+        let found: Vec<_> = found
+            .matches
+            .iter()
+            .map(|m| m.item_metadata.item_id.clone())
+            .collect();
+        assert_eq!(found, vec![receipt.item_id.clone()]);
+
+        store.set_immutable(receipt.item_id.clone(), false).unwrap();
+        store.delete_item(receipt.item_id).unwrap();
+        assert_eq!(store.db.count_items().unwrap(), 0);
     }
 
     #[test]
-    fn open_at_path_sweeps_stale_index_dirs() {
+    fn sync_safe_store_shares_history_through_snapshots() {
         let temp = tempfile::tempdir().unwrap();
-        let db_path = temp.path().join("test.db");
-        let stale_dir = temp.path().join("tantivy_index_v6");
-        std::fs::create_dir_all(&stale_dir).unwrap();
-
-        let _store = ClipboardStore::open_at_path(&db_path).unwrap();
+        let snapshot = temp.path().join("Dropbox").join("clipboard.db");
+        let snapshot_path = snapshot.to_string_lossy().into_owned();
+        assert!(matches!(
+            ClipboardStore::new(snapshot_path.clone()),
+            Err(ClipKittyError::InvalidInput(_))
+        ));
 
-        assert!(!stale_dir.exists(), "stale v6 index dir should be removed");
-        assert!(ClipboardStore::index_path_for_database(&db_path).exists());
+        let laptop_dir = temp.path().join("laptop");
+        let laptop = ClipboardStore::open_sync_safe(
+            snapshot_path.clone(),
+            laptop_dir.to_string_lossy().into_owned(),
+        )
+        .unwrap();
+        let id = laptop
+            .save_text("shared between devices".into(), None, None)
+            .unwrap();
+        let desktop_dir = temp.path().join("desktop").to_string_lossy().into_owned();
+        assert!(matches!(
+            ClipboardStore::open_sync_safe(snapshot_path.clone(), desktop_dir.clone()),
+            Err(ClipKittyError::StoreInUse(_))
+        ));
+        laptop.publish_snapshot().unwrap();
+        drop(laptop);
+
+        let desktop = ClipboardStore::open_sync_safe(snapshot_path, desktop_dir.clone()).unwrap();
+        assert_eq!(desktop.fetch_by_ids(vec![id]).unwrap().len(), 1);
+        let local_database = Path::new(&desktop_dir).join("clipboard.db");
+        assert_eq!(
+            inspect_store_bootstrap(local_database.to_string_lossy().into_owned()).unwrap(),
+            StoreBootstrapPlan::RebuildIndex
+        );
     }
 
     #[test]
@@ -1515,6 +6408,114 @@ mod tests {
         assert!(dup.is_empty());
     }
 
+    #[tokio::test]
+    async fn save_texts_batch_dedupes_and_indexes_every_text() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let existing = store.save_text("already here".into(), None, None).unwrap();
+        let input = |text: &str| TextInput {
+            text: text.into(),
+            source_app: Some("Notes".into()),
+            source_app_bundle_id: None,
+        };
+        let ids = store
+            .save_texts_batch(vec![
+                input("batch alpha"),
+                input("batch beta"),
+                input("batch alpha"),
+                input("already here"),
+            ])
+            .unwrap();
+
+        assert_eq!(ids.len(), 4);
+        assert!(!ids[0].is_empty() && !ids[1].is_empty());
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(ids[2], "");
+        assert_eq!(ids[3], "");
+        assert_eq!(store.get_copy_history(ids[0].clone()).unwrap().len(), 2);
+        assert_eq!(store.get_copy_history(existing).unwrap().len(), 2);
+
+        let result = store
+            .search("batch".into(), ListPresentationProfile::CompactRow)
+            .await
+            .unwrap();
+        assert_eq!(result.total_count, 2);
+    }
+
+    #[test]
+    fn rapid_repeat_copies_merge_into_the_first() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let safari = || Some("com.apple.Safari".to_string());
+        let id = store.save_text("selected".into(), None, safari()).unwrap();
+        for _ in 0..3 {
+            assert_eq!(
+                store.save_text("selected".into(), None, safari()).unwrap(),
+                ""
+            );
+        }
+        assert_eq!(store.get_copy_history(id.clone()).unwrap().len(), 1);
+
+        // Without the window, each repeat is a duplicate copy.
+        store.set_repeat_copy_window(0).unwrap();
+        store.save_text("selected".into(), None, safari()).unwrap();
+        assert_eq!(store.get_copy_history(id.clone()).unwrap().len(), 2);
+
+        // A repeat of a deleted item is captured anew.
+        store.set_repeat_copy_window(60_000).unwrap();
+        store.delete_item(id).unwrap();
+        let again = store.save_text("selected".into(), None, safari()).unwrap();
+        assert!(!again.is_empty());
+    }
+
+    #[test]
+    fn copy_history_records_each_duplicate_copy() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let id = store
+            .save_text(
+                "hello world".into(),
+                Some("Safari".into()),
+                Some("com.apple.Safari".into()),
+            )
+            .unwrap();
+        store
+            .save_text("hello world".into(), Some("Notes".into()), None)
+            .unwrap();
+        store
+            .register_app_metadata(vec![AppMetadata {
+                bundle_id: "com.apple.Safari".into(),
+                display_name: "Safari Browser".into(),
+                icon_hash: None,
+                category: None,
+                is_installed: true,
+            }])
+            .unwrap();
+
+        let history = store.get_copy_history(id.clone()).unwrap();
+        let sources: Vec<_> = history
+            .iter()
+            .map(|copy| {
+                (
+                    copy.source_app.as_deref(),
+                    copy.source_app_bundle_id.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            sources,
+            [
+                (Some("Safari Browser"), Some("com.apple.Safari")),
+                (Some("Notes"), None),
+            ]
+        );
+        assert!(history[0].copied_at_unix <= history[1].copied_at_unix);
+
+        let legacy = insert_indexed_text_with_timestamp(&store, "saved before copy logging", 1000);
+        let history = store.get_copy_history(legacy.item_id).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].copied_at_unix, 1000);
+
+        assert!(store.get_copy_history("missing".into()).is_err());
+    }
+
     #[cfg(feature = "sync")]
     #[test]
     fn queued_full_index_rebuild_resets_stale_index_documents() {
@@ -1561,6 +6562,266 @@ mod tests {
         assert_eq!(store.indexer.num_docs(), 1);
     }
 
+    #[test]
+    fn panics_become_internal_errors_with_breadcrumbs() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let result: Result<(), ClipKittyError> =
+            store.contain("explode", || panic!("invariant broken"));
+        let Err(ClipKittyError::Internal(message)) = result else {
+            panic!("expected an internal error, got {result:?}");
+        };
+        assert!(message.starts_with("explode: invariant broken at "));
+
+        let recorded = store.recent_internal_errors(10).unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].operation, "explode");
+        assert!(recorded[0].message.contains("store.rs"));
+        assert!(!recorded[0].backtrace.is_empty());
+        // The store stays usable afterwards.
+        assert!(!store
+            .save_text("after".into(), None, None)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        warnings: Mutex<Vec<StorageWarning>>,
+        item_events: Mutex<Vec<(&'static str, String)>>,
+    }
+
+    impl StoreObserver for RecordingObserver {
+        fn on_storage_warning(&self, warning: StorageWarning) {
+            self.warnings.lock().push(warning);
+        }
+
+        fn on_item_added(&self, item_id: String) {
+            self.item_events.lock().push(("added", item_id));
+        }
+
+        fn on_item_updated(&self, item_id: String) {
+            self.item_events.lock().push(("updated", item_id));
+        }
+
+        fn on_item_deleted(&self, item_id: String) {
+            self.item_events.lock().push(("deleted", item_id));
+        }
+    }
+
+    /// An observer that panics when the store lets go of it.
+    struct PanickingObserver;
+
+    impl StoreObserver for PanickingObserver {
+        fn on_storage_warning(&self, _warning: StorageWarning) {}
+        fn on_item_added(&self, _item_id: String) {}
+        fn on_item_updated(&self, _item_id: String) {}
+        fn on_item_deleted(&self, _item_id: String) {}
+    }
+
+    impl Drop for PanickingObserver {
+        fn drop(&mut self) {
+            panic!("observer torn down");
+        }
+    }
+
+    #[test]
+    fn setters_contain_panics() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        store
+            .set_observer(Some(Arc::new(PanickingObserver)))
+            .unwrap();
+        let result = store.set_observer(None);
+        let Err(ClipKittyError::Internal(message)) = result else {
+            panic!("expected an internal error, got {result:?}");
+        };
+        assert!(message.starts_with("set_observer: observer torn down at "));
+        assert_eq!(
+            store.recent_internal_errors(10).unwrap()[0].operation,
+            "set_observer"
+        );
+        assert!(store
+            .format_excerpt("still\nworks".into(), ListPresentationProfile::CompactRow)
+            .is_ok());
+    }
+
+    #[test]
+    fn observer_hears_items_added_updated_and_deleted() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let observer = Arc::new(RecordingObserver::default());
+        store.set_observer(Some(observer.clone())).unwrap();
+
+        let first = store.save_text("first".into(), None, None).unwrap();
+        let second = store.save_text("second".into(), None, None).unwrap();
+        store.save_text("first".into(), None, None).unwrap();
+        store.pin_item(second.clone()).unwrap();
+        store.delete_item(first.clone()).unwrap();
+        store.clear().unwrap();
+
+        assert_eq!(
+            *observer.item_events.lock(),
+            vec![
+                ("added", first.clone()),
+                ("added", second.clone()),
+                ("updated", first.clone()),
+                ("updated", second.clone()),
+                ("deleted", first),
+                ("deleted", second),
+            ]
+        );
+        assert!(observer.warnings.lock().is_empty());
+    }
+
+    #[test]
+    fn low_disk_pauses_image_capture_and_warns_observer() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let observer = Arc::new(RecordingObserver::default());
+        store.set_observer(Some(observer.clone())).unwrap();
+        store.set_low_disk_threshold(1_000).unwrap();
+
+        store.apply_disk_transition(store.disk_space.observe(10));
+        let image_id = store
+            .save_image(vec![0u8; 64], None, None, None, false)
+            .unwrap();
+        assert!(image_id.is_empty());
+        assert_eq!(store.db.count_items().unwrap(), 0);
+        // Text capture continues in low-disk mode.
+        assert!(!store
+            .save_text("still captured".into(), None, None)
+            .unwrap()
+            .is_empty());
+
+        store.apply_disk_transition(store.disk_space.observe(5_000));
+        assert_eq!(
+            *observer.warnings.lock(),
+            vec![
+                StorageWarning::LowDiskSpace {
+                    available_bytes: 10,
+                    threshold_bytes: 1_000,
+                },
+                StorageWarning::DiskSpaceRecovered {
+                    available_bytes: 5_000,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn full_disk_write_errors_reach_observer() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let observer = Arc::new(RecordingObserver::default());
+        store.set_observer(Some(observer.clone())).unwrap();
+
+        let full = rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_FULL),
+            None,
+        );
+        let error = ClipKittyError::from(crate::database::DatabaseError::from(full));
+        assert!(matches!(
+            store.observe_write::<()>(Err(error)),
+            Err(ClipKittyError::StorageFull)
+        ));
+        assert_eq!(*observer.warnings.lock(), vec![StorageWarning::DiskFull]);
+    }
+
+    #[tokio::test]
+    async fn paste_target_priors_personalize_search_ranking() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let now = chrono::Utc::now().timestamp();
+        let mut from_terminal = StoredItem::new_text(
+            "this is a test 1".to_string(),
+            Some("Terminal".into()),
+            Some("com.apple.Terminal".into()),
+        );
+        from_terminal.timestamp_unix = now - 10;
+        store.db.insert_item(&from_terminal).unwrap();
+        store
+            .indexer
+            .add_document(&from_terminal.item_id, "this is a test 1", now - 10)
+            .unwrap();
+        // Priors come from pasting other Terminal text, so frecency leaves
+        // the matching items alone.
+        let terminal_row = store
+            .db
+            .insert_item(&StoredItem::new_text(
+                "ls -la".to_string(),
+                Some("Terminal".into()),
+                Some("com.apple.Terminal".into()),
+            ))
+            .unwrap();
+        let newer = insert_indexed_text_with_timestamp(&store, "this is a test 2", now - 5);
+        let link = insert_indexed_text_with_timestamp(&store, "https://example.com", now - 20);
+        store.indexer.commit().unwrap();
+
+        for _ in 0..3 {
+            context_service::record_paste(&store.db, terminal_row, "com.apple.dt.Xcode", None)
+                .unwrap();
+        }
+        for _ in 0..2 {
+            context_service::record_paste(&store.db, link.id.unwrap(), "com.apple.dt.Xcode", None)
+                .unwrap();
+        }
+
+        let query = "this is a test".to_string();
+        let plain = store
+            .search(query.clone(), ListPresentationProfile::CompactRow)
+            .await
+            .unwrap();
+        assert_eq!(plain.matches[0].item_metadata.item_id, newer.item_id);
+
+        store.set_paste_target(Some("com.apple.dt.Xcode".into()));
+        let personalized = store
+            .search(query.clone(), ListPresentationProfile::CompactRow)
+            .await
+            .unwrap();
+        assert_eq!(
+            personalized.matches[0].item_metadata.item_id,
+            from_terminal.item_id
+        );
+
+        store.reset_paste_priors(None).unwrap();
+        assert!(store.paste_priors().unwrap().is_empty());
+        let reset = store
+            .search(query, ListPresentationProfile::CompactRow)
+            .await
+            .unwrap();
+        assert_eq!(reset.matches[0].item_metadata.item_id, newer.item_id);
+    }
+
+    #[tokio::test]
+    async fn frequently_pasted_items_outrank_newer_one_offs() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        let now = chrono::Utc::now().timestamp();
+        let command =
+            insert_indexed_text_with_timestamp(&store, "git rebase --onto main", now - 3 * 86_400);
+        let one_off = insert_indexed_text_with_timestamp(&store, "git rebase --onto dev", now - 60);
+        store.indexer.commit().unwrap();
+        let top = || async {
+            store
+                .search(
+                    "git rebase".to_string(),
+                    ListPresentationProfile::CompactRow,
+                )
+                .await
+                .unwrap()
+                .matches[0]
+                .item_metadata
+                .item_id
+                .clone()
+        };
+        assert_eq!(top().await, one_off.item_id);
+
+        // A single paste is not reuse.
+        store
+            .record_paste(one_off.item_id.clone(), "com.apple.Terminal".into(), None)
+            .unwrap();
+        for _ in 0..2 {
+            store
+                .record_paste(command.item_id.clone(), "com.apple.Terminal".into(), None)
+                .unwrap();
+        }
+        assert_eq!(top().await, command.item_id);
+    }
+
     #[tokio::test]
     async fn update_timestamp_refreshes_search_ranking_timestamp() {
         let store = ClipboardStore::new_in_memory().unwrap();
@@ -1602,11 +6863,13 @@ mod tests {
         }
         store.indexer.commit().unwrap();
 
-        let first = store.start_search(
-            "invoice".to_string(),
-            ItemQueryFilter::All,
-            ListPresentationProfile::CompactRow,
-        );
+        let first = store
+            .start_search(
+                "invoice".to_string(),
+                ItemQueryFilter::All,
+                ListPresentationProfile::CompactRow,
+            )
+            .unwrap();
         let _second = store.start_search(
             String::new(),
             ItemQueryFilter::All,
@@ -1616,4 +6879,38 @@ mod tests {
         let outcome = first.await_result().await.unwrap();
         assert_eq!(outcome, SearchOutcome::Cancelled);
     }
+
+    #[tokio::test]
+    async fn packed_search_result_packs_the_same_matches() {
+        let store = ClipboardStore::new_in_memory().unwrap();
+        for i in 0..5i64 {
+            insert_indexed_text_with_timestamp(&store, &format!("invoice draft {i}"), 1000 + i);
+        }
+        store.indexer.commit().unwrap();
+        let start = || {
+            store
+                .start_search(
+                    "invoice".to_string(),
+                    ItemQueryFilter::All,
+                    ListPresentationProfile::CompactRow,
+                )
+                .unwrap()
+        };
+
+        let SearchOutcome::Success { result } = start().await_result().await.unwrap() else {
+            panic!("search was cancelled");
+        };
+        let PackedSearchOutcome::Success { result: packed } =
+            start().await_packed_result().await.unwrap()
+        else {
+            panic!("search was cancelled");
+        };
+        assert_eq!(result.matches.len(), 5);
+        assert_eq!(
+            packed.packed_matches,
+            crate::packed_results::pack_matches(&result.matches)
+        );
+        assert_eq!(packed.total_count, result.total_count);
+        assert_eq!(packed.first_preview_payload, result.first_preview_payload);
+    }
 }
@@ -0,0 +1,538 @@
+//! JSON, XML, and YAML payloads, recognized and laid out for reading.
+//!
+//! Formatting works on the text as copied rather than on a parsed value, so
+//! key order, number spelling, string escapes, and attribute quoting all
+//! survive; only whitespace between tokens changes. JSON is validated with
+//! serde before it is reformatted. XML is checked for balanced tags, and YAML
+//! for lines that are all mappings, sequence items, or block scalar text.
+
+use crate::interface::{FormattedStructure, StructuredFormat, StructuredStyle};
+
+const INDENT: &str = "  ";
+
+/// The format of `text`, if the whole of it is a JSON object or array, a
+/// single XML document, or YAML with at least one mapping (a bare `- ` list
+/// is as likely to be notes).
+pub(crate) fn detect_structured_format(text: &str) -> Option<StructuredFormat> {
+    let trimmed = text.trim();
+    if (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<serde::de::IgnoredAny>(trimmed).is_ok()
+    {
+        return Some(StructuredFormat::Json);
+    }
+    if trimmed.starts_with('<') && xml_tokens(trimmed).is_some() {
+        return Some(StructuredFormat::Xml);
+    }
+    if yaml_lines(trimmed).is_some() {
+        return Some(StructuredFormat::Yaml);
+    }
+    None
+}
+
+/// `text` laid out in `style`, or None if it is not structured data.
+pub(crate) fn format_structured(text: &str, style: StructuredStyle) -> Option<FormattedStructure> {
+    let format = detect_structured_format(text)?;
+    let trimmed = text.trim();
+    let text = match format {
+        StructuredFormat::Json => format_json(trimmed, style),
+        StructuredFormat::Xml => format_xml(&xml_tokens(trimmed)?, style),
+        StructuredFormat::Yaml => format_yaml(&yaml_lines(trimmed)?, style),
+    };
+    Some(FormattedStructure { format, text })
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// JSON
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Reformat already validated JSON. Strings and scalars are copied verbatim.
+fn format_json(text: &str, style: StructuredStyle) -> String {
+    let pretty = style == StructuredStyle::Pretty;
+    let tokens = json_tokens(text);
+    let mut out = String::with_capacity(text.len());
+    let mut depth = 0usize;
+    let mut index = 0;
+    while index < tokens.len() {
+        match tokens[index] {
+            open @ ("{" | "[") => {
+                out.push_str(open);
+                // Empty containers stay on one line.
+                if matches!(tokens.get(index + 1), Some(&("}" | "]"))) {
+                    out.push_str(tokens[index + 1]);
+                    index += 1;
+                } else {
+                    depth += 1;
+                    newline(&mut out, depth, pretty);
+                }
+            }
+            close @ ("}" | "]") => {
+                depth = depth.saturating_sub(1);
+                newline(&mut out, depth, pretty);
+                out.push_str(close);
+            }
+            "," => {
+                out.push(',');
+                newline(&mut out, depth, pretty);
+            }
+            ":" => out.push_str(if pretty { ": " } else { ":" }),
+            scalar => out.push_str(scalar),
+        }
+        index += 1;
+    }
+    out
+}
+
+/// Structural characters, strings, and bare scalars, whitespace dropped.
+fn json_tokens(text: &str) -> Vec<&str> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        let start = index;
+        match bytes[index] {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                index += 1;
+                continue;
+            }
+            b'{' | b'}' | b'[' | b']' | b',' | b':' => index += 1,
+            b'"' => {
+                index += 1;
+                while index < bytes.len() && bytes[index] != b'"' {
+                    index += if bytes[index] == b'\\' { 2 } else { 1 };
+                }
+                index = (index + 1).min(bytes.len());
+            }
+            _ => {
+                while index < bytes.len()
+                    && !matches!(
+                        bytes[index],
+                        b' ' | b'\t' | b'\n' | b'\r' | b'{' | b'}' | b'[' | b']' | b',' | b':'
+                    )
+                {
+                    index += 1;
+                }
+            }
+        }
+        tokens.push(&text[start..index]);
+    }
+    tokens
+}
+
+fn newline(out: &mut String, depth: usize, pretty: bool) {
+    if pretty {
+        out.push('\n');
+        out.push_str(&INDENT.repeat(depth));
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// XML
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, PartialEq)]
+enum XmlToken<'a> {
+    /// `<name ...>`
+    Open(&'a str),
+    /// `</name>`
+    Close(&'a str),
+    /// Self-closing tags, comments, CDATA, declarations, and processing
+    /// instructions: markup that neither opens nor closes an element.
+    Markup(&'a str),
+    Text(&'a str),
+}
+
+/// The document's tokens, or None unless it is one well-formed element,
+/// optionally preceded by a declaration, doctype, or comments.
+fn xml_tokens(text: &str) -> Option<Vec<XmlToken<'_>>> {
+    let mut tokens = Vec::new();
+    let mut open: Vec<&str> = Vec::new();
+    let mut roots = 0;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let Some(body) = rest.strip_prefix('<') else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let text = &rest[..end];
+            if open.is_empty() && !text.trim().is_empty() {
+                return None;
+            }
+            tokens.push(XmlToken::Text(text));
+            rest = &rest[end..];
+            continue;
+        };
+        let (token, len) = if body.starts_with("!--") {
+            let len = rest.find("-->")? + 3;
+            (XmlToken::Markup(&rest[..len]), len)
+        } else if body.starts_with("![CDATA[") {
+            let len = rest.find("]]>")? + 3;
+            (XmlToken::Markup(&rest[..len]), len)
+        } else if body.starts_with('?') {
+            let len = rest.find("?>")? + 2;
+            (XmlToken::Markup(&rest[..len]), len)
+        } else if body.starts_with('!') {
+            let len = rest.find('>')? + 1;
+            (XmlToken::Markup(&rest[..len]), len)
+        } else {
+            let len = tag_len(rest)?;
+            let tag = &rest[..len];
+            let name = tag
+                .trim_start_matches(['<', '/'])
+                .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                .next()
+                .filter(|name| is_xml_name(name))?;
+            if tag.starts_with("</") {
+                if open.pop() != Some(name) {
+                    return None;
+                }
+                (XmlToken::Close(tag), len)
+            } else if tag.ends_with("/>") {
+                roots += usize::from(open.is_empty());
+                (XmlToken::Markup(tag), len)
+            } else {
+                roots += usize::from(open.is_empty());
+                open.push(name);
+                (XmlToken::Open(tag), len)
+            }
+        };
+        tokens.push(token);
+        rest = &rest[len..];
+    }
+    (open.is_empty() && roots == 1).then_some(tokens)
+}
+
+/// Length of the tag at the start of `text`, up to its `>` outside quotes.
+fn tag_len(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return Some(index + 1),
+            (None, '<') if index > 0 => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn is_xml_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == ':')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | ':' | '-' | '.'))
+}
+
+fn format_xml(tokens: &[XmlToken<'_>], style: StructuredStyle) -> String {
+    // Whitespace between tags is layout; text with anything else in it is
+    // content and kept.
+    let tokens: Vec<&XmlToken<'_>> = tokens
+        .iter()
+        .filter(|token| !matches!(token, XmlToken::Text(text) if text.trim().is_empty()))
+        .collect();
+    if style == StructuredStyle::Minified {
+        return tokens
+            .iter()
+            .map(|token| match token {
+                XmlToken::Open(raw)
+                | XmlToken::Close(raw)
+                | XmlToken::Markup(raw)
+                | XmlToken::Text(raw) => *raw,
+            })
+            .collect();
+    }
+    let mut lines: Vec<String> = Vec::new();
+    let mut depth = 0usize;
+    let mut index = 0;
+    while index < tokens.len() {
+        let indent = INDENT.repeat(depth);
+        match (tokens[index], tokens.get(index + 1), tokens.get(index + 2)) {
+            // An element holding only text, or nothing, stays on one line.
+            (XmlToken::Open(open), Some(XmlToken::Text(text)), Some(XmlToken::Close(close))) => {
+                lines.push(format!("{indent}{open}{}{close}", text.trim()));
+                index += 3;
+            }
+            (XmlToken::Open(open), Some(XmlToken::Close(close)), _) => {
+                lines.push(format!("{indent}{open}{close}"));
+                index += 2;
+            }
+            (XmlToken::Open(open), _, _) => {
+                lines.push(format!("{indent}{open}"));
+                depth += 1;
+                index += 1;
+            }
+            (XmlToken::Close(close), _, _) => {
+                depth = depth.saturating_sub(1);
+                lines.push(format!("{}{close}", INDENT.repeat(depth)));
+                index += 1;
+            }
+            (XmlToken::Markup(raw), _, _) => {
+                lines.push(format!("{indent}{raw}"));
+                index += 1;
+            }
+            (XmlToken::Text(text), _, _) => {
+                lines.push(format!("{indent}{}", text.trim()));
+                index += 1;
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// YAML
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum YamlLine<'a> {
+    Blank,
+    /// `# ...`, without its indentation.
+    Comment(&'a str),
+    /// `---` or `...`.
+    DocumentMarker(&'a str),
+    /// A mapping entry or sequence item, and its indentation.
+    Node {
+        indent: usize,
+        content: &'a str,
+    },
+    /// A line of a `|` or `>` block scalar, with its indentation past the
+    /// block's own.
+    BlockText {
+        extra_indent: usize,
+        content: &'a str,
+    },
+}
+
+/// The document's lines, or None unless every line is structural and at
+/// least two are mapping entries or sequence items, one of them a mapping.
+fn yaml_lines(text: &str) -> Option<Vec<YamlLine<'_>>> {
+    let mut lines = Vec::new();
+    let mut nodes = 0;
+    let mut has_mapping = false;
+    // Indentation of the line that opened a block scalar, then of its text.
+    let mut block: Option<(usize, Option<usize>)> = None;
+    for line in text.lines() {
+        let line = line.trim_end();
+        let content = line.trim_start();
+        let indent = line.len() - content.len();
+        if line[..indent].contains('\t') {
+            return None;
+        }
+        if let Some((owner, base)) = block {
+            if content.is_empty() {
+                lines.push(YamlLine::BlockText {
+                    extra_indent: 0,
+                    content: "",
+                });
+                continue;
+            }
+            if indent > owner {
+                let base = base.unwrap_or(indent);
+                block = Some((owner, Some(base)));
+                lines.push(YamlLine::BlockText {
+                    extra_indent: indent.saturating_sub(base),
+                    content,
+                });
+                continue;
+            }
+            block = None;
+        }
+        let parsed = if content.is_empty() {
+            YamlLine::Blank
+        } else if content.starts_with('#') {
+            YamlLine::Comment(content)
+        } else if indent == 0 && (content == "---" || content == "...") {
+            YamlLine::DocumentMarker(content)
+        } else {
+            let entry = content
+                .strip_prefix("- ")
+                .map(str::trim_start)
+                .unwrap_or(content);
+            let is_item = entry.len() != content.len() || content == "-";
+            let is_mapping = is_mapping_entry(entry);
+            if !is_item && !is_mapping {
+                return None;
+            }
+            nodes += 1;
+            has_mapping |= is_mapping;
+            if opens_block_scalar(content) {
+                block = Some((indent, None));
+            }
+            YamlLine::Node { indent, content }
+        };
+        lines.push(parsed);
+    }
+    (nodes >= 2 && has_mapping).then_some(lines)
+}
+
+/// `key:` or `key: value`, with a plain or quoted key.
+fn is_mapping_entry(content: &str) -> bool {
+    let key_len = match content.chars().next() {
+        Some(quote @ ('"' | '\'')) => match content[1..].find(quote) {
+            Some(end) => end + 2,
+            None => return false,
+        },
+        _ => content
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/')))
+            .unwrap_or(content.len()),
+    };
+    key_len > 0
+        && content[key_len..]
+            .strip_prefix(':')
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+}
+
+/// Whether the line ends in a block scalar indicator: `|`, `>-`, `|+2`.
+fn opens_block_scalar(content: &str) -> bool {
+    let Some(last) = content.split_whitespace().last() else {
+        return false;
+    };
+    let mut chars = last.chars();
+    matches!(chars.next(), Some('|' | '>'))
+        && chars.all(|c| matches!(c, '-' | '+') || c.is_ascii_digit())
+        && (content.len() == last.len()
+            || content[..content.len() - last.len()].ends_with(": ")
+            || content[..content.len() - last.len()].ends_with("- "))
+}
+
+/// Re-indent two spaces per level. A sequence item's content is a level of
+/// its own, so keys under `- name:` stay aligned with `name`.
+fn format_yaml(lines: &[YamlLine<'_>], style: StructuredStyle) -> String {
+    let minified = style == StructuredStyle::Minified;
+    let mut out: Vec<String> = Vec::new();
+    // (original indentation, new indentation) of each open level.
+    let mut levels: Vec<(usize, usize)> = Vec::new();
+    let mut node_indent = 0;
+    for line in lines {
+        match *line {
+            YamlLine::Blank => {
+                if !minified && out.last().is_some_and(|last| !last.is_empty()) {
+                    out.push(String::new());
+                }
+            }
+            YamlLine::Comment(comment) => {
+                if !minified {
+                    let indent = levels.last().map_or(0, |&(_, new)| new);
+                    out.push(format!("{}{comment}", " ".repeat(indent)));
+                }
+            }
+            YamlLine::DocumentMarker(marker) => {
+                levels.clear();
+                out.push(marker.to_string());
+            }
+            YamlLine::Node { indent, content } => {
+                while levels
+                    .last()
+                    .is_some_and(|&(original, _)| original > indent)
+                {
+                    levels.pop();
+                }
+                let new = match levels.last() {
+                    Some(&(original, new)) if original == indent => new,
+                    Some(&(_, new)) => {
+                        levels.push((indent, new + INDENT.len()));
+                        new + INDENT.len()
+                    }
+                    None => {
+                        levels.push((indent, 0));
+                        0
+                    }
+                };
+                if let Some(rest) = content.strip_prefix('-') {
+                    let entry = rest.trim_start();
+                    let original_entry = indent + content.len() - entry.len();
+                    levels.push((original_entry, new + INDENT.len()));
+                    out.push(
+                        format!("{}- {entry}", " ".repeat(new))
+                            .trim_end()
+                            .to_string(),
+                    );
+                } else {
+                    out.push(format!("{}{content}", " ".repeat(new)));
+                }
+                node_indent = new;
+            }
+            YamlLine::BlockText {
+                extra_indent,
+                content,
+            } => {
+                if content.is_empty() {
+                    out.push(String::new());
+                } else {
+                    let indent = node_indent + INDENT.len() + extra_indent;
+                    out.push(format!("{}{content}", " ".repeat(indent)));
+                }
+            }
+        }
+    }
+    while out.last().is_some_and(String::is_empty) {
+        out.pop();
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formatted(text: &str, style: StructuredStyle) -> (StructuredFormat, String) {
+        let formatted = format_structured(text, style).unwrap();
+        (formatted.format, formatted.text)
+    }
+
+    #[test]
+    fn json_is_laid_out_without_reordering_or_respelling() {
+        let minified = r#"{"name":"clip, \"kitty\"","tags":[],"sizes":[1,2.50],"nested":{"ok":true,"none":null}}"#;
+        let pretty = "{\n  \"name\": \"clip, \\\"kitty\\\"\",\n  \"tags\": [],\n  \"sizes\": [\n    1,\n    2.50\n  ],\n  \"nested\": {\n    \"ok\": true,\n    \"none\": null\n  }\n}";
+        assert_eq!(
+            formatted(minified, StructuredStyle::Pretty),
+            (StructuredFormat::Json, pretty.to_string())
+        );
+        assert_eq!(
+            formatted(pretty, StructuredStyle::Minified),
+            (StructuredFormat::Json, minified.to_string())
+        );
+    }
+
+    #[test]
+    fn xml_elements_get_a_line_each() {
+        let minified = r#"<?xml version="1.0"?><feed a="x > y"><title>Notes</title><entry id="1"><empty/><body>one  two</body></entry><link></link></feed>"#;
+        let pretty = "<?xml version=\"1.0\"?>\n<feed a=\"x > y\">\n  <title>Notes</title>\n  <entry id=\"1\">\n    <empty/>\n    <body>one  two</body>\n  </entry>\n  <link></link>\n</feed>";
+        assert_eq!(
+            formatted(minified, StructuredStyle::Pretty),
+            (StructuredFormat::Xml, pretty.to_string())
+        );
+        assert_eq!(
+            formatted(pretty, StructuredStyle::Minified),
+            (StructuredFormat::Xml, minified.to_string())
+        );
+    }
+
+    #[test]
+    fn yaml_is_reindented_by_level() {
+        let yaml = "# service\nservice:\n    name: api\n    ports:\n        -   80\n        - 443\n    env:\n    -   name: MODE\n        value: prod\n\n\n    script: |\n        echo hi\n\n          indented\nnext: 1\n";
+        let pretty = "# service\nservice:\n  name: api\n  ports:\n    - 80\n    - 443\n  env:\n  - name: MODE\n    value: prod\n\n  script: |\n    echo hi\n\n      indented\nnext: 1";
+        assert_eq!(
+            formatted(yaml, StructuredStyle::Pretty),
+            (StructuredFormat::Yaml, pretty.to_string())
+        );
+        let minified = "service:\n  name: api\n  ports:\n    - 80\n    - 443\n  env:\n  - name: MODE\n    value: prod\n  script: |\n    echo hi\n\n      indented\nnext: 1";
+        assert_eq!(
+            formatted(yaml, StructuredStyle::Minified),
+            (StructuredFormat::Yaml, minified.to_string())
+        );
+    }
+
+    #[test]
+    fn other_text_is_not_structured() {
+        for text in [
+            "Just a sentence: nothing more",
+            "42",
+            "{ not json }",
+            "<b>bold</b> and <i>italic</i>",
+            "<p>unclosed",
+            "a < b > c",
+            "- one\n- two",
+        ] {
+            assert_eq!(detect_structured_format(text), None, "{text:?}");
+        }
+    }
+}
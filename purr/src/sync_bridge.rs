@@ -42,6 +42,25 @@ pub(crate) fn snapshot_from_stored_item_with_bookmark(
         ClipboardContent::Color { value } => TypeSpecificData::Color {
             value: value.clone(),
         },
+        ClipboardContent::PhoneNumber { value } => TypeSpecificData::PhoneNumber {
+            value: value.clone(),
+        },
+        ClipboardContent::PostalAddress { value } => TypeSpecificData::PostalAddress {
+            value: value.clone(),
+        },
+        ClipboardContent::Markdown { value, source } => TypeSpecificData::Markdown {
+            value: value.clone(),
+            source: source.clone(),
+        },
+        ClipboardContent::RichText {
+            value,
+            rtf_data,
+            html,
+        } => TypeSpecificData::RichText {
+            value: value.clone(),
+            rtf_data_base64: rtf_data.as_deref().map(base64_encode),
+            html: html.clone(),
+        },
         ClipboardContent::Link {
             url,
             metadata_state,
@@ -96,6 +115,7 @@ pub(crate) fn snapshot_from_stored_item_with_bookmark(
     };
 
     ItemSnapshotData {
+        external_id: Some(item.external_id.clone()).filter(|id| !id.is_empty()),
         content_type: item.content.database_type().to_string(),
         content_text: item.content.text_content().to_string(),
         content_hash: item.content_hash.clone(),
@@ -105,6 +125,7 @@ pub(crate) fn snapshot_from_stored_item_with_bookmark(
         is_bookmarked,
         thumbnail_base64,
         color_rgba: item.color_rgba,
+        reminder_at_unix: None,
         type_specific,
     }
 }
@@ -147,6 +168,31 @@ pub(crate) fn stored_item_from_snapshot(
         purr_sync::types::TypeSpecificData::Color { value } => ClipboardContent::Color {
             value: value.clone(),
         },
+        purr_sync::types::TypeSpecificData::PhoneNumber { value } => {
+            ClipboardContent::PhoneNumber {
+                value: value.clone(),
+            }
+        }
+        purr_sync::types::TypeSpecificData::PostalAddress { value } => {
+            ClipboardContent::PostalAddress {
+                value: value.clone(),
+            }
+        }
+        purr_sync::types::TypeSpecificData::Markdown { value, source } => {
+            ClipboardContent::Markdown {
+                value: value.clone(),
+                source: source.clone(),
+            }
+        }
+        purr_sync::types::TypeSpecificData::RichText {
+            value,
+            rtf_data_base64,
+            html,
+        } => ClipboardContent::RichText {
+            value: value.clone(),
+            rtf_data: rtf_data_base64.as_deref().map(base64_decode).transpose()?,
+            html: html.clone(),
+        },
         purr_sync::types::TypeSpecificData::Link { url, metadata } => {
             let metadata_state = match metadata {
                 Some(meta) => {
@@ -203,8 +249,9 @@ pub(crate) fn stored_item_from_snapshot(
         }
     };
 
+    // File thumbnails are derived locally rather than synced.
     let thumbnail = match &content {
-        ClipboardContent::File { .. } => None,
+        ClipboardContent::File { files, .. } => crate::thumbnail::render_file_thumbnail(files),
         _ => snapshot
             .thumbnail_base64
             .as_deref()
@@ -216,6 +263,10 @@ pub(crate) fn stored_item_from_snapshot(
     Ok(crate::models::StoredItem {
         id: None,
         item_id,
+        external_id: snapshot
+            .external_id
+            .clone()
+            .unwrap_or_else(|| crate::models::StoredItem::external_id_at(snapshot.timestamp_unix)),
         content,
         content_hash: snapshot.content_hash.clone(),
         timestamp_unix: snapshot.timestamp_unix,
@@ -223,6 +274,8 @@ pub(crate) fn stored_item_from_snapshot(
         source_app_bundle_id: snapshot.source_app_bundle_id.clone(),
         thumbnail,
         color_rgba: snapshot.color_rgba,
+        ocr_text: None,
+        file_contents_text: None,
     })
 }
 
@@ -263,6 +316,10 @@ pub(crate) trait SyncEmitter: Send + Sync {
         description: &str,
     ) -> Result<(), ClipKittyError>;
 
+    fn emit_reminder_set(&self, item_id: &str, fire_at_unix: i64) -> Result<(), ClipKittyError>;
+
+    fn emit_reminder_cleared(&self, item_id: &str) -> Result<(), ClipKittyError>;
+
     fn set_index_dirty(&self) -> Result<(), ClipKittyError>;
 }
 
@@ -396,7 +453,9 @@ impl SyncEmitter for RealSyncEmitter {
         let event = ItemEvent::new_local(
             item_id.to_string(),
             &self.local_device_id(),
-            ItemEventPayload::ItemCreated { snapshot },
+            ItemEventPayload::ItemCreated {
+                snapshot: Box::new(snapshot),
+            },
         );
         self.append_local_event_and_advance(&event)
     }
@@ -518,6 +577,37 @@ impl SyncEmitter for RealSyncEmitter {
         Ok(())
     }
 
+    fn emit_reminder_set(&self, item_id: &str, fire_at_unix: i64) -> Result<(), ClipKittyError> {
+        let sync = self.sync_store();
+        if let Some(versions) = self.materialized_projection_versions(&sync, item_id)? {
+            let event = ItemEvent::new_local(
+                item_id.to_string(),
+                &self.local_device_id(),
+                ItemEventPayload::ReminderSet {
+                    fire_at_unix,
+                    base_reminder_version: versions.reminder,
+                },
+            );
+            self.append_local_event_and_advance(&event)?;
+        }
+        Ok(())
+    }
+
+    fn emit_reminder_cleared(&self, item_id: &str) -> Result<(), ClipKittyError> {
+        let sync = self.sync_store();
+        if let Some(versions) = self.materialized_projection_versions(&sync, item_id)? {
+            let event = ItemEvent::new_local(
+                item_id.to_string(),
+                &self.local_device_id(),
+                ItemEventPayload::ReminderCleared {
+                    base_reminder_version: versions.reminder,
+                },
+            );
+            self.append_local_event_and_advance(&event)?;
+        }
+        Ok(())
+    }
+
     fn set_index_dirty(&self) -> Result<(), ClipKittyError> {
         let sync = self.sync_store();
         sync.set_dirty_flag(FLAG_INDEX_DIRTY, true)?;
@@ -0,0 +1,476 @@
+//! Stores kept in file-synced folders (iCloud Drive, Dropbox, OneDrive, ...).
+//!
+//! File-level sync uploads SQLite's WAL and Tantivy's segment files
+//! independently of each other, so a live store inside a synced folder shows
+//! up torn on other devices. Such paths are refused for plain stores. In
+//! sync-safe mode the live database and index stay in a local directory and
+//! the synced folder only ever holds a single-file snapshot, replaced
+//! atomically by rename. A lock sentinel beside the snapshot names the device
+//! writing it, so a second device refuses to open over a fresh one.
+
+use crate::database::{Database, DatabaseError};
+use crate::database_import;
+use crate::indexer::Indexer;
+use crate::interface::{ClipKittyError, ImportStrategy};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Path components that mark a folder as synced by a file-sync client.
+const SYNCED_FOLDER_MARKERS: &[&str] = &[
+    "Mobile Documents",
+    "com~apple~CloudDocs",
+    "CloudStorage",
+    "Dropbox",
+    "OneDrive",
+    "Google Drive",
+];
+
+/// A sentinel whose owner hasn't refreshed it for this long is abandoned,
+/// e.g. by a device that crashed or went offline.
+const SENTINEL_STALE_AFTER: Duration = Duration::from_secs(15 * 60);
+
+/// How long a device taking over an abandoned sentinel waits before reading
+/// it back, so a second device taking it over at the same time loses.
+const SENTINEL_CONFIRM_DELAY: Duration = Duration::from_millis(500);
+
+const DEVICE_ID_FILE: &str = ".clipkitty-device-id";
+/// Modification time of the snapshot this device last published or restored.
+const SNAPSHOT_STATE_FILE: &str = ".clipkitty-snapshot-state";
+
+/// Whether `path` lies inside a folder managed by a file-sync client.
+pub(crate) fn is_synced_path(path: &Path) -> bool {
+    path.components().any(|component| {
+        component
+            .as_os_str()
+            .to_str()
+            .is_some_and(|name| SYNCED_FOLDER_MARKERS.contains(&name))
+    })
+}
+
+/// Refuse to open a live (WAL-mode) store inside a synced folder.
+pub(crate) fn refuse_synced_path(path: &Path) -> Result<(), ClipKittyError> {
+    if is_synced_path(path) {
+        return Err(ClipKittyError::InvalidInput(format!(
+            "{} is in a synced folder; open it with open_sync_safe",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Sentinel {
+    device_id: String,
+    pid: u32,
+    updated_at_unix: u64,
+}
+
+/// A claimed single-file snapshot in a synced folder.
+pub(crate) struct SyncedSnapshot {
+    snapshot: PathBuf,
+    local_directory: PathBuf,
+    device_id: String,
+}
+
+impl SyncedSnapshot {
+    /// Claim `snapshot` for this device, failing with `StoreInUse` while
+    /// another device holds a fresh sentinel.
+    pub(crate) fn acquire(snapshot: &Path, local_directory: &Path) -> Result<Self, ClipKittyError> {
+        std::fs::create_dir_all(local_directory).map_err(DatabaseError::from)?;
+        if let Some(parent) = snapshot.parent() {
+            std::fs::create_dir_all(parent).map_err(DatabaseError::from)?;
+        }
+        let claimed = Self {
+            snapshot: snapshot.to_path_buf(),
+            local_directory: local_directory.to_path_buf(),
+            device_id: device_id(local_directory)?,
+        };
+        claimed.claim_sentinel()?;
+        Ok(claimed)
+    }
+
+    /// Create the sentinel with `create_new`, so of two devices claiming a
+    /// free snapshot only one succeeds. An existing sentinel is reused when
+    /// it is this device's, and taken over when it is stale.
+    fn claim_sentinel(&self) -> Result<(), ClipKittyError> {
+        let path = self.sentinel_path();
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(mut file) => {
+                file.write_all(self.sentinel_contents()?.as_bytes())
+                    .and_then(|()| file.sync_all())
+                    .map_err(DatabaseError::from)?;
+                return Ok(());
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(error) => return Err(DatabaseError::from(error).into()),
+        }
+        match self.read_sentinel() {
+            Some(sentinel) if sentinel.device_id == self.device_id => self.write_sentinel(),
+            Some(sentinel) => {
+                let age = unix_now().saturating_sub(sentinel.updated_at_unix);
+                if age < SENTINEL_STALE_AFTER.as_secs() {
+                    return Err(self.in_use_by(&sentinel, age));
+                }
+                self.take_over_sentinel()
+            }
+            // Unreadable: another device may be writing it right now.
+            None if std::fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| {
+                    modified
+                        .elapsed()
+                        .map_or(true, |age| age < SENTINEL_STALE_AFTER)
+                }) =>
+            {
+                Err(ClipKittyError::StoreInUse(format!(
+                    "another device is claiming {}",
+                    self.snapshot.display()
+                )))
+            }
+            None => self.take_over_sentinel(),
+        }
+    }
+
+    /// Overwrite an abandoned sentinel, then read it back after a delay in
+    /// case another device overwrote it too.
+    fn take_over_sentinel(&self) -> Result<(), ClipKittyError> {
+        self.write_sentinel()?;
+        std::thread::sleep(SENTINEL_CONFIRM_DELAY);
+        match self.read_sentinel() {
+            Some(sentinel) if sentinel.device_id == self.device_id => Ok(()),
+            Some(sentinel) => Err(self.in_use_by(&sentinel, 0)),
+            None => Err(ClipKittyError::StoreInUse(format!(
+                "another device is claiming {}",
+                self.snapshot.display()
+            ))),
+        }
+    }
+
+    fn in_use_by(&self, sentinel: &Sentinel, age: u64) -> ClipKittyError {
+        ClipKittyError::StoreInUse(format!(
+            "device {} (pid {}) updated {} {age}s ago",
+            sentinel.device_id,
+            sentinel.pid,
+            self.snapshot.display()
+        ))
+    }
+
+    /// Where the live database lives for this snapshot.
+    pub(crate) fn local_database(&self) -> PathBuf {
+        self.local_directory
+            .join(self.snapshot.file_name().unwrap_or_default())
+    }
+
+    /// Replace the local database with the snapshot when another device
+    /// published since this one last synced. The last published snapshot
+    /// wins, except that items copied here since that sync, which no snapshot
+    /// holds yet, are merged into it first (`import_database`). The local
+    /// index is dropped so it is rebuilt from the new data. Returns whether
+    /// the local database was replaced.
+    pub(crate) fn restore_if_newer(&self, local_index: &Path) -> Result<bool, ClipKittyError> {
+        if self.is_evicted() {
+            return Err(ClipKittyError::InvalidInput(format!(
+                "{} is not downloaded yet",
+                self.snapshot.display()
+            )));
+        }
+        let Some(published) = modified_nanos(&self.snapshot) else {
+            return Ok(false);
+        };
+        let local = self.local_database();
+        let last_seen = self.read_snapshot_state();
+        let replace = match last_seen {
+            Some(seen) => seen != published,
+            // First sync-safe open on this device: adopt the snapshot unless
+            // there is local history, which the next publish will upload.
+            None => !local.exists(),
+        };
+        if !replace {
+            return Ok(false);
+        }
+        let staged = with_suffix(&local, ".restoring");
+        std::fs::copy(&self.snapshot, &staged).map_err(DatabaseError::from)?;
+        Database::verify_snapshot(&staged)?;
+        if let (true, Some(seen)) = (local.exists(), last_seen) {
+            // The staged copy's index is discarded with the local one.
+            // Closing `db` checkpoints its WAL back into the staged file.
+            let merged = Database::open(&staged)
+                .map_err(ClipKittyError::from)
+                .and_then(|db| {
+                    database_import::import_database(
+                        &db,
+                        &Indexer::create_in_ram()?,
+                        &local,
+                        ImportStrategy::Merge,
+                        Some((seen / 1_000_000_000) as i64),
+                        |_| Ok(()),
+                    )
+                });
+            if let Err(error) = merged {
+                for suffix in ["", "-wal", "-shm"] {
+                    let _ = std::fs::remove_file(with_suffix(&staged, suffix));
+                }
+                return Err(error);
+            }
+        }
+        for suffix in ["-wal", "-shm"] {
+            let _ = std::fs::remove_file(with_suffix(&local, suffix));
+        }
+        std::fs::rename(&staged, &local).map_err(DatabaseError::from)?;
+        let _ = std::fs::remove_dir_all(local_index);
+        self.write_snapshot_state(published)?;
+        Ok(true)
+    }
+
+    /// Write a fresh snapshot of `db` and atomically replace the published
+    /// one, so the synced folder never holds a partial file.
+    pub(crate) fn publish(&self, db: &Database) -> Result<(), ClipKittyError> {
+        let staged = self.snapshot.with_file_name(format!(
+            ".{}.publishing",
+            self.snapshot
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+        ));
+        let _ = std::fs::remove_file(&staged);
+        db.snapshot_to(&staged)?;
+        if let Err(error) = std::fs::rename(&staged, &self.snapshot) {
+            let _ = std::fs::remove_file(&staged);
+            return Err(DatabaseError::from(error).into());
+        }
+        if let Some(published) = modified_nanos(&self.snapshot) {
+            self.write_snapshot_state(published)?;
+        }
+        self.write_sentinel()
+    }
+
+    /// Remove the sentinel if this device still owns it.
+    pub(crate) fn release(&self) {
+        if self
+            .read_sentinel()
+            .is_some_and(|sentinel| sentinel.device_id == self.device_id)
+        {
+            let _ = std::fs::remove_file(self.sentinel_path());
+        }
+    }
+
+    /// iCloud replaces files it evicted from local storage with a hidden
+    /// `.<name>.icloud` placeholder.
+    fn is_evicted(&self) -> bool {
+        !self.snapshot.exists()
+            && self
+                .snapshot
+                .with_file_name(format!(
+                    ".{}.icloud",
+                    self.snapshot
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                ))
+                .exists()
+    }
+
+    fn sentinel_path(&self) -> PathBuf {
+        with_suffix(&self.snapshot, ".lock")
+    }
+
+    fn read_sentinel(&self) -> Option<Sentinel> {
+        let contents = std::fs::read_to_string(self.sentinel_path()).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    fn write_sentinel(&self) -> Result<(), ClipKittyError> {
+        write_atomically(&self.sentinel_path(), self.sentinel_contents()?.as_bytes())
+    }
+
+    fn sentinel_contents(&self) -> Result<String, ClipKittyError> {
+        let sentinel = Sentinel {
+            device_id: self.device_id.clone(),
+            pid: std::process::id(),
+            updated_at_unix: unix_now(),
+        };
+        serde_json::to_string(&sentinel)
+            .map_err(|error| ClipKittyError::Internal(error.to_string()))
+    }
+
+    fn read_snapshot_state(&self) -> Option<u128> {
+        std::fs::read_to_string(self.local_directory.join(SNAPSHOT_STATE_FILE))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    fn write_snapshot_state(&self, published: u128) -> Result<(), ClipKittyError> {
+        write_atomically(
+            &self.local_directory.join(SNAPSHOT_STATE_FILE),
+            published.to_string().as_bytes(),
+        )
+    }
+}
+
+/// Stable identifier for this device, kept in the local (unsynced) directory.
+fn device_id(local_directory: &Path) -> Result<String, ClipKittyError> {
+    let path = local_directory.join(DEVICE_ID_FILE);
+    if let Ok(existing) = std::fs::read_to_string(&path) {
+        let existing = existing.trim();
+        if !existing.is_empty() {
+            return Ok(existing.to_string());
+        }
+    }
+    let generated = uuid::Uuid::new_v4().to_string();
+    write_atomically(&path, generated.as_bytes())?;
+    Ok(generated)
+}
+
+fn write_atomically(path: &Path, contents: &[u8]) -> Result<(), ClipKittyError> {
+    let staged = with_suffix(path, ".tmp");
+    std::fs::write(&staged, contents).map_err(DatabaseError::from)?;
+    std::fs::rename(&staged, path).map_err(DatabaseError::from)?;
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    PathBuf::from(format!("{}{suffix}", path.display()))
+}
+
+fn modified_nanos(path: &Path) -> Option<u128> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StoredItem;
+
+    #[test]
+    fn detects_sync_client_folders() {
+        for path in [
+            "/Users/ana/Library/Mobile Documents/com~apple~CloudDocs/ClipKitty/clipboard.db",
+            "/Users/ana/Library/CloudStorage/Dropbox/clipboard.db",
+            "/Users/ana/Dropbox/ClipKitty/clipboard.db",
+        ] {
+            assert!(is_synced_path(Path::new(path)), "{path}");
+        }
+        assert!(!is_synced_path(Path::new(
+            "/Users/ana/Library/Application Support/ClipKitty/clipboard.db"
+        )));
+    }
+
+    #[test]
+    fn fresh_sentinel_from_another_device_refuses_open() {
+        let temp = tempfile::tempdir().unwrap();
+        let snapshot = temp.path().join("synced").join("clipboard.db");
+        let laptop = SyncedSnapshot::acquire(&snapshot, &temp.path().join("laptop")).unwrap();
+
+        let desktop = SyncedSnapshot::acquire(&snapshot, &temp.path().join("desktop"));
+        assert!(matches!(desktop, Err(ClipKittyError::StoreInUse(_))));
+
+        laptop.release();
+        assert!(SyncedSnapshot::acquire(&snapshot, &temp.path().join("desktop")).is_ok());
+    }
+
+    #[test]
+    fn published_snapshot_restores_on_another_device() {
+        let temp = tempfile::tempdir().unwrap();
+        let snapshot = temp.path().join("synced").join("clipboard.db");
+
+        let laptop = SyncedSnapshot::acquire(&snapshot, &temp.path().join("laptop")).unwrap();
+        let db = Database::open(laptop.local_database()).unwrap();
+        laptop.publish(&db).unwrap();
+        laptop.release();
+        // Publishing leaves no staging file or WAL beside the snapshot.
+        let synced_files: Vec<_> = std::fs::read_dir(snapshot.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(synced_files, vec![std::ffi::OsString::from("clipboard.db")]);
+
+        let desktop = SyncedSnapshot::acquire(&snapshot, &temp.path().join("desktop")).unwrap();
+        let index = temp.path().join("desktop").join("index");
+        assert!(desktop.restore_if_newer(&index).unwrap());
+        assert!(desktop.local_database().exists());
+        // Already in sync: nothing to restore the second time.
+        assert!(!desktop.restore_if_newer(&index).unwrap());
+        // The laptop doesn't restore its own publish.
+        assert!(!laptop.restore_if_newer(&index).unwrap());
+    }
+
+    #[test]
+    fn restoring_keeps_items_copied_since_the_last_sync() {
+        let temp = tempfile::tempdir().unwrap();
+        let snapshot = temp.path().join("synced").join("clipboard.db");
+        let index = temp.path().join("index");
+
+        let laptop = SyncedSnapshot::acquire(&snapshot, &temp.path().join("laptop")).unwrap();
+        let laptop_db = Database::open(laptop.local_database()).unwrap();
+        laptop_db
+            .insert_item(&StoredItem::new_text("shared".into(), None, None))
+            .unwrap();
+        laptop.publish(&laptop_db).unwrap();
+        laptop.release();
+
+        let desktop = SyncedSnapshot::acquire(&snapshot, &temp.path().join("desktop")).unwrap();
+        assert!(desktop.restore_if_newer(&index).unwrap());
+        let desktop_db = Database::open(desktop.local_database()).unwrap();
+        desktop_db
+            .insert_item(&StoredItem::new_text("desktop only".into(), None, None))
+            .unwrap();
+        drop(desktop_db);
+        desktop.release();
+
+        // The laptop publishes again before the desktop does.
+        let laptop = SyncedSnapshot::acquire(&snapshot, &temp.path().join("laptop")).unwrap();
+        laptop_db
+            .insert_item(&StoredItem::new_text("laptop later".into(), None, None))
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+        laptop.publish(&laptop_db).unwrap();
+        laptop.release();
+
+        let desktop = SyncedSnapshot::acquire(&snapshot, &temp.path().join("desktop")).unwrap();
+        assert!(desktop.restore_if_newer(&index).unwrap());
+        let desktop_db = Database::open(desktop.local_database()).unwrap();
+        let mut texts: Vec<String> = desktop_db
+            .fetch_items_by_ids(&desktop_db.fetch_all_item_ids().unwrap())
+            .unwrap()
+            .iter()
+            .map(|item| item.text_content().to_string())
+            .collect();
+        texts.sort();
+        assert_eq!(texts, ["desktop only", "laptop later", "shared"]);
+    }
+
+    #[test]
+    fn concurrent_claims_of_a_free_snapshot_leave_one_owner() {
+        let temp = tempfile::tempdir().unwrap();
+        let snapshot = temp.path().join("synced").join("clipboard.db");
+        let claims: Vec<_> = (0..4)
+            .map(|device| {
+                let snapshot = snapshot.clone();
+                let local = temp.path().join(format!("device-{device}"));
+                std::thread::spawn(move || SyncedSnapshot::acquire(&snapshot, &local).is_ok())
+            })
+            .collect();
+        let owners = claims
+            .into_iter()
+            .map(|claim| claim.join().unwrap())
+            .filter(|owns| *owns)
+            .count();
+        assert_eq!(owners, 1);
+    }
+}
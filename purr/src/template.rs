@@ -0,0 +1,353 @@
+//! Snippet templates from history items.
+//!
+//! Scans text for spans that usually change between uses (UUIDs, email
+//! addresses, dates, and numbers) and replaces each with a named
+//! `{placeholder}`. Repeated values share one placeholder. Literal braces in
+//! the source are doubled so expanding the template restores them verbatim.
+
+use crate::interface::{SnippetTemplate, TemplatePlaceholder, TemplatePlaceholderKind};
+
+pub(crate) fn templatize_text(text: &str) -> SnippetTemplate {
+    let bytes = text.as_bytes();
+    let mut body = String::with_capacity(text.len());
+    let mut placeholders: Vec<TemplatePlaceholder> = Vec::new();
+    let mut literal_start = 0;
+    let mut index = 0;
+    while index < bytes.len() {
+        // Detectors only match ASCII starts, so `index` is a char boundary
+        // whenever one fires.
+        let detected = if index == 0 || !is_word_byte(bytes[index - 1]) {
+            detect_at(bytes, index)
+        } else {
+            None
+        };
+        match detected {
+            Some((kind, end)) => {
+                push_literal(&mut body, &text[literal_start..index]);
+                let name = placeholder_name(&mut placeholders, kind, &text[index..end]);
+                body.push('{');
+                body.push_str(&name);
+                body.push('}');
+                index = end;
+                literal_start = end;
+            }
+            None => index += 1,
+        }
+    }
+    push_literal(&mut body, &text[literal_start..]);
+    SnippetTemplate { body, placeholders }
+}
+
+/// Most specific detector first: a UUID or date also looks like numbers.
+fn detect_at(bytes: &[u8], start: usize) -> Option<(TemplatePlaceholderKind, usize)> {
+    if let Some(end) = uuid_end(bytes, start) {
+        return Some((TemplatePlaceholderKind::Uuid, end));
+    }
+    if let Some(end) = email_end(bytes, start) {
+        return Some((TemplatePlaceholderKind::Email, end));
+    }
+    if let Some(end) = date_end(bytes, start) {
+        return Some((TemplatePlaceholderKind::Date, end));
+    }
+    number_end(bytes, start).map(|end| (TemplatePlaceholderKind::Number, end))
+}
+
+fn placeholder_name(
+    placeholders: &mut Vec<TemplatePlaceholder>,
+    kind: TemplatePlaceholderKind,
+    original: &str,
+) -> String {
+    if let Some(existing) = placeholders
+        .iter()
+        .find(|placeholder| placeholder.kind == kind && placeholder.original == original)
+    {
+        return existing.name.clone();
+    }
+    let base = match kind {
+        TemplatePlaceholderKind::Email => "email",
+        TemplatePlaceholderKind::Date => "date",
+        TemplatePlaceholderKind::Number => "number",
+        TemplatePlaceholderKind::Uuid => "uuid",
+    };
+    let ordinal = placeholders
+        .iter()
+        .filter(|placeholder| placeholder.kind == kind)
+        .count()
+        + 1;
+    let name = if ordinal == 1 {
+        base.to_string()
+    } else {
+        format!("{base}_{ordinal}")
+    };
+    placeholders.push(TemplatePlaceholder {
+        name: name.clone(),
+        kind,
+        original: original.to_string(),
+    });
+    name
+}
+
+fn push_literal(body: &mut String, literal: &str) {
+    for c in literal.chars() {
+        match c {
+            '{' => body.push_str("{{"),
+            '}' => body.push_str("}}"),
+            _ => body.push(c),
+        }
+    }
+}
+
+/// Bytes that continue a word. Non-ASCII bytes count so spans never start or
+/// end inside a word written in another script.
+fn is_word_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte >= 0x80
+}
+
+fn ends_word(bytes: &[u8], end: usize) -> bool {
+    bytes.get(end).is_none_or(|byte| !is_word_byte(*byte))
+}
+
+fn digit_run(bytes: &[u8], start: usize) -> usize {
+    bytes[start.min(bytes.len())..]
+        .iter()
+        .take_while(|byte| byte.is_ascii_digit())
+        .count()
+}
+
+fn parse_digits(bytes: &[u8], start: usize, len: usize) -> u32 {
+    bytes[start..start + len]
+        .iter()
+        .fold(0, |value, byte| value * 10 + u32::from(byte - b'0'))
+}
+
+/// `8-4-4-4-12` hex groups.
+fn uuid_end(bytes: &[u8], start: usize) -> Option<usize> {
+    const GROUPS: [usize; 5] = [8, 4, 4, 4, 12];
+    let mut cursor = start;
+    for (group_index, group_len) in GROUPS.iter().enumerate() {
+        if group_index > 0 {
+            if bytes.get(cursor) != Some(&b'-') {
+                return None;
+            }
+            cursor += 1;
+        }
+        let hex = bytes[cursor.min(bytes.len())..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_hexdigit())
+            .count();
+        if hex != *group_len {
+            return None;
+        }
+        cursor += group_len;
+    }
+    ends_word(bytes, cursor).then_some(cursor)
+}
+
+fn email_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let is_local = |byte: &u8| byte.is_ascii_alphanumeric() || b"._%+-".contains(byte);
+    if !bytes[start].is_ascii_alphanumeric() {
+        return None;
+    }
+    let local = bytes[start..]
+        .iter()
+        .take_while(|byte| is_local(byte))
+        .count();
+    let at = start + local;
+    if bytes.get(at) != Some(&b'@') {
+        return None;
+    }
+    let domain_start = at + 1;
+    let mut end = domain_start
+        + bytes[domain_start..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_alphanumeric() || **byte == b'.' || **byte == b'-')
+            .count();
+    // A sentence ending right after the address keeps its full stop.
+    while end > domain_start && matches!(bytes[end - 1], b'.' | b'-') {
+        end -= 1;
+    }
+    let domain = &bytes[domain_start..end];
+    let mut labels = domain.split(|byte| *byte == b'.');
+    let top_level = labels.next_back()?;
+    let valid = domain.contains(&b'.')
+        && labels.all(|label| !label.is_empty())
+        && top_level.len() >= 2
+        && top_level.iter().all(u8::is_ascii_alphabetic);
+    (valid && ends_word(bytes, end)).then_some(end)
+}
+
+/// `YYYY-MM-DD` (optionally with a `THH:MM[:SS][Z]` time), `YYYY/MM/DD`, and
+/// day-month-year or month-day-year with a four-digit year.
+fn date_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let lead = digit_run(bytes, start);
+    let separator = *bytes.get(start + lead)?;
+    if !matches!(separator, b'-' | b'/' | b'.') {
+        return None;
+    }
+    let second_start = start + lead + 1;
+    let second = digit_run(bytes, second_start);
+    if !(1..=2).contains(&second) || bytes.get(second_start + second) != Some(&separator) {
+        return None;
+    }
+    let third_start = second_start + second + 1;
+    let third = digit_run(bytes, third_start);
+    let year_first = lead == 4 && separator != b'.';
+    let lengths_valid = if year_first {
+        (1..=2).contains(&third)
+    } else {
+        (1..=2).contains(&lead) && third == 4
+    };
+    if !lengths_valid {
+        return None;
+    }
+
+    let first_value = parse_digits(bytes, start, lead);
+    let second_value = parse_digits(bytes, second_start, second);
+    let third_value = parse_digits(bytes, third_start, third);
+    let valid = if year_first {
+        (1..=12).contains(&second_value) && (1..=31).contains(&third_value)
+    } else {
+        (1..=31).contains(&first_value)
+            && (1..=31).contains(&second_value)
+            && (first_value <= 12 || second_value <= 12)
+    };
+    if !valid {
+        return None;
+    }
+    let mut end = third_start + third;
+    if lead == 4 && separator == b'-' {
+        end = time_end(bytes, end).unwrap_or(end);
+    }
+    ends_word(bytes, end).then_some(end)
+}
+
+fn time_end(bytes: &[u8], start: usize) -> Option<usize> {
+    if bytes.get(start) != Some(&b'T') {
+        return None;
+    }
+    let mut cursor = start + 1;
+    for part in 0..3 {
+        if part > 0 {
+            if bytes.get(cursor) != Some(&b':') || digit_run(bytes, cursor + 1) != 2 {
+                if part == 1 {
+                    return None;
+                }
+                break;
+            }
+            cursor += 1;
+        }
+        if digit_run(bytes, cursor) != 2 {
+            return None;
+        }
+        cursor += 2;
+    }
+    if bytes.get(cursor) == Some(&b'Z') {
+        cursor += 1;
+    }
+    Some(cursor)
+}
+
+/// Integers, `1,000`-style grouped integers, and decimals. Dotted runs like
+/// version strings or IP addresses are left alone.
+fn number_end(bytes: &[u8], start: usize) -> Option<usize> {
+    if start > 0 && bytes[start - 1] == b'.' {
+        return None;
+    }
+    let mut end = start + digit_run(bytes, start);
+    if end == start {
+        return None;
+    }
+    while bytes.get(end) == Some(&b',') && digit_run(bytes, end + 1) == 3 {
+        end += 4;
+    }
+    if bytes.get(end) == Some(&b'.') {
+        let fraction = digit_run(bytes, end + 1);
+        if fraction > 0 {
+            end += 1 + fraction;
+        }
+    }
+    let dotted_tail =
+        bytes.get(end) == Some(&b'.') && bytes.get(end + 1).is_some_and(u8::is_ascii_digit);
+    (!dotted_tail && ends_word(bytes, end)).then_some(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(template: &SnippetTemplate) -> Vec<(&str, TemplatePlaceholderKind, &str)> {
+        template
+            .placeholders
+            .iter()
+            .map(|placeholder| {
+                (
+                    placeholder.name.as_str(),
+                    placeholder.kind,
+                    placeholder.original.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn replaces_each_kind_with_named_placeholders() {
+        let template = templatize_text(
+            "Hi ana@example.com, order 1,250 (id 3f2b8c1e-9a7d-4e55-b0c2-7d1e9f3a6b40) ships 2024-03-15.",
+        );
+        assert_eq!(
+            template.body,
+            "Hi {email}, order {number} (id {uuid}) ships {date}."
+        );
+        assert_eq!(
+            kinds(&template),
+            vec![
+                ("email", TemplatePlaceholderKind::Email, "ana@example.com"),
+                ("number", TemplatePlaceholderKind::Number, "1,250"),
+                (
+                    "uuid",
+                    TemplatePlaceholderKind::Uuid,
+                    "3f2b8c1e-9a7d-4e55-b0c2-7d1e9f3a6b40"
+                ),
+                ("date", TemplatePlaceholderKind::Date, "2024-03-15"),
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_values_share_a_placeholder() {
+        let template = templatize_text("pay 40 now, 40 later, 12.5 tip");
+        assert_eq!(
+            template.body,
+            "pay {number} now, {number} later, {number_2} tip"
+        );
+        assert_eq!(template.placeholders.len(), 2);
+    }
+
+    #[test]
+    fn leaves_words_versions_and_addresses_alone() {
+        for text in [
+            "ipv6 and sha256",
+            "v1.2.3",
+            "host 10.0.0.1",
+            "user@localhost",
+        ] {
+            let template = templatize_text(text);
+            assert_eq!(template.body, text);
+            assert!(template.placeholders.is_empty());
+        }
+    }
+
+    #[test]
+    fn recognizes_date_forms() {
+        assert_eq!(
+            templatize_text("at 2024-03-15T09:30:00Z or 15/03/2024 or 3.15.2024").body,
+            "at {date} or {date_2} or {date_3}"
+        );
+    }
+
+    #[test]
+    fn escapes_literal_braces() {
+        let template = templatize_text("{\"count\": 3, \"naïve\": true}");
+        assert_eq!(template.body, "{{\"count\": {number}, \"naïve\": true}}");
+    }
+}
@@ -0,0 +1,406 @@
+//! Thumbnails rendered in Rust
+//!
+//! Small PNG previews for items the platform cannot thumbnail itself, such as
+//! color palettes and gradients, and typed-document glyphs for files, plus
+//! medium thumbnails for the image grid.
+
+use crate::content_detection::Palette;
+use crate::interface::{DocumentKind, FileEntry, FilePreviewSnapshot};
+use image::{Rgba, RgbaImage};
+
+/// Edge length of generated list thumbnails, in pixels.
+pub(crate) const THUMBNAIL_SIZE: u32 = 64;
+
+/// Longest edge of image grid thumbnails, in pixels.
+pub(crate) const GRID_THUMBNAIL_SIZE: u32 = 256;
+
+const GRID_JPEG_QUALITY: u8 = 80;
+
+/// Render a palette as equal-width vertical stripes, or a gradient as a
+/// horizontal blend between its stops.
+pub(crate) fn render_palette(palette: &Palette) -> Option<Vec<u8>> {
+    if palette.colors.is_empty() {
+        return None;
+    }
+    let colors: Vec<[f32; 4]> = palette.colors.iter().map(|&rgba| unpack(rgba)).collect();
+    let image = RgbaImage::from_fn(THUMBNAIL_SIZE, THUMBNAIL_SIZE, |x, _| {
+        let t = x as f32 / (THUMBNAIL_SIZE - 1) as f32;
+        if palette.is_gradient {
+            gradient_at(&colors, t)
+        } else {
+            let index =
+                ((x as usize * colors.len()) / THUMBNAIL_SIZE as usize).min(colors.len() - 1);
+            to_pixel(colors[index])
+        }
+    });
+    encode_png(&image)
+}
+
+fn gradient_at(colors: &[[f32; 4]], t: f32) -> Rgba<u8> {
+    if colors.len() == 1 {
+        return to_pixel(colors[0]);
+    }
+    let scaled = t * (colors.len() - 1) as f32;
+    let index = (scaled.floor() as usize).min(colors.len() - 2);
+    let local = scaled - index as f32;
+    let (from, to) = (colors[index], colors[index + 1]);
+    let mut mixed = [0.0; 4];
+    for channel in 0..4 {
+        mixed[channel] = from[channel] + (to[channel] - from[channel]) * local;
+    }
+    to_pixel(mixed)
+}
+
+fn unpack(rgba: u32) -> [f32; 4] {
+    [
+        ((rgba >> 24) & 0xFF) as f32,
+        ((rgba >> 16) & 0xFF) as f32,
+        ((rgba >> 8) & 0xFF) as f32,
+        (rgba & 0xFF) as f32,
+    ]
+}
+
+fn to_pixel(channels: [f32; 4]) -> Rgba<u8> {
+    Rgba(channels.map(|value| value.round().clamp(0.0, 255.0) as u8))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Typed document glyphs
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Longest extension drawn on the badge; longer ones are not rendered.
+const MAX_BADGE_CHARS: usize = 4;
+
+/// Glyph cell of the built-in 3x5 bitmap font, scaled up by `GLYPH_SCALE`.
+const GLYPH_WIDTH: u32 = 3;
+const GLYPH_HEIGHT: u32 = 5;
+const GLYPH_SCALE: u32 = 2;
+const GLYPH_SPACING: u32 = 2;
+
+const PAGE_LEFT: u32 = 12;
+const PAGE_RIGHT: u32 = 52;
+const PAGE_TOP: u32 = 4;
+const PAGE_BOTTOM: u32 = 60;
+const PAGE_FOLD: u32 = 10;
+const BADGE_TOP: u32 = 36;
+const BADGE_HEIGHT: u32 = GLYPH_HEIGHT * GLYPH_SCALE + 6;
+
+const PAGE_FILL: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const PAGE_EDGE: Rgba<u8> = Rgba([170, 170, 170, 255]);
+const FOLD_FILL: Rgba<u8> = Rgba([220, 220, 220, 255]);
+const BADGE_TEXT: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+/// Render the list thumbnail for a file item: an extension badge over a
+/// document shape, tinted by document kind. Items whose first file already has
+/// a platform image preview, or no usable extension, get no thumbnail.
+pub(crate) fn render_file_thumbnail(files: &[FileEntry]) -> Option<Vec<u8>> {
+    let first = files.first()?;
+    if matches!(first.preview, FilePreviewSnapshot::Image { .. }) {
+        return None;
+    }
+    let kind = crate::icon_service::document_kind(&first.uti, &first.filename);
+    if kind == DocumentKind::Folder {
+        return None;
+    }
+    let (_, extension) = first.filename.rsplit_once('.')?;
+    render_document(extension, kind)
+}
+
+fn render_document(extension: &str, kind: DocumentKind) -> Option<Vec<u8>> {
+    let label: Vec<[u8; 5]> = extension
+        .chars()
+        .map(|ch| glyph(ch.to_ascii_uppercase()))
+        .collect::<Option<_>>()?;
+    if label.is_empty() || label.len() > MAX_BADGE_CHARS {
+        return None;
+    }
+
+    let mut image = RgbaImage::new(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+    draw_page(&mut image);
+
+    let text_width =
+        label.len() as u32 * (GLYPH_WIDTH * GLYPH_SCALE + GLYPH_SPACING) - GLYPH_SPACING;
+    let badge_width = text_width + 8;
+    let badge_left = (THUMBNAIL_SIZE - badge_width) / 2;
+    fill_rect(
+        &mut image,
+        badge_left,
+        BADGE_TOP,
+        badge_left + badge_width,
+        BADGE_TOP + BADGE_HEIGHT,
+        badge_color(kind),
+    );
+
+    let mut x = badge_left + 4;
+    for rows in label {
+        draw_glyph(&mut image, x, BADGE_TOP + 3, rows);
+        x += GLYPH_WIDTH * GLYPH_SCALE + GLYPH_SPACING;
+    }
+    encode_png(&image)
+}
+
+fn draw_page(image: &mut RgbaImage) {
+    let fold_left = PAGE_RIGHT - PAGE_FOLD;
+    let fold_bottom = PAGE_TOP + PAGE_FOLD;
+    for y in PAGE_TOP..PAGE_BOTTOM {
+        for x in PAGE_LEFT..PAGE_RIGHT {
+            let in_fold = x >= fold_left && y < fold_bottom;
+            let pixel = if in_fold && x - fold_left > y - PAGE_TOP {
+                // Cut-away corner above the fold diagonal stays transparent.
+                continue;
+            } else if in_fold {
+                FOLD_FILL
+            } else if x == PAGE_LEFT || x == PAGE_RIGHT - 1 || y == PAGE_TOP || y == PAGE_BOTTOM - 1
+            {
+                PAGE_EDGE
+            } else {
+                PAGE_FILL
+            };
+            image.put_pixel(x, y, pixel);
+        }
+    }
+}
+
+fn fill_rect(image: &mut RgbaImage, left: u32, top: u32, right: u32, bottom: u32, color: Rgba<u8>) {
+    for y in top..bottom.min(THUMBNAIL_SIZE) {
+        for x in left..right.min(THUMBNAIL_SIZE) {
+            image.put_pixel(x, y, color);
+        }
+    }
+}
+
+fn draw_glyph(image: &mut RgbaImage, left: u32, top: u32, rows: [u8; 5]) {
+    for (row, bits) in rows.iter().enumerate() {
+        for column in 0..GLYPH_WIDTH {
+            if bits & (0b100 >> column) != 0 {
+                let x = left + column * GLYPH_SCALE;
+                let y = top + row as u32 * GLYPH_SCALE;
+                fill_rect(image, x, y, x + GLYPH_SCALE, y + GLYPH_SCALE, BADGE_TEXT);
+            }
+        }
+    }
+}
+
+fn badge_color(kind: DocumentKind) -> Rgba<u8> {
+    let [r, g, b] = match kind {
+        DocumentKind::Pdf => [0xD9, 0x3B, 0x3B],
+        DocumentKind::Image => [0x8E, 0x5B, 0xD6],
+        DocumentKind::Audio => [0xE0, 0x4A, 0x8B],
+        DocumentKind::Video => [0xE8, 0x7A, 0x2A],
+        DocumentKind::Archive => [0x8B, 0x6A, 0x4A],
+        DocumentKind::Spreadsheet => [0x2E, 0x9E, 0x57],
+        DocumentKind::Presentation => [0xE0, 0x8E, 0x1E],
+        DocumentKind::WordProcessing => [0x2F, 0x6F, 0xD8],
+        DocumentKind::SourceCode => [0x1F, 0x95, 0x9A],
+        DocumentKind::PlainText
+        | DocumentKind::Folder
+        | DocumentKind::Application
+        | DocumentKind::Generic => [0x7A, 0x7A, 0x80],
+    };
+    Rgba([r, g, b, 255])
+}
+
+/// Rows of a 3x5 bitmap glyph, most significant of the low three bits leftmost.
+fn glyph(ch: char) -> Option<[u8; 5]> {
+    Some(match ch {
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b110],
+        _ => return None,
+    })
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Image grid
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Original dimensions and a medium thumbnail of a decodable image.
+pub(crate) struct GridImage {
+    pub width: u32,
+    pub height: u32,
+    pub thumbnail: Option<Vec<u8>>,
+}
+
+/// Decode an image and scale it to fit `GRID_THUMBNAIL_SIZE`. Opaque images are
+/// encoded as JPEG, images with alpha as PNG. Returns None for formats the
+/// `image` crate cannot decode (HEIC).
+pub(crate) fn render_grid_thumbnail(data: &[u8]) -> Option<GridImage> {
+    let image = image::load_from_memory(data).ok()?;
+    let scaled = image.thumbnail(GRID_THUMBNAIL_SIZE, GRID_THUMBNAIL_SIZE);
+    let thumbnail = if scaled.color().has_alpha() {
+        encode_png(&scaled.to_rgba8())
+    } else {
+        encode_jpeg(&scaled.to_rgb8())
+    };
+    Some(GridImage {
+        width: image.width(),
+        height: image.height(),
+        thumbnail,
+    })
+}
+
+fn encode_jpeg(image: &image::RgbImage) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut bytes, GRID_JPEG_QUALITY)
+        .encode_image(image)
+        .ok()?;
+    Some(bytes)
+}
+
+pub(crate) fn encode_png(image: &RgbaImage) -> Option<Vec<u8>> {
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut bytes, image::ImageFormat::Png).ok()?;
+    Some(bytes.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> RgbaImage {
+        image::load_from_memory(bytes).unwrap().to_rgba8()
+    }
+
+    #[test]
+    fn palette_renders_stripes_in_order() {
+        let palette = Palette {
+            colors: vec![0xFF0000FF, 0x0000FFFF],
+            is_gradient: false,
+        };
+        let image = decode(&render_palette(&palette).unwrap());
+        assert_eq!(image.dimensions(), (THUMBNAIL_SIZE, THUMBNAIL_SIZE));
+        assert_eq!(image.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(
+            image.get_pixel(THUMBNAIL_SIZE - 1, 0),
+            &Rgba([0, 0, 255, 255])
+        );
+    }
+
+    #[test]
+    fn gradient_blends_between_stops() {
+        let palette = Palette {
+            colors: vec![0x000000FF, 0xFFFFFFFF],
+            is_gradient: true,
+        };
+        let image = decode(&render_palette(&palette).unwrap());
+        assert_eq!(image.get_pixel(0, 0), &Rgba([0, 0, 0, 255]));
+        assert_eq!(
+            image.get_pixel(THUMBNAIL_SIZE - 1, 0),
+            &Rgba([255, 255, 255, 255])
+        );
+        let middle = image.get_pixel(THUMBNAIL_SIZE / 2, 0);
+        assert!(middle[0] > 100 && middle[0] < 160);
+    }
+
+    #[test]
+    fn grid_thumbnail_fits_bounds_and_keeps_dimensions() {
+        let source = RgbaImage::from_pixel(1024, 512, Rgba([10, 20, 30, 255]));
+        let png = encode_png(&source).unwrap();
+        let grid = render_grid_thumbnail(&png).unwrap();
+        assert_eq!((grid.width, grid.height), (1024, 512));
+        let thumbnail = image::load_from_memory(&grid.thumbnail.unwrap()).unwrap();
+        assert_eq!(
+            (thumbnail.width(), thumbnail.height()),
+            (GRID_THUMBNAIL_SIZE, GRID_THUMBNAIL_SIZE / 2)
+        );
+
+        assert!(render_grid_thumbnail(b"not an image").is_none());
+    }
+
+    fn file_entry(filename: &str, uti: &str, preview: FilePreviewSnapshot) -> FileEntry {
+        FileEntry {
+            path: format!("/tmp/{filename}"),
+            filename: filename.to_string(),
+            file_size: 1,
+            uti: uti.to_string(),
+            bookmark_data: Vec::new(),
+            file_status: crate::interface::FileStatus::Available,
+            preview,
+        }
+    }
+
+    #[test]
+    fn file_thumbnail_draws_tinted_badge() {
+        let files = [file_entry(
+            "report.pdf",
+            "com.adobe.pdf",
+            FilePreviewSnapshot::not_captured(),
+        )];
+        let image = decode(&render_file_thumbnail(&files).unwrap());
+        // Page body is white, badge corner carries the PDF tint.
+        assert_eq!(image.get_pixel(PAGE_LEFT + 2, PAGE_BOTTOM - 4), &PAGE_FILL);
+        let badge_left = (THUMBNAIL_SIZE - (3 * 8 - 2 + 8)) / 2;
+        assert_eq!(
+            image.get_pixel(badge_left, BADGE_TOP),
+            &badge_color(DocumentKind::Pdf)
+        );
+        // Outside the page stays transparent.
+        assert_eq!(image.get_pixel(0, 0)[3], 0);
+    }
+
+    #[test]
+    fn file_thumbnail_skipped_when_not_renderable() {
+        let with_preview = [file_entry(
+            "photo.png",
+            "public.png",
+            FilePreviewSnapshot::Image {
+                preview_data: vec![1, 2, 3],
+            },
+        )];
+        assert!(render_file_thumbnail(&with_preview).is_none());
+
+        let no_extension = [file_entry(
+            "Makefile",
+            "public.data",
+            FilePreviewSnapshot::not_captured(),
+        )];
+        assert!(render_file_thumbnail(&no_extension).is_none());
+
+        let long_extension = [file_entry(
+            "notes.backup",
+            "",
+            FilePreviewSnapshot::not_captured(),
+        )];
+        assert!(render_file_thumbnail(&long_extension).is_none());
+
+        let folder = [file_entry(
+            "Photos.app",
+            "public.folder",
+            FilePreviewSnapshot::not_captured(),
+        )];
+        assert!(render_file_thumbnail(&folder).is_none());
+    }
+}
@@ -0,0 +1,115 @@
+//! Shared-memory handoff for row thumbnails.
+//!
+//! Copying thumbnail bytes across the FFI for every visible row adds up to
+//! megabytes per frame while scrolling long lists. With the cache enabled,
+//! each distinct thumbnail is appended once to a file the app maps into
+//! memory, and rows carry an `ItemIcon::SharedThumbnail` range instead of
+//! the bytes. The file only grows while the cache is open, so a mapping
+//! stays valid; the app remaps when a range ends past what it has mapped.
+//! Once the file reaches `MAX_CACHE_BYTES`, new thumbnails go inline again.
+
+use crate::interface::{ClipKittyError, ItemIcon, ItemMatch};
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// Size past which thumbnails stop being appended to the cache file.
+const MAX_CACHE_BYTES: u64 = 128 * 1024 * 1024;
+
+struct CacheState {
+    file: File,
+    len: u64,
+    /// Thumbnail digest to its (offset, length) in the file.
+    ranges: HashMap<[u8; 32], (u64, u64)>,
+}
+
+pub(crate) struct ThumbnailCache {
+    state: Mutex<CacheState>,
+}
+
+impl ThumbnailCache {
+    /// Create or truncate the cache file at `path`. Ranges handed out by an
+    /// earlier cache on the same file are no longer valid.
+    pub(crate) fn open(path: &Path) -> Result<Self, ClipKittyError> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .map_err(|e| {
+                ClipKittyError::InvalidInput(format!(
+                    "cannot open thumbnail cache {}: {e}",
+                    path.display()
+                ))
+            })?;
+        Ok(Self {
+            state: Mutex::new(CacheState {
+                file,
+                len: 0,
+                ranges: HashMap::new(),
+            }),
+        })
+    }
+
+    /// The file range holding `bytes`, appending them if not already
+    /// cached. None when the cache is full or the write fails.
+    fn share(&self, bytes: &[u8]) -> Option<(u64, u64)> {
+        let digest: [u8; 32] = Sha256::digest(bytes).into();
+        let mut state = self.state.lock();
+        if let Some(range) = state.ranges.get(&digest) {
+            return Some(*range);
+        }
+        let length = bytes.len() as u64;
+        if state.len + length > MAX_CACHE_BYTES {
+            return None;
+        }
+        state.file.write_all(bytes).ok()?;
+        let range = (state.len, length);
+        state.len += length;
+        state.ranges.insert(digest, range);
+        Some(range)
+    }
+
+    /// Replace inline thumbnails in `matches` with ranges of the cache file.
+    pub(crate) fn share_icons(&self, matches: &mut [ItemMatch]) {
+        for item in matches {
+            let icon = &mut item.item_metadata.icon;
+            if let ItemIcon::Thumbnail { bytes } = icon {
+                if let Some((offset, length)) = self.share(bytes) {
+                    *icon = ItemIcon::SharedThumbnail { offset, length };
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> u64 {
+        self.state.lock().len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_thumbnails_share_one_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("thumbnails.cache");
+        let cache = ThumbnailCache::open(&path).unwrap();
+
+        assert_eq!(cache.share(b"first"), Some((0, 5)));
+        assert_eq!(cache.share(b"second!"), Some((5, 7)));
+        assert_eq!(cache.share(b"first"), Some((0, 5)));
+        assert_eq!(cache.len(), 12);
+        assert_eq!(std::fs::read(&path).unwrap(), b"firstsecond!");
+
+        // Reopening truncates, so old ranges are never read back as new data.
+        let cache = ThumbnailCache::open(&path).unwrap();
+        assert_eq!(cache.share(b"second!"), Some((0, 7)));
+    }
+}
@@ -0,0 +1,278 @@
+//! Display strings for timestamps.
+//!
+//! Every client (app, CLI, widgets) formats times through these functions
+//! so they read the same everywhere. Relative times count minutes and
+//! hours, then calendar days in the caller's time zone ("yesterday"), and
+//! fall back to the date after a week. Locales are BCP 47 or Apple
+//! identifiers ("de-DE", "en_GB", "zh-Hans-CN"); only the language and
+//! region matter, and languages without a table here read as English.
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Timelike};
+
+/// Days before a relative time becomes a date.
+const RELATIVE_DAYS: i64 = 7;
+
+struct Phrases {
+    just_now: &'static str,
+    /// Templates with `{n}` for the count.
+    minutes_ago: &'static str,
+    hours_ago: &'static str,
+    yesterday: &'static str,
+    days_ago: &'static str,
+    months: [&'static str; 12],
+}
+
+const ENGLISH: Phrases = Phrases {
+    just_now: "just now",
+    minutes_ago: "{n} min ago",
+    hours_ago: "{n} hr ago",
+    yesterday: "yesterday",
+    days_ago: "{n} days ago",
+    months: [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ],
+};
+
+const GERMAN: Phrases = Phrases {
+    just_now: "gerade eben",
+    minutes_ago: "vor {n} Min.",
+    hours_ago: "vor {n} Std.",
+    yesterday: "gestern",
+    days_ago: "vor {n} Tagen",
+    months: [
+        "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.", "Okt.", "Nov.",
+        "Dez.",
+    ],
+};
+
+const FRENCH: Phrases = Phrases {
+    just_now: "à l’instant",
+    minutes_ago: "il y a {n} min",
+    hours_ago: "il y a {n} h",
+    yesterday: "hier",
+    days_ago: "il y a {n} jours",
+    months: [
+        "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.",
+        "déc.",
+    ],
+};
+
+const SPANISH: Phrases = Phrases {
+    just_now: "ahora mismo",
+    minutes_ago: "hace {n} min",
+    hours_ago: "hace {n} h",
+    yesterday: "ayer",
+    days_ago: "hace {n} días",
+    months: [
+        "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct", "nov", "dic",
+    ],
+};
+
+const JAPANESE: Phrases = Phrases {
+    just_now: "たった今",
+    minutes_ago: "{n}分前",
+    hours_ago: "{n}時間前",
+    yesterday: "昨日",
+    days_ago: "{n}日前",
+    months: [
+        "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+    ],
+};
+
+const CHINESE: Phrases = Phrases {
+    just_now: "刚刚",
+    minutes_ago: "{n}分钟前",
+    hours_ago: "{n}小时前",
+    yesterday: "昨天",
+    days_ago: "{n}天前",
+    months: JAPANESE.months,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Language {
+    English { us: bool },
+    German,
+    French,
+    Spanish,
+    Japanese,
+    Chinese,
+}
+
+impl Language {
+    fn parse(locale: &str) -> Self {
+        let mut parts = locale.split(['-', '_']);
+        let language = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let region =
+            parts.find(|part| part.len() == 2 && part.chars().all(|c| c.is_ascii_alphabetic()));
+        match language.as_str() {
+            "de" => Language::German,
+            "fr" => Language::French,
+            "es" => Language::Spanish,
+            "ja" => Language::Japanese,
+            "zh" => Language::Chinese,
+            _ => Language::English {
+                us: region.is_none_or(|region| region.eq_ignore_ascii_case("US")),
+            },
+        }
+    }
+
+    fn phrases(self) -> &'static Phrases {
+        match self {
+            Language::English { .. } => &ENGLISH,
+            Language::German => &GERMAN,
+            Language::French => &FRENCH,
+            Language::Spanish => &SPANISH,
+            Language::Japanese => &JAPANESE,
+            Language::Chinese => &CHINESE,
+        }
+    }
+
+    fn date(self, date: NaiveDate) -> String {
+        let (day, year) = (date.day(), date.year());
+        let month = self.phrases().months[date.month0() as usize];
+        match self {
+            Language::English { us: true } => format!("{month} {day}, {year}"),
+            Language::English { us: false } | Language::French | Language::Spanish => {
+                format!("{day} {month} {year}")
+            }
+            Language::German => format!("{day}. {month} {year}"),
+            Language::Japanese | Language::Chinese => format!("{year}年{month}{day}日"),
+        }
+    }
+
+    fn date_and_time(self, local: DateTime<FixedOffset>) -> String {
+        let date = self.date(local.date_naive());
+        let (hour, minute) = (local.hour(), local.minute());
+        match self {
+            Language::English { us: true } => {
+                let (is_pm, hour12) = local.hour12();
+                let period = if is_pm { "PM" } else { "AM" };
+                format!("{date} at {hour12}:{minute:02} {period}")
+            }
+            Language::English { us: false } => format!("{date} at {hour:02}:{minute:02}"),
+            Language::German => format!("{date} um {hour:02}:{minute:02}"),
+            Language::French => format!("{date} à {hour:02}:{minute:02}"),
+            Language::Spanish => format!("{date}, {hour:02}:{minute:02}"),
+            Language::Japanese | Language::Chinese => format!("{date} {hour}:{minute:02}"),
+        }
+    }
+}
+
+fn local_time(timestamp_unix: i64, utc_offset_seconds: i32) -> Option<DateTime<FixedOffset>> {
+    let offset = FixedOffset::east_opt(utc_offset_seconds)?;
+    Some(DateTime::from_timestamp(timestamp_unix, 0)?.with_timezone(&offset))
+}
+
+/// How long before `now_unix` the timestamp was, e.g. "2 min ago",
+/// "yesterday", or its date once it is a week old. Calendar days are
+/// counted at `utc_offset_seconds` from UTC. Timestamps in the future read
+/// as "just now".
+#[uniffi::export]
+pub fn format_relative_time(
+    timestamp_unix: i64,
+    now_unix: i64,
+    utc_offset_seconds: i32,
+    locale: String,
+) -> String {
+    let language = Language::parse(&locale);
+    let phrases = language.phrases();
+    let (Some(then), Some(now)) = (
+        local_time(timestamp_unix, utc_offset_seconds),
+        local_time(now_unix, utc_offset_seconds),
+    ) else {
+        return String::new();
+    };
+    let elapsed = now_unix.saturating_sub(timestamp_unix);
+    let days = (now.date_naive() - then.date_naive()).num_days();
+    if elapsed < 60 {
+        phrases.just_now.to_string()
+    } else if elapsed < 3600 {
+        phrases
+            .minutes_ago
+            .replace("{n}", &(elapsed / 60).to_string())
+    } else if days == 0 {
+        phrases
+            .hours_ago
+            .replace("{n}", &(elapsed / 3600).to_string())
+    } else if days == 1 {
+        phrases.yesterday.to_string()
+    } else if days < RELATIVE_DAYS {
+        phrases.days_ago.replace("{n}", &days.to_string())
+    } else {
+        language.date(then.date_naive())
+    }
+}
+
+/// The timestamp's date and time of day at `utc_offset_seconds` from UTC,
+/// e.g. "Mar 5, 2026 at 2:03 PM" or "5. März 2026 um 14:03".
+#[uniffi::export]
+pub fn format_absolute_time(
+    timestamp_unix: i64,
+    utc_offset_seconds: i32,
+    locale: String,
+) -> String {
+    local_time(timestamp_unix, utc_offset_seconds)
+        .map(|local| Language::parse(&locale).date_and_time(local))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026-03-05 14:03:00 UTC, a Thursday.
+    const THURSDAY_AFTERNOON: i64 = 1_772_719_380;
+
+    fn relative(seconds_ago: i64, locale: &str) -> String {
+        format_relative_time(
+            THURSDAY_AFTERNOON - seconds_ago,
+            THURSDAY_AFTERNOON,
+            0,
+            locale.into(),
+        )
+    }
+
+    #[test]
+    fn relative_times_count_up_to_calendar_days() {
+        assert_eq!(relative(-30, "en-US"), "just now");
+        assert_eq!(relative(125, "en-US"), "2 min ago");
+        assert_eq!(relative(3 * 3600, "en-US"), "3 hr ago");
+        assert_eq!(relative(15 * 3600, "en-US"), "yesterday");
+        assert_eq!(relative(3 * 86_400, "en-US"), "3 days ago");
+        assert_eq!(relative(30 * 86_400, "en-US"), "Feb 3, 2026");
+        assert_eq!(relative(30 * 86_400, "en_GB"), "3 Feb 2026");
+
+        assert_eq!(relative(125, "de-DE"), "vor 2 Min.");
+        assert_eq!(relative(15 * 3600, "fr_FR"), "hier");
+        assert_eq!(relative(3 * 86_400, "es"), "hace 3 días");
+        assert_eq!(relative(3 * 3600, "ja-JP"), "3時間前");
+        assert_eq!(relative(15 * 3600, "zh-Hans-CN"), "昨天");
+        assert_eq!(relative(125, "ko-KR"), "2 min ago");
+    }
+
+    #[test]
+    fn calendar_days_follow_the_time_zone() {
+        // 13 hours earlier is 01:03 the same day in UTC, but the previous
+        // evening nine hours behind.
+        let then = THURSDAY_AFTERNOON - 13 * 3600;
+        let utc = format_relative_time(then, THURSDAY_AFTERNOON, 0, "en".into());
+        let pacific = format_relative_time(then, THURSDAY_AFTERNOON, -9 * 3600, "en".into());
+        assert_eq!(utc, "13 hr ago");
+        assert_eq!(pacific, "yesterday");
+    }
+
+    #[test]
+    fn absolute_times_use_the_locale_order_and_clock() {
+        let format = |locale: &str| format_absolute_time(THURSDAY_AFTERNOON, 3600, locale.into());
+        assert_eq!(format("en-US"), "Mar 5, 2026 at 3:03 PM");
+        assert_eq!(format("en-GB"), "5 Mar 2026 at 15:03");
+        assert_eq!(format("de"), "5. März 2026 um 15:03");
+        assert_eq!(format("fr-CA"), "5 mars 2026 à 15:03");
+        assert_eq!(format("es-MX"), "5 mar 2026, 15:03");
+        assert_eq!(format("ja"), "2026年3月5日 15:03");
+        assert_eq!(
+            format_absolute_time(THURSDAY_AFTERNOON, 90_000, "en".into()),
+            ""
+        );
+    }
+}
@@ -0,0 +1,554 @@
+//! Topics: history grouped by what it is about.
+//!
+//! Text items are clustered offline: each becomes a TF-IDF vector, spherical
+//! k-means groups the vectors, and a group is labeled with the words that
+//! weigh most in its centroid ("Kubernetes", "Apartment rent"). Membership is
+//! stored in `topic_items`, so deleting an item drops it from its topic.
+//!
+//! `refresh` runs from `run_maintenance` and is incremental: items copied
+//! since the last run join the nearest topic they are close enough to, and
+//! only once a quarter as many items as the last grouping saw have arrived
+//! are the newest `MAX_CORPUS_ITEMS` grouped again from scratch.
+
+use crate::browse_service::baseline_matches;
+use crate::content_detection::is_probably_secret;
+use crate::database::{Database, DatabaseError};
+use crate::interface::{ClipKittyError, ListPresentationProfile, TopicPage, TopicSummary};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+const TOPICS_NAMESPACE: &str = "topics";
+const MODEL_KEY: &str = "model";
+/// Leading characters of an item that are clustered; plenty to tell what
+/// it is about.
+const DOCUMENT_PREFIX_CHARS: usize = 4000;
+/// Newest items grouped from scratch; older ones keep no topic.
+const MAX_CORPUS_ITEMS: usize = 5000;
+/// Fewer text items than this are not worth grouping.
+const MIN_CORPUS_ITEMS: usize = 12;
+/// Smaller groups are not topics, and topics that shrink below it go.
+const MIN_TOPIC_ITEMS: usize = 3;
+const MAX_TOPICS: usize = 24;
+/// Cosine similarity to its topic's centroid an item needs to belong to it.
+const MIN_SIMILARITY: f32 = 0.2;
+const MAX_ITERATIONS: usize = 20;
+/// Heaviest centroid terms kept, for the label and for placing new items.
+const CENTROID_TERMS: usize = 48;
+/// A second word joins the label when it weighs at least this share of the
+/// first.
+const LABEL_SECOND_TERM_SHARE: f32 = 0.5;
+
+const STOP_WORDS: &[&str] = &[
+    "about", "after", "again", "all", "also", "and", "any", "are", "aren", "because", "been",
+    "before", "being", "but", "can", "could", "did", "didn", "does", "doesn", "don", "each", "for",
+    "from", "get", "had", "has", "have", "her", "here", "him", "his", "how", "into", "its", "just",
+    "like", "more", "most", "not", "now", "only", "other", "our", "out", "over", "please", "she",
+    "should", "some", "such", "than", "that", "the", "their", "them", "then", "there", "these",
+    "they", "this", "those", "too", "very", "was", "wasn", "were", "what", "when", "where",
+    "which", "while", "who", "why", "will", "with", "would", "you", "your",
+];
+
+/// What `refresh` remembers between runs.
+#[derive(Serialize, Deserialize)]
+struct TopicModel {
+    /// Newest row ID any run has looked at.
+    last_item_id: i64,
+    /// Items the last grouping from scratch clustered.
+    corpus_size: u64,
+    /// Items looked at one by one since then.
+    added_since_grouping: u64,
+    /// Inverse document frequency of every centroid term.
+    idf: HashMap<String, f32>,
+    /// Weight of any other term: rarer than every term that was kept.
+    default_idf: f32,
+}
+
+/// A topic's centroid, heaviest term first.
+type Centroid = Vec<(String, f32)>;
+
+/// Place items copied since the last run, dropping topics that have shrunk,
+/// or group history from scratch when enough has changed.
+pub(crate) fn refresh(db: &Database) -> Result<(), ClipKittyError> {
+    let Some(mut model) = load_model(db)? else {
+        return regroup(db);
+    };
+    place_new_items(db, &mut model)?;
+    if model.added_since_grouping * 4 >= model.corpus_size.max(MIN_CORPUS_ITEMS as u64) {
+        return regroup(db);
+    }
+    db.delete_small_topics(MIN_TOPIC_ITEMS as u64)?;
+    save_model(db, &model)
+}
+
+/// Topics with at least one item outside the trash, largest first.
+pub(crate) fn list_topics(db: &Database) -> Result<Vec<TopicSummary>, ClipKittyError> {
+    Ok(db
+        .fetch_topic_summaries()?
+        .into_iter()
+        .map(|(topic_id, label, item_count)| TopicSummary {
+            topic_id,
+            label,
+            item_count,
+        })
+        .collect())
+}
+
+/// One page of a topic's items, newest first.
+pub(crate) fn browse_topic(
+    db: &Database,
+    topic_id: &str,
+    offset: u64,
+    limit: u64,
+    presentation: ListPresentationProfile,
+) -> Result<TopicPage, ClipKittyError> {
+    let members = db
+        .fetch_topic_member_ids(topic_id)?
+        .ok_or_else(|| ClipKittyError::InvalidInput(format!("unknown topic `{topic_id}`")))?;
+    let total_count = members.len() as u64;
+    let page: Vec<i64> = members
+        .into_iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+        .collect();
+    Ok(TopicPage {
+        matches: baseline_matches(db, &page, presentation)?,
+        total_count,
+    })
+}
+
+/// Cluster the newest items and replace every topic with the result.
+fn regroup(db: &Database) -> Result<(), ClipKittyError> {
+    let documents = db.fetch_topic_documents(0, MAX_CORPUS_ITEMS, DOCUMENT_PREFIX_CHARS)?;
+    let last_item_id = documents.first().map_or(0, |(row_id, _)| *row_id);
+    let documents: Vec<(i64, HashMap<String, usize>)> = documents
+        .iter()
+        .filter(|(_, text)| !is_probably_secret(text))
+        .map(|(row_id, text)| (*row_id, term_counts(text)))
+        .filter(|(_, counts)| !counts.is_empty())
+        .collect();
+
+    let mut model = TopicModel {
+        last_item_id,
+        corpus_size: documents.len() as u64,
+        added_since_grouping: 0,
+        idf: HashMap::new(),
+        default_idf: 0.0,
+    };
+    let mut topics = Vec::new();
+    let mut members = Vec::new();
+    if documents.len() >= MIN_CORPUS_ITEMS {
+        let clustering = cluster(&documents);
+        let mut labels = HashSet::new();
+        for (centroid, rows) in clustering.topics {
+            let topic_id = uuid::Uuid::new_v4().to_string();
+            for (term, _) in &centroid {
+                model.idf.insert(term.clone(), clustering.idf[term]);
+            }
+            members.extend(rows.into_iter().map(|row_id| (row_id, topic_id.clone())));
+            let label = label(&centroid, &mut labels);
+            topics.push((topic_id, label, encode_centroid(&centroid)));
+        }
+        model.default_idf = clustering.default_idf;
+    }
+    db.replace_topics(&topics, &members)?;
+    save_model(db, &model)
+}
+
+/// Put each item copied since the last run in the topic whose centroid it
+/// is most similar to, if it is similar enough.
+fn place_new_items(db: &Database, model: &mut TopicModel) -> Result<(), ClipKittyError> {
+    let documents =
+        db.fetch_topic_documents(model.last_item_id, MAX_CORPUS_ITEMS, DOCUMENT_PREFIX_CHARS)?;
+    let Some((newest, _)) = documents.first() else {
+        return Ok(());
+    };
+    model.last_item_id = *newest;
+    model.added_since_grouping += documents.len() as u64;
+
+    let centroids = db
+        .fetch_topic_centroids()?
+        .into_iter()
+        .map(|(topic_id, encoded)| Ok((topic_id, decode_centroid(&encoded)?)))
+        .collect::<Result<Vec<_>, DatabaseError>>()?;
+    if centroids.is_empty() {
+        return Ok(());
+    }
+    let mut members = Vec::new();
+    for (row_id, text) in &documents {
+        if is_probably_secret(text) {
+            continue;
+        }
+        let weights: HashMap<String, f32> = term_counts(text)
+            .into_iter()
+            .map(|(term, count)| {
+                let idf = model.idf.get(&term).copied().unwrap_or(model.default_idf);
+                (term, tf(count) * idf)
+            })
+            .collect();
+        let norm = weights.values().map(|w| w * w).sum::<f32>().sqrt();
+        if norm == 0.0 {
+            continue;
+        }
+        let best = centroids
+            .iter()
+            .map(|(topic_id, centroid)| {
+                let dot: f32 = centroid
+                    .iter()
+                    .filter_map(|(term, weight)| weights.get(term).map(|w| w * weight))
+                    .sum();
+                (topic_id, dot / norm)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((topic_id, similarity)) = best {
+            if similarity >= MIN_SIMILARITY {
+                members.push((*row_id, topic_id.clone()));
+            }
+        }
+    }
+    Ok(db.add_topic_members(&members)?)
+}
+
+struct Clustering {
+    /// Each kept topic's trimmed centroid and member row IDs.
+    topics: Vec<(Centroid, Vec<i64>)>,
+    idf: HashMap<String, f32>,
+    default_idf: f32,
+}
+
+/// Spherical k-means over TF-IDF vectors. Deterministic: the first centroid
+/// is the newest item and each next one the item least like those chosen.
+fn cluster(documents: &[(i64, HashMap<String, usize>)]) -> Clustering {
+    let n = documents.len();
+    // Terms in one item say nothing about grouping, and terms in most of
+    // them say nothing about any one group.
+    let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+    for (_, counts) in documents {
+        for term in counts.keys() {
+            *document_frequency.entry(term).or_default() += 1;
+        }
+    }
+    let mut vocabulary: Vec<&str> = document_frequency
+        .iter()
+        .filter(|(_, &df)| df >= 2 && df * 2 <= n)
+        .map(|(&term, _)| term)
+        .collect();
+    vocabulary.sort_unstable();
+    let index: HashMap<&str, usize> = vocabulary
+        .iter()
+        .enumerate()
+        .map(|(i, term)| (*term, i))
+        .collect();
+    let idf: Vec<f32> = vocabulary
+        .iter()
+        .map(|term| (n as f32 / document_frequency[term] as f32).ln())
+        .collect();
+
+    let vectors: Vec<(i64, Vec<(usize, f32)>)> = documents
+        .iter()
+        .filter_map(|(row_id, counts)| {
+            let mut vector: Vec<(usize, f32)> = counts
+                .iter()
+                .filter_map(|(term, &count)| {
+                    let i = *index.get(term.as_str())?;
+                    Some((i, tf(count) * idf[i]))
+                })
+                .collect();
+            let norm = vector.iter().map(|(_, w)| w * w).sum::<f32>().sqrt();
+            if norm == 0.0 {
+                return None;
+            }
+            vector.sort_unstable_by_key(|(i, _)| *i);
+            vector.iter_mut().for_each(|(_, w)| *w /= norm);
+            Some((*row_id, vector))
+        })
+        .collect();
+
+    let k = ((vectors.len() as f64 / 2.0).sqrt().round() as usize)
+        .clamp(2, MAX_TOPICS)
+        .min(vectors.len() / MIN_TOPIC_ITEMS);
+    let mut centroids = initial_centroids(&vectors, k, vocabulary.len());
+    let mut assignment = vec![usize::MAX; vectors.len()];
+    for _ in 0..MAX_ITERATIONS {
+        let mut changed = false;
+        for (slot, (_, vector)) in assignment.iter_mut().zip(&vectors) {
+            let nearest = nearest(&centroids, vector);
+            changed |= *slot != nearest;
+            *slot = nearest;
+        }
+        if !changed {
+            break;
+        }
+        for (c, centroid) in centroids.iter_mut().enumerate() {
+            let mut sum = vec![0.0f32; vocabulary.len()];
+            for ((_, vector), _) in vectors.iter().zip(&assignment).filter(|(_, &a)| a == c) {
+                for &(i, w) in vector {
+                    sum[i] += w;
+                }
+            }
+            let norm = sum.iter().map(|w| w * w).sum::<f32>().sqrt();
+            // An emptied cluster keeps its centroid.
+            if norm > 0.0 {
+                *centroid = sum.into_iter().map(|w| w / norm).collect();
+            }
+        }
+    }
+
+    let mut topics = Vec::new();
+    for (c, centroid) in centroids.iter().enumerate() {
+        let rows: Vec<i64> = vectors
+            .iter()
+            .zip(&assignment)
+            .filter(|((_, vector), &a)| a == c && dot(centroid, vector) >= MIN_SIMILARITY)
+            .map(|((row_id, _), _)| *row_id)
+            .collect();
+        if rows.len() < MIN_TOPIC_ITEMS {
+            continue;
+        }
+        let mut terms: Centroid = centroid
+            .iter()
+            .enumerate()
+            .filter(|(_, &w)| w > 0.0)
+            .map(|(i, &w)| (vocabulary[i].to_string(), w))
+            .collect();
+        terms.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        terms.truncate(CENTROID_TERMS);
+        topics.push((terms, rows));
+    }
+    Clustering {
+        topics,
+        idf: vocabulary
+            .iter()
+            .zip(&idf)
+            .map(|(term, &idf)| (term.to_string(), idf))
+            .collect(),
+        default_idf: (n as f32).ln(),
+    }
+}
+
+fn initial_centroids(
+    vectors: &[(i64, Vec<(usize, f32)>)],
+    k: usize,
+    dimensions: usize,
+) -> Vec<Vec<f32>> {
+    let dense = |vector: &[(usize, f32)]| {
+        let mut centroid = vec![0.0f32; dimensions];
+        for &(i, w) in vector {
+            centroid[i] = w;
+        }
+        centroid
+    };
+    let mut centroids = Vec::with_capacity(k);
+    let Some((_, first)) = vectors.first() else {
+        return centroids;
+    };
+    centroids.push(dense(first));
+    let mut closest: Vec<f32> = vectors.iter().map(|(_, v)| dot(&centroids[0], v)).collect();
+    while centroids.len() < k {
+        let Some((pick, _)) = closest.iter().enumerate().min_by(|a, b| a.1.total_cmp(b.1)) else {
+            break;
+        };
+        centroids.push(dense(&vectors[pick].1));
+        let added = centroids.last().expect("just pushed");
+        for (similarity, (_, vector)) in closest.iter_mut().zip(vectors) {
+            *similarity = similarity.max(dot(added, vector));
+        }
+    }
+    centroids
+}
+
+/// Index of the centroid most similar to `vector`, the first on a tie.
+fn nearest(centroids: &[Vec<f32>], vector: &[(usize, f32)]) -> usize {
+    centroids
+        .iter()
+        .map(|centroid| dot(centroid, vector))
+        .enumerate()
+        .fold((0, f32::MIN), |best, (c, similarity)| {
+            if similarity > best.1 {
+                (c, similarity)
+            } else {
+                best
+            }
+        })
+        .0
+}
+
+fn dot(centroid: &[f32], vector: &[(usize, f32)]) -> f32 {
+    vector.iter().map(|&(i, w)| centroid[i] * w).sum()
+}
+
+fn tf(count: usize) -> f32 {
+    1.0 + (count as f32).ln()
+}
+
+/// Lowercased words of 3 to 24 characters, mostly letters, that are not
+/// stop words, with how often each occurs.
+fn term_counts(text: &str) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        let chars = word.chars().count();
+        if !(3..=24).contains(&chars) {
+            continue;
+        }
+        let letters = word.chars().filter(|c| c.is_alphabetic()).count();
+        let word = word.to_lowercase();
+        if letters * 2 <= chars || STOP_WORDS.contains(&word.as_str()) {
+            continue;
+        }
+        *counts.entry(word).or_default() += 1;
+    }
+    counts
+}
+
+/// The heaviest term, capitalized, and the second when it weighs nearly as
+/// much. Terms are added until the label differs from those already used.
+fn label(centroid: &Centroid, used: &mut HashSet<String>) -> String {
+    let mut words: Vec<&str> = Vec::new();
+    for (position, (term, weight)) in centroid.iter().enumerate() {
+        let wanted = position == 0
+            || (position == 1 && *weight >= centroid[0].1 * LABEL_SECOND_TERM_SHARE)
+            || used.contains(&words.join(" "));
+        if !wanted {
+            break;
+        }
+        words.push(term);
+    }
+    let mut label = words.join(" ");
+    if let Some(first) = label.chars().next() {
+        label = first.to_uppercase().chain(label.chars().skip(1)).collect();
+    }
+    used.insert(words.join(" "));
+    label
+}
+
+fn encode_centroid(centroid: &Centroid) -> String {
+    serde_json::to_string(centroid).expect("a centroid always serializes")
+}
+
+fn decode_centroid(encoded: &str) -> Result<Centroid, DatabaseError> {
+    serde_json::from_str(encoded)
+        .map_err(|_| DatabaseError::InconsistentData("topic centroid is unreadable".into()))
+}
+
+fn load_model(db: &Database) -> Result<Option<TopicModel>, ClipKittyError> {
+    let Some(bytes) = db.get_key_value(TOPICS_NAMESPACE, MODEL_KEY)? else {
+        return Ok(None);
+    };
+    let model = serde_json::from_slice(&bytes)
+        .map_err(|_| DatabaseError::InconsistentData("topic model is unreadable".into()))?;
+    Ok(Some(model))
+}
+
+fn save_model(db: &Database, model: &TopicModel) -> Result<(), ClipKittyError> {
+    let encoded = serde_json::to_vec(model).expect("a topic model always serializes");
+    Ok(db.put_key_value(TOPICS_NAMESPACE, MODEL_KEY, &encoded)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StoredItem;
+
+    const KUBERNETES: &[&str] = &[
+        "kubectl get pods -n staging on the kubernetes cluster",
+        "kubernetes deployment rollout restart for the api pods",
+        "kubectl describe pod api-7f9c crashloop kubernetes",
+        "scale the kubernetes deployment to three pods",
+        "kubernetes cluster upgrade notes: drain nodes before pods move",
+        "kubectl logs pods api kubernetes staging",
+    ];
+    const APARTMENT: &[&str] = &[
+        "apartment viewing Tuesday, rent 1800 with deposit",
+        "landlord says the apartment rent includes heating",
+        "second apartment: rent 1650, deposit two months, landlord nice",
+        "apartment checklist: deposit, lease length, rent increases",
+        "email landlord about the apartment lease and rent",
+        "apartment near the park, rent negotiable, lease starts June",
+    ];
+    const JAPAN: &[&str] = &[
+        "Kyoto ryokan booking for the japan trip",
+        "japan trip itinerary: Tokyo, Kyoto, Osaka by shinkansen",
+        "shinkansen tickets Tokyo to Kyoto, japan rail pass",
+        "ryokan in Kyoto with onsen, japan trip day four",
+        "Osaka street food list for the japan trip",
+        "japan rail pass covers the shinkansen to Osaka",
+    ];
+
+    fn insert(db: &Database, texts: &[&str]) -> Vec<i64> {
+        texts
+            .iter()
+            .map(|text| {
+                db.insert_item(&StoredItem::new_text(text.to_string(), None, None))
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    fn topic_of(db: &Database, row_id: i64) -> Option<String> {
+        list_topics(db).unwrap().into_iter().find_map(|topic| {
+            db.fetch_topic_member_ids(&topic.topic_id)
+                .unwrap()
+                .unwrap()
+                .contains(&row_id)
+                .then_some(topic.topic_id)
+        })
+    }
+
+    #[test]
+    fn groups_history_into_labeled_topics_and_places_new_items() {
+        let db = Database::open_in_memory().unwrap();
+        let kubernetes = insert(&db, KUBERNETES);
+        let apartment = insert(&db, APARTMENT);
+        let japan = insert(&db, JAPAN);
+        refresh(&db).unwrap();
+
+        let topics = list_topics(&db).unwrap();
+        assert_eq!(topics.len(), 3, "{topics:?}");
+        for (rows, word) in [
+            (&kubernetes, "kubernetes"),
+            (&apartment, "apartment"),
+            (&japan, "japan"),
+        ] {
+            let topic_id = topic_of(&db, rows[0]).unwrap();
+            let topic = topics.iter().find(|t| t.topic_id == topic_id).unwrap();
+            assert!(topic.label.to_lowercase().contains(word), "{topic:?}");
+            for row_id in rows {
+                assert_eq!(topic_of(&db, *row_id).as_ref(), Some(&topic_id));
+            }
+        }
+
+        let later = insert(
+            &db,
+            &[
+                "kubectl rollout status for the kubernetes pods",
+                "Grocery list: milk, eggs, bread",
+            ],
+        );
+        refresh(&db).unwrap();
+        assert_eq!(topic_of(&db, later[0]), topic_of(&db, kubernetes[0]));
+        assert_eq!(topic_of(&db, later[1]), None);
+        assert_eq!(list_topics(&db).unwrap().len(), 3);
+
+        let page = browse_topic(
+            &db,
+            &topic_of(&db, kubernetes[0]).unwrap(),
+            0,
+            2,
+            ListPresentationProfile::CompactRow,
+        )
+        .unwrap();
+        assert_eq!(page.total_count, 7);
+        assert_eq!(page.matches.len(), 2);
+        assert!(matches!(
+            browse_topic(&db, "nope", 0, 2, ListPresentationProfile::CompactRow),
+            Err(ClipKittyError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn small_histories_have_no_topics() {
+        let db = Database::open_in_memory().unwrap();
+        insert(&db, &KUBERNETES[..4]);
+        refresh(&db).unwrap();
+        assert!(list_topics(&db).unwrap().is_empty());
+    }
+}
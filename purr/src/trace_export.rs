@@ -0,0 +1,251 @@
+//! Developer trace export.
+//!
+//! Search phases, hot database calls, and index commits run inside `tracing`
+//! spans. They cost one atomic load each until `start` installs the exporter,
+//! which appends every finished span to a file as one OTLP/JSON
+//! `ExportTraceServiceRequest` per line: the format the OpenTelemetry
+//! collector's file receiver reads and that Jaeger and Perfetto can import.
+
+use crate::interface::ClipKittyError;
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::Interest;
+use tracing::{Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+/// OTLP `SPAN_KIND_INTERNAL`.
+const SPAN_KIND_INTERNAL: u8 = 1;
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static WRITER: Lazy<Mutex<Option<BufWriter<File>>>> = Lazy::new(|| Mutex::new(None));
+static INSTALLED: OnceCell<()> = OnceCell::new();
+
+/// Start appending finished spans to `path`, replacing any previous target.
+pub(crate) fn start(path: &Path) -> Result<(), ClipKittyError> {
+    INSTALLED.get_or_try_init(|| {
+        let subscriber = tracing_subscriber::registry().with(ExportLayer);
+        tracing::subscriber::set_global_default(subscriber).map_err(|_| {
+            ClipKittyError::InvalidInput("another tracing subscriber is already installed".into())
+        })
+    })?;
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|error| {
+            ClipKittyError::InvalidInput(format!(
+                "cannot open trace file {}: {error}",
+                path.display()
+            ))
+        })?;
+    *WRITER.lock() = Some(BufWriter::new(file));
+    ACTIVE.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Stop exporting and flush buffered spans. A no-op when not exporting.
+pub(crate) fn stop() -> Result<(), ClipKittyError> {
+    ACTIVE.store(false, Ordering::Relaxed);
+    if let Some(mut writer) = WRITER.lock().take() {
+        writer.flush().map_err(|error| {
+            ClipKittyError::InvalidInput(format!("trace flush failed: {error}"))
+        })?;
+    }
+    Ok(())
+}
+
+/// Per-span state kept in the registry's extensions until the span closes.
+struct SpanRecord {
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+    parent_span_id: Option<[u8; 8]>,
+    start_unix_nanos: u128,
+    attributes: Vec<Value>,
+}
+
+struct ExportLayer;
+
+impl<S> Layer<S> for ExportLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        Interest::sometimes()
+    }
+
+    fn enabled(&self, _metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+        ACTIVE.load(Ordering::Relaxed)
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let parent = span.parent().and_then(|parent| {
+            parent
+                .extensions()
+                .get::<SpanRecord>()
+                .map(|record| (record.trace_id, record.span_id))
+        });
+        let (trace_id, parent_span_id) = match parent {
+            Some((trace_id, parent_span_id)) => (trace_id, Some(parent_span_id)),
+            None => (rand::random(), None),
+        };
+        let mut visitor = AttributeVisitor::default();
+        attrs.record(&mut visitor);
+        span.extensions_mut().insert(SpanRecord {
+            trace_id,
+            span_id: rand::random(),
+            parent_span_id,
+            start_unix_nanos: unix_nanos(),
+            attributes: visitor.0,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut visitor = AttributeVisitor::default();
+        values.record(&mut visitor);
+        let mut extensions = span.extensions_mut();
+        if let Some(record) = extensions.get_mut::<SpanRecord>() {
+            record.attributes.extend(visitor.0);
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let record = span.extensions_mut().remove::<SpanRecord>();
+        let Some(record) = record else {
+            return;
+        };
+        let line = export_request(span.name(), &record, unix_nanos());
+        if let Some(writer) = WRITER.lock().as_mut() {
+            let _ = writeln!(writer, "{line}");
+        }
+    }
+}
+
+fn export_request(name: &str, record: &SpanRecord, end_unix_nanos: u128) -> Value {
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [attribute("service.name", json!({ "stringValue": "purr" }))],
+            },
+            "scopeSpans": [{
+                "scope": { "name": "purr" },
+                "spans": [{
+                    "traceId": hex(&record.trace_id),
+                    "spanId": hex(&record.span_id),
+                    "parentSpanId": record.parent_span_id.as_ref().map(|id| hex(id)).unwrap_or_default(),
+                    "name": name,
+                    "kind": SPAN_KIND_INTERNAL,
+                    "startTimeUnixNano": record.start_unix_nanos.to_string(),
+                    "endTimeUnixNano": end_unix_nanos.to_string(),
+                    "attributes": record.attributes,
+                }],
+            }],
+        }],
+    })
+}
+
+fn attribute(key: &str, value: Value) -> Value {
+    json!({ "key": key, "value": value })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn unix_nanos() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or(0)
+}
+
+/// Collects span fields as OTLP `KeyValue` attributes.
+#[derive(Default)]
+struct AttributeVisitor(Vec<Value>);
+
+impl Visit for AttributeVisitor {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.push(attribute(
+            field.name(),
+            json!({ "intValue": value.to_string() }),
+        ));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.push(attribute(
+            field.name(),
+            json!({ "intValue": value.to_string() }),
+        ));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0
+            .push(attribute(field.name(), json!({ "boolValue": value })));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0
+            .push(attribute(field.name(), json!({ "stringValue": value })));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.push(attribute(
+            field.name(),
+            json!({ "stringValue": format!("{value:?}") }),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_nested_spans_as_otlp_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trace.jsonl");
+        start(&path).unwrap();
+        {
+            let _outer = tracing::info_span!("trace_export_test.outer", items = 3u64).entered();
+            let _inner = tracing::debug_span!("trace_export_test.inner", phase = "one").entered();
+        }
+        stop().unwrap();
+
+        let spans: Vec<Value> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .map(|request| request["resourceSpans"][0]["scopeSpans"][0]["spans"][0].clone())
+            .filter(|span| {
+                span["name"]
+                    .as_str()
+                    .is_some_and(|name| name.starts_with("trace_export_test."))
+            })
+            .collect();
+        assert_eq!(spans.len(), 2);
+        let (inner, outer) = (&spans[0], &spans[1]);
+        assert_eq!(inner["name"], "trace_export_test.inner");
+        assert_eq!(inner["traceId"], outer["traceId"]);
+        assert_eq!(inner["parentSpanId"], outer["spanId"]);
+        assert_eq!(outer["parentSpanId"], "");
+        assert_eq!(outer["attributes"][0]["value"]["intValue"], "3");
+        assert_eq!(inner["attributes"][0]["value"]["stringValue"], "one");
+    }
+}
@@ -0,0 +1,194 @@
+//! Built-in rewrites of an item's text, for pasting it in another shape.
+//!
+//! Every transformation works on the text as copied: the Markdown source
+//! rather than its render, a link's URL, a color's value. Only
+//! `StripFormatting` starts from the plain text lists and search show.
+
+use base64::Engine;
+
+use crate::interface::{ClipboardContent, StructuredFormat, StructuredStyle, Transformation};
+use crate::structured_data;
+
+/// Why an item's text could not be transformed, completing "item `…` ".
+pub(crate) type TransformError = &'static str;
+
+/// `content`'s text with `transformation` applied.
+pub(crate) fn apply(
+    content: &ClipboardContent,
+    transformation: Transformation,
+) -> Result<String, TransformError> {
+    let text = match content {
+        ClipboardContent::Image { .. } | ClipboardContent::File { .. } => {
+            return Err("has no text to transform")
+        }
+        _ if transformation == Transformation::StripFormatting => {
+            return Ok(content.text_content().to_string())
+        }
+        ClipboardContent::Markdown { source, .. } => source.as_str(),
+        other => other.text_content(),
+    };
+    match transformation {
+        Transformation::TrimWhitespace => Ok(trim_whitespace(text)),
+        Transformation::StripFormatting => Ok(text.to_string()),
+        Transformation::Uppercase => Ok(text.to_uppercase()),
+        Transformation::Lowercase => Ok(text.to_lowercase()),
+        Transformation::TitleCase => Ok(title_case(text)),
+        Transformation::UrlDecode => percent_decode(text),
+        Transformation::Base64Decode => base64_decode(text),
+        Transformation::JsonPrettify => {
+            match structured_data::format_structured(text, StructuredStyle::Pretty) {
+                Some(formatted) if formatted.format == StructuredFormat::Json => Ok(formatted.text),
+                _ => Err("is not JSON"),
+            }
+        }
+    }
+}
+
+/// Blank space around the text and at the end of each line removed.
+fn trim_whitespace(text: &str) -> String {
+    text.trim()
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn title_case(text: &str) -> String {
+    let mut titled = String::with_capacity(text.len());
+    let mut at_word_start = true;
+    for c in text.chars() {
+        if at_word_start {
+            titled.extend(c.to_uppercase());
+        } else {
+            titled.extend(c.to_lowercase());
+        }
+        at_word_start = c.is_whitespace();
+    }
+    titled
+}
+
+/// `%XX` escapes decoded; a `%` not followed by two hex digits, and `+`,
+/// are kept as written.
+fn percent_decode(text: &str) -> Result<String, TransformError> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| bytes.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| "does not decode to text")
+}
+
+/// Standard or URL-safe base64, padded or not, ignoring line breaks.
+fn base64_decode(text: &str) -> Result<String, TransformError> {
+    use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let bytes = [STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD]
+        .iter()
+        .find_map(|engine| engine.decode(&compact).ok())
+        .ok_or("is not valid base64")?;
+    String::from_utf8(bytes).map_err(|_| "does not decode to text")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> ClipboardContent {
+        ClipboardContent::Text {
+            value: value.into(),
+        }
+    }
+
+    #[test]
+    fn applies_each_built_in_transformation() {
+        let cases = [
+            (
+                Transformation::TrimWhitespace,
+                "  one  \n two\t\n\n",
+                "one\n two",
+            ),
+            (Transformation::Uppercase, "straße", "STRASSE"),
+            (Transformation::Lowercase, "Hello World", "hello world"),
+            (
+                Transformation::TitleCase,
+                "the QUICK brown-fox",
+                "The Quick Brown-fox",
+            ),
+            (Transformation::UrlDecode, "a%20b%2Fc+d%zz%", "a b/c+d%zz%"),
+            (
+                Transformation::Base64Decode,
+                "aGVsbG8g\nd29ybGQ=",
+                "hello world",
+            ),
+            (Transformation::Base64Decode, "aGk_Pz8-", "hi???>"),
+            (
+                Transformation::JsonPrettify,
+                r#"{"a":[1]}"#,
+                "{\n  \"a\": [\n    1\n  ]\n}",
+            ),
+        ];
+        for (transformation, input, expected) in cases {
+            assert_eq!(
+                apply(&text(input), transformation).as_deref(),
+                Ok(expected),
+                "{transformation:?} {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn strips_formatting_but_otherwise_keeps_the_source() {
+        let markdown = ClipboardContent::Markdown {
+            value: "Title\n• item".into(),
+            source: "# Title\n- item".into(),
+        };
+        assert_eq!(
+            apply(&markdown, Transformation::StripFormatting).as_deref(),
+            Ok("Title\n• item")
+        );
+        assert_eq!(
+            apply(&markdown, Transformation::Uppercase).as_deref(),
+            Ok("# TITLE\n- ITEM")
+        );
+    }
+
+    #[test]
+    fn rejects_text_the_transformation_does_not_fit() {
+        assert_eq!(
+            apply(&text("not base64!"), Transformation::Base64Decode),
+            Err("is not valid base64")
+        );
+        assert_eq!(
+            apply(&text("/w=="), Transformation::Base64Decode),
+            Err("does not decode to text")
+        );
+        assert_eq!(
+            apply(&text("key: value"), Transformation::JsonPrettify),
+            Err("is not JSON")
+        );
+        let image = ClipboardContent::Image {
+            data: vec![1],
+            description: "Image".into(),
+            is_animated: false,
+        };
+        assert_eq!(
+            apply(&image, Transformation::Uppercase),
+            Err("has no text to transform")
+        );
+    }
+}
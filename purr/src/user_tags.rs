@@ -0,0 +1,166 @@
+//! User-defined tags on clipboard items.
+//!
+//! Tags live in the `tags` table and ride along on every search document of
+//! the item they tag, so a `#name` word in a query restricts results to the
+//! items carrying that tag. Names are canonical: trimmed, without a leading
+//! `#`, and lowercased.
+
+use crate::database::{Database, TagRestriction};
+use crate::indexer::Indexer;
+use crate::interface::{ClipKittyError, TagFilter, TagSummary};
+use crate::save_service::{self, ReindexOutcome};
+use crate::search::filter_words;
+
+const MAX_TAG_CHARS: usize = 64;
+
+pub(crate) fn canonical_name(name: &str) -> String {
+    name.trim().trim_start_matches('#').to_lowercase()
+}
+
+fn validated_name(name: &str) -> Result<String, ClipKittyError> {
+    let canonical = canonical_name(name);
+    if canonical.is_empty() {
+        return Err(ClipKittyError::InvalidInput("tag name is empty".into()));
+    }
+    if canonical.chars().any(char::is_whitespace) {
+        return Err(ClipKittyError::InvalidInput(format!(
+            "tag `{canonical}` contains whitespace"
+        )));
+    }
+    if canonical.chars().count() > MAX_TAG_CHARS {
+        return Err(ClipKittyError::InvalidInput(format!(
+            "tag names are limited to {MAX_TAG_CHARS} characters"
+        )));
+    }
+    Ok(canonical)
+}
+
+pub(crate) fn add(
+    db: &Database,
+    indexer: &Indexer,
+    item_id: i64,
+    name: &str,
+) -> Result<ReindexOutcome, ClipKittyError> {
+    db.add_user_tag(item_id, &validated_name(name)?)?;
+    save_service::reindex_item(db, indexer, item_id)
+}
+
+pub(crate) fn remove(
+    db: &Database,
+    indexer: &Indexer,
+    item_id: i64,
+    name: &str,
+) -> Result<ReindexOutcome, ClipKittyError> {
+    db.remove_user_tag(item_id, &canonical_name(name))?;
+    save_service::reindex_item(db, indexer, item_id)
+}
+
+pub(crate) fn list(db: &Database) -> Result<Vec<TagSummary>, ClipKittyError> {
+    Ok(db
+        .fetch_user_tag_counts()?
+        .into_iter()
+        .map(|(name, item_count)| TagSummary { name, item_count })
+        .collect())
+}
+
+/// `filter` with canonical tag names, or None when it restricts nothing.
+pub(crate) fn canonical_filter(filter: &TagFilter) -> Option<TagFilter> {
+    let canonical = |tags: &[String]| tags.iter().map(|tag| canonical_name(tag)).collect();
+    match filter {
+        TagFilter::All { tags } if tags.is_empty() => None,
+        TagFilter::All { tags } => Some(TagFilter::All {
+            tags: canonical(tags),
+        }),
+        TagFilter::Any { tags } => Some(TagFilter::Any {
+            tags: canonical(tags),
+        }),
+    }
+}
+
+/// Take `#name` words naming existing tags out of `query` and add them to
+/// `tags` as one all-of filter. Other `#` words, such as hex colors, and
+/// quoted or escaped ones stay in the query text. Returns the remaining
+/// query text.
+pub(crate) fn extract_hashtags(
+    db: &Database,
+    query: &str,
+    tags: &mut TagRestriction,
+) -> Result<String, ClipKittyError> {
+    let words = filter_words(query);
+    let is_hashtag =
+        |&(word, can_filter): &(&str, bool)| can_filter && word.len() > 1 && word.starts_with('#');
+    let candidates: Vec<String> = words
+        .iter()
+        .filter(|word| is_hashtag(word))
+        .map(|&(word, _)| canonical_name(word))
+        .collect();
+    if candidates.is_empty() {
+        return Ok(query.to_string());
+    }
+    let existing = db.filter_existing_user_tags(&candidates)?;
+    if existing.is_empty() {
+        return Ok(query.to_string());
+    }
+
+    let mut names = Vec::new();
+    let mut remaining = Vec::new();
+    for candidate in &words {
+        let word = candidate.0;
+        let name = canonical_name(word);
+        if is_hashtag(candidate) && existing.contains(&name) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        } else {
+            remaining.push(word);
+        }
+    }
+    tags.user.push(TagFilter::All { tags: names });
+    Ok(remaining.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StoredItem;
+
+    #[test]
+    fn hashtags_for_existing_tags_become_filters() {
+        let db = Database::open_in_memory().unwrap();
+        let item = StoredItem::new_text("standup notes".into(), None, None);
+        let row_id = db.insert_item(&item).unwrap();
+        db.add_user_tag(row_id, "work").unwrap();
+
+        let mut tags = TagRestriction::default();
+        let text = extract_hashtags(&db, "notes #Work #ff0000 #work", &mut tags).unwrap();
+        assert_eq!(text, "notes #ff0000");
+        assert_eq!(
+            tags.user,
+            vec![TagFilter::All {
+                tags: vec!["work".into()]
+            }]
+        );
+
+        let mut untouched = TagRestriction::default();
+        assert_eq!(
+            extract_hashtags(&db, "#ff0000  swatch", &mut untouched).unwrap(),
+            "#ff0000  swatch"
+        );
+        assert!(untouched.is_empty());
+
+        let mut literal = TagRestriction::default();
+        assert_eq!(
+            extract_hashtags(&db, "\\#work \"#work\" -#work", &mut literal).unwrap(),
+            "\\#work \"#work\" -#work"
+        );
+        assert!(literal.is_empty());
+    }
+
+    #[test]
+    fn names_are_validated_and_canonical() {
+        assert_eq!(validated_name("  #Work ").unwrap(), "work");
+        assert!(validated_name("#").is_err());
+        assert!(validated_name("two words").is_err());
+        assert!(validated_name(&"x".repeat(MAX_TAG_CHARS + 1)).is_err());
+    }
+}
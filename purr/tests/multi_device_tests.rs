@@ -65,6 +65,7 @@ impl TestDevice {
 
 fn text_snapshot(text: &str) -> ItemSnapshotData {
     ItemSnapshotData {
+        external_id: None,
         content_type: "text".to_string(),
         content_text: text.to_string(),
         content_hash: format!("hash_{text}"),
@@ -74,6 +75,7 @@ fn text_snapshot(text: &str) -> ItemSnapshotData {
         is_bookmarked: false,
         thumbnail_base64: None,
         color_rgba: None,
+        reminder_at_unix: None,
         type_specific: TypeSpecificData::Text {
             value: text.to_string(),
         },
@@ -152,7 +154,7 @@ fn test_concurrent_edit_conflict_forks() {
         "shared-item-1".to_string(),
         "device-a",
         ItemEventPayload::ItemCreated {
-            snapshot: text_snapshot("original text"),
+            snapshot: Box::new(text_snapshot("original text")),
         },
     );
 
@@ -235,7 +237,7 @@ fn test_out_of_order_events_deferred_then_resolved() {
         "item-ooo".to_string(),
         "device-a",
         ItemEventPayload::ItemCreated {
-            snapshot: text_snapshot("original"),
+            snapshot: Box::new(text_snapshot("original")),
         },
     );
 
@@ -275,7 +277,7 @@ fn test_delete_on_one_device_tombstones_on_other() {
         "item-del".to_string(),
         "device-a",
         ItemEventPayload::ItemCreated {
-            snapshot: text_snapshot("will be deleted"),
+            snapshot: Box::new(text_snapshot("will be deleted")),
         },
     );
     replay::apply_remote_event(device_a.db.pool(), &create_event).unwrap();
@@ -311,7 +313,7 @@ fn test_bookmark_and_edit_apply_independently() {
         "item-indep".to_string(),
         "device-a",
         ItemEventPayload::ItemCreated {
-            snapshot: text_snapshot("independent domains"),
+            snapshot: Box::new(text_snapshot("independent domains")),
         },
     );
     replay::apply_remote_event(device_a.db.pool(), &create_event).unwrap();
@@ -357,7 +359,7 @@ fn test_duplicate_event_across_devices() {
         "item-dup".to_string(),
         "device-a",
         ItemEventPayload::ItemCreated {
-            snapshot: text_snapshot("dedup test"),
+            snapshot: Box::new(text_snapshot("dedup test")),
         },
     );
 
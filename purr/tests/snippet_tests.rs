@@ -419,8 +419,12 @@ async fn format_excerpt_matches_profile() {
     let store = ClipboardStore::new(db_path.to_str().unwrap().to_string()).unwrap();
 
     let content = "Hello\nWorld\nFoo";
-    let compact = store.format_excerpt(content.to_string(), ListPresentationProfile::CompactRow);
-    let card = store.format_excerpt(content.to_string(), ListPresentationProfile::Card);
+    let compact = store
+        .format_excerpt(content.to_string(), ListPresentationProfile::CompactRow)
+        .unwrap();
+    let card = store
+        .format_excerpt(content.to_string(), ListPresentationProfile::Card)
+        .unwrap();
 
     assert!(
         !compact.contains('\n'),
@@ -47,6 +47,7 @@ fn store_db(dir: &TempDir) -> Database {
 
 fn text_snapshot(text: &str) -> ItemSnapshotData {
     ItemSnapshotData {
+        external_id: None,
         content_type: "text".to_string(),
         content_text: text.to_string(),
         content_hash: format!("hash_{text}"),
@@ -56,6 +57,7 @@ fn text_snapshot(text: &str) -> ItemSnapshotData {
         is_bookmarked: false,
         thumbnail_base64: None,
         color_rgba: None,
+        reminder_at_unix: None,
         type_specific: TypeSpecificData::Text {
             value: text.to_string(),
         },
@@ -64,6 +66,7 @@ fn text_snapshot(text: &str) -> ItemSnapshotData {
 
 fn image_snapshot(desc: &str) -> ItemSnapshotData {
     ItemSnapshotData {
+        external_id: None,
         content_type: "image".to_string(),
         content_text: desc.to_string(),
         content_hash: format!("hash_img_{desc}"),
@@ -73,6 +76,7 @@ fn image_snapshot(desc: &str) -> ItemSnapshotData {
         is_bookmarked: false,
         thumbnail_base64: None,
         color_rgba: None,
+        reminder_at_unix: None,
         type_specific: TypeSpecificData::Image {
             data_base64: "iVBORw0KGgo=".to_string(),
             description: desc.to_string(),
@@ -83,6 +87,7 @@ fn image_snapshot(desc: &str) -> ItemSnapshotData {
 
 fn link_snapshot(url: &str) -> ItemSnapshotData {
     ItemSnapshotData {
+        external_id: None,
         content_type: "link".to_string(),
         content_text: url.to_string(),
         content_hash: format!("hash_link_{url}"),
@@ -92,6 +97,7 @@ fn link_snapshot(url: &str) -> ItemSnapshotData {
         is_bookmarked: false,
         thumbnail_base64: None,
         color_rgba: None,
+        reminder_at_unix: None,
         type_specific: TypeSpecificData::Link {
             url: url.to_string(),
             metadata: None,
@@ -140,6 +146,7 @@ fn default_versions() -> VersionVector {
         existence: 1,
         touch: 1,
         metadata: 1,
+        reminder: 0,
     }
 }
 
@@ -190,7 +197,7 @@ mod projector_tests {
     fn item_created_on_empty_aggregate_applies() {
         let snapshot = text_snapshot("hello");
         let payload = ItemEventPayload::ItemCreated {
-            snapshot: snapshot.clone(),
+            snapshot: Box::new(snapshot.clone()),
         };
 
         let result = projector::apply_event(None, &payload);
@@ -217,7 +224,7 @@ mod projector_tests {
         let snapshot = text_snapshot("hello");
         let agg = live_aggregate(snapshot.clone(), default_versions());
         let payload = ItemEventPayload::ItemCreated {
-            snapshot: snapshot.clone(),
+            snapshot: Box::new(snapshot.clone()),
         };
 
         let result = projector::apply_event(Some(&agg), &payload);
@@ -408,6 +415,56 @@ mod projector_tests {
         }
     }
 
+    #[test]
+    fn reminder_set_and_cleared_track_their_own_version() {
+        let mut versions = default_versions();
+        versions.bookmark = 4;
+        let agg = live_aggregate(text_snapshot("coupon"), versions);
+        let set = ItemEventPayload::ReminderSet {
+            fire_at_unix: 1_700_000_000,
+            base_reminder_version: 0,
+        };
+
+        let agg = match projector::apply_event(Some(&agg), &set) {
+            ApplyResult::Applied(delta) => {
+                assert!(!delta.index_dirty);
+                assert_eq!(delta.bumped_domains, vec![VersionDomain::Reminder]);
+                delta.new_aggregate
+            }
+            other => panic!("expected Applied, got {other:?}"),
+        };
+        match &agg {
+            ItemAggregate::Live(live) => {
+                assert_eq!(live.snapshot.reminder_at_unix, Some(1_700_000_000));
+                assert_eq!(live.versions.reminder, 1);
+                assert_eq!(live.versions.bookmark, 4);
+            }
+            _ => panic!("expected Live"),
+        }
+
+        // A second device cleared from the original base; the set wins.
+        let stale_clear = ItemEventPayload::ReminderCleared {
+            base_reminder_version: 0,
+        };
+        assert!(matches!(
+            projector::apply_event(Some(&agg), &stale_clear),
+            ApplyResult::Ignored(IgnoreReason::StaleVersion {
+                domain: VersionDomain::Reminder,
+                ..
+            })
+        ));
+        let clear = ItemEventPayload::ReminderCleared {
+            base_reminder_version: 1,
+        };
+        match projector::apply_event(Some(&agg), &clear) {
+            ApplyResult::Applied(delta) => match &delta.new_aggregate {
+                ItemAggregate::Live(live) => assert_eq!(live.snapshot.reminder_at_unix, None),
+                _ => panic!("expected Live"),
+            },
+            other => panic!("expected Applied, got {other:?}"),
+        }
+    }
+
     #[test]
     fn bookmark_vs_content_edit_both_apply_independently() {
         // Start with a fresh item.
@@ -690,7 +747,7 @@ mod store_tests {
             "item-1".to_string(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("hello"),
+                snapshot: Box::new(text_snapshot("hello")),
             },
         );
 
@@ -711,7 +768,7 @@ mod store_tests {
             "item-1".to_string(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("hello"),
+                snapshot: Box::new(text_snapshot("hello")),
             },
         );
         sync.append_local_event(&event).unwrap();
@@ -746,6 +803,7 @@ mod store_tests {
             existence: 1,
             touch: 5,
             metadata: 2,
+            reminder: 0,
         };
         sync.upsert_projection("global-1", &ProjectionState::Materialized { versions })
             .unwrap();
@@ -847,7 +905,7 @@ mod store_tests {
             "item-1".to_string(),
             "dev-1",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("test"),
+                snapshot: Box::new(text_snapshot("test")),
             },
         );
         sync.append_local_event(&event).unwrap();
@@ -906,7 +964,7 @@ mod replay_tests {
             "item-1".to_string(),
             "device-B",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("remote item"),
+                snapshot: Box::new(text_snapshot("remote item")),
             },
         );
 
@@ -930,7 +988,7 @@ mod replay_tests {
             "item-1".to_string(),
             "device-B",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("remote item"),
+                snapshot: Box::new(text_snapshot("remote item")),
             },
         );
 
@@ -986,7 +1044,7 @@ mod replay_tests {
             "item-1".to_string(),
             "device-B",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("original"),
+                snapshot: Box::new(text_snapshot("original")),
             },
         );
 
@@ -1015,6 +1073,7 @@ mod replay_tests {
             existence: 1,
             touch: 10,
             metadata: 3,
+            reminder: 0,
         };
         let agg = ItemAggregate::Live(LiveItemState {
             snapshot: text_snapshot("snapshot data"),
@@ -1085,7 +1144,7 @@ mod replay_tests {
             "item-1".to_string(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("original"),
+                snapshot: Box::new(text_snapshot("original")),
             },
         );
         replay::apply_remote_event(db.pool(), &create_event).unwrap();
@@ -1137,7 +1196,7 @@ mod compaction_tests {
             global_id.to_string(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("compaction test"),
+                snapshot: Box::new(text_snapshot("compaction test")),
             },
         );
         create_event.recorded_at = recorded_at_base;
@@ -1278,6 +1337,8 @@ mod write_path_tests {
     #[test]
     fn duplicate_save_text_emits_touch_event() {
         let (store, _dir) = test_store();
+        // Copies this close together would otherwise merge into one.
+        store.set_repeat_copy_window(0).unwrap();
 
         // First save.
         let id = store
@@ -1433,6 +1494,27 @@ mod write_path_tests {
         assert_eq!(cleared_events.len(), 1);
     }
 
+    #[test]
+    fn reminders_emit_set_and_cleared_events() {
+        let (store, _dir) = test_store();
+
+        let id = store
+            .save_text("1 Infinite Loop".to_string(), None, None)
+            .unwrap();
+        store.set_reminder(id.clone(), 1_700_000_000).unwrap();
+        store.clear_reminder(id.clone()).unwrap();
+        // Clearing again has nothing to sync.
+        store.clear_reminder(id).unwrap();
+
+        let pending = store.pending_local_events().unwrap();
+        let reminder_events: Vec<_> = pending
+            .iter()
+            .map(|e| e.payload_type.as_str())
+            .filter(|payload_type| payload_type.starts_with("reminder_"))
+            .collect();
+        assert_eq!(reminder_events, ["reminder_set", "reminder_cleared"]);
+    }
+
     #[test]
     fn update_timestamp_emits_touched_event() {
         let (store, _dir) = test_store();
@@ -1463,7 +1545,7 @@ mod serialization_tests {
             "item-1".to_string(),
             "dev-A",
             ItemEventPayload::ItemCreated {
-                snapshot: snapshot.clone(),
+                snapshot: Box::new(snapshot.clone()),
             },
         );
 
@@ -1519,6 +1601,7 @@ mod serialization_tests {
                 existence: 1,
                 touch: 7,
                 metadata: 2,
+                reminder: 0,
             },
         });
 
@@ -1557,7 +1640,7 @@ mod serialization_tests {
     fn all_payload_types_serialize() {
         let payloads = vec![
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("test"),
+                snapshot: Box::new(text_snapshot("test")),
             },
             ItemEventPayload::TextEdited {
                 new_text: "edit".to_string(),
@@ -1588,6 +1671,13 @@ mod serialization_tests {
                 description: "A photo".to_string(),
                 base_content_version: 1,
             },
+            ItemEventPayload::ReminderSet {
+                fire_at_unix: 1_700_000_000,
+                base_reminder_version: 0,
+            },
+            ItemEventPayload::ReminderCleared {
+                base_reminder_version: 1,
+            },
         ];
 
         for payload in payloads {
@@ -1609,7 +1699,7 @@ mod tombstone_tests {
     fn create_then_delete_produces_tombstone() {
         let snapshot = text_snapshot("mortal item");
         let create_payload = ItemEventPayload::ItemCreated {
-            snapshot: snapshot.clone(),
+            snapshot: Box::new(snapshot.clone()),
         };
         let create_result = projector::apply_event(None, &create_payload);
         let agg = match create_result {
@@ -1641,6 +1731,7 @@ mod tombstone_tests {
             existence: 2,
             touch: 1,
             metadata: 1,
+            reminder: 0,
         };
         let agg = tombstone_aggregate("text", versions);
 
@@ -1747,6 +1838,7 @@ mod snapshot_data_tests {
     #[test]
     fn file_snapshot_data() {
         let snap = ItemSnapshotData {
+            external_id: None,
             content_type: "file".to_string(),
             content_text: "File: test.txt".to_string(),
             content_hash: "hash_file".to_string(),
@@ -1756,6 +1848,7 @@ mod snapshot_data_tests {
             is_bookmarked: false,
             thumbnail_base64: None,
             color_rgba: None,
+            reminder_at_unix: None,
             type_specific: TypeSpecificData::File {
                 display_name: "File: test.txt".to_string(),
                 files: vec![FileSnapshotEntry {
@@ -1786,7 +1879,9 @@ mod projector_audit_tests {
     fn item_created_bookmarked_bumps_bookmark_domain() {
         let mut snapshot = text_snapshot("bookmarked item");
         snapshot.is_bookmarked = true;
-        let payload = ItemEventPayload::ItemCreated { snapshot };
+        let payload = ItemEventPayload::ItemCreated {
+            snapshot: Box::new(snapshot),
+        };
 
         let result = projector::apply_event(None, &payload);
         match result {
@@ -1808,7 +1903,9 @@ mod projector_audit_tests {
     fn item_created_unbookmarked_does_not_bump_bookmark_domain() {
         let snapshot = text_snapshot("plain item");
         assert!(!snapshot.is_bookmarked);
-        let payload = ItemEventPayload::ItemCreated { snapshot };
+        let payload = ItemEventPayload::ItemCreated {
+            snapshot: Box::new(snapshot),
+        };
 
         let result = projector::apply_event(None, &payload);
         match result {
@@ -2020,6 +2117,7 @@ mod compaction_audit_tests {
                 existence: 2,
                 touch: 1,
                 metadata: 1,
+                reminder: 0,
             },
             content_type: "text".to_string(),
         });
@@ -2034,6 +2132,7 @@ mod compaction_audit_tests {
                     existence: 2,
                     touch: 1,
                     metadata: 1,
+                    reminder: 0,
                 },
             },
         )
@@ -2067,7 +2166,7 @@ mod compaction_audit_tests {
             gid.to_string(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("purge test"),
+                snapshot: Box::new(text_snapshot("purge test")),
             },
         );
         sync.append_local_event(&create_event).unwrap();
@@ -2367,7 +2466,7 @@ mod replay_audit_tests {
             gid.to_string(),
             "device-B",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("original"),
+                snapshot: Box::new(text_snapshot("original")),
             },
         );
 
@@ -2398,7 +2497,7 @@ mod replay_audit_tests {
             gid.to_string(),
             "device-C",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("batch test"),
+                snapshot: Box::new(text_snapshot("batch test")),
             },
         );
         replay::apply_remote_event(db.pool(), &create).unwrap();
@@ -2418,7 +2517,7 @@ mod replay_audit_tests {
             gid.to_string(),
             "device-C",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("batch test"),
+                snapshot: Box::new(text_snapshot("batch test")),
             },
         );
 
@@ -2553,7 +2652,7 @@ mod replay_audit_tests {
             "remote-bookmark-item".to_string(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("bookmark me"),
+                snapshot: Box::new(text_snapshot("bookmark me")),
             },
         );
         store
@@ -2605,7 +2704,7 @@ mod replay_audit_tests {
             "remote-stable-id".to_string(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("before edit"),
+                snapshot: Box::new(text_snapshot("before edit")),
             },
         );
         store
@@ -2659,7 +2758,7 @@ mod replay_audit_tests {
             "remote-index-queue".to_string(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("queued search text"),
+                snapshot: Box::new(text_snapshot("queued search text")),
             },
         );
 
@@ -2732,7 +2831,7 @@ mod replay_audit_tests {
             "remote-index-delete".to_string(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("delete from search"),
+                snapshot: Box::new(text_snapshot("delete from search")),
             },
         );
         store.apply_remote_event(event_record(&created)).unwrap();
@@ -2781,14 +2880,14 @@ mod replay_audit_tests {
             "remote-index-one".to_string(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("first queued item"),
+                snapshot: Box::new(text_snapshot("first queued item")),
             },
         );
         let second = ItemEvent::new_local(
             "remote-index-two".to_string(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("second queued item"),
+                snapshot: Box::new(text_snapshot("second queued item")),
             },
         );
         store
@@ -2918,7 +3017,7 @@ mod replay_audit_tests {
             "remote-heal-event".to_string(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("healed from sync state"),
+                snapshot: Box::new(text_snapshot("healed from sync state")),
             },
         );
         let make_record = || purr::interface::SyncEventRecord {
@@ -2983,6 +3082,55 @@ mod replay_audit_tests {
         assert_eq!(healed_items[0].text_content(), "snapshot healed");
     }
 
+    #[test]
+    fn remote_snapshot_preview_classifies_without_applying() {
+        let (store, dir) = test_store();
+
+        let snapshot = ItemSnapshot::initial(
+            "remote-preview".to_string(),
+            live_aggregate(text_snapshot("preview me"), default_versions()),
+        );
+        let preview = store
+            .preview_remote_snapshots(vec![snapshot_record(&snapshot)])
+            .unwrap();
+        assert_eq!(preview.new_items, 1);
+        assert_eq!(
+            preview.estimated_growth_bytes,
+            ("preview me".len() + "TestApp".len() + "com.test".len()) as u64
+        );
+        assert!(store_db(&dir).fetch_all_items().unwrap().is_empty());
+
+        assert!(store
+            .apply_remote_snapshot(snapshot_record(&snapshot))
+            .unwrap());
+        let edited = ItemSnapshot::initial(
+            "remote-preview".to_string(),
+            live_aggregate(text_snapshot("edited elsewhere"), default_versions()),
+        );
+        let deleted = ItemSnapshot::initial(
+            "remote-preview".to_string(),
+            tombstone_aggregate("text", default_versions()),
+        );
+        let preview = store
+            .preview_remote_snapshots(vec![snapshot_record(&snapshot)])
+            .unwrap();
+        assert_eq!((preview.new_items, preview.duplicates), (0, 1));
+        assert_eq!(
+            store
+                .preview_remote_snapshots(vec![snapshot_record(&edited)])
+                .unwrap()
+                .conflicts,
+            1
+        );
+        assert_eq!(
+            store
+                .preview_remote_snapshots(vec![snapshot_record(&deleted)])
+                .unwrap()
+                .conflicts,
+            1
+        );
+    }
+
     #[test]
     fn cloud_cleanup_waits_for_remote_delete_before_pruning_dedup() {
         let (store, dir) = test_store();
@@ -2991,7 +3139,7 @@ mod replay_audit_tests {
             "remote-cleanup-item".to_string(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("cleanup me"),
+                snapshot: Box::new(text_snapshot("cleanup me")),
             },
         );
         store
@@ -3046,7 +3194,7 @@ mod replay_audit_tests {
             "shared-item".to_string(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("before edit"),
+                snapshot: Box::new(text_snapshot("before edit")),
             },
         );
         store
@@ -3209,7 +3357,7 @@ mod replay_audit_tests {
             "remote-delete-batch".to_string(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("delete me remotely"),
+                snapshot: Box::new(text_snapshot("delete me remotely")),
             },
         );
         store
@@ -3356,7 +3504,7 @@ mod replay_audit_tests {
             item_id.clone(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("before edit"),
+                snapshot: Box::new(text_snapshot("before edit")),
             },
         );
         created.event_id = "evt-1-created".to_string();
@@ -3482,7 +3630,7 @@ mod forward_compat_tests {
             "item-fwd".to_string(),
             "device-A",
             ItemEventPayload::ItemCreated {
-                snapshot: text_snapshot("forward compat test"),
+                snapshot: Box::new(text_snapshot("forward compat test")),
             },
         );
         replay::apply_remote_event(db.pool(), &create).unwrap();